@@ -2808,12 +2808,26 @@
         },
       );
 
+      let occlusionQuerySet;
+      if (descriptor.occlusionQuerySet) {
+        occlusionQuerySet = assertResource(descriptor.occlusionQuerySet, {
+          prefix,
+          context: "occlusion query set",
+        });
+        assertDeviceMatch(device, descriptor.occlusionQuerySet, {
+          prefix,
+          resourceContext: "occlusion query set",
+          selfContext: "this",
+        });
+      }
+
       const { rid } = core.opSync(
         "op_webgpu_command_encoder_begin_render_pass",
         commandEncoderRid,
         descriptor.label,
         colorAttachments,
         depthStencilAttachment,
+        occlusionQuerySet,
       );
 
       const renderPassEncoder = createGPURenderPassEncoder(
@@ -3581,12 +3595,51 @@
       );
     }
 
-    beginOcclusionQuery(_queryIndex) {
-      throw new Error("Not yet implemented");
+    /**
+     * @param {number} queryIndex
+     */
+    beginOcclusionQuery(queryIndex) {
+      webidl.assertBranded(this, GPURenderPassEncoderPrototype);
+      const prefix =
+        "Failed to execute 'beginOcclusionQuery' on 'GPURenderPassEncoder'";
+      webidl.requiredArguments(arguments.length, 1, { prefix });
+      queryIndex = webidl.converters.GPUSize32(queryIndex, {
+        prefix,
+        context: "Argument 1",
+      });
+      assertDevice(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      assertResource(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      const renderPassRid = assertResource(this, { prefix, context: "this" });
+      core.opSync(
+        "op_webgpu_render_pass_begin_occlusion_query",
+        renderPassRid,
+        queryIndex,
+      );
     }
 
     endOcclusionQuery() {
-      throw new Error("Not yet implemented");
+      webidl.assertBranded(this, GPURenderPassEncoderPrototype);
+      const prefix =
+        "Failed to execute 'endOcclusionQuery' on 'GPURenderPassEncoder'";
+      assertDevice(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      assertResource(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      const renderPassRid = assertResource(this, { prefix, context: "this" });
+      core.opSync(
+        "op_webgpu_render_pass_end_occlusion_query",
+        renderPassRid,
+      );
     }
 
     /**
@@ -419,7 +419,10 @@ pub async fn op_webgpu_request_device(
     let (device, maybe_err) = gfx_select!(adapter => instance.adapter_request_device(
       adapter,
       &descriptor,
-      std::env::var("DENO_WEBGPU_TRACE").ok().as_ref().map(std::path::Path::new),
+      std::env::var("DENO_WEBGPU_TRACE").ok().as_ref().map(|dir| wgpu_types::TraceDescriptor {
+        path: std::path::Path::new(dir),
+        format: wgpu_types::TraceFormat::Ron,
+      }),
       std::marker::PhantomData
     ));
     if let Some(err) = maybe_err {
@@ -597,6 +600,8 @@ fn declare_webgpu_ops() -> Vec<deno_core::OpDecl> {
         render_pass::op_webgpu_render_pass_set_scissor_rect::decl(),
         render_pass::op_webgpu_render_pass_set_blend_constant::decl(),
         render_pass::op_webgpu_render_pass_set_stencil_reference::decl(),
+        render_pass::op_webgpu_render_pass_begin_occlusion_query::decl(),
+        render_pass::op_webgpu_render_pass_end_occlusion_query::decl(),
         render_pass::op_webgpu_render_pass_begin_pipeline_statistics_query::decl(),
         render_pass::op_webgpu_render_pass_end_pipeline_statistics_query::decl(),
         render_pass::op_webgpu_render_pass_write_timestamp::decl(),
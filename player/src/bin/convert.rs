@@ -0,0 +1,35 @@
+/*! This is a converter between the RON and binary trace formats.
+!*/
+
+use wgc::device::trace::{self, TraceFormat};
+
+use std::path::Path;
+
+fn main() {
+    env_logger::init();
+
+    let mut args = std::env::args().skip(1);
+    let (input, output) = match (args.next(), args.next()) {
+        (Some(input), Some(output)) => (input, output),
+        _ => panic!("Provide the input trace and the output path as the parameters"),
+    };
+
+    log::info!("Loading trace '{}'", input);
+    let (format, actions, store) = trace::load(Path::new(&input)).unwrap();
+    let target_format = match format {
+        TraceFormat::Ron => TraceFormat::Binary,
+        TraceFormat::Binary => {
+            std::fs::create_dir_all(&output).unwrap();
+            TraceFormat::Ron
+        }
+    };
+    log::info!(
+        "Converting {} actions from {:?} to {:?}",
+        actions.len(),
+        format,
+        target_format
+    );
+
+    player::write_trace(Path::new(&output), target_format, actions, &store).unwrap();
+    log::info!("Written '{}'", output);
+}
@@ -0,0 +1,239 @@
+/*! This is a minimizer for WebGPU traces.
+ *
+ * It replays the trace to find the error it produces, and then
+ * delta-debugs the actions away while the same error still reproduces.
+ * Every trial keeps the actions creating the resources that the
+ * retained actions refer to.
+ *
+ * The empty backend of `wgpu-hal` doesn't expose any adapters, so the trials
+ * run on the CPU rasterizer when the "software" feature is enabled,
+ * and on the backend of the trace otherwise.
+!*/
+
+use player::{resources, GlobalPlay as _, IdentityPassThroughFactory, OffscreenSurfaces};
+use wgc::{device::trace, gfx_select};
+
+use std::{
+    collections::BTreeSet,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+const USAGE: &str = "\
+Usage: minimize [--backend <name>] <trace-dir | trace-file> <output-dir>";
+
+/// Replays the trace with only the actions at `indices`,
+/// returning the error it stops at.
+struct Trial<'a> {
+    init: &'a trace::Action<'static>,
+    actions: &'a [trace::Action<'static>],
+    store: &'a trace::DataStore,
+    trace_backend: wgt::Backend,
+    backend: wgt::Backend,
+    runs: usize,
+}
+
+impl Trial<'_> {
+    fn run(&mut self, indices: &BTreeSet<usize>) -> Option<String> {
+        self.runs += 1;
+        let global = wgc::hub::Global::new(
+            "minimizer",
+            IdentityPassThroughFactory,
+            wgt::Backends::all(),
+        );
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let device = self.init_device(&global);
+            let mut offscreen = OffscreenSurfaces::default();
+            let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
+            for &index in indices {
+                let action = self.prepare(&self.actions[index]);
+                if let Some(action) =
+                    gfx_select!(device => offscreen.process(&global, device, action))
+                {
+                    gfx_select!(device => global.process(device, action, self.store, &mut command_buffer_id_manager));
+                }
+            }
+            gfx_select!(device => global.device_poll(device, wgt::Maintain::Wait)).unwrap();
+        }));
+        // Tearing down a device that hit an error may fail as well.
+        let _ = panic::catch_unwind(AssertUnwindSafe(move || drop(global)));
+        result.err().map(|payload| {
+            if let Some(message) = payload.downcast_ref::<String>() {
+                message.clone()
+            } else if let Some(message) = payload.downcast_ref::<&str>() {
+                message.to_string()
+            } else {
+                "unknown error".to_string()
+            }
+        })
+    }
+
+    /// Clones an action out of the trace, moving it to the trial backend.
+    fn prepare(&self, action: &trace::Action) -> trace::Action<'static> {
+        // Actions aren't `Clone`, so every trial takes a fresh copy.
+        player::rezip_action(action, self.trace_backend, self.backend)
+    }
+
+    fn init_device(
+        &self,
+        global: &wgc::hub::Global<IdentityPassThroughFactory>,
+    ) -> wgc::id::DeviceId {
+        let desc = match *self.init {
+            trace::Action::Init { ref desc, .. } => desc,
+            _ => unreachable!(),
+        };
+        let adapter = global
+            .request_adapter(
+                &wgc::instance::RequestAdapterOptions {
+                    power_preference: wgt::PowerPreference::LowPower,
+                    force_fallback_adapter: false,
+                    compatible_surface: None,
+                },
+                wgc::instance::AdapterInputs::IdSet(
+                    &[wgc::id::TypedId::zip(0, 0, self.backend)],
+                    |id| id.backend(),
+                ),
+            )
+            .expect("Unable to find an adapter for selected backend");
+        let id = wgc::id::TypedId::zip(1, 0, self.backend);
+        let (_, error) = gfx_select!(adapter => global.adapter_request_device(
+            adapter,
+            desc,
+            None,
+            id
+        ));
+        if let Some(e) = error {
+            panic!("{:?}", e);
+        }
+        id
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let mut backend = None;
+    let mut paths = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--backend" => {
+                let name = args.next().unwrap_or_default();
+                backend = Some(
+                    player::parse_backend(&name)
+                        .unwrap_or_else(|| panic!("Unknown backend '{}'\n{}", name, USAGE)),
+                );
+            }
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    let (input, output) = match paths.as_slice() {
+        [input, output] if input.exists() => (input.clone(), output.clone()),
+        _ => panic!("Provide the trace and the output dir paths\n{}", USAGE),
+    };
+
+    log::info!("Loading trace '{:?}'", input);
+    let (_, mut actions, store) = trace::load(&input).unwrap();
+    if actions.is_empty() {
+        panic!("The trace is empty");
+    }
+    let init = actions.remove(0);
+    let trace_backend = match init {
+        trace::Action::Init { backend, .. } => backend,
+        _ => panic!("Expected Action::Init"),
+    };
+    let backend = backend.unwrap_or(if cfg!(feature = "software") {
+        wgt::Backend::Software
+    } else {
+        trace_backend
+    });
+    log::info!("Minimizing {} actions on {:?}", actions.len(), backend);
+
+    let creators = resources::creators(&actions);
+
+    let mut trial = Trial {
+        init: &init,
+        actions: &actions,
+        store: &store,
+        trace_backend,
+        backend,
+        runs: 0,
+    };
+
+    // Errors are expected in most of the trials, don't spam about them.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut current = (0..actions.len()).collect::<BTreeSet<_>>();
+    let target = match trial.run(&current) {
+        Some(error) => error,
+        None => {
+            panic::set_hook(default_hook);
+            println!("The trace replays without errors, there is nothing to minimize");
+            return;
+        }
+    };
+    println!("Reproducing: {}", target);
+
+    // Delta debugging: try keeping only one of `granularity` chunks of the
+    // current set, then try removing each of the chunks, and refine the
+    // chunks when neither reproduces the error.
+    let mut granularity = 2;
+    while current.len() >= 2 {
+        let list = current.iter().cloned().collect::<Vec<_>>();
+        let chunk_size = (list.len() + granularity - 1) / granularity;
+        let chunks = list.chunks(chunk_size).collect::<Vec<_>>();
+
+        let subsets = chunks.iter().map(|chunk| chunk.to_vec());
+        let complements = (0..chunks.len()).map(|skip| {
+            chunks
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != skip)
+                .flat_map(|(_, chunk)| chunk.iter().cloned())
+                .collect::<Vec<_>>()
+        });
+
+        let mut reduced = None;
+        for candidate in subsets.chain(complements) {
+            let candidate = resources::dependency_closure(candidate, &actions, &creators);
+            if candidate.len() < current.len() && trial.run(&candidate).as_ref() == Some(&target) {
+                reduced = Some(candidate);
+                break;
+            }
+        }
+
+        match reduced {
+            Some(set) => {
+                current = set;
+                granularity = (granularity - 1).max(2);
+                log::info!("Reduced to {} actions", current.len());
+            }
+            None if granularity >= current.len() => break,
+            None => granularity = (granularity * 2).min(current.len()),
+        }
+    }
+
+    panic::set_hook(default_hook);
+    println!(
+        "Reduced to {} out of {} actions in {} runs",
+        current.len(),
+        actions.len(),
+        trial.runs
+    );
+
+    std::fs::create_dir_all(&output).unwrap();
+    let minimal = std::iter::once(init).chain(
+        actions
+            .into_iter()
+            .enumerate()
+            .filter(|&(index, _)| current.contains(&index))
+            .map(|(_, action)| action),
+    );
+    player::write_trace(&output, trace::TraceFormat::Ron, minimal, &store).unwrap();
+    println!("Written '{}'", Path::new(&output).display());
+}
@@ -1,31 +1,152 @@
 /*! This is a player for WebGPU traces.
 !*/
 
-use player::{GlobalPlay as _, IdentityPassThroughFactory};
+use player::{GlobalPlay as _, IdentityPassThroughFactory, OffscreenSurfaces};
 use wgc::{device::trace, gfx_select};
 
 use std::{
-    fs,
+    ops::Range,
     path::{Path, PathBuf},
+    time::Instant,
 };
 
+const USAGE: &str = "\
+Usage: play [OPTIONS] <trace-dir | trace-file>
+
+Options:
+    --backend <name>      replay on a different backend: vulkan, metal, dx12, dx11, gl,
+                          or software (if built with the `software` feature)
+    --frames <N..M>       stop before frame M, only reporting the timing of frames N..M
+    --until-action <K>    stop after executing K actions
+    --headless            replace surfaces with offscreen textures";
+
+#[derive(Debug, Default)]
+struct Options {
+    path: PathBuf,
+    backend: Option<wgt::Backend>,
+    frames: Option<Range<u32>>,
+    until_action: Option<usize>,
+    headless: bool,
+}
+
+fn parse_frames(value: &str) -> Option<Range<u32>> {
+    let mut parts = value.splitn(2, "..");
+    let start = parts.next()?.parse().ok()?;
+    let end = parts.next()?.parse().ok()?;
+    Some(start..end)
+}
+
+fn parse_options() -> Options {
+    let mut options = Options::default();
+    let mut path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .unwrap_or_else(|| panic!("Missing value for {}\n{}", name, USAGE))
+        };
+        match arg.as_str() {
+            "--backend" => {
+                let name = value("--backend");
+                options.backend = Some(
+                    player::parse_backend(&name)
+                        .unwrap_or_else(|| panic!("Unknown backend '{}'\n{}", name, USAGE)),
+                );
+            }
+            "--frames" => {
+                let range = value("--frames");
+                options.frames = Some(
+                    parse_frames(&range)
+                        .unwrap_or_else(|| panic!("Invalid frame range '{}'\n{}", range, USAGE)),
+                );
+            }
+            "--until-action" => {
+                let count = value("--until-action");
+                options.until_action = Some(
+                    count
+                        .parse()
+                        .unwrap_or_else(|_| panic!("Invalid action count '{}'\n{}", count, USAGE)),
+                );
+            }
+            "--headless" => options.headless = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(PathBuf::from(arg)),
+            _ => panic!("Unexpected argument '{}'\n{}", arg, USAGE),
+        }
+    }
+    options.path = match path {
+        Some(path) if path.exists() => path,
+        _ => panic!("Provide the trace dir or binary trace file path\n{}", USAGE),
+    };
+    // Without a window, surfaces can only be replayed offscreen.
+    options.headless |= cfg!(not(feature = "winit"));
+    options
+}
+
+/// Feeds the actions of a trace to the device, keeping track of the
+/// frame and action limits, and of the frame timings.
+struct Replay {
+    actions: Vec<trace::Action<'static>>,
+    trace_backend: wgt::Backend,
+    backend: wgt::Backend,
+    frames: Option<Range<u32>>,
+    actions_left: Option<usize>,
+    frame: u32,
+    frame_start: Instant,
+}
+
+impl Replay {
+    fn next_action(&mut self) -> Option<trace::Action<'static>> {
+        if let Some(ref frames) = self.frames {
+            if self.frame >= frames.end {
+                return None;
+            }
+        }
+        match self.actions_left {
+            Some(0) => return None,
+            Some(ref mut count) => *count -= 1,
+            None => {}
+        }
+        let action = self.actions.pop()?;
+        Some(if self.trace_backend != self.backend {
+            player::rezip_action(&action, self.trace_backend, self.backend)
+        } else {
+            action
+        })
+    }
+
+    fn end_frame(&mut self) {
+        let now = Instant::now();
+        let in_range = match self.frames {
+            Some(ref frames) => frames.contains(&self.frame),
+            None => true,
+        };
+        if in_range {
+            println!(
+                "Frame {} took {:.3} ms",
+                self.frame,
+                (now - self.frame_start).as_secs_f64() * 1000.0
+            );
+        }
+        self.frame += 1;
+        self.frame_start = now;
+    }
+}
+
 fn main() {
     #[cfg(feature = "winit")]
     use winit::{event_loop::EventLoop, window::WindowBuilder};
 
     env_logger::init();
 
-    //TODO: setting for the backend bits
-    //TODO: setting for the target frame, or controls
-
-    let dir = match std::env::args().nth(1) {
-        Some(arg) if Path::new(&arg).is_dir() => PathBuf::from(arg),
-        _ => panic!("Provide the dir path as the parameter"),
-    };
+    let options = parse_options();
 
-    log::info!("Loading trace '{:?}'", dir);
-    let file = fs::File::open(dir.join(trace::FILE_NAME)).unwrap();
-    let mut actions: Vec<trace::Action> = ron::de::from_reader(file).unwrap();
+    log::info!("Loading trace '{:?}'", options.path);
+    let (format, mut actions, store) = trace::load(&options.path).unwrap();
+    log::info!("Trace format is {:?}", format);
     actions.reverse(); // allows us to pop from the top
     log::info!("Found {} actions", actions.len());
 
@@ -38,6 +159,7 @@ fn main() {
     let window = WindowBuilder::new()
         .with_title("wgpu player")
         .with_resizable(true)
+        .with_visible(!options.headless)
         .build(&event_loop)
         .unwrap();
 
@@ -48,21 +170,33 @@ fn main() {
     let surface =
         global.instance_create_surface(&window, wgc::id::TypedId::zip(0, 1, wgt::Backend::Empty));
 
-    let device = match actions.pop() {
+    let (device, mut replay) = match actions.pop() {
         Some(trace::Action::Init { desc, backend }) => {
-            log::info!("Initializing the device for backend: {:?}", backend);
+            let target_backend = options.backend.unwrap_or(backend);
+            if target_backend != backend {
+                log::info!(
+                    "Overriding the trace backend {:?} with {:?}",
+                    backend,
+                    target_backend
+                );
+            }
+            log::info!("Initializing the device for backend: {:?}", target_backend);
             let adapter = global
                 .request_adapter(
                     &wgc::instance::RequestAdapterOptions {
                         power_preference: wgt::PowerPreference::LowPower,
                         force_fallback_adapter: false,
                         #[cfg(feature = "winit")]
-                        compatible_surface: Some(surface),
+                        compatible_surface: if options.headless {
+                            None
+                        } else {
+                            Some(surface)
+                        },
                         #[cfg(not(feature = "winit"))]
                         compatible_surface: None,
                     },
                     wgc::instance::AdapterInputs::IdSet(
-                        &[wgc::id::TypedId::zip(0, 0, backend)],
+                        &[wgc::id::TypedId::zip(0, 0, target_backend)],
                         |id| id.backend(),
                     ),
                 )
@@ -70,7 +204,7 @@ fn main() {
 
             let info = gfx_select!(adapter => global.adapter_get_info(adapter)).unwrap();
             log::info!("Picked '{}'", info.name);
-            let id = wgc::id::TypedId::zip(1, 0, backend);
+            let id = wgc::id::TypedId::zip(1, 0, target_backend);
             let (_, error) = gfx_select!(adapter => global.adapter_request_device(
                 adapter,
                 &desc,
@@ -80,23 +214,46 @@ fn main() {
             if let Some(e) = error {
                 panic!("{:?}", e);
             }
-            id
+            let replay = Replay {
+                actions,
+                trace_backend: backend,
+                backend: target_backend,
+                frames: options.frames.clone(),
+                actions_left: options.until_action,
+                frame: 0,
+                frame_start: Instant::now(),
+            };
+            (id, replay)
         }
         _ => panic!("Expected Action::Init"),
     };
 
     log::info!("Executing actions");
-    #[cfg(not(feature = "winit"))]
-    {
+    if options.headless {
+        let mut offscreen = OffscreenSurfaces::default();
         gfx_select!(device => global.device_start_capture(device));
 
-        while let Some(action) = actions.pop() {
-            gfx_select!(device => global.process(device, action, &dir, &mut command_buffer_id_manager));
+        while let Some(action) = replay.next_action() {
+            let ends_frame = matches!(
+                action,
+                trace::Action::Present(_) | trace::Action::DiscardSurfaceTexture(_)
+            );
+            if let Some(action) = gfx_select!(device => offscreen.process(&global, device, action))
+            {
+                gfx_select!(device => global.process(device, action, &store, &mut command_buffer_id_manager));
+            }
+            if ends_frame {
+                gfx_select!(device => global.device_poll(device, wgt::Maintain::Wait)).unwrap();
+                replay.end_frame();
+            }
         }
 
         gfx_select!(device => global.device_stop_capture(device));
         gfx_select!(device => global.device_poll(device, wgt::Maintain::Wait)).unwrap();
+        println!("Finished at frame {}", replay.frame);
+        return;
     }
+
     #[cfg(feature = "winit")]
     {
         use winit::{
@@ -105,7 +262,6 @@ fn main() {
         };
 
         let mut resize_config = None;
-        let mut frame_count = 0;
         let mut done = false;
         event_loop.run(move |event, _, control_flow| {
             *control_flow = ControlFlow::Poll;
@@ -114,7 +270,7 @@ fn main() {
                     window.request_redraw();
                 }
                 Event::RedrawRequested(_) if resize_config.is_none() => loop {
-                    match actions.pop() {
+                    match replay.next_action() {
                         Some(trace::Action::ConfigureSurface(_device_id, config)) => {
                             log::info!("Configuring the surface");
                             let current_size: (u32, u32) = window.inner_size().into();
@@ -134,22 +290,23 @@ fn main() {
                             }
                         }
                         Some(trace::Action::Present(id)) => {
-                            frame_count += 1;
-                            log::debug!("Presenting frame {}", frame_count);
+                            log::debug!("Presenting frame {}", replay.frame);
                             gfx_select!(device => global.surface_present(id)).unwrap();
+                            replay.end_frame();
                             break;
                         }
                         Some(trace::Action::DiscardSurfaceTexture(id)) => {
-                            log::debug!("Discarding frame {}", frame_count);
+                            log::debug!("Discarding frame {}", replay.frame);
                             gfx_select!(device => global.surface_texture_discard(id)).unwrap();
+                            replay.end_frame();
                             break;
                         }
                         Some(action) => {
-                            gfx_select!(device => global.process(device, action, &dir, &mut command_buffer_id_manager));
+                            gfx_select!(device => global.process(device, action, &store, &mut command_buffer_id_manager));
                         }
                         None => {
                             if !done {
-                                println!("Finished the end at frame {}", frame_count);
+                                println!("Finished the end at frame {}", replay.frame);
                                 done = true;
                             }
                             break;
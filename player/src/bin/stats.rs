@@ -0,0 +1,379 @@
+/*! This is an inspector of WebGPU traces.
+ *
+ * It reports statistics about a trace without replaying it,
+ * so it doesn't need a device.
+!*/
+
+use player::resources::{self, Resource};
+use wgc::{
+    command::{ComputeCommand, RenderCommand},
+    device::trace::{self, Action, Command, DataStore},
+    id,
+};
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+};
+
+/// How many of the largest uploads to report.
+const LARGEST_UPLOADS: usize = 10;
+
+#[derive(Debug, Default)]
+struct KindStats {
+    created: usize,
+    live: usize,
+    peak_live: usize,
+    memory: u64,
+    peak_memory: u64,
+}
+
+#[derive(Debug, Default)]
+struct CommandBufferStats {
+    copies: usize,
+    clears: usize,
+    compute_passes: usize,
+    dispatches: usize,
+    render_passes: usize,
+    draws: usize,
+    bundles: usize,
+}
+
+impl CommandBufferStats {
+    fn add_render_commands(&mut self, commands: &[RenderCommand]) {
+        for command in commands {
+            match *command {
+                RenderCommand::Draw { .. }
+                | RenderCommand::DrawIndexed { .. }
+                | RenderCommand::MultiDrawIndirect { .. }
+                | RenderCommand::MultiDrawIndirectCount { .. } => self.draws += 1,
+                RenderCommand::ExecuteBundle(_) => self.bundles += 1,
+                _ => {}
+            }
+        }
+    }
+
+    fn new(commands: &[Command]) -> Self {
+        let mut stats = Self::default();
+        for command in commands {
+            match *command {
+                Command::CopyBufferToBuffer { .. }
+                | Command::CopyBufferToTexture { .. }
+                | Command::CopyTextureToBuffer { .. }
+                | Command::CopyTextureToTexture { .. } => stats.copies += 1,
+                Command::ClearBuffer { .. } | Command::ClearTexture { .. } => stats.clears += 1,
+                Command::RunComputePass { ref base } => {
+                    stats.compute_passes += 1;
+                    stats.dispatches += base
+                        .commands
+                        .iter()
+                        .filter(|command| {
+                            matches!(
+                                command,
+                                ComputeCommand::Dispatch(_)
+                                    | ComputeCommand::DispatchIndirect { .. }
+                            )
+                        })
+                        .count();
+                }
+                Command::RunRenderPass { ref base, .. } => {
+                    stats.render_passes += 1;
+                    stats.add_render_commands(&base.commands);
+                }
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
+/// Estimates the memory taken by a texture, including all of its mips.
+fn texture_size(desc: &wgc::resource::TextureDescriptor) -> u64 {
+    let info = desc.format.describe();
+    let (block_width, block_height) = (
+        info.block_dimensions.0 as u32,
+        info.block_dimensions.1 as u32,
+    );
+    let layers = match desc.dimension {
+        wgt::TextureDimension::D3 => 1,
+        _ => desc.size.depth_or_array_layers,
+    };
+    (0..desc.mip_level_count)
+        .map(|level| {
+            let width = (desc.size.width >> level).max(1);
+            let height = (desc.size.height >> level).max(1);
+            let depth = match desc.dimension {
+                wgt::TextureDimension::D3 => (desc.size.depth_or_array_layers >> level).max(1),
+                _ => 1,
+            };
+            let blocks = ((width + block_width - 1) / block_width) as u64
+                * ((height + block_height - 1) / block_height) as u64
+                * depth as u64;
+            blocks * info.block_size as u64
+        })
+        .sum::<u64>()
+        * layers as u64
+        * desc.sample_count as u64
+}
+
+/// Statistics gathered from the actions of a trace.
+#[derive(Debug, Default)]
+struct Stats {
+    kinds: BTreeMap<&'static str, KindStats>,
+    /// Created resources, with the index of the action creating them.
+    created: Vec<(usize, Resource)>,
+    used: HashSet<Resource>,
+    /// Size, action index and destination of every upload.
+    uploads: Vec<(u64, usize, Resource)>,
+    /// Number of submissions of every frame.
+    submissions_per_frame: Vec<usize>,
+    /// Every command buffer, with the index of the submission it's part of.
+    command_buffers: Vec<(u64, CommandBufferStats)>,
+    /// Draws of every render bundle.
+    render_bundles: Vec<(id::RenderBundleId, usize)>,
+}
+
+impl Stats {
+    fn collect(actions: &[Action], store: &DataStore) -> Self {
+        let mut stats = Self {
+            submissions_per_frame: vec![0],
+            ..Default::default()
+        };
+        let mut sizes = HashMap::<Resource, u64>::new();
+        // Every command buffer of a submission is traced as its own `Submit`.
+        let mut last_submission = None;
+
+        for (index, action) in actions.iter().enumerate() {
+            for resource in resources::created(action) {
+                let size = match *action {
+                    Action::CreateBuffer(_, ref desc) => desc.size,
+                    Action::CreateTexture(_, ref desc) => texture_size(desc),
+                    _ => 0,
+                };
+                let kind = stats.kinds.entry(resource.kind()).or_default();
+                kind.created += 1;
+                kind.live += 1;
+                kind.peak_live = kind.peak_live.max(kind.live);
+                kind.memory += size;
+                kind.peak_memory = kind.peak_memory.max(kind.memory);
+                sizes.insert(resource, size);
+                stats.created.push((index, resource));
+            }
+
+            match resources::released(action) {
+                Some(resource) => {
+                    // Buffers and textures can be both destroyed and freed.
+                    if let Some(size) = sizes.remove(&resource) {
+                        let kind = stats.kinds.get_mut(resource.kind()).unwrap();
+                        kind.live -= 1;
+                        kind.memory -= size;
+                    }
+                }
+                None => stats.used.extend(resources::referenced(action)),
+            }
+
+            match *action {
+                Action::WriteBuffer { id, ref range, .. } => {
+                    stats
+                        .uploads
+                        .push((range.end - range.start, index, Resource::Buffer(id)))
+                }
+                Action::WriteTexture {
+                    ref to, ref data, ..
+                } => {
+                    let size = store.read(data).map_or(0, |data| data.len() as u64);
+                    stats
+                        .uploads
+                        .push((size, index, Resource::Texture(to.texture)));
+                }
+                Action::Submit(submission, ref commands) => {
+                    if last_submission != Some(submission) {
+                        last_submission = Some(submission);
+                        *stats.submissions_per_frame.last_mut().unwrap() += 1;
+                    }
+                    stats
+                        .command_buffers
+                        .push((submission, CommandBufferStats::new(commands)));
+                }
+                Action::CreateRenderBundle { id, ref base, .. } => {
+                    let mut bundle = CommandBufferStats::default();
+                    bundle.add_render_commands(&base.commands);
+                    stats.render_bundles.push((id, bundle.draws));
+                }
+                Action::Present(_) | Action::DiscardSurfaceTexture(_) => {
+                    stats.submissions_per_frame.push(0)
+                }
+                _ => {}
+            }
+        }
+
+        // The last frame is only counted if something was submitted after the last present.
+        if stats.submissions_per_frame.len() > 1
+            && *stats.submissions_per_frame.last().unwrap() == 0
+        {
+            stats.submissions_per_frame.pop();
+        }
+        stats.uploads.sort_by(|a, b| b.0.cmp(&a.0));
+        stats
+    }
+
+    /// Created resources that no action refers to, with the index of the action creating them.
+    fn unused(&self) -> impl Iterator<Item = &(usize, Resource)> {
+        self.created
+            .iter()
+            .filter(move |&&(_, resource)| !self.used.contains(&resource))
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let path = match std::env::args().nth(1) {
+        Some(arg) if Path::new(&arg).exists() => arg,
+        _ => panic!("Provide the trace dir or binary trace file path as the parameter"),
+    };
+    let (format, actions, store) = trace::load(Path::new(&path)).unwrap();
+    println!(
+        "Trace '{}' ({:?}) has {} actions",
+        path,
+        format,
+        actions.len()
+    );
+    let stats = Stats::collect(&actions, &store);
+
+    println!();
+    println!("Resources:");
+    println!(
+        "\t{:<20} {:>8} {:>8} {:>14}",
+        "type", "created", "peak", "peak memory"
+    );
+    for (kind, kind_stats) in stats.kinds.iter() {
+        println!(
+            "\t{:<20} {:>8} {:>8} {:>14}",
+            kind, kind_stats.created, kind_stats.peak_live, kind_stats.peak_memory
+        );
+    }
+
+    println!();
+    println!(
+        "Submissions per frame ({} frames):",
+        stats.submissions_per_frame.len()
+    );
+    for (frame, count) in stats.submissions_per_frame.iter().enumerate() {
+        println!("\tframe {}: {}", frame, count);
+    }
+
+    println!();
+    println!("Largest uploads:");
+    for &(size, index, resource) in stats.uploads.iter().take(LARGEST_UPLOADS) {
+        println!(
+            "\t{} bytes to {} {:?} at action {}",
+            size,
+            resource.kind(),
+            resource,
+            index
+        );
+    }
+
+    println!();
+    println!("Unused resources:");
+    for &(index, resource) in stats.unused() {
+        println!(
+            "\t{} {:?} created at action {}",
+            resource.kind(),
+            resource,
+            index
+        );
+    }
+
+    println!();
+    println!("Command buffers:");
+    for (command_buffer, (submission, cmd_stats)) in stats.command_buffers.iter().enumerate() {
+        println!(
+            "\tcommand buffer {} of submission {}: {} copies, {} clears, \
+            {} compute passes with {} dispatches, {} render passes with {} draws and {} bundles",
+            command_buffer,
+            submission,
+            cmd_stats.copies,
+            cmd_stats.clears,
+            cmd_stats.compute_passes,
+            cmd_stats.dispatches,
+            cmd_stats.render_passes,
+            cmd_stats.draws,
+            cmd_stats.bundles
+        );
+    }
+
+    println!();
+    println!("Render bundles:");
+    for &(id, draws) in stats.render_bundles.iter() {
+        println!("\trender bundle {:?}: {} draws", id, draws);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stats;
+    use player::resources::Resource;
+    use wgc::{
+        device::trace::{Action, Command, DataStore},
+        id::TypedId as _,
+    };
+
+    #[test]
+    fn collect_stats() {
+        let backend = wgt::Backend::Empty;
+        let buffer = wgc::id::BufferId::zip(0, 1, backend);
+        let unused_buffer = wgc::id::BufferId::zip(1, 1, backend);
+        let buffer_desc = wgt::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgt::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        let actions = [
+            Action::CreateBuffer(buffer, buffer_desc.clone()),
+            Action::CreateBuffer(unused_buffer, buffer_desc),
+            Action::WriteBuffer {
+                id: buffer,
+                data: "data1.bin".to_string(),
+                range: 0..4,
+                queued: true,
+            },
+            // A submission of two command buffers.
+            Action::Submit(
+                1,
+                vec![Command::ClearBuffer {
+                    dst: buffer,
+                    offset: 0,
+                    size: None,
+                }],
+            ),
+            Action::Submit(1, Vec::new()),
+            Action::Submit(2, Vec::new()),
+            Action::Present(wgc::id::SurfaceId::zip(0, 1, backend)),
+            Action::Submit(3, Vec::new()),
+            Action::FreeBuffer(buffer),
+        ];
+        let stats = Stats::collect(&actions, &DataStore::Embedded(Default::default()));
+
+        let buffers = &stats.kinds["buffer"];
+        assert_eq!(buffers.created, 2);
+        assert_eq!(buffers.live, 1);
+        assert_eq!(buffers.peak_memory, 32);
+        assert_eq!(stats.submissions_per_frame, [2, 1]);
+        assert_eq!(
+            stats
+                .command_buffers
+                .iter()
+                .map(|&(submission, ref cmd_stats)| (submission, cmd_stats.clears))
+                .collect::<Vec<_>>(),
+            [(1, 1), (1, 0), (2, 0), (3, 0)]
+        );
+        assert_eq!(stats.uploads, [(4, 2, Resource::Buffer(buffer))]);
+        assert_eq!(
+            stats.unused().collect::<Vec<_>>(),
+            [&(1, Resource::Buffer(unused_buffer))]
+        );
+    }
+}
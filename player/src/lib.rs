@@ -8,7 +8,10 @@
 
 use wgc::device::trace;
 
-use std::{borrow::Cow, fmt::Debug, fs, marker::PhantomData, path::Path};
+use std::{borrow::Cow, collections::HashMap, fmt::Debug, marker::PhantomData, path::Path};
+
+pub mod resources;
+mod rezip;
 
 #[derive(Debug)]
 pub struct IdentityPassThrough<I>(PhantomData<I>);
@@ -44,7 +47,7 @@ pub trait GlobalPlay {
         &self,
         device: wgc::id::DeviceId,
         action: trace::Action,
-        dir: &Path,
+        store: &trace::DataStore,
         comb_manager: &mut wgc::hub::IdentityManager,
     );
 }
@@ -125,12 +128,14 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                     base,
                     target_colors,
                     target_depth_stencil,
+                    occlusion_query_set_id,
                 } => {
                     self.command_encoder_run_render_pass_impl::<A>(
                         encoder,
                         base.as_ref(),
                         &target_colors,
                         target_depth_stencil.as_ref(),
+                        occlusion_query_set_id,
                     )
                     .unwrap();
                 }
@@ -148,7 +153,7 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
         &self,
         device: wgc::id::DeviceId,
         action: trace::Action,
-        dir: &Path,
+        store: &trace::DataStore,
         comb_manager: &mut wgc::hub::IdentityManager,
     ) {
         use wgc::device::trace::Action;
@@ -251,7 +256,7 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
             }
             Action::CreateShaderModule { id, desc, data } => {
                 log::info!("Creating shader from {}", data);
-                let code = fs::read_to_string(dir.join(&data)).unwrap();
+                let code = String::from_utf8(store.read(&data).unwrap().into_owned()).unwrap();
                 let source = if data.ends_with(".wgsl") {
                     wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Owned(code))
                 } else if data.ends_with(".ron") {
@@ -312,6 +317,24 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
             Action::DestroyRenderPipeline(id) => {
                 self.render_pipeline_drop::<A>(id);
             }
+            Action::CreatePipelineCache { id, desc, data } => {
+                let data = data.map(|data| store.read(&data).unwrap());
+                // The cache contents are specific to the driver that recorded the trace,
+                // so fall back to an empty cache instead of failing the replay.
+                let desc = wgc::pipeline::PipelineCacheDescriptor {
+                    fallback: true,
+                    ..desc
+                };
+                let (_, error) = unsafe {
+                    self.device_create_pipeline_cache::<A>(device, &desc, data.as_deref(), id)
+                };
+                if let Some(e) = error {
+                    panic!("{:?}", e);
+                }
+            }
+            Action::DestroyPipelineCache(id) => {
+                self.pipeline_cache_drop::<A>(id);
+            }
             Action::CreateRenderBundle { id, desc, base } => {
                 let bundle =
                     wgc::command::RenderBundleEncoder::new(&desc, device, Some(base)).unwrap();
@@ -343,7 +366,7 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 range,
                 queued,
             } => {
-                let bin = std::fs::read(dir.join(data)).unwrap();
+                let bin = store.read(&data).unwrap();
                 let size = (range.end - range.start) as usize;
                 if queued {
                     self.queue_write_buffer::<A>(device, id, range.start, &bin)
@@ -360,7 +383,7 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 layout,
                 size,
             } => {
-                let bin = std::fs::read(dir.join(data)).unwrap();
+                let bin = store.read(&data).unwrap();
                 self.queue_write_texture::<A>(device, &to, &bin, &layout, &size)
                     .unwrap();
             }
@@ -382,3 +405,155 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
         }
     }
 }
+
+/// Parses the name of a backend given on the command line.
+///
+/// The software backend is only known with the "software" feature.
+pub fn parse_backend(name: &str) -> Option<wgt::Backend> {
+    Some(match name.to_lowercase().as_str() {
+        "vulkan" | "vk" => wgt::Backend::Vulkan,
+        "metal" | "mtl" => wgt::Backend::Metal,
+        "dx12" | "d3d12" => wgt::Backend::Dx12,
+        "dx11" | "d3d11" => wgt::Backend::Dx11,
+        "gl" | "gles" | "opengl" => wgt::Backend::Gl,
+        "software" if cfg!(feature = "software") => wgt::Backend::Software,
+        _ => return None,
+    })
+}
+
+/// Moves all the IDs referenced by an action from one backend to another.
+///
+/// IDs carry their backend in the upper bits, so replaying a trace on a different
+/// backend requires re-zipping every one of them, not just the device.
+/// With the same backend on both sides, this just makes an owned copy of the action.
+pub fn rezip_action(
+    action: &trace::Action,
+    from: wgt::Backend,
+    to: wgt::Backend,
+) -> trace::Action<'static> {
+    use serde::Deserialize as _;
+
+    let string = ron::ser::to_string(action).unwrap();
+    let mut deserializer = ron::Deserializer::from_str(&string).unwrap();
+    trace::Action::deserialize(rezip::Deserializer::new(
+        &mut deserializer,
+        rezip::Rezip { from, to },
+    ))
+    .unwrap()
+}
+
+/// Stands in for the surfaces of a trace with offscreen textures,
+/// so that it can be replayed without a window.
+#[derive(Debug, Default)]
+pub struct OffscreenSurfaces {
+    configs: HashMap<wgc::id::SurfaceId, wgt::SurfaceConfiguration>,
+    current: HashMap<wgc::id::SurfaceId, wgc::id::TextureId>,
+}
+
+impl OffscreenSurfaces {
+    /// Handles a surface action, or gives any other action back.
+    pub fn process<'a, A: wgc::hub::HalApi>(
+        &mut self,
+        global: &wgc::hub::Global<IdentityPassThroughFactory>,
+        device: wgc::id::DeviceId,
+        action: trace::Action<'a>,
+    ) -> Option<trace::Action<'a>> {
+        use wgc::device::trace::Action;
+        match action {
+            Action::ConfigureSurface(surface_id, config) => {
+                self.configs.insert(surface_id, config);
+            }
+            Action::GetSurfaceTexture { id, parent_id } => {
+                let config = self
+                    .configs
+                    .get(&parent_id)
+                    .expect("Surface is not configured");
+                let desc = wgc::resource::TextureDescriptor {
+                    label: Some(Cow::Borrowed("offscreen surface")),
+                    size: wgt::Extent3d {
+                        width: config.width,
+                        height: config.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgt::TextureDimension::D2,
+                    format: config.format,
+                    usage: config.usage,
+                };
+                global.device_maintain_ids::<A>(device).unwrap();
+                let (_, error) = global.device_create_texture::<A>(device, &desc, id);
+                if let Some(e) = error {
+                    panic!("{:?}", e);
+                }
+                self.current.insert(parent_id, id);
+            }
+            Action::Present(surface_id) | Action::DiscardSurfaceTexture(surface_id) => {
+                if let Some(texture_id) = self.current.remove(&surface_id) {
+                    global.texture_drop::<A>(texture_id, false);
+                }
+            }
+            other => return Some(other),
+        }
+        None
+    }
+}
+
+fn copy_blob(target: &mut trace::Trace, store: &trace::DataStore, name: String) -> String {
+    let data = store.read(&name).unwrap();
+    let kind = Path::new(&name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("bin");
+    target.make_binary(kind, &data)
+}
+
+/// Writes the actions out as a new trace, copying the data blobs they refer to.
+pub fn write_trace<'a>(
+    path: &Path,
+    format: trace::TraceFormat,
+    actions: impl IntoIterator<Item = trace::Action<'a>>,
+    store: &trace::DataStore,
+) -> Result<(), std::io::Error> {
+    use wgc::device::trace::Action;
+    let mut target = trace::Trace::new(path, format)?;
+    for action in actions {
+        let action = match action {
+            Action::CreateShaderModule { id, desc, data } => Action::CreateShaderModule {
+                id,
+                desc,
+                data: copy_blob(&mut target, store, data),
+            },
+            Action::CreatePipelineCache { id, desc, data } => Action::CreatePipelineCache {
+                id,
+                desc,
+                data: data.map(|data| copy_blob(&mut target, store, data)),
+            },
+            Action::WriteBuffer {
+                id,
+                data,
+                range,
+                queued,
+            } => Action::WriteBuffer {
+                id,
+                data: copy_blob(&mut target, store, data),
+                range,
+                queued,
+            },
+            Action::WriteTexture {
+                to,
+                data,
+                layout,
+                size,
+            } => Action::WriteTexture {
+                to,
+                data: copy_blob(&mut target, store, data),
+                layout,
+                size,
+            },
+            other => other,
+        };
+        target.add(action);
+    }
+    Ok(())
+}
@@ -0,0 +1,414 @@
+/*! Resources created and referenced by trace actions.
+ *
+ * IDs of different resource types share the same index space,
+ * so every ID is paired with its type here.
+!*/
+
+use wgc::{
+    command::{ComputeCommand, RenderCommand},
+    device::trace::{Action, Command},
+    id,
+};
+
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Resource {
+    Surface(id::SurfaceId),
+    Buffer(id::BufferId),
+    Texture(id::TextureId),
+    TextureView(id::TextureViewId),
+    Sampler(id::SamplerId),
+    BindGroupLayout(id::BindGroupLayoutId),
+    PipelineLayout(id::PipelineLayoutId),
+    BindGroup(id::BindGroupId),
+    ShaderModule(id::ShaderModuleId),
+    ComputePipeline(id::ComputePipelineId),
+    RenderPipeline(id::RenderPipelineId),
+    PipelineCache(id::PipelineCacheId),
+    RenderBundle(id::RenderBundleId),
+    QuerySet(id::QuerySetId),
+}
+
+impl Resource {
+    /// Name of the resource type, for reporting.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Resource::Surface(_) => "surface",
+            Resource::Buffer(_) => "buffer",
+            Resource::Texture(_) => "texture",
+            Resource::TextureView(_) => "texture view",
+            Resource::Sampler(_) => "sampler",
+            Resource::BindGroupLayout(_) => "bind group layout",
+            Resource::PipelineLayout(_) => "pipeline layout",
+            Resource::BindGroup(_) => "bind group",
+            Resource::ShaderModule(_) => "shader module",
+            Resource::ComputePipeline(_) => "compute pipeline",
+            Resource::RenderPipeline(_) => "render pipeline",
+            Resource::PipelineCache(_) => "pipeline cache",
+            Resource::RenderBundle(_) => "render bundle",
+            Resource::QuerySet(_) => "query set",
+        }
+    }
+}
+
+/// Returns the resources that come into existence with an action.
+///
+/// Configuring a surface is treated as creating it, since surfaces
+/// themselves are not part of a trace.
+pub fn created(action: &Action) -> Vec<Resource> {
+    match *action {
+        Action::ConfigureSurface(id, _) => vec![Resource::Surface(id)],
+        Action::CreateBuffer(id, _) => vec![Resource::Buffer(id)],
+        Action::CreateTexture(id, _) | Action::GetSurfaceTexture { id, .. } => {
+            vec![Resource::Texture(id)]
+        }
+        Action::CreateTextureView { id, .. } => vec![Resource::TextureView(id)],
+        Action::CreateSampler(id, _) => vec![Resource::Sampler(id)],
+        Action::CreateBindGroupLayout(id, _) => vec![Resource::BindGroupLayout(id)],
+        Action::CreatePipelineLayout(id, _) => vec![Resource::PipelineLayout(id)],
+        Action::CreateBindGroup(id, _) => vec![Resource::BindGroup(id)],
+        Action::CreateShaderModule { id, .. } => vec![Resource::ShaderModule(id)],
+        Action::CreateComputePipeline {
+            id,
+            ref implicit_context,
+            ..
+        } => {
+            let mut list = vec![Resource::ComputePipeline(id)];
+            list.extend(implicit_context.iter().flat_map(implicit_resources));
+            list
+        }
+        Action::CreateRenderPipeline {
+            id,
+            ref implicit_context,
+            ..
+        } => {
+            let mut list = vec![Resource::RenderPipeline(id)];
+            list.extend(implicit_context.iter().flat_map(implicit_resources));
+            list
+        }
+        Action::CreatePipelineCache { id, .. } => vec![Resource::PipelineCache(id)],
+        Action::CreateRenderBundle { id, .. } => vec![Resource::RenderBundle(id)],
+        Action::CreateQuerySet { id, .. } => vec![Resource::QuerySet(id)],
+        _ => Vec::new(),
+    }
+}
+
+fn implicit_resources(context: &wgc::device::ImplicitPipelineContext) -> Vec<Resource> {
+    let mut list = vec![Resource::PipelineLayout(context.root_id)];
+    list.extend(
+        context
+            .group_ids
+            .iter()
+            .map(|&id| Resource::BindGroupLayout(id)),
+    );
+    list
+}
+
+/// Returns the resource that an action frees or destroys.
+pub fn released(action: &Action) -> Option<Resource> {
+    Some(match *action {
+        Action::FreeBuffer(id) | Action::DestroyBuffer(id) => Resource::Buffer(id),
+        Action::FreeTexture(id) | Action::DestroyTexture(id) => Resource::Texture(id),
+        Action::DestroyTextureView(id) => Resource::TextureView(id),
+        Action::DestroySampler(id) => Resource::Sampler(id),
+        Action::DestroyBindGroupLayout(id) => Resource::BindGroupLayout(id),
+        Action::DestroyPipelineLayout(id) => Resource::PipelineLayout(id),
+        Action::DestroyBindGroup(id) => Resource::BindGroup(id),
+        Action::DestroyShaderModule(id) => Resource::ShaderModule(id),
+        Action::DestroyComputePipeline(id) => Resource::ComputePipeline(id),
+        Action::DestroyRenderPipeline(id) => Resource::RenderPipeline(id),
+        Action::DestroyPipelineCache(id) => Resource::PipelineCache(id),
+        Action::DestroyRenderBundle(id) => Resource::RenderBundle(id),
+        Action::DestroyQuerySet(id) => Resource::QuerySet(id),
+        _ => return None,
+    })
+}
+
+/// Maps every resource to the indices of the actions creating it.
+///
+/// IDs are reused after the resources are freed, so there may be several.
+pub fn creators(actions: &[Action]) -> HashMap<Resource, Vec<usize>> {
+    let mut creators = HashMap::<Resource, Vec<usize>>::new();
+    for (index, action) in actions.iter().enumerate() {
+        for resource in created(action) {
+            creators.entry(resource).or_default().push(index);
+        }
+    }
+    creators
+}
+
+/// Extends the set of actions with the ones creating the resources they refer to.
+pub fn dependency_closure(
+    indices: impl IntoIterator<Item = usize>,
+    actions: &[Action],
+    creators: &HashMap<Resource, Vec<usize>>,
+) -> BTreeSet<usize> {
+    let mut set = BTreeSet::new();
+    let mut stack = indices.into_iter().collect::<Vec<_>>();
+    while let Some(index) = stack.pop() {
+        if !set.insert(index) {
+            continue;
+        }
+        for resource in referenced(&actions[index]) {
+            if let Some(list) = creators.get(&resource) {
+                stack.extend(list.iter().cloned().filter(|&i| !set.contains(&i)));
+            }
+        }
+    }
+    set
+}
+
+/// Returns the resources that an action needs to exist.
+pub fn referenced(action: &Action) -> Vec<Resource> {
+    let mut list = Vec::new();
+    match *action {
+        Action::Init { .. }
+        | Action::ConfigureSurface(..)
+        | Action::CreateBuffer(..)
+        | Action::CreateTexture(..)
+        | Action::CreateSampler(..)
+        | Action::CreateBindGroupLayout(..)
+        | Action::CreateShaderModule { .. }
+        | Action::CreateQuerySet { .. } => {}
+        Action::FreeBuffer(id) | Action::DestroyBuffer(id) => list.push(Resource::Buffer(id)),
+        Action::FreeTexture(id) | Action::DestroyTexture(id) => list.push(Resource::Texture(id)),
+        Action::CreateTextureView { parent_id, .. } => list.push(Resource::Texture(parent_id)),
+        Action::DestroyTextureView(id) => list.push(Resource::TextureView(id)),
+        Action::DestroySampler(id) => list.push(Resource::Sampler(id)),
+        Action::GetSurfaceTexture { parent_id, .. } => list.push(Resource::Surface(parent_id)),
+        Action::Present(id) | Action::DiscardSurfaceTexture(id) => list.push(Resource::Surface(id)),
+        Action::DestroyBindGroupLayout(id) => list.push(Resource::BindGroupLayout(id)),
+        Action::CreatePipelineLayout(_, ref desc) => list.extend(
+            desc.bind_group_layouts
+                .iter()
+                .map(|&id| Resource::BindGroupLayout(id)),
+        ),
+        Action::DestroyPipelineLayout(id) => list.push(Resource::PipelineLayout(id)),
+        Action::CreateBindGroup(_, ref desc) => {
+            list.push(Resource::BindGroupLayout(desc.layout));
+            for entry in desc.entries.iter() {
+                binding_resources(&entry.resource, &mut list);
+            }
+        }
+        Action::DestroyBindGroup(id) => list.push(Resource::BindGroup(id)),
+        Action::DestroyShaderModule(id) => list.push(Resource::ShaderModule(id)),
+        Action::CreateComputePipeline { ref desc, .. } => {
+            list.extend(desc.layout.map(Resource::PipelineLayout));
+            list.push(Resource::ShaderModule(desc.stage.module));
+            list.extend(desc.cache.map(Resource::PipelineCache));
+        }
+        Action::DestroyComputePipeline(id) => list.push(Resource::ComputePipeline(id)),
+        Action::CreateRenderPipeline { ref desc, .. } => {
+            list.extend(desc.layout.map(Resource::PipelineLayout));
+            list.push(Resource::ShaderModule(desc.vertex.stage.module));
+            if let Some(ref fragment) = desc.fragment {
+                list.push(Resource::ShaderModule(fragment.stage.module));
+            }
+            list.extend(desc.cache.map(Resource::PipelineCache));
+        }
+        Action::DestroyRenderPipeline(id) => list.push(Resource::RenderPipeline(id)),
+        Action::DestroyPipelineCache(id) => list.push(Resource::PipelineCache(id)),
+        Action::CreateRenderBundle { ref base, .. } => {
+            for command in base.commands.iter() {
+                render_command_resources(command, &mut list);
+            }
+        }
+        Action::DestroyRenderBundle(id) => list.push(Resource::RenderBundle(id)),
+        Action::DestroyQuerySet(id) => list.push(Resource::QuerySet(id)),
+        Action::WriteBuffer { id, .. } => list.push(Resource::Buffer(id)),
+        Action::WriteTexture { ref to, .. } => list.push(Resource::Texture(to.texture)),
+        Action::Submit(_, ref commands) => {
+            for command in commands.iter() {
+                command_resources(command, &mut list);
+            }
+        }
+    }
+    list
+}
+
+fn binding_resources(resource: &wgc::binding_model::BindingResource, list: &mut Vec<Resource>) {
+    use wgc::binding_model::BindingResource as Br;
+    match *resource {
+        Br::Buffer(ref binding) => list.push(Resource::Buffer(binding.buffer_id)),
+        Br::BufferArray(ref bindings) => list.extend(
+            bindings
+                .iter()
+                .map(|binding| Resource::Buffer(binding.buffer_id)),
+        ),
+        Br::Sampler(id) => list.push(Resource::Sampler(id)),
+        Br::SamplerArray(ref ids) => list.extend(ids.iter().map(|&id| Resource::Sampler(id))),
+        Br::TextureView(id) => list.push(Resource::TextureView(id)),
+        Br::TextureViewArray(ref ids) => {
+            list.extend(ids.iter().map(|&id| Resource::TextureView(id)))
+        }
+    }
+}
+
+fn render_command_resources(command: &RenderCommand, list: &mut Vec<Resource>) {
+    match *command {
+        RenderCommand::SetBindGroup { bind_group_id, .. } => {
+            list.push(Resource::BindGroup(bind_group_id))
+        }
+        RenderCommand::SetPipeline(id) => list.push(Resource::RenderPipeline(id)),
+        RenderCommand::SetIndexBuffer { buffer_id, .. }
+        | RenderCommand::SetVertexBuffer { buffer_id, .. }
+        | RenderCommand::MultiDrawIndirect { buffer_id, .. } => {
+            list.push(Resource::Buffer(buffer_id))
+        }
+        RenderCommand::MultiDrawIndirectCount {
+            buffer_id,
+            count_buffer_id,
+            ..
+        } => {
+            list.push(Resource::Buffer(buffer_id));
+            list.push(Resource::Buffer(count_buffer_id));
+        }
+        RenderCommand::WriteTimestamp { query_set_id, .. }
+        | RenderCommand::BeginPipelineStatisticsQuery { query_set_id, .. } => {
+            list.push(Resource::QuerySet(query_set_id))
+        }
+        RenderCommand::ExecuteBundle(id) => list.push(Resource::RenderBundle(id)),
+        _ => {}
+    }
+}
+
+fn compute_command_resources(command: &ComputeCommand, list: &mut Vec<Resource>) {
+    match *command {
+        ComputeCommand::SetBindGroup { bind_group_id, .. } => {
+            list.push(Resource::BindGroup(bind_group_id))
+        }
+        ComputeCommand::SetPipeline(id) => list.push(Resource::ComputePipeline(id)),
+        ComputeCommand::DispatchIndirect { buffer_id, .. } => {
+            list.push(Resource::Buffer(buffer_id))
+        }
+        ComputeCommand::WriteTimestamp { query_set_id, .. }
+        | ComputeCommand::BeginPipelineStatisticsQuery { query_set_id, .. } => {
+            list.push(Resource::QuerySet(query_set_id))
+        }
+        _ => {}
+    }
+}
+
+fn command_resources(command: &Command, list: &mut Vec<Resource>) {
+    match *command {
+        Command::CopyBufferToBuffer { src, dst, .. } => {
+            list.push(Resource::Buffer(src));
+            list.push(Resource::Buffer(dst));
+        }
+        Command::CopyBufferToTexture {
+            ref src, ref dst, ..
+        } => {
+            list.push(Resource::Buffer(src.buffer));
+            list.push(Resource::Texture(dst.texture));
+        }
+        Command::CopyTextureToBuffer {
+            ref src, ref dst, ..
+        } => {
+            list.push(Resource::Texture(src.texture));
+            list.push(Resource::Buffer(dst.buffer));
+        }
+        Command::CopyTextureToTexture {
+            ref src, ref dst, ..
+        } => {
+            list.push(Resource::Texture(src.texture));
+            list.push(Resource::Texture(dst.texture));
+        }
+        Command::ClearBuffer { dst, .. } => list.push(Resource::Buffer(dst)),
+        Command::ClearTexture { dst, .. } => list.push(Resource::Texture(dst)),
+        Command::WriteTimestamp { query_set_id, .. } => list.push(Resource::QuerySet(query_set_id)),
+        Command::ResolveQuerySet {
+            query_set_id,
+            destination,
+            ..
+        } => {
+            list.push(Resource::QuerySet(query_set_id));
+            list.push(Resource::Buffer(destination));
+        }
+        Command::PushDebugGroup(_) | Command::PopDebugGroup | Command::InsertDebugMarker(_) => {}
+        Command::RunComputePass { ref base } => {
+            for command in base.commands.iter() {
+                compute_command_resources(command, list);
+            }
+        }
+        Command::RunRenderPass {
+            ref base,
+            ref target_colors,
+            ref target_depth_stencil,
+            occlusion_query_set_id,
+        } => {
+            for command in base.commands.iter() {
+                render_command_resources(command, list);
+            }
+            for at in target_colors.iter() {
+                list.push(Resource::TextureView(at.view));
+                list.extend(at.resolve_target.map(Resource::TextureView));
+            }
+            list.extend(
+                target_depth_stencil
+                    .as_ref()
+                    .map(|at| Resource::TextureView(at.view)),
+            );
+            list.extend(occlusion_query_set_id.map(Resource::QuerySet));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{creators, dependency_closure, Resource};
+    use wgc::{device::trace::Action, id::TypedId as _};
+
+    #[test]
+    fn closure_of_dependencies() {
+        let backend = wgt::Backend::Empty;
+        let buffer = wgc::id::BufferId::zip(0, 1, backend);
+        let texture = wgc::id::TextureId::zip(0, 1, backend);
+        let view = wgc::id::TextureViewId::zip(0, 1, backend);
+        let buffer_desc = wgt::BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: wgt::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        let actions = [
+            Action::CreateBuffer(buffer, buffer_desc.clone()),
+            Action::CreateTexture(
+                texture,
+                wgt::TextureDescriptor {
+                    label: None,
+                    size: wgt::Extent3d::default(),
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgt::TextureDimension::D2,
+                    format: wgt::TextureFormat::Rgba8Unorm,
+                    usage: wgt::TextureUsages::TEXTURE_BINDING,
+                },
+            ),
+            Action::CreateTextureView {
+                id: view,
+                parent_id: texture,
+                desc: Default::default(),
+            },
+            Action::FreeBuffer(buffer),
+            // The ID of the buffer is reused.
+            Action::CreateBuffer(buffer, buffer_desc),
+            Action::DestroyTextureView(view),
+        ];
+        let creators = creators(&actions);
+        assert_eq!(creators[&Resource::Buffer(buffer)], [0, 4]);
+
+        let closure = |indices: &[usize]| {
+            dependency_closure(indices.iter().cloned(), &actions, &creators)
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        // Creations don't depend on anything.
+        assert_eq!(closure(&[0]), [0]);
+        // The view and the texture it is made from.
+        assert_eq!(closure(&[5]), [1, 2, 5]);
+        // Every creation of a reused ID is kept.
+        assert_eq!(closure(&[3]), [0, 3, 4]);
+        assert!(closure(&[]).is_empty());
+    }
+}
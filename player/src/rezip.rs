@@ -0,0 +1,327 @@
+/*! Moving the IDs of a trace from one backend to another.
+ *
+ * IDs are deserialized from `Id(index, epoch, backend)`, so wrapping the deserializer
+ * and substituting the `Backend` values reaches every ID of an action,
+ * without enumerating the descriptor types that contain them.
+!*/
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer as _};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Rezip {
+    pub from: wgt::Backend,
+    pub to: wgt::Backend,
+}
+
+impl Rezip {
+    fn backend(self, backend: wgt::Backend) -> wgt::Backend {
+        if backend == self.from {
+            self.to
+        } else {
+            backend
+        }
+    }
+}
+
+/// Deserializer that moves every `Backend` it encounters.
+pub struct Deserializer<D> {
+    inner: D,
+    rezip: Rezip,
+}
+
+impl<D> Deserializer<D> {
+    pub fn new(inner: D, rezip: Rezip) -> Self {
+        Self { inner, rezip }
+    }
+}
+
+struct Wrap<T> {
+    inner: T,
+    rezip: Rezip,
+}
+
+impl<T> Wrap<T> {
+    fn new(inner: T, rezip: Rezip) -> Self {
+        Self { inner, rezip }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident)*) => {$(
+        fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+            self.inner.$method(Wrap::new(visitor, self.rezip))
+        }
+    )*};
+}
+
+impl<'de, D: de::Deserializer<'de>> de::Deserializer<'de> for Deserializer<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any deserialize_bool
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_seq deserialize_map
+        deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner
+            .deserialize_unit_struct(name, Wrap::new(visitor, self.rezip))
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner
+            .deserialize_newtype_struct(name, Wrap::new(visitor, self.rezip))
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner
+            .deserialize_tuple(len, Wrap::new(visitor, self.rezip))
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner
+            .deserialize_tuple_struct(name, len, Wrap::new(visitor, self.rezip))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner
+            .deserialize_struct(name, fields, Wrap::new(visitor, self.rezip))
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        if name == "Backend" {
+            let backend = <wgt::Backend as de::Deserialize>::deserialize(self.inner)?;
+            let variant: de::value::StringDeserializer<D::Error> =
+                format!("{:?}", self.rezip.backend(backend)).into_deserializer();
+            visitor.visit_enum(variant)
+        } else {
+            self.inner
+                .deserialize_enum(name, variants, Wrap::new(visitor, self.rezip))
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty))*) => {$(
+        fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+            self.inner.$method(v)
+        }
+    )*};
+}
+
+impl<'de, V: de::Visitor<'de>> de::Visitor<'de> for Wrap<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool)
+        visit_i8(i8) visit_i16(i16) visit_i32(i32) visit_i64(i64)
+        visit_u8(u8) visit_u16(u16) visit_u32(u32) visit_u64(u64)
+        visit_f32(f32) visit_f64(f64) visit_char(char)
+        visit_str(&str) visit_borrowed_str(&'de str) visit_string(String)
+        visit_bytes(&[u8]) visit_borrowed_bytes(&'de [u8]) visit_byte_buf(Vec<u8>)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        self.inner.visit_some(Deserializer::new(d, self.rezip))
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        self.inner
+            .visit_newtype_struct(Deserializer::new(d, self.rezip))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_seq(Wrap::new(seq, self.rezip))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_map(Wrap::new(map, self.rezip))
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_enum(Wrap::new(data, self.rezip))
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Wrap<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        self.inner.deserialize(Deserializer::new(d, self.rezip))
+    }
+}
+
+impl<'de, A: de::SeqAccess<'de>> de::SeqAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        self.inner.next_element_seed(Wrap::new(seed, self.rezip))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: de::MapAccess<'de>> de::MapAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.inner.next_key_seed(Wrap::new(seed, self.rezip))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        self.inner.next_value_seed(Wrap::new(seed, self.rezip))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: de::EnumAccess<'de>> de::EnumAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+    type Variant = Wrap<A::Variant>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), A::Error> {
+        let rezip = self.rezip;
+        let (value, variant) = self.inner.variant_seed(Wrap::new(seed, rezip))?;
+        Ok((value, Wrap::new(variant, rezip)))
+    }
+}
+
+impl<'de, A: de::VariantAccess<'de>> de::VariantAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        self.inner.newtype_variant_seed(Wrap::new(seed, self.rezip))
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.inner
+            .tuple_variant(len, Wrap::new(visitor, self.rezip))
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.inner
+            .struct_variant(fields, Wrap::new(visitor, self.rezip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use wgc::{device::trace::Action, id::TypedId as _};
+
+    #[test]
+    fn rezip_action() {
+        let (from, to) = (wgt::Backend::Vulkan, wgt::Backend::Gl);
+        // The label looks like the end of a serialized ID, it must be left alone.
+        let label = Some(Cow::Borrowed("view,Vulkan)"));
+
+        let init = Action::Init {
+            desc: wgt::DeviceDescriptor {
+                label: label.clone(),
+                ..Default::default()
+            },
+            backend: from,
+        };
+        match crate::rezip_action(&init, from, to) {
+            Action::Init { desc, backend } => {
+                assert_eq!(backend, to);
+                assert_eq!(desc.label, label);
+            }
+            other => panic!("Unexpected {:?}", other),
+        }
+
+        let view = Action::CreateTextureView {
+            id: wgc::id::TextureViewId::zip(1, 2, from),
+            // IDs of other backends are kept.
+            parent_id: wgc::id::TextureId::zip(3, 4, wgt::Backend::Metal),
+            desc: wgc::resource::TextureViewDescriptor {
+                label: label.clone(),
+                ..Default::default()
+            },
+        };
+        match crate::rezip_action(&view, from, to) {
+            Action::CreateTextureView {
+                id,
+                parent_id,
+                desc,
+            } => {
+                assert_eq!(id.unzip(), (1, 2, to));
+                assert_eq!(parent_id.unzip(), (3, 4, wgt::Backend::Metal));
+                assert_eq!(desc.label, label);
+            }
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+/*! Tests for the trace converter.
+!*/
+
+use std::{path::Path, process::Command};
+use wgc::{device::trace, id::TypedId as _};
+
+const DATA: [u8; 4] = [1, 2, 3, 4];
+
+fn convert(input: &Path, output: &Path) {
+    let status = Command::new(env!("CARGO_BIN_EXE_convert"))
+        .arg(input)
+        .arg(output)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+/// Loads a trace, returning its actions as RON and the data of its buffer write.
+fn load(path: &Path, expected_format: trace::TraceFormat) -> (String, Vec<u8>) {
+    let (format, actions, store) = trace::load(path).unwrap();
+    assert_eq!(format, expected_format);
+    let data = actions
+        .iter()
+        .find_map(|action| match *action {
+            trace::Action::WriteBuffer { ref data, .. } => Some(store.read(data).unwrap().to_vec()),
+            _ => None,
+        })
+        .unwrap();
+    (ron::ser::to_string(&actions).unwrap(), data)
+}
+
+#[test]
+fn convert_round_trip() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("convert");
+    let _ = std::fs::remove_dir_all(&dir);
+    let ron_dir = dir.join("ron");
+    let binary_file = dir.join("trace.bin");
+    let converted_dir = dir.join("converted");
+    std::fs::create_dir_all(&ron_dir).unwrap();
+
+    {
+        let mut trace = trace::Trace::new(&ron_dir, trace::TraceFormat::Ron).unwrap();
+        trace.add(trace::Action::Init {
+            desc: Default::default(),
+            backend: wgt::Backend::Empty,
+        });
+        let buffer = wgc::id::BufferId::zip(1, 0, wgt::Backend::Empty);
+        trace.add(trace::Action::CreateBuffer(
+            buffer,
+            wgt::BufferDescriptor {
+                label: None,
+                size: DATA.len() as wgt::BufferAddress,
+                usage: wgt::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        ));
+        let data = trace.make_binary("bin", &DATA);
+        trace.add(trace::Action::WriteBuffer {
+            id: buffer,
+            data,
+            range: 0..DATA.len() as wgt::BufferAddress,
+            queued: true,
+        });
+        trace.add(trace::Action::Submit(1, Vec::new()));
+    }
+
+    convert(&ron_dir, &binary_file);
+    convert(&binary_file, &converted_dir);
+
+    let original = load(&ron_dir, trace::TraceFormat::Ron);
+    assert_eq!(original.1, DATA);
+    assert_eq!(load(&binary_file, trace::TraceFormat::Binary), original);
+    assert_eq!(load(&converted_dir, trace::TraceFormat::Ron), original);
+
+    // Keep the header and the tag of the first record, and claim a huge action follows.
+    let mut corrupt = std::fs::read(&binary_file).unwrap();
+    corrupt.truncate(trace::BINARY_MAGIC.len() + std::mem::size_of_val(&trace::BINARY_VERSION) + 1);
+    corrupt.extend_from_slice(&u64::MAX.to_le_bytes());
+    let corrupt_file = dir.join("corrupt.bin");
+    std::fs::write(&corrupt_file, corrupt).unwrap();
+    assert!(matches!(
+        trace::load(&corrupt_file),
+        Err(trace::LoadError::Io(_))
+    ));
+}
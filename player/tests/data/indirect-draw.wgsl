@@ -0,0 +1,15 @@
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) x: f32,
+) -> @builtin(position) vec4<f32> {
+    // thin triangle covering only the center of the texel at `x`
+    let dx = f32(i32(vertex_index) - 1) * 0.2;
+    let y = select(-1.0, 3.0, vertex_index == 1u);
+    return vec4<f32>(x + dx, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
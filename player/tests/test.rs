@@ -5,14 +5,18 @@
  *  Test requirements:
  *    - all IDs have the backend `Empty`
  *    - all expected buffers have `MAP_READ` usage
+ *    - all expected textures have `COPY_SRC` usage
  *    - last action is `Submit`
  *    - no swapchain use
 !*/
 
 use player::{GlobalPlay, IdentityPassThroughFactory};
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     fs::{read_to_string, File},
     io::{Read, Seek, SeekFrom},
+    num::NonZeroU32,
     path::{Path, PathBuf},
     slice,
 };
@@ -48,10 +52,60 @@ struct Expectation {
     data: ExpectedData,
 }
 
+#[derive(serde::Deserialize)]
+enum ExpectedTexels {
+    /// File with the texel blocks tightly packed, row after row.
+    Raw(String),
+    /// PNG image with the same layout of texels as the texture.
+    Png(String),
+}
+
+impl ExpectedTexels {
+    fn load(&self, dir: &Path) -> Vec<u8> {
+        match *self {
+            ExpectedTexels::Raw(ref name) => std::fs::read(dir.join(name)).unwrap(),
+            ExpectedTexels::Png(ref name) => {
+                let decoder = png::Decoder::new(File::open(dir.join(name)).unwrap());
+                let mut reader = decoder.read_info().unwrap();
+                let mut data = vec![0; reader.output_buffer_size()];
+                let info = reader.next_frame(&mut data).unwrap();
+                data.truncate(info.buffer_size());
+                data
+            }
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct TextureExpectation {
+    name: String,
+    texture: RawId,
+    #[serde(default)]
+    mip_level: u32,
+    #[serde(default)]
+    array_layer: u32,
+    #[serde(default)]
+    aspect: wgt::TextureAspect,
+    data: ExpectedTexels,
+    /// Largest difference allowed between the expected and the actual bytes.
+    #[serde(default)]
+    tolerance: u8,
+}
+
+/// Staging buffer receiving the contents of an expected texture.
+struct TextureReadback {
+    buffer: wgc::id::BufferId,
+    bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    rows: u32,
+}
+
 #[derive(serde::Deserialize)]
 struct Test<'a> {
     features: wgt::Features,
     expectations: Vec<Expectation>,
+    #[serde(default)]
+    texture_expectations: Vec<TextureExpectation>,
     actions: Vec<wgc::device::trace::Action<'a>>,
 }
 
@@ -70,6 +124,7 @@ impl Test<'_> {
             wgt::Backend::Dx12 => "Dx12",
             wgt::Backend::Dx11 => "Dx11",
             wgt::Backend::Gl => "Gl",
+            wgt::Backend::Software => "Software",
             _ => unreachable!(),
         };
         let string = read_to_string(path).unwrap().replace("Empty", backend_name);
@@ -99,11 +154,115 @@ impl Test<'_> {
             panic!("{:?}", e);
         }
 
+        let store = wgc::device::trace::DataStore::Directory(dir.to_path_buf());
+        // The harness creates its own staging buffers after the ones of the test.
+        let mut textures = HashMap::new();
+        let mut next_buffer_index = 0;
+        for action in self.actions.iter() {
+            match *action {
+                wgc::device::trace::Action::CreateTexture(id, ref desc) => {
+                    let (index, epoch, _) = id.unzip();
+                    textures.insert((index, epoch), (desc.size, desc.format));
+                }
+                wgc::device::trace::Action::CreateBuffer(id, _) => {
+                    next_buffer_index = next_buffer_index.max(id.unzip().0 + 1);
+                }
+                _ => {}
+            }
+        }
+
         let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
         println!("\t\t\tRunning...");
         for action in self.actions {
-            wgc::gfx_select!(device => global.process(device, action, dir, &mut command_buffer_id_manager));
+            wgc::gfx_select!(device => global.process(device, action, &store, &mut command_buffer_id_manager));
+        }
+
+        let mut readbacks = Vec::with_capacity(self.texture_expectations.len());
+        if !self.texture_expectations.is_empty() {
+            println!("\t\t\tCopying textures...");
+            let (encoder, error) = wgc::gfx_select!(device => global.device_create_command_encoder(
+                device,
+                &wgt::CommandEncoderDescriptor { label: Some(Cow::Borrowed("texture expectations")) },
+                command_buffer_id_manager.alloc(backend)
+            ));
+            if let Some(e) = error {
+                panic!("{:?}", e);
+            }
+            for (i, expect) in self.texture_expectations.iter().enumerate() {
+                let &(size, format) = textures
+                    .get(&(expect.texture.index, expect.texture.epoch))
+                    .expect("Expected texture is not created by the test");
+                let info = format.describe();
+                let (block_width, block_height) = (
+                    info.block_dimensions.0 as u32,
+                    info.block_dimensions.1 as u32,
+                );
+                let block_size = match expect.aspect {
+                    wgt::TextureAspect::StencilOnly => 1,
+                    _ => info.block_size as u32,
+                };
+                let width = (size.width >> expect.mip_level).max(1);
+                let height = (size.height >> expect.mip_level).max(1);
+                let blocks_x = (width + block_width - 1) / block_width;
+                let rows = (height + block_height - 1) / block_height;
+                let bytes_per_row = blocks_x * block_size;
+                let alignment = wgt::COPY_BYTES_PER_ROW_ALIGNMENT;
+                let padded_bytes_per_row = (bytes_per_row + alignment - 1) / alignment * alignment;
+
+                let buffer = wgc::id::TypedId::zip(next_buffer_index + i as u32, 1, backend);
+                let (_, error) = wgc::gfx_select!(device => global.device_create_buffer(
+                    device,
+                    &wgc::resource::BufferDescriptor {
+                        label: Some(Cow::Borrowed("texture readback")),
+                        size: padded_bytes_per_row as wgt::BufferAddress * rows as wgt::BufferAddress,
+                        usage: wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    },
+                    buffer
+                ));
+                if let Some(e) = error {
+                    panic!("{:?}", e);
+                }
+                wgc::gfx_select!(device => global.command_encoder_copy_texture_to_buffer(
+                    encoder,
+                    &wgc::command::ImageCopyTexture {
+                        texture: wgc::id::TypedId::zip(expect.texture.index, expect.texture.epoch, backend),
+                        mip_level: expect.mip_level,
+                        origin: wgt::Origin3d { x: 0, y: 0, z: expect.array_layer },
+                        aspect: expect.aspect,
+                    },
+                    &wgc::command::ImageCopyBuffer {
+                        buffer,
+                        layout: wgt::ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                            rows_per_image: NonZeroU32::new(rows),
+                        },
+                    },
+                    &wgt::Extent3d {
+                        width: blocks_x * block_width,
+                        height: rows * block_height,
+                        depth_or_array_layers: 1,
+                    }
+                ))
+                .unwrap();
+                readbacks.push(TextureReadback {
+                    buffer,
+                    bytes_per_row,
+                    padded_bytes_per_row,
+                    rows,
+                });
+            }
+            let (cmdbuf, error) = wgc::gfx_select!(device => global.command_encoder_finish(
+                encoder,
+                &wgt::CommandBufferDescriptor { label: None }
+            ));
+            if let Some(e) = error {
+                panic!("{:?}", e);
+            }
+            wgc::gfx_select!(device => global.queue_submit(device, &[cmdbuf])).unwrap();
         }
+
         println!("\t\t\tMapping...");
         for expect in &self.expectations {
             let buffer = wgc::id::TypedId::zip(expect.buffer.index, expect.buffer.epoch, backend);
@@ -120,6 +279,20 @@ impl Test<'_> {
             .unwrap();
         }
 
+        for readback in &readbacks {
+            wgc::gfx_select!(device => global.buffer_map_async(
+                readback.buffer,
+                0 .. readback.padded_bytes_per_row as wgt::BufferAddress * readback.rows as wgt::BufferAddress,
+                wgc::resource::BufferMapOperation {
+                    host: wgc::device::HostMap::Read,
+                    callback: wgc::resource::BufferMapCallback::from_rust(
+                        Box::new(map_callback)
+                    ),
+                }
+            ))
+            .unwrap();
+        }
+
         println!("\t\t\tWaiting...");
         wgc::gfx_select!(device => global.device_poll(device, wgt::Maintain::Wait)).unwrap();
 
@@ -155,6 +328,45 @@ impl Test<'_> {
             }
         }
 
+        for (expect, readback) in self.texture_expectations.iter().zip(readbacks) {
+            println!("\t\t\tChecking {}", expect.name);
+            let (ptr, size) =
+                wgc::gfx_select!(device => global.buffer_get_mapped_range(readback.buffer, 0, None))
+                    .unwrap();
+            let padded = unsafe { slice::from_raw_parts(ptr, size as usize) };
+            let contents = padded
+                .chunks(readback.padded_bytes_per_row as usize)
+                .take(readback.rows as usize)
+                .flat_map(|row| &row[..readback.bytes_per_row as usize])
+                .cloned()
+                .collect::<Vec<u8>>();
+            let expected_data = expect.data.load(dir);
+
+            #[allow(unknown_lints, clippy::if_then_panic)]
+            if expected_data.len() != contents.len() {
+                panic!(
+                    "Test expectation is not met!\nTexture has {} bytes of data, but expected {}",
+                    contents.len(),
+                    expected_data.len()
+                );
+            }
+            let mismatch = contents
+                .iter()
+                .zip(expected_data.iter())
+                .position(|(&a, &b)| (a as i16 - b as i16).abs() > expect.tolerance as i16);
+            if let Some(offset) = mismatch {
+                let row_size = readback.bytes_per_row as usize;
+                panic!(
+                    "Test expectation is not met!\nTexture byte {} (row {}) was {}, but expected {} with tolerance {}",
+                    offset % row_size,
+                    offset / row_size,
+                    contents[offset],
+                    expected_data[offset],
+                    expect.tolerance
+                );
+            }
+        }
+
         wgc::gfx_select!(device => global.clear_backend(()));
     }
 }
@@ -171,6 +383,7 @@ const BACKENDS: &[wgt::Backend] = &[
     wgt::Backend::Dx12,
     wgt::Backend::Dx11,
     wgt::Backend::Gl,
+    wgt::Backend::Software,
 ];
 
 impl Corpus {
@@ -18,5 +18,6 @@ fn main() {
                 wasm
             )
         },
+        software: { feature = "software" },
     }
 }
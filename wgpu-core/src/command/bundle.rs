@@ -81,13 +81,14 @@ index format changes.
 use crate::{
     binding_model::{self, buffer_binding_type_alignment},
     command::{
-        BasePass, BindGroupStateChange, DrawError, MapPassErr, PassErrorScope, RenderCommand,
-        RenderCommandError, StateChange,
+        BasePass, BindGroupStateChange, DrawError, IndirectKind, IndirectScratch,
+        IndirectValidationError, MapPassErr, PassErrorScope, RenderCommand, RenderCommandError,
+        StateChange,
     },
     conv,
     device::{
-        AttachmentData, Device, DeviceError, MissingDownlevelFlags, RenderPassContext,
-        SHADER_STAGE_COUNT,
+        AttachmentData, Device, DeviceError, MissingDownlevelFlags, MissingFeatures,
+        RenderPassContext, SHADER_STAGE_COUNT,
     },
     error::{ErrorFormatter, PrettyError},
     hub::{GlobalIdentityHandlerFactory, HalApi, Hub, Resource, Storage, Token},
@@ -100,7 +101,7 @@ use crate::{
     Label, LabelHelpers, LifeGuard, Stored,
 };
 use arrayvec::ArrayVec;
-use std::{borrow::Cow, mem, num::NonZeroU32, ops::Range};
+use std::{borrow::Cow, mem, num::NonZeroU32, ops::Range, str};
 use thiserror::Error;
 
 use hal::CommandEncoder as _;
@@ -264,9 +265,11 @@ impl RenderBundleEncoder {
         let mut commands = Vec::new();
         let mut buffer_memory_init_actions = Vec::new();
         let mut texture_memory_init_actions = Vec::new();
+        let mut indirect_limits = Vec::new();
 
         let base = self.base.as_ref();
         let mut next_dynamic_offset = 0;
+        let mut debug_scope_depth = 0u32;
 
         for &command in base.commands {
             match command {
@@ -534,20 +537,31 @@ impl RenderBundleEncoder {
                 RenderCommand::MultiDrawIndirect {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: false,
+                    count,
+                    indexed,
                 } => {
                     let scope = PassErrorScope::Draw {
-                        indexed: false,
+                        indexed,
                         indirect: true,
                         pipeline: state.pipeline_id(),
                     };
+                    let stride = match indexed {
+                        false => mem::size_of::<wgt::DrawIndirectArgs>(),
+                        true => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
+                    } as u64;
+
+                    if count.is_some() {
+                        device
+                            .require_features(wgt::Features::MULTI_DRAW_INDIRECT)
+                            .map_pass_err(scope)?;
+                    }
                     device
                         .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
                         .map_pass_err(scope)?;
 
                     let pipeline = state.pipeline(scope)?;
                     let used_bind_groups = pipeline.used_bind_groups;
+                    let vertex_limits = state.vertex_limits(pipeline);
 
                     let buffer: &resource::Buffer<A> = state
                         .trackers
@@ -559,33 +573,65 @@ impl RenderBundleEncoder {
                     check_buffer_usage(buffer.usage, wgt::BufferUsages::INDIRECT)
                         .map_pass_err(scope)?;
 
+                    let actual_count = count.map_or(1, |c| c.get());
+                    let end_offset = offset + stride * actual_count as u64;
+                    if end_offset > buffer.size {
+                        return Err(RenderBundleErrorInner::IndirectBufferOverrun {
+                            count,
+                            offset,
+                            end_offset,
+                            buffer_size: buffer.size,
+                        })
+                        .map_pass_err(scope);
+                    }
                     buffer_memory_init_actions.extend(buffer.initialization_status.create_action(
                         buffer_id,
-                        offset..(offset + mem::size_of::<wgt::DrawIndirectArgs>() as u64),
+                        offset..end_offset,
                         MemoryInitKind::NeedsInitializedMemory,
                     ));
 
+                    let mut limits = [vertex_limits.vertex_limit, vertex_limits.instance_limit];
+                    if indexed {
+                        let index = match state.index {
+                            Some(ref mut index) => index,
+                            None => return Err(DrawError::MissingIndexBuffer).map_pass_err(scope),
+                        };
+                        limits[0] = index.limit();
+                        commands.extend(index.flush());
+                    }
+                    indirect_limits.push(limits);
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds(used_bind_groups, base.dynamic_offsets));
                     commands.push(command);
                 }
-                RenderCommand::MultiDrawIndirect {
+                RenderCommand::MultiDrawIndirectCount {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: true,
+                    count_buffer_id,
+                    count_buffer_offset,
+                    max_count,
+                    indexed,
                 } => {
                     let scope = PassErrorScope::Draw {
-                        indexed: true,
+                        indexed,
                         indirect: true,
                         pipeline: state.pipeline_id(),
                     };
+                    let stride = match indexed {
+                        false => mem::size_of::<wgt::DrawIndirectArgs>(),
+                        true => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
+                    } as u64;
+
+                    device
+                        .require_features(wgt::Features::MULTI_DRAW_INDIRECT_COUNT)
+                        .map_pass_err(scope)?;
                     device
                         .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
                         .map_pass_err(scope)?;
 
                     let pipeline = state.pipeline(scope)?;
                     let used_bind_groups = pipeline.used_bind_groups;
+                    let vertex_limits = state.vertex_limits(pipeline);
 
                     let buffer: &resource::Buffer<A> = state
                         .trackers
@@ -597,27 +643,80 @@ impl RenderBundleEncoder {
                     check_buffer_usage(buffer.usage, wgt::BufferUsages::INDIRECT)
                         .map_pass_err(scope)?;
 
+                    let count_buffer: &resource::Buffer<A> = state
+                        .trackers
+                        .buffers
+                        .merge_single(&*buffer_guard, count_buffer_id, hal::BufferUses::INDIRECT)
+                        .map_pass_err(scope)?;
+                    self.check_valid_to_use(count_buffer.device_id.value)
+                        .map_pass_err(scope)?;
+                    check_buffer_usage(count_buffer.usage, wgt::BufferUsages::INDIRECT)
+                        .map_pass_err(scope)?;
+
+                    let end_offset = offset + stride * max_count as u64;
+                    if end_offset > buffer.size {
+                        return Err(RenderBundleErrorInner::IndirectBufferOverrun {
+                            count: None,
+                            offset,
+                            end_offset,
+                            buffer_size: buffer.size,
+                        })
+                        .map_pass_err(scope);
+                    }
                     buffer_memory_init_actions.extend(buffer.initialization_status.create_action(
                         buffer_id,
-                        offset..(offset + mem::size_of::<wgt::DrawIndirectArgs>() as u64),
+                        offset..end_offset,
                         MemoryInitKind::NeedsInitializedMemory,
                     ));
 
-                    let index = match state.index {
-                        Some(ref mut index) => index,
-                        None => return Err(DrawError::MissingIndexBuffer).map_pass_err(scope),
-                    };
+                    let begin_count_offset = count_buffer_offset;
+                    let end_count_offset = count_buffer_offset + 4;
+                    if end_count_offset > count_buffer.size {
+                        return Err(RenderBundleErrorInner::IndirectCountBufferOverrun {
+                            begin_count_offset,
+                            end_count_offset,
+                            count_buffer_size: count_buffer.size,
+                        })
+                        .map_pass_err(scope);
+                    }
+                    buffer_memory_init_actions.extend(
+                        count_buffer.initialization_status.create_action(
+                            count_buffer_id,
+                            begin_count_offset..end_count_offset,
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ),
+                    );
 
-                    commands.extend(index.flush());
+                    let mut limits = [vertex_limits.vertex_limit, vertex_limits.instance_limit];
+                    if indexed {
+                        let index = match state.index {
+                            Some(ref mut index) => index,
+                            None => return Err(DrawError::MissingIndexBuffer).map_pass_err(scope),
+                        };
+                        limits[0] = index.limit();
+                        commands.extend(index.flush());
+                    }
+                    indirect_limits.push(limits);
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds(used_bind_groups, base.dynamic_offsets));
                     commands.push(command);
                 }
-                RenderCommand::MultiDrawIndirect { .. }
-                | RenderCommand::MultiDrawIndirectCount { .. } => unimplemented!(),
-                RenderCommand::PushDebugGroup { color: _, len: _ } => unimplemented!(),
-                RenderCommand::InsertDebugMarker { color: _, len: _ } => unimplemented!(),
-                RenderCommand::PopDebugGroup => unimplemented!(),
+                RenderCommand::PushDebugGroup { color: _, len: _ } => {
+                    debug_scope_depth += 1;
+                    commands.push(command);
+                }
+                RenderCommand::InsertDebugMarker { color: _, len: _ } => {
+                    commands.push(command);
+                }
+                RenderCommand::PopDebugGroup => {
+                    let scope = PassErrorScope::PopDebugGroup;
+                    if debug_scope_depth == 0 {
+                        return Err(RenderBundleErrorInner::InvalidPopDebugGroup)
+                            .map_pass_err(scope);
+                    }
+                    debug_scope_depth -= 1;
+                    commands.push(command);
+                }
                 RenderCommand::WriteTimestamp { .. }
                 | RenderCommand::BeginPipelineStatisticsQuery { .. }
                 | RenderCommand::EndPipelineStatisticsQuery => unimplemented!(),
@@ -625,16 +724,27 @@ impl RenderBundleEncoder {
                 | RenderCommand::SetBlendConstant(_)
                 | RenderCommand::SetStencilReference(_)
                 | RenderCommand::SetViewport { .. }
-                | RenderCommand::SetScissor(_) => unreachable!("not supported by a render bundle"),
+                | RenderCommand::SetScissor(_)
+                | RenderCommand::BeginOcclusionQuery { .. }
+                | RenderCommand::EndOcclusionQuery => {
+                    unreachable!("not supported by a render bundle")
+                }
             }
         }
 
+        if debug_scope_depth != 0 {
+            return Err(RenderBundleErrorInner::UnclosedDebugGroups {
+                count: debug_scope_depth,
+            })
+            .map_pass_err(PassErrorScope::Bundle);
+        }
+
         Ok(RenderBundle {
             base: BasePass {
                 label: desc.label.as_ref().map(|cow| cow.to_string()),
                 commands,
                 dynamic_offsets: state.flat_dynamic_offsets,
-                string_data: Vec::new(),
+                string_data: base.string_data.to_vec(),
                 push_constant_data: Vec::new(),
             },
             is_depth_read_only: self.is_depth_read_only,
@@ -646,6 +756,7 @@ impl RenderBundleEncoder {
             used: state.trackers,
             buffer_memory_init_actions,
             texture_memory_init_actions,
+            indirect_limits,
             context: self.context,
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
         })
@@ -694,6 +805,8 @@ pub enum ExecutionError {
     DestroyedBuffer(id::BufferId),
     #[error("using {0} in a render bundle is not implemented")]
     Unimplemented(&'static str),
+    #[error(transparent)]
+    IndirectValidation(#[from] IndirectValidationError),
 }
 impl PrettyError for ExecutionError {
     fn fmt_pretty(&self, fmt: &mut ErrorFormatter) {
@@ -703,6 +816,7 @@ impl PrettyError for ExecutionError {
                 fmt.buffer_label(&id);
             }
             Self::Unimplemented(_reason) => {}
+            Self::IndirectValidation(_) => {}
         };
     }
 }
@@ -722,6 +836,8 @@ pub struct RenderBundle<A: HalApi> {
     pub(crate) used: RenderBundleScope<A>,
     pub(super) buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     pub(super) texture_memory_init_actions: Vec<TextureInitTrackerAction>,
+    /// Limits checked by indirect argument validation, one per indirect draw.
+    pub(super) indirect_limits: Vec<[u32; 2]>,
     pub(super) context: RenderPassContext,
     pub(crate) life_guard: LifeGuard,
 }
@@ -730,6 +846,10 @@ unsafe impl<A: HalApi> Send for RenderBundle<A> {}
 unsafe impl<A: HalApi> Sync for RenderBundle<A> {}
 
 impl<A: HalApi> RenderBundle<A> {
+    pub(super) fn commands(&self) -> &[RenderCommand] {
+        &self.base.commands
+    }
+
     /// Actually encode the contents into a native command buffer.
     ///
     /// This is partially duplicating the logic of `command_encoder_run_render_pass`.
@@ -739,6 +859,9 @@ impl<A: HalApi> RenderBundle<A> {
     /// Note that the function isn't expected to fail, generally.
     /// All the validation has already been done by this point.
     /// The only failure condition is if some of the used buffers are destroyed.
+    ///
+    /// Indirect draws are redirected to `indirect_scratch` when it is given,
+    /// which must have space reserved for them.
     pub(super) unsafe fn execute(
         &self,
         raw: &mut A::CommandEncoder,
@@ -749,8 +872,11 @@ impl<A: HalApi> RenderBundle<A> {
         bind_group_guard: &Storage<crate::binding_model::BindGroup<A>, id::BindGroupId>,
         pipeline_guard: &Storage<crate::pipeline::RenderPipeline<A>, id::RenderPipelineId>,
         buffer_guard: &Storage<crate::resource::Buffer<A>, id::BufferId>,
+        mut indirect_scratch: Option<&mut IndirectScratch<A>>,
     ) -> Result<(), ExecutionError> {
         let mut offsets = self.base.dynamic_offsets.as_slice();
+        let mut indirect_limits = self.indirect_limits.iter();
+        let mut string_offset = 0;
         let mut pipeline_layout_id = None::<id::Valid<id::PipelineLayoutId>>;
         if let Some(ref label) = self.base.label {
             raw.begin_debug_marker(label);
@@ -873,8 +999,8 @@ impl<A: HalApi> RenderBundle<A> {
                 RenderCommand::MultiDrawIndirect {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: false,
+                    count,
+                    indexed,
                 } => {
                     let buffer = buffer_guard
                         .get(buffer_id)
@@ -882,13 +1008,30 @@ impl<A: HalApi> RenderBundle<A> {
                         .raw
                         .as_ref()
                         .ok_or(ExecutionError::DestroyedBuffer(buffer_id))?;
-                    raw.draw_indirect(buffer, offset, 1);
+                    let count = count.map_or(1, |c| c.get());
+                    let limits = *indirect_limits.next().unwrap();
+                    let (buffer, offset) = match indirect_scratch {
+                        Some(ref mut scratch) => scratch.redirect(
+                            buffer_id,
+                            offset,
+                            count,
+                            IndirectKind::draw(indexed),
+                            limits,
+                        )?,
+                        None => (buffer, offset),
+                    };
+                    match indexed {
+                        false => raw.draw_indirect(buffer, offset, count),
+                        true => raw.draw_indexed_indirect(buffer, offset, count),
+                    }
                 }
-                RenderCommand::MultiDrawIndirect {
+                RenderCommand::MultiDrawIndirectCount {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: true,
+                    count_buffer_id,
+                    count_buffer_offset,
+                    max_count,
+                    indexed,
                 } => {
                     let buffer = buffer_guard
                         .get(buffer_id)
@@ -896,16 +1039,56 @@ impl<A: HalApi> RenderBundle<A> {
                         .raw
                         .as_ref()
                         .ok_or(ExecutionError::DestroyedBuffer(buffer_id))?;
-                    raw.draw_indexed_indirect(buffer, offset, 1);
+                    let count_buffer = buffer_guard
+                        .get(count_buffer_id)
+                        .unwrap()
+                        .raw
+                        .as_ref()
+                        .ok_or(ExecutionError::DestroyedBuffer(count_buffer_id))?;
+                    let limits = *indirect_limits.next().unwrap();
+                    let (buffer, offset) = match indirect_scratch {
+                        Some(ref mut scratch) => scratch.redirect(
+                            buffer_id,
+                            offset,
+                            max_count,
+                            IndirectKind::draw(indexed),
+                            limits,
+                        )?,
+                        None => (buffer, offset),
+                    };
+                    match indexed {
+                        false => raw.draw_indirect_count(
+                            buffer,
+                            offset,
+                            count_buffer,
+                            count_buffer_offset,
+                            max_count,
+                        ),
+                        true => raw.draw_indexed_indirect_count(
+                            buffer,
+                            offset,
+                            count_buffer,
+                            count_buffer_offset,
+                            max_count,
+                        ),
+                    }
                 }
-                RenderCommand::MultiDrawIndirect { .. }
-                | RenderCommand::MultiDrawIndirectCount { .. } => {
-                    return Err(ExecutionError::Unimplemented("multi-draw-indirect"))
+                RenderCommand::PushDebugGroup { color: _, len } => {
+                    let label =
+                        str::from_utf8(&self.base.string_data[string_offset..string_offset + len])
+                            .unwrap();
+                    string_offset += len;
+                    raw.begin_debug_marker(label);
                 }
-                RenderCommand::PushDebugGroup { .. }
-                | RenderCommand::InsertDebugMarker { .. }
-                | RenderCommand::PopDebugGroup => {
-                    return Err(ExecutionError::Unimplemented("debug-markers"))
+                RenderCommand::InsertDebugMarker { color: _, len } => {
+                    let label =
+                        str::from_utf8(&self.base.string_data[string_offset..string_offset + len])
+                            .unwrap();
+                    string_offset += len;
+                    raw.insert_debug_marker(label);
+                }
+                RenderCommand::PopDebugGroup => {
+                    raw.end_debug_marker();
                 }
                 RenderCommand::WriteTimestamp { .. }
                 | RenderCommand::BeginPipelineStatisticsQuery { .. }
@@ -916,7 +1099,9 @@ impl<A: HalApi> RenderBundle<A> {
                 | RenderCommand::SetBlendConstant(_)
                 | RenderCommand::SetStencilReference(_)
                 | RenderCommand::SetViewport { .. }
-                | RenderCommand::SetScissor(_) => unreachable!(),
+                | RenderCommand::SetScissor(_)
+                | RenderCommand::BeginOcclusionQuery { .. }
+                | RenderCommand::EndOcclusionQuery => unreachable!(),
             }
         }
 
@@ -1363,7 +1548,27 @@ pub(super) enum RenderBundleErrorInner {
     #[error(transparent)]
     Draw(#[from] DrawError),
     #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+    #[error(transparent)]
     MissingDownlevelFlags(#[from] MissingDownlevelFlags),
+    #[error("indirect draw uses bytes {offset}..{end_offset} {} which overruns indirect buffer of size {buffer_size}",
+        count.map_or_else(String::new, |v| format!("(using count {})", v)))]
+    IndirectBufferOverrun {
+        count: Option<NonZeroU32>,
+        offset: u64,
+        end_offset: u64,
+        buffer_size: u64,
+    },
+    #[error("indirect draw uses bytes {begin_count_offset}..{end_count_offset} which overruns indirect buffer of size {count_buffer_size}")]
+    IndirectCountBufferOverrun {
+        begin_count_offset: u64,
+        end_count_offset: u64,
+        count_buffer_size: u64,
+    },
+    #[error("cannot pop debug group, because number of pushed debug groups is zero")]
+    InvalidPopDebugGroup,
+    #[error("render bundle has {count} debug group(s) that were pushed but never popped")]
+    UnclosedDebugGroups { count: u32 },
 }
 
 impl<T> From<T> for RenderBundleErrorInner
@@ -1389,6 +1594,10 @@ impl RenderBundleError {
         scope: PassErrorScope::Bundle,
         inner: RenderBundleErrorInner::Device(DeviceError::Invalid),
     };
+    pub(crate) const LOST_DEVICE: Self = RenderBundleError {
+        scope: PassErrorScope::Bundle,
+        inner: RenderBundleErrorInner::Device(DeviceError::Lost),
+    };
 }
 impl PrettyError for RenderBundleError {
     fn fmt_pretty(&self, fmt: &mut ErrorFormatter) {
@@ -1414,7 +1623,7 @@ where
 pub mod bundle_ffi {
     use super::{RenderBundleEncoder, RenderCommand};
     use crate::{id, RawString};
-    use std::{convert::TryInto, slice};
+    use std::{convert::TryInto, ffi, num::NonZeroU32, slice};
     use wgt::{BufferAddress, BufferSize, DynamicOffset, IndexFormat};
 
     /// # Safety
@@ -1593,21 +1802,101 @@ pub mod bundle_ffi {
         });
     }
 
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indirect(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count: u32,
+    ) {
+        bundle.base.commands.push(RenderCommand::MultiDrawIndirect {
+            buffer_id,
+            offset,
+            count: NonZeroU32::new(count),
+            indexed: false,
+        });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indexed_indirect(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count: u32,
+    ) {
+        bundle.base.commands.push(RenderCommand::MultiDrawIndirect {
+            buffer_id,
+            offset,
+            count: NonZeroU32::new(count),
+            indexed: true,
+        });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indirect_count(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count_buffer_id: id::BufferId,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        bundle
+            .base
+            .commands
+            .push(RenderCommand::MultiDrawIndirectCount {
+                buffer_id,
+                offset,
+                count_buffer_id,
+                count_buffer_offset,
+                max_count,
+                indexed: false,
+            });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indexed_indirect_count(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count_buffer_id: id::BufferId,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        bundle
+            .base
+            .commands
+            .push(RenderCommand::MultiDrawIndirectCount {
+                buffer_id,
+                offset,
+                count_buffer_id,
+                count_buffer_offset,
+                max_count,
+                indexed: true,
+            });
+    }
+
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_push_debug_group(
-        _bundle: &mut RenderBundleEncoder,
-        _label: RawString,
+        bundle: &mut RenderBundleEncoder,
+        label: RawString,
     ) {
-        //TODO
+        let bytes = ffi::CStr::from_ptr(label).to_bytes();
+        bundle.base.string_data.extend_from_slice(bytes);
+
+        bundle.base.commands.push(RenderCommand::PushDebugGroup {
+            color: 0,
+            len: bytes.len(),
+        });
     }
 
     #[no_mangle]
-    pub extern "C" fn wgpu_render_bundle_pop_debug_group(_bundle: &mut RenderBundleEncoder) {
-        //TODO
+    pub extern "C" fn wgpu_render_bundle_pop_debug_group(bundle: &mut RenderBundleEncoder) {
+        bundle.base.commands.push(RenderCommand::PopDebugGroup);
     }
 
     /// # Safety
@@ -1616,9 +1905,15 @@ pub mod bundle_ffi {
     /// is a valid null-terminated string.
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_insert_debug_marker(
-        _bundle: &mut RenderBundleEncoder,
-        _label: RawString,
+        bundle: &mut RenderBundleEncoder,
+        label: RawString,
     ) {
-        //TODO
+        let bytes = ffi::CStr::from_ptr(label).to_bytes();
+        bundle.base.string_data.extend_from_slice(bytes);
+
+        bundle.base.commands.push(RenderCommand::InsertDebugMarker {
+            color: 0,
+            len: bytes.len(),
+        });
     }
 }
@@ -7,7 +7,8 @@ use crate::{
         end_pipeline_statistics_query,
         memory_init::{fixup_discarded_surfaces, SurfacesInDiscardState},
         BasePass, BasePassRef, BindGroupStateChange, CommandBuffer, CommandEncoderError,
-        CommandEncoderStatus, MapPassErr, PassErrorScope, QueryUseError, StateChange,
+        CommandEncoderStatus, IndirectKind, IndirectScratch, IndirectSpace,
+        IndirectValidationError, MapPassErr, PassErrorScope, QueryUseError, StateChange,
     },
     device::MissingDownlevelFlags,
     error::{ErrorFormatter, PrettyError},
@@ -193,6 +194,8 @@ pub enum ComputePassErrorInner {
     QueryUse(#[from] QueryUseError),
     #[error(transparent)]
     MissingDownlevelFlags(#[from] MissingDownlevelFlags),
+    #[error(transparent)]
+    IndirectValidation(#[from] IndirectValidationError),
 }
 
 impl PrettyError for ComputePassErrorInner {
@@ -375,6 +378,65 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             Some(&*query_set_guard),
         );
 
+        // Indirect arguments are validated before the pass starts. Dispatches are
+        // only collected up to the first invalid one, which fails the pass anyway.
+        let mut indirect_offsets = Vec::new();
+        if device.indirect_validation.is_some() {
+            let dispatches = base
+                .commands
+                .iter()
+                .filter_map(|command| match *command {
+                    ComputeCommand::DispatchIndirect { buffer_id, offset } => {
+                        Some((buffer_id, offset))
+                    }
+                    _ => None,
+                })
+                .take_while(|&(buffer_id, offset)| {
+                    buffer_guard.get(buffer_id).map_or(false, |buffer| {
+                        buffer.raw.is_some()
+                            && offset + IndirectKind::Dispatch.stride() <= buffer.size
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut indirect_space = IndirectSpace::default();
+            for &(buffer_id, offset) in dispatches.iter() {
+                indirect_space.add(
+                    buffer_guard.get(buffer_id).ok(),
+                    offset,
+                    1,
+                    IndirectKind::Dispatch,
+                );
+            }
+            if !indirect_space.is_empty() {
+                let mut scratch = IndirectScratch::new(&device.raw, &indirect_space)
+                    .map_err(IndirectValidationError::from)
+                    .map_pass_err(init_scope)?;
+                let limits = [cmd_buf.limits.max_compute_workgroups_per_dimension, 0];
+                let buffer_tracker = &mut cmd_buf.trackers.buffers;
+                let encode_result = dispatches
+                    .into_iter()
+                    .try_for_each(|(buffer_id, offset)| {
+                        let (_, scratch_offset) = scratch.redirect(
+                            buffer_id,
+                            offset,
+                            1,
+                            IndirectKind::Dispatch,
+                            limits,
+                        )?;
+                        indirect_offsets.push(scratch_offset);
+                        Ok(())
+                    })
+                    .and_then(|()| scratch.encode(device, raw, buffer_tracker, &*buffer_guard));
+                cmd_buf.indirect_scratch.push(scratch);
+                encode_result.map_pass_err(init_scope)?;
+            }
+        }
+        let indirect_scratch = match indirect_offsets.is_empty() {
+            true => None,
+            false => cmd_buf.indirect_scratch.last(),
+        };
+        let mut indirect_offsets = indirect_offsets.into_iter();
+
         let hal_desc = hal::ComputePassDescriptor { label: base.label };
         unsafe {
             raw.begin_compute_pass(&hal_desc);
@@ -659,6 +721,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             &*texture_guard,
                         )
                         .map_pass_err(scope)?;
+                    let (buf_raw, offset) = match indirect_scratch {
+                        // Every valid dispatch got its arguments redirected up front.
+                        Some(scratch) => (scratch.raw(), indirect_offsets.next().unwrap()),
+                        None => (buf_raw, offset),
+                    };
                     unsafe {
                         raw.dispatch_indirect(buf_raw, offset);
                     }
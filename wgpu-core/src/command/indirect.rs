@@ -0,0 +1,517 @@
+//! Validation of the contents of indirect buffers.
+//!
+//! With [`wgt::Features::INDIRECT_ARGUMENT_VALIDATION`] enabled, every pass
+//! that uses indirect commands gets an [`IndirectScratch`] buffer. The
+//! arguments of each indirect call are copied into it, and checked by an
+//! internal compute dispatch that runs before the pass. Calls that would go
+//! out of bounds get their counts zeroed, and the pass reads its arguments
+//! from the scratch buffer instead of the user's one.
+//!
+//! The scratch buffer is laid out as follows:
+//!   - a 16 byte header, holding the number of records, and the number of
+//!     records handled by each row of workgroups of the validation dispatch;
+//!   - one 16 byte record per indirect call, holding the word offset of its
+//!     arguments, the kind of call, and the two limits to check against;
+//!   - the arguments of all the calls.
+
+use std::{fmt, iter, mem, num::NonZeroU64, ptr};
+
+use crate::{
+    command::RenderCommand,
+    device::{queue::TempResource, CreateDeviceError, Device, DeviceError},
+    hub::{HalApi, Storage},
+    id,
+    resource::Buffer,
+    track::BufferTracker,
+};
+
+use hal::{CommandEncoder as _, Device as _};
+use thiserror::Error;
+
+const SHADER: &str = "
+@group(0) @binding(0)
+var<storage, read_write> words: array<u32>;
+
+fn in_bounds(first: u32, count: u32, limit: u32) -> bool {
+    return count <= limit && first <= limit - count;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.y * words[1] + global_id.x;
+    if (index >= words[0]) {
+        return;
+    }
+    let record = 4u + index * 4u;
+    let args = words[record];
+    let kind = words[record + 1u];
+    let limit_a = words[record + 2u];
+    let limit_b = words[record + 3u];
+
+    if (kind == 0u) {
+        // vertex_count, instance_count, first_vertex, first_instance
+        if (!in_bounds(words[args + 2u], words[args], limit_a)
+            || !in_bounds(words[args + 3u], words[args + 1u], limit_b)) {
+            words[args] = 0u;
+            words[args + 1u] = 0u;
+        }
+    } else if (kind == 1u) {
+        // index_count, instance_count, first_index, base_vertex, first_instance
+        if (!in_bounds(words[args + 2u], words[args], limit_a)
+            || !in_bounds(words[args + 4u], words[args + 1u], limit_b)) {
+            words[args] = 0u;
+            words[args + 1u] = 0u;
+        }
+    } else {
+        // x, y, z
+        if (words[args] > limit_a || words[args + 1u] > limit_a || words[args + 2u] > limit_a) {
+            words[args] = 0u;
+            words[args + 1u] = 0u;
+            words[args + 2u] = 0u;
+        }
+    }
+}
+";
+
+const WORKGROUP_SIZE: u32 = 64;
+const HEADER_SIZE: wgt::BufferAddress = 16;
+const RECORD_SIZE: wgt::BufferAddress = 16;
+
+/// Error encountered when validating indirect arguments.
+#[derive(Clone, Debug, Error)]
+pub enum IndirectValidationError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("indirect buffer {0:?} is invalid or destroyed")]
+    DestroyedBuffer(id::BufferId),
+    #[error("indirect calls exceed the scratch space reserved for their validation")]
+    ScratchOverrun,
+}
+
+/// The kind of an indirect call, as understood by the validation shader.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum IndirectKind {
+    /// Checked against the vertex and instance limits.
+    Draw = 0,
+    /// Checked against the index and instance limits.
+    DrawIndexed = 1,
+    /// Checked against `max_compute_workgroups_per_dimension`.
+    Dispatch = 2,
+}
+
+impl IndirectKind {
+    pub(crate) fn draw(indexed: bool) -> Self {
+        match indexed {
+            false => Self::Draw,
+            true => Self::DrawIndexed,
+        }
+    }
+
+    pub(crate) fn stride(self) -> wgt::BufferAddress {
+        (match self {
+            Self::Draw => mem::size_of::<wgt::DrawIndirectArgs>(),
+            Self::DrawIndexed => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
+            Self::Dispatch => mem::size_of::<wgt::DispatchIndirectArgs>(),
+        }) as wgt::BufferAddress
+    }
+}
+
+/// Device-wide objects used to validate indirect arguments.
+#[derive(Debug)]
+pub(crate) struct IndirectValidation<A: hal::Api> {
+    module: A::ShaderModule,
+    bind_group_layout: A::BindGroupLayout,
+    pipeline_layout: A::PipelineLayout,
+    pipeline: A::ComputePipeline,
+}
+
+fn failed(error: impl fmt::Display) -> CreateDeviceError {
+    CreateDeviceError::FailedToCreateIndirectValidation(error.to_string())
+}
+
+impl<A: hal::Api> IndirectValidation<A> {
+    pub(crate) fn new(device: &A::Device) -> Result<Self, CreateDeviceError> {
+        let module = naga::front::wgsl::parse_str(SHADER).map_err(|err| {
+            CreateDeviceError::FailedToCreateIndirectValidation(err.emit_to_string(SHADER))
+        })?;
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .map_err(failed)?;
+
+        let hal_desc = hal::ShaderModuleDescriptor {
+            label: Some("(wgpu internal) Indirect validation"),
+            runtime_checks: true,
+        };
+        let hal_shader = hal::ShaderInput::Naga(hal::NagaShader { module, info });
+        let module =
+            unsafe { device.create_shader_module(&hal_desc, hal_shader) }.map_err(failed)?;
+
+        let entries = [wgt::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgt::ShaderStages::COMPUTE,
+            ty: wgt::BindingType::Buffer {
+                ty: wgt::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }];
+        let bind_group_layout = unsafe {
+            device.create_bind_group_layout(&hal::BindGroupLayoutDescriptor {
+                label: Some("(wgpu internal) Indirect validation"),
+                flags: hal::BindGroupLayoutFlags::empty(),
+                entries: &entries,
+            })
+        }
+        .map_err(failed)?;
+        let pipeline_layout = unsafe {
+            device.create_pipeline_layout(&hal::PipelineLayoutDescriptor {
+                label: Some("(wgpu internal) Indirect validation"),
+                flags: hal::PipelineLayoutFlags::empty(),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            })
+        }
+        .map_err(failed)?;
+        let pipeline = unsafe {
+            device.create_compute_pipeline(&hal::ComputePipelineDescriptor {
+                label: Some("(wgpu internal) Indirect validation"),
+                layout: &pipeline_layout,
+                stage: hal::ProgrammableStage {
+                    module: &module,
+                    entry_point: "main",
+                    constants: &Default::default(),
+                },
+                cache: None,
+            })
+        }
+        .map_err(failed)?;
+
+        Ok(Self {
+            module,
+            bind_group_layout,
+            pipeline_layout,
+            pipeline,
+        })
+    }
+
+    pub(crate) fn dispose(self, device: &A::Device) {
+        unsafe {
+            device.destroy_compute_pipeline(self.pipeline);
+            device.destroy_pipeline_layout(self.pipeline_layout);
+            device.destroy_bind_group_layout(self.bind_group_layout);
+            device.destroy_shader_module(self.module);
+        }
+    }
+}
+
+/// Amount of scratch space required by the indirect calls of a pass.
+#[derive(Debug, Default)]
+pub(crate) struct IndirectSpace {
+    records: wgt::BufferAddress,
+    arguments: wgt::BufferAddress,
+}
+
+impl IndirectSpace {
+    /// Reserves space for `count` calls of the given kind, reading from `buffer` at `offset`.
+    ///
+    /// Calls that don't fit in the buffer will fail validation of the pass,
+    /// so they are clamped here to keep the scratch buffer size bounded.
+    pub(crate) fn add<A: hal::Api>(
+        &mut self,
+        buffer: Option<&Buffer<A>>,
+        offset: wgt::BufferAddress,
+        count: u32,
+        kind: IndirectKind,
+    ) {
+        let available = buffer.map_or(0, |buffer| buffer.size.saturating_sub(offset));
+        let count = (count as wgt::BufferAddress).min(available / kind.stride());
+        self.records += count;
+        self.arguments += count * kind.stride();
+    }
+
+    /// Reserves space for the indirect draws among render `commands`.
+    ///
+    /// The draws of executed bundles are not included.
+    pub(crate) fn add_render_commands<A: hal::Api>(
+        &mut self,
+        commands: &[RenderCommand],
+        buffer_guard: &Storage<Buffer<A>, id::BufferId>,
+    ) {
+        for command in commands {
+            match *command {
+                RenderCommand::MultiDrawIndirect {
+                    buffer_id,
+                    offset,
+                    count,
+                    indexed,
+                } => self.add(
+                    buffer_guard.get(buffer_id).ok(),
+                    offset,
+                    count.map_or(1, |c| c.get()),
+                    IndirectKind::draw(indexed),
+                ),
+                RenderCommand::MultiDrawIndirectCount {
+                    buffer_id,
+                    offset,
+                    max_count,
+                    indexed,
+                    ..
+                } => self.add(
+                    buffer_guard.get(buffer_id).ok(),
+                    offset,
+                    max_count,
+                    IndirectKind::draw(indexed),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+}
+
+/// Scratch buffer holding the validated arguments of the indirect calls of a pass.
+#[derive(Debug)]
+pub(crate) struct IndirectScratch<A: hal::Api> {
+    buffer: A::Buffer,
+    /// Offset of the next free byte for arguments.
+    cursor: wgt::BufferAddress,
+    size: wgt::BufferAddress,
+    /// Header and records, uploaded at the start of the buffer.
+    words: Vec<u32>,
+    copies: Vec<(id::BufferId, hal::BufferCopy)>,
+    staging: Option<A::Buffer>,
+    bind_group: Option<A::BindGroup>,
+}
+
+impl<A: HalApi> IndirectScratch<A> {
+    pub(crate) fn new(device: &A::Device, space: &IndirectSpace) -> Result<Self, DeviceError> {
+        let arguments_offset = HEADER_SIZE + space.records * RECORD_SIZE;
+        let size = arguments_offset + space.arguments;
+        let buffer = unsafe {
+            device.create_buffer(&hal::BufferDescriptor {
+                label: Some("(wgpu internal) Indirect scratch"),
+                size,
+                usage: hal::BufferUses::COPY_DST
+                    | hal::BufferUses::STORAGE_READ_WRITE
+                    | hal::BufferUses::INDIRECT,
+                memory_flags: hal::MemoryFlags::TRANSIENT,
+            })
+        }?;
+        let mut words = Vec::with_capacity((arguments_offset / 4) as usize);
+        words.extend_from_slice(&[0; (HEADER_SIZE / 4) as usize]);
+        Ok(Self {
+            buffer,
+            cursor: arguments_offset,
+            size,
+            words,
+            copies: Vec::new(),
+            staging: None,
+            bind_group: None,
+        })
+    }
+
+    pub(crate) fn raw(&self) -> &A::Buffer {
+        &self.buffer
+    }
+
+    /// Redirects `count` consecutive indirect calls, read from `source` at `offset`,
+    /// into the scratch buffer.
+    ///
+    /// `limits` are the bounds checked by the validation shader, see [`IndirectKind`].
+    /// Returns the buffer and offset to read the arguments from.
+    pub(crate) fn redirect(
+        &mut self,
+        source: id::BufferId,
+        offset: wgt::BufferAddress,
+        count: u32,
+        kind: IndirectKind,
+        limits: [u32; 2],
+    ) -> Result<(&A::Buffer, wgt::BufferAddress), IndirectValidationError> {
+        let stride = kind.stride();
+        let start = self.cursor;
+        let end = start + count as wgt::BufferAddress * stride;
+        if end > self.size {
+            return Err(IndirectValidationError::ScratchOverrun);
+        }
+        self.cursor = end;
+
+        if let Some(size) = NonZeroU64::new(self.cursor - start) {
+            self.copies.push((
+                source,
+                hal::BufferCopy {
+                    src_offset: offset,
+                    dst_offset: start,
+                    size,
+                },
+            ));
+        }
+        for i in 0..count as wgt::BufferAddress {
+            let word = (start + i * stride) / 4;
+            self.words
+                .extend_from_slice(&[word as u32, kind as u32, limits[0], limits[1]]);
+        }
+        Ok((&self.buffer, start))
+    }
+
+    /// Records the copies and the validation dispatch into `encoder`,
+    /// which has to be executed before the pass.
+    pub(crate) fn encode(
+        &mut self,
+        device: &Device<A>,
+        encoder: &mut A::CommandEncoder,
+        trackers: &mut BufferTracker<A>,
+        buffer_guard: &Storage<Buffer<A>, id::BufferId>,
+    ) -> Result<(), IndirectValidationError> {
+        let validation = device
+            .indirect_validation
+            .as_ref()
+            .expect("Indirect validation is not enabled");
+        let record_count = (self.words.len() as u32 - HEADER_SIZE as u32 / 4) / 4;
+        if record_count == 0 {
+            return Ok(());
+        }
+
+        let max_groups = device.limits.max_compute_workgroups_per_dimension.max(1);
+        let group_count = (record_count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+        let groups_x = group_count.min(max_groups);
+        let groups_y = (group_count + groups_x - 1) / groups_x;
+        self.words[0] = record_count;
+        self.words[1] = groups_x * WORKGROUP_SIZE;
+
+        let data_size = (self.words.len() * 4) as wgt::BufferAddress;
+        let staging = unsafe {
+            device.raw.create_buffer(&hal::BufferDescriptor {
+                label: Some("(wgpu internal) Indirect staging"),
+                size: data_size,
+                usage: hal::BufferUses::MAP_WRITE | hal::BufferUses::COPY_SRC,
+                memory_flags: hal::MemoryFlags::TRANSIENT,
+            })
+        }
+        .map_err(DeviceError::from)?;
+        let staging = self.staging.insert(staging);
+        unsafe {
+            let mapping = device
+                .raw
+                .map_buffer(staging, 0..data_size)
+                .map_err(DeviceError::from)?;
+            ptr::copy_nonoverlapping(
+                self.words.as_ptr() as *const u8,
+                mapping.ptr.as_ptr(),
+                data_size as usize,
+            );
+            if !mapping.is_coherent {
+                device
+                    .raw
+                    .flush_mapped_ranges(staging, iter::once(0..data_size));
+            }
+            device
+                .raw
+                .unmap_buffer(staging)
+                .map_err(DeviceError::from)?;
+        }
+
+        let buffers = [hal::BufferBinding {
+            buffer: &self.buffer,
+            offset: 0,
+            size: None,
+        }];
+        let entries = [hal::BindGroupEntry {
+            binding: 0,
+            resource_index: 0,
+            count: 1,
+        }];
+        let bind_group = unsafe {
+            device.raw.create_bind_group(&hal::BindGroupDescriptor {
+                label: Some("(wgpu internal) Indirect validation"),
+                layout: &validation.bind_group_layout,
+                buffers: &buffers,
+                samplers: &[],
+                textures: &[],
+                entries: &entries,
+            })
+        }
+        .map_err(DeviceError::from)?;
+        let bind_group = self.bind_group.insert(bind_group);
+
+        let mut source_barriers = Vec::new();
+        for &(source, _) in self.copies.iter() {
+            let (buffer, pending) = trackers
+                .set_single(buffer_guard, source, hal::BufferUses::COPY_SRC)
+                .ok_or(IndirectValidationError::DestroyedBuffer(source))?;
+            if buffer.raw.is_none() {
+                return Err(IndirectValidationError::DestroyedBuffer(source));
+            }
+            source_barriers.extend(pending.map(|pending| pending.into_hal(buffer)));
+        }
+
+        unsafe {
+            encoder.transition_buffers(source_barriers.into_iter().chain([
+                hal::BufferBarrier {
+                    buffer: &*staging,
+                    usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
+                },
+                hal::BufferBarrier {
+                    buffer: &self.buffer,
+                    usage: hal::BufferUses::empty()..hal::BufferUses::COPY_DST,
+                },
+            ]));
+            encoder.copy_buffer_to_buffer(
+                staging,
+                &self.buffer,
+                iter::once(hal::BufferCopy {
+                    src_offset: 0,
+                    dst_offset: 0,
+                    size: NonZeroU64::new(data_size).unwrap(),
+                }),
+            );
+            for &(source, region) in self.copies.iter() {
+                let raw = buffer_guard[id::Valid(source)].raw.as_ref().unwrap();
+                encoder.copy_buffer_to_buffer(raw, &self.buffer, iter::once(region));
+            }
+            encoder.transition_buffers(iter::once(hal::BufferBarrier {
+                buffer: &self.buffer,
+                usage: hal::BufferUses::COPY_DST..hal::BufferUses::STORAGE_READ_WRITE,
+            }));
+
+            encoder.begin_compute_pass(&hal::ComputePassDescriptor {
+                label: Some("(wgpu internal) Indirect validation"),
+            });
+            encoder.set_compute_pipeline(&validation.pipeline);
+            encoder.set_bind_group(&validation.pipeline_layout, 0, bind_group, &[]);
+            encoder.dispatch([groups_x, groups_y, 1]);
+            encoder.end_compute_pass();
+
+            encoder.transition_buffers(iter::once(hal::BufferBarrier {
+                buffer: &self.buffer,
+                usage: hal::BufferUses::STORAGE_READ_WRITE..hal::BufferUses::INDIRECT,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Returns the resources to be freed once the command buffer is done executing.
+    pub(crate) fn into_temp_resources(self) -> impl Iterator<Item = TempResource<A>> {
+        iter::once(TempResource::Buffer(self.buffer))
+            .chain(self.staging.map(TempResource::Buffer))
+            .chain(self.bind_group.map(TempResource::BindGroup))
+    }
+
+    pub(crate) fn dispose(self, device: &A::Device) {
+        unsafe {
+            if let Some(bind_group) = self.bind_group {
+                device.destroy_bind_group(bind_group);
+            }
+            if let Some(staging) = self.staging {
+                device.destroy_buffer(staging);
+            }
+            device.destroy_buffer(self.buffer);
+        }
+    }
+}
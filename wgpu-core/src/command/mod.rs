@@ -3,6 +3,7 @@ mod bundle;
 mod clear;
 mod compute;
 mod draw;
+mod indirect;
 mod memory_init;
 mod query;
 mod render;
@@ -11,8 +12,10 @@ mod transfer;
 use std::slice;
 
 pub(crate) use self::clear::clear_texture;
+pub(crate) use self::indirect::{IndirectKind, IndirectScratch, IndirectSpace, IndirectValidation};
 pub use self::{
-    bundle::*, clear::ClearError, compute::*, draw::*, query::*, render::*, transfer::*,
+    bundle::*, clear::ClearError, compute::*, draw::*, indirect::IndirectValidationError, query::*,
+    render::*, transfer::*,
 };
 
 use self::memory_init::CommandBufferTextureMemoryActions;
@@ -87,6 +90,7 @@ pub struct BakedCommands<A: HalApi> {
     pub(crate) trackers: Tracker<A>,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     texture_memory_actions: CommandBufferTextureMemoryActions,
+    pub(crate) indirect_scratch: Vec<IndirectScratch<A>>,
 }
 
 pub(crate) struct DestroyedBufferError(pub id::BufferId);
@@ -99,6 +103,7 @@ pub struct CommandBuffer<A: HalApi> {
     pub(crate) trackers: Tracker<A>,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     texture_memory_actions: CommandBufferTextureMemoryActions,
+    indirect_scratch: Vec<IndirectScratch<A>>,
     limits: wgt::Limits,
     support_clear_texture: bool,
     #[cfg(feature = "trace")]
@@ -127,6 +132,7 @@ impl<A: HalApi> CommandBuffer<A> {
             trackers: Tracker::new(),
             buffer_memory_init_actions: Default::default(),
             texture_memory_actions: Default::default(),
+            indirect_scratch: Vec::new(),
             limits,
             support_clear_texture: features.contains(wgt::Features::CLEAR_TEXTURE),
             #[cfg(feature = "trace")]
@@ -223,6 +229,7 @@ impl<A: HalApi> CommandBuffer<A> {
             trackers: self.trackers,
             buffer_memory_init_actions: self.buffer_memory_init_actions,
             texture_memory_actions: self.texture_memory_actions,
+            indirect_scratch: self.indirect_scratch,
         }
     }
 }
@@ -577,6 +584,10 @@ pub enum PassErrorScope {
     BeginPipelineStatisticsQuery,
     #[error("In a end_pipeline_statistics_query command")]
     EndPipelineStatisticsQuery,
+    #[error("In a begin_occlusion_query command")]
+    BeginOcclusionQuery,
+    #[error("In a end_occlusion_query command")]
+    EndOcclusionQuery,
     #[error("In a execute_bundle command")]
     ExecuteBundle,
     #[error("In a dispatch command, indirect:{indirect}")]
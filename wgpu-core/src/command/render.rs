@@ -3,11 +3,12 @@ use crate::{
     command::{
         self,
         bind::Binder,
-        end_pipeline_statistics_query,
+        end_occlusion_query, end_pipeline_statistics_query,
         memory_init::{fixup_discarded_surfaces, SurfacesInDiscardState},
         BasePass, BasePassRef, BindGroupStateChange, CommandBuffer, CommandEncoderError,
-        CommandEncoderStatus, DrawError, ExecutionError, MapPassErr, PassErrorScope, QueryResetMap,
-        QueryUseError, RenderCommand, RenderCommandError, StateChange,
+        CommandEncoderStatus, DrawError, ExecutionError, IndirectKind, IndirectScratch,
+        IndirectSpace, IndirectValidationError, MapPassErr, PassErrorScope, QueryResetMap,
+        QueryUseError, RenderCommand, RenderCommandError, SimplifiedQueryType, StateChange,
     },
     device::{
         AttachmentData, Device, MissingDownlevelFlags, MissingFeatures,
@@ -176,6 +177,8 @@ pub struct RenderPassDescriptor<'a> {
     pub color_attachments: Cow<'a, [RenderPassColorAttachment]>,
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<&'a RenderPassDepthStencilAttachment>,
+    /// The query set to use for occlusion queries within the render pass, if any.
+    pub occlusion_query_set: Option<id::QuerySetId>,
 }
 
 #[cfg_attr(feature = "serial-pass", derive(Deserialize, Serialize))]
@@ -184,6 +187,7 @@ pub struct RenderPass {
     parent_id: id::CommandEncoderId,
     color_targets: ArrayVec<RenderPassColorAttachment, { hal::MAX_COLOR_ATTACHMENTS }>,
     depth_stencil_target: Option<RenderPassDepthStencilAttachment>,
+    occlusion_query_set_id: Option<id::QuerySetId>,
 
     // Resource binding dedupe state.
     #[cfg_attr(feature = "serial-pass", serde(skip))]
@@ -199,6 +203,7 @@ impl RenderPass {
             parent_id,
             color_targets: desc.color_attachments.iter().cloned().collect(),
             depth_stencil_target: desc.depth_stencil_attachment.cloned(),
+            occlusion_query_set_id: desc.occlusion_query_set,
 
             current_bind_groups: BindGroupStateChange::new(),
             current_pipeline: StateChange::new(),
@@ -215,6 +220,7 @@ impl RenderPass {
             base: self.base,
             target_colors: self.color_targets.into_iter().collect(),
             target_depth_stencil: self.depth_stencil_target,
+            occlusion_query_set_id: self.occlusion_query_set_id,
         }
     }
 
@@ -238,10 +244,11 @@ impl fmt::Debug for RenderPass {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "RenderPass {{ encoder_id: {:?}, color_targets: {:?}, depth_stencil_target: {:?}, data: {:?} commands, {:?} dynamic offsets, and {:?} push constant u32s }}",
+            "RenderPass {{ encoder_id: {:?}, color_targets: {:?}, depth_stencil_target: {:?}, occlusion_query_set: {:?}, data: {:?} commands, {:?} dynamic offsets, and {:?} push constant u32s }}",
             self.parent_id,
             self.color_targets,
             self.depth_stencil_target,
+            self.occlusion_query_set_id,
             self.base.commands.len(),
             self.base.dynamic_offsets.len(),
             self.base.push_constant_data.len(),
@@ -374,6 +381,15 @@ struct State {
 }
 
 impl State {
+    /// Limits checked by indirect argument validation, see [`IndirectKind`].
+    fn indirect_limits(&self, indexed: bool) -> [u32; 2] {
+        let first = match indexed {
+            false => self.vertex.vertex_limit,
+            true => self.index.limit,
+        };
+        [first, self.vertex.instance_limit]
+    }
+
     fn is_ready(&self, indexed: bool) -> Result<(), DrawError> {
         // Determine how many vertex buffers have already been bound
         let vertex_buffer_count = self.vertex.inputs.iter().take_while(|v| v.bound).count() as u32;
@@ -491,6 +507,12 @@ pub enum RenderPassErrorInner {
     },
     #[error("cannot pop debug group, because number of pushed debug groups is zero")]
     InvalidPopDebugGroup,
+    #[error(
+        "occlusion query was used without an occlusion query set in the render pass descriptor"
+    )]
+    MissingOcclusionQuerySet,
+    #[error("occlusion query {query_index} was not ended before the end of the render pass")]
+    UnendedOcclusionQuery { query_index: u32 },
     #[error(transparent)]
     ResourceUsageConflict(#[from] UsageConflict),
     #[error("render bundle has incompatible targets, {0}")]
@@ -515,6 +537,8 @@ pub enum RenderPassErrorInner {
     Bind(#[from] BindError),
     #[error(transparent)]
     QueryUse(#[from] QueryUseError),
+    #[error(transparent)]
+    IndirectValidation(#[from] IndirectValidationError),
     #[error("multiview layer count must match")]
     MultiViewMismatch,
     #[error(
@@ -1068,6 +1092,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             pass.base.as_ref(),
             &pass.color_targets,
             pass.depth_stencil_target.as_ref(),
+            pass.occlusion_query_set_id,
         )
     }
 
@@ -1078,6 +1103,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         base: BasePassRef<RenderCommand>,
         color_attachments: &[RenderPassColorAttachment],
         depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
+        occlusion_query_set_id: Option<id::QuerySetId>,
     ) -> Result<(), RenderPassError> {
         profiling::scope!("run_render_pass", "CommandEncoder");
         let init_scope = PassErrorScope::Pass(encoder_id);
@@ -1086,7 +1112,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut token = Token::root();
         let (device_guard, mut token) = hub.devices.read(&mut token);
 
-        let (scope, query_reset_state, pending_discard_init_fixups) = {
+        let (scope, query_reset_state, pending_discard_init_fixups, uses_indirect_scratch) = {
             let (mut cmb_guard, mut token) = hub.command_buffers.write(&mut token);
 
             // Spell out the type, to placate rust-analyzer.
@@ -1105,6 +1131,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     base: BasePass::from_ref(base),
                     target_colors: color_attachments.to_vec(),
                     target_depth_stencil: depth_stencil_attachment.cloned(),
+                    occlusion_query_set_id,
                 });
             }
 
@@ -1149,6 +1176,48 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Some(&*query_set_guard),
             );
 
+            let occlusion_query_set = match occlusion_query_set_id {
+                Some(query_set_id) => {
+                    let query_set: &resource::QuerySet<A> = cmd_buf
+                        .trackers
+                        .query_sets
+                        .add_single(&*query_set_guard, query_set_id)
+                        .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
+                        .map_pass_err(init_scope)?;
+                    let set_type = SimplifiedQueryType::from(query_set.desc.ty);
+                    if set_type != SimplifiedQueryType::Occlusion {
+                        return Err(QueryUseError::IncompatibleType {
+                            set_type,
+                            query_type: SimplifiedQueryType::Occlusion,
+                        })
+                        .map_pass_err(init_scope);
+                    }
+                    Some((query_set_id, query_set))
+                }
+                None => None,
+            };
+
+            let mut indirect_space = IndirectSpace::default();
+            if device.indirect_validation.is_some() {
+                indirect_space.add_render_commands(base.commands, &*buffer_guard);
+                for command in base.commands {
+                    if let RenderCommand::ExecuteBundle(bundle_id) = *command {
+                        if let Ok(bundle) = bundle_guard.get(bundle_id) {
+                            indirect_space.add_render_commands(bundle.commands(), &*buffer_guard);
+                        }
+                    }
+                }
+            }
+            let mut indirect_scratch = if indirect_space.is_empty() {
+                None
+            } else {
+                let scratch = IndirectScratch::new(&device.raw, &indirect_space)
+                    .map_err(IndirectValidationError::from)
+                    .map_pass_err(init_scope)?;
+                cmd_buf.indirect_scratch.push(scratch);
+                cmd_buf.indirect_scratch.last_mut()
+            };
+
             let raw = &mut cmd_buf.encoder.raw;
 
             let mut state = State {
@@ -1165,6 +1234,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let mut dynamic_offset_count = 0;
             let mut string_offset = 0;
             let mut active_query = None;
+            let mut active_occlusion_query = None;
             let mut query_reset_state = QueryResetMap::new();
 
             for command in base.commands {
@@ -1710,6 +1780,19 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             ),
                         );
 
+                        let (indirect_raw, offset) = match indirect_scratch {
+                            Some(ref mut scratch) => scratch
+                                .redirect(
+                                    buffer_id,
+                                    offset,
+                                    actual_count,
+                                    IndirectKind::draw(indexed),
+                                    state.indirect_limits(indexed),
+                                )
+                                .map_pass_err(scope)?,
+                            None => (indirect_raw, offset),
+                        };
+
                         match indexed {
                             false => unsafe {
                                 raw.draw_indirect(indirect_raw, offset, actual_count);
@@ -1812,6 +1895,19 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             ),
                         );
 
+                        let (indirect_raw, offset) = match indirect_scratch {
+                            Some(ref mut scratch) => scratch
+                                .redirect(
+                                    buffer_id,
+                                    offset,
+                                    max_count,
+                                    IndirectKind::draw(indexed),
+                                    state.indirect_limits(indexed),
+                                )
+                                .map_pass_err(scope)?,
+                            None => (indirect_raw, offset),
+                        };
+
                         match indexed {
                             false => unsafe {
                                 raw.draw_indirect_count(
@@ -1914,6 +2010,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         end_pipeline_statistics_query(raw, &*query_set_guard, &mut active_query)
                             .map_pass_err(scope)?;
                     }
+                    RenderCommand::BeginOcclusionQuery { query_index } => {
+                        let scope = PassErrorScope::BeginOcclusionQuery;
+
+                        let (query_set_id, query_set) = occlusion_query_set
+                            .ok_or(RenderPassErrorInner::MissingOcclusionQuerySet)
+                            .map_pass_err(scope)?;
+
+                        query_set
+                            .validate_and_begin_occlusion_query(
+                                raw,
+                                query_set_id,
+                                query_index,
+                                Some(&mut query_reset_state),
+                                &mut active_occlusion_query,
+                            )
+                            .map_pass_err(scope)?;
+                    }
+                    RenderCommand::EndOcclusionQuery => {
+                        let scope = PassErrorScope::EndOcclusionQuery;
+
+                        end_occlusion_query(raw, &*query_set_guard, &mut active_occlusion_query)
+                            .map_pass_err(scope)?;
+                    }
                     RenderCommand::ExecuteBundle(bundle_id) => {
                         let scope = PassErrorScope::ExecuteBundle;
                         let bundle: &command::RenderBundle<A> = cmd_buf
@@ -1964,14 +2083,22 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 &*bind_group_guard,
                                 &*render_pipeline_guard,
                                 &*buffer_guard,
+                                indirect_scratch.as_deref_mut(),
                             )
                         }
                         .map_err(|e| match e {
                             ExecutionError::DestroyedBuffer(id) => {
-                                RenderCommandError::DestroyedBuffer(id)
+                                RenderPassErrorInner::RenderCommand(
+                                    RenderCommandError::DestroyedBuffer(id),
+                                )
                             }
                             ExecutionError::Unimplemented(what) => {
-                                RenderCommandError::Unimplemented(what)
+                                RenderPassErrorInner::RenderCommand(
+                                    RenderCommandError::Unimplemented(what),
+                                )
+                            }
+                            ExecutionError::IndirectValidation(e) => {
+                                RenderPassErrorInner::IndirectValidation(e)
                             }
                         })
                         .map_pass_err(scope)?;
@@ -1990,12 +2117,22 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
             }
 
+            if let Some((_, query_index)) = active_occlusion_query {
+                return Err(RenderPassErrorInner::UnendedOcclusionQuery { query_index })
+                    .map_pass_err(init_scope);
+            }
+
             log::trace!("Merging renderpass into cmd_buf {:?}", encoder_id);
             let (trackers, pending_discard_init_fixups) =
                 info.finish(raw, &*texture_guard).map_pass_err(init_scope)?;
 
             cmd_buf.encoder.close();
-            (trackers, query_reset_state, pending_discard_init_fixups)
+            (
+                trackers,
+                query_reset_state,
+                pending_discard_init_fixups,
+                indirect_scratch.is_some(),
+            )
         };
 
         let (mut cmb_guard, mut token) = hub.command_buffers.write(&mut token);
@@ -2024,6 +2161,20 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .map_err(RenderCommandError::InvalidQuerySet)
                 .map_pass_err(PassErrorScope::QueryReset)?;
 
+            if uses_indirect_scratch {
+                cmd_buf
+                    .indirect_scratch
+                    .last_mut()
+                    .unwrap()
+                    .encode(
+                        &device_guard[cmd_buf.device_id.value],
+                        transit,
+                        &mut cmd_buf.trackers.buffers,
+                        &*buffer_guard,
+                    )
+                    .map_pass_err(init_scope)?;
+            }
+
             super::CommandBuffer::insert_barriers_from_scope(
                 transit,
                 &mut cmd_buf.trackers,
@@ -2423,6 +2574,21 @@ pub mod render_ffi {
             .push(RenderCommand::EndPipelineStatisticsQuery);
     }
 
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_begin_occlusion_query(
+        pass: &mut RenderPass,
+        query_index: u32,
+    ) {
+        pass.base
+            .commands
+            .push(RenderCommand::BeginOcclusionQuery { query_index });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_end_occlusion_query(pass: &mut RenderPass) {
+        pass.base.commands.push(RenderCommand::EndOcclusionQuery);
+    }
+
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
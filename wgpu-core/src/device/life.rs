@@ -336,6 +336,7 @@ impl<A: hal::Api> LifetimeTracker<A> {
                     last_resources.textures.push(raw);
                     last_resources.texture_views.extend(views);
                 }
+                TempResource::BindGroup(raw) => last_resources.bind_groups.push(raw),
             }
         }
 
@@ -439,6 +440,7 @@ impl<A: hal::Api> LifetimeTracker<A> {
                 resources.texture_views.extend(views);
                 resources.textures.push(raw);
             }
+            TempResource::BindGroup(raw) => resources.bind_groups.push(raw),
         }
     }
 
@@ -829,6 +831,42 @@ impl<A: HalApi> LifetimeTracker<A> {
         }
     }
 
+    /// Fail all the pending mappings, because the device is lost.
+    ///
+    /// The buffers stay in the mapping lists, and are skipped by
+    /// [`handle_mapping`] since they are no longer waiting.
+    ///
+    /// [`handle_mapping`]: LifetimeTracker::handle_mapping
+    #[must_use]
+    pub(super) fn fail_mappings<G: GlobalIdentityHandlerFactory>(
+        &mut self,
+        hub: &Hub<A, G>,
+        token: &mut Token<super::Device<A>>,
+    ) -> Vec<super::BufferMapPendingClosure> {
+        let (mut buffer_guard, _) = hub.buffers.write(token);
+        let buffer_ids = self
+            .mapped
+            .iter()
+            .map(|stored| stored.value)
+            .chain(self.ready_to_map.iter().cloned())
+            .chain(self.active.iter().flat_map(|a| a.mapped.iter().cloned()));
+
+        let mut pending_callbacks = Vec::new();
+        for buffer_id in buffer_ids {
+            let buffer = &mut buffer_guard[buffer_id];
+            match std::mem::replace(&mut buffer.map_state, resource::BufferMapState::Idle) {
+                resource::BufferMapState::Waiting(pending_mapping) => {
+                    pending_callbacks.push((
+                        pending_mapping.op,
+                        resource::BufferMapAsyncStatus::ContextLost,
+                    ));
+                }
+                other => buffer.map_state = other,
+            }
+        }
+        pending_callbacks
+    }
+
     /// Map the buffers in `self.ready_to_map`.
     ///
     /// Return a list of mapping notifications to send.
@@ -7,7 +7,7 @@ use crate::{
         BufferInitTracker, BufferInitTrackerAction, MemoryInitKind, TextureInitRange,
         TextureInitTracker, TextureInitTrackerAction,
     },
-    instance, pipeline, present, resource,
+    instance, pipeline, pipeline_cache, present, resource,
     track::{BindGroupStates, TextureSelector, Tracker},
     validation::{self, check_buffer_usage, check_texture_usage},
     FastHashMap, Label, LabelHelpers as _, LifeGuard, MultiRefCount, RefCount, Stored,
@@ -22,7 +22,16 @@ use smallvec::SmallVec;
 use thiserror::Error;
 use wgt::{BufferAddress, TextureFormat, TextureViewDimension};
 
-use std::{borrow::Cow, iter, mem, num::NonZeroU32, ops::Range, ptr};
+use std::{
+    borrow::Cow,
+    ffi::CString,
+    iter, mem,
+    num::NonZeroU32,
+    ops::Range,
+    os::raw::c_char,
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 mod life;
 pub mod queue;
@@ -129,16 +138,84 @@ impl RenderPassContext {
 
 pub type BufferMapPendingClosure = (resource::BufferMapOperation, resource::BufferMapAsyncStatus);
 
+#[repr(C)]
+pub struct DeviceLostClosureC {
+    callback: unsafe extern "C" fn(
+        user_data: *mut u8,
+        reason: wgt::DeviceLostReason,
+        message: *const c_char,
+    ),
+    user_data: *mut u8,
+}
+
+unsafe impl Send for DeviceLostClosureC {}
+
+pub struct DeviceLostClosure {
+    // We wrap this so creating the enum in the C variant can be unsafe,
+    // allowing our call function to be safe.
+    inner: DeviceLostClosureInner,
+}
+
+enum DeviceLostClosureInner {
+    Rust {
+        callback: Box<dyn FnOnce(wgt::DeviceLostReason, String) + Send + 'static>,
+    },
+    C {
+        inner: DeviceLostClosureC,
+    },
+}
+
+impl DeviceLostClosure {
+    pub fn from_rust(
+        callback: Box<dyn FnOnce(wgt::DeviceLostReason, String) + Send + 'static>,
+    ) -> Self {
+        Self {
+            inner: DeviceLostClosureInner::Rust { callback },
+        }
+    }
+
+    /// # Safety
+    ///
+    /// - The callback pointer must be valid to call with the provided user_data pointer.
+    /// - Both pointers must point to 'static data as the callback may happen at an unspecified time.
+    /// - The message pointer is only valid for the duration of the call.
+    pub unsafe fn from_c(inner: DeviceLostClosureC) -> Self {
+        Self {
+            inner: DeviceLostClosureInner::C { inner },
+        }
+    }
+
+    pub(crate) fn call(self, reason: wgt::DeviceLostReason, message: String) {
+        match self.inner {
+            DeviceLostClosureInner::Rust { callback } => callback(reason, message),
+            // SAFETY: the contract of the call to from_c says that this unsafe is sound.
+            DeviceLostClosureInner::C { inner } => unsafe {
+                let message = CString::new(message).unwrap_or_default();
+                (inner.callback)(inner.user_data, reason, message.as_ptr())
+            },
+        }
+    }
+}
+
+pub struct DeviceLostInvocation {
+    closure: DeviceLostClosure,
+    reason: wgt::DeviceLostReason,
+    message: String,
+}
+
 #[derive(Default)]
 pub struct UserClosures {
     pub mappings: Vec<BufferMapPendingClosure>,
     pub submissions: SmallVec<[queue::SubmittedWorkDoneClosure; 1]>,
+    pub device_lost_invocations: SmallVec<[DeviceLostInvocation; 1]>,
 }
 
 impl UserClosures {
     fn extend(&mut self, other: Self) {
         self.mappings.extend(other.mappings);
         self.submissions.extend(other.submissions);
+        self.device_lost_invocations
+            .extend(other.device_lost_invocations);
     }
 
     fn fire(self) {
@@ -150,6 +227,11 @@ impl UserClosures {
         for closure in self.submissions {
             closure.call();
         }
+        for invocation in self.device_lost_invocations {
+            invocation
+                .closure
+                .call(invocation.reason, invocation.message);
+        }
     }
 }
 
@@ -261,6 +343,14 @@ pub struct Device<A: HalApi> {
     pub(crate) adapter_id: Stored<id::AdapterId>,
     pub(crate) queue: A::Queue,
     pub(crate) zero_buffer: A::Buffer,
+    /// Pipeline validating indirect arguments, if [`wgt::Features::INDIRECT_ARGUMENT_VALIDATION`]
+    /// is enabled.
+    pub(crate) indirect_validation: Option<command::IndirectValidation<A>>,
+    /// Cleared once the device is lost or destroyed, after which any new work
+    /// is rejected with [`DeviceError::Lost`].
+    valid: AtomicBool,
+    /// Closure to call once the device is lost or destroyed.
+    device_lost_closure: Mutex<Option<DeviceLostClosure>>,
     //pub(crate) cmd_allocator: command::CommandAllocator<A>,
     //mem_allocator: Mutex<alloc::MemoryAllocator<A>>,
     //desc_allocator: Mutex<descriptor::DescriptorAllocator<A>>,
@@ -304,6 +394,8 @@ pub enum CreateDeviceError {
     OutOfMemory,
     #[error("failed to create internal buffer for initializing textures")]
     FailedToCreateZeroBuffer(#[from] DeviceError),
+    #[error("failed to create internal pipeline for validating indirect arguments: {0}")]
+    FailedToCreateIndirectValidation(String),
 }
 
 impl<A: HalApi> Device<A> {
@@ -334,10 +426,10 @@ impl<A: HalApi> Device<A> {
         alignments: hal::Alignments,
         downlevel: wgt::DownlevelCapabilities,
         desc: &DeviceDescriptor,
-        trace_path: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
     ) -> Result<Self, CreateDeviceError> {
         #[cfg(not(feature = "trace"))]
-        if let Some(_) = trace_path {
+        if let Some(_) = trace_desc {
             log::error!("Feature 'trace' is not enabled");
         }
         let fence =
@@ -381,6 +473,15 @@ impl<A: HalApi> Device<A> {
                 }));
         }
 
+        let indirect_validation = if desc
+            .features
+            .contains(wgt::Features::INDIRECT_ARGUMENT_VALIDATION)
+        {
+            Some(command::IndirectValidation::new(&open.device)?)
+        } else {
+            None
+        };
+
         let life_guard = LifeGuard::new("<device>");
         let ref_count = life_guard.add_ref();
         Ok(Self {
@@ -388,6 +489,9 @@ impl<A: HalApi> Device<A> {
             adapter_id,
             queue: open.queue,
             zero_buffer,
+            indirect_validation,
+            valid: AtomicBool::new(true),
+            device_lost_closure: Mutex::new(None),
             life_guard,
             ref_count,
             command_allocator: Mutex::new(com_alloc),
@@ -397,7 +501,7 @@ impl<A: HalApi> Device<A> {
             life_tracker: Mutex::new(life::LifetimeTracker::new()),
             temp_suspected: life::SuspectedResources::default(),
             #[cfg(feature = "trace")]
-            trace: trace_path.and_then(|path| match trace::Trace::new(path) {
+            trace: trace_desc.and_then(|td| match trace::Trace::new(td.path, td.format) {
                 Ok(mut trace) => {
                     trace.add(trace::Action::Init {
                         desc: desc.clone(),
@@ -406,7 +510,7 @@ impl<A: HalApi> Device<A> {
                     Some(Mutex::new(trace))
                 }
                 Err(e) => {
-                    log::error!("Unable to start a trace in '{:?}': {:?}", path, e);
+                    log::error!("Unable to start a trace in '{:?}': {:?}", td.path, e);
                     None
                 }
             }),
@@ -418,6 +522,37 @@ impl<A: HalApi> Device<A> {
         })
     }
 
+    pub(crate) fn is_valid(&self) -> bool {
+        self.valid.load(Ordering::Acquire)
+    }
+
+    /// Marks the device as lost, and fails all the pending buffer mappings.
+    ///
+    /// Returns the closures to fire once nothing is locked, including the
+    /// device lost closure if it wasn't called yet.
+    fn lose<G: GlobalIdentityHandlerFactory>(
+        &self,
+        life_tracker: &mut life::LifetimeTracker<A>,
+        hub: &Hub<A, G>,
+        token: &mut Token<Self>,
+        reason: wgt::DeviceLostReason,
+        message: &str,
+    ) -> UserClosures {
+        self.valid.store(false, Ordering::Release);
+        let mut closures = UserClosures {
+            mappings: life_tracker.fail_mappings(hub, token),
+            ..Default::default()
+        };
+        if let Some(closure) = self.device_lost_closure.lock().take() {
+            closures.device_lost_invocations.push(DeviceLostInvocation {
+                closure,
+                reason,
+                message: message.to_string(),
+            });
+        }
+        closures
+    }
+
     fn lock_life<'this, 'token: 'this>(
         &'this self,
         //TODO: fix this - the token has to be borrowed for the lock
@@ -466,27 +601,52 @@ impl<A: HalApi> Device<A> {
         );
         life_tracker.triage_mapped(hub, token);
 
-        let last_done_index = if maintain.is_wait() {
-            let index_to_wait_for = match maintain {
-                wgt::Maintain::WaitForSubmissionIndex(submission_index) => {
-                    // We don't need to check to see if the queue id matches
-                    // as we already checked this from inside the poll call.
-                    submission_index.index
+        // Nothing is going to execute on a lost device anymore,
+        // so all of its submissions are considered done.
+        let mut closures = UserClosures::default();
+        let last_done_index = if self.is_valid() {
+            let fence_value = if maintain.is_wait() {
+                let index_to_wait_for = match maintain {
+                    wgt::Maintain::WaitForSubmissionIndex(submission_index) => {
+                        // We don't need to check to see if the queue id matches
+                        // as we already checked this from inside the poll call.
+                        submission_index.index
+                    }
+                    _ => self.active_submission_index,
+                };
+                unsafe {
+                    self.raw
+                        .wait(&self.fence, index_to_wait_for, CLEANUP_WAIT_MS)
                 }
-                _ => self.active_submission_index,
-            };
-            unsafe {
-                self.raw
-                    .wait(&self.fence, index_to_wait_for, CLEANUP_WAIT_MS)
-                    .map_err(DeviceError::from)?
+                .map(|_| index_to_wait_for)
+            } else {
+                unsafe { self.raw.get_fence_value(&self.fence) }
             };
-            index_to_wait_for
-        } else {
-            unsafe {
-                self.raw
-                    .get_fence_value(&self.fence)
-                    .map_err(DeviceError::from)?
+            match fence_value {
+                Ok(index) => index,
+                Err(hal::DeviceError::Lost) => {
+                    closures = self.lose(
+                        &mut life_tracker,
+                        hub,
+                        token,
+                        wgt::DeviceLostReason::Unknown,
+                        "device was lost while waiting for the GPU",
+                    );
+                    self.active_submission_index
+                }
+                Err(error) => return Err(DeviceError::from(error).into()),
             }
+        } else {
+            // The device may have been marked as lost by a failed submission,
+            // in which case the user hasn't been notified yet.
+            closures = self.lose(
+                &mut life_tracker,
+                hub,
+                token,
+                wgt::DeviceLostReason::Unknown,
+                "device was lost",
+            );
+            self.active_submission_index
         };
 
         let submission_closures =
@@ -494,10 +654,11 @@ impl<A: HalApi> Device<A> {
         let mapping_closures = life_tracker.handle_mapping(hub, &self.raw, &self.trackers, token);
         life_tracker.cleanup(&self.raw);
 
-        let closures = UserClosures {
+        closures.extend(UserClosures {
             mappings: mapping_closures,
             submissions: submission_closures,
-        };
+            device_lost_invocations: SmallVec::new(),
+        });
         Ok((closures, life_tracker.queue_empty()))
     }
 
@@ -596,6 +757,11 @@ impl<A: HalApi> Device<A> {
             usage |= hal::BufferUses::COPY_DST;
         }
 
+        if desc.usage.contains(wgt::BufferUsages::INDIRECT) && self.indirect_validation.is_some() {
+            // Indirect arguments are copied out for validation.
+            usage |= hal::BufferUses::COPY_SRC;
+        }
+
         let actual_size = if desc.size == 0 {
             wgt::COPY_BUFFER_ALIGNMENT
         } else if desc.usage.contains(wgt::BufferUsages::VERTEX) {
@@ -1322,6 +1488,59 @@ impl<A: HalApi> Device<A> {
         })
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        self_id: id::DeviceId,
+        adapter: &instance::Adapter<A>,
+        desc: &pipeline::PipelineCacheDescriptor,
+        data: Option<&[u8]>,
+    ) -> Result<pipeline::PipelineCache<A>, pipeline::CreatePipelineCacheError> {
+        self.require_features(wgt::Features::PIPELINE_CACHE)?;
+
+        let data = match data {
+            Some(data) => {
+                let validated = match self.raw.pipeline_cache_validation_key() {
+                    Some(key) => {
+                        let source = pipeline_cache::CacheSource::new(&adapter.raw.info, key);
+                        pipeline_cache::validate(&source, data)
+                    }
+                    None => Err(pipeline::PipelineCacheValidationError::Unsupported),
+                };
+                match validated {
+                    Ok(data) => Some(data),
+                    Err(error) if desc.fallback => {
+                        log::warn!(
+                            "Pipeline cache data was rejected ({}), starting empty",
+                            error
+                        );
+                        None
+                    }
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            None => None,
+        };
+
+        let hal_desc = hal::PipelineCacheDescriptor {
+            label: desc.label.borrow_option(),
+            data,
+        };
+        let raw = self
+            .raw
+            .create_pipeline_cache(&hal_desc)
+            .map_err(DeviceError::from)?;
+
+        Ok(pipeline::PipelineCache {
+            raw,
+            device_id: Stored {
+                value: id::Valid(self_id),
+                ref_count: self.life_guard.add_ref(),
+            },
+            #[cfg(debug_assertions)]
+            label: desc.label.borrow_or_default().to_string(),
+        })
+    }
+
     fn deduplicate_bind_group_layout(
         self_id: id::DeviceId,
         entry_map: &binding_model::BindEntryMap,
@@ -2332,7 +2551,8 @@ impl<A: HalApi> Device<A> {
         let mut shader_binding_sizes = FastHashMap::default();
 
         let io = validation::StageIo::default();
-        let (shader_module_guard, _) = hub.shader_modules.read(&mut token);
+        let (shader_module_guard, mut token) = hub.shader_modules.read(&mut token);
+        let (pipeline_cache_guard, _) = hub.pipeline_caches.read(&mut token);
 
         let shader_module = shader_module_guard
             .get(desc.stage.module)
@@ -2360,6 +2580,7 @@ impl<A: HalApi> Device<A> {
                     &mut derived_group_layouts,
                     &mut shader_binding_sizes,
                     &desc.stage.entry_point,
+                    &desc.stage.constants,
                     flag,
                     io,
                 )?;
@@ -2383,13 +2604,25 @@ impl<A: HalApi> Device<A> {
         let late_sized_buffer_groups =
             Device::make_late_sized_buffer_groups(&shader_binding_sizes, layout, &*bgl_guard);
 
+        let cache = match desc.cache {
+            Some(cache_id) => Some(
+                &pipeline_cache_guard
+                    .get(cache_id)
+                    .map_err(|_| pipeline::CreateComputePipelineError::InvalidCache)?
+                    .raw,
+            ),
+            None => None,
+        };
+
         let pipeline_desc = hal::ComputePipelineDescriptor {
             label: desc.label.borrow_option(),
             layout: &layout.raw,
             stage: hal::ProgrammableStage {
                 entry_point: desc.stage.entry_point.as_ref(),
                 module: &shader_module.raw,
+                constants: &desc.stage.constants,
             },
+            cache,
         };
 
         let raw =
@@ -2647,7 +2880,8 @@ impl<A: HalApi> Device<A> {
             sc
         };
 
-        let (shader_module_guard, _) = hub.shader_modules.read(&mut token);
+        let (shader_module_guard, mut token) = hub.shader_modules.read(&mut token);
+        let (pipeline_cache_guard, _) = hub.pipeline_caches.read(&mut token);
 
         let vertex_stage = {
             let stage = &desc.vertex.stage;
@@ -2680,6 +2914,7 @@ impl<A: HalApi> Device<A> {
                         &mut derived_group_layouts,
                         &mut shader_binding_sizes,
                         &stage.entry_point,
+                        &stage.constants,
                         flag,
                         io,
                     )
@@ -2693,6 +2928,7 @@ impl<A: HalApi> Device<A> {
             hal::ProgrammableStage {
                 module: &shader_module.raw,
                 entry_point: stage.entry_point.as_ref(),
+                constants: &stage.constants,
             }
         };
 
@@ -2726,6 +2962,7 @@ impl<A: HalApi> Device<A> {
                                 &mut derived_group_layouts,
                                 &mut shader_binding_sizes,
                                 &fragment.stage.entry_point,
+                                &fragment.stage.constants,
                                 flag,
                                 io,
                             )
@@ -2740,6 +2977,7 @@ impl<A: HalApi> Device<A> {
                 Some(hal::ProgrammableStage {
                     module: &shader_module.raw,
                     entry_point: fragment.stage.entry_point.as_ref(),
+                    constants: &fragment.stage.constants,
                 })
             }
             None => None,
@@ -2802,6 +3040,16 @@ impl<A: HalApi> Device<A> {
         let late_sized_buffer_groups =
             Device::make_late_sized_buffer_groups(&shader_binding_sizes, layout, &*bgl_guard);
 
+        let cache = match desc.cache {
+            Some(cache_id) => Some(
+                &pipeline_cache_guard
+                    .get(cache_id)
+                    .map_err(|_| pipeline::CreateRenderPipelineError::InvalidCache)?
+                    .raw,
+            ),
+            None => None,
+        };
+
         let pipeline_desc = hal::RenderPipelineDescriptor {
             label: desc.label.borrow_option(),
             layout: &layout.raw,
@@ -2813,6 +3061,7 @@ impl<A: HalApi> Device<A> {
             fragment_stage,
             color_targets,
             multiview: desc.multiview,
+            cache,
         };
         let raw =
             unsafe { self.raw.create_render_pipeline(&pipeline_desc) }.map_err(
@@ -2989,6 +3238,9 @@ impl<A: HalApi> Device<A> {
         unsafe {
             self.raw.destroy_command_encoder(baked.encoder);
         }
+        for scratch in baked.indirect_scratch {
+            scratch.dispose(&self.raw);
+        }
     }
 
     /// Wait for idle and remove resources that we can, before we die.
@@ -3010,6 +3262,9 @@ impl<A: HalApi> Device<A> {
     pub(crate) fn dispose(self) {
         self.pending_writes.dispose(&self.raw);
         self.command_allocator.into_inner().dispose(&self.raw);
+        if let Some(indirect_validation) = self.indirect_validation {
+            indirect_validation.dispose(&self.raw);
+        }
         unsafe {
             self.raw.destroy_buffer(self.zero_buffer);
             self.raw.destroy_fence(self.fence);
@@ -3126,6 +3381,27 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         surface.get_supported_formats(adapter)
     }
 
+    pub fn surface_get_supported_present_modes<A: HalApi>(
+        &self,
+        surface_id: id::SurfaceId,
+        adapter_id: id::AdapterId,
+    ) -> Result<Vec<wgt::PresentMode>, instance::GetSurfacePreferredFormatError> {
+        profiling::scope!("Surface::get_supported_present_modes");
+        let hub = A::hub(self);
+        let mut token = Token::root();
+
+        let (surface_guard, mut token) = self.surfaces.read(&mut token);
+        let (adapter_guard, mut _token) = hub.adapters.read(&mut token);
+        let adapter = adapter_guard
+            .get(adapter_id)
+            .map_err(|_| instance::GetSurfacePreferredFormatError::InvalidAdapter)?;
+        let surface = surface_guard
+            .get(surface_id)
+            .map_err(|_| instance::GetSurfacePreferredFormatError::InvalidSurface)?;
+
+        surface.get_supported_present_modes(adapter)
+    }
+
     pub fn device_features<A: HalApi>(
         &self,
         device_id: id::DeviceId,
@@ -3162,6 +3438,67 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(device.downlevel.clone())
     }
 
+    pub fn device_memory_report<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<wgt::MemoryReport, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        let mut report = device.raw.memory_report();
+        let resources = &mut report.resources;
+
+        let (query_set_guard, mut token) = hub.query_sets.read(&mut token);
+        for (_, query_set) in query_set_guard.iter(A::VARIANT) {
+            if query_set.device_id.value.0 != device_id {
+                continue;
+            }
+            let values_per_query = match query_set.desc.ty {
+                wgt::QueryType::Occlusion | wgt::QueryType::Timestamp => 1,
+                wgt::QueryType::PipelineStatistics(types) => types.bits().count_ones(),
+            };
+            resources.query_sets += 1;
+            resources.query_set_bytes += query_set.desc.count as u64
+                * values_per_query as u64
+                * mem::size_of::<u64>() as u64;
+        }
+
+        let (buffer_guard, mut token) = hub.buffers.read(&mut token);
+        for (_, buffer) in buffer_guard.iter(A::VARIANT) {
+            if buffer.device_id.value.0 != device_id {
+                continue;
+            }
+            resources.buffers += 1;
+            resources.buffer_bytes += buffer.size;
+        }
+
+        let (texture_guard, _) = hub.textures.read(&mut token);
+        for (_, texture) in texture_guard.iter(A::VARIANT) {
+            // Surface textures are owned by the swapchain.
+            if texture.device_id.value.0 != device_id
+                || matches!(texture.inner, resource::TextureInner::Surface { .. })
+            {
+                continue;
+            }
+            let desc = &texture.desc;
+            let format_info = desc.format.describe();
+            let mip_bytes = (0..desc.mip_level_count)
+                .map(|mip| {
+                    let size = desc.mip_level_size(mip).unwrap().physical_size(desc.format);
+                    let blocks = (size.width / format_info.block_dimensions.0 as u32) as u64
+                        * (size.height / format_info.block_dimensions.1 as u32) as u64
+                        * size.depth_or_array_layers as u64;
+                    blocks * format_info.block_size as u64
+                })
+                .sum::<u64>();
+            resources.textures += 1;
+            resources.texture_bytes += mip_bytes * desc.sample_count as u64;
+        }
+
+        Ok(report)
+    }
+
     pub fn device_create_buffer<A: HalApi>(
         &self,
         device_id: id::DeviceId,
@@ -3180,6 +3517,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 let mut desc = desc.clone();
@@ -3564,6 +3904,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace
@@ -3619,6 +3962,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
 
             // NB: Any change done through the raw texture handle will not be recorded in the replay
             #[cfg(feature = "trace")]
@@ -3899,6 +4245,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace
@@ -3977,6 +4326,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace
@@ -4073,6 +4425,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace
@@ -4152,6 +4507,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace
@@ -4242,6 +4600,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 let mut trace = trace.lock();
@@ -4302,6 +4663,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 let mut trace = trace.lock();
@@ -4353,6 +4717,111 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    #[allow(unused_unsafe)]
+    /// # Safety
+    ///
+    /// The data is passed to the driver as-is once its header has been validated,
+    /// and must have been returned by [`Global::pipeline_cache_get_data`].
+    pub unsafe fn device_create_pipeline_cache<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &pipeline::PipelineCacheDescriptor,
+        data: Option<&[u8]>,
+        id_in: Input<G, id::PipelineCacheId>,
+    ) -> (
+        id::PipelineCacheId,
+        Option<pipeline::CreatePipelineCacheError>,
+    ) {
+        profiling::scope!("create_pipeline_cache", "Device");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let fid = hub.pipeline_caches.prepare(id_in);
+
+        let (adapter_guard, mut token) = hub.adapters.read(&mut token);
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let error = loop {
+            let device = match device_guard.get(device_id) {
+                Ok(device) => device,
+                Err(_) => break DeviceError::Invalid.into(),
+            };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
+            #[cfg(feature = "trace")]
+            if let Some(ref trace) = device.trace {
+                let mut trace = trace.lock();
+                let data = data.map(|data| trace.make_binary("bin", data));
+                trace.add(trace::Action::CreatePipelineCache {
+                    id: fid.id(),
+                    desc: desc.clone(),
+                    data,
+                });
+            }
+
+            let adapter = &adapter_guard[device.adapter_id.value];
+            let cache =
+                match unsafe { device.create_pipeline_cache(device_id, adapter, desc, data) } {
+                    Ok(cache) => cache,
+                    Err(e) => break e,
+                };
+            let id = fid.assign(cache, &mut token);
+            return (id.0, None);
+        };
+
+        let id = fid.assign_error(desc.label.borrow_or_default(), &mut token);
+        (id, Some(error))
+    }
+
+    /// Returns the contents of the cache, prefixed with a header identifying
+    /// the device that produced it, or `None` if the backend can't serialize it.
+    pub fn pipeline_cache_get_data<A: HalApi>(
+        &self,
+        pipeline_cache_id: id::PipelineCacheId,
+    ) -> Option<Vec<u8>> {
+        profiling::scope!("get_data", "PipelineCache");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (adapter_guard, mut token) = hub.adapters.read(&mut token);
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (pipeline_cache_guard, _) = hub.pipeline_caches.read(&mut token);
+
+        let cache = pipeline_cache_guard.get(pipeline_cache_id).ok()?;
+        let device = &device_guard[cache.device_id.value];
+        let adapter = &adapter_guard[device.adapter_id.value];
+
+        let key = device.raw.pipeline_cache_validation_key()?;
+        let data = unsafe { device.raw.pipeline_cache_get_data(&cache.raw) }?;
+        let source = pipeline_cache::CacheSource::new(&adapter.raw.info, key);
+        Some(pipeline_cache::add_header(&source, &data))
+    }
+
+    pub fn pipeline_cache_drop<A: HalApi>(&self, pipeline_cache_id: id::PipelineCacheId) {
+        profiling::scope!("drop", "PipelineCache");
+        log::debug!("pipeline cache {:?} is dropped", pipeline_cache_id);
+
+        // Pipelines don't keep their cache alive, so it can be destroyed right away.
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (cache, _) = hub
+            .pipeline_caches
+            .unregister(pipeline_cache_id, &mut token);
+        if let Some(cache) = cache {
+            let device = &device_guard[cache.device_id.value];
+            #[cfg(feature = "trace")]
+            if let Some(ref trace) = device.trace {
+                trace
+                    .lock()
+                    .add(trace::Action::DestroyPipelineCache(pipeline_cache_id));
+            }
+            unsafe {
+                device.raw.destroy_pipeline_cache(cache.raw);
+            }
+        }
+    }
+
     pub fn device_create_command_encoder<A: HalApi>(
         &self,
         device_id: id::DeviceId,
@@ -4371,6 +4840,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid,
             };
+            if !device.is_valid() {
+                break DeviceError::Lost;
+            }
             let dev_stored = Stored {
                 value: id::Valid(device_id),
                 ref_count: device.life_guard.add_ref(),
@@ -4463,6 +4935,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break command::RenderBundleError::INVALID_DEVICE,
             };
+            if !device.is_valid() {
+                break command::RenderBundleError::LOST_DEVICE;
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace.lock().add(trace::Action::CreateRenderBundle {
@@ -4545,6 +5020,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace.lock().add(trace::Action::CreateQuerySet {
@@ -4630,6 +5108,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             let adapter = &adapter_guard[device.adapter_id.value];
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
@@ -4773,6 +5254,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace.lock().add(trace::Action::CreateComputePipeline {
@@ -4952,6 +5436,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
+            if !device.is_valid() {
+                break DeviceError::Lost.into();
+            }
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace
@@ -5164,6 +5651,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             all_queue_empty =
                 self.poll_devices::<hal::api::Gles>(force_wait, &mut closures)? && all_queue_empty;
         }
+        #[cfg(software)]
+        {
+            all_queue_empty = self.poll_devices::<hal::api::Software>(force_wait, &mut closures)?
+                && all_queue_empty;
+        }
 
         closures.fire();
 
@@ -5192,6 +5684,75 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    /// Set the closure to call once the device is lost or destroyed,
+    /// replacing any previous one.
+    ///
+    /// If the device is already lost, the closure is called right away.
+    pub fn device_set_device_lost_closure<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        closure: DeviceLostClosure,
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let closure_opt = {
+            let (device_guard, _) = hub.devices.read(&mut token);
+            let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+            if device.is_valid() {
+                *device.device_lost_closure.lock() = Some(closure);
+                None
+            } else {
+                Some(closure)
+            }
+        };
+        if let Some(closure) = closure_opt {
+            closure.call(
+                wgt::DeviceLostReason::Unknown,
+                "device is already lost".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Destroy the device, which is then considered lost.
+    ///
+    /// The work already submitted is waited upon, then the pending buffer
+    /// mappings fail and the device lost closure is called.
+    /// Any further use of the device fails with [`DeviceError::Lost`].
+    pub fn device_destroy<A: HalApi>(&self, device_id: id::DeviceId) -> Result<(), InvalidDevice> {
+        profiling::scope!("destroy", "Device");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let closures = {
+            let (device_guard, mut token) = hub.devices.read(&mut token);
+            let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+            if !device.is_valid() {
+                return Ok(());
+            }
+            let current_index = device.active_submission_index;
+            if let Err(error) = unsafe {
+                device
+                    .raw
+                    .wait(&device.fence, current_index, CLEANUP_WAIT_MS)
+            } {
+                log::error!("failed to wait for the device: {:?}", error);
+            }
+            let mut life_tracker = device.lock_life(&mut token);
+            device.lose(
+                &mut life_tracker,
+                hub,
+                &mut token,
+                wgt::DeviceLostReason::Destroyed,
+                "device was destroyed",
+            )
+        };
+
+        // the closures should execute with nothing locked!
+        closures.fire();
+        Ok(())
+    }
+
     pub fn device_drop<A: HalApi>(&self, device_id: id::DeviceId) {
         profiling::scope!("drop", "Device");
         log::debug!("device {:?} is dropped", device_id);
@@ -5267,6 +5828,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .map_err(|_| resource::BufferAccessError::Invalid)?;
 
             check_buffer_usage(buffer.usage, pub_usage)?;
+            if !device_guard[buffer.device_id.value].is_valid() {
+                op.callback
+                    .call(resource::BufferMapAsyncStatus::ContextLost);
+                return Ok(());
+            }
             buffer.map_state = match buffer.map_state {
                 resource::BufferMapState::Init { .. } | resource::BufferMapState::Active { .. } => {
                     return Err(resource::BufferAccessError::AlreadyMapped);
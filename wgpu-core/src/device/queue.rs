@@ -18,7 +18,7 @@ use crate::{
 use hal::{CommandEncoder as _, Device as _, Queue as _};
 use parking_lot::Mutex;
 use smallvec::SmallVec;
-use std::{iter, mem, num::NonZeroU32, ptr};
+use std::{iter, mem, num::NonZeroU32, ptr, sync::atomic::Ordering};
 use thiserror::Error;
 
 /// Number of command buffers that we generate from the same pool
@@ -112,6 +112,7 @@ impl<A: hal::Api> StagingData<A> {
 pub enum TempResource<A: hal::Api> {
     Buffer(A::Buffer),
     Texture(A::Texture, SmallVec<[A::TextureView; 1]>),
+    BindGroup(A::BindGroup),
 }
 
 /// A queue execution for a particular command encoder.
@@ -170,6 +171,9 @@ impl<A: hal::Api> PendingWrites<A> {
                     }
                     device.destroy_texture(texture);
                 },
+                TempResource::BindGroup(bind_group) => unsafe {
+                    device.destroy_bind_group(bind_group);
+                },
             }
         }
     }
@@ -638,10 +642,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let device = device_guard
                 .get_mut(queue_id)
                 .map_err(|_| DeviceError::Invalid)?;
+            if !device.is_valid() {
+                return Err(DeviceError::Lost.into());
+            }
             device.temp_suspected.clear();
             device.active_submission_index += 1;
             let submit_index = device.active_submission_index;
             let mut active_executions = Vec::new();
+            let mut temp_resources = Vec::new();
             let mut used_surface_textures = track::TextureUsageScope::new();
 
             {
@@ -830,6 +838,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         let transit = unsafe { baked.encoder.end_encoding().unwrap() };
                         baked.list.insert(0, transit);
 
+                        temp_resources.extend(
+                            baked
+                                .indirect_scratch
+                                .drain(..)
+                                .flat_map(|scratch| scratch.into_temp_resources()),
+                        );
+
                         // Transition surface textures into `Present` state.
                         // Note: we could technically do it after all of the command buffers,
                         // but here we have a command encoder by hand, so it's easier to use it.
@@ -869,6 +884,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     ref mut pending_writes,
                     ref mut queue,
                     ref mut fence,
+                    ref valid,
                     ..
                 } = *device;
 
@@ -939,11 +955,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             .flat_map(|pool_execution| pool_execution.cmd_buffers.iter()),
                     )
                     .collect::<Vec<_>>();
-                unsafe {
-                    queue
-                        .submit(&refs, Some((fence, submit_index)))
-                        .map_err(DeviceError::from)?;
-                }
+                unsafe { queue.submit(&refs, Some((fence, submit_index))) }.map_err(|error| {
+                    if let hal::DeviceError::Lost = error {
+                        // The user is notified on the next maintenance of the device.
+                        valid.store(false, Ordering::Release);
+                    }
+                    DeviceError::from(error)
+                })?;
             }
 
             profiling::scope!("cleanup");
@@ -959,7 +977,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let mut pending_write_resources = mem::take(&mut device.pending_writes.temp_resources);
             device.lock_life(&mut token).track_submission(
                 submit_index,
-                pending_write_resources.drain(..),
+                pending_write_resources.drain(..).chain(temp_resources),
                 active_executions,
             );
 
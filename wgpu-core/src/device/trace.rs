@@ -96,6 +96,13 @@ pub enum Action<'a> {
         implicit_context: Option<super::ImplicitPipelineContext>,
     },
     DestroyRenderPipeline(id::RenderPipelineId),
+    CreatePipelineCache {
+        id: id::PipelineCacheId,
+        desc: crate::pipeline::PipelineCacheDescriptor<'a>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        data: Option<FileName>,
+    },
+    DestroyPipelineCache(id::PipelineCacheId),
     CreateRenderBundle {
         id: id::RenderBundleId,
         desc: crate::command::RenderBundleEncoderDescriptor<'a>,
@@ -178,6 +185,34 @@ pub enum Command {
         base: crate::command::BasePass<crate::command::RenderCommand>,
         target_colors: Vec<crate::command::RenderPassColorAttachment>,
         target_depth_stencil: Option<crate::command::RenderPassDepthStencilAttachment>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        occlusion_query_set_id: Option<id::QuerySetId>,
+    },
+}
+
+/// Magic bytes at the start of a binary trace file.
+pub const BINARY_MAGIC: [u8; 8] = *b"WGPUTRCB";
+/// Version of the binary trace container layout.
+pub const BINARY_VERSION: u32 = 1;
+
+// Every record in a binary trace starts with one of these tags.
+// An action record is followed by a `u64` length and the encoded action.
+// A blob record is followed by a `u32` name length, the name,
+// a `u64` length, and the raw contents.
+const RECORD_ACTION: u8 = 0;
+const RECORD_BLOB: u8 = 1;
+
+pub use wgt::TraceFormat;
+
+#[cfg(feature = "trace")]
+#[derive(Debug)]
+enum TraceWriter {
+    Ron {
+        file: std::fs::File,
+        config: ron::ser::PrettyConfig,
+    },
+    Binary {
+        file: std::io::BufWriter<std::fs::File>,
     },
 }
 
@@ -185,21 +220,33 @@ pub enum Command {
 #[derive(Debug)]
 pub struct Trace {
     path: std::path::PathBuf,
-    file: std::fs::File,
-    config: ron::ser::PrettyConfig,
+    writer: TraceWriter,
     binary_id: usize,
 }
 
 #[cfg(feature = "trace")]
 impl Trace {
-    pub fn new(path: &std::path::Path) -> Result<Self, std::io::Error> {
-        log::info!("Tracing into '{:?}'", path);
-        let mut file = std::fs::File::create(path.join(FILE_NAME))?;
-        file.write_all(b"[\n")?;
+    pub fn new(path: &std::path::Path, format: TraceFormat) -> Result<Self, std::io::Error> {
+        log::info!("Tracing into '{:?}' as {:?}", path, format);
+        let writer = match format {
+            TraceFormat::Ron => {
+                let mut file = std::fs::File::create(path.join(FILE_NAME))?;
+                file.write_all(b"[\n")?;
+                TraceWriter::Ron {
+                    file,
+                    config: ron::ser::PrettyConfig::default(),
+                }
+            }
+            TraceFormat::Binary => {
+                let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                file.write_all(&BINARY_MAGIC)?;
+                file.write_all(&BINARY_VERSION.to_le_bytes())?;
+                TraceWriter::Binary { file }
+            }
+        };
         Ok(Self {
             path: path.to_path_buf(),
-            file,
-            config: ron::ser::PrettyConfig::default(),
+            writer,
             binary_id: 0,
         })
     }
@@ -207,17 +254,61 @@ impl Trace {
     pub fn make_binary(&mut self, kind: &str, data: &[u8]) -> String {
         self.binary_id += 1;
         let name = format!("data{}.{}", self.binary_id, kind);
-        let _ = std::fs::write(self.path.join(&name), data);
+        match self.writer {
+            TraceWriter::Ron { .. } => {
+                let _ = std::fs::write(self.path.join(&name), data);
+            }
+            TraceWriter::Binary { ref mut file } => {
+                let result = (|| {
+                    file.write_all(&[RECORD_BLOB])?;
+                    file.write_all(&(name.len() as u32).to_le_bytes())?;
+                    file.write_all(name.as_bytes())?;
+                    file.write_all(&(data.len() as u64).to_le_bytes())?;
+                    file.write_all(data)
+                })();
+                if let Err(e) = result {
+                    log::warn!("Failed to write trace blob: {:?}", e);
+                }
+            }
+        }
         name
     }
 
-    pub(crate) fn add(&mut self, action: Action) {
-        match ron::ser::to_string_pretty(&action, self.config.clone()) {
-            Ok(string) => {
-                let _ = writeln!(self.file, "{},", string);
-            }
-            Err(e) => {
-                log::warn!("RON serialization failure: {:?}", e);
+    pub fn add(&mut self, action: Action) {
+        match self.writer {
+            TraceWriter::Ron {
+                ref mut file,
+                ref config,
+            } => match ron::ser::to_string_pretty(&action, config.clone()) {
+                Ok(string) => {
+                    let _ = writeln!(file, "{},", string);
+                }
+                Err(e) => {
+                    log::warn!("RON serialization failure: {:?}", e);
+                }
+            },
+            TraceWriter::Binary { ref mut file } => {
+                match bincode::serialize(&action) {
+                    Ok(bytes) => {
+                        let result = (|| {
+                            file.write_all(&[RECORD_ACTION])?;
+                            file.write_all(&(bytes.len() as u64).to_le_bytes())?;
+                            file.write_all(&bytes)
+                        })();
+                        if let Err(e) = result {
+                            log::warn!("Failed to write trace action: {:?}", e);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Binary serialization failure: {:?}", e);
+                    }
+                }
+                // Keep the trace usable up to the last submission if the process dies.
+                if let Action::Submit(..) = action {
+                    if let Err(e) = file.flush() {
+                        log::warn!("Failed to flush the trace: {:?}", e);
+                    }
+                }
             }
         }
     }
@@ -226,6 +317,204 @@ impl Trace {
 #[cfg(feature = "trace")]
 impl Drop for Trace {
     fn drop(&mut self) {
-        let _ = self.file.write_all(b"]");
+        match self.writer {
+            TraceWriter::Ron { ref mut file, .. } => {
+                let _ = file.write_all(b"]");
+            }
+            TraceWriter::Binary { ref mut file } => {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "replay")]
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("failed to read the trace: {0}")]
+    Io(String),
+    #[error("failed to parse the RON trace: {0}")]
+    Ron(String),
+    #[error("failed to decode a binary trace action: {0}")]
+    Binary(String),
+    #[error("not a binary trace file")]
+    InvalidMagic,
+    #[error("unsupported binary trace version {0}")]
+    UnsupportedVersion(u32),
+    #[error("unknown binary trace record {0}")]
+    UnknownRecord(u8),
+}
+
+#[cfg(feature = "replay")]
+impl From<std::io::Error> for LoadError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error.to_string())
+    }
+}
+
+/// Where the data blobs referenced by trace actions are stored.
+#[cfg(feature = "replay")]
+#[derive(Debug)]
+pub enum DataStore {
+    /// Blobs are files next to `trace.ron`.
+    Directory(std::path::PathBuf),
+    /// Blobs were embedded into a binary trace.
+    Embedded(std::collections::HashMap<String, Vec<u8>>),
+}
+
+#[cfg(feature = "replay")]
+impl DataStore {
+    pub fn read(&self, name: &str) -> Result<std::borrow::Cow<[u8]>, std::io::Error> {
+        match *self {
+            Self::Directory(ref dir) => std::fs::read(dir.join(name)).map(Into::into),
+            Self::Embedded(ref blobs) => match blobs.get(name) {
+                Some(data) => Ok(data[..].into()),
+                None => Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("blob '{}' is missing from the trace", name),
+                )),
+            },
+        }
+    }
+}
+
+/// Loads a trace in either format: a directory is read as a RON trace,
+/// a file as a binary trace.
+#[cfg(feature = "replay")]
+pub fn load(
+    path: &std::path::Path,
+) -> Result<(TraceFormat, Vec<Action<'static>>, DataStore), LoadError> {
+    if path.is_dir() {
+        let file = std::fs::File::open(path.join(FILE_NAME))?;
+        let actions = ron::de::from_reader(file).map_err(|e| LoadError::Ron(e.to_string()))?;
+        Ok((
+            TraceFormat::Ron,
+            actions,
+            DataStore::Directory(path.to_path_buf()),
+        ))
+    } else {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let (actions, blobs) = read_binary(file)?;
+        Ok((TraceFormat::Binary, actions, DataStore::Embedded(blobs)))
+    }
+}
+
+#[cfg(feature = "replay")]
+fn read_binary<R: std::io::Read>(
+    mut reader: R,
+) -> Result<
+    (
+        Vec<Action<'static>>,
+        std::collections::HashMap<String, Vec<u8>>,
+    ),
+    LoadError,
+> {
+    fn read_array<R: std::io::Read, const N: usize>(reader: &mut R) -> std::io::Result<[u8; N]> {
+        let mut bytes = [0; N];
+        reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+    /// Reads `len` bytes, without trusting `len` to allocate them upfront.
+    fn read_vec<R: std::io::Read>(reader: &mut R, len: u64) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        if reader.by_ref().take(len).read_to_end(&mut bytes)? as u64 != len {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(bytes)
+    }
+
+    if read_array::<_, 8>(&mut reader)? != BINARY_MAGIC {
+        return Err(LoadError::InvalidMagic);
+    }
+    let version = u32::from_le_bytes(read_array(&mut reader)?);
+    if version != BINARY_VERSION {
+        return Err(LoadError::UnsupportedVersion(version));
+    }
+
+    let mut actions = Vec::new();
+    let mut blobs = std::collections::HashMap::new();
+    loop {
+        let mut tag = [0u8];
+        if reader.read(&mut tag)? == 0 {
+            break;
+        }
+        match tag[0] {
+            RECORD_ACTION => {
+                let len = u64::from_le_bytes(read_array(&mut reader)?);
+                let bytes = read_vec(&mut reader, len)?;
+                let action = bincode::deserialize::<Action>(&bytes)
+                    .map_err(|e| LoadError::Binary(e.to_string()))?;
+                actions.push(action);
+            }
+            RECORD_BLOB => {
+                let name_len = u32::from_le_bytes(read_array(&mut reader)?);
+                let name =
+                    String::from_utf8_lossy(&read_vec(&mut reader, name_len.into())?).into_owned();
+                let len = u64::from_le_bytes(read_array(&mut reader)?);
+                blobs.insert(name, read_vec(&mut reader, len)?);
+            }
+            other => return Err(LoadError::UnknownRecord(other)),
+        }
+    }
+    Ok((actions, blobs))
+}
+
+#[cfg(all(test, feature = "trace", feature = "replay"))]
+mod tests {
+    use super::{load, Action, Trace, TraceFormat};
+    use crate::id::{self, TypedId as _};
+
+    const DATA: [u8; 4] = [1, 2, 3, 4];
+
+    /// Records a small trace, and loads it back before the trace is dropped.
+    fn round_trip(path: &std::path::Path, format: TraceFormat) -> String {
+        let mut trace = Trace::new(path, format).unwrap();
+        trace.add(Action::Init {
+            desc: Default::default(),
+            backend: wgt::Backend::Empty,
+        });
+        let buffer = id::BufferId::zip(1, 0, wgt::Backend::Empty);
+        trace.add(Action::CreateBuffer(
+            buffer,
+            crate::resource::BufferDescriptor {
+                label: Some("buffer".into()),
+                size: DATA.len() as wgt::BufferAddress,
+                usage: wgt::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        ));
+        let data = trace.make_binary("bin", &DATA);
+        trace.add(Action::WriteBuffer {
+            id: buffer,
+            data,
+            range: 0..DATA.len() as wgt::BufferAddress,
+            queued: true,
+        });
+        trace.add(Action::Submit(1, Vec::new()));
+
+        // A RON trace is only closed on drop.
+        if format == TraceFormat::Ron {
+            drop(trace);
+        }
+        let (loaded_format, actions, store) = load(path).unwrap();
+        assert_eq!(loaded_format, format);
+        assert_eq!(actions.len(), 4);
+        match actions[2] {
+            Action::WriteBuffer { ref data, .. } => {
+                assert_eq!(&*store.read(data).unwrap(), &DATA[..]);
+            }
+            ref other => panic!("Unexpected {:?}", other),
+        }
+        ron::ser::to_string(&actions).unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        let dir = std::env::temp_dir().join(format!("wgpu-trace-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ron = round_trip(&dir, TraceFormat::Ron);
+        let binary = round_trip(&dir.join("trace.bin"), TraceFormat::Binary);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(ron, binary);
     }
 }
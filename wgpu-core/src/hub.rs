@@ -4,7 +4,7 @@ use crate::{
     device::Device,
     id,
     instance::{Adapter, HalSurface, Instance, Surface},
-    pipeline::{ComputePipeline, RenderPipeline, ShaderModule},
+    pipeline::{ComputePipeline, PipelineCache, RenderPipeline, ShaderModule},
     resource::{Buffer, QuerySet, Sampler, Texture, TextureClearMode, TextureView},
     Epoch, Index,
 };
@@ -343,6 +343,9 @@ impl<A: HalApi> Access<QuerySet<A>> for ComputePipeline<A> {}
 impl<A: HalApi> Access<QuerySet<A>> for Sampler<A> {}
 impl<A: HalApi> Access<ShaderModule<A>> for Device<A> {}
 impl<A: HalApi> Access<ShaderModule<A>> for BindGroupLayout<A> {}
+impl<A: HalApi> Access<PipelineCache<A>> for Root {}
+impl<A: HalApi> Access<PipelineCache<A>> for Device<A> {}
+impl<A: HalApi> Access<PipelineCache<A>> for ShaderModule<A> {}
 impl<A: HalApi> Access<Buffer<A>> for Root {}
 impl<A: HalApi> Access<Buffer<A>> for Device<A> {}
 impl<A: HalApi> Access<Buffer<A>> for BindGroupLayout<A> {}
@@ -450,6 +453,7 @@ pub trait GlobalIdentityHandlerFactory:
     + IdentityHandlerFactory<id::RenderBundleId>
     + IdentityHandlerFactory<id::RenderPipelineId>
     + IdentityHandlerFactory<id::ComputePipelineId>
+    + IdentityHandlerFactory<id::PipelineCacheId>
     + IdentityHandlerFactory<id::QuerySetId>
     + IdentityHandlerFactory<id::BufferId>
     + IdentityHandlerFactory<id::TextureId>
@@ -613,6 +617,7 @@ pub struct HubReport {
     pub render_bundles: StorageReport,
     pub render_pipelines: StorageReport,
     pub compute_pipelines: StorageReport,
+    pub pipeline_caches: StorageReport,
     pub query_sets: StorageReport,
     pub buffers: StorageReport,
     pub textures: StorageReport,
@@ -637,6 +642,7 @@ pub struct Hub<A: HalApi, F: GlobalIdentityHandlerFactory> {
     pub render_bundles: Registry<RenderBundle<A>, id::RenderBundleId, F>,
     pub render_pipelines: Registry<RenderPipeline<A>, id::RenderPipelineId, F>,
     pub compute_pipelines: Registry<ComputePipeline<A>, id::ComputePipelineId, F>,
+    pub pipeline_caches: Registry<PipelineCache<A>, id::PipelineCacheId, F>,
     pub query_sets: Registry<QuerySet<A>, id::QuerySetId, F>,
     pub buffers: Registry<Buffer<A>, id::BufferId, F>,
     pub textures: Registry<Texture<A>, id::TextureId, F>,
@@ -657,6 +663,7 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
             render_bundles: Registry::new(A::VARIANT, factory),
             render_pipelines: Registry::new(A::VARIANT, factory),
             compute_pipelines: Registry::new(A::VARIANT, factory),
+            pipeline_caches: Registry::new(A::VARIANT, factory),
             query_sets: Registry::new(A::VARIANT, factory),
             buffers: Registry::new(A::VARIANT, factory),
             textures: Registry::new(A::VARIANT, factory),
@@ -746,6 +753,14 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
                 }
             }
         }
+        for element in self.pipeline_caches.data.write().map.drain(..) {
+            if let Element::Occupied(cache, _) = element {
+                let device = &devices[cache.device_id.value];
+                unsafe {
+                    device.raw.destroy_pipeline_cache(cache.raw);
+                }
+            }
+        }
         for element in self.bind_group_layouts.data.write().map.drain(..) {
             if let Element::Occupied(bgl, _) = element {
                 let device = &devices[bgl.device_id.value];
@@ -833,6 +848,7 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
             render_bundles: self.render_bundles.data.read().generate_report(),
             render_pipelines: self.render_pipelines.data.read().generate_report(),
             compute_pipelines: self.compute_pipelines.data.read().generate_report(),
+            pipeline_caches: self.pipeline_caches.data.read().generate_report(),
             query_sets: self.query_sets.data.read().generate_report(),
             buffers: self.buffers.data.read().generate_report(),
             textures: self.textures.data.read().generate_report(),
@@ -853,6 +869,8 @@ pub struct Hubs<F: GlobalIdentityHandlerFactory> {
     dx11: Hub<hal::api::Dx11, F>,
     #[cfg(gl)]
     gl: Hub<hal::api::Gles, F>,
+    #[cfg(software)]
+    software: Hub<hal::api::Software, F>,
 }
 
 impl<F: GlobalIdentityHandlerFactory> Hubs<F> {
@@ -868,6 +886,8 @@ impl<F: GlobalIdentityHandlerFactory> Hubs<F> {
             dx11: Hub::new(factory),
             #[cfg(gl)]
             gl: Hub::new(factory),
+            #[cfg(software)]
+            software: Hub::new(factory),
         }
     }
 }
@@ -885,6 +905,8 @@ pub struct GlobalReport {
     pub dx11: Option<HubReport>,
     #[cfg(gl)]
     pub gl: Option<HubReport>,
+    #[cfg(software)]
+    pub software: Option<HubReport>,
 }
 
 pub struct Global<G: GlobalIdentityHandlerFactory> {
@@ -970,6 +992,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             } else {
                 None
             },
+            #[cfg(software)]
+            software: if self.instance.software.is_some() {
+                Some(self.hubs.software.generate_report())
+            } else {
+                None
+            },
         }
     }
 }
@@ -1001,6 +1029,10 @@ impl<G: GlobalIdentityHandlerFactory> Drop for Global<G> {
         {
             self.hubs.gl.clear(&mut *surface_guard, true);
         }
+        #[cfg(software)]
+        {
+            self.hubs.software.clear(&mut *surface_guard, true);
+        }
 
         // destroy surfaces
         for element in surface_guard.map.drain(..) {
@@ -1160,6 +1192,31 @@ impl HalApi for hal::api::Gles {
     }
 }
 
+#[cfg(software)]
+impl HalApi for hal::api::Software {
+    const VARIANT: Backend = Backend::Software;
+    fn create_instance_from_hal(name: &str, hal_instance: Self::Instance) -> Instance {
+        #[allow(clippy::needless_update)]
+        Instance {
+            name: name.to_owned(),
+            software: Some(hal_instance),
+            ..Default::default()
+        }
+    }
+    fn instance_as_hal(instance: &Instance) -> Option<&Self::Instance> {
+        instance.software.as_ref()
+    }
+    fn hub<G: GlobalIdentityHandlerFactory>(global: &Global<G>) -> &Hub<Self, G> {
+        &global.hubs.software
+    }
+    fn get_surface(surface: &Surface) -> &HalSurface<Self> {
+        surface.software.as_ref().unwrap()
+    }
+    fn get_surface_mut(surface: &mut Surface) -> &mut HalSurface<Self> {
+        surface.software.as_mut().unwrap()
+    }
+}
+
 #[cfg(test)]
 fn _test_send_sync(global: &Global<IdentityManagerFactory>) {
     fn test_internal<T: Send + Sync>(_: T) {}
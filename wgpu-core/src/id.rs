@@ -106,6 +106,7 @@ impl<T> Id<T> {
             3 => Backend::Dx12,
             4 => Backend::Dx11,
             5 => Backend::Gl,
+            7 => Backend::Software,
             _ => unreachable!(),
         }
     }
@@ -207,6 +208,7 @@ pub type BindGroupId = Id<crate::binding_model::BindGroup<Dummy>>;
 pub type ShaderModuleId = Id<crate::pipeline::ShaderModule<Dummy>>;
 pub type RenderPipelineId = Id<crate::pipeline::RenderPipeline<Dummy>>;
 pub type ComputePipelineId = Id<crate::pipeline::ComputePipeline<Dummy>>;
+pub type PipelineCacheId = Id<crate::pipeline::PipelineCache<Dummy>>;
 // Command
 pub type CommandEncoderId = CommandBufferId;
 pub type CommandBufferId = Id<crate::command::CommandBuffer<Dummy>>;
@@ -225,6 +227,7 @@ fn test_id_backend() {
         Backend::Dx12,
         Backend::Dx11,
         Backend::Gl,
+        Backend::Software,
     ] {
         let id: Id<()> = Id::zip(1, 0, b);
         let (_id, _epoch, backend) = id.unzip();
@@ -245,6 +248,7 @@ fn test_id() {
         Backend::Dx12,
         Backend::Dx11,
         Backend::Gl,
+        Backend::Software,
     ];
     for &i in &indexes {
         for &e in &epochs {
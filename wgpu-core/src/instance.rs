@@ -64,6 +64,8 @@ pub struct Instance {
     pub dx11: Option<HalInstance<hal::api::Dx11>>,
     #[cfg(gl)]
     pub gl: Option<HalInstance<hal::api::Gles>>,
+    #[cfg(software)]
+    pub software: Option<HalInstance<hal::api::Software>>,
 }
 
 impl Instance {
@@ -97,6 +99,8 @@ impl Instance {
             dx11: init(hal::api::Dx11, backends),
             #[cfg(gl)]
             gl: init(hal::api::Gles, backends),
+            #[cfg(software)]
+            software: init(hal::api::Software, backends),
         }
     }
 
@@ -122,6 +126,8 @@ impl Instance {
         destroy(hal::api::Dx11, &self.dx11, surface.dx11);
         #[cfg(gl)]
         destroy(hal::api::Gles, &self.gl, surface.gl);
+        #[cfg(software)]
+        destroy(hal::api::Software, &self.software, surface.software);
     }
 }
 
@@ -137,6 +143,8 @@ pub struct Surface {
     pub dx11: Option<HalSurface<hal::api::Dx11>>,
     #[cfg(gl)]
     pub gl: Option<HalSurface<hal::api::Gles>>,
+    #[cfg(software)]
+    pub software: Option<HalSurface<hal::api::Software>>,
 }
 
 impl crate::hub::Resource for Surface {
@@ -172,6 +180,23 @@ impl Surface {
 
         Ok(caps.formats)
     }
+
+    pub fn get_supported_present_modes<A: HalApi>(
+        &self,
+        adapter: &Adapter<A>,
+    ) -> Result<Vec<wgt::PresentMode>, GetSurfacePreferredFormatError> {
+        let suf = A::get_surface(self);
+        let caps = unsafe {
+            profiling::scope!("surface_capabilities");
+            adapter
+                .raw
+                .adapter
+                .surface_capabilities(&suf.raw)
+                .ok_or(GetSurfacePreferredFormatError::UnsupportedQueueFamily)?
+        };
+
+        Ok(caps.present_modes)
+    }
 }
 
 pub struct Adapter<A: hal::Api> {
@@ -180,7 +205,19 @@ pub struct Adapter<A: hal::Api> {
 }
 
 impl<A: HalApi> Adapter<A> {
-    fn new(raw: hal::ExposedAdapter<A>) -> Self {
+    fn new(mut raw: hal::ExposedAdapter<A>) -> Self {
+        // Indirect argument validation is implemented here, on top of compute shaders.
+        let indirect_validation_flags =
+            wgt::DownlevelFlags::COMPUTE_SHADERS | wgt::DownlevelFlags::INDIRECT_EXECUTION;
+        if raw
+            .capabilities
+            .downlevel
+            .flags
+            .contains(indirect_validation_flags)
+        {
+            raw.features |= wgt::Features::INDIRECT_ARGUMENT_VALIDATION;
+        }
+
         Self {
             raw,
             life_guard: LifeGuard::new("<Adapter>"),
@@ -254,7 +291,7 @@ impl<A: HalApi> Adapter<A> {
         self_id: AdapterId,
         open: hal::OpenDevice<A>,
         desc: &DeviceDescriptor,
-        trace_path: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
     ) -> Result<Device<A>, RequestDeviceError> {
         let caps = &self.raw.capabilities;
         Device::new(
@@ -266,7 +303,7 @@ impl<A: HalApi> Adapter<A> {
             caps.alignments.clone(),
             caps.downlevel.clone(),
             desc,
-            trace_path,
+            trace_desc,
         )
         .or(Err(RequestDeviceError::OutOfMemory))
     }
@@ -275,7 +312,7 @@ impl<A: HalApi> Adapter<A> {
         &self,
         self_id: AdapterId,
         desc: &DeviceDescriptor,
-        trace_path: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
     ) -> Result<Device<A>, RequestDeviceError> {
         // Verify all features were exposed by the adapter
         if !self.raw.features.contains(desc.features) {
@@ -314,14 +351,16 @@ impl<A: HalApi> Adapter<A> {
             return Err(RequestDeviceError::LimitsExceeded(failed));
         }
 
-        let open = unsafe { self.raw.adapter.open(desc.features, &desc.limits) }.map_err(
-            |err| match err {
-                hal::DeviceError::Lost => RequestDeviceError::DeviceLost,
-                hal::DeviceError::OutOfMemory => RequestDeviceError::OutOfMemory,
-            },
-        )?;
+        let hal_features = desc.features - wgt::Features::INDIRECT_ARGUMENT_VALIDATION;
+        let open =
+            unsafe { self.raw.adapter.open(hal_features, &desc.limits) }.map_err(
+                |err| match err {
+                    hal::DeviceError::Lost => RequestDeviceError::DeviceLost,
+                    hal::DeviceError::OutOfMemory => RequestDeviceError::OutOfMemory,
+                },
+            )?;
 
-        self.create_device_from_hal(self_id, open, desc, trace_path)
+        self.create_device_from_hal(self_id, open, desc, trace_desc)
     }
 }
 
@@ -446,6 +485,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             dx11: init(hal::api::Dx11, &self.instance.dx11, handle),
             #[cfg(gl)]
             gl: init(hal::api::Gles, &self.instance.gl, handle),
+            #[cfg(software)]
+            software: init(hal::api::Software, &self.instance.software, handle),
         };
 
         let mut token = Token::root();
@@ -475,6 +516,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             vulkan: None,
             #[cfg(gl)]
             gl: None,
+            #[cfg(software)]
+            software: None,
         };
 
         let mut token = Token::root();
@@ -498,6 +541,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         .expect("Create surface from canvas")
                 },
             }),
+            #[cfg(software)]
+            software: None,
         };
 
         let mut token = Token::root();
@@ -521,6 +566,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         .expect("Create surface from offscreen canvas")
                 },
             }),
+            #[cfg(software)]
+            software: None,
         };
 
         let mut token = Token::root();
@@ -549,6 +596,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             dx11: None,
             #[cfg(gl)]
             gl: None,
+            #[cfg(software)]
+            software: None,
         };
 
         let mut token = Token::root();
@@ -620,6 +669,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         self.enumerate(hal::api::Dx11, &self.instance.dx11, &inputs, &mut adapters);
         #[cfg(gl)]
         self.enumerate(hal::api::Gles, &self.instance.gl, &inputs, &mut adapters);
+        #[cfg(software)]
+        self.enumerate(
+            hal::api::Software,
+            &self.instance.software,
+            &inputs,
+            &mut adapters,
+        );
 
         adapters
     }
@@ -740,6 +796,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             desc.force_fallback_adapter,
             &mut device_types,
         );
+        #[cfg(software)]
+        let (id_software, adapters_software) = gather(
+            hal::api::Software,
+            self.instance.software.as_ref(),
+            &inputs,
+            compatible_surface,
+            desc.force_fallback_adapter,
+            &mut device_types,
+        );
 
         // need to free the token to be used by `select`
         drop(surface_guard);
@@ -797,6 +862,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if let Some(id) = self.select(&mut selected, id_gl, adapters_gl) {
             return Ok(id);
         }
+        #[cfg(software)]
+        if let Some(id) = self.select(&mut selected, id_software, adapters_software) {
+            return Ok(id);
+        }
         let _ = selected;
 
         log::warn!("Some adapters are present, but enumerating them failed!");
@@ -827,6 +896,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             Backend::Dx11 => fid.assign(Adapter::new(hal_adapter), &mut token).0,
             #[cfg(gl)]
             Backend::Gl => fid.assign(Adapter::new(hal_adapter), &mut token).0,
+            #[cfg(software)]
+            Backend::Software => fid.assign(Adapter::new(hal_adapter), &mut token).0,
             _ => unreachable!(),
         }
     }
@@ -920,7 +991,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         &self,
         adapter_id: AdapterId,
         desc: &DeviceDescriptor,
-        trace_path: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
         id_in: Input<G, DeviceId>,
     ) -> (DeviceId, Option<RequestDeviceError>) {
         profiling::scope!("request_device", "Adapter");
@@ -935,7 +1006,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(adapter) => adapter,
                 Err(_) => break RequestDeviceError::InvalidAdapter,
             };
-            let device = match adapter.create_device(adapter_id, desc, trace_path) {
+            let device = match adapter.create_device(adapter_id, desc, trace_desc) {
                 Ok(device) => device,
                 Err(e) => break e,
             };
@@ -956,7 +1027,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         adapter_id: AdapterId,
         hal_device: hal::OpenDevice<A>,
         desc: &DeviceDescriptor,
-        trace_path: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
         id_in: Input<G, DeviceId>,
     ) -> (DeviceId, Option<RequestDeviceError>) {
         profiling::scope!("create_device_from_hal", "Adapter");
@@ -972,7 +1043,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Err(_) => break RequestDeviceError::InvalidAdapter,
             };
             let device =
-                match adapter.create_device_from_hal(adapter_id, hal_device, desc, trace_path) {
+                match adapter.create_device_from_hal(adapter_id, hal_device, desc, trace_desc) {
                     Ok(device) => device,
                     Err(e) => break e,
                 };
@@ -1008,6 +1079,7 @@ pub fn parse_backends_from_comma_list(string: &str) -> Backends {
             "metal" | "mtl" => Backends::METAL,
             "opengl" | "gles" | "gl" => Backends::GL,
             "webgpu" => Backends::BROWSER_WEBGPU,
+            "software" | "cpu" => Backends::SOFTWARE,
             b => {
                 log::warn!("unknown backend string '{}'", b);
                 continue;
@@ -43,6 +43,7 @@ pub mod id;
 mod init_tracker;
 pub mod instance;
 pub mod pipeline;
+mod pipeline_cache;
 pub mod present;
 pub mod resource;
 mod track;
@@ -306,6 +307,8 @@ macro_rules! gfx_select {
                 target_arch = "wasm32"
             ))]
             wgt::Backend::Gl => $global.$method::<$crate::api::Gles>( $($param),+ ),
+            #[cfg(feature = "software")]
+            wgt::Backend::Software => $global.$method::<$crate::api::Software>( $($param),* ),
             other => panic!("Unexpected backend {:?}", other),
 
         }
@@ -2,7 +2,7 @@ use crate::{
     binding_model::{CreateBindGroupLayoutError, CreatePipelineLayoutError},
     device::{DeviceError, MissingDownlevelFlags, MissingFeatures, RenderPassContext},
     hub::Resource,
-    id::{DeviceId, PipelineLayoutId, ShaderModuleId},
+    id::{DeviceId, PipelineCacheId, PipelineLayoutId, ShaderModuleId},
     validation, Label, LifeGuard, Stored,
 };
 use arrayvec::ArrayVec;
@@ -142,6 +142,21 @@ impl CreateShaderModuleError {
             _ => None,
         }
     }
+
+    /// Returns the location of the error in the WGSL source of the module,
+    /// if the error carries a span.
+    ///
+    /// Modules created from other languages are converted to naga IR before
+    /// validation, so their validation errors have no location.
+    pub fn source_location(&self) -> Option<naga::SourceLocation> {
+        match *self {
+            CreateShaderModuleError::Parsing(ref err) => err.inner.location(&err.source),
+            CreateShaderModuleError::Validation(ref err) if !err.source.is_empty() => {
+                err.inner.location(&err.source)
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Describes a programmable pipeline stage.
@@ -154,6 +169,10 @@ pub struct ProgrammableStageDescriptor<'a> {
     /// The name of the entry point in the compiled shader. There must be a function that returns
     /// void with this name in the shader.
     pub entry_point: Cow<'a, str>,
+    /// Values of the pipeline-overridable constants of the module, keyed by
+    /// either the numeric ID or the name of the constant.
+    #[cfg_attr(any(feature = "trace", feature = "replay"), serde(default))]
+    pub constants: Cow<'a, hal::PipelineConstants>,
 }
 
 /// Number of implicit bind groups derived at pipeline creation.
@@ -181,6 +200,9 @@ pub struct ComputePipelineDescriptor<'a> {
     pub layout: Option<PipelineLayoutId>,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStageDescriptor<'a>,
+    /// The pipeline cache to use when creating this pipeline.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub cache: Option<PipelineCacheId>,
 }
 
 #[derive(Clone, Debug, Error)]
@@ -189,6 +211,8 @@ pub enum CreateComputePipelineError {
     Device(#[from] DeviceError),
     #[error("pipeline layout is invalid")]
     InvalidLayout,
+    #[error("pipeline cache is invalid")]
+    InvalidCache,
     #[error("unable to derive an implicit layout")]
     Implicit(#[from] ImplicitLayoutError),
     #[error("error matching shader requirements against the pipeline")]
@@ -276,6 +300,69 @@ pub struct RenderPipelineDescriptor<'a> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The pipeline cache to use when creating this pipeline.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub cache: Option<PipelineCacheId>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize))]
+#[cfg_attr(feature = "replay", derive(serde::Deserialize))]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// If the provided data is rejected, create an empty cache instead of
+    /// reporting an error.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub fallback: bool,
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum PipelineCacheValidationError {
+    #[error("the pipeline cache data was truncated")]
+    Truncated,
+    #[error("the pipeline cache data is not in a known format")]
+    UnknownFormat,
+    #[error("the pipeline cache data was produced by an older or newer version of wgpu")]
+    Outdated,
+    #[error("the pipeline cache data was produced by a different backend, adapter or driver")]
+    DeviceMismatch,
+    #[error("the pipeline cache data is corrupted")]
+    Corrupted,
+    #[error("the backend doesn't support loading pipeline cache data")]
+    Unsupported,
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum CreatePipelineCacheError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("pipeline cache data was rejected")]
+    Validation(#[from] PipelineCacheValidationError),
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+}
+
+#[derive(Debug)]
+pub struct PipelineCache<A: hal::Api> {
+    pub(crate) raw: A::PipelineCache,
+    pub(crate) device_id: Stored<DeviceId>,
+    #[cfg(debug_assertions)]
+    pub(crate) label: String,
+}
+
+impl<A: hal::Api> Resource for PipelineCache<A> {
+    const TYPE: &'static str = "PipelineCache";
+
+    fn life_guard(&self) -> &LifeGuard {
+        unreachable!()
+    }
+
+    fn label(&self) -> &str {
+        #[cfg(debug_assertions)]
+        return &self.label;
+        #[cfg(not(debug_assertions))]
+        return "";
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -317,6 +404,8 @@ pub enum CreateRenderPipelineError {
     Device(#[from] DeviceError),
     #[error("pipeline layout is invalid")]
     InvalidLayout,
+    #[error("pipeline cache is invalid")]
+    InvalidCache,
     #[error("unable to derive an implicit layout")]
     Implicit(#[from] ImplicitLayoutError),
     #[error("color state [{0}] is invalid")]
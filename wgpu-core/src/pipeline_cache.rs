@@ -0,0 +1,176 @@
+/*! Header of serialized pipeline caches.
+
+The data returned by the backends is opaque, and most drivers don't cope well
+with data produced by another driver, or even by another version of the same
+driver. So every blob handed out by [`Global::pipeline_cache_get_data`] is
+prefixed with a header describing where it comes from, which is checked
+before the data reaches the backend.
+
+All values are stored in little-endian order:
+
+| Offset | Size | Contents                                      |
+| ------ | ---- | --------------------------------------------- |
+| 0      | 8    | [`MAGIC`]                                     |
+| 8      | 4    | [`VERSION`]                                   |
+| 12     | 4    | backend, as a [`wgt::Backend`]                |
+| 16     | 8    | adapter vendor ID                             |
+| 24     | 8    | adapter device ID                             |
+| 32     | 16   | backend-specific validation key               |
+| 48     | 8    | size of the data following the header         |
+| 56     | 8    | FNV-1a hash of the data following the header  |
+
+[`Global::pipeline_cache_get_data`]: crate::hub::Global::pipeline_cache_get_data
+!*/
+
+use crate::pipeline::PipelineCacheValidationError as Error;
+
+const MAGIC: [u8; 8] = *b"WGPUPLCH";
+/// Has to be bumped whenever the layout of the header changes.
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 64;
+
+/// Where the cache data comes from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct CacheSource {
+    pub backend: wgt::Backend,
+    pub vendor: u64,
+    pub device: u64,
+    pub validation_key: [u8; 16],
+}
+
+impl CacheSource {
+    pub fn new(info: &wgt::AdapterInfo, validation_key: [u8; 16]) -> Self {
+        Self {
+            backend: info.backend,
+            vendor: info.vendor as u64,
+            device: info.device as u64,
+            validation_key,
+        }
+    }
+}
+
+fn hash(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Prefixes the backend data with a header.
+pub(crate) fn add_header(source: &CacheSource, data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(HEADER_SIZE + data.len());
+    result.extend_from_slice(&MAGIC);
+    result.extend_from_slice(&VERSION.to_le_bytes());
+    result.extend_from_slice(&(source.backend as u32).to_le_bytes());
+    result.extend_from_slice(&source.vendor.to_le_bytes());
+    result.extend_from_slice(&source.device.to_le_bytes());
+    result.extend_from_slice(&source.validation_key);
+    result.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    result.extend_from_slice(&hash(data).to_le_bytes());
+    debug_assert_eq!(result.len(), HEADER_SIZE);
+    result.extend_from_slice(data);
+    result
+}
+
+/// Checks that the data was produced by `expected`, and returns the data
+/// to pass to the backend.
+pub(crate) fn validate<'a>(expected: &CacheSource, data: &'a [u8]) -> Result<&'a [u8], Error> {
+    if data.len() < HEADER_SIZE {
+        return Err(Error::Truncated);
+    }
+    let (header, payload) = data.split_at(HEADER_SIZE);
+
+    let read_u32 = |offset: usize| {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&header[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
+    };
+    let read_u64 = |offset: usize| {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&header[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    };
+
+    if header[..8] != MAGIC {
+        return Err(Error::UnknownFormat);
+    }
+    if read_u32(8) != VERSION {
+        return Err(Error::Outdated);
+    }
+    if read_u32(12) != expected.backend as u32
+        || read_u64(16) != expected.vendor
+        || read_u64(24) != expected.device
+        || header[32..48] != expected.validation_key
+    {
+        return Err(Error::DeviceMismatch);
+    }
+    let size = read_u64(48);
+    if size > payload.len() as u64 {
+        return Err(Error::Truncated);
+    }
+    if size != payload.len() as u64 || read_u64(56) != hash(payload) {
+        return Err(Error::Corrupted);
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: CacheSource = CacheSource {
+        backend: wgt::Backend::Vulkan,
+        vendor: 0x10de,
+        device: 0x2204,
+        validation_key: [7; 16],
+    };
+
+    #[test]
+    fn round_trip() {
+        let data = add_header(&SOURCE, b"pipelines");
+        assert_eq!(validate(&SOURCE, &data).unwrap(), b"pipelines");
+    }
+
+    #[test]
+    fn rejects_other_sources() {
+        let data = add_header(&SOURCE, b"pipelines");
+        let other_driver = CacheSource {
+            validation_key: [8; 16],
+            ..SOURCE
+        };
+        assert!(matches!(
+            validate(&other_driver, &data),
+            Err(Error::DeviceMismatch)
+        ));
+        let other_backend = CacheSource {
+            backend: wgt::Backend::Gl,
+            ..SOURCE
+        };
+        assert!(matches!(
+            validate(&other_backend, &data),
+            Err(Error::DeviceMismatch)
+        ));
+    }
+
+    #[test]
+    fn rejects_damaged_data() {
+        let mut data = add_header(&SOURCE, b"pipelines");
+        assert!(matches!(
+            validate(&SOURCE, &data[..HEADER_SIZE + 3]),
+            Err(Error::Truncated)
+        ));
+        assert!(matches!(
+            validate(&SOURCE, b"not a cache"),
+            Err(Error::Truncated)
+        ));
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(matches!(validate(&SOURCE, &data), Err(Error::Corrupted)));
+        data[0] = b'X';
+        assert!(matches!(
+            validate(&SOURCE, &data),
+            Err(Error::UnknownFormat)
+        ));
+    }
+}
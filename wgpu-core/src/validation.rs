@@ -101,19 +101,38 @@ enum Varying {
     BuiltIn(naga::BuiltIn),
 }
 
-#[allow(unused)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct SpecializationConstant {
     id: u32,
+    name: Option<String>,
     ty: NumericType,
 }
 
+impl SpecializationConstant {
+    fn matches(&self, key: &str) -> bool {
+        self.name.as_deref() == Some(key) || key.parse() == Ok(self.id)
+    }
+
+    /// Checks that the value is representable by the type of the constant.
+    fn accepts(&self, value: f64) -> bool {
+        match self.ty.kind {
+            naga::ScalarKind::Bool => true,
+            naga::ScalarKind::Float => value.is_finite(),
+            naga::ScalarKind::Sint => {
+                value.fract() == 0.0 && value >= i32::MIN as f64 && value <= i32::MAX as f64
+            }
+            naga::ScalarKind::Uint => {
+                value.fract() == 0.0 && value >= 0.0 && value <= u32::MAX as f64
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct EntryPoint {
     inputs: Vec<Varying>,
     outputs: Vec<Varying>,
     resources: Vec<(naga::Handle<Resource>, GlobalUse)>,
-    #[allow(unused)]
     spec_constants: Vec<SpecializationConstant>,
     sampling_pairs: FastHashSet<(naga::Handle<Resource>, naga::Handle<Resource>)>,
     workgroup_size: [u32; 3],
@@ -265,6 +284,14 @@ pub enum StageError {
     },
     #[error("location[{location}] is provided by the previous stage output but is not consumed as input by this stage.")]
     InputNotConsumed { location: wgt::ShaderLocation },
+    #[error("pipeline constant '{0}' doesn't match any overridable constant of the shader")]
+    UnknownConstant(String),
+    #[error("value {value} of pipeline constant '{key}' is not representable by its type {ty}")]
+    InvalidConstantValue {
+        key: String,
+        value: f64,
+        ty: NumericType,
+    },
 }
 
 fn map_storage_format_to_naga(format: wgt::TextureFormat) -> Option<naga::StorageFormat> {
@@ -910,6 +937,23 @@ impl Interface {
             resource_mapping.insert(var_handle, handle);
         }
 
+        let spec_constants = module
+            .constants
+            .iter()
+            .filter_map(|(_, constant)| match constant.inner {
+                naga::ConstantInner::Scalar { width, ref value } => Some(SpecializationConstant {
+                    id: constant.specialization?,
+                    name: constant.name.clone(),
+                    ty: NumericType {
+                        dim: NumericDimension::Scalar,
+                        kind: value.scalar_kind(),
+                        width,
+                    },
+                }),
+                naga::ConstantInner::Composite { .. } => None,
+            })
+            .collect::<Vec<_>>();
+
         let mut entry_points = FastHashMap::default();
         entry_points.reserve(module.entry_points.len());
         for (index, entry_point) in module.entry_points.iter().enumerate() {
@@ -943,6 +987,9 @@ impl Interface {
             }
 
             ep.workgroup_size = entry_point.workgroup_size;
+            // Naga doesn't track which constants an entry point uses,
+            // so all of them are considered to be overridable.
+            ep.spec_constants = spec_constants.clone();
 
             entry_points.insert((entry_point.stage, entry_point.name.clone()), ep);
         }
@@ -961,6 +1008,7 @@ impl Interface {
         derived_layouts: &mut [BindEntryMap],
         shader_binding_sizes: &mut FastHashMap<naga::ResourceBinding, wgt::BufferSize>,
         entry_point_name: &str,
+        constants: &hal::PipelineConstants,
         stage_bit: wgt::ShaderStages,
         inputs: StageIo,
     ) -> Result<StageIo, StageError> {
@@ -978,6 +1026,22 @@ impl Interface {
             .get(&pair)
             .ok_or(StageError::MissingEntryPoint(pair.1))?;
 
+        // check the pipeline constants
+        for (key, &value) in constants.iter() {
+            let sc = entry_point
+                .spec_constants
+                .iter()
+                .find(|sc| sc.matches(key))
+                .ok_or_else(|| StageError::UnknownConstant(key.clone()))?;
+            if !sc.accepts(value) {
+                return Err(StageError::InvalidConstantValue {
+                    key: key.clone(),
+                    value,
+                    ty: sc.ty,
+                });
+            }
+        }
+
         // check resources visibility
         for &(handle, usage) in entry_point.resources.iter() {
             let res = &self.resources[handle];
@@ -1204,3 +1268,96 @@ impl Interface {
         Ok(outputs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Interface, StageError, StageIo};
+    use crate::FastHashMap;
+
+    const SHADER: &str = "
+let SCALE: u32 = 2u;
+let OFFSET: i32 = -1;
+
+@compute @workgroup_size(1)
+fn main() {}
+";
+
+    /// Checks the compute stage, with `SCALE` and `OFFSET` overridable
+    /// with the IDs 0 and 1.
+    fn check(constants: &[(&str, f64)]) -> Result<StageIo, StageError> {
+        let mut module = naga::front::wgsl::parse_str(SHADER).unwrap();
+        // WGSL has no syntax for overridable constants yet.
+        let handles = module
+            .constants
+            .iter()
+            .filter_map(|(handle, constant)| match constant.name.as_deref() {
+                Some("SCALE") => Some((handle, 0)),
+                Some("OFFSET") => Some((handle, 1)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (handle, id) in handles {
+            module.constants.get_mut(handle).specialization = Some(id);
+        }
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+
+        let interface = Interface::new(
+            &module,
+            &info,
+            wgt::Features::empty(),
+            wgt::Limits::default(),
+        );
+        let constants = constants
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value))
+            .collect();
+        interface.check_stage(
+            None,
+            &mut [],
+            &mut FastHashMap::default(),
+            "main",
+            &constants,
+            wgt::ShaderStages::COMPUTE,
+            StageIo::default(),
+        )
+    }
+
+    #[test]
+    fn valid_constants() {
+        assert!(check(&[]).is_ok());
+        assert!(check(&[("SCALE", 3.0), ("OFFSET", -7.0)]).is_ok());
+        assert!(check(&[("0", 3.0), ("1", 0.0)]).is_ok());
+    }
+
+    #[test]
+    fn unknown_constant() {
+        for &key in ["MISSING", "2"].iter() {
+            match check(&[(key, 1.0)]) {
+                Err(StageError::UnknownConstant(ref name)) if name == key => {}
+                other => panic!("unexpected result for '{}': {:?}", key, other),
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_constant_value() {
+        let cases = [
+            ("SCALE", -1.0),
+            ("SCALE", 1.5),
+            ("SCALE", 4294967296.0),
+            ("OFFSET", 2147483648.0),
+            ("OFFSET", f64::NAN),
+        ];
+        for &(key, value) in cases.iter() {
+            match check(&[(key, value)]) {
+                Err(StageError::InvalidConstantValue { key: ref name, .. }) if name == key => {}
+                other => panic!("unexpected result for '{}' = {}: {:?}", key, value, other),
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use winapi::{
-    shared::{dxgi, dxgi1_2, dxgi1_4, dxgi1_6, winerror},
+    shared::{dxgi, dxgi1_2, dxgi1_4, dxgi1_6, windef, winerror},
     Interface,
 };
 
@@ -208,3 +208,18 @@ pub fn create_factory(
     // We tried to create 4 and 2, but only succeeded with 1.
     Ok((lib_dxgi, native::DxgiFactory::Factory1(factory1)))
 }
+
+/// Disables the automatic Alt+Enter handling of DXGI for the window of a swap chain.
+pub fn make_window_association(factory: native::DxgiFactory, wnd_handle: windef::HWND) {
+    const DXGI_MWA_NO_WINDOW_CHANGES: u32 = 1;
+    const DXGI_MWA_NO_ALT_ENTER: u32 = 2;
+    let hr = unsafe {
+        factory.MakeWindowAssociation(
+            wnd_handle,
+            DXGI_MWA_NO_WINDOW_CHANGES | DXGI_MWA_NO_ALT_ENTER,
+        )
+    };
+    if let Err(err) = hr.into_result() {
+        log::warn!("Unable to make the window association: {}", err);
+    }
+}
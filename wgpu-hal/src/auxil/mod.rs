@@ -4,6 +4,8 @@ pub(super) mod dxgi;
 #[cfg(feature = "renderdoc")]
 pub(super) mod renderdoc;
 
+use std::borrow::Cow;
+
 pub mod db {
     pub mod intel {
         pub const VENDOR: u32 = 0x8086;
@@ -32,6 +34,50 @@ pub fn map_naga_stage(stage: naga::ShaderStage) -> wgt::ShaderStages {
     }
 }
 
+/// Substitutes the pipeline-overridable constants of the module with the given values,
+/// turning them into regular constants.
+///
+/// Constants missing from `constants` keep their default value. The module is only
+/// cloned if there is anything to substitute.
+pub fn apply_pipeline_constants<'a>(
+    module: &'a naga::Module,
+    constants: &crate::PipelineConstants,
+) -> Cow<'a, naga::Module> {
+    if constants.is_empty() {
+        return Cow::Borrowed(module);
+    }
+
+    let mut module = module.clone();
+    let overridden = module
+        .constants
+        .iter()
+        .filter_map(|(handle, constant)| {
+            let id = constant.specialization?;
+            let value = constants
+                .get(&id.to_string())
+                .or_else(|| constant.name.as_ref().and_then(|name| constants.get(name)))?;
+            Some((handle, *value))
+        })
+        .collect::<Vec<_>>();
+    for (handle, value) in overridden {
+        let constant = module.constants.get_mut(handle);
+        constant.specialization = None;
+        if let naga::ConstantInner::Scalar {
+            value: ref mut scalar,
+            ..
+        } = constant.inner
+        {
+            *scalar = match *scalar {
+                naga::ScalarValue::Sint(_) => naga::ScalarValue::Sint(value as i64),
+                naga::ScalarValue::Uint(_) => naga::ScalarValue::Uint(value as u64),
+                naga::ScalarValue::Float(_) => naga::ScalarValue::Float(value),
+                naga::ScalarValue::Bool(_) => naga::ScalarValue::Bool(value != 0.0),
+            };
+        }
+    }
+    Cow::Owned(module)
+}
+
 pub fn align_to(value: u32, alignment: u32) -> u32 {
     if alignment.is_power_of_two() {
         (value + alignment - 1) & !(alignment - 1)
@@ -1,28 +1,196 @@
-use std::num::NonZeroU64;
+use crate::auxil::{self, dxgi::result::HResult as _};
 
-use winapi::um::{d3d11, d3dcommon};
+use parking_lot::Mutex;
+use std::{mem, num::NonZeroU64, sync::Arc};
+use winapi::{
+    shared::{windef, winerror},
+    um::{d3d11, d3dcommon, winuser},
+};
 
 impl crate::Adapter<super::Api> for super::Adapter {
     unsafe fn open(
         &self,
-        features: wgt::Features,
-        limits: &wgt::Limits,
+        _features: wgt::Features,
+        _limits: &wgt::Limits,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
-        todo!()
+        // The device and the immediate context stay owned by the adapter as well.
+        self.device.AddRef();
+        self.context.AddRef();
+        let shared = Arc::new(super::DeviceShared {
+            raw: self.device,
+            context: Mutex::new(self.context),
+            private_caps: self.private_caps.clone(),
+        });
+
+        let query_desc = d3d11::D3D11_QUERY_DESC {
+            Query: d3d11::D3D11_QUERY_TIMESTAMP_DISJOINT,
+            MiscFlags: 0,
+        };
+        let mut disjoint_query = native::WeakPtr::<d3d11::ID3D11Query>::null();
+        self.device
+            .CreateQuery(&query_desc, disjoint_query.mut_self())
+            .into_device_result("Disjoint query creation")?;
+
+        // The timestamp frequency can only be queried with a disjoint query.
+        let frequency = {
+            let context = shared.context.lock();
+            context.Begin(disjoint_query.as_mut_ptr() as *mut _);
+            context.End(disjoint_query.as_mut_ptr() as *mut _);
+            let data = super::DeviceShared::get_query_data::<
+                d3d11::D3D11_QUERY_DATA_TIMESTAMP_DISJOINT,
+            >(&context, disjoint_query);
+            match data {
+                Ok(data) => data.Frequency,
+                Err(err) => {
+                    disjoint_query.destroy();
+                    return Err(err);
+                }
+            }
+        };
+        let timestamp_period = if frequency == 0 {
+            1.0
+        } else {
+            (1_000_000_000.0 / frequency as f64) as f32
+        };
+
+        let device = super::Device {
+            shared: Arc::clone(&shared),
+        };
+        let queue = super::Queue {
+            shared,
+            disjoint_query,
+            timestamp_period,
+        };
+
+        Ok(crate::OpenDevice { device, queue })
     }
 
+    #[allow(trivial_casts)]
     unsafe fn texture_format_capabilities(
         &self,
         format: wgt::TextureFormat,
     ) -> crate::TextureFormatCapabilities {
-        todo!()
+        use crate::TextureFormatCapabilities as Tfc;
+
+        let raw_format = auxil::dxgi::conv::map_texture_format(format);
+        let mut support = 0;
+        if self.device.CheckFormatSupport(raw_format, &mut support) != winerror::S_OK {
+            return Tfc::empty();
+        }
+        let mut data = d3d11::D3D11_FEATURE_DATA_FORMAT_SUPPORT2 {
+            InFormat: raw_format,
+            OutFormatSupport2: 0,
+        };
+        let hr = self.device.CheckFeatureSupport(
+            d3d11::D3D11_FEATURE_FORMAT_SUPPORT2,
+            &mut data as *mut _ as *mut _,
+            mem::size_of::<d3d11::D3D11_FEATURE_DATA_FORMAT_SUPPORT2>() as _,
+        );
+        let support2 = if hr == winerror::S_OK {
+            data.OutFormatSupport2
+        } else {
+            0
+        };
+
+        // The copies are done by the CPU or through the staging resources.
+        let mut caps = Tfc::COPY_SRC | Tfc::COPY_DST;
+        let is_texture = support
+            & (d3d11::D3D11_FORMAT_SUPPORT_TEXTURE1D
+                | d3d11::D3D11_FORMAT_SUPPORT_TEXTURE2D
+                | d3d11::D3D11_FORMAT_SUPPORT_TEXTURE3D
+                | d3d11::D3D11_FORMAT_SUPPORT_TEXTURECUBE)
+            != 0;
+        caps.set(
+            Tfc::SAMPLED,
+            is_texture && support & d3d11::D3D11_FORMAT_SUPPORT_SHADER_LOAD != 0,
+        );
+        caps.set(
+            Tfc::SAMPLED_LINEAR,
+            support & d3d11::D3D11_FORMAT_SUPPORT_SHADER_SAMPLE != 0,
+        );
+        caps.set(
+            Tfc::COLOR_ATTACHMENT,
+            support & d3d11::D3D11_FORMAT_SUPPORT_RENDER_TARGET != 0,
+        );
+        caps.set(
+            Tfc::COLOR_ATTACHMENT_BLEND,
+            support & d3d11::D3D11_FORMAT_SUPPORT_BLENDABLE != 0,
+        );
+        caps.set(
+            Tfc::DEPTH_STENCIL_ATTACHMENT,
+            support & d3d11::D3D11_FORMAT_SUPPORT_DEPTH_STENCIL != 0,
+        );
+        caps.set(
+            Tfc::STORAGE,
+            support & d3d11::D3D11_FORMAT_SUPPORT_TYPED_UNORDERED_ACCESS_VIEW != 0,
+        );
+        caps.set(
+            Tfc::STORAGE_READ_WRITE,
+            support2 & d3d11::D3D11_FORMAT_SUPPORT2_UAV_TYPED_LOAD != 0,
+        );
+
+        let no_msaa_load = caps.contains(Tfc::SAMPLED)
+            && support & d3d11::D3D11_FORMAT_SUPPORT_MULTISAMPLE_LOAD == 0;
+        let no_msaa_target = support
+            & (d3d11::D3D11_FORMAT_SUPPORT_RENDER_TARGET
+                | d3d11::D3D11_FORMAT_SUPPORT_DEPTH_STENCIL)
+            != 0
+            && support & d3d11::D3D11_FORMAT_SUPPORT_MULTISAMPLE_RENDERTARGET == 0;
+        caps.set(Tfc::MULTISAMPLE, !no_msaa_load && !no_msaa_target);
+        caps.set(
+            Tfc::MULTISAMPLE_RESOLVE,
+            support & d3d11::D3D11_FORMAT_SUPPORT_MULTISAMPLE_RESOLVE != 0,
+        );
+
+        caps
     }
 
     unsafe fn surface_capabilities(
         &self,
         surface: &super::Surface,
     ) -> Option<crate::SurfaceCapabilities> {
-        todo!()
+        let current_extent = {
+            let mut rect: windef::RECT = mem::zeroed();
+            if winuser::GetClientRect(surface.wnd_handle, &mut rect) != 0 {
+                Some(wgt::Extent3d {
+                    width: (rect.right - rect.left) as u32,
+                    height: (rect.bottom - rect.top) as u32,
+                    depth_or_array_layers: 1,
+                })
+            } else {
+                log::warn!("Unable to get the window client rect");
+                None
+            }
+        };
+
+        Some(crate::SurfaceCapabilities {
+            formats: vec![
+                wgt::TextureFormat::Bgra8UnormSrgb,
+                wgt::TextureFormat::Bgra8Unorm,
+                wgt::TextureFormat::Rgba8UnormSrgb,
+                wgt::TextureFormat::Rgba8Unorm,
+                wgt::TextureFormat::Rgb10a2Unorm,
+                wgt::TextureFormat::Rgba16Float,
+            ],
+            // we use the blit model, only the first buffer is ever rendered to
+            swap_chain_sizes: 1..=16,
+            current_extent,
+            // TODO: figure out the exact bounds
+            extents: wgt::Extent3d {
+                width: 16,
+                height: 16,
+                depth_or_array_layers: 1,
+            }..=wgt::Extent3d {
+                width: 4096,
+                height: 4096,
+                depth_or_array_layers: 1,
+            },
+            usage: crate::TextureUses::COLOR_TARGET
+                | crate::TextureUses::COPY_SRC
+                | crate::TextureUses::COPY_DST,
+            present_modes: vec![wgt::PresentMode::Fifo, wgt::PresentMode::Immediate],
+            composite_alpha_modes: vec![crate::CompositeAlphaMode::Opaque],
+        })
     }
 }
 
@@ -30,6 +198,7 @@ impl super::Adapter {
     pub(super) fn expose(
         instance: &super::library::D3D11Lib,
         adapter: native::DxgiAdapter,
+        instance_flags: crate::InstanceFlags,
     ) -> Option<crate::ExposedAdapter<super::Api>> {
         use d3dcommon::{
             D3D_FEATURE_LEVEL_10_0 as FL10_0, D3D_FEATURE_LEVEL_10_1 as FL10_1,
@@ -38,7 +207,7 @@ impl super::Adapter {
             D3D_FEATURE_LEVEL_9_3 as FL9_3,
         };
 
-        let (device, feature_level) = instance.create_device(adapter)?;
+        let (device, context, feature_level) = instance.create_device(adapter)?;
 
         //
         // Query Features from d3d11
@@ -85,8 +254,8 @@ impl super::Adapter {
         //
         // TODO(cwfitzgerald): Needed downlevel features: 3D dispatch
 
+        // Naga can't emit push constants for D3D11, they are not exposed.
         let mut features = wgt::Features::DEPTH_CLIP_CONTROL
-            | wgt::Features::PUSH_CONSTANTS
             | wgt::Features::POLYGON_MODE_LINE
             | wgt::Features::CLEAR_TEXTURE
             | wgt::Features::TEXTURE_FORMAT_16BIT_NORM
@@ -118,7 +287,8 @@ impl super::Adapter {
         if feature_level >= FL10_0 {
             downlevel |= wgt::DownlevelFlags::INDEPENDENT_BLEND;
             downlevel |= wgt::DownlevelFlags::FRAGMENT_STORAGE;
-            downlevel |= wgt::DownlevelFlags::FRAGMENT_WRITABLE_STORAGE;
+            // Unordered access views are only bound in compute passes,
+            // so there is no FRAGMENT_WRITABLE_STORAGE.
             features |= wgt::Features::DEPTH_CLIP_CONTROL;
             features |= wgt::Features::TIMESTAMP_QUERY;
             features |= wgt::Features::PIPELINE_STATISTICS_QUERY;
@@ -198,7 +368,15 @@ impl super::Adapter {
             max_texture_dimension_3d,
             max_texture_array_layers: max_texture_dimension_3d,
             max_bind_groups: u32::MAX,
-            max_dynamic_uniform_buffers_per_pipeline_layout: max_constant_buffers,
+            // Dynamic offsets need `*SetConstantBuffers1`.
+            max_dynamic_uniform_buffers_per_pipeline_layout: if d3d11_features
+                .ConstantBufferOffsetting
+                != 0
+            {
+                max_constant_buffers
+            } else {
+                0
+            },
             max_dynamic_storage_buffers_per_pipeline_layout: 0,
             max_sampled_textures_per_shader_stage: max_sampled_textures,
             max_samplers_per_shader_stage: max_samplers,
@@ -210,7 +388,7 @@ impl super::Adapter {
             max_vertex_buffers,
             max_vertex_attributes: max_vertex_buffers,
             max_vertex_buffer_array_stride: u32::MAX,
-            max_push_constant_size: 1 << 16,
+            max_push_constant_size: 0,
             min_uniform_buffer_offset_alignment: 256,
             min_storage_buffer_offset_alignment: 1,
             max_inter_stage_shader_components: max_output_registers,
@@ -248,11 +426,30 @@ impl super::Adapter {
         // Build up the structs
         //
 
-        let api_adapter = super::Adapter { device };
+        let private_caps = super::PrivateCapabilities {
+            instance_flags,
+            feature_level,
+            shader_profile: match feature_level {
+                FL9_1 | FL9_2 => "4_0_level_9_1",
+                FL9_3 => "4_0_level_9_3",
+                FL10_0 => "4_0",
+                FL10_1 => "4_1",
+                _ => "5_0",
+            },
+            constant_buffer_offsets: d3d11_features.ConstantBufferOffsetting != 0,
+            constant_buffer_partial_update: d3d11_features.ConstantBufferPartialUpdate != 0,
+        };
+
+        let api_adapter = super::Adapter {
+            device,
+            context,
+            private_caps,
+        };
 
+        // Buffer-texture copies are done by the CPU, so there is no alignment requirement.
         let alignments = crate::Alignments {
-            buffer_copy_offset: NonZeroU64::new(1).unwrap(), // todo
-            buffer_copy_pitch: NonZeroU64::new(1).unwrap(),  // todo
+            buffer_copy_offset: NonZeroU64::new(1).unwrap(),
+            buffer_copy_pitch: NonZeroU64::new(1).unwrap(),
         };
 
         let capabilities = crate::Capabilities {
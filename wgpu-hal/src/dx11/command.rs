@@ -1,39 +1,277 @@
+use crate::auxil::{self, dxgi::result::HResult as _};
+
+use arrayvec::ArrayVec;
+use std::{mem, ops::Range, ptr};
+use winapi::um::d3d11;
+
+impl super::CommandEncoder {
+    fn prepare_marker(&mut self, marker: &str) -> *const u16 {
+        self.temp_marker.clear();
+        self.temp_marker.extend(marker.encode_utf16());
+        self.temp_marker.push(0);
+        self.temp_marker.as_ptr()
+    }
+
+    /// Closes the command list recorded so far.
+    unsafe fn finish_commands(&mut self) -> Result<(), crate::DeviceError> {
+        let mut list = native::WeakPtr::<d3d11::ID3D11CommandList>::null();
+        self.context
+            .FinishCommandList(0, list.mut_self())
+            .into_device_result("FinishCommandList")?;
+        self.steps.push(super::Step::Commands(list));
+        Ok(())
+    }
+
+    unsafe fn push_transfer(&mut self, transfer: super::Transfer) {
+        // The transfer has to execute after the commands recorded before it.
+        if self.finish_commands().is_ok() {
+            self.steps.push(super::Step::Transfer(transfer));
+        }
+    }
+
+    unsafe fn begin_pass(&mut self, kind: super::PassKind, label: crate::Label) {
+        self.context.ClearState();
+        self.pass.clear();
+        self.pass.kind = kind;
+        if let Some(label) = label {
+            if !self.annotation.is_null() {
+                let wide_label = self.prepare_marker(label);
+                self.annotation.BeginEvent(wide_label);
+                self.pass.has_label = true;
+            }
+        }
+    }
+
+    unsafe fn end_pass(&mut self) {
+        if self.pass.has_label {
+            self.annotation.EndEvent();
+        }
+        self.pass.clear();
+    }
+
+    unsafe fn bind_special_constants(&mut self, slot: Option<u32>) {
+        self.pass.special_constants_slot = slot;
+        // Force an update, the values of another layout may be stale.
+        self.pass.special_constants = None;
+        if let Some(slot) = slot {
+            let buffers = [self.special_constants_buffer.as_mut_ptr()];
+            match self.pass.kind {
+                super::PassKind::Render => {
+                    self.context.VSSetConstantBuffers(slot, 1, buffers.as_ptr())
+                }
+                super::PassKind::Compute => {
+                    self.context.CSSetConstantBuffers(slot, 1, buffers.as_ptr())
+                }
+                super::PassKind::Transfer => {}
+            }
+        }
+    }
+
+    unsafe fn update_special_constants(&mut self, values: [u32; 3]) {
+        if self.pass.special_constants_slot.is_none() || self.pass.special_constants == Some(values)
+        {
+            return;
+        }
+        let data = [values[0], values[1], values[2], 0];
+        self.context.UpdateSubresource(
+            self.special_constants_buffer.as_mut_ptr() as *mut _,
+            0,
+            ptr::null(),
+            data.as_ptr() as *const _,
+            0,
+            0,
+        );
+        self.pass.special_constants = Some(values);
+    }
+
+    /// Copies the special constants from the arguments of an indirect command,
+    /// which are only known by the GPU.
+    unsafe fn copy_special_constants(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        size: u32,
+    ) {
+        if self.pass.special_constants_slot.is_none() {
+            return;
+        }
+        let src_box = d3d11::D3D11_BOX {
+            left: offset as u32,
+            top: 0,
+            front: 0,
+            right: offset as u32 + size,
+            bottom: 1,
+            back: 1,
+        };
+        self.context.CopySubresourceRegion(
+            self.special_constants_buffer.as_mut_ptr() as *mut _,
+            0,
+            0,
+            0,
+            0,
+            buffer.resource.as_mut_ptr() as *mut _,
+            0,
+            &src_box,
+        );
+        self.pass.special_constants = None;
+    }
+
+    unsafe fn flush_vertex_buffers(&mut self) {
+        while self.pass.dirty_vertex_buffers != 0 {
+            let index = self.pass.dirty_vertex_buffers.trailing_zeros();
+            self.pass.dirty_vertex_buffers ^= 1 << index;
+            let vb = &self.pass.vertex_buffers[index as usize];
+            let buffers = [vb.buffer.as_mut_ptr()];
+            self.context.IASetVertexBuffers(
+                index,
+                1,
+                buffers.as_ptr(),
+                &self.pass.vertex_strides[index as usize],
+                &vb.offset,
+            );
+        }
+    }
+
+    unsafe fn prepare_draw(&mut self, base_vertex: i32, base_instance: u32) {
+        self.flush_vertex_buffers();
+        self.update_special_constants([base_vertex as u32, base_instance, 0]);
+    }
+
+    unsafe fn set_blend_state(&mut self) {
+        self.context.OMSetBlendState(
+            self.pass.blend_state.as_mut_ptr(),
+            &self.pass.blend_constants,
+            self.pass.sample_mask,
+        );
+    }
+
+    unsafe fn set_depth_stencil_state(&mut self) {
+        self.context.OMSetDepthStencilState(
+            self.pass.depth_stencil_state.as_mut_ptr(),
+            self.pass.stencil_reference,
+        );
+    }
+
+    unsafe fn set_constant_buffer(
+        &mut self,
+        slot: u32,
+        binding: &super::ConstantBufferBinding,
+        first_constant: u32,
+    ) {
+        let context = self.context;
+        let buffers = [binding.raw.as_mut_ptr()];
+        let use_offsets =
+            self.shared.private_caps.constant_buffer_offsets && !self.context1.is_null();
+        let visibility = binding.visibility;
+        let num_constants = binding.num_constants;
+
+        match self.pass.kind {
+            super::PassKind::Render if use_offsets => {
+                if visibility.contains(wgt::ShaderStages::VERTEX) {
+                    self.context1.VSSetConstantBuffers1(
+                        slot,
+                        1,
+                        buffers.as_ptr(),
+                        &first_constant,
+                        &num_constants,
+                    );
+                }
+                if visibility.contains(wgt::ShaderStages::FRAGMENT) {
+                    self.context1.PSSetConstantBuffers1(
+                        slot,
+                        1,
+                        buffers.as_ptr(),
+                        &first_constant,
+                        &num_constants,
+                    );
+                }
+            }
+            super::PassKind::Render => {
+                if visibility.contains(wgt::ShaderStages::VERTEX) {
+                    context.VSSetConstantBuffers(slot, 1, buffers.as_ptr());
+                }
+                if visibility.contains(wgt::ShaderStages::FRAGMENT) {
+                    context.PSSetConstantBuffers(slot, 1, buffers.as_ptr());
+                }
+            }
+            super::PassKind::Compute if use_offsets => {
+                if visibility.contains(wgt::ShaderStages::COMPUTE) {
+                    self.context1.CSSetConstantBuffers1(
+                        slot,
+                        1,
+                        buffers.as_ptr(),
+                        &first_constant,
+                        &num_constants,
+                    );
+                }
+            }
+            super::PassKind::Compute => {
+                if visibility.contains(wgt::ShaderStages::COMPUTE) {
+                    context.CSSetConstantBuffers(slot, 1, buffers.as_ptr());
+                }
+            }
+            super::PassKind::Transfer => {}
+        }
+    }
+}
+
 impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     unsafe fn begin_encoding(&mut self, label: crate::Label) -> Result<(), crate::DeviceError> {
-        todo!()
+        self.pass.clear();
+        super::device::set_debug_name(&self.context, label);
+        Ok(())
     }
 
     unsafe fn discard_encoding(&mut self) {
-        todo!()
+        let mut list = native::WeakPtr::<d3d11::ID3D11CommandList>::null();
+        if self.context.FinishCommandList(0, list.mut_self()) == 0 {
+            list.destroy();
+        }
+        for step in self.steps.drain(..) {
+            if let super::Step::Commands(list) = step {
+                list.destroy();
+            }
+        }
     }
 
     unsafe fn end_encoding(&mut self) -> Result<super::CommandBuffer, crate::DeviceError> {
-        todo!()
+        self.finish_commands()?;
+        Ok(super::CommandBuffer {
+            steps: mem::take(&mut self.steps),
+        })
     }
 
     unsafe fn reset_all<I>(&mut self, command_buffers: I)
     where
         I: Iterator<Item = super::CommandBuffer>,
     {
-        todo!()
+        for cmd_buf in command_buffers {
+            for step in cmd_buf.steps {
+                if let super::Step::Commands(list) = step {
+                    list.destroy();
+                }
+            }
+        }
     }
 
-    unsafe fn transition_buffers<'a, T>(&mut self, barriers: T)
+    unsafe fn transition_buffers<'a, T>(&mut self, _barriers: T)
     where
         T: Iterator<Item = crate::BufferBarrier<'a, super::Api>>,
     {
-        todo!()
+        // D3D11 tracks the resource states by itself.
     }
 
-    unsafe fn transition_textures<'a, T>(&mut self, barriers: T)
+    unsafe fn transition_textures<'a, T>(&mut self, _barriers: T)
     where
         T: Iterator<Item = crate::TextureBarrier<'a, super::Api>>,
     {
-        todo!()
     }
 
     unsafe fn clear_buffer(&mut self, buffer: &super::Buffer, range: crate::MemoryRange) {
-        todo!()
+        self.push_transfer(super::Transfer::FillBuffer {
+            dst: buffer.clone(),
+            range,
+        });
     }
 
     unsafe fn copy_buffer_to_buffer<T>(
@@ -44,19 +282,73 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferCopy>,
     {
-        todo!()
+        if src.host.is_some() || dst.host.is_some() {
+            self.push_transfer(super::Transfer::CopyBuffer {
+                src: src.clone(),
+                dst: dst.clone(),
+                regions: regions.collect(),
+            });
+            return;
+        }
+
+        for r in regions {
+            let src_box = d3d11::D3D11_BOX {
+                left: r.src_offset as u32,
+                top: 0,
+                front: 0,
+                right: (r.src_offset + r.size.get()) as u32,
+                bottom: 1,
+                back: 1,
+            };
+            self.context.CopySubresourceRegion(
+                dst.resource.as_mut_ptr() as *mut _,
+                0,
+                r.dst_offset as u32,
+                0,
+                0,
+                src.resource.as_mut_ptr() as *mut _,
+                0,
+                &src_box,
+            );
+        }
     }
 
     unsafe fn copy_texture_to_texture<T>(
         &mut self,
         src: &super::Texture,
-        src_usage: crate::TextureUses,
+        _src_usage: crate::TextureUses,
         dst: &super::Texture,
         regions: T,
     ) where
         T: Iterator<Item = crate::TextureCopy>,
     {
-        todo!()
+        for r in regions {
+            // 3D textures are copied in one go, the array layers one by one.
+            let (layers, depth) = match src.dimension {
+                wgt::TextureDimension::D3 => (1, r.size.depth),
+                wgt::TextureDimension::D1 | wgt::TextureDimension::D2 => (r.size.depth, 1),
+            };
+            let src_box = d3d11::D3D11_BOX {
+                left: r.src_base.origin.x,
+                top: r.src_base.origin.y,
+                front: r.src_base.origin.z,
+                right: r.src_base.origin.x + r.size.width,
+                bottom: r.src_base.origin.y + r.size.height,
+                back: r.src_base.origin.z + depth,
+            };
+            for layer in 0..layers {
+                self.context.CopySubresourceRegion(
+                    dst.resource.as_mut_ptr(),
+                    dst.calc_subresource(r.dst_base.mip_level, r.dst_base.array_layer + layer),
+                    r.dst_base.origin.x,
+                    r.dst_base.origin.y,
+                    r.dst_base.origin.z,
+                    src.resource.as_mut_ptr(),
+                    src.calc_subresource(r.src_base.mip_level, r.src_base.array_layer + layer),
+                    &src_box,
+                );
+            }
+        }
     }
 
     unsafe fn copy_buffer_to_texture<T>(
@@ -67,19 +359,27 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferTextureCopy>,
     {
-        todo!()
+        self.push_transfer(super::Transfer::CopyBufferToTexture {
+            src: src.clone(),
+            dst: dst.clone(),
+            regions: regions.collect(),
+        });
     }
 
     unsafe fn copy_texture_to_buffer<T>(
         &mut self,
         src: &super::Texture,
-        src_usage: crate::TextureUses,
+        _src_usage: crate::TextureUses,
         dst: &super::Buffer,
         regions: T,
     ) where
         T: Iterator<Item = crate::BufferTextureCopy>,
     {
-        todo!()
+        self.push_transfer(super::Transfer::CopyTextureToBuffer {
+            src: src.clone(),
+            dst: dst.clone(),
+            regions: regions.collect(),
+        });
     }
 
     unsafe fn set_bind_group(
@@ -89,68 +389,257 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         group: &super::BindGroup,
         dynamic_offsets: &[wgt::DynamicOffset],
     ) {
-        todo!()
+        let base = layout.bind_group_bases[index as usize];
+        let context = self.context;
+        let (vertex, fragment, compute) = match self.pass.kind {
+            super::PassKind::Render => (true, true, false),
+            super::PassKind::Compute => (false, false, true),
+            super::PassKind::Transfer => (false, false, false),
+        };
+
+        let mut dynamic_offsets = dynamic_offsets.iter();
+        for (i, cb) in group.constant_buffers.iter().enumerate() {
+            let mut first_constant = cb.first_constant;
+            if cb.dynamic {
+                first_constant += *dynamic_offsets.next().unwrap() / 16;
+            }
+            self.set_constant_buffer(base.constant_buffers + i as u32, cb, first_constant);
+        }
+
+        for (i, binding) in group.shader_resources.iter().enumerate() {
+            let slot = base.shader_resources + i as u32;
+            let views = [binding.raw.as_mut_ptr()];
+            if vertex && binding.visibility.contains(wgt::ShaderStages::VERTEX) {
+                context.VSSetShaderResources(slot, 1, views.as_ptr());
+            }
+            if fragment && binding.visibility.contains(wgt::ShaderStages::FRAGMENT) {
+                context.PSSetShaderResources(slot, 1, views.as_ptr());
+            }
+            if compute && binding.visibility.contains(wgt::ShaderStages::COMPUTE) {
+                context.CSSetShaderResources(slot, 1, views.as_ptr());
+            }
+        }
+
+        for (i, binding) in group.samplers.iter().enumerate() {
+            let slot = base.samplers + i as u32;
+            let samplers = [binding.raw.as_mut_ptr()];
+            if vertex && binding.visibility.contains(wgt::ShaderStages::VERTEX) {
+                context.VSSetSamplers(slot, 1, samplers.as_ptr());
+            }
+            if fragment && binding.visibility.contains(wgt::ShaderStages::FRAGMENT) {
+                context.PSSetSamplers(slot, 1, samplers.as_ptr());
+            }
+            if compute && binding.visibility.contains(wgt::ShaderStages::COMPUTE) {
+                context.CSSetSamplers(slot, 1, samplers.as_ptr());
+            }
+        }
+
+        // Unordered access views are only bound in the compute passes, see the module docs.
+        if compute {
+            for (i, binding) in group.unordered_access.iter().enumerate() {
+                if binding.visibility.contains(wgt::ShaderStages::COMPUTE) {
+                    let views = [binding.raw.as_mut_ptr()];
+                    context.CSSetUnorderedAccessViews(
+                        base.unordered_access + i as u32,
+                        1,
+                        views.as_ptr(),
+                        ptr::null(),
+                    );
+                }
+            }
+        }
     }
 
     unsafe fn set_push_constants(
         &mut self,
-        layout: &super::PipelineLayout,
-        stages: wgt::ShaderStages,
-        offset: u32,
-        data: &[u32],
+        _layout: &super::PipelineLayout,
+        _stages: wgt::ShaderStages,
+        _offset: u32,
+        _data: &[u32],
     ) {
-        todo!()
+        unreachable!("PUSH_CONSTANTS is not exposed by the D3D11 backend")
     }
 
     unsafe fn insert_debug_marker(&mut self, label: &str) {
-        todo!()
+        if !self.annotation.is_null() {
+            let wide_label = self.prepare_marker(label);
+            self.annotation.SetMarker(wide_label);
+        }
     }
 
     unsafe fn begin_debug_marker(&mut self, group_label: &str) {
-        todo!()
+        if !self.annotation.is_null() {
+            let wide_label = self.prepare_marker(group_label);
+            self.annotation.BeginEvent(wide_label);
+        }
     }
 
     unsafe fn end_debug_marker(&mut self) {
-        todo!()
+        if !self.annotation.is_null() {
+            self.annotation.EndEvent();
+        }
     }
 
     unsafe fn begin_query(&mut self, set: &super::QuerySet, index: u32) {
-        todo!()
+        self.context
+            .Begin(set.raw[index as usize].as_mut_ptr() as *mut _);
     }
 
     unsafe fn end_query(&mut self, set: &super::QuerySet, index: u32) {
-        todo!()
+        self.context
+            .End(set.raw[index as usize].as_mut_ptr() as *mut _);
     }
 
     unsafe fn write_timestamp(&mut self, set: &super::QuerySet, index: u32) {
-        todo!()
+        self.context
+            .End(set.raw[index as usize].as_mut_ptr() as *mut _);
     }
 
-    unsafe fn reset_queries(&mut self, set: &super::QuerySet, range: std::ops::Range<u32>) {
-        todo!()
+    unsafe fn reset_queries(&mut self, _set: &super::QuerySet, _range: Range<u32>) {
+        // nothing to do here
     }
 
     unsafe fn copy_query_results(
         &mut self,
         set: &super::QuerySet,
-        range: std::ops::Range<u32>,
+        range: Range<u32>,
         buffer: &super::Buffer,
         offset: wgt::BufferAddress,
         stride: wgt::BufferSize,
     ) {
-        todo!()
+        self.push_transfer(super::Transfer::CopyQueryResults {
+            queries: set.raw[range.start as usize..range.end as usize].to_vec(),
+            ty: set.ty,
+            dst: buffer.clone(),
+            offset,
+            stride,
+        });
     }
 
+    // render
+
     unsafe fn begin_render_pass(&mut self, desc: &crate::RenderPassDescriptor<super::Api>) {
-        todo!()
+        self.begin_pass(super::PassKind::Render, desc.label);
+        let context = self.context;
+
+        let mut color_views =
+            ArrayVec::<*mut d3d11::ID3D11RenderTargetView, { crate::MAX_COLOR_ATTACHMENTS }>::new();
+        for cat in desc.color_attachments {
+            let rtv = cat.target.view.rtv;
+            if !cat.ops.contains(crate::AttachmentOps::LOAD) {
+                let value = [
+                    cat.clear_value.r as f32,
+                    cat.clear_value.g as f32,
+                    cat.clear_value.b as f32,
+                    cat.clear_value.a as f32,
+                ];
+                context.ClearRenderTargetView(rtv.as_mut_ptr(), &value);
+            }
+            color_views.push(rtv.as_mut_ptr());
+
+            if let Some(ref target) = cat.resolve_target {
+                self.pass.resolves.push(super::Resolve {
+                    src: (
+                        cat.target.view.texture.resource,
+                        cat.target.view.subresource,
+                    ),
+                    dst: (target.view.texture.resource, target.view.subresource),
+                    format: target.view.format,
+                });
+            }
+        }
+
+        let ds_view = match desc.depth_stencil_attachment {
+            None => ptr::null_mut(),
+            Some(ref ds) => {
+                let view = ds.target.view;
+                let dsv = if ds
+                    .target
+                    .usage
+                    .contains(crate::TextureUses::DEPTH_STENCIL_WRITE)
+                    || view.dsv_ro.is_null()
+                {
+                    view.dsv_rw
+                } else {
+                    view.dsv_ro
+                };
+
+                let mut flags = 0;
+                if !ds.depth_ops.contains(crate::AttachmentOps::LOAD) {
+                    flags |= d3d11::D3D11_CLEAR_DEPTH;
+                }
+                if !ds.stencil_ops.contains(crate::AttachmentOps::LOAD) {
+                    flags |= d3d11::D3D11_CLEAR_STENCIL;
+                }
+                if flags != 0 {
+                    context.ClearDepthStencilView(
+                        dsv.as_mut_ptr(),
+                        flags,
+                        ds.clear_value.0,
+                        ds.clear_value.1 as u8,
+                    );
+                }
+                dsv.as_mut_ptr()
+            }
+        };
+
+        context.OMSetRenderTargets(color_views.len() as u32, color_views.as_ptr(), ds_view);
+
+        let raw_vp = d3d11::D3D11_VIEWPORT {
+            TopLeftX: 0.0,
+            TopLeftY: 0.0,
+            Width: desc.extent.width as f32,
+            Height: desc.extent.height as f32,
+            MinDepth: 0.0,
+            MaxDepth: 1.0,
+        };
+        let raw_rect = d3d11::D3D11_RECT {
+            left: 0,
+            top: 0,
+            right: desc.extent.width as i32,
+            bottom: desc.extent.height as i32,
+        };
+        context.RSSetViewports(1, &raw_vp);
+        context.RSSetScissorRects(1, &raw_rect);
     }
 
     unsafe fn end_render_pass(&mut self) {
-        todo!()
+        // Unbind the targets before resolving them.
+        self.context
+            .OMSetRenderTargets(0, ptr::null(), ptr::null_mut());
+        for resolve in self.pass.resolves.iter() {
+            self.context.ResolveSubresource(
+                resolve.dst.0.as_mut_ptr(),
+                resolve.dst.1,
+                resolve.src.0.as_mut_ptr(),
+                resolve.src.1,
+                resolve.format,
+            );
+        }
+        self.end_pass();
     }
 
     unsafe fn set_render_pipeline(&mut self, pipeline: &super::RenderPipeline) {
-        todo!()
+        let context = self.context;
+        context.IASetPrimitiveTopology(pipeline.topology);
+        context.IASetInputLayout(pipeline.input_layout.as_mut_ptr());
+        context.VSSetShader(pipeline.vs.as_mut_ptr(), ptr::null(), 0);
+        context.PSSetShader(pipeline.ps.as_mut_ptr(), ptr::null(), 0);
+        context.RSSetState(pipeline.rasterizer_state.as_mut_ptr());
+
+        self.pass.blend_state = pipeline.blend_state;
+        self.pass.sample_mask = pipeline.sample_mask;
+        self.set_blend_state();
+        self.pass.depth_stencil_state = pipeline.depth_stencil_state;
+        self.set_depth_stencil_state();
+
+        // The strides are part of the vertex buffer bindings.
+        if self.pass.vertex_strides != pipeline.vertex_strides {
+            self.pass.vertex_strides = pipeline.vertex_strides;
+            self.pass.dirty_vertex_buffers = (1 << crate::MAX_VERTEX_BUFFERS) - 1;
+        }
+
+        self.bind_special_constants(pipeline.special_constants_slot);
     }
 
     unsafe fn set_index_buffer<'a>(
@@ -158,7 +647,11 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         binding: crate::BufferBinding<'a, super::Api>,
         format: wgt::IndexFormat,
     ) {
-        todo!()
+        self.context.IASetIndexBuffer(
+            binding.buffer.resource.as_mut_ptr(),
+            auxil::dxgi::conv::map_index_format(format),
+            binding.offset as u32,
+        );
     }
 
     unsafe fn set_vertex_buffer<'a>(
@@ -166,23 +659,42 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         index: u32,
         binding: crate::BufferBinding<'a, super::Api>,
     ) {
-        todo!()
+        let vb = &mut self.pass.vertex_buffers[index as usize];
+        vb.buffer = binding.buffer.resource;
+        vb.offset = binding.offset as u32;
+        self.pass.dirty_vertex_buffers |= 1 << index;
     }
 
-    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: std::ops::Range<f32>) {
-        todo!()
+    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: Range<f32>) {
+        let raw_vp = d3d11::D3D11_VIEWPORT {
+            TopLeftX: rect.x,
+            TopLeftY: rect.y,
+            Width: rect.w,
+            Height: rect.h,
+            MinDepth: depth_range.start,
+            MaxDepth: depth_range.end,
+        };
+        self.context.RSSetViewports(1, &raw_vp);
     }
 
     unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {
-        todo!()
+        let raw_rect = d3d11::D3D11_RECT {
+            left: rect.x as i32,
+            top: rect.y as i32,
+            right: (rect.x + rect.w) as i32,
+            bottom: (rect.y + rect.h) as i32,
+        };
+        self.context.RSSetScissorRects(1, &raw_rect);
     }
 
     unsafe fn set_stencil_reference(&mut self, value: u32) {
-        todo!()
+        self.pass.stencil_reference = value;
+        self.set_depth_stencil_state();
     }
 
     unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {
-        todo!()
+        self.pass.blend_constants = *color;
+        self.set_blend_state();
     }
 
     unsafe fn draw(
@@ -192,7 +704,9 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         start_instance: u32,
         instance_count: u32,
     ) {
-        todo!()
+        self.prepare_draw(start_vertex as i32, start_instance);
+        self.context
+            .DrawInstanced(vertex_count, instance_count, start_vertex, start_instance);
     }
 
     unsafe fn draw_indexed(
@@ -203,7 +717,14 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         start_instance: u32,
         instance_count: u32,
     ) {
-        todo!()
+        self.prepare_draw(base_vertex, start_instance);
+        self.context.DrawIndexedInstanced(
+            index_count,
+            instance_count,
+            start_index,
+            base_vertex,
+            start_instance,
+        );
     }
 
     unsafe fn draw_indirect(
@@ -212,7 +733,15 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         offset: wgt::BufferAddress,
         draw_count: u32,
     ) {
-        todo!()
+        self.flush_vertex_buffers();
+        let stride = mem::size_of::<wgt::DrawIndirectArgs>() as u32;
+        for i in 0..draw_count {
+            let args_offset = offset + (i * stride) as wgt::BufferAddress;
+            // `first_vertex` and `first_instance`
+            self.copy_special_constants(buffer, args_offset + 8, 8);
+            self.context
+                .DrawInstancedIndirect(buffer.resource.as_mut_ptr(), args_offset as u32);
+        }
     }
 
     unsafe fn draw_indexed_indirect(
@@ -221,48 +750,63 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         offset: wgt::BufferAddress,
         draw_count: u32,
     ) {
-        todo!()
+        self.flush_vertex_buffers();
+        let stride = mem::size_of::<wgt::DrawIndexedIndirectArgs>() as u32;
+        for i in 0..draw_count {
+            let args_offset = offset + (i * stride) as wgt::BufferAddress;
+            // `base_vertex` and `first_instance`
+            self.copy_special_constants(buffer, args_offset + 12, 8);
+            self.context
+                .DrawIndexedInstancedIndirect(buffer.resource.as_mut_ptr(), args_offset as u32);
+        }
     }
 
     unsafe fn draw_indirect_count(
         &mut self,
-        buffer: &super::Buffer,
-        offset: wgt::BufferAddress,
-        count_buffer: &super::Buffer,
-        count_offset: wgt::BufferAddress,
-        max_count: u32,
+        _buffer: &super::Buffer,
+        _offset: wgt::BufferAddress,
+        _count_buffer: &super::Buffer,
+        _count_offset: wgt::BufferAddress,
+        _max_count: u32,
     ) {
-        todo!()
+        unreachable!("MULTI_DRAW_INDIRECT_COUNT is not exposed by the D3D11 backend")
     }
 
     unsafe fn draw_indexed_indirect_count(
         &mut self,
-        buffer: &super::Buffer,
-        offset: wgt::BufferAddress,
-        count_buffer: &super::Buffer,
-        count_offset: wgt::BufferAddress,
-        max_count: u32,
+        _buffer: &super::Buffer,
+        _offset: wgt::BufferAddress,
+        _count_buffer: &super::Buffer,
+        _count_offset: wgt::BufferAddress,
+        _max_count: u32,
     ) {
-        todo!()
+        unreachable!("MULTI_DRAW_INDIRECT_COUNT is not exposed by the D3D11 backend")
     }
 
+    // compute
+
     unsafe fn begin_compute_pass(&mut self, desc: &crate::ComputePassDescriptor) {
-        todo!()
+        self.begin_pass(super::PassKind::Compute, desc.label);
     }
 
     unsafe fn end_compute_pass(&mut self) {
-        todo!()
+        self.end_pass();
     }
 
     unsafe fn set_compute_pipeline(&mut self, pipeline: &super::ComputePipeline) {
-        todo!()
+        self.context
+            .CSSetShader(pipeline.cs.as_mut_ptr(), ptr::null(), 0);
+        self.bind_special_constants(pipeline.special_constants_slot);
     }
 
     unsafe fn dispatch(&mut self, count: [u32; 3]) {
-        todo!()
+        self.update_special_constants(count);
+        self.context.Dispatch(count[0], count[1], count[2]);
     }
 
     unsafe fn dispatch_indirect(&mut self, buffer: &super::Buffer, offset: wgt::BufferAddress) {
-        todo!()
+        self.copy_special_constants(buffer, offset, 12);
+        self.context
+            .DispatchIndirect(buffer.resource.as_mut_ptr(), offset as u32);
     }
 }
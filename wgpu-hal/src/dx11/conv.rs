@@ -0,0 +1,764 @@
+//! Conversions to the D3D11 values.
+//!
+//! These don't depend on `winapi`, so that they are built and tested on every platform.
+
+use std::iter;
+#[cfg(windows)]
+use winapi::um::d3d11::{
+    D3D11_BLEND_DESC, D3D11_DEPTH_STENCILOP_DESC, D3D11_DEPTH_STENCIL_DESC, D3D11_RASTERIZER_DESC,
+    D3D11_RENDER_TARGET_BLEND_DESC,
+};
+
+/// Values of the `d3d11.h` and `d3dcommon.h` definitions used by the conversions.
+#[allow(non_camel_case_types)]
+pub mod d3d11 {
+    pub type D3D11_BIND_FLAG = u32;
+    pub type D3D11_RESOURCE_MISC_FLAG = u32;
+    pub type D3D11_TEXTURE_ADDRESS_MODE = u32;
+    pub type D3D11_FILTER_TYPE = u32;
+    pub type D3D11_FILTER = u32;
+    pub type D3D11_COMPARISON_FUNC = u32;
+    pub type D3D11_PRIMITIVE_TOPOLOGY = u32;
+    pub type D3D11_FILL_MODE = u32;
+    pub type D3D11_CULL_MODE = u32;
+    pub type D3D11_BLEND = u32;
+    pub type D3D11_BLEND_OP = u32;
+    pub type D3D11_STENCIL_OP = u32;
+    pub type D3D11_DEPTH_WRITE_MASK = u32;
+    pub type D3D11_QUERY = u32;
+
+    pub const D3D11_BIND_VERTEX_BUFFER: D3D11_BIND_FLAG = 0x1;
+    pub const D3D11_BIND_INDEX_BUFFER: D3D11_BIND_FLAG = 0x2;
+    pub const D3D11_BIND_CONSTANT_BUFFER: D3D11_BIND_FLAG = 0x4;
+    pub const D3D11_BIND_SHADER_RESOURCE: D3D11_BIND_FLAG = 0x8;
+    pub const D3D11_BIND_RENDER_TARGET: D3D11_BIND_FLAG = 0x20;
+    pub const D3D11_BIND_DEPTH_STENCIL: D3D11_BIND_FLAG = 0x40;
+    pub const D3D11_BIND_UNORDERED_ACCESS: D3D11_BIND_FLAG = 0x80;
+
+    pub const D3D11_RESOURCE_MISC_DRAWINDIRECT_ARGS: D3D11_RESOURCE_MISC_FLAG = 0x10;
+    pub const D3D11_RESOURCE_MISC_BUFFER_ALLOW_RAW_VIEWS: D3D11_RESOURCE_MISC_FLAG = 0x20;
+
+    pub const D3D11_TEXTURE_ADDRESS_WRAP: D3D11_TEXTURE_ADDRESS_MODE = 1;
+    pub const D3D11_TEXTURE_ADDRESS_MIRROR: D3D11_TEXTURE_ADDRESS_MODE = 2;
+    pub const D3D11_TEXTURE_ADDRESS_CLAMP: D3D11_TEXTURE_ADDRESS_MODE = 3;
+    pub const D3D11_TEXTURE_ADDRESS_BORDER: D3D11_TEXTURE_ADDRESS_MODE = 4;
+
+    pub const D3D11_FILTER_TYPE_POINT: D3D11_FILTER_TYPE = 0;
+    pub const D3D11_FILTER_TYPE_LINEAR: D3D11_FILTER_TYPE = 1;
+    pub const D3D11_FILTER_REDUCTION_TYPE_STANDARD: u32 = 0;
+    pub const D3D11_FILTER_REDUCTION_TYPE_COMPARISON: u32 = 1;
+    pub const D3D11_MIN_FILTER_SHIFT: u32 = 4;
+    pub const D3D11_MAG_FILTER_SHIFT: u32 = 2;
+    pub const D3D11_MIP_FILTER_SHIFT: u32 = 0;
+    pub const D3D11_FILTER_REDUCTION_TYPE_SHIFT: u32 = 7;
+    pub const D3D11_FILTER_ANISOTROPIC: D3D11_FILTER = 0x55;
+
+    pub const D3D11_COMPARISON_NEVER: D3D11_COMPARISON_FUNC = 1;
+    pub const D3D11_COMPARISON_LESS: D3D11_COMPARISON_FUNC = 2;
+    pub const D3D11_COMPARISON_EQUAL: D3D11_COMPARISON_FUNC = 3;
+    pub const D3D11_COMPARISON_LESS_EQUAL: D3D11_COMPARISON_FUNC = 4;
+    pub const D3D11_COMPARISON_GREATER: D3D11_COMPARISON_FUNC = 5;
+    pub const D3D11_COMPARISON_NOT_EQUAL: D3D11_COMPARISON_FUNC = 6;
+    pub const D3D11_COMPARISON_GREATER_EQUAL: D3D11_COMPARISON_FUNC = 7;
+    pub const D3D11_COMPARISON_ALWAYS: D3D11_COMPARISON_FUNC = 8;
+
+    pub const D3D_PRIMITIVE_TOPOLOGY_POINTLIST: D3D11_PRIMITIVE_TOPOLOGY = 1;
+    pub const D3D_PRIMITIVE_TOPOLOGY_LINELIST: D3D11_PRIMITIVE_TOPOLOGY = 2;
+    pub const D3D_PRIMITIVE_TOPOLOGY_LINESTRIP: D3D11_PRIMITIVE_TOPOLOGY = 3;
+    pub const D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST: D3D11_PRIMITIVE_TOPOLOGY = 4;
+    pub const D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP: D3D11_PRIMITIVE_TOPOLOGY = 5;
+
+    pub const D3D11_FILL_WIREFRAME: D3D11_FILL_MODE = 2;
+    pub const D3D11_FILL_SOLID: D3D11_FILL_MODE = 3;
+
+    pub const D3D11_CULL_NONE: D3D11_CULL_MODE = 1;
+    pub const D3D11_CULL_FRONT: D3D11_CULL_MODE = 2;
+    pub const D3D11_CULL_BACK: D3D11_CULL_MODE = 3;
+
+    pub const D3D11_BLEND_ZERO: D3D11_BLEND = 1;
+    pub const D3D11_BLEND_ONE: D3D11_BLEND = 2;
+    pub const D3D11_BLEND_SRC_COLOR: D3D11_BLEND = 3;
+    pub const D3D11_BLEND_INV_SRC_COLOR: D3D11_BLEND = 4;
+    pub const D3D11_BLEND_SRC_ALPHA: D3D11_BLEND = 5;
+    pub const D3D11_BLEND_INV_SRC_ALPHA: D3D11_BLEND = 6;
+    pub const D3D11_BLEND_DEST_ALPHA: D3D11_BLEND = 7;
+    pub const D3D11_BLEND_INV_DEST_ALPHA: D3D11_BLEND = 8;
+    pub const D3D11_BLEND_DEST_COLOR: D3D11_BLEND = 9;
+    pub const D3D11_BLEND_INV_DEST_COLOR: D3D11_BLEND = 10;
+    pub const D3D11_BLEND_SRC_ALPHA_SAT: D3D11_BLEND = 11;
+    pub const D3D11_BLEND_BLEND_FACTOR: D3D11_BLEND = 14;
+    pub const D3D11_BLEND_INV_BLEND_FACTOR: D3D11_BLEND = 15;
+
+    pub const D3D11_BLEND_OP_ADD: D3D11_BLEND_OP = 1;
+    pub const D3D11_BLEND_OP_SUBTRACT: D3D11_BLEND_OP = 2;
+    pub const D3D11_BLEND_OP_REV_SUBTRACT: D3D11_BLEND_OP = 3;
+    pub const D3D11_BLEND_OP_MIN: D3D11_BLEND_OP = 4;
+    pub const D3D11_BLEND_OP_MAX: D3D11_BLEND_OP = 5;
+
+    pub const D3D11_STENCIL_OP_KEEP: D3D11_STENCIL_OP = 1;
+    pub const D3D11_STENCIL_OP_ZERO: D3D11_STENCIL_OP = 2;
+    pub const D3D11_STENCIL_OP_REPLACE: D3D11_STENCIL_OP = 3;
+    pub const D3D11_STENCIL_OP_INCR_SAT: D3D11_STENCIL_OP = 4;
+    pub const D3D11_STENCIL_OP_DECR_SAT: D3D11_STENCIL_OP = 5;
+    pub const D3D11_STENCIL_OP_INVERT: D3D11_STENCIL_OP = 6;
+    pub const D3D11_STENCIL_OP_INCR: D3D11_STENCIL_OP = 7;
+    pub const D3D11_STENCIL_OP_DECR: D3D11_STENCIL_OP = 8;
+
+    pub const D3D11_DEPTH_WRITE_MASK_ZERO: D3D11_DEPTH_WRITE_MASK = 0;
+    pub const D3D11_DEPTH_WRITE_MASK_ALL: D3D11_DEPTH_WRITE_MASK = 1;
+
+    pub const D3D11_QUERY_OCCLUSION: D3D11_QUERY = 1;
+    pub const D3D11_QUERY_TIMESTAMP: D3D11_QUERY = 2;
+    pub const D3D11_QUERY_PIPELINE_STATISTICS: D3D11_QUERY = 4;
+
+    pub const D3D11_SIMULTANEOUS_RENDER_TARGET_COUNT: u32 = 8;
+}
+
+/// Buffers that are only ever mapped and copied live in host memory,
+/// D3D11 can't copy between a mappable resource and a texture.
+pub fn is_host_buffer(usage: crate::BufferUses) -> bool {
+    (crate::BufferUses::MAP_READ
+        | crate::BufferUses::MAP_WRITE
+        | crate::BufferUses::COPY_SRC
+        | crate::BufferUses::COPY_DST)
+        .contains(usage)
+}
+
+pub fn map_buffer_usage_to_bind_flags(usage: crate::BufferUses) -> d3d11::D3D11_BIND_FLAG {
+    use crate::BufferUses as Bu;
+    let mut flags = 0;
+    if usage.contains(Bu::UNIFORM) {
+        flags |= d3d11::D3D11_BIND_CONSTANT_BUFFER;
+    }
+    if usage.contains(Bu::VERTEX) {
+        flags |= d3d11::D3D11_BIND_VERTEX_BUFFER;
+    }
+    if usage.contains(Bu::INDEX) {
+        flags |= d3d11::D3D11_BIND_INDEX_BUFFER;
+    }
+    if usage.contains(Bu::STORAGE_READ) {
+        flags |= d3d11::D3D11_BIND_SHADER_RESOURCE;
+    }
+    if usage.contains(Bu::STORAGE_READ_WRITE) {
+        flags |= d3d11::D3D11_BIND_SHADER_RESOURCE | d3d11::D3D11_BIND_UNORDERED_ACCESS;
+    }
+    flags
+}
+
+pub fn map_buffer_usage_to_misc_flags(usage: crate::BufferUses) -> d3d11::D3D11_RESOURCE_MISC_FLAG {
+    use crate::BufferUses as Bu;
+    let mut flags = 0;
+    if usage.intersects(Bu::STORAGE_READ | Bu::STORAGE_READ_WRITE) {
+        flags |= d3d11::D3D11_RESOURCE_MISC_BUFFER_ALLOW_RAW_VIEWS;
+    }
+    if usage.contains(Bu::INDIRECT) {
+        flags |= d3d11::D3D11_RESOURCE_MISC_DRAWINDIRECT_ARGS;
+    }
+    flags
+}
+
+pub fn map_texture_usage_to_bind_flags(usage: crate::TextureUses) -> d3d11::D3D11_BIND_FLAG {
+    use crate::TextureUses as Tu;
+    let mut flags = 0;
+    if usage.contains(Tu::RESOURCE) {
+        flags |= d3d11::D3D11_BIND_SHADER_RESOURCE;
+    }
+    if usage.contains(Tu::COLOR_TARGET) {
+        flags |= d3d11::D3D11_BIND_RENDER_TARGET;
+    }
+    if usage.intersects(Tu::DEPTH_STENCIL_READ | Tu::DEPTH_STENCIL_WRITE) {
+        flags |= d3d11::D3D11_BIND_DEPTH_STENCIL;
+    }
+    if usage.intersects(Tu::STORAGE_READ | Tu::STORAGE_READ_WRITE) {
+        flags |= d3d11::D3D11_BIND_UNORDERED_ACCESS;
+    }
+    flags
+}
+
+pub fn map_address_mode(mode: wgt::AddressMode) -> d3d11::D3D11_TEXTURE_ADDRESS_MODE {
+    use wgt::AddressMode as Am;
+    match mode {
+        Am::Repeat => d3d11::D3D11_TEXTURE_ADDRESS_WRAP,
+        Am::MirrorRepeat => d3d11::D3D11_TEXTURE_ADDRESS_MIRROR,
+        Am::ClampToEdge => d3d11::D3D11_TEXTURE_ADDRESS_CLAMP,
+        Am::ClampToBorder => d3d11::D3D11_TEXTURE_ADDRESS_BORDER,
+    }
+}
+
+pub fn map_filter_mode(mode: wgt::FilterMode) -> d3d11::D3D11_FILTER_TYPE {
+    match mode {
+        wgt::FilterMode::Nearest => d3d11::D3D11_FILTER_TYPE_POINT,
+        wgt::FilterMode::Linear => d3d11::D3D11_FILTER_TYPE_LINEAR,
+    }
+}
+
+pub fn map_filter(desc: &crate::SamplerDescriptor) -> d3d11::D3D11_FILTER {
+    let reduction = match desc.compare {
+        Some(_) => d3d11::D3D11_FILTER_REDUCTION_TYPE_COMPARISON,
+        None => d3d11::D3D11_FILTER_REDUCTION_TYPE_STANDARD,
+    };
+    map_filter_mode(desc.min_filter) << d3d11::D3D11_MIN_FILTER_SHIFT
+        | map_filter_mode(desc.mag_filter) << d3d11::D3D11_MAG_FILTER_SHIFT
+        | map_filter_mode(desc.mipmap_filter) << d3d11::D3D11_MIP_FILTER_SHIFT
+        | reduction << d3d11::D3D11_FILTER_REDUCTION_TYPE_SHIFT
+        | desc
+            .anisotropy_clamp
+            .map_or(0, |_| d3d11::D3D11_FILTER_ANISOTROPIC)
+}
+
+pub fn map_comparison(func: wgt::CompareFunction) -> d3d11::D3D11_COMPARISON_FUNC {
+    use wgt::CompareFunction as Cf;
+    match func {
+        Cf::Never => d3d11::D3D11_COMPARISON_NEVER,
+        Cf::Less => d3d11::D3D11_COMPARISON_LESS,
+        Cf::LessEqual => d3d11::D3D11_COMPARISON_LESS_EQUAL,
+        Cf::Equal => d3d11::D3D11_COMPARISON_EQUAL,
+        Cf::GreaterEqual => d3d11::D3D11_COMPARISON_GREATER_EQUAL,
+        Cf::Greater => d3d11::D3D11_COMPARISON_GREATER,
+        Cf::NotEqual => d3d11::D3D11_COMPARISON_NOT_EQUAL,
+        Cf::Always => d3d11::D3D11_COMPARISON_ALWAYS,
+    }
+}
+
+pub fn map_border_color(border_color: Option<wgt::SamplerBorderColor>) -> [f32; 4] {
+    use wgt::SamplerBorderColor as Sbc;
+    match border_color {
+        Some(Sbc::TransparentBlack) | Some(Sbc::Zero) | None => [0.0; 4],
+        Some(Sbc::OpaqueBlack) => [0.0, 0.0, 0.0, 1.0],
+        Some(Sbc::OpaqueWhite) => [1.0; 4],
+    }
+}
+
+pub fn map_label(name: &str) -> Vec<u16> {
+    name.encode_utf16().chain(iter::once(0)).collect()
+}
+
+pub fn map_topology(topology: wgt::PrimitiveTopology) -> d3d11::D3D11_PRIMITIVE_TOPOLOGY {
+    match topology {
+        wgt::PrimitiveTopology::PointList => d3d11::D3D_PRIMITIVE_TOPOLOGY_POINTLIST,
+        wgt::PrimitiveTopology::LineList => d3d11::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+        wgt::PrimitiveTopology::LineStrip => d3d11::D3D_PRIMITIVE_TOPOLOGY_LINESTRIP,
+        wgt::PrimitiveTopology::TriangleList => d3d11::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+        wgt::PrimitiveTopology::TriangleStrip => d3d11::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+    }
+}
+
+pub fn map_polygon_mode(mode: wgt::PolygonMode) -> d3d11::D3D11_FILL_MODE {
+    match mode {
+        wgt::PolygonMode::Point => {
+            log::error!("Point rasterization is not supported");
+            d3d11::D3D11_FILL_WIREFRAME
+        }
+        wgt::PolygonMode::Line => d3d11::D3D11_FILL_WIREFRAME,
+        wgt::PolygonMode::Fill => d3d11::D3D11_FILL_SOLID,
+    }
+}
+
+#[cfg(windows)]
+pub fn map_rasterizer(
+    primitive: &wgt::PrimitiveState,
+    depth_stencil: Option<&wgt::DepthStencilState>,
+    multisample: &wgt::MultisampleState,
+) -> D3D11_RASTERIZER_DESC {
+    let bias = depth_stencil.map(|ds| ds.bias).unwrap_or_default();
+    D3D11_RASTERIZER_DESC {
+        FillMode: map_polygon_mode(primitive.polygon_mode),
+        CullMode: match primitive.cull_mode {
+            None => d3d11::D3D11_CULL_NONE,
+            Some(wgt::Face::Front) => d3d11::D3D11_CULL_FRONT,
+            Some(wgt::Face::Back) => d3d11::D3D11_CULL_BACK,
+        },
+        FrontCounterClockwise: match primitive.front_face {
+            wgt::FrontFace::Cw => 0,
+            wgt::FrontFace::Ccw => 1,
+        },
+        DepthBias: bias.constant,
+        DepthBiasClamp: bias.clamp,
+        SlopeScaledDepthBias: bias.slope_scale,
+        DepthClipEnable: if primitive.unclipped_depth { 0 } else { 1 },
+        // The scissor is always set, to the full render target by default.
+        ScissorEnable: 1,
+        MultisampleEnable: if multisample.count > 1 { 1 } else { 0 },
+        AntialiasedLineEnable: 0,
+    }
+}
+
+fn map_blend_factor(factor: wgt::BlendFactor, is_alpha: bool) -> d3d11::D3D11_BLEND {
+    use wgt::BlendFactor as Bf;
+    match factor {
+        Bf::Zero => d3d11::D3D11_BLEND_ZERO,
+        Bf::One => d3d11::D3D11_BLEND_ONE,
+        Bf::Src if is_alpha => d3d11::D3D11_BLEND_SRC_ALPHA,
+        Bf::Src => d3d11::D3D11_BLEND_SRC_COLOR,
+        Bf::OneMinusSrc if is_alpha => d3d11::D3D11_BLEND_INV_SRC_ALPHA,
+        Bf::OneMinusSrc => d3d11::D3D11_BLEND_INV_SRC_COLOR,
+        Bf::Dst if is_alpha => d3d11::D3D11_BLEND_DEST_ALPHA,
+        Bf::Dst => d3d11::D3D11_BLEND_DEST_COLOR,
+        Bf::OneMinusDst if is_alpha => d3d11::D3D11_BLEND_INV_DEST_ALPHA,
+        Bf::OneMinusDst => d3d11::D3D11_BLEND_INV_DEST_COLOR,
+        Bf::SrcAlpha => d3d11::D3D11_BLEND_SRC_ALPHA,
+        Bf::OneMinusSrcAlpha => d3d11::D3D11_BLEND_INV_SRC_ALPHA,
+        Bf::DstAlpha => d3d11::D3D11_BLEND_DEST_ALPHA,
+        Bf::OneMinusDstAlpha => d3d11::D3D11_BLEND_INV_DEST_ALPHA,
+        Bf::Constant => d3d11::D3D11_BLEND_BLEND_FACTOR,
+        Bf::OneMinusConstant => d3d11::D3D11_BLEND_INV_BLEND_FACTOR,
+        Bf::SrcAlphaSaturated => d3d11::D3D11_BLEND_SRC_ALPHA_SAT,
+    }
+}
+
+fn map_blend_component(
+    component: &wgt::BlendComponent,
+    is_alpha: bool,
+) -> (
+    d3d11::D3D11_BLEND_OP,
+    d3d11::D3D11_BLEND,
+    d3d11::D3D11_BLEND,
+) {
+    let raw_op = match component.operation {
+        wgt::BlendOperation::Add => d3d11::D3D11_BLEND_OP_ADD,
+        wgt::BlendOperation::Subtract => d3d11::D3D11_BLEND_OP_SUBTRACT,
+        wgt::BlendOperation::ReverseSubtract => d3d11::D3D11_BLEND_OP_REV_SUBTRACT,
+        wgt::BlendOperation::Min => d3d11::D3D11_BLEND_OP_MIN,
+        wgt::BlendOperation::Max => d3d11::D3D11_BLEND_OP_MAX,
+    };
+    let raw_src = map_blend_factor(component.src_factor, is_alpha);
+    let raw_dst = map_blend_factor(component.dst_factor, is_alpha);
+    (raw_op, raw_src, raw_dst)
+}
+
+#[cfg(windows)]
+pub fn map_blend(
+    color_targets: &[wgt::ColorTargetState],
+    multisample: &wgt::MultisampleState,
+) -> D3D11_BLEND_DESC {
+    let dummy_target = D3D11_RENDER_TARGET_BLEND_DESC {
+        BlendEnable: 0,
+        SrcBlend: d3d11::D3D11_BLEND_ONE,
+        DestBlend: d3d11::D3D11_BLEND_ZERO,
+        BlendOp: d3d11::D3D11_BLEND_OP_ADD,
+        SrcBlendAlpha: d3d11::D3D11_BLEND_ONE,
+        DestBlendAlpha: d3d11::D3D11_BLEND_ZERO,
+        BlendOpAlpha: d3d11::D3D11_BLEND_OP_ADD,
+        RenderTargetWriteMask: 0,
+    };
+    let mut raw_targets = [dummy_target; d3d11::D3D11_SIMULTANEOUS_RENDER_TARGET_COUNT as usize];
+
+    for (raw, ct) in raw_targets.iter_mut().zip(color_targets.iter()) {
+        raw.RenderTargetWriteMask = ct.write_mask.bits() as u8;
+        if let Some(ref blend) = ct.blend {
+            let (color_op, color_src, color_dst) = map_blend_component(&blend.color, false);
+            let (alpha_op, alpha_src, alpha_dst) = map_blend_component(&blend.alpha, true);
+            raw.BlendEnable = 1;
+            raw.BlendOp = color_op;
+            raw.SrcBlend = color_src;
+            raw.DestBlend = color_dst;
+            raw.BlendOpAlpha = alpha_op;
+            raw.SrcBlendAlpha = alpha_src;
+            raw.DestBlendAlpha = alpha_dst;
+        }
+    }
+
+    D3D11_BLEND_DESC {
+        AlphaToCoverageEnable: if multisample.alpha_to_coverage_enabled {
+            1
+        } else {
+            0
+        },
+        IndependentBlendEnable: 1,
+        RenderTarget: raw_targets,
+    }
+}
+
+fn map_stencil_op(op: wgt::StencilOperation) -> d3d11::D3D11_STENCIL_OP {
+    use wgt::StencilOperation as So;
+    match op {
+        So::Keep => d3d11::D3D11_STENCIL_OP_KEEP,
+        So::Zero => d3d11::D3D11_STENCIL_OP_ZERO,
+        So::Replace => d3d11::D3D11_STENCIL_OP_REPLACE,
+        So::IncrementClamp => d3d11::D3D11_STENCIL_OP_INCR_SAT,
+        So::IncrementWrap => d3d11::D3D11_STENCIL_OP_INCR,
+        So::DecrementClamp => d3d11::D3D11_STENCIL_OP_DECR_SAT,
+        So::DecrementWrap => d3d11::D3D11_STENCIL_OP_DECR,
+        So::Invert => d3d11::D3D11_STENCIL_OP_INVERT,
+    }
+}
+
+#[cfg(windows)]
+fn map_stencil_face(face: &wgt::StencilFaceState) -> D3D11_DEPTH_STENCILOP_DESC {
+    D3D11_DEPTH_STENCILOP_DESC {
+        StencilFailOp: map_stencil_op(face.fail_op),
+        StencilDepthFailOp: map_stencil_op(face.depth_fail_op),
+        StencilPassOp: map_stencil_op(face.pass_op),
+        StencilFunc: map_comparison(face.compare),
+    }
+}
+
+#[cfg(windows)]
+pub fn map_depth_stencil(ds: Option<&wgt::DepthStencilState>) -> D3D11_DEPTH_STENCIL_DESC {
+    let ds = match ds {
+        Some(ds) => ds,
+        None => {
+            let disabled = map_stencil_face(&wgt::StencilFaceState::IGNORE);
+            return D3D11_DEPTH_STENCIL_DESC {
+                DepthEnable: 0,
+                DepthWriteMask: d3d11::D3D11_DEPTH_WRITE_MASK_ZERO,
+                DepthFunc: d3d11::D3D11_COMPARISON_ALWAYS,
+                StencilEnable: 0,
+                StencilReadMask: 0,
+                StencilWriteMask: 0,
+                FrontFace: disabled,
+                BackFace: disabled,
+            };
+        }
+    };
+    D3D11_DEPTH_STENCIL_DESC {
+        DepthEnable: if ds.is_depth_enabled() { 1 } else { 0 },
+        DepthWriteMask: if ds.depth_write_enabled {
+            d3d11::D3D11_DEPTH_WRITE_MASK_ALL
+        } else {
+            d3d11::D3D11_DEPTH_WRITE_MASK_ZERO
+        },
+        DepthFunc: map_comparison(ds.depth_compare),
+        StencilEnable: if ds.stencil.is_enabled() { 1 } else { 0 },
+        StencilReadMask: ds.stencil.read_mask as u8,
+        StencilWriteMask: ds.stencil.write_mask as u8,
+        FrontFace: map_stencil_face(&ds.stencil.front),
+        BackFace: map_stencil_face(&ds.stencil.back),
+    }
+}
+
+pub fn map_query_type(ty: wgt::QueryType) -> d3d11::D3D11_QUERY {
+    match ty {
+        wgt::QueryType::Occlusion => d3d11::D3D11_QUERY_OCCLUSION,
+        wgt::QueryType::PipelineStatistics(_) => d3d11::D3D11_QUERY_PIPELINE_STATISTICS,
+        wgt::QueryType::Timestamp => d3d11::D3D11_QUERY_TIMESTAMP,
+    }
+}
+
+/// Picks the statistics enabled in `types` out of the values of
+/// `D3D11_QUERY_DATA_PIPELINE_STATISTICS`, in the order of the flags.
+///
+/// `values` are the vertex shader, clipper, clipper output primitive,
+/// pixel shader and compute shader counters.
+pub fn map_pipeline_statistics(types: wgt::PipelineStatisticsTypes, values: [u64; 5]) -> Vec<u64> {
+    use wgt::PipelineStatisticsTypes as Pst;
+    let all = [
+        Pst::VERTEX_SHADER_INVOCATIONS,
+        Pst::CLIPPER_INVOCATIONS,
+        Pst::CLIPPER_PRIMITIVES_OUT,
+        Pst::FRAGMENT_SHADER_INVOCATIONS,
+        Pst::COMPUTE_SHADER_INVOCATIONS,
+    ];
+    all.iter()
+        .zip(values.iter())
+        .filter(|&(&ty, _)| types.contains(ty))
+        .map(|(_, &value)| value)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_buffer() {
+        use crate::BufferUses as Bu;
+        assert!(is_host_buffer(Bu::MAP_READ | Bu::COPY_DST));
+        assert!(is_host_buffer(Bu::MAP_WRITE | Bu::COPY_SRC));
+        assert!(is_host_buffer(Bu::COPY_SRC | Bu::COPY_DST));
+        assert!(!is_host_buffer(Bu::MAP_WRITE | Bu::VERTEX));
+        assert!(!is_host_buffer(Bu::COPY_DST | Bu::UNIFORM));
+    }
+
+    #[test]
+    fn buffer_flags() {
+        use crate::BufferUses as Bu;
+        assert_eq!(
+            map_buffer_usage_to_bind_flags(Bu::VERTEX | Bu::INDEX),
+            d3d11::D3D11_BIND_VERTEX_BUFFER | d3d11::D3D11_BIND_INDEX_BUFFER
+        );
+        assert_eq!(
+            map_buffer_usage_to_bind_flags(Bu::STORAGE_READ_WRITE),
+            d3d11::D3D11_BIND_SHADER_RESOURCE | d3d11::D3D11_BIND_UNORDERED_ACCESS
+        );
+        assert_eq!(map_buffer_usage_to_bind_flags(Bu::INDIRECT), 0);
+        assert_eq!(
+            map_buffer_usage_to_misc_flags(Bu::STORAGE_READ | Bu::INDIRECT),
+            d3d11::D3D11_RESOURCE_MISC_BUFFER_ALLOW_RAW_VIEWS
+                | d3d11::D3D11_RESOURCE_MISC_DRAWINDIRECT_ARGS
+        );
+        assert_eq!(map_buffer_usage_to_misc_flags(Bu::UNIFORM), 0);
+    }
+
+    #[test]
+    fn texture_flags() {
+        use crate::TextureUses as Tu;
+        assert_eq!(
+            map_texture_usage_to_bind_flags(Tu::RESOURCE | Tu::COLOR_TARGET),
+            d3d11::D3D11_BIND_SHADER_RESOURCE | d3d11::D3D11_BIND_RENDER_TARGET
+        );
+        assert_eq!(
+            map_texture_usage_to_bind_flags(Tu::DEPTH_STENCIL_READ | Tu::STORAGE_READ_WRITE),
+            d3d11::D3D11_BIND_DEPTH_STENCIL | d3d11::D3D11_BIND_UNORDERED_ACCESS
+        );
+        assert_eq!(map_texture_usage_to_bind_flags(Tu::COPY_DST), 0);
+    }
+
+    #[test]
+    fn filter() {
+        let mut desc = crate::SamplerDescriptor {
+            label: None,
+            address_modes: [wgt::AddressMode::ClampToEdge; 3],
+            mag_filter: wgt::FilterMode::Nearest,
+            min_filter: wgt::FilterMode::Nearest,
+            mipmap_filter: wgt::FilterMode::Nearest,
+            lod_clamp: None,
+            compare: None,
+            anisotropy_clamp: None,
+            border_color: None,
+        };
+        // D3D11_FILTER_MIN_MAG_MIP_POINT
+        assert_eq!(map_filter(&desc), 0x00);
+        desc.min_filter = wgt::FilterMode::Linear;
+        desc.mag_filter = wgt::FilterMode::Linear;
+        // D3D11_FILTER_MIN_MAG_LINEAR_MIP_POINT
+        assert_eq!(map_filter(&desc), 0x14);
+        desc.mipmap_filter = wgt::FilterMode::Linear;
+        desc.compare = Some(wgt::CompareFunction::Less);
+        // D3D11_FILTER_COMPARISON_MIN_MAG_MIP_LINEAR
+        assert_eq!(map_filter(&desc), 0x95);
+        desc.compare = None;
+        desc.anisotropy_clamp = std::num::NonZeroU8::new(16);
+        // D3D11_FILTER_ANISOTROPIC
+        assert_eq!(map_filter(&desc), 0x55);
+    }
+
+    #[test]
+    fn blend() {
+        let component = wgt::BlendComponent {
+            src_factor: wgt::BlendFactor::Src,
+            dst_factor: wgt::BlendFactor::OneMinusDst,
+            operation: wgt::BlendOperation::ReverseSubtract,
+        };
+        assert_eq!(
+            map_blend_component(&component, false),
+            (
+                d3d11::D3D11_BLEND_OP_REV_SUBTRACT,
+                d3d11::D3D11_BLEND_SRC_COLOR,
+                d3d11::D3D11_BLEND_INV_DEST_COLOR,
+            )
+        );
+        // Color factors can't be used for the alpha channel.
+        assert_eq!(
+            map_blend_component(&component, true),
+            (
+                d3d11::D3D11_BLEND_OP_REV_SUBTRACT,
+                d3d11::D3D11_BLEND_SRC_ALPHA,
+                d3d11::D3D11_BLEND_INV_DEST_ALPHA,
+            )
+        );
+    }
+
+    #[test]
+    fn pipeline_statistics() {
+        use wgt::PipelineStatisticsTypes as Pst;
+        let values = [1, 2, 3, 4, 5];
+        assert_eq!(map_pipeline_statistics(Pst::all(), values), values);
+        assert_eq!(
+            map_pipeline_statistics(
+                Pst::CLIPPER_PRIMITIVES_OUT | Pst::COMPUTE_SHADER_INVOCATIONS,
+                values
+            ),
+            [3, 5]
+        );
+        assert!(map_pipeline_statistics(Pst::empty(), values).is_empty());
+    }
+
+    #[test]
+    fn label() {
+        assert_eq!(map_label("ab"), [0x61, 0x62, 0]);
+    }
+
+    /// Checks the values against the `winapi` definitions.
+    #[cfg(windows)]
+    #[test]
+    fn winapi_values() {
+        use winapi::um::{d3d11 as w, d3dcommon};
+        let pairs = [
+            (d3d11::D3D11_BIND_VERTEX_BUFFER, w::D3D11_BIND_VERTEX_BUFFER),
+            (d3d11::D3D11_BIND_INDEX_BUFFER, w::D3D11_BIND_INDEX_BUFFER),
+            (
+                d3d11::D3D11_BIND_CONSTANT_BUFFER,
+                w::D3D11_BIND_CONSTANT_BUFFER,
+            ),
+            (
+                d3d11::D3D11_BIND_SHADER_RESOURCE,
+                w::D3D11_BIND_SHADER_RESOURCE,
+            ),
+            (d3d11::D3D11_BIND_RENDER_TARGET, w::D3D11_BIND_RENDER_TARGET),
+            (d3d11::D3D11_BIND_DEPTH_STENCIL, w::D3D11_BIND_DEPTH_STENCIL),
+            (
+                d3d11::D3D11_BIND_UNORDERED_ACCESS,
+                w::D3D11_BIND_UNORDERED_ACCESS,
+            ),
+            (
+                d3d11::D3D11_RESOURCE_MISC_DRAWINDIRECT_ARGS,
+                w::D3D11_RESOURCE_MISC_DRAWINDIRECT_ARGS,
+            ),
+            (
+                d3d11::D3D11_RESOURCE_MISC_BUFFER_ALLOW_RAW_VIEWS,
+                w::D3D11_RESOURCE_MISC_BUFFER_ALLOW_RAW_VIEWS,
+            ),
+            (
+                d3d11::D3D11_TEXTURE_ADDRESS_WRAP,
+                w::D3D11_TEXTURE_ADDRESS_WRAP,
+            ),
+            (
+                d3d11::D3D11_TEXTURE_ADDRESS_MIRROR,
+                w::D3D11_TEXTURE_ADDRESS_MIRROR,
+            ),
+            (
+                d3d11::D3D11_TEXTURE_ADDRESS_CLAMP,
+                w::D3D11_TEXTURE_ADDRESS_CLAMP,
+            ),
+            (
+                d3d11::D3D11_TEXTURE_ADDRESS_BORDER,
+                w::D3D11_TEXTURE_ADDRESS_BORDER,
+            ),
+            (d3d11::D3D11_FILTER_TYPE_POINT, w::D3D11_FILTER_TYPE_POINT),
+            (d3d11::D3D11_FILTER_TYPE_LINEAR, w::D3D11_FILTER_TYPE_LINEAR),
+            (
+                d3d11::D3D11_FILTER_REDUCTION_TYPE_STANDARD,
+                w::D3D11_FILTER_REDUCTION_TYPE_STANDARD,
+            ),
+            (
+                d3d11::D3D11_FILTER_REDUCTION_TYPE_COMPARISON,
+                w::D3D11_FILTER_REDUCTION_TYPE_COMPARISON,
+            ),
+            (d3d11::D3D11_MIN_FILTER_SHIFT, w::D3D11_MIN_FILTER_SHIFT),
+            (d3d11::D3D11_MAG_FILTER_SHIFT, w::D3D11_MAG_FILTER_SHIFT),
+            (d3d11::D3D11_MIP_FILTER_SHIFT, w::D3D11_MIP_FILTER_SHIFT),
+            (
+                d3d11::D3D11_FILTER_REDUCTION_TYPE_SHIFT,
+                w::D3D11_FILTER_REDUCTION_TYPE_SHIFT,
+            ),
+            (d3d11::D3D11_FILTER_ANISOTROPIC, w::D3D11_FILTER_ANISOTROPIC),
+            (d3d11::D3D11_COMPARISON_NEVER, w::D3D11_COMPARISON_NEVER),
+            (d3d11::D3D11_COMPARISON_LESS, w::D3D11_COMPARISON_LESS),
+            (d3d11::D3D11_COMPARISON_EQUAL, w::D3D11_COMPARISON_EQUAL),
+            (
+                d3d11::D3D11_COMPARISON_LESS_EQUAL,
+                w::D3D11_COMPARISON_LESS_EQUAL,
+            ),
+            (d3d11::D3D11_COMPARISON_GREATER, w::D3D11_COMPARISON_GREATER),
+            (
+                d3d11::D3D11_COMPARISON_NOT_EQUAL,
+                w::D3D11_COMPARISON_NOT_EQUAL,
+            ),
+            (
+                d3d11::D3D11_COMPARISON_GREATER_EQUAL,
+                w::D3D11_COMPARISON_GREATER_EQUAL,
+            ),
+            (d3d11::D3D11_COMPARISON_ALWAYS, w::D3D11_COMPARISON_ALWAYS),
+            (
+                d3d11::D3D_PRIMITIVE_TOPOLOGY_POINTLIST,
+                d3dcommon::D3D_PRIMITIVE_TOPOLOGY_POINTLIST,
+            ),
+            (
+                d3d11::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+                d3dcommon::D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+            ),
+            (
+                d3d11::D3D_PRIMITIVE_TOPOLOGY_LINESTRIP,
+                d3dcommon::D3D_PRIMITIVE_TOPOLOGY_LINESTRIP,
+            ),
+            (
+                d3d11::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+                d3dcommon::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+            ),
+            (
+                d3d11::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+                d3dcommon::D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+            ),
+            (d3d11::D3D11_FILL_WIREFRAME, w::D3D11_FILL_WIREFRAME),
+            (d3d11::D3D11_FILL_SOLID, w::D3D11_FILL_SOLID),
+            (d3d11::D3D11_CULL_NONE, w::D3D11_CULL_NONE),
+            (d3d11::D3D11_CULL_FRONT, w::D3D11_CULL_FRONT),
+            (d3d11::D3D11_CULL_BACK, w::D3D11_CULL_BACK),
+            (d3d11::D3D11_BLEND_ZERO, w::D3D11_BLEND_ZERO),
+            (d3d11::D3D11_BLEND_ONE, w::D3D11_BLEND_ONE),
+            (d3d11::D3D11_BLEND_SRC_COLOR, w::D3D11_BLEND_SRC_COLOR),
+            (
+                d3d11::D3D11_BLEND_INV_SRC_COLOR,
+                w::D3D11_BLEND_INV_SRC_COLOR,
+            ),
+            (d3d11::D3D11_BLEND_SRC_ALPHA, w::D3D11_BLEND_SRC_ALPHA),
+            (
+                d3d11::D3D11_BLEND_INV_SRC_ALPHA,
+                w::D3D11_BLEND_INV_SRC_ALPHA,
+            ),
+            (d3d11::D3D11_BLEND_DEST_ALPHA, w::D3D11_BLEND_DEST_ALPHA),
+            (
+                d3d11::D3D11_BLEND_INV_DEST_ALPHA,
+                w::D3D11_BLEND_INV_DEST_ALPHA,
+            ),
+            (d3d11::D3D11_BLEND_DEST_COLOR, w::D3D11_BLEND_DEST_COLOR),
+            (
+                d3d11::D3D11_BLEND_INV_DEST_COLOR,
+                w::D3D11_BLEND_INV_DEST_COLOR,
+            ),
+            (
+                d3d11::D3D11_BLEND_SRC_ALPHA_SAT,
+                w::D3D11_BLEND_SRC_ALPHA_SAT,
+            ),
+            (d3d11::D3D11_BLEND_BLEND_FACTOR, w::D3D11_BLEND_BLEND_FACTOR),
+            (
+                d3d11::D3D11_BLEND_INV_BLEND_FACTOR,
+                w::D3D11_BLEND_INV_BLEND_FACTOR,
+            ),
+            (d3d11::D3D11_BLEND_OP_ADD, w::D3D11_BLEND_OP_ADD),
+            (d3d11::D3D11_BLEND_OP_SUBTRACT, w::D3D11_BLEND_OP_SUBTRACT),
+            (
+                d3d11::D3D11_BLEND_OP_REV_SUBTRACT,
+                w::D3D11_BLEND_OP_REV_SUBTRACT,
+            ),
+            (d3d11::D3D11_BLEND_OP_MIN, w::D3D11_BLEND_OP_MIN),
+            (d3d11::D3D11_BLEND_OP_MAX, w::D3D11_BLEND_OP_MAX),
+            (d3d11::D3D11_STENCIL_OP_KEEP, w::D3D11_STENCIL_OP_KEEP),
+            (d3d11::D3D11_STENCIL_OP_ZERO, w::D3D11_STENCIL_OP_ZERO),
+            (d3d11::D3D11_STENCIL_OP_REPLACE, w::D3D11_STENCIL_OP_REPLACE),
+            (
+                d3d11::D3D11_STENCIL_OP_INCR_SAT,
+                w::D3D11_STENCIL_OP_INCR_SAT,
+            ),
+            (
+                d3d11::D3D11_STENCIL_OP_DECR_SAT,
+                w::D3D11_STENCIL_OP_DECR_SAT,
+            ),
+            (d3d11::D3D11_STENCIL_OP_INVERT, w::D3D11_STENCIL_OP_INVERT),
+            (d3d11::D3D11_STENCIL_OP_INCR, w::D3D11_STENCIL_OP_INCR),
+            (d3d11::D3D11_STENCIL_OP_DECR, w::D3D11_STENCIL_OP_DECR),
+            (
+                d3d11::D3D11_DEPTH_WRITE_MASK_ZERO,
+                w::D3D11_DEPTH_WRITE_MASK_ZERO,
+            ),
+            (
+                d3d11::D3D11_DEPTH_WRITE_MASK_ALL,
+                w::D3D11_DEPTH_WRITE_MASK_ALL,
+            ),
+            (d3d11::D3D11_QUERY_OCCLUSION, w::D3D11_QUERY_OCCLUSION),
+            (d3d11::D3D11_QUERY_TIMESTAMP, w::D3D11_QUERY_TIMESTAMP),
+            (
+                d3d11::D3D11_QUERY_PIPELINE_STATISTICS,
+                w::D3D11_QUERY_PIPELINE_STATISTICS,
+            ),
+            (
+                d3d11::D3D11_SIMULTANEOUS_RENDER_TARGET_COUNT,
+                w::D3D11_SIMULTANEOUS_RENDER_TARGET_COUNT,
+            ),
+        ];
+        for (index, &(ours, theirs)) in pairs.iter().enumerate() {
+            assert_eq!(ours, theirs, "value #{}", index);
+        }
+    }
+}
@@ -1,23 +1,718 @@
-use std::{ffi::c_void, mem};
+use super::conv;
+use crate::auxil::{self, dxgi::result::HResult as _};
 
-use winapi::um::d3d11;
+use arrayvec::ArrayVec;
+use parking_lot::Mutex;
+use std::{borrow::Cow, ffi, mem, num::NonZeroU32, ptr, slice, sync::Arc, thread, time};
+use winapi::{
+    shared::{dxgiformat, dxgitype, winerror},
+    um::{d3d11, d3d11_1, d3dcommon, d3dcompiler},
+    Interface as _,
+};
 
-use crate::auxil::dxgi::result::HResult;
+// this has to match Naga's HLSL backend, and also needs to be null-terminated
+const NAGA_LOCATION_SEMANTIC: &[u8] = b"LOC\0";
+
+/// Layout of a buffer-texture copy, in bytes.
+struct CopyLayout {
+    row_bytes: usize,
+    rows: usize,
+    /// Number of array layers, or 1 for 3D textures.
+    layers: usize,
+    /// Number of depth slices, or 1 for the other textures.
+    depth: u32,
+    is_volume: bool,
+    buffer_row_pitch: usize,
+    buffer_image_pitch: usize,
+}
+
+impl CopyLayout {
+    fn new(
+        format: wgt::TextureFormat,
+        dimension: wgt::TextureDimension,
+        copy: &crate::BufferTextureCopy,
+    ) -> Self {
+        let info = format.describe();
+        let (block_width, block_height) = info.block_dimensions;
+        let row_bytes = (copy.size.width / block_width as u32) as usize * info.block_size as usize;
+        let rows = (copy.size.height / block_height as u32) as usize;
+        let buffer_row_pitch = copy
+            .buffer_layout
+            .bytes_per_row
+            .map_or(row_bytes, |bpr| bpr.get() as usize);
+        let buffer_image_pitch = copy
+            .buffer_layout
+            .rows_per_image
+            .map_or(rows, |rpi| (rpi.get() / block_height as u32) as usize)
+            * buffer_row_pitch;
+        let is_volume = dimension == wgt::TextureDimension::D3;
+        let (layers, depth) = match dimension {
+            wgt::TextureDimension::D3 => (1, copy.size.depth),
+            wgt::TextureDimension::D1 | wgt::TextureDimension::D2 => (copy.size.depth as usize, 1),
+        };
+        Self {
+            row_bytes,
+            rows,
+            layers,
+            depth,
+            is_volume,
+            buffer_row_pitch,
+            buffer_image_pitch,
+        }
+    }
+
+    /// Number of bytes the copy spans in the buffer, from the copy offset.
+    fn buffer_span(&self) -> usize {
+        let images = self.layers * self.depth as usize;
+        if images == 0 || self.rows == 0 {
+            return 0;
+        }
+        (images - 1) * self.buffer_image_pitch
+            + (self.rows - 1) * self.buffer_row_pitch
+            + self.row_bytes
+    }
+
+    fn texture_box(
+        &self,
+        origin: wgt::Origin3d,
+        copy: &crate::BufferTextureCopy,
+    ) -> d3d11::D3D11_BOX {
+        d3d11::D3D11_BOX {
+            left: origin.x,
+            top: origin.y,
+            front: if self.is_volume { origin.z } else { 0 },
+            right: origin.x + copy.size.width,
+            bottom: origin.y + copy.size.height,
+            back: if self.is_volume {
+                origin.z + self.depth
+            } else {
+                1
+            },
+        }
+    }
+}
+
+fn buffer_box(range: crate::MemoryRange) -> d3d11::D3D11_BOX {
+    d3d11::D3D11_BOX {
+        left: range.start as u32,
+        top: 0,
+        front: 0,
+        right: range.end as u32,
+        bottom: 1,
+        back: 1,
+    }
+}
+
+/// Names the object in the debug layer and the graphics debuggers.
+pub(super) unsafe fn set_debug_name(object: &d3d11::ID3D11DeviceChild, label: crate::Label) {
+    if let Some(name) = label {
+        object.SetPrivateData(
+            &d3dcommon::WKPDID_D3DDebugObjectName,
+            name.len() as u32,
+            name.as_ptr() as *const _,
+        );
+    }
+}
+
+impl Drop for super::DeviceShared {
+    fn drop(&mut self) {
+        unsafe {
+            self.context.get_mut().destroy();
+            self.raw.destroy();
+        }
+    }
+}
+
+impl super::DeviceShared {
+    unsafe fn create_staging_buffer(
+        &self,
+        size: wgt::BufferAddress,
+    ) -> Result<native::WeakPtr<d3d11::ID3D11Resource>, crate::DeviceError> {
+        let desc = d3d11::D3D11_BUFFER_DESC {
+            ByteWidth: size as u32,
+            Usage: d3d11::D3D11_USAGE_STAGING,
+            BindFlags: 0,
+            CPUAccessFlags: d3d11::D3D11_CPU_ACCESS_READ,
+            MiscFlags: 0,
+            StructureByteStride: 0,
+        };
+        let mut staging = native::WeakPtr::<d3d11::ID3D11Resource>::null();
+        self.raw
+            .CreateBuffer(&desc, ptr::null(), staging.mut_self() as *mut *mut _)
+            .into_device_result("Staging buffer creation")?;
+        Ok(staging)
+    }
+
+    /// Creates a staging texture covering a single image of the copy.
+    unsafe fn create_staging_texture(
+        &self,
+        texture: &super::Texture,
+        copy: &crate::BufferTextureCopy,
+        layout: &CopyLayout,
+    ) -> Result<native::WeakPtr<d3d11::ID3D11Resource>, crate::DeviceError> {
+        let format = auxil::dxgi::conv::map_texture_format_nodepth(texture.format);
+        let mut staging = native::WeakPtr::<d3d11::ID3D11Resource>::null();
+        let hr = match texture.dimension {
+            wgt::TextureDimension::D1 => {
+                let desc = d3d11::D3D11_TEXTURE1D_DESC {
+                    Width: copy.size.width,
+                    MipLevels: 1,
+                    ArraySize: 1,
+                    Format: format,
+                    Usage: d3d11::D3D11_USAGE_STAGING,
+                    BindFlags: 0,
+                    CPUAccessFlags: d3d11::D3D11_CPU_ACCESS_READ,
+                    MiscFlags: 0,
+                };
+                self.raw
+                    .CreateTexture1D(&desc, ptr::null(), staging.mut_self() as *mut *mut _)
+            }
+            wgt::TextureDimension::D2 => {
+                let desc = d3d11::D3D11_TEXTURE2D_DESC {
+                    Width: copy.size.width,
+                    Height: copy.size.height,
+                    MipLevels: 1,
+                    ArraySize: 1,
+                    Format: format,
+                    SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
+                        Count: 1,
+                        Quality: 0,
+                    },
+                    Usage: d3d11::D3D11_USAGE_STAGING,
+                    BindFlags: 0,
+                    CPUAccessFlags: d3d11::D3D11_CPU_ACCESS_READ,
+                    MiscFlags: 0,
+                };
+                self.raw
+                    .CreateTexture2D(&desc, ptr::null(), staging.mut_self() as *mut *mut _)
+            }
+            wgt::TextureDimension::D3 => {
+                let desc = d3d11::D3D11_TEXTURE3D_DESC {
+                    Width: copy.size.width,
+                    Height: copy.size.height,
+                    Depth: layout.depth,
+                    MipLevels: 1,
+                    Format: format,
+                    Usage: d3d11::D3D11_USAGE_STAGING,
+                    BindFlags: 0,
+                    CPUAccessFlags: d3d11::D3D11_CPU_ACCESS_READ,
+                    MiscFlags: 0,
+                };
+                self.raw
+                    .CreateTexture3D(&desc, ptr::null(), staging.mut_self() as *mut *mut _)
+            }
+        };
+        hr.into_device_result("Staging texture creation")?;
+        Ok(staging)
+    }
+
+    /// Copies a region of `src` into `staging` and maps it for reading.
+    /// This waits for the GPU to finish all the work submitted so far.
+    unsafe fn read_back<F: FnOnce(&d3d11::D3D11_MAPPED_SUBRESOURCE)>(
+        &self,
+        context: &d3d11::ID3D11DeviceContext,
+        staging: native::WeakPtr<d3d11::ID3D11Resource>,
+        src: (*mut d3d11::ID3D11Resource, u32),
+        src_box: &d3d11::D3D11_BOX,
+        read: F,
+    ) -> Result<(), crate::DeviceError> {
+        context.CopySubresourceRegion(staging.as_mut_ptr(), 0, 0, 0, 0, src.0, src.1, src_box);
+
+        let mut mapped = mem::zeroed::<d3d11::D3D11_MAPPED_SUBRESOURCE>();
+        let hr = {
+            profiling::scope!("ID3D11DeviceContext::Map");
+            context.Map(
+                staging.as_mut_ptr(),
+                0,
+                d3d11::D3D11_MAP_READ,
+                0,
+                &mut mapped,
+            )
+        };
+        let result = hr.into_device_result("Staging map");
+        if result.is_ok() {
+            read(&mapped);
+            context.Unmap(staging.as_mut_ptr(), 0);
+        }
+        staging.destroy();
+        result
+    }
+
+    unsafe fn read_buffer<'a>(
+        &self,
+        context: &d3d11::ID3D11DeviceContext,
+        src: &'a super::Buffer,
+        range: crate::MemoryRange,
+    ) -> Result<Cow<'a, [u8]>, crate::DeviceError> {
+        let size = (range.end - range.start) as usize;
+        if let Some(ptr) = src.host_ptr() {
+            let data = slice::from_raw_parts(ptr.as_ptr().offset(range.start as isize), size);
+            return Ok(Cow::Borrowed(data));
+        }
+
+        let mut data = vec![0; size];
+        if size != 0 {
+            let staging = self.create_staging_buffer(size as wgt::BufferAddress)?;
+            self.read_back(
+                context,
+                staging,
+                (src.resource.as_mut_ptr() as *mut _, 0),
+                &buffer_box(range),
+                |mapped| {
+                    ptr::copy_nonoverlapping(mapped.pData as *const u8, data.as_mut_ptr(), size)
+                },
+            )?;
+        }
+        Ok(Cow::Owned(data))
+    }
+
+    unsafe fn write_buffer(
+        &self,
+        context: &d3d11::ID3D11DeviceContext,
+        dst: &super::Buffer,
+        offset: wgt::BufferAddress,
+        data: &[u8],
+    ) {
+        if let Some(ptr) = dst.host_ptr() {
+            ptr::copy(
+                data.as_ptr(),
+                ptr.as_ptr().offset(offset as isize),
+                data.len(),
+            );
+            return;
+        }
+        if data.is_empty() {
+            return;
+        }
+
+        let range = offset..offset + data.len() as wgt::BufferAddress;
+        let is_partial = range.start != 0 || range.end < dst.size;
+        if !is_partial {
+            context.UpdateSubresource(
+                dst.resource.as_mut_ptr() as *mut _,
+                0,
+                ptr::null(),
+                data.as_ptr() as *const _,
+                0,
+                0,
+            );
+        } else if !dst.usage.contains(crate::BufferUses::UNIFORM) {
+            context.UpdateSubresource(
+                dst.resource.as_mut_ptr() as *mut _,
+                0,
+                &buffer_box(range),
+                data.as_ptr() as *const _,
+                0,
+                0,
+            );
+        } else if self.private_caps.constant_buffer_partial_update {
+            let mut context1 = native::WeakPtr::<d3d11_1::ID3D11DeviceContext1>::null();
+            let hr = context.QueryInterface(
+                &d3d11_1::ID3D11DeviceContext1::uuidof(),
+                context1.mut_void(),
+            );
+            if hr.into_result().is_ok() {
+                context1.UpdateSubresource1(
+                    dst.resource.as_mut_ptr() as *mut _,
+                    0,
+                    &buffer_box(range),
+                    data.as_ptr() as *const _,
+                    0,
+                    0,
+                    0,
+                );
+                context1.destroy();
+            }
+        } else {
+            log::error!("Partial updates of uniform buffers require D3D11.1");
+        }
+    }
+
+    /// Waits for the query to finish, flushing the context.
+    pub(super) unsafe fn get_query_data<T>(
+        context: &d3d11::ID3D11DeviceContext,
+        query: native::WeakPtr<d3d11::ID3D11Query>,
+    ) -> Result<T, crate::DeviceError> {
+        let mut data = mem::zeroed::<T>();
+        loop {
+            let hr = context.GetData(
+                query.as_mut_ptr() as *mut _,
+                &mut data as *mut T as *mut _,
+                mem::size_of::<T>() as u32,
+                0,
+            );
+            if hr != winerror::S_FALSE {
+                hr.into_device_result("Query data")?;
+                return Ok(data);
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Executes a transfer step on the immediate context.
+    pub(super) unsafe fn execute_transfer(
+        &self,
+        context: &d3d11::ID3D11DeviceContext,
+        transfer: &super::Transfer,
+    ) -> Result<(), crate::DeviceError> {
+        use super::Transfer as T;
+
+        match *transfer {
+            T::CopyBuffer {
+                ref src,
+                ref dst,
+                ref regions,
+            } => {
+                for r in regions {
+                    let src_range = r.src_offset..r.src_offset + r.size.get();
+                    if src.host.is_none() && dst.host.is_none() {
+                        context.CopySubresourceRegion(
+                            dst.resource.as_mut_ptr() as *mut _,
+                            0,
+                            r.dst_offset as u32,
+                            0,
+                            0,
+                            src.resource.as_mut_ptr() as *mut _,
+                            0,
+                            &buffer_box(src_range),
+                        );
+                    } else {
+                        let data = self.read_buffer(context, src, src_range)?;
+                        self.write_buffer(context, dst, r.dst_offset, &data);
+                    }
+                }
+            }
+            T::FillBuffer { ref dst, ref range } => {
+                let zeros = vec![0; (range.end - range.start) as usize];
+                self.write_buffer(context, dst, range.start, &zeros);
+            }
+            T::CopyBufferToTexture {
+                ref src,
+                ref dst,
+                ref regions,
+            } => {
+                for r in regions {
+                    let layout = CopyLayout::new(dst.format, dst.dimension, r);
+                    let offset = r.buffer_layout.offset;
+                    let data = self.read_buffer(
+                        context,
+                        src,
+                        offset..offset + layout.buffer_span() as wgt::BufferAddress,
+                    )?;
+                    if data.is_empty() {
+                        continue;
+                    }
+                    let dst_box = layout.texture_box(r.texture_base.origin, r);
+                    for layer in 0..layout.layers {
+                        let subresource = dst.calc_subresource(
+                            r.texture_base.mip_level,
+                            r.texture_base.array_layer + layer as u32,
+                        );
+                        context.UpdateSubresource(
+                            dst.resource.as_mut_ptr(),
+                            subresource,
+                            &dst_box,
+                            data[layer * layout.buffer_image_pitch..].as_ptr() as *const _,
+                            layout.buffer_row_pitch as u32,
+                            layout.buffer_image_pitch as u32,
+                        );
+                    }
+                }
+            }
+            T::CopyTextureToBuffer {
+                ref src,
+                ref dst,
+                ref regions,
+            } => {
+                for r in regions {
+                    let layout = CopyLayout::new(src.format, src.dimension, r);
+                    if layout.buffer_span() == 0 {
+                        continue;
+                    }
+                    let src_box = layout.texture_box(r.texture_base.origin, r);
+                    for layer in 0..layout.layers {
+                        let subresource = src.calc_subresource(
+                            r.texture_base.mip_level,
+                            r.texture_base.array_layer + layer as u32,
+                        );
+                        let staging = self.create_staging_texture(src, r, &layout)?;
+                        let mut image =
+                            vec![0u8; layout.depth as usize * layout.rows * layout.row_bytes];
+                        self.read_back(
+                            context,
+                            staging,
+                            (src.resource.as_mut_ptr(), subresource),
+                            &src_box,
+                            |mapped| {
+                                for z in 0..layout.depth as usize {
+                                    for y in 0..layout.rows {
+                                        let src_offset = z * mapped.DepthPitch as usize
+                                            + y * mapped.RowPitch as usize;
+                                        let dst_offset = (z * layout.rows + y) * layout.row_bytes;
+                                        ptr::copy_nonoverlapping(
+                                            (mapped.pData as *const u8).add(src_offset),
+                                            image.as_mut_ptr().add(dst_offset),
+                                            layout.row_bytes,
+                                        );
+                                    }
+                                }
+                            },
+                        )?;
+
+                        // The bytes in between the rows have to be preserved,
+                        // so the rows are written one by one.
+                        for z in 0..layout.depth as usize {
+                            for y in 0..layout.rows {
+                                let offset = r.buffer_layout.offset as usize
+                                    + (layer + z) * layout.buffer_image_pitch
+                                    + y * layout.buffer_row_pitch;
+                                let row_start = (z * layout.rows + y) * layout.row_bytes;
+                                self.write_buffer(
+                                    context,
+                                    dst,
+                                    offset as wgt::BufferAddress,
+                                    &image[row_start..row_start + layout.row_bytes],
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            T::CopyQueryResults {
+                ref queries,
+                ty,
+                ref dst,
+                offset,
+                stride,
+            } => {
+                for (i, &query) in queries.iter().enumerate() {
+                    let values = match ty {
+                        wgt::QueryType::Occlusion | wgt::QueryType::Timestamp => {
+                            vec![Self::get_query_data::<u64>(context, query)?]
+                        }
+                        wgt::QueryType::PipelineStatistics(types) => {
+                            let data = Self::get_query_data::<
+                                d3d11::D3D11_QUERY_DATA_PIPELINE_STATISTICS,
+                            >(context, query)?;
+                            conv::map_pipeline_statistics(
+                                types,
+                                [
+                                    data.VSInvocations,
+                                    data.CInvocations,
+                                    data.CPrimitives,
+                                    data.PSInvocations,
+                                    data.CSInvocations,
+                                ],
+                            )
+                        }
+                    };
+                    let bytes = slice::from_raw_parts(
+                        values.as_ptr() as *const u8,
+                        values.len() * mem::size_of::<u64>(),
+                    );
+                    self.write_buffer(context, dst, offset + i as u64 * stride.get(), bytes);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl super::FenceState {
+    /// Retires the event queries the GPU went past, without flushing the context.
+    unsafe fn update(&mut self, context: &d3d11::ID3D11DeviceContext) {
+        // The queries finish in the order of submission.
+        let finished = self
+            .pending
+            .iter()
+            .take_while(|&&(_, query)| {
+                let hr = context.GetData(
+                    query.as_mut_ptr() as *mut _,
+                    ptr::null_mut(),
+                    0,
+                    d3d11::D3D11_ASYNC_GETDATA_DONOTFLUSH,
+                );
+                hr == winerror::S_OK
+            })
+            .count();
+        for (value, query) in self.pending.drain(..finished) {
+            self.completed = value;
+            query.destroy();
+        }
+    }
+}
+
+impl super::Device {
+    fn load_shader(
+        &self,
+        stage: &crate::ProgrammableStage<super::Api>,
+        layout: &super::PipelineLayout,
+        naga_stage: naga::ShaderStage,
+    ) -> Result<native::Blob, crate::PipelineError> {
+        use naga::back::hlsl;
+
+        let stage_bit = crate::auxil::map_naga_stage(naga_stage);
+        let module =
+            crate::auxil::apply_pipeline_constants(&stage.module.naga.module, stage.constants);
+        let mut source = String::new();
+        let mut writer = hlsl::Writer::new(&mut source, &layout.naga_options);
+        let reflection_info = {
+            profiling::scope!("naga::back::hlsl::write");
+            writer
+                .write(&module, &stage.module.naga.info)
+                .map_err(|e| crate::PipelineError::Linkage(stage_bit, format!("HLSL: {:?}", e)))?
+        };
+
+        let full_stage = format!(
+            "{}_{}\0",
+            naga_stage.to_hlsl_str(),
+            self.shared.private_caps.shader_profile
+        );
+        let ep_index = module
+            .entry_points
+            .iter()
+            .position(|ep| ep.stage == naga_stage && ep.name == stage.entry_point)
+            .ok_or(crate::PipelineError::EntryPoint(naga_stage))?;
+        let raw_ep = reflection_info.entry_point_names[ep_index]
+            .as_ref()
+            .map(|name| ffi::CString::new(name.as_str()).unwrap())
+            .map_err(|e| crate::PipelineError::Linkage(stage_bit, format!("{}", e)))?;
+
+        let mut shader_data = native::Blob::null();
+        let mut error = native::Blob::null();
+        let mut compile_flags = d3dcompiler::D3DCOMPILE_ENABLE_STRICTNESS;
+        if self
+            .shared
+            .private_caps
+            .instance_flags
+            .contains(crate::InstanceFlags::DEBUG)
+        {
+            compile_flags |=
+                d3dcompiler::D3DCOMPILE_DEBUG | d3dcompiler::D3DCOMPILE_SKIP_OPTIMIZATION;
+        }
+
+        let source_name = match stage.module.raw_name {
+            Some(ref cstr) => cstr.as_c_str().as_ptr(),
+            None => ptr::null(),
+        };
+
+        let hr = unsafe {
+            profiling::scope!("d3dcompiler::D3DCompile");
+            d3dcompiler::D3DCompile(
+                source.as_ptr() as *const _,
+                source.len(),
+                source_name,
+                ptr::null(),
+                ptr::null_mut(),
+                raw_ep.as_ptr(),
+                full_stage.as_ptr() as *const i8,
+                compile_flags,
+                0,
+                shader_data.mut_void() as *mut *mut _,
+                error.mut_void() as *mut *mut _,
+            )
+        };
+
+        let (result, log_level) = match hr.into_result() {
+            Ok(()) => (Ok(shader_data), log::Level::Info),
+            Err(e) => {
+                let mut full_msg = format!("D3DCompile error ({})", e);
+                if !error.is_null() {
+                    use std::fmt::Write as _;
+                    let message = unsafe {
+                        slice::from_raw_parts(
+                            error.GetBufferPointer() as *const u8,
+                            error.GetBufferSize(),
+                        )
+                    };
+                    let _ = write!(full_msg, ": {}", String::from_utf8_lossy(message));
+                    unsafe {
+                        error.destroy();
+                    }
+                }
+                (
+                    Err(crate::PipelineError::Linkage(stage_bit, full_msg)),
+                    log::Level::Warn,
+                )
+            }
+        };
+
+        log::log!(
+            log_level,
+            "Naga generated shader for {:?} at {:?}:\n{}",
+            raw_ep,
+            naga_stage,
+            source
+        );
+        result
+    }
+}
 
 impl crate::Device<super::Api> for super::Device {
     unsafe fn exit(self, queue: super::Queue) {
-        todo!()
+        queue.disjoint_query.destroy();
+        // The device and the immediate context are released with the last reference,
+        // which may be held by a command encoder.
     }
 
     unsafe fn create_buffer(
         &self,
         desc: &crate::BufferDescriptor,
     ) -> Result<super::Buffer, crate::DeviceError> {
-        todo!()
+        if conv::is_host_buffer(desc.usage) {
+            let words = ((desc.size + 7) / 8) as usize;
+            let memory = vec![0u64; words].into_boxed_slice();
+            let host = super::HostMemory {
+                ptr: ptr::NonNull::new_unchecked(Box::into_raw(memory) as *mut u64),
+                words,
+            };
+            return Ok(super::Buffer {
+                resource: native::WeakPtr::null(),
+                host: Some(host),
+                usage: desc.usage,
+                size: desc.size,
+            });
+        }
+
+        // Constant buffers have to be a multiple of 16 bytes, raw views a multiple of 4.
+        let alignment_mask = if desc.usage.contains(crate::BufferUses::UNIFORM) {
+            15
+        } else {
+            3
+        };
+        let raw_desc = d3d11::D3D11_BUFFER_DESC {
+            ByteWidth: ((desc.size + alignment_mask) & !alignment_mask) as u32,
+            Usage: d3d11::D3D11_USAGE_DEFAULT,
+            BindFlags: conv::map_buffer_usage_to_bind_flags(desc.usage),
+            CPUAccessFlags: 0,
+            MiscFlags: conv::map_buffer_usage_to_misc_flags(desc.usage),
+            StructureByteStride: 0,
+        };
+
+        let mut resource = native::WeakPtr::<d3d11::ID3D11Buffer>::null();
+        let hr = self
+            .shared
+            .raw
+            .CreateBuffer(&raw_desc, ptr::null(), resource.mut_self());
+        hr.into_device_result("Buffer creation")?;
+        set_debug_name(&resource, desc.label);
+
+        Ok(super::Buffer {
+            resource,
+            host: None,
+            usage: desc.usage,
+            size: desc.size,
+        })
     }
 
     unsafe fn destroy_buffer(&self, buffer: super::Buffer) {
-        todo!()
+        match buffer.host {
+            Some(host) => {
+                let memory = ptr::slice_from_raw_parts_mut(host.ptr.as_ptr(), host.words);
+                drop(Box::from_raw(memory));
+            }
+            None => buffer.resource.destroy(),
+        }
     }
 
     unsafe fn map_buffer(
@@ -25,36 +720,124 @@ impl crate::Device<super::Api> for super::Device {
         buffer: &super::Buffer,
         range: crate::MemoryRange,
     ) -> Result<crate::BufferMapping, crate::DeviceError> {
-        todo!()
+        let ptr = buffer.host_ptr().ok_or_else(|| {
+            log::error!("Only the buffers in host memory can be mapped");
+            crate::DeviceError::Lost
+        })?;
+        Ok(crate::BufferMapping {
+            ptr: ptr::NonNull::new_unchecked(ptr.as_ptr().offset(range.start as isize)),
+            is_coherent: true,
+        })
     }
 
-    unsafe fn unmap_buffer(&self, buffer: &super::Buffer) -> Result<(), crate::DeviceError> {
-        todo!()
+    unsafe fn unmap_buffer(&self, _buffer: &super::Buffer) -> Result<(), crate::DeviceError> {
+        Ok(())
     }
 
-    unsafe fn flush_mapped_ranges<I>(&self, buffer: &super::Buffer, ranges: I)
-    where
-        I: Iterator<Item = crate::MemoryRange>,
-    {
-        todo!()
-    }
-
-    unsafe fn invalidate_mapped_ranges<I>(&self, buffer: &super::Buffer, ranges: I)
-    where
-        I: Iterator<Item = crate::MemoryRange>,
-    {
-        todo!()
-    }
+    unsafe fn flush_mapped_ranges<I>(&self, _buffer: &super::Buffer, _ranges: I) {}
+    unsafe fn invalidate_mapped_ranges<I>(&self, _buffer: &super::Buffer, _ranges: I) {}
 
     unsafe fn create_texture(
         &self,
         desc: &crate::TextureDescriptor,
     ) -> Result<super::Texture, crate::DeviceError> {
-        todo!()
+        let format = if crate::FormatAspects::from(desc.format)
+            .contains(crate::FormatAspects::COLOR)
+            || !desc.usage.intersects(
+                crate::TextureUses::RESOURCE
+                    | crate::TextureUses::STORAGE_READ
+                    | crate::TextureUses::STORAGE_READ_WRITE,
+            ) {
+            auxil::dxgi::conv::map_texture_format(desc.format)
+        } else {
+            // Depth textures viewed as SRV need to be typeless.
+            auxil::dxgi::conv::map_texture_format_depth_typeless(desc.format)
+        };
+        let bind_flags = conv::map_texture_usage_to_bind_flags(desc.usage);
+
+        let mut resource = native::WeakPtr::<d3d11::ID3D11Resource>::null();
+        let hr = match desc.dimension {
+            wgt::TextureDimension::D1 => {
+                let raw_desc = d3d11::D3D11_TEXTURE1D_DESC {
+                    Width: desc.size.width,
+                    MipLevels: desc.mip_level_count,
+                    ArraySize: desc.size.depth_or_array_layers,
+                    Format: format,
+                    Usage: d3d11::D3D11_USAGE_DEFAULT,
+                    BindFlags: bind_flags,
+                    CPUAccessFlags: 0,
+                    MiscFlags: 0,
+                };
+                self.shared.raw.CreateTexture1D(
+                    &raw_desc,
+                    ptr::null(),
+                    resource.mut_self() as *mut *mut _,
+                )
+            }
+            wgt::TextureDimension::D2 => {
+                let array_size = desc.size.depth_or_array_layers;
+                let is_cube_compatible = array_size % 6 == 0
+                    && desc.size.width == desc.size.height
+                    && desc.sample_count == 1;
+                let raw_desc = d3d11::D3D11_TEXTURE2D_DESC {
+                    Width: desc.size.width,
+                    Height: desc.size.height,
+                    MipLevels: desc.mip_level_count,
+                    ArraySize: array_size,
+                    Format: format,
+                    SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
+                        Count: desc.sample_count,
+                        Quality: 0,
+                    },
+                    Usage: d3d11::D3D11_USAGE_DEFAULT,
+                    BindFlags: bind_flags,
+                    CPUAccessFlags: 0,
+                    MiscFlags: if is_cube_compatible {
+                        d3d11::D3D11_RESOURCE_MISC_TEXTURECUBE
+                    } else {
+                        0
+                    },
+                };
+                self.shared.raw.CreateTexture2D(
+                    &raw_desc,
+                    ptr::null(),
+                    resource.mut_self() as *mut *mut _,
+                )
+            }
+            wgt::TextureDimension::D3 => {
+                let raw_desc = d3d11::D3D11_TEXTURE3D_DESC {
+                    Width: desc.size.width,
+                    Height: desc.size.height,
+                    Depth: desc.size.depth_or_array_layers,
+                    MipLevels: desc.mip_level_count,
+                    Format: format,
+                    Usage: d3d11::D3D11_USAGE_DEFAULT,
+                    BindFlags: bind_flags,
+                    CPUAccessFlags: 0,
+                    MiscFlags: 0,
+                };
+                self.shared.raw.CreateTexture3D(
+                    &raw_desc,
+                    ptr::null(),
+                    resource.mut_self() as *mut *mut _,
+                )
+            }
+        };
+        hr.into_device_result("Texture creation")?;
+        set_debug_name(&resource, desc.label);
+
+        Ok(super::Texture {
+            resource,
+            format: desc.format,
+            dimension: desc.dimension,
+            size: desc.size,
+            mip_level_count: desc.mip_level_count,
+            sample_count: desc.sample_count,
+        })
     }
 
     unsafe fn destroy_texture(&self, texture: super::Texture) {
-        todo!()
+        texture.resource.destroy();
     }
 
     unsafe fn create_texture_view(
@@ -62,66 +845,412 @@ impl crate::Device<super::Api> for super::Device {
         texture: &super::Texture,
         desc: &crate::TextureViewDescriptor,
     ) -> Result<super::TextureView, crate::DeviceError> {
-        todo!()
+        let view_desc = desc.to_internal(texture);
+        let resource = texture.resource.as_mut_ptr();
+
+        let mut srv = native::WeakPtr::null();
+        if desc.usage.intersects(crate::TextureUses::RESOURCE) {
+            let raw_desc = view_desc.to_srv();
+            self.shared
+                .raw
+                .CreateShaderResourceView(resource, &raw_desc, srv.mut_self())
+                .into_device_result("SRV creation")?;
+        }
+        let mut uav = native::WeakPtr::null();
+        if desc
+            .usage
+            .intersects(crate::TextureUses::STORAGE_READ | crate::TextureUses::STORAGE_READ_WRITE)
+        {
+            let raw_desc = view_desc.to_uav();
+            self.shared
+                .raw
+                .CreateUnorderedAccessView(resource, &raw_desc, uav.mut_self())
+                .into_device_result("UAV creation")?;
+        }
+        let mut rtv = native::WeakPtr::null();
+        if desc.usage.intersects(crate::TextureUses::COLOR_TARGET) {
+            let raw_desc = view_desc.to_rtv();
+            self.shared
+                .raw
+                .CreateRenderTargetView(resource, &raw_desc, rtv.mut_self())
+                .into_device_result("RTV creation")?;
+        }
+        let mut dsv_rw = native::WeakPtr::null();
+        if desc
+            .usage
+            .intersects(crate::TextureUses::DEPTH_STENCIL_WRITE)
+        {
+            let raw_desc = view_desc.to_dsv(crate::FormatAspects::empty());
+            self.shared
+                .raw
+                .CreateDepthStencilView(resource, &raw_desc, dsv_rw.mut_self())
+                .into_device_result("DSV creation")?;
+        }
+        let mut dsv_ro = native::WeakPtr::null();
+        // Read-only depth stencil views require feature level 11.
+        if desc
+            .usage
+            .intersects(crate::TextureUses::DEPTH_STENCIL_READ)
+            && self.shared.private_caps.feature_level >= d3dcommon::D3D_FEATURE_LEVEL_11_0
+        {
+            let raw_desc = view_desc.to_dsv(desc.format.into());
+            self.shared
+                .raw
+                .CreateDepthStencilView(resource, &raw_desc, dsv_ro.mut_self())
+                .into_device_result("DSV creation")?;
+        }
+
+        Ok(super::TextureView {
+            texture: texture.clone(),
+            subresource: texture
+                .calc_subresource(desc.range.base_mip_level, desc.range.base_array_layer),
+            format: view_desc.format,
+            srv,
+            uav,
+            rtv,
+            dsv_rw,
+            dsv_ro,
+        })
     }
 
     unsafe fn destroy_texture_view(&self, view: super::TextureView) {
-        todo!()
+        if !view.srv.is_null() {
+            view.srv.destroy();
+        }
+        if !view.uav.is_null() {
+            view.uav.destroy();
+        }
+        if !view.rtv.is_null() {
+            view.rtv.destroy();
+        }
+        if !view.dsv_rw.is_null() {
+            view.dsv_rw.destroy();
+        }
+        if !view.dsv_ro.is_null() {
+            view.dsv_ro.destroy();
+        }
     }
 
     unsafe fn create_sampler(
         &self,
         desc: &crate::SamplerDescriptor,
     ) -> Result<super::Sampler, crate::DeviceError> {
-        todo!()
+        let lod_clamp = desc
+            .lod_clamp
+            .clone()
+            .unwrap_or(0.0..d3d11::D3D11_FLOAT32_MAX);
+        let raw_desc = d3d11::D3D11_SAMPLER_DESC {
+            Filter: conv::map_filter(desc),
+            AddressU: conv::map_address_mode(desc.address_modes[0]),
+            AddressV: conv::map_address_mode(desc.address_modes[1]),
+            AddressW: conv::map_address_mode(desc.address_modes[2]),
+            MipLODBias: 0.0,
+            MaxAnisotropy: desc.anisotropy_clamp.map_or(0, |aniso| aniso.get() as u32),
+            ComparisonFunc: desc
+                .compare
+                .map_or(d3d11::D3D11_COMPARISON_NEVER, conv::map_comparison),
+            BorderColor: conv::map_border_color(desc.border_color),
+            MinLOD: lod_clamp.start,
+            MaxLOD: lod_clamp.end,
+        };
+
+        let mut raw = native::WeakPtr::<d3d11::ID3D11SamplerState>::null();
+        self.shared
+            .raw
+            .CreateSamplerState(&raw_desc, raw.mut_self())
+            .into_device_result("Sampler creation")?;
+        set_debug_name(&raw, desc.label);
+
+        Ok(super::Sampler { raw })
     }
 
     unsafe fn destroy_sampler(&self, sampler: super::Sampler) {
-        todo!()
+        sampler.raw.destroy();
     }
 
     unsafe fn create_command_encoder(
         &self,
         desc: &crate::CommandEncoderDescriptor<super::Api>,
     ) -> Result<super::CommandEncoder, crate::DeviceError> {
-        todo!()
+        let mut context = native::WeakPtr::<d3d11::ID3D11DeviceContext>::null();
+        self.shared
+            .raw
+            .CreateDeferredContext(0, context.mut_self())
+            .into_device_result("Deferred context creation")?;
+        set_debug_name(&context, desc.label);
+
+        // Both are optional, the pointers stay null on failure.
+        let (context1, _) = context.cast::<d3d11_1::ID3D11DeviceContext1>();
+        let (annotation, _) = context.cast::<d3d11_1::ID3DUserDefinedAnnotation>();
+
+        let buffer_desc = d3d11::D3D11_BUFFER_DESC {
+            ByteWidth: 16,
+            Usage: d3d11::D3D11_USAGE_DEFAULT,
+            BindFlags: d3d11::D3D11_BIND_CONSTANT_BUFFER,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+            StructureByteStride: 0,
+        };
+        let mut special_constants_buffer = native::WeakPtr::<d3d11::ID3D11Buffer>::null();
+        self.shared
+            .raw
+            .CreateBuffer(
+                &buffer_desc,
+                ptr::null(),
+                special_constants_buffer.mut_self(),
+            )
+            .into_device_result("Special constants buffer creation")?;
+
+        Ok(super::CommandEncoder {
+            shared: Arc::clone(&self.shared),
+            context,
+            context1,
+            annotation,
+            special_constants_buffer,
+            steps: Vec::new(),
+            pass: super::PassState::new(),
+            temp_marker: Vec::new(),
+        })
     }
 
-    unsafe fn destroy_command_encoder(&self, pool: super::CommandEncoder) {
-        todo!()
+    unsafe fn destroy_command_encoder(&self, encoder: super::CommandEncoder) {
+        for step in encoder.steps {
+            if let super::Step::Commands(list) = step {
+                list.destroy();
+            }
+        }
+        encoder.special_constants_buffer.destroy();
+        if !encoder.annotation.is_null() {
+            encoder.annotation.destroy();
+        }
+        if !encoder.context1.is_null() {
+            encoder.context1.destroy();
+        }
+        encoder.context.destroy();
     }
 
     unsafe fn create_bind_group_layout(
         &self,
         desc: &crate::BindGroupLayoutDescriptor,
     ) -> Result<super::BindGroupLayout, crate::DeviceError> {
-        todo!()
+        Ok(super::BindGroupLayout {
+            entries: desc.entries.to_vec(),
+        })
     }
 
-    unsafe fn destroy_bind_group_layout(&self, bg_layout: super::BindGroupLayout) {
-        todo!()
-    }
+    unsafe fn destroy_bind_group_layout(&self, _bg_layout: super::BindGroupLayout) {}
 
     unsafe fn create_pipeline_layout(
         &self,
         desc: &crate::PipelineLayoutDescriptor<super::Api>,
     ) -> Result<super::PipelineLayout, crate::DeviceError> {
-        todo!()
-    }
+        use naga::back::hlsl;
+
+        let mut binding_map = hlsl::BindingMap::default();
+        let mut counts = super::RegisterCounts::default();
+        let mut bind_group_bases = ArrayVec::new();
+
+        for (index, bgl) in desc.bind_group_layouts.iter().enumerate() {
+            bind_group_bases.push(counts);
+            for entry in bgl.entries.iter() {
+                let register = match entry.ty {
+                    wgt::BindingType::Buffer {
+                        ty: wgt::BufferBindingType::Uniform,
+                        ..
+                    } => &mut counts.constant_buffers,
+                    wgt::BindingType::Buffer {
+                        ty: wgt::BufferBindingType::Storage { read_only: true },
+                        ..
+                    }
+                    | wgt::BindingType::Texture { .. } => &mut counts.shader_resources,
+                    wgt::BindingType::Buffer {
+                        ty: wgt::BufferBindingType::Storage { read_only: false },
+                        ..
+                    }
+                    | wgt::BindingType::StorageTexture { .. } => &mut counts.unordered_access,
+                    wgt::BindingType::Sampler { .. } => &mut counts.samplers,
+                };
+                binding_map.insert(
+                    naga::ResourceBinding {
+                        group: index as u32,
+                        binding: entry.binding,
+                    },
+                    hlsl::BindTarget {
+                        space: 0,
+                        register: *register,
+                        binding_array_size: entry.count.map(NonZeroU32::get),
+                    },
+                );
+                *register += entry.count.map_or(1, NonZeroU32::get);
+            }
+        }
+
+        let special_constants_slot = if desc.flags.intersects(
+            crate::PipelineLayoutFlags::BASE_VERTEX_INSTANCE
+                | crate::PipelineLayoutFlags::NUM_WORK_GROUPS,
+        ) {
+            Some(counts.constant_buffers)
+        } else {
+            None
+        };
+        let special_constants_binding = special_constants_slot.map(|register| hlsl::BindTarget {
+            space: 0,
+            register,
+            binding_array_size: None,
+        });
+
+        log::trace!("Bindings {:#?}", binding_map);
 
-    unsafe fn destroy_pipeline_layout(&self, pipeline_layout: super::PipelineLayout) {
-        todo!()
+        Ok(super::PipelineLayout {
+            bind_group_bases,
+            special_constants_slot,
+            naga_options: hlsl::Options {
+                shader_model: hlsl::ShaderModel::V5_0,
+                binding_map,
+                fake_missing_bindings: false,
+                special_constants_binding,
+            },
+        })
     }
 
+    unsafe fn destroy_pipeline_layout(&self, _pipeline_layout: super::PipelineLayout) {}
+
     unsafe fn create_bind_group(
         &self,
         desc: &crate::BindGroupDescriptor<super::Api>,
     ) -> Result<super::BindGroup, crate::DeviceError> {
-        todo!()
+        let mut group = super::BindGroup {
+            constant_buffers: Vec::new(),
+            shader_resources: Vec::new(),
+            samplers: Vec::new(),
+            unordered_access: Vec::new(),
+            buffer_srvs: Vec::new(),
+            buffer_uavs: Vec::new(),
+        };
+
+        for (layout, entry) in desc.layout.entries.iter().zip(desc.entries.iter()) {
+            let start = entry.resource_index as usize;
+            let end = start + entry.count as usize;
+            let visibility = layout.visibility;
+            match layout.ty {
+                wgt::BindingType::Buffer {
+                    ty,
+                    has_dynamic_offset,
+                    ..
+                } => {
+                    for data in &desc.buffers[start..end] {
+                        let size = data
+                            .size
+                            .map_or(data.buffer.size - data.offset, wgt::BufferSize::get);
+                        match ty {
+                            wgt::BufferBindingType::Uniform => {
+                                if data.offset != 0
+                                    && !self.shared.private_caps.constant_buffer_offsets
+                                {
+                                    log::error!(
+                                        "Uniform buffer offsets require D3D11.1 constant buffer offsetting"
+                                    );
+                                }
+                                group.constant_buffers.push(super::ConstantBufferBinding {
+                                    raw: data.buffer.resource,
+                                    visibility,
+                                    first_constant: (data.offset / 16) as u32,
+                                    // Has to be a multiple of 16 constants, and at most 4096.
+                                    num_constants: ((((size + 15) / 16) + 15) & !15).min(4096)
+                                        as u32,
+                                    dynamic: has_dynamic_offset,
+                                });
+                            }
+                            wgt::BufferBindingType::Storage { read_only: true } => {
+                                let mut raw_desc = d3d11::D3D11_SHADER_RESOURCE_VIEW_DESC {
+                                    Format: dxgiformat::DXGI_FORMAT_R32_TYPELESS,
+                                    ViewDimension: d3dcommon::D3D11_SRV_DIMENSION_BUFFEREX,
+                                    u: mem::zeroed(),
+                                };
+                                *raw_desc.u.BufferEx_mut() = d3d11::D3D11_BUFFEREX_SRV {
+                                    FirstElement: (data.offset / 4) as u32,
+                                    NumElements: (size / 4) as u32,
+                                    Flags: d3d11::D3D11_BUFFEREX_SRV_FLAG_RAW,
+                                };
+                                let mut raw = native::WeakPtr::null();
+                                self.shared
+                                    .raw
+                                    .CreateShaderResourceView(
+                                        data.buffer.resource.as_mut_ptr() as *mut _,
+                                        &raw_desc,
+                                        raw.mut_self(),
+                                    )
+                                    .into_device_result("Buffer SRV creation")?;
+                                group.buffer_srvs.push(raw);
+                                group
+                                    .shader_resources
+                                    .push(super::Binding { raw, visibility });
+                            }
+                            wgt::BufferBindingType::Storage { read_only: false } => {
+                                let mut raw_desc = d3d11::D3D11_UNORDERED_ACCESS_VIEW_DESC {
+                                    Format: dxgiformat::DXGI_FORMAT_R32_TYPELESS,
+                                    ViewDimension: d3d11::D3D11_UAV_DIMENSION_BUFFER,
+                                    u: mem::zeroed(),
+                                };
+                                *raw_desc.u.Buffer_mut() = d3d11::D3D11_BUFFER_UAV {
+                                    FirstElement: (data.offset / 4) as u32,
+                                    NumElements: (size / 4) as u32,
+                                    Flags: d3d11::D3D11_BUFFER_UAV_FLAG_RAW,
+                                };
+                                let mut raw = native::WeakPtr::null();
+                                self.shared
+                                    .raw
+                                    .CreateUnorderedAccessView(
+                                        data.buffer.resource.as_mut_ptr() as *mut _,
+                                        &raw_desc,
+                                        raw.mut_self(),
+                                    )
+                                    .into_device_result("Buffer UAV creation")?;
+                                group.buffer_uavs.push(raw);
+                                group
+                                    .unordered_access
+                                    .push(super::Binding { raw, visibility });
+                            }
+                        }
+                    }
+                }
+                wgt::BindingType::Texture { .. } => {
+                    for data in &desc.textures[start..end] {
+                        group.shader_resources.push(super::Binding {
+                            raw: data.view.srv,
+                            visibility,
+                        });
+                    }
+                }
+                wgt::BindingType::StorageTexture { .. } => {
+                    for data in &desc.textures[start..end] {
+                        group.unordered_access.push(super::Binding {
+                            raw: data.view.uav,
+                            visibility,
+                        });
+                    }
+                }
+                wgt::BindingType::Sampler { .. } => {
+                    for data in &desc.samplers[start..end] {
+                        group.samplers.push(super::Binding {
+                            raw: data.raw,
+                            visibility,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(group)
     }
 
     unsafe fn destroy_bind_group(&self, group: super::BindGroup) {
-        todo!()
+        for srv in group.buffer_srvs {
+            srv.destroy();
+        }
+        for uav in group.buffer_uavs {
+            uav.destroy();
+        }
     }
 
     unsafe fn create_shader_module(
@@ -129,59 +1258,273 @@ impl crate::Device<super::Api> for super::Device {
         desc: &crate::ShaderModuleDescriptor,
         shader: crate::ShaderInput,
     ) -> Result<super::ShaderModule, crate::ShaderError> {
-        todo!()
+        let raw_name = desc.label.and_then(|label| ffi::CString::new(label).ok());
+        match shader {
+            crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule { naga, raw_name }),
+            crate::ShaderInput::SpirV(_) => {
+                panic!("SPIRV_SHADER_PASSTHROUGH is not enabled for this backend")
+            }
+        }
     }
 
-    unsafe fn destroy_shader_module(&self, module: super::ShaderModule) {
-        todo!()
+    unsafe fn destroy_shader_module(&self, _module: super::ShaderModule) {
+        // just drop
     }
 
     unsafe fn create_render_pipeline(
         &self,
         desc: &crate::RenderPipelineDescriptor<super::Api>,
     ) -> Result<super::RenderPipeline, crate::PipelineError> {
-        todo!()
+        let mut shader_stages = wgt::ShaderStages::VERTEX;
+
+        let blob_vs =
+            self.load_shader(&desc.vertex_stage, desc.layout, naga::ShaderStage::Vertex)?;
+        let blob_fs = match desc.fragment_stage {
+            Some(ref stage) => {
+                shader_stages |= wgt::ShaderStages::FRAGMENT;
+                match self.load_shader(stage, desc.layout, naga::ShaderStage::Fragment) {
+                    Ok(blob) => blob,
+                    Err(err) => {
+                        blob_vs.destroy();
+                        return Err(err);
+                    }
+                }
+            }
+            None => native::Blob::null(),
+        };
+
+        let mut vertex_strides = [0; crate::MAX_VERTEX_BUFFERS];
+        let mut input_element_descs = Vec::new();
+        for (i, (stride, vbuf)) in vertex_strides
+            .iter_mut()
+            .zip(desc.vertex_buffers)
+            .enumerate()
+        {
+            *stride = vbuf.array_stride as u32;
+            let (slot_class, step_rate) = match vbuf.step_mode {
+                wgt::VertexStepMode::Vertex => (d3d11::D3D11_INPUT_PER_VERTEX_DATA, 0),
+                wgt::VertexStepMode::Instance => (d3d11::D3D11_INPUT_PER_INSTANCE_DATA, 1),
+            };
+            for attribute in vbuf.attributes {
+                input_element_descs.push(d3d11::D3D11_INPUT_ELEMENT_DESC {
+                    SemanticName: NAGA_LOCATION_SEMANTIC.as_ptr() as *const _,
+                    SemanticIndex: attribute.shader_location,
+                    Format: auxil::dxgi::conv::map_vertex_format(attribute.format),
+                    InputSlot: i as u32,
+                    AlignedByteOffset: attribute.offset as u32,
+                    InputSlotClass: slot_class,
+                    InstanceDataStepRate: step_rate,
+                });
+            }
+        }
+
+        let raw_rasterizer = conv::map_rasterizer(
+            &desc.primitive,
+            desc.depth_stencil.as_ref(),
+            &desc.multisample,
+        );
+        let raw_blend = conv::map_blend(desc.color_targets, &desc.multisample);
+        let raw_depth_stencil = conv::map_depth_stencil(desc.depth_stencil.as_ref());
+
+        let mut pipeline = super::RenderPipeline {
+            vs: native::WeakPtr::null(),
+            ps: native::WeakPtr::null(),
+            input_layout: native::WeakPtr::null(),
+            rasterizer_state: native::WeakPtr::null(),
+            blend_state: native::WeakPtr::null(),
+            depth_stencil_state: native::WeakPtr::null(),
+            topology: conv::map_topology(desc.primitive.topology),
+            vertex_strides,
+            sample_mask: desc.multisample.mask as u32,
+            special_constants_slot: desc.layout.special_constants_slot,
+        };
+
+        let hr = {
+            profiling::scope!("ID3D11Device::CreateRenderPipeline");
+            let mut hr = self.shared.raw.CreateVertexShader(
+                blob_vs.GetBufferPointer(),
+                blob_vs.GetBufferSize(),
+                ptr::null_mut(),
+                pipeline.vs.mut_self(),
+            );
+            if hr == winerror::S_OK && !blob_fs.is_null() {
+                hr = self.shared.raw.CreatePixelShader(
+                    blob_fs.GetBufferPointer(),
+                    blob_fs.GetBufferSize(),
+                    ptr::null_mut(),
+                    pipeline.ps.mut_self(),
+                );
+            }
+            if hr == winerror::S_OK && !input_element_descs.is_empty() {
+                hr = self.shared.raw.CreateInputLayout(
+                    input_element_descs.as_ptr(),
+                    input_element_descs.len() as u32,
+                    blob_vs.GetBufferPointer(),
+                    blob_vs.GetBufferSize(),
+                    pipeline.input_layout.mut_self(),
+                );
+            }
+            if hr == winerror::S_OK {
+                hr = self
+                    .shared
+                    .raw
+                    .CreateRasterizerState(&raw_rasterizer, pipeline.rasterizer_state.mut_self());
+            }
+            if hr == winerror::S_OK {
+                hr = self
+                    .shared
+                    .raw
+                    .CreateBlendState(&raw_blend, pipeline.blend_state.mut_self());
+            }
+            if hr == winerror::S_OK {
+                hr = self.shared.raw.CreateDepthStencilState(
+                    &raw_depth_stencil,
+                    pipeline.depth_stencil_state.mut_self(),
+                );
+            }
+            hr
+        };
+
+        blob_vs.destroy();
+        if !blob_fs.is_null() {
+            blob_fs.destroy();
+        }
+
+        if let Err(err) = hr.into_result() {
+            self.destroy_render_pipeline(pipeline);
+            return Err(crate::PipelineError::Linkage(
+                shader_stages,
+                err.into_owned(),
+            ));
+        }
+
+        set_debug_name(&pipeline.vs, desc.label);
+
+        Ok(pipeline)
     }
 
     unsafe fn destroy_render_pipeline(&self, pipeline: super::RenderPipeline) {
-        todo!()
+        // The creation may have failed half way.
+        if !pipeline.vs.is_null() {
+            pipeline.vs.destroy();
+        }
+        if !pipeline.ps.is_null() {
+            pipeline.ps.destroy();
+        }
+        if !pipeline.input_layout.is_null() {
+            pipeline.input_layout.destroy();
+        }
+        if !pipeline.rasterizer_state.is_null() {
+            pipeline.rasterizer_state.destroy();
+        }
+        if !pipeline.blend_state.is_null() {
+            pipeline.blend_state.destroy();
+        }
+        if !pipeline.depth_stencil_state.is_null() {
+            pipeline.depth_stencil_state.destroy();
+        }
     }
 
     unsafe fn create_compute_pipeline(
         &self,
         desc: &crate::ComputePipelineDescriptor<super::Api>,
     ) -> Result<super::ComputePipeline, crate::PipelineError> {
-        todo!()
+        let blob_cs = self.load_shader(&desc.stage, desc.layout, naga::ShaderStage::Compute)?;
+
+        let mut cs = native::WeakPtr::<d3d11::ID3D11ComputeShader>::null();
+        let hr = {
+            profiling::scope!("ID3D11Device::CreateComputeShader");
+            self.shared.raw.CreateComputeShader(
+                blob_cs.GetBufferPointer(),
+                blob_cs.GetBufferSize(),
+                ptr::null_mut(),
+                cs.mut_self(),
+            )
+        };
+
+        blob_cs.destroy();
+
+        hr.into_result().map_err(|err| {
+            crate::PipelineError::Linkage(wgt::ShaderStages::COMPUTE, err.into_owned())
+        })?;
+        set_debug_name(&cs, desc.label);
+
+        Ok(super::ComputePipeline {
+            cs,
+            special_constants_slot: desc.layout.special_constants_slot,
+        })
     }
 
     unsafe fn destroy_compute_pipeline(&self, pipeline: super::ComputePipeline) {
-        todo!()
+        pipeline.cs.destroy();
+    }
+
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        Ok(super::PipelineCache)
+    }
+    unsafe fn pipeline_cache_get_data(&self, _cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        None
     }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
 
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
     ) -> Result<super::QuerySet, crate::DeviceError> {
-        todo!()
+        let raw_desc = d3d11::D3D11_QUERY_DESC {
+            Query: conv::map_query_type(desc.ty),
+            MiscFlags: 0,
+        };
+
+        let mut set = super::QuerySet {
+            raw: Vec::with_capacity(desc.count as usize),
+            ty: desc.ty,
+        };
+        for _ in 0..desc.count {
+            let mut raw = native::WeakPtr::<d3d11::ID3D11Query>::null();
+            let hr = self.shared.raw.CreateQuery(&raw_desc, raw.mut_self());
+            if let Err(err) = hr.into_device_result("Query creation") {
+                self.destroy_query_set(set);
+                return Err(err);
+            }
+            set_debug_name(&raw, desc.label);
+            set.raw.push(raw);
+        }
+
+        Ok(set)
     }
 
     unsafe fn destroy_query_set(&self, set: super::QuerySet) {
-        todo!()
+        for raw in set.raw {
+            raw.destroy();
+        }
     }
 
     unsafe fn create_fence(&self) -> Result<super::Fence, crate::DeviceError> {
-        todo!()
+        Ok(super::Fence {
+            state: Mutex::new(super::FenceState {
+                completed: 0,
+                pending: Vec::new(),
+            }),
+        })
     }
 
     unsafe fn destroy_fence(&self, fence: super::Fence) {
-        todo!()
+        for (_, query) in fence.state.into_inner().pending {
+            query.destroy();
+        }
     }
 
     unsafe fn get_fence_value(
         &self,
         fence: &super::Fence,
     ) -> Result<crate::FenceValue, crate::DeviceError> {
-        todo!()
+        let mut state = fence.state.lock();
+        state.update(&self.shared.context.lock());
+        Ok(state.completed)
     }
 
     unsafe fn wait(
@@ -190,16 +1533,31 @@ impl crate::Device<super::Api> for super::Device {
         value: crate::FenceValue,
         timeout_ms: u32,
     ) -> Result<bool, crate::DeviceError> {
-        todo!()
+        // D3D11 has no way to block on a query, so it has to be polled.
+        let start = time::Instant::now();
+        let timeout = time::Duration::from_millis(timeout_ms as u64);
+        loop {
+            {
+                let mut state = fence.state.lock();
+                if state.completed < value {
+                    state.update(&self.shared.context.lock());
+                }
+                if state.completed >= value {
+                    return Ok(true);
+                }
+            }
+            if start.elapsed() >= timeout {
+                return Ok(false);
+            }
+            thread::sleep(time::Duration::from_millis(1));
+        }
     }
 
     unsafe fn start_capture(&self) -> bool {
-        todo!()
+        false
     }
 
-    unsafe fn stop_capture(&self) {
-        todo!()
-    }
+    unsafe fn stop_capture(&self) {}
 }
 
 impl crate::Queue<super::Api> for super::Queue {
@@ -208,19 +1566,67 @@ impl crate::Queue<super::Api> for super::Queue {
         command_buffers: &[&super::CommandBuffer],
         signal_fence: Option<(&mut super::Fence, crate::FenceValue)>,
     ) -> Result<(), crate::DeviceError> {
-        todo!()
+        let context = self.shared.context.lock();
+
+        context.Begin(self.disjoint_query.as_mut_ptr() as *mut _);
+        let mut result = Ok(());
+        'outer: for cmd_buf in command_buffers {
+            for step in cmd_buf.steps.iter() {
+                match *step {
+                    super::Step::Commands(list) => {
+                        profiling::scope!("ID3D11DeviceContext::ExecuteCommandList");
+                        context.ExecuteCommandList(list.as_mut_ptr(), 0);
+                    }
+                    super::Step::Transfer(ref transfer) => {
+                        profiling::scope!("transfer");
+                        result = self.shared.execute_transfer(&context, transfer);
+                        if result.is_err() {
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+        }
+        context.End(self.disjoint_query.as_mut_ptr() as *mut _);
+
+        if let Some((fence, value)) = signal_fence {
+            let raw_desc = d3d11::D3D11_QUERY_DESC {
+                Query: d3d11::D3D11_QUERY_EVENT,
+                MiscFlags: 0,
+            };
+            let mut query = native::WeakPtr::<d3d11::ID3D11Query>::null();
+            self.shared
+                .raw
+                .CreateQuery(&raw_desc, query.mut_self())
+                .into_device_result("Event query creation")?;
+            context.End(query.as_mut_ptr() as *mut _);
+            fence.state.get_mut().pending.push((value, query));
+        }
+
+        context.Flush();
+        result
     }
 
     unsafe fn present(
         &mut self,
         surface: &mut super::Surface,
-        texture: super::SurfaceTexture,
+        _texture: super::Texture,
     ) -> Result<(), crate::SurfaceError> {
-        todo!()
+        let sc = surface.swap_chain.as_ref().unwrap();
+        let interval = match sc.present_mode {
+            wgt::PresentMode::Immediate => 0,
+            _ => 1,
+        };
+
+        profiling::scope!("IDXGISwapChain::Present");
+        sc.raw.Present(interval, 0).into_result().map_err(|err| {
+            log::error!("Present failed: {}", err);
+            crate::SurfaceError::Lost
+        })
     }
 
     unsafe fn get_timestamp_period(&self) -> f32 {
-        todo!()
+        self.timestamp_period
     }
 }
 
@@ -230,7 +1636,7 @@ impl super::D3D11Device {
         let mut value = mem::zeroed::<T>();
         let ret = self.CheckFeatureSupport(
             feature,
-            &mut value as *mut T as *mut c_void,
+            &mut value as *mut T as *mut ffi::c_void,
             mem::size_of::<T>() as u32,
         );
         assert_eq!(ret.into_result(), Ok(()));
@@ -22,18 +22,26 @@ impl crate::Instance<super::Api> for super::Instance {
             lib_d3d11,
             lib_dxgi,
             factory,
+            flags: desc.flags,
         })
     }
 
     unsafe fn create_surface(
         &self,
-        rwh: &impl raw_window_handle::HasRawWindowHandle,
+        has_handle: &impl raw_window_handle::HasRawWindowHandle,
     ) -> Result<super::Surface, crate::InstanceError> {
-        todo!()
+        match has_handle.raw_window_handle() {
+            raw_window_handle::RawWindowHandle::Win32(handle) => Ok(super::Surface {
+                factory: self.factory,
+                wnd_handle: handle.hwnd as *mut _,
+                swap_chain: None,
+            }),
+            _ => Err(crate::InstanceError),
+        }
     }
 
-    unsafe fn destroy_surface(&self, surface: super::Surface) {
-        todo!()
+    unsafe fn destroy_surface(&self, _surface: super::Surface) {
+        // just drop
     }
 
     unsafe fn enumerate_adapters(&self) -> Vec<crate::ExposedAdapter<super::Api>> {
@@ -41,7 +49,7 @@ impl crate::Instance<super::Api> for super::Instance {
 
         adapters
             .into_iter()
-            .filter_map(|adapter| super::Adapter::expose(&self.lib_d3d11, adapter))
+            .filter_map(|adapter| super::Adapter::expose(&self.lib_d3d11, adapter, self.flags))
             .collect()
     }
 }
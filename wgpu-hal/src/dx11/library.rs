@@ -52,7 +52,11 @@ impl D3D11Lib {
     pub fn create_device(
         &self,
         adapter: native::DxgiAdapter,
-    ) -> Option<(super::D3D11Device, d3dcommon::D3D_FEATURE_LEVEL)> {
+    ) -> Option<(
+        super::D3D11Device,
+        native::WeakPtr<d3d11::ID3D11DeviceContext>,
+        d3dcommon::D3D_FEATURE_LEVEL,
+    )> {
         let feature_levels = [
             d3dcommon::D3D_FEATURE_LEVEL_11_1,
             d3dcommon::D3D_FEATURE_LEVEL_11_0,
@@ -64,6 +68,7 @@ impl D3D11Lib {
         ];
 
         let mut device = native::WeakPtr::<d3d11::ID3D11Device>::null();
+        let mut context = native::WeakPtr::<d3d11::ID3D11DeviceContext>::null();
         let mut feature_level: d3dcommon::D3D_FEATURE_LEVEL = 0;
 
         // We need to try this twice. If the first time fails due to E_INVALIDARG
@@ -83,7 +88,7 @@ impl D3D11Lib {
                 d3d11::D3D11_SDK_VERSION,
                 device.mut_self(),
                 &mut feature_level,
-                ptr::null_mut(), // device context
+                context.mut_self(),
             )
         };
 
@@ -100,7 +105,7 @@ impl D3D11Lib {
                     d3d11::D3D11_SDK_VERSION,
                     device.mut_self(),
                     &mut feature_level,
-                    ptr::null_mut(), // device context
+                    context.mut_self(),
                 )
             };
         }
@@ -118,7 +123,7 @@ impl D3D11Lib {
             match device.cast::<d3d11_2::ID3D11Device2>().into_result() {
                 Ok(device2) => {
                     device.destroy();
-                    return Some((super::D3D11Device::Device2(device2), feature_level));
+                    return Some((super::D3D11Device::Device2(device2), context, feature_level));
                 }
                 Err(hr) => {
                     log::info!("Failed to cast device to ID3D11Device2: {}", hr)
@@ -131,7 +136,7 @@ impl D3D11Lib {
             match device.cast::<d3d11_1::ID3D11Device1>().into_result() {
                 Ok(device1) => {
                     device.destroy();
-                    return Some((super::D3D11Device::Device1(device1), feature_level));
+                    return Some((super::D3D11Device::Device1(device1), context, feature_level));
                 }
                 Err(hr) => {
                     log::info!("Failed to cast device to ID3D11Device1: {}", hr)
@@ -139,6 +144,6 @@ impl D3D11Lib {
             }
         }
 
-        Some((super::D3D11Device::Device(device), feature_level))
+        Some((super::D3D11Device::Device(device), context, feature_level))
     }
 }
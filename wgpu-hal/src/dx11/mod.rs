@@ -1,13 +1,49 @@
-#![allow(dead_code)]
-#![allow(unused_variables)]
+/*!
+# DirectX11 API internals.
 
-use winapi::um::{d3d11, d3d11_1, d3d11_2};
+The backend is hidden behind the `WGPU_UNSTABLE_DX11_BACKEND` environment variable.
+
+## Command recording
+
+Every command encoder records into its own deferred context, and the resulting
+command lists are executed on the immediate context at submission.
+
+D3D11 can't copy between buffers and textures, and buffers that can be mapped
+can't be bound to the pipeline. For this reason, buffers that are only mapped
+and copied live in host memory, and the copies involving them, as well as
+all the buffer-texture copies, are recorded as [`Transfer`] steps
+that are executed by the CPU on the immediate context at submission,
+in between the command lists. Reading GPU data back this way stalls the queue.
+
+## Resource binding
+
+D3D11 has no descriptor sets, so all the bind groups of a pipeline layout
+share the same register space. Each bind group gets the next contiguous range
+of constant buffer, shader resource, sampler and unordered access registers,
+see [`PipelineLayout`].
+
+Unordered access views are only bound in compute passes.
+
+!*/
 
 mod adapter;
 mod command;
+mod conv;
 mod device;
 mod instance;
 mod library;
+mod view;
+
+use crate::auxil::{self, dxgi::result::HResult as _};
+
+use arrayvec::ArrayVec;
+use parking_lot::Mutex;
+use std::{ffi, fmt, ptr::NonNull, sync::Arc};
+use winapi::{
+    shared::{dxgi, dxgitype, windef},
+    um::{d3d11, d3d11_1, d3d11_2, d3dcommon},
+    Interface as _,
+};
 
 #[derive(Clone)]
 pub struct Api;
@@ -24,7 +60,7 @@ impl crate::Api for Api {
 
     type Buffer = Buffer;
     type Texture = Texture;
-    type SurfaceTexture = SurfaceTexture;
+    type SurfaceTexture = Texture;
     type TextureView = TextureView;
     type Sampler = Sampler;
     type QuerySet = QuerySet;
@@ -36,21 +72,53 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 pub struct Instance {
     lib_d3d11: library::D3D11Lib,
     lib_dxgi: native::DxgiLib,
     factory: native::DxgiFactory,
+    flags: crate::InstanceFlags,
 }
 
 unsafe impl Send for Instance {}
 unsafe impl Sync for Instance {}
 
-pub struct Surface {}
+struct SwapChain {
+    raw: native::WeakPtr<dxgi::IDXGISwapChain>,
+    // The blit model swap chain only exposes the first buffer.
+    buffer: native::WeakPtr<d3d11::ID3D11Texture2D>,
+    present_mode: wgt::PresentMode,
+    format: wgt::TextureFormat,
+    size: wgt::Extent3d,
+}
+
+pub struct Surface {
+    factory: native::DxgiFactory,
+    wnd_handle: windef::HWND,
+    swap_chain: Option<SwapChain>,
+}
+
+unsafe impl Send for Surface {}
+unsafe impl Sync for Surface {}
+
+#[derive(Clone, Debug)]
+struct PrivateCapabilities {
+    instance_flags: crate::InstanceFlags,
+    feature_level: d3dcommon::D3D_FEATURE_LEVEL,
+    /// Suffix of the FXC profiles, matching the feature level.
+    shader_profile: &'static str,
+    /// `*SetConstantBuffers1` can bind constant buffers at an offset.
+    constant_buffer_offsets: bool,
+    /// `UpdateSubresource1` can update a part of a constant buffer.
+    constant_buffer_partial_update: bool,
+}
 
 pub struct Adapter {
     device: D3D11Device,
+    context: native::WeakPtr<d3d11::ID3D11DeviceContext>,
+    private_caps: PrivateCapabilities,
 }
 
 unsafe impl Send for Adapter {}
@@ -65,49 +133,362 @@ native::weak_com_inheritance_chain! {
     }
 }
 
-pub struct Device {}
+struct DeviceShared {
+    raw: D3D11Device,
+    /// The immediate context, shared by the device and the queue.
+    context: Mutex<native::WeakPtr<d3d11::ID3D11DeviceContext>>,
+    private_caps: PrivateCapabilities,
+}
+
+unsafe impl Send for DeviceShared {}
+unsafe impl Sync for DeviceShared {}
+
+pub struct Device {
+    shared: Arc<DeviceShared>,
+}
 
 unsafe impl Send for Device {}
 unsafe impl Sync for Device {}
 
-pub struct Queue {}
+pub struct Queue {
+    shared: Arc<DeviceShared>,
+    /// Brackets every submission, timestamps are only valid inside of a disjoint query.
+    disjoint_query: native::WeakPtr<d3d11::ID3D11Query>,
+    timestamp_period: f32,
+}
+
+unsafe impl Send for Queue {}
+unsafe impl Sync for Queue {}
 
-pub struct CommandEncoder {}
+/// A copy that D3D11 can't record, executed on the immediate context at submission.
+enum Transfer {
+    CopyBuffer {
+        src: Buffer,
+        dst: Buffer,
+        regions: Vec<crate::BufferCopy>,
+    },
+    FillBuffer {
+        dst: Buffer,
+        range: crate::MemoryRange,
+    },
+    CopyBufferToTexture {
+        src: Buffer,
+        dst: Texture,
+        regions: Vec<crate::BufferTextureCopy>,
+    },
+    CopyTextureToBuffer {
+        src: Texture,
+        dst: Buffer,
+        regions: Vec<crate::BufferTextureCopy>,
+    },
+    CopyQueryResults {
+        queries: Vec<native::WeakPtr<d3d11::ID3D11Query>>,
+        ty: wgt::QueryType,
+        dst: Buffer,
+        offset: wgt::BufferAddress,
+        stride: wgt::BufferSize,
+    },
+}
 
-pub struct CommandBuffer {}
+enum Step {
+    Commands(native::WeakPtr<d3d11::ID3D11CommandList>),
+    Transfer(Transfer),
+}
 
-#[derive(Debug)]
-pub struct Buffer {}
-#[derive(Debug)]
-pub struct Texture {}
-#[derive(Debug)]
-pub struct SurfaceTexture {}
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PassKind {
+    Render,
+    Compute,
+    Transfer,
+}
 
-impl std::borrow::Borrow<Texture> for SurfaceTexture {
-    fn borrow(&self) -> &Texture {
-        todo!()
+#[derive(Clone, Copy)]
+struct VertexBufferBinding {
+    buffer: native::WeakPtr<d3d11::ID3D11Buffer>,
+    offset: u32,
+}
+
+struct Resolve {
+    src: (native::WeakPtr<d3d11::ID3D11Resource>, u32),
+    dst: (native::WeakPtr<d3d11::ID3D11Resource>, u32),
+    format: native::Format,
+}
+
+struct PassState {
+    kind: PassKind,
+    has_label: bool,
+    resolves: ArrayVec<Resolve, { crate::MAX_COLOR_ATTACHMENTS }>,
+    vertex_buffers: [VertexBufferBinding; crate::MAX_VERTEX_BUFFERS],
+    dirty_vertex_buffers: usize,
+    vertex_strides: [u32; crate::MAX_VERTEX_BUFFERS],
+    blend_state: native::WeakPtr<d3d11::ID3D11BlendState>,
+    blend_constants: [f32; 4],
+    sample_mask: u32,
+    depth_stencil_state: native::WeakPtr<d3d11::ID3D11DepthStencilState>,
+    stencil_reference: u32,
+    special_constants_slot: Option<u32>,
+    special_constants: Option<[u32; 3]>,
+}
+
+impl PassState {
+    fn new() -> Self {
+        PassState {
+            kind: PassKind::Transfer,
+            has_label: false,
+            resolves: ArrayVec::new(),
+            vertex_buffers: [VertexBufferBinding {
+                buffer: native::WeakPtr::null(),
+                offset: 0,
+            }; crate::MAX_VERTEX_BUFFERS],
+            dirty_vertex_buffers: 0,
+            vertex_strides: [0; crate::MAX_VERTEX_BUFFERS],
+            blend_state: native::WeakPtr::null(),
+            blend_constants: [0.0; 4],
+            sample_mask: !0,
+            depth_stencil_state: native::WeakPtr::null(),
+            stencil_reference: 0,
+            special_constants_slot: None,
+            special_constants: None,
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+pub struct CommandEncoder {
+    shared: Arc<DeviceShared>,
+    /// The deferred context the commands are recorded into.
+    context: native::WeakPtr<d3d11::ID3D11DeviceContext>,
+    /// Null if the runtime doesn't support D3D11.1.
+    context1: native::WeakPtr<d3d11_1::ID3D11DeviceContext1>,
+    /// Null if the runtime doesn't support D3D11.1.
+    annotation: native::WeakPtr<d3d11_1::ID3DUserDefinedAnnotation>,
+    /// Backs the base vertex/instance and the workgroup count builtins.
+    special_constants_buffer: native::WeakPtr<d3d11::ID3D11Buffer>,
+    steps: Vec<Step>,
+    pass: PassState,
+    temp_marker: Vec<u16>,
+}
+
+unsafe impl Send for CommandEncoder {}
+unsafe impl Sync for CommandEncoder {}
+
+pub struct CommandBuffer {
+    steps: Vec<Step>,
+}
+
+unsafe impl Send for CommandBuffer {}
+unsafe impl Sync for CommandBuffer {}
+
+/// Host memory backing the buffers that are only mapped and copied.
+#[derive(Clone, Copy, Debug)]
+struct HostMemory {
+    ptr: NonNull<u64>,
+    words: usize,
+}
+
+/// Cloning doesn't duplicate the underlying objects, only the handles.
+#[derive(Clone, Debug)]
+pub struct Buffer {
+    /// Null for the buffers in host memory.
+    resource: native::WeakPtr<d3d11::ID3D11Buffer>,
+    host: Option<HostMemory>,
+    usage: crate::BufferUses,
+    size: wgt::BufferAddress,
+}
+
+unsafe impl Send for Buffer {}
+unsafe impl Sync for Buffer {}
+
+impl Buffer {
+    fn host_ptr(&self) -> Option<NonNull<u8>> {
+        self.host.map(|host| host.ptr.cast())
+    }
+}
+
+/// Cloning doesn't duplicate the underlying resource, only the handle.
+#[derive(Clone, Debug)]
+pub struct Texture {
+    resource: native::WeakPtr<d3d11::ID3D11Resource>,
+    format: wgt::TextureFormat,
+    dimension: wgt::TextureDimension,
+    size: wgt::Extent3d,
+    mip_level_count: u32,
+    sample_count: u32,
+}
+
+unsafe impl Send for Texture {}
+unsafe impl Sync for Texture {}
+
+impl Texture {
+    fn array_layer_count(&self) -> u32 {
+        match self.dimension {
+            wgt::TextureDimension::D1 | wgt::TextureDimension::D3 => 1,
+            wgt::TextureDimension::D2 => self.size.depth_or_array_layers,
+        }
+    }
+
+    fn calc_subresource(&self, mip_level: u32, array_layer: u32) -> u32 {
+        mip_level + array_layer * self.mip_level_count
+    }
+
+    fn calc_subresource_for_copy(&self, base: &crate::TextureCopyBase) -> u32 {
+        self.calc_subresource(base.mip_level, base.array_layer)
     }
 }
 
 #[derive(Debug)]
-pub struct TextureView {}
+pub struct TextureView {
+    texture: Texture,
+    subresource: u32,
+    format: native::Format,
+    /// Null for each of the views the usage doesn't require.
+    srv: native::WeakPtr<d3d11::ID3D11ShaderResourceView>,
+    uav: native::WeakPtr<d3d11::ID3D11UnorderedAccessView>,
+    rtv: native::WeakPtr<d3d11::ID3D11RenderTargetView>,
+    dsv_rw: native::WeakPtr<d3d11::ID3D11DepthStencilView>,
+    dsv_ro: native::WeakPtr<d3d11::ID3D11DepthStencilView>,
+}
+
+unsafe impl Send for TextureView {}
+unsafe impl Sync for TextureView {}
+
 #[derive(Debug)]
-pub struct Sampler {}
+pub struct Sampler {
+    raw: native::WeakPtr<d3d11::ID3D11SamplerState>,
+}
+
+unsafe impl Send for Sampler {}
+unsafe impl Sync for Sampler {}
+
 #[derive(Debug)]
-pub struct QuerySet {}
+pub struct QuerySet {
+    raw: Vec<native::WeakPtr<d3d11::ID3D11Query>>,
+    ty: wgt::QueryType,
+}
+
+unsafe impl Send for QuerySet {}
+unsafe impl Sync for QuerySet {}
+
 #[derive(Debug)]
-pub struct Fence {}
+struct FenceState {
+    completed: crate::FenceValue,
+    /// Event queries issued after the submissions, with the value they signal.
+    pending: Vec<(crate::FenceValue, native::WeakPtr<d3d11::ID3D11Query>)>,
+}
+
+#[derive(Debug)]
+pub struct Fence {
+    state: Mutex<FenceState>,
+}
+
+unsafe impl Send for Fence {}
+unsafe impl Sync for Fence {}
+
+pub struct BindGroupLayout {
+    entries: Vec<wgt::BindGroupLayoutEntry>,
+}
+
+/// Number of registers of each type.
+#[derive(Clone, Copy, Debug, Default)]
+struct RegisterCounts {
+    constant_buffers: u32,
+    shader_resources: u32,
+    samplers: u32,
+    unordered_access: u32,
+}
+
+struct Binding<T> {
+    raw: native::WeakPtr<T>,
+    visibility: wgt::ShaderStages,
+}
+
+// Derived `Debug` would require it from the COM interfaces.
+impl<T> fmt::Debug for Binding<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Binding")
+            .field("raw", &self.raw)
+            .field("visibility", &self.visibility)
+            .finish()
+    }
+}
+
 #[derive(Debug)]
+struct ConstantBufferBinding {
+    raw: native::WeakPtr<d3d11::ID3D11Buffer>,
+    visibility: wgt::ShaderStages,
+    /// In units of 16-byte constants, as expected by `*SetConstantBuffers1`.
+    first_constant: u32,
+    num_constants: u32,
+    dynamic: bool,
+}
 
-pub struct BindGroupLayout {}
 #[derive(Debug)]
-pub struct BindGroup {}
+pub struct BindGroup {
+    constant_buffers: Vec<ConstantBufferBinding>,
+    shader_resources: Vec<Binding<d3d11::ID3D11ShaderResourceView>>,
+    samplers: Vec<Binding<d3d11::ID3D11SamplerState>>,
+    unordered_access: Vec<Binding<d3d11::ID3D11UnorderedAccessView>>,
+    /// Views of the storage buffers, owned by the group.
+    buffer_srvs: Vec<native::WeakPtr<d3d11::ID3D11ShaderResourceView>>,
+    buffer_uavs: Vec<native::WeakPtr<d3d11::ID3D11UnorderedAccessView>>,
+}
+
+unsafe impl Send for BindGroup {}
+unsafe impl Sync for BindGroup {}
+
+/// The registers of a pipeline layout are assigned in the order of the bind groups,
+/// then the order of the entries. The special constants come last.
+pub struct PipelineLayout {
+    /// The first register of each type used by each bind group.
+    bind_group_bases: ArrayVec<RegisterCounts, { crate::MAX_BIND_GROUPS }>,
+    special_constants_slot: Option<u32>,
+    naga_options: naga::back::hlsl::Options,
+}
+
 #[derive(Debug)]
-pub struct PipelineLayout {}
+pub struct ShaderModule {
+    naga: crate::NagaShader,
+    raw_name: Option<ffi::CString>,
+}
+
+pub struct RenderPipeline {
+    vs: native::WeakPtr<d3d11::ID3D11VertexShader>,
+    /// Null without a fragment stage.
+    ps: native::WeakPtr<d3d11::ID3D11PixelShader>,
+    /// Null without vertex buffers.
+    input_layout: native::WeakPtr<d3d11::ID3D11InputLayout>,
+    rasterizer_state: native::WeakPtr<d3d11::ID3D11RasterizerState>,
+    blend_state: native::WeakPtr<d3d11::ID3D11BlendState>,
+    depth_stencil_state: native::WeakPtr<d3d11::ID3D11DepthStencilState>,
+    topology: d3d11::D3D11_PRIMITIVE_TOPOLOGY,
+    vertex_strides: [u32; crate::MAX_VERTEX_BUFFERS],
+    sample_mask: u32,
+    special_constants_slot: Option<u32>,
+}
+
+unsafe impl Send for RenderPipeline {}
+unsafe impl Sync for RenderPipeline {}
+
+pub struct ComputePipeline {
+    cs: native::WeakPtr<d3d11::ID3D11ComputeShader>,
+    special_constants_slot: Option<u32>,
+}
+
+unsafe impl Send for ComputePipeline {}
+unsafe impl Sync for ComputePipeline {}
+
 #[derive(Debug)]
-pub struct ShaderModule {}
-pub struct RenderPipeline {}
-pub struct ComputePipeline {}
+pub struct PipelineCache;
+
+impl SwapChain {
+    unsafe fn release_resources(self) -> native::WeakPtr<dxgi::IDXGISwapChain> {
+        self.buffer.destroy();
+        self.raw
+    }
+}
 
 impl crate::Surface<Api> for Surface {
     unsafe fn configure(
@@ -115,21 +496,114 @@ impl crate::Surface<Api> for Surface {
         device: &Device,
         config: &crate::SurfaceConfiguration,
     ) -> Result<(), crate::SurfaceError> {
-        todo!()
+        // The blit model accepts sRGB formats, unlike the flip model.
+        let format = auxil::dxgi::conv::map_texture_format(config.format);
+
+        let swap_chain = match self.swap_chain.take() {
+            Some(sc) => {
+                let raw = sc.release_resources();
+                let result = raw.ResizeBuffers(
+                    config.swap_chain_size,
+                    config.extent.width,
+                    config.extent.height,
+                    format,
+                    0,
+                );
+                if let Err(err) = result.into_result() {
+                    log::error!("ResizeBuffers failed: {}", err);
+                    return Err(crate::SurfaceError::Other("window is in use"));
+                }
+                raw
+            }
+            None => {
+                let mut desc = dxgi::DXGI_SWAP_CHAIN_DESC {
+                    BufferDesc: dxgitype::DXGI_MODE_DESC {
+                        Width: config.extent.width,
+                        Height: config.extent.height,
+                        RefreshRate: dxgitype::DXGI_RATIONAL {
+                            Numerator: 0,
+                            Denominator: 1,
+                        },
+                        Format: format,
+                        ScanlineOrdering: dxgitype::DXGI_MODE_SCANLINE_ORDER_UNSPECIFIED,
+                        Scaling: dxgitype::DXGI_MODE_SCALING_UNSPECIFIED,
+                    },
+                    SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
+                        Count: 1,
+                        Quality: 0,
+                    },
+                    BufferUsage: dxgitype::DXGI_USAGE_RENDER_TARGET_OUTPUT,
+                    BufferCount: config.swap_chain_size,
+                    OutputWindow: self.wnd_handle,
+                    Windowed: 1,
+                    SwapEffect: dxgi::DXGI_SWAP_EFFECT_DISCARD,
+                    Flags: 0,
+                };
+
+                let mut swap_chain = native::WeakPtr::<dxgi::IDXGISwapChain>::null();
+                let hr = {
+                    profiling::scope!("IDXGIFactory::CreateSwapChain");
+                    self.factory.CreateSwapChain(
+                        device.shared.raw.as_mut_ptr() as *mut _,
+                        &mut desc,
+                        swap_chain.mut_self(),
+                    )
+                };
+                if let Err(err) = hr.into_result() {
+                    log::error!("SwapChain creation error: {}", err);
+                    return Err(crate::SurfaceError::Other("swap chain creation"));
+                }
+                swap_chain
+            }
+        };
+
+        auxil::dxgi::factory::make_window_association(self.factory, self.wnd_handle);
+
+        let mut buffer = native::WeakPtr::<d3d11::ID3D11Texture2D>::null();
+        let hr = swap_chain.GetBuffer(0, &d3d11::ID3D11Texture2D::uuidof(), buffer.mut_void());
+        if let Err(err) = hr.into_result() {
+            log::error!("Unable to get the swap chain buffer: {}", err);
+            swap_chain.destroy();
+            return Err(crate::SurfaceError::Other("swap chain buffer"));
+        }
+
+        self.swap_chain = Some(SwapChain {
+            raw: swap_chain,
+            buffer,
+            present_mode: config.present_mode,
+            format: config.format,
+            size: config.extent,
+        });
+
+        Ok(())
     }
 
-    unsafe fn unconfigure(&mut self, device: &Device) {
-        todo!()
+    unsafe fn unconfigure(&mut self, _device: &Device) {
+        if let Some(sc) = self.swap_chain.take() {
+            let raw = sc.release_resources();
+            raw.destroy();
+        }
     }
 
     unsafe fn acquire_texture(
         &mut self,
         _timeout: Option<std::time::Duration>,
     ) -> Result<Option<crate::AcquiredSurfaceTexture<Api>>, crate::SurfaceError> {
-        todo!()
-    }
+        let sc = self.swap_chain.as_ref().unwrap();
 
-    unsafe fn discard_texture(&mut self, texture: SurfaceTexture) {
-        todo!()
+        let texture = Texture {
+            resource: native::WeakPtr::from_raw(sc.buffer.as_mut_ptr() as *mut _),
+            format: sc.format,
+            dimension: wgt::TextureDimension::D2,
+            size: sc.size,
+            mip_level_count: 1,
+            sample_count: 1,
+        };
+        Ok(Some(crate::AcquiredSurfaceTexture {
+            texture,
+            suboptimal: false,
+        }))
     }
+
+    unsafe fn discard_texture(&mut self, _texture: Texture) {}
 }
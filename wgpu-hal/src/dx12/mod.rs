@@ -79,6 +79,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 // Limited by D3D12's root signature size of 64. Each element takes 1 or 2 entries.
@@ -519,6 +520,9 @@ pub struct ComputePipeline {
 unsafe impl Send for ComputePipeline {}
 unsafe impl Sync for ComputePipeline {}
 
+#[derive(Debug)]
+pub struct PipelineCache;
+
 impl SwapChain {
     unsafe fn release_resources(self) -> native::WeakPtr<dxgi1_4::IDXGISwapChain3> {
         for resource in self.resources {
@@ -663,13 +667,7 @@ impl crate::Surface<Api> for Surface {
 
         match self.target {
             SurfaceTarget::WndHandle(wnd_handle) => {
-                // Disable automatic Alt+Enter handling by DXGI.
-                const DXGI_MWA_NO_WINDOW_CHANGES: u32 = 1;
-                const DXGI_MWA_NO_ALT_ENTER: u32 = 2;
-                self.factory.MakeWindowAssociation(
-                    wnd_handle,
-                    DXGI_MWA_NO_WINDOW_CHANGES | DXGI_MWA_NO_ALT_ENTER,
-                );
+                auxil::dxgi::factory::make_window_association(self.factory, wnd_handle);
             }
             SurfaceTarget::Visual(_) => {}
         }
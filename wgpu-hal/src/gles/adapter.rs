@@ -91,6 +91,20 @@ impl super::Adapter {
         }
     }
 
+    /// Parses the version of a desktop GL context, like `4.6 (Core Profile) Mesa 22.0.1`.
+    ///
+    /// Returns `None` for GLES and WebGL version strings, which `parse_version` handles.
+    pub(super) fn parse_desktop_version(src: &str) -> Option<(u8, u8)> {
+        if src.starts_with("WebGL ") || src.contains(" ES ") {
+            return None;
+        }
+        let release = src.split(' ').next()?;
+        let mut it = release.split('.');
+        let major = it.next()?.parse().ok()?;
+        let minor = it.next()?.parse().ok()?;
+        Some((major, minor))
+    }
+
     fn make_info(vendor_orig: String, renderer_orig: String) -> wgt::AdapterInfo {
         let vendor = vendor_orig.to_lowercase();
         let renderer = renderer_orig.to_lowercase();
@@ -168,6 +182,11 @@ impl super::Adapter {
 
     pub(super) unsafe fn expose(
         context: super::AdapterContext,
+        timer_query_fns: Option<super::TimerQueryFns>,
+        program_binary_fns: Option<super::ProgramBinaryFns>,
+        multi_draw_indirect_fns: Option<super::MultiDrawIndirectFns>,
+        polygon_mode_fns: Option<super::PolygonModeFns>,
+        multiview_fns: Option<super::MultiviewFns>,
     ) -> Option<crate::ExposedAdapter<super::Api>> {
         let gl = context.lock();
         let extensions = gl.supported_extensions();
@@ -191,7 +210,24 @@ impl super::Adapter {
 
         log::debug!("Extensions: {:#?}", extensions);
 
-        let ver = Self::parse_version(&version).ok()?;
+        // Desktop GL accepts the GLSL ES 3.00 that naga emits since 4.3, and GLSL ES 3.10
+        // since 4.5 (`GL_ARB_ES3_compatibility` and `GL_ARB_ES3_1_compatibility`),
+        // so these contexts are handled like GLES 3.0 and 3.1 ones.
+        let desktop_ver = Self::parse_desktop_version(&version);
+        let ver = match desktop_ver {
+            Some(desktop_ver) if desktop_ver >= (4, 5) => (3, 1),
+            Some(desktop_ver) if desktop_ver >= (4, 3) => (3, 0),
+            Some(desktop_ver) => {
+                log::warn!(
+                    "Returned GL context is {}.{}, when 4.3+ is required",
+                    desktop_ver.0,
+                    desktop_ver.1
+                );
+                return None;
+            }
+            None => Self::parse_version(&version).ok()?,
+        };
+        let is_desktop = desktop_ver.is_some();
         if ver < (3, 0) {
             log::warn!(
                 "Returned GLES context is {}.{}, when 3.0+ was requested",
@@ -207,7 +243,11 @@ impl super::Adapter {
         let shading_language_version = {
             let sl_version = gl.get_parameter_string(glow::SHADING_LANGUAGE_VERSION);
             log::info!("SL version: {}", &sl_version);
-            let (sl_major, sl_minor) = Self::parse_version(&sl_version).ok()?;
+            let (sl_major, sl_minor) = if is_desktop {
+                ver
+            } else {
+                Self::parse_version(&sl_version).ok()?
+            };
             let value = sl_major as u16 * 100 + sl_minor as u16 * 10;
             naga::back::glsl::Version::Embedded(value)
         };
@@ -273,10 +313,13 @@ impl super::Adapter {
         downlevel_flags.set(wgt::DownlevelFlags::INDIRECT_EXECUTION, ver >= (3, 1));
         //TODO: we can actually support positive `base_vertex` in the same way
         // as we emulate the `start_instance`. But we can't deal with negatives...
-        downlevel_flags.set(wgt::DownlevelFlags::BASE_VERTEX, ver >= (3, 2));
+        downlevel_flags.set(
+            wgt::DownlevelFlags::BASE_VERTEX,
+            ver >= (3, 2) || is_desktop,
+        );
         downlevel_flags.set(
             wgt::DownlevelFlags::INDEPENDENT_BLEND,
-            ver >= (3, 2) || extensions.contains("GL_EXT_draw_buffers_indexed"),
+            ver >= (3, 2) || is_desktop || extensions.contains("GL_EXT_draw_buffers_indexed"),
         );
         downlevel_flags.set(
             wgt::DownlevelFlags::VERTEX_STORAGE,
@@ -290,7 +333,9 @@ impl super::Adapter {
             extensions.contains("EXT_texture_filter_anisotropic"),
         );
 
-        let is_ext_color_buffer_float_supported = extensions.contains("EXT_color_buffer_float");
+        // Float formats are color-renderable in core desktop GL.
+        let is_ext_color_buffer_float_supported =
+            is_desktop || extensions.contains("EXT_color_buffer_float");
 
         let mut features = wgt::Features::empty()
             | wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
@@ -339,6 +384,58 @@ impl super::Adapter {
             extensions.contains("GL_KHR_texture_compression_astc_ldr")
                 || extensions.contains("WEBGL_compressed_texture_astc"),
         );
+        let timer_query_fns = timer_query_fns.filter(|_| {
+            extensions.contains("GL_EXT_disjoint_timer_query")
+                || extensions.contains("GL_ARB_timer_query")
+        });
+        features.set(wgt::Features::TIMESTAMP_QUERY, timer_query_fns.is_some());
+        // Program binaries are useless if the driver doesn't accept any format.
+        let program_binary_fns = program_binary_fns
+            .filter(|_| gl.get_parameter_i32(glow::NUM_PROGRAM_BINARY_FORMATS) > 0);
+        let pipeline_cache_key = program_binary_fns.map(|_| {
+            let driver = (&vendor, &renderer, &version);
+            let mut key = [0; 16];
+            key[..8].copy_from_slice(&fxhash::hash64(&(0u8, driver)).to_le_bytes());
+            key[8..].copy_from_slice(&fxhash::hash64(&(1u8, driver)).to_le_bytes());
+            key
+        });
+        features.set(wgt::Features::PIPELINE_CACHE, program_binary_fns.is_some());
+        // See "Multi-draw" in the module docs.
+        let emulate_multi_draw_indirect = match std::env::var("WGPU_GL_EMULATE_MULTI_DRAW_INDIRECT")
+        {
+            Ok(string) => string == "1" || string == "true",
+            Err(_) => false,
+        };
+        let multi_draw_indirect_fns = multi_draw_indirect_fns.filter(|_| {
+            !emulate_multi_draw_indirect
+                && (extensions.contains("GL_EXT_multi_draw_indirect")
+                    || extensions.contains("GL_ARB_multi_draw_indirect"))
+        });
+        // Without `glMultiDraw*Indirect` the draws are issued one by one.
+        features.set(wgt::Features::MULTI_DRAW_INDIRECT, ver >= (3, 1));
+        // `GL_ARB_indirect_parameters` only exists on desktop GL, where it's core since 4.6.
+        features.set(
+            wgt::Features::MULTI_DRAW_INDIRECT_COUNT,
+            multi_draw_indirect_fns.map_or(false, |fns| fns.supports_count())
+                && desktop_ver.map_or(false, |desktop_ver| {
+                    desktop_ver >= (4, 6) || extensions.contains("GL_ARB_indirect_parameters")
+                }),
+        );
+        // `glPolygonMode` is core in desktop GL, GLES needs an extension for it.
+        let polygon_mode_fns = polygon_mode_fns.filter(|_| {
+            is_desktop
+                || extensions.contains("GL_NV_polygon_mode")
+                || extensions.contains("GL_ANGLE_polygon_mode")
+        });
+        features.set(wgt::Features::POLYGON_MODE_LINE, polygon_mode_fns.is_some());
+        // `GL_ANGLE_polygon_mode` only has the line mode.
+        features.set(
+            wgt::Features::POLYGON_MODE_POINT,
+            polygon_mode_fns.is_some() && (is_desktop || extensions.contains("GL_NV_polygon_mode")),
+        );
+        // The view index in shaders other than the vertex one needs the second version.
+        let multiview_fns = multiview_fns.filter(|_| extensions.contains("GL_OVR_multiview2"));
+        features.set(wgt::Features::MULTIVIEW, multiview_fns.is_some());
 
         let mut private_caps = super::PrivateCapabilities::empty();
         private_caps.set(
@@ -370,6 +467,19 @@ impl super::Adapter {
             super::PrivateCapabilities::GET_BUFFER_SUB_DATA,
             cfg!(target_arch = "wasm32"),
         );
+        private_caps.set(
+            super::PrivateCapabilities::MULTI_DRAW_INDIRECT,
+            multi_draw_indirect_fns.is_some(),
+        );
+        private_caps.set(
+            super::PrivateCapabilities::MEMORY_INFO_NVX,
+            extensions.contains("GL_NVX_gpu_memory_info"),
+        );
+        private_caps.set(
+            super::PrivateCapabilities::MEMORY_INFO_ATI,
+            extensions.contains("GL_ATI_meminfo"),
+        );
+        private_caps.set(super::PrivateCapabilities::DESKTOP_GL, is_desktop);
 
         let max_texture_size = gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE) as u32;
         let max_texture_3d_size = gl.get_parameter_i32(glow::MAX_3D_TEXTURE_SIZE) as u32;
@@ -503,6 +613,12 @@ impl super::Adapter {
                     shading_language_version,
                     max_texture_size,
                     is_ext_color_buffer_float_supported,
+                    timer_query_fns,
+                    program_binary_fns,
+                    multi_draw_indirect_fns,
+                    polygon_mode_fns,
+                    multiview_fns,
+                    pipeline_cache_key,
                 }),
             },
             info: Self::make_info(vendor, renderer),
@@ -565,6 +681,17 @@ impl crate::Adapter<super::Api> for super::Adapter {
             .map_err(|_| crate::DeviceError::OutOfMemory)?;
         gl.bind_vertex_array(Some(main_vao));
 
+        if self
+            .shared
+            .private_caps
+            .contains(super::PrivateCapabilities::DESKTOP_GL)
+        {
+            // Match the behavior of GLES, where these are always enabled.
+            gl.enable(glow::FRAMEBUFFER_SRGB);
+            gl.enable(glow::PRIMITIVE_RESTART_FIXED_INDEX);
+            gl.enable(glow::TEXTURE_CUBE_MAP_SEAMLESS);
+        }
+
         let zero_buffer = gl
             .create_buffer()
             .map_err(|_| crate::DeviceError::OutOfMemory)?;
@@ -811,4 +938,26 @@ mod tests {
             Ok((3, 0))
         );
     }
+
+    #[test]
+    fn test_desktop_version_parse() {
+        assert_eq!(
+            Adapter::parse_desktop_version("4.5 (Core Profile) Mesa 22.0.1"),
+            Some((4, 5))
+        );
+        assert_eq!(
+            Adapter::parse_desktop_version("4.6.0 NVIDIA 510.60.02"),
+            Some((4, 6))
+        );
+        assert_eq!(Adapter::parse_desktop_version("3.3"), Some((3, 3)));
+        assert_eq!(Adapter::parse_desktop_version("4"), None);
+        assert_eq!(
+            Adapter::parse_desktop_version("OpenGL ES 3.2 Mesa 22.0.1"),
+            None
+        );
+        assert_eq!(
+            Adapter::parse_desktop_version("WebGL 2.0 (OpenGL ES 3.0 Chromium)"),
+            None
+        );
+    }
 }
@@ -387,11 +387,13 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     unsafe fn end_query(&mut self, set: &super::QuerySet, _index: u32) {
         self.cmd_buffer.commands.push(C::EndQuery(set.target));
     }
-    unsafe fn write_timestamp(&mut self, _set: &super::QuerySet, _index: u32) {
-        unimplemented!()
+    unsafe fn write_timestamp(&mut self, set: &super::QuerySet, index: u32) {
+        let query = set.queries[index as usize];
+        self.cmd_buffer.commands.push(C::TimestampQuery(query));
     }
     unsafe fn reset_queries(&mut self, _set: &super::QuerySet, _range: Range<u32>) {
-        //TODO: what do we do here?
+        // GL query objects don't need to be reset: every `glBeginQuery` or
+        // `glQueryCounter` overwrites the previous result.
     }
     unsafe fn copy_query_results(
         &mut self,
@@ -408,6 +410,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         let query_range = start as u32..self.cmd_buffer.queries.len() as u32;
         self.cmd_buffer.commands.push(C::CopyQueryResults {
             query_range,
+            query_target: set.target,
             dst: buffer.clone(),
             dst_target: buffer.target,
             dst_offset: offset,
@@ -448,6 +451,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                     self.cmd_buffer.commands.push(C::BindAttachment {
                         attachment,
                         view: cat.target.view.clone(),
+                        multiview: desc.multiview,
                     });
                     if let Some(ref rat) = cat.resolve_target {
                         self.state
@@ -468,6 +472,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                     self.cmd_buffer.commands.push(C::BindAttachment {
                         attachment,
                         view: dsat.target.view.clone(),
+                        multiview: desc.multiview,
                     });
                     if aspects.contains(crate::FormatAspects::DEPTH)
                         && !dsat.depth_ops.contains(crate::AttachmentOps::STORE)
@@ -910,6 +915,19 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         draw_count: u32,
     ) {
         self.prepare_draw(0);
+        if draw_count > 1
+            && self
+                .private_caps
+                .contains(super::PrivateCapabilities::MULTI_DRAW_INDIRECT)
+        {
+            self.cmd_buffer.commands.push(C::MultiDrawIndirect {
+                topology: self.state.topology,
+                indirect_buf: buffer.raw.unwrap(),
+                indirect_offset: offset,
+                draw_count,
+            });
+            return;
+        }
         for draw in 0..draw_count as wgt::BufferAddress {
             let indirect_offset =
                 offset + draw * mem::size_of::<wgt::DrawIndirectArgs>() as wgt::BufferAddress;
@@ -931,6 +949,20 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             wgt::IndexFormat::Uint16 => glow::UNSIGNED_SHORT,
             wgt::IndexFormat::Uint32 => glow::UNSIGNED_INT,
         };
+        if draw_count > 1
+            && self
+                .private_caps
+                .contains(super::PrivateCapabilities::MULTI_DRAW_INDIRECT)
+        {
+            self.cmd_buffer.commands.push(C::MultiDrawIndexedIndirect {
+                topology: self.state.topology,
+                index_type,
+                indirect_buf: buffer.raw.unwrap(),
+                indirect_offset: offset,
+                draw_count,
+            });
+            return;
+        }
         for draw in 0..draw_count as wgt::BufferAddress {
             let indirect_offset = offset
                 + draw * mem::size_of::<wgt::DrawIndexedIndirectArgs>() as wgt::BufferAddress;
@@ -944,23 +976,48 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     }
     unsafe fn draw_indirect_count(
         &mut self,
-        _buffer: &super::Buffer,
-        _offset: wgt::BufferAddress,
-        _count_buffer: &super::Buffer,
-        _count_offset: wgt::BufferAddress,
-        _max_count: u32,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        count_buffer: &super::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
     ) {
-        unreachable!()
+        // The count is only known on the GPU, so there is no fallback
+        // and the feature is only exposed with `glMultiDraw*IndirectCount`.
+        self.prepare_draw(0);
+        self.cmd_buffer.commands.push(C::MultiDrawIndirectCount {
+            topology: self.state.topology,
+            indirect_buf: buffer.raw.unwrap(),
+            indirect_offset: offset,
+            count_buf: count_buffer.raw.unwrap(),
+            count_offset,
+            max_count,
+        });
     }
     unsafe fn draw_indexed_indirect_count(
         &mut self,
-        _buffer: &super::Buffer,
-        _offset: wgt::BufferAddress,
-        _count_buffer: &super::Buffer,
-        _count_offset: wgt::BufferAddress,
-        _max_count: u32,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        count_buffer: &super::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
     ) {
-        unreachable!()
+        self.prepare_draw(0);
+        let index_type = match self.state.index_format {
+            wgt::IndexFormat::Uint16 => glow::UNSIGNED_SHORT,
+            wgt::IndexFormat::Uint32 => glow::UNSIGNED_INT,
+        };
+        self.cmd_buffer
+            .commands
+            .push(C::MultiDrawIndexedIndirectCount {
+                topology: self.state.topology,
+                index_type,
+                indirect_buf: buffer.raw.unwrap(),
+                indirect_offset: offset,
+                count_buf: count_buffer.raw.unwrap(),
+                count_offset,
+                max_count,
+            });
     }
 
     // compute
@@ -3,7 +3,9 @@ use crate::auxil::map_naga_stage;
 use glow::HasContext;
 use std::{
     convert::TryInto,
-    iter, ptr,
+    iter,
+    num::NonZeroU32,
+    ptr,
     sync::{Arc, Mutex},
 };
 
@@ -20,6 +22,7 @@ struct CompilationContext<'a> {
     layout: &'a super::PipelineLayout,
     sampler_map: &'a mut super::SamplerBindMap,
     name_binding_map: &'a mut NameBindingMap,
+    multiview: Option<NonZeroU32>,
 }
 
 impl CompilationContext<'_> {
@@ -124,12 +127,37 @@ impl super::Device {
         }
     }
 
-    fn create_shader(
+    /// Switches the view index of the GLSL generated by naga to `GL_OVR_multiview2`.
+    ///
+    /// Naga writes the Vulkan flavor of the builtin, which doesn't exist in GLES.
+    fn enable_multiview(source: &str, naga_stage: naga::ShaderStage, views: NonZeroU32) -> String {
+        //TODO: drop the patching once naga's GLSL backend targets `GL_OVR_multiview2` itself,
+        // taking the view count in `glsl::Options` and writing `gl_ViewID_OVR` for the view index.
+        // It is covered by the `multiview` test of `wgpu`, and the test below.
+        let mut lines = source.lines();
+        let mut output = String::with_capacity(source.len() + 100);
+        // The extension has to follow the `#version` line.
+        if let Some(version) = lines.next() {
+            output.push_str(version);
+            output.push('\n');
+        }
+        output.push_str("#extension GL_OVR_multiview2 : require\n");
+        if naga_stage == naga::ShaderStage::Vertex {
+            output.push_str(&format!("layout(num_views = {}) in;\n", views));
+        }
+        for line in lines.filter(|line| !line.starts_with("#extension GL_EXT_multiview")) {
+            output.push_str(&line.replace("gl_ViewIndex", "int(gl_ViewID_OVR)"));
+            output.push('\n');
+        }
+        output
+    }
+
+    fn generate_shader(
         gl: &glow::Context,
         naga_stage: naga::ShaderStage,
         stage: &crate::ProgrammableStage<super::Api>,
         context: CompilationContext,
-    ) -> Result<glow::Shader, crate::PipelineError> {
+    ) -> Result<String, crate::PipelineError> {
         use naga::back::glsl;
         let pipeline_options = glsl::PipelineOptions {
             shader_stage: naga_stage,
@@ -137,8 +165,8 @@ impl super::Device {
         };
 
         let shader = &stage.module.naga;
-        let entry_point_index = shader
-            .module
+        let module = crate::auxil::apply_pipeline_constants(&shader.module, stage.constants);
+        let entry_point_index = module
             .entry_points
             .iter()
             .position(|ep| ep.name.as_str() == stage.entry_point)
@@ -164,7 +192,7 @@ impl super::Device {
         let mut output = String::new();
         let mut writer = glsl::Writer::new(
             &mut output,
-            &shader.module,
+            &module,
             &shader.info,
             &context.layout.naga_options,
             &pipeline_options,
@@ -180,15 +208,19 @@ impl super::Device {
             crate::PipelineError::Linkage(map_naga_stage(naga_stage), msg)
         })?;
 
+        if let Some(views) = context.multiview {
+            output = Self::enable_multiview(&output, naga_stage, views);
+        }
+
         log::debug!("Naga generated shader:\n{}", output);
 
         context.consume_reflection(
-            &shader.module,
+            &module,
             shader.info.get_entry_point(entry_point_index),
             reflection_info,
         );
 
-        unsafe { Self::compile_shader(gl, &output, naga_stage, stage.module.label.as_deref()) }
+        Ok(output)
     }
 
     unsafe fn create_pipeline<'a, I: Iterator<Item = ShaderStage<'a>>>(
@@ -197,6 +229,8 @@ impl super::Device {
         shaders: I,
         layout: &super::PipelineLayout,
         #[cfg_attr(target_arch = "wasm32", allow(unused))] label: Option<&str>,
+        cache: Option<&super::PipelineCache>,
+        multiview: Option<NonZeroU32>,
     ) -> Result<super::PipelineInner, crate::PipelineError> {
         let program = gl.create_program().unwrap();
         #[cfg(not(target_arch = "wasm32"))]
@@ -209,7 +243,7 @@ impl super::Device {
         let mut name_binding_map = NameBindingMap::default();
         let mut sampler_map = [None; super::MAX_TEXTURE_SLOTS];
         let mut has_stages = wgt::ShaderStages::empty();
-        let mut shaders_to_delete = arrayvec::ArrayVec::<_, 3>::new();
+        let mut sources = arrayvec::ArrayVec::<_, 3>::new();
 
         for (naga_stage, stage) in shaders {
             has_stages |= map_naga_stage(naga_stage);
@@ -217,10 +251,11 @@ impl super::Device {
                 layout,
                 sampler_map: &mut sampler_map,
                 name_binding_map: &mut name_binding_map,
+                multiview,
             };
 
-            let shader = Self::create_shader(gl, naga_stage, stage, context)?;
-            shaders_to_delete.push(shader);
+            let source = Self::generate_shader(gl, naga_stage, stage, context)?;
+            sources.push((naga_stage, source, stage.module.label.as_deref()));
         }
 
         // Create empty fragment shader if only vertex shader is present
@@ -231,33 +266,70 @@ impl super::Device {
             };
             let shader_src = format!("#version {} es \n void main(void) {{}}", version,);
             log::info!("Only vertex shader is present. Creating an empty fragment shader",);
-            let shader = Self::compile_shader(
-                gl,
-                &shader_src,
+            sources.push((
                 naga::ShaderStage::Fragment,
+                shader_src,
                 Some("(wgpu internal) dummy fragment shader"),
-            )?;
-            shaders_to_delete.push(shader);
+            ));
         }
 
-        for &shader in shaders_to_delete.iter() {
-            gl.attach_shader(program, shader);
+        // The generated sources fully determine the linked program,
+        // so they are used to look it up in the cache.
+        let cache = cache.zip(self.shared.program_binary_fns.as_ref());
+        let cache_key = sources
+            .iter()
+            .map(|&(_, ref source, _)| source.as_str())
+            .collect::<Vec<_>>()
+            .join("\0");
+
+        let mut linked_from_cache = false;
+        if let Some((cache, fns)) = cache {
+            if let Some(binary) = cache.programs.lock().get(&cache_key) {
+                fns.load_binary(program, binary);
+                linked_from_cache = gl.get_program_link_status(program);
+                if linked_from_cache {
+                    log::info!("\tLoaded program {:?} from the pipeline cache", program);
+                } else {
+                    log::info!("\tProgram binary was rejected by the driver, recompiling");
+                }
+            }
         }
-        gl.link_program(program);
 
-        for shader in shaders_to_delete {
-            gl.delete_shader(shader);
-        }
+        if !linked_from_cache {
+            let mut shaders_to_delete = arrayvec::ArrayVec::<_, 3>::new();
+            for &(naga_stage, ref source, label) in sources.iter() {
+                let shader = Self::compile_shader(gl, source, naga_stage, label)?;
+                shaders_to_delete.push(shader);
+            }
 
-        log::info!("\tLinked program {:?}", program);
+            for &shader in shaders_to_delete.iter() {
+                gl.attach_shader(program, shader);
+            }
+            if let Some((_, fns)) = cache {
+                fns.set_retrievable(program);
+            }
+            gl.link_program(program);
 
-        let linked_ok = gl.get_program_link_status(program);
-        let msg = gl.get_program_info_log(program);
-        if !linked_ok {
-            return Err(crate::PipelineError::Linkage(has_stages, msg));
-        }
-        if !msg.is_empty() {
-            log::warn!("\tLink: {}", msg);
+            for shader in shaders_to_delete {
+                gl.delete_shader(shader);
+            }
+
+            log::info!("\tLinked program {:?}", program);
+
+            let linked_ok = gl.get_program_link_status(program);
+            let msg = gl.get_program_info_log(program);
+            if !linked_ok {
+                return Err(crate::PipelineError::Linkage(has_stages, msg));
+            }
+            if !msg.is_empty() {
+                log::warn!("\tLink: {}", msg);
+            }
+
+            if let Some((cache, fns)) = cache {
+                if let Some(binary) = fns.get(program) {
+                    cache.programs.lock().insert(cache_key, binary);
+                }
+            }
         }
 
         if !self
@@ -977,7 +1049,14 @@ impl crate::Device<super::Api> for super::Device {
                 .as_ref()
                 .map(|fs| (naga::ShaderStage::Fragment, fs)),
         );
-        let inner = self.create_pipeline(gl, shaders, desc.layout, desc.label)?;
+        let inner = self.create_pipeline(
+            gl,
+            shaders,
+            desc.layout,
+            desc.label,
+            desc.cache,
+            desc.multiview,
+        )?;
 
         let (vertex_buffers, vertex_attributes) = {
             let mut buffers = Vec::new();
@@ -1045,7 +1124,7 @@ impl crate::Device<super::Api> for super::Device {
     ) -> Result<super::ComputePipeline, crate::PipelineError> {
         let gl = &self.shared.context.lock();
         let shaders = iter::once((naga::ShaderStage::Compute, &desc.stage));
-        let inner = self.create_pipeline(gl, shaders, desc.layout, desc.label)?;
+        let inner = self.create_pipeline(gl, shaders, desc.layout, desc.label, desc.cache, None)?;
 
         Ok(super::ComputePipeline { inner })
     }
@@ -1054,6 +1133,26 @@ impl crate::Device<super::Api> for super::Device {
         gl.delete_program(pipeline.inner.program);
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        Ok(match desc.data {
+            Some(data) => super::PipelineCache::from_data(data).unwrap_or_else(|| {
+                log::warn!("Pipeline cache data is malformed, starting with an empty cache");
+                super::PipelineCache::default()
+            }),
+            None => super::PipelineCache::default(),
+        })
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        self.shared.pipeline_cache_key
+    }
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        self.shared.program_binary_fns.map(|_| cache.to_data())
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
+
     #[cfg_attr(target_arch = "wasm32", allow(unused))]
     unsafe fn create_query_set(
         &self,
@@ -1084,6 +1183,7 @@ impl crate::Device<super::Api> for super::Device {
             queries: queries.into_boxed_slice(),
             target: match desc.ty {
                 wgt::QueryType::Occlusion => glow::ANY_SAMPLES_PASSED,
+                wgt::QueryType::Timestamp => glow::TIMESTAMP,
                 _ => unimplemented!(),
             },
         })
@@ -1160,6 +1260,47 @@ impl crate::Device<super::Api> for super::Device {
         self.render_doc
             .end_frame_capture(ptr::null_mut(), ptr::null_mut())
     }
+
+    fn memory_report(&self) -> wgt::MemoryReport {
+        let private_caps = self.shared.private_caps;
+        let gl = &self.shared.context.lock();
+        // GL has no notion of heaps, the video memory is reported as one heap if the
+        // driver tells anything about it.
+        let heap = if private_caps.contains(super::PrivateCapabilities::MEMORY_INFO_NVX) {
+            let (total, available) = unsafe {
+                (
+                    gl.get_parameter_i32(super::GPU_MEMORY_INFO_TOTAL_AVAILABLE_MEMORY_NVX),
+                    gl.get_parameter_i32(super::GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX),
+                )
+            };
+            let total = total.max(0) as u64 * 1024;
+            let available = available.max(0) as u64 * 1024;
+            Some(wgt::MemoryHeapReport {
+                size: total,
+                device_local: true,
+                usage: total.saturating_sub(available),
+                budget: Some(total),
+            })
+        } else if private_caps.contains(super::PrivateCapabilities::MEMORY_INFO_ATI) {
+            // Only the free memory is known: total, largest block, total auxiliary, largest auxiliary.
+            let mut free = [0; 4];
+            unsafe { gl.get_parameter_i32_slice(super::TEXTURE_FREE_MEMORY_ATI, &mut free) };
+            Some(wgt::MemoryHeapReport {
+                size: 0,
+                device_local: true,
+                usage: 0,
+                budget: Some(free[0].max(0) as u64 * 1024),
+            })
+        } else {
+            None
+        };
+
+        wgt::MemoryReport {
+            heaps: heap.into_iter().collect(),
+            allocator: None,
+            resources: wgt::ResourceMemoryReport::default(),
+        }
+    }
 }
 
 // SAFE: WASM doesn't have threads
@@ -1167,3 +1308,81 @@ impl crate::Device<super::Api> for super::Device {
 unsafe impl Sync for super::Device {}
 #[cfg(target_arch = "wasm32")]
 unsafe impl Send for super::Device {}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    const SHADER: &str = "
+@vertex
+fn vs_main(@builtin(view_index) view_index: i32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(f32(view_index), 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(view_index) view_index: i32) -> @location(0) vec4<f32> {
+    return vec4<f32>(f32(view_index), 0.0, 0.0, 1.0);
+}
+";
+
+    fn generate_multiview_shader(stage: naga::ShaderStage, entry_point: &str) -> String {
+        use naga::back::glsl;
+
+        let module = naga::front::wgsl::Parser::new().parse(SHADER).unwrap();
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+        let options = glsl::Options {
+            version: glsl::Version::Embedded(300),
+            writer_flags: glsl::WriterFlags::empty(),
+            binding_map: Default::default(),
+        };
+        let pipeline_options = glsl::PipelineOptions {
+            shader_stage: stage,
+            entry_point: entry_point.to_string(),
+        };
+        let mut output = String::new();
+        glsl::Writer::new(
+            &mut output,
+            &module,
+            &info,
+            &options,
+            &pipeline_options,
+            naga::proc::BoundsCheckPolicies::default(),
+        )
+        .unwrap()
+        .write()
+        .unwrap();
+        super::super::Device::enable_multiview(&output, stage, NonZeroU32::new(2).unwrap())
+    }
+
+    #[test]
+    fn enable_multiview() {
+        for &(stage, entry_point) in &[
+            (naga::ShaderStage::Vertex, "vs_main"),
+            (naga::ShaderStage::Fragment, "fs_main"),
+        ] {
+            let source = generate_multiview_shader(stage, entry_point);
+            let mut lines = source.lines();
+            assert_eq!(lines.next(), Some("#version 300 es"), "{}", source);
+            assert_eq!(
+                lines.next(),
+                Some("#extension GL_OVR_multiview2 : require"),
+                "{}",
+                source
+            );
+            assert_eq!(
+                source.contains("layout(num_views = 2) in;"),
+                stage == naga::ShaderStage::Vertex,
+                "{}",
+                source
+            );
+            assert!(source.contains("int(gl_ViewID_OVR)"), "{}", source);
+            assert!(!source.contains("gl_ViewIndex"), "{}", source);
+            assert!(!source.contains("GL_EXT_multiview"), "{}", source);
+        }
+    }
+}
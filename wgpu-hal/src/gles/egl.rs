@@ -8,7 +8,11 @@ use std::{ffi, os::raw, ptr, sync::Arc, time::Duration};
 const CONTEXT_LOCK_TIMEOUT_SECS: u64 = 1;
 
 const EGL_CONTEXT_FLAGS_KHR: i32 = 0x30FC;
+const EGL_CONTEXT_MINOR_VERSION_KHR: i32 = 0x30FB;
+const EGL_CONTEXT_OPENGL_PROFILE_MASK_KHR: i32 = 0x30FD;
+const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT_KHR: i32 = 0x0001;
 const EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR: i32 = 0x0001;
+const EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR: i32 = 0x0004;
 const EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT: i32 = 0x30BF;
 const EGL_PLATFORM_WAYLAND_KHR: u32 = 0x31D8;
 const EGL_PLATFORM_X11_KHR: u32 = 0x31D5;
@@ -159,13 +163,18 @@ enum SrgbFrameBufferKind {
     Khr,
 }
 
-/// Choose GLES framebuffer configuration.
+/// Choose framebuffer configuration, renderable with the given client API.
 fn choose_config(
     egl: &EglInstance,
     display: egl::Display,
     srgb_kind: SrgbFrameBufferKind,
+    api: ClientApi,
 ) -> Result<(egl::Config, bool), crate::InstanceError> {
     //TODO: EGL_SLOW_CONFIG
+    let renderable_type = match api {
+        ClientApi::Gles => egl::OPENGL_ES2_BIT,
+        ClientApi::DesktopGl => egl::OPENGL_BIT,
+    };
     let tiers = [
         (
             "off-screen",
@@ -173,7 +182,7 @@ fn choose_config(
                 egl::SURFACE_TYPE,
                 egl::PBUFFER_BIT,
                 egl::RENDERABLE_TYPE,
-                egl::OPENGL_ES2_BIT,
+                renderable_type,
             ][..],
         ),
         ("presentation", &[egl::SURFACE_TYPE, egl::WINDOW_BIT][..]),
@@ -230,6 +239,99 @@ fn choose_config(
     Err(crate::InstanceError)
 }
 
+/// Create a context of the given client API.
+///
+/// Returns the chosen configuration and whether it can present natively, along with the context.
+fn create_context(
+    flags: crate::InstanceFlags,
+    egl: &EglInstance,
+    display: egl::Display,
+    version: (i32, i32),
+    display_extensions: &str,
+    srgb_kind: SrgbFrameBufferKind,
+    api: ClientApi,
+) -> Result<(egl::Config, bool, egl::Context), crate::InstanceError> {
+    let (config, supports_native_window) = choose_config(egl, display, srgb_kind, api)?;
+    egl.bind_api(api.raw()).map_err(|e| {
+        log::warn!("unable to bind {:?}: {:?}", api, e);
+        crate::InstanceError
+    })?;
+
+    let needs_robustness = true;
+    let mut khr_context_flags = 0;
+    let supports_khr_context = display_extensions.contains("EGL_KHR_create_context");
+
+    //TODO: make it so `Device` == EGL Context
+    let mut context_attributes = match api {
+        ClientApi::Gles => vec![
+            egl::CONTEXT_CLIENT_VERSION,
+            3, // Request GLES 3.0 or higher
+        ],
+        // Request a core profile of GL 4.3 or higher, which accepts the GLSL ES
+        // that naga emits, see `Adapter::expose`.
+        // `EGL_CONTEXT_CLIENT_VERSION` is the same as `EGL_CONTEXT_MAJOR_VERSION`.
+        ClientApi::DesktopGl => vec![
+            egl::CONTEXT_CLIENT_VERSION,
+            4,
+            EGL_CONTEXT_MINOR_VERSION_KHR,
+            3,
+            EGL_CONTEXT_OPENGL_PROFILE_MASK_KHR,
+            EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT_KHR,
+        ],
+    };
+    if flags.contains(crate::InstanceFlags::DEBUG) {
+        if version >= (1, 5) {
+            log::info!("\tEGL context: +debug");
+            context_attributes.push(egl::CONTEXT_OPENGL_DEBUG);
+            context_attributes.push(egl::TRUE as _);
+        } else if supports_khr_context {
+            log::info!("\tEGL context: +debug KHR");
+            khr_context_flags |= EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR;
+        } else {
+            log::info!("\tEGL context: -debug");
+        }
+    }
+    if needs_robustness {
+        //Note: the core version can fail if robustness is not supported
+        // (regardless of whether the extension is supported!).
+        // In fact, Angle does precisely that awful behavior, so we don't try it there.
+        if version >= (1, 5) && !display_extensions.contains("EGL_ANGLE_") {
+            log::info!("\tEGL context: +robust access");
+            context_attributes.push(egl::CONTEXT_OPENGL_ROBUST_ACCESS);
+            context_attributes.push(egl::TRUE as _);
+        } else if api == ClientApi::DesktopGl && supports_khr_context {
+            log::info!("\tEGL context: +robust access KHR");
+            khr_context_flags |= EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR;
+        } else if display_extensions.contains("EGL_EXT_create_context_robustness") {
+            log::info!("\tEGL context: +robust access EXT");
+            context_attributes.push(EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT);
+            context_attributes.push(egl::TRUE as _);
+        } else {
+            //Note: `EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR` is only tried
+            // for desktop GL, it doesn't apply to GLES.
+            log::warn!("\tEGL context: -robust access");
+        }
+
+        //TODO do we need `egl::CONTEXT_OPENGL_NOTIFICATION_STRATEGY_EXT`?
+    }
+    if khr_context_flags != 0 {
+        context_attributes.push(EGL_CONTEXT_FLAGS_KHR);
+        context_attributes.push(khr_context_flags);
+    }
+    context_attributes.push(egl::NONE);
+    match egl.create_context(display, config, None, &context_attributes) {
+        Ok(context) => Ok((config, supports_native_window, context)),
+        Err(e) => {
+            let name = match api {
+                ClientApi::Gles => "GLES 3.x",
+                ClientApi::DesktopGl => "GL 4.3+ core",
+            };
+            log::warn!("unable to create {} context: {:?}", name, e);
+            Err(crate::InstanceError)
+        }
+    }
+}
+
 fn gl_debug_message_callback(source: u32, gltype: u32, id: u32, severity: u32, message: &str) {
     let source_str = match source {
         glow::DEBUG_SOURCE_API => "API",
@@ -279,16 +381,39 @@ fn gl_debug_message_callback(source: u32, gltype: u32, id: u32, severity: u32, m
     }
 }
 
+/// Client API of an EGL context.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ClientApi {
+    Gles,
+    DesktopGl,
+}
+
+impl ClientApi {
+    fn raw(self) -> egl::Enum {
+        match self {
+            Self::Gles => egl::OPENGL_ES_API,
+            Self::DesktopGl => egl::OPENGL_API,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 struct EglContext {
     instance: Arc<EglInstance>,
     display: egl::Display,
     raw: egl::Context,
     pbuffer: Option<egl::Surface>,
+    api: ClientApi,
 }
 
 impl EglContext {
+    /// Bind the client API of the context, which EGL tracks per thread,
+    /// so that releasing the current context applies to it.
+    fn bind_api(&self) {
+        self.instance.bind_api(self.api.raw()).unwrap();
+    }
     fn make_current(&self) {
+        self.bind_api();
         self.instance
             .make_current(self.display, self.pbuffer, self.pbuffer, Some(self.raw))
             .unwrap();
@@ -454,61 +579,54 @@ impl Inner {
             }
         }
 
-        let (config, supports_native_window) = choose_config(&egl, display, srgb_kind)?;
-        egl.bind_api(egl::OPENGL_ES_API).unwrap();
-
-        let needs_robustness = true;
-        let mut khr_context_flags = 0;
         let supports_khr_context = display_extensions.contains("EGL_KHR_create_context");
-
-        //TODO: make it so `Device` == EGL Context
-        let mut context_attributes = vec![
-            egl::CONTEXT_CLIENT_VERSION,
-            3, // Request GLES 3.0 or higher
-        ];
-        if flags.contains(crate::InstanceFlags::DEBUG) {
-            if version >= (1, 5) {
-                log::info!("\tEGL context: +debug");
-                context_attributes.push(egl::CONTEXT_OPENGL_DEBUG);
-                context_attributes.push(egl::TRUE as _);
-            } else if supports_khr_context {
-                log::info!("\tEGL context: +debug KHR");
-                khr_context_flags |= EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR;
-            } else {
-                log::info!("\tEGL context: -debug");
-            }
-        }
-        if needs_robustness {
-            //Note: the core version can fail if robustness is not supported
-            // (regardless of whether the extension is supported!).
-            // In fact, Angle does precisely that awful behavior, so we don't try it there.
-            if version >= (1, 5) && !display_extensions.contains("EGL_ANGLE_") {
-                log::info!("\tEGL context: +robust access");
-                context_attributes.push(egl::CONTEXT_OPENGL_ROBUST_ACCESS);
-                context_attributes.push(egl::TRUE as _);
-            } else if display_extensions.contains("EGL_EXT_create_context_robustness") {
-                log::info!("\tEGL context: +robust access EXT");
-                context_attributes.push(EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT);
-                context_attributes.push(egl::TRUE as _);
-            } else {
-                //Note: we aren't trying `EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR`
-                // because it's for desktop GL only, not GLES.
-                log::warn!("\tEGL context: -robust access");
-            }
-
-            //TODO do we need `egl::CONTEXT_OPENGL_NOTIFICATION_STRATEGY_EXT`?
-        }
-        if khr_context_flags != 0 {
-            context_attributes.push(EGL_CONTEXT_FLAGS_KHR);
-            context_attributes.push(khr_context_flags);
-        }
-        context_attributes.push(egl::NONE);
-        let context = match egl.create_context(display, config, None, &context_attributes) {
-            Ok(context) => context,
-            Err(e) => {
-                log::warn!("unable to create GLES 3.x context: {:?}", e);
-                return Err(crate::InstanceError);
-            }
+        // Desktop GL contexts have features that GLES ones lack, like `glPolygonMode`,
+        // so they are preferred where EGL can create a core profile.
+        let supports_desktop_gl = cfg!(all(
+            unix,
+            not(any(
+                target_os = "android",
+                target_os = "macos",
+                target_os = "ios"
+            ))
+        )) && !cfg!(feature = "emscripten")
+            && (version >= (1, 5) || supports_khr_context)
+            && egl
+                .query_string(Some(display), egl::CLIENT_APIS)
+                .map_or(false, |apis| {
+                    apis.to_string_lossy()
+                        .split_whitespace()
+                        .any(|api| api == "OpenGL")
+                });
+        let desktop_gl = if supports_desktop_gl {
+            create_context(
+                flags,
+                &egl,
+                display,
+                version,
+                &display_extensions,
+                srgb_kind,
+                ClientApi::DesktopGl,
+            )
+            .ok()
+            .map(|created| (created, ClientApi::DesktopGl))
+        } else {
+            None
+        };
+        let ((config, supports_native_window, context), api) = match desktop_gl {
+            Some(created) => created,
+            None => (
+                create_context(
+                    flags,
+                    &egl,
+                    display,
+                    version,
+                    &display_extensions,
+                    srgb_kind,
+                    ClientApi::Gles,
+                )?,
+                ClientApi::Gles,
+            ),
         };
 
         // Testing if context can be binded without surface
@@ -535,6 +653,7 @@ impl Inner {
                 display,
                 raw: context,
                 pbuffer,
+                api,
             },
             version,
             supports_native_window,
@@ -840,13 +959,22 @@ impl crate::Instance<super::Api> for Instance {
         let inner = self.inner.lock();
         inner.egl.make_current();
 
-        let gl = glow::Context::from_loader_function(|name| {
+        let mut loader = |name: &str| {
             inner
                 .egl
                 .instance
                 .get_proc_address(name)
                 .map_or(ptr::null(), |p| p as *const _)
-        });
+        };
+        let gl = glow::Context::from_loader_function(&mut loader);
+        let desktop =
+            super::Adapter::parse_desktop_version(&gl.get_parameter_string(glow::VERSION))
+                .is_some();
+        let timer_query_fns = super::TimerQueryFns::load(&mut loader);
+        let program_binary_fns = super::ProgramBinaryFns::load(&mut loader);
+        let multi_draw_indirect_fns = super::MultiDrawIndirectFns::load(&mut loader);
+        let polygon_mode_fns = super::PolygonModeFns::load(&mut loader, desktop);
+        let multiview_fns = super::MultiviewFns::load(&mut loader);
 
         if self.flags.contains(crate::InstanceFlags::DEBUG) && gl.supports_debug() {
             log::info!(
@@ -863,10 +991,17 @@ impl crate::Instance<super::Api> for Instance {
 
         inner.egl.unmake_current();
 
-        super::Adapter::expose(AdapterContext {
-            glow: Mutex::new(gl),
-            egl: Some(inner.egl.clone()),
-        })
+        super::Adapter::expose(
+            AdapterContext {
+                glow: Mutex::new(gl),
+                egl: Some(inner.egl.clone()),
+            },
+            timer_query_fns,
+            program_binary_fns,
+            multi_draw_indirect_fns,
+            polygon_mode_fns,
+            multiview_fns,
+        )
         .into_iter()
         .collect()
     }
@@ -874,12 +1009,27 @@ impl crate::Instance<super::Api> for Instance {
 
 impl super::Adapter {
     pub unsafe fn new_external(
-        fun: impl FnMut(&str) -> *const ffi::c_void,
+        mut fun: impl FnMut(&str) -> *const ffi::c_void,
     ) -> Option<crate::ExposedAdapter<super::Api>> {
-        Self::expose(AdapterContext {
-            glow: Mutex::new(glow::Context::from_loader_function(fun)),
-            egl: None,
-        })
+        let gl = glow::Context::from_loader_function(&mut fun);
+        let desktop =
+            Self::parse_desktop_version(&gl.get_parameter_string(glow::VERSION)).is_some();
+        let timer_query_fns = super::TimerQueryFns::load(&mut fun);
+        let program_binary_fns = super::ProgramBinaryFns::load(&mut fun);
+        let multi_draw_indirect_fns = super::MultiDrawIndirectFns::load(&mut fun);
+        let polygon_mode_fns = super::PolygonModeFns::load(&mut fun, desktop);
+        let multiview_fns = super::MultiviewFns::load(&mut fun);
+        Self::expose(
+            AdapterContext {
+                glow: Mutex::new(gl),
+                egl: None,
+            },
+            timer_query_fns,
+            program_binary_fns,
+            multi_draw_indirect_fns,
+            polygon_mode_fns,
+            multiview_fns,
+        )
     }
 }
 
@@ -919,6 +1069,7 @@ impl Surface {
     ) -> Result<(), crate::SurfaceError> {
         let sc = self.swapchain.as_ref().unwrap();
 
+        self.egl.bind_api();
         self.egl
             .instance
             .make_current(
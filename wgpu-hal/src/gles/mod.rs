@@ -54,6 +54,15 @@ To address this, we invalidate the vertex buffers based on:
   - whether or not `start_instance` is used
   - stride has changed
 
+## Multi-draw
+
+Multi-draw-indirect uses `glMultiDraw*Indirect` when `GL_EXT_multi_draw_indirect` or
+`GL_ARB_multi_draw_indirect` is available, and otherwise issues the draws one by one.
+Setting `WGPU_GL_EMULATE_MULTI_DRAW_INDIRECT=1` forces the latter, so that it can be tested.
+
+The indirect count variants need `glMultiDraw*IndirectCount`, which only desktop GL has
+(`GL_ARB_indirect_parameters`, core in 4.6), since the count can't be read on the CPU.
+
 */
 
 ///cbindgen:ignore
@@ -115,6 +124,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 bitflags::bitflags! {
@@ -138,6 +148,14 @@ bitflags::bitflags! {
         const CAN_DISABLE_DRAW_BUFFER = 1 << 6;
         /// Supports `glGetBufferSubData`
         const GET_BUFFER_SUB_DATA = 1 << 7;
+        /// Supports `glMultiDraw*Indirect`, issuing all the draws of a multi-draw at once.
+        const MULTI_DRAW_INDIRECT = 1 << 8;
+        /// Supports querying the video memory with `GL_NVX_gpu_memory_info`.
+        const MEMORY_INFO_NVX = 1 << 9;
+        /// Supports querying the free video memory with `GL_ATI_meminfo`.
+        const MEMORY_INFO_ATI = 1 << 10;
+        /// The context is a desktop GL one, not a GLES one.
+        const DESKTOP_GL = 1 << 11;
     }
 }
 
@@ -185,6 +203,377 @@ struct AdapterShared {
     shading_language_version: naga::back::glsl::Version,
     max_texture_size: u32,
     is_ext_color_buffer_float_supported: bool,
+    timer_query_fns: Option<TimerQueryFns>,
+    program_binary_fns: Option<ProgramBinaryFns>,
+    multi_draw_indirect_fns: Option<MultiDrawIndirectFns>,
+    polygon_mode_fns: Option<PolygonModeFns>,
+    multiview_fns: Option<MultiviewFns>,
+    /// Identifies the driver that produced program binaries, so that they
+    /// are not loaded by a different one. Only set with `program_binary_fns`.
+    pipeline_cache_key: Option<[u8; 16]>,
+}
+
+/// Entry points of `GL_EXT_disjoint_timer_query` / `GL_ARB_timer_query`,
+/// which are not exposed by `glow`.
+#[derive(Clone, Copy, Debug)]
+struct TimerQueryFns {
+    query_counter: unsafe extern "system" fn(u32, u32),
+    get_query_object_ui64v: unsafe extern "system" fn(u32, u32, *mut u64),
+}
+
+impl TimerQueryFns {
+    /// Resolve the entry points with the given loader, trying both the core
+    /// and the `EXT` suffixed names.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    unsafe fn load(mut loader: impl FnMut(&str) -> *const std::ffi::c_void) -> Option<Self> {
+        // The queries can't be passed to the entry points, see `raw_query`.
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+        let mut find = |names: &[&str]| {
+            names
+                .iter()
+                .map(|&name| loader(name))
+                .find(|ptr| !ptr.is_null())
+        };
+        let query_counter = find(&["glQueryCounter", "glQueryCounterEXT"])?;
+        let get_query_object_ui64v = find(&["glGetQueryObjectui64v", "glGetQueryObjectui64vEXT"])?;
+        Some(Self {
+            query_counter: std::mem::transmute(query_counter),
+            get_query_object_ui64v: std::mem::transmute(get_query_object_ui64v),
+        })
+    }
+
+    unsafe fn query_counter(&self, query: glow::Query) {
+        (self.query_counter)(Self::raw_query(query), glow::TIMESTAMP);
+    }
+
+    unsafe fn query_result(&self, query: glow::Query) -> u64 {
+        let mut result = 0;
+        (self.get_query_object_ui64v)(Self::raw_query(query), glow::QUERY_RESULT, &mut result);
+        result
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe fn raw_query(query: glow::Query) -> u32 {
+        std::mem::transmute(query)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    unsafe fn raw_query(_query: glow::Query) -> u32 {
+        unreachable!("timer queries are not exposed on WebGL")
+    }
+}
+
+/// `GL_PARAMETER_BUFFER` of `GL_ARB_indirect_parameters`, the binding of the draw count buffer.
+const PARAMETER_BUFFER: u32 = 0x80EE;
+
+/// Queries of `GL_NVX_gpu_memory_info` and `GL_ATI_meminfo`, in kilobytes.
+const GPU_MEMORY_INFO_TOTAL_AVAILABLE_MEMORY_NVX: u32 = 0x9048;
+const GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX: u32 = 0x9049;
+const TEXTURE_FREE_MEMORY_ATI: u32 = 0x87FC;
+
+type MultiDrawArraysIndirectCount =
+    unsafe extern "system" fn(u32, *const std::ffi::c_void, isize, i32, i32);
+type MultiDrawElementsIndirectCount =
+    unsafe extern "system" fn(u32, u32, *const std::ffi::c_void, isize, i32, i32);
+
+/// Entry points of `GL_EXT_multi_draw_indirect` / `GL_ARB_multi_draw_indirect`
+/// and of `GL_ARB_indirect_parameters`, which are not exposed by `glow`.
+///
+/// The indirect data is always read from the bound `GL_DRAW_INDIRECT_BUFFER`,
+/// and the draw count from the bound `GL_PARAMETER_BUFFER`.
+#[derive(Clone, Copy, Debug)]
+struct MultiDrawIndirectFns {
+    multi_draw_arrays_indirect: unsafe extern "system" fn(u32, *const std::ffi::c_void, i32, i32),
+    multi_draw_elements_indirect:
+        unsafe extern "system" fn(u32, u32, *const std::ffi::c_void, i32, i32),
+    multi_draw_arrays_indirect_count: Option<MultiDrawArraysIndirectCount>,
+    multi_draw_elements_indirect_count: Option<MultiDrawElementsIndirectCount>,
+}
+
+impl MultiDrawIndirectFns {
+    /// Resolve the entry points with the given loader, trying both the core
+    /// and the extension suffixed names.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    unsafe fn load(mut loader: impl FnMut(&str) -> *const std::ffi::c_void) -> Option<Self> {
+        let mut find = |names: &[&str]| {
+            names
+                .iter()
+                .map(|&name| loader(name))
+                .find(|ptr| !ptr.is_null())
+        };
+        let multi_draw_arrays_indirect =
+            find(&["glMultiDrawArraysIndirect", "glMultiDrawArraysIndirectEXT"])?;
+        let multi_draw_elements_indirect = find(&[
+            "glMultiDrawElementsIndirect",
+            "glMultiDrawElementsIndirectEXT",
+        ])?;
+        let multi_draw_arrays_indirect_count = find(&[
+            "glMultiDrawArraysIndirectCount",
+            "glMultiDrawArraysIndirectCountARB",
+        ]);
+        let multi_draw_elements_indirect_count = find(&[
+            "glMultiDrawElementsIndirectCount",
+            "glMultiDrawElementsIndirectCountARB",
+        ]);
+        Some(Self {
+            multi_draw_arrays_indirect: std::mem::transmute(multi_draw_arrays_indirect),
+            multi_draw_elements_indirect: std::mem::transmute(multi_draw_elements_indirect),
+            multi_draw_arrays_indirect_count: multi_draw_arrays_indirect_count
+                .map(|ptr| std::mem::transmute(ptr)),
+            multi_draw_elements_indirect_count: multi_draw_elements_indirect_count
+                .map(|ptr| std::mem::transmute(ptr)),
+        })
+    }
+
+    fn supports_count(&self) -> bool {
+        self.multi_draw_arrays_indirect_count.is_some()
+            && self.multi_draw_elements_indirect_count.is_some()
+    }
+
+    unsafe fn draw_arrays(&self, topology: u32, indirect_offset: wgt::BufferAddress, count: u32) {
+        (self.multi_draw_arrays_indirect)(
+            topology,
+            indirect_offset as usize as *const _,
+            count as i32,
+            std::mem::size_of::<wgt::DrawIndirectArgs>() as i32,
+        );
+    }
+
+    unsafe fn draw_elements(
+        &self,
+        topology: u32,
+        index_type: u32,
+        indirect_offset: wgt::BufferAddress,
+        count: u32,
+    ) {
+        (self.multi_draw_elements_indirect)(
+            topology,
+            index_type,
+            indirect_offset as usize as *const _,
+            count as i32,
+            std::mem::size_of::<wgt::DrawIndexedIndirectArgs>() as i32,
+        );
+    }
+
+    unsafe fn draw_arrays_count(
+        &self,
+        topology: u32,
+        indirect_offset: wgt::BufferAddress,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    ) {
+        (self.multi_draw_arrays_indirect_count.unwrap())(
+            topology,
+            indirect_offset as usize as *const _,
+            count_offset as isize,
+            max_count as i32,
+            std::mem::size_of::<wgt::DrawIndirectArgs>() as i32,
+        );
+    }
+
+    unsafe fn draw_elements_count(
+        &self,
+        topology: u32,
+        index_type: u32,
+        indirect_offset: wgt::BufferAddress,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    ) {
+        (self.multi_draw_elements_indirect_count.unwrap())(
+            topology,
+            index_type,
+            indirect_offset as usize as *const _,
+            count_offset as isize,
+            max_count as i32,
+            std::mem::size_of::<wgt::DrawIndexedIndirectArgs>() as i32,
+        );
+    }
+}
+
+/// Entry point of `glPolygonMode` on desktop GL, or of `GL_NV_polygon_mode` /
+/// `GL_ANGLE_polygon_mode` on GLES, which is not exposed by `glow` for GLES contexts.
+#[derive(Clone, Copy, Debug)]
+struct PolygonModeFns {
+    polygon_mode: unsafe extern "system" fn(u32, u32),
+}
+
+impl PolygonModeFns {
+    /// Resolve the entry point with the given loader: the core one for desktop GL contexts,
+    /// and the extension ones otherwise.
+    ///
+    /// Loaders may return stubs for any name, so the core entry point is never
+    /// tried on GLES, nor the extension ones on desktop GL.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    unsafe fn load(
+        mut loader: impl FnMut(&str) -> *const std::ffi::c_void,
+        desktop: bool,
+    ) -> Option<Self> {
+        let names: &[&str] = if desktop {
+            &["glPolygonMode"]
+        } else {
+            &["glPolygonModeNV", "glPolygonModeANGLE"]
+        };
+        let polygon_mode = names
+            .iter()
+            .map(|&name| loader(name))
+            .find(|ptr| !ptr.is_null())?;
+        Some(Self {
+            polygon_mode: std::mem::transmute(polygon_mode),
+        })
+    }
+
+    unsafe fn set(&self, mode: u32) {
+        (self.polygon_mode)(glow::FRONT_AND_BACK, mode);
+    }
+}
+
+/// Entry point of `GL_OVR_multiview`, which is not exposed by `glow`.
+#[derive(Clone, Copy, Debug)]
+struct MultiviewFns {
+    framebuffer_texture_multiview: unsafe extern "system" fn(u32, u32, u32, i32, i32, i32),
+}
+
+impl MultiviewFns {
+    /// Resolve the entry point with the given loader.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    unsafe fn load(mut loader: impl FnMut(&str) -> *const std::ffi::c_void) -> Option<Self> {
+        // The textures can't be passed to the entry point, see `raw_texture`.
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+        let framebuffer_texture_multiview = loader("glFramebufferTextureMultiviewOVR");
+        if framebuffer_texture_multiview.is_null() {
+            return None;
+        }
+        Some(Self {
+            framebuffer_texture_multiview: std::mem::transmute(framebuffer_texture_multiview),
+        })
+    }
+
+    /// Attaches `views` layers of the texture, starting at `base_layer`.
+    unsafe fn framebuffer_texture(
+        &self,
+        fbo_target: u32,
+        attachment: u32,
+        texture: glow::Texture,
+        level: u32,
+        base_layer: u32,
+        views: u32,
+    ) {
+        (self.framebuffer_texture_multiview)(
+            fbo_target,
+            attachment,
+            Self::raw_texture(texture),
+            level as i32,
+            base_layer as i32,
+            views as i32,
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe fn raw_texture(texture: glow::Texture) -> u32 {
+        std::mem::transmute(texture)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    unsafe fn raw_texture(_texture: glow::Texture) -> u32 {
+        unreachable!("multiview is not exposed on WebGL")
+    }
+}
+
+/// Entry points of `GL_ARB_get_program_binary` / GLES 3.0 program binaries,
+/// which are not exposed by `glow`.
+#[derive(Clone, Copy, Debug)]
+struct ProgramBinaryFns {
+    program_parameter_i: unsafe extern "system" fn(u32, u32, i32),
+    get_program_iv: unsafe extern "system" fn(u32, u32, *mut i32),
+    get_program_binary:
+        unsafe extern "system" fn(u32, i32, *mut i32, *mut u32, *mut std::ffi::c_void),
+    program_binary: unsafe extern "system" fn(u32, u32, *const std::ffi::c_void, i32),
+}
+
+impl ProgramBinaryFns {
+    /// Resolve the entry points with the given loader, trying both the core
+    /// and the `OES` suffixed names.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    unsafe fn load(mut loader: impl FnMut(&str) -> *const std::ffi::c_void) -> Option<Self> {
+        // The programs can't be passed to the entry points, see `raw_program`.
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+        let mut find = |names: &[&str]| {
+            names
+                .iter()
+                .map(|&name| loader(name))
+                .find(|ptr| !ptr.is_null())
+        };
+        let program_parameter_i = find(&["glProgramParameteri"])?;
+        let get_program_iv = find(&["glGetProgramiv"])?;
+        let get_program_binary = find(&["glGetProgramBinary", "glGetProgramBinaryOES"])?;
+        let program_binary = find(&["glProgramBinary", "glProgramBinaryOES"])?;
+        Some(Self {
+            program_parameter_i: std::mem::transmute(program_parameter_i),
+            get_program_iv: std::mem::transmute(get_program_iv),
+            get_program_binary: std::mem::transmute(get_program_binary),
+            program_binary: std::mem::transmute(program_binary),
+        })
+    }
+
+    /// Must be called before linking for the binary to be retrievable.
+    unsafe fn set_retrievable(&self, program: glow::Program) {
+        (self.program_parameter_i)(
+            Self::raw_program(program),
+            glow::PROGRAM_BINARY_RETRIEVABLE_HINT,
+            glow::TRUE as i32,
+        );
+    }
+
+    unsafe fn get(&self, program: glow::Program) -> Option<ProgramBinary> {
+        let raw = Self::raw_program(program);
+        let mut length = 0;
+        (self.get_program_iv)(raw, glow::PROGRAM_BINARY_LENGTH, &mut length);
+        if length <= 0 {
+            return None;
+        }
+        let mut data = vec![0u8; length as usize];
+        let mut format = 0;
+        (self.get_program_binary)(
+            raw,
+            length,
+            &mut length,
+            &mut format,
+            data.as_mut_ptr() as *mut _,
+        );
+        data.truncate(length.max(0) as usize);
+        if data.is_empty() {
+            None
+        } else {
+            Some(ProgramBinary { format, data })
+        }
+    }
+
+    /// Loads the binary into the program. The link status of the program
+    /// has to be checked afterwards, since drivers may reject the binary.
+    unsafe fn load_binary(&self, program: glow::Program, binary: &ProgramBinary) {
+        (self.program_binary)(
+            Self::raw_program(program),
+            binary.format,
+            binary.data.as_ptr() as *const _,
+            binary.data.len() as i32,
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe fn raw_program(program: glow::Program) -> u32 {
+        std::mem::transmute(program)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    unsafe fn raw_program(_program: glow::Program) -> u32 {
+        unreachable!("program binaries are not exposed on WebGL")
+    }
 }
 
 pub struct Adapter {
@@ -475,6 +864,72 @@ unsafe impl Send for ComputePipeline {}
 #[cfg(target_arch = "wasm32")]
 unsafe impl Sync for ComputePipeline {}
 
+#[derive(Debug)]
+struct ProgramBinary {
+    format: u32,
+    data: Vec<u8>,
+}
+
+/// Linked program binaries, keyed by the GLSL sources they were built from.
+///
+/// The whole sources are kept, since a hash collision would link a program
+/// of different shaders.
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    programs: parking_lot::Mutex<fxhash::FxHashMap<String, ProgramBinary>>,
+}
+
+impl PipelineCache {
+    /// Parses data previously produced by [`PipelineCache::to_data`].
+    /// Returns `None` if the data is malformed.
+    fn from_data(mut data: &[u8]) -> Option<Self> {
+        fn take<'a>(data: &mut &'a [u8], size: usize) -> Option<&'a [u8]> {
+            if data.len() < size {
+                return None;
+            }
+            let (head, tail) = data.split_at(size);
+            *data = tail;
+            Some(head)
+        }
+
+        let mut programs = fxhash::FxHashMap::default();
+        while !data.is_empty() {
+            let mut key_size = [0; 4];
+            key_size.copy_from_slice(take(&mut data, 4)?);
+            let key = take(&mut data, u32::from_le_bytes(key_size) as usize)?;
+            let key = String::from_utf8(key.to_vec()).ok()?;
+            let mut format = [0; 4];
+            format.copy_from_slice(take(&mut data, 4)?);
+            let mut size = [0; 4];
+            size.copy_from_slice(take(&mut data, 4)?);
+            let binary = take(&mut data, u32::from_le_bytes(size) as usize)?;
+            programs.insert(
+                key,
+                ProgramBinary {
+                    format: u32::from_le_bytes(format),
+                    data: binary.to_vec(),
+                },
+            );
+        }
+        Some(Self {
+            programs: parking_lot::Mutex::new(programs),
+        })
+    }
+
+    fn to_data(&self) -> Vec<u8> {
+        let programs = self.programs.lock();
+        let mut data = Vec::new();
+        for (key, binary) in programs.iter() {
+            data.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            data.extend_from_slice(key.as_bytes());
+            data.extend_from_slice(&binary.format.to_le_bytes());
+            data.extend_from_slice(&(binary.data.len() as u32).to_le_bytes());
+            data.extend_from_slice(&binary.data);
+        }
+        data
+    }
+}
+
 #[derive(Debug)]
 pub struct QuerySet {
     queries: Box<[glow::Query]>,
@@ -565,6 +1020,7 @@ struct PrimitiveState {
     front_face: u32,
     cull_face: u32,
     unclipped_depth: bool,
+    polygon_mode: u32,
 }
 
 type InvalidatedAttachments = ArrayVec<u32, { crate::MAX_COLOR_ATTACHMENTS + 2 }>;
@@ -596,6 +1052,36 @@ enum Command {
         indirect_buf: glow::Buffer,
         indirect_offset: wgt::BufferAddress,
     },
+    MultiDrawIndirect {
+        topology: u32,
+        indirect_buf: glow::Buffer,
+        indirect_offset: wgt::BufferAddress,
+        draw_count: u32,
+    },
+    MultiDrawIndexedIndirect {
+        topology: u32,
+        index_type: u32,
+        indirect_buf: glow::Buffer,
+        indirect_offset: wgt::BufferAddress,
+        draw_count: u32,
+    },
+    MultiDrawIndirectCount {
+        topology: u32,
+        indirect_buf: glow::Buffer,
+        indirect_offset: wgt::BufferAddress,
+        count_buf: glow::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    },
+    MultiDrawIndexedIndirectCount {
+        topology: u32,
+        index_type: u32,
+        indirect_buf: glow::Buffer,
+        indirect_offset: wgt::BufferAddress,
+        count_buf: glow::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    },
     Dispatch([u32; 3]),
     DispatchIndirect {
         indirect_buf: glow::Buffer,
@@ -642,8 +1128,10 @@ enum Command {
     SetIndexBuffer(glow::Buffer),
     BeginQuery(glow::Query, BindTarget),
     EndQuery(BindTarget),
+    TimestampQuery(glow::Query),
     CopyQueryResults {
         query_range: Range<u32>,
+        query_target: BindTarget,
         dst: Buffer,
         dst_target: BindTarget,
         dst_offset: wgt::BufferAddress,
@@ -654,6 +1142,8 @@ enum Command {
     BindAttachment {
         attachment: u32,
         view: TextureView,
+        /// Number of views of a multiview render pass, starting at the first layer of the view.
+        multiview: Option<std::num::NonZeroU32>,
     },
     ResolveAttachment {
         attachment: u32,
@@ -759,3 +1249,41 @@ pub struct CommandEncoder {
     state: command::State,
     private_caps: PrivateCapabilities,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PipelineCache, ProgramBinary};
+
+    #[test]
+    fn pipeline_cache_data() {
+        let cache = PipelineCache::default();
+        {
+            let mut programs = cache.programs.lock();
+            programs.insert(
+                "vertex\0fragment".to_string(),
+                ProgramBinary {
+                    format: 1,
+                    data: vec![1, 2, 3],
+                },
+            );
+            programs.insert(
+                "vertex\0other fragment".to_string(),
+                ProgramBinary {
+                    format: 2,
+                    data: vec![4],
+                },
+            );
+        }
+        let data = cache.to_data();
+
+        let loaded = PipelineCache::from_data(&data).unwrap();
+        let programs = loaded.programs.lock();
+        assert_eq!(programs.len(), 2);
+        let binary = &programs["vertex\0fragment"];
+        assert_eq!((binary.format, binary.data.as_slice()), (1, &[1, 2, 3][..]));
+        let binary = &programs["vertex\0other fragment"];
+        assert_eq!((binary.format, binary.data.as_slice()), (2, &[4][..]));
+
+        assert!(PipelineCache::from_data(&data[..data.len() - 1]).is_none());
+    }
+}
@@ -197,6 +197,60 @@ impl super::Queue {
                 gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(indirect_buf));
                 gl.draw_elements_indirect_offset(topology, index_type, indirect_offset as i32);
             }
+            C::MultiDrawIndirect {
+                topology,
+                indirect_buf,
+                indirect_offset,
+                draw_count,
+            } => {
+                let fns = self.shared.multi_draw_indirect_fns.as_ref().unwrap();
+                gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(indirect_buf));
+                fns.draw_arrays(topology, indirect_offset, draw_count);
+            }
+            C::MultiDrawIndexedIndirect {
+                topology,
+                index_type,
+                indirect_buf,
+                indirect_offset,
+                draw_count,
+            } => {
+                let fns = self.shared.multi_draw_indirect_fns.as_ref().unwrap();
+                gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(indirect_buf));
+                fns.draw_elements(topology, index_type, indirect_offset, draw_count);
+            }
+            C::MultiDrawIndirectCount {
+                topology,
+                indirect_buf,
+                indirect_offset,
+                count_buf,
+                count_offset,
+                max_count,
+            } => {
+                let fns = self.shared.multi_draw_indirect_fns.as_ref().unwrap();
+                gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(indirect_buf));
+                gl.bind_buffer(super::PARAMETER_BUFFER, Some(count_buf));
+                fns.draw_arrays_count(topology, indirect_offset, count_offset, max_count);
+            }
+            C::MultiDrawIndexedIndirectCount {
+                topology,
+                index_type,
+                indirect_buf,
+                indirect_offset,
+                count_buf,
+                count_offset,
+                max_count,
+            } => {
+                let fns = self.shared.multi_draw_indirect_fns.as_ref().unwrap();
+                gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(indirect_buf));
+                gl.bind_buffer(super::PARAMETER_BUFFER, Some(count_buf));
+                fns.draw_elements_count(
+                    topology,
+                    index_type,
+                    indirect_offset,
+                    count_offset,
+                    max_count,
+                );
+            }
             C::Dispatch(group_counts) => {
                 gl.dispatch_compute(group_counts[0], group_counts[1], group_counts[2]);
             }
@@ -654,16 +708,29 @@ impl super::Queue {
             C::EndQuery(target) => {
                 gl.end_query(target);
             }
+            C::TimestampQuery(query) => {
+                let timer_query_fns = self.shared.timer_query_fns.as_ref().unwrap();
+                timer_query_fns.query_counter(query);
+            }
             C::CopyQueryResults {
                 ref query_range,
+                query_target,
                 ref dst,
                 dst_target,
                 dst_offset,
             } => {
                 self.temp_query_results.clear();
                 for &query in queries[query_range.start as usize..query_range.end as usize].iter() {
-                    let result = gl.get_query_parameter_u32(query, glow::QUERY_RESULT);
-                    self.temp_query_results.push(result as u64);
+                    // Timestamps are 64-bit nanosecond values, which would be
+                    // truncated by `glGetQueryObjectuiv`.
+                    let result = match query_target {
+                        glow::TIMESTAMP => {
+                            let timer_query_fns = self.shared.timer_query_fns.as_ref().unwrap();
+                            timer_query_fns.query_result(query)
+                        }
+                        _ => gl.get_query_parameter_u32(query, glow::QUERY_RESULT) as u64,
+                    };
+                    self.temp_query_results.push(result);
                 }
                 let query_data = slice::from_raw_parts(
                     self.temp_query_results.as_ptr() as *const u8,
@@ -714,9 +781,21 @@ impl super::Queue {
             C::BindAttachment {
                 attachment,
                 ref view,
-            } => {
-                self.set_attachment(gl, glow::DRAW_FRAMEBUFFER, attachment, view);
-            }
+                multiview,
+            } => match (multiview, &view.inner) {
+                (Some(views), &super::TextureInner::Texture { raw, .. }) => {
+                    let fns = self.shared.multiview_fns.as_ref().unwrap();
+                    fns.framebuffer_texture(
+                        glow::DRAW_FRAMEBUFFER,
+                        attachment,
+                        raw,
+                        view.mip_levels.start,
+                        view.array_layers.start,
+                        views.get(),
+                    );
+                }
+                _ => self.set_attachment(gl, glow::DRAW_FRAMEBUFFER, attachment, view),
+            },
             C::ResolveAttachment {
                 attachment,
                 ref dst,
@@ -985,6 +1064,9 @@ impl super::Queue {
                         gl.disable(glow::DEPTH_CLAMP);
                     }
                 }
+                if let Some(ref fns) = self.shared.polygon_mode_fns {
+                    fns.set(state.polygon_mode);
+                }
             }
             C::SetBlendConstant(c) => {
                 gl.blend_color(c[0], c[1], c[2], c[3]);
@@ -101,9 +101,16 @@ impl crate::Instance<super::Api> for Instance {
             None => return Vec::new(),
         };
 
-        super::Adapter::expose(AdapterContext { glow_context: gl })
-            .into_iter()
-            .collect()
+        super::Adapter::expose(
+            AdapterContext { glow_context: gl },
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .into_iter()
+        .collect()
     }
 
     unsafe fn create_surface(
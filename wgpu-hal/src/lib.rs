@@ -54,6 +54,11 @@ compile_error!("DX12 API enabled on non-Windows OS. If your project is not using
 
 #[cfg(all(feature = "dx11", windows))]
 mod dx11;
+// The D3D11 conversions don't need `winapi`, test them on every platform.
+#[cfg(all(feature = "dx11", not(windows), test))]
+#[allow(dead_code)]
+#[path = "dx11/conv.rs"]
+mod dx11_conv;
 #[cfg(all(feature = "dx12", windows))]
 mod dx12;
 mod empty;
@@ -61,12 +66,14 @@ mod empty;
 mod gles;
 #[cfg(all(feature = "metal"))]
 mod metal;
+#[cfg(feature = "software")]
+mod software;
 #[cfg(feature = "vulkan")]
 mod vulkan;
 
 pub mod auxil;
 pub mod api {
-    #[cfg(feature = "dx11")]
+    #[cfg(all(feature = "dx11", windows))]
     pub use super::dx11::Api as Dx11;
     #[cfg(feature = "dx12")]
     pub use super::dx12::Api as Dx12;
@@ -75,6 +82,8 @@ pub mod api {
     pub use super::gles::Api as Gles;
     #[cfg(feature = "metal")]
     pub use super::metal::Api as Metal;
+    #[cfg(feature = "software")]
+    pub use super::software::Api as Software;
     #[cfg(feature = "vulkan")]
     pub use super::vulkan::Api as Vulkan;
 }
@@ -172,6 +181,7 @@ pub trait Api: Clone + Sized {
     type ShaderModule: fmt::Debug + Send + Sync;
     type RenderPipeline: Send + Sync;
     type ComputePipeline: Send + Sync;
+    type PipelineCache: fmt::Debug + Send + Sync;
 }
 
 pub trait Instance<A: Api>: Sized + Send + Sync {
@@ -305,6 +315,27 @@ pub trait Device<A: Api>: Send + Sync {
     ) -> Result<A::ComputePipeline, PipelineError>;
     unsafe fn destroy_compute_pipeline(&self, pipeline: A::ComputePipeline);
 
+    /// Creates a pipeline cache, optionally seeded with data previously
+    /// returned by [`Device::pipeline_cache_get_data`].
+    ///
+    /// The data is passed to the driver as is, the caller is responsible
+    /// for making sure it was produced by a compatible device, see
+    /// [`Device::pipeline_cache_validation_key`].
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &PipelineCacheDescriptor,
+    ) -> Result<A::PipelineCache, DeviceError>;
+    /// Returns a key identifying the driver that produced the cache data.
+    /// Data with a different key must not be passed to `create_pipeline_cache`.
+    ///
+    /// Returns `None` if the backend doesn't support pipeline caches.
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        None
+    }
+    /// Serializes the contents of the cache, if the backend supports it.
+    unsafe fn pipeline_cache_get_data(&self, cache: &A::PipelineCache) -> Option<Vec<u8>>;
+    unsafe fn destroy_pipeline_cache(&self, cache: A::PipelineCache);
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<Label>,
@@ -323,6 +354,14 @@ pub trait Device<A: Api>: Send + Sync {
 
     unsafe fn start_capture(&self) -> bool;
     unsafe fn stop_capture(&self);
+
+    /// Returns the usage of the device memory heaps and the allocator statistics,
+    /// as far as the backend can tell.
+    ///
+    /// The resource breakdown is left empty, it's filled by the caller.
+    fn memory_report(&self) -> wgt::MemoryReport {
+        wgt::MemoryReport::default()
+    }
 }
 
 pub trait Queue<A: Api>: Send + Sync {
@@ -964,6 +1003,10 @@ pub struct ShaderModuleDescriptor<'a> {
     pub runtime_checks: bool,
 }
 
+/// Values of the pipeline-overridable constants of a stage, keyed by either
+/// the numeric ID or the name of the constant.
+pub type PipelineConstants = std::collections::HashMap<String, f64>;
+
 /// Describes a programmable pipeline stage.
 #[derive(Debug)]
 pub struct ProgrammableStage<'a, A: Api> {
@@ -972,6 +1015,9 @@ pub struct ProgrammableStage<'a, A: Api> {
     /// The name of the entry point in the compiled shader. There must be a function that returns
     /// void with this name in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the module.
+    /// The keys are expected to match constants of the module.
+    pub constants: &'a PipelineConstants,
 }
 
 // Rust gets confused about the impl requirements for `A`
@@ -980,10 +1026,19 @@ impl<A: Api> Clone for ProgrammableStage<'_, A> {
         Self {
             module: self.module,
             entry_point: self.entry_point,
+            constants: self.constants,
         }
     }
 }
 
+/// Describes a pipeline cache.
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// Data previously returned by [`Device::pipeline_cache_get_data`].
+    pub data: Option<&'a [u8]>,
+}
+
 /// Describes a compute pipeline.
 #[derive(Clone, Debug)]
 pub struct ComputePipelineDescriptor<'a, A: Api> {
@@ -992,6 +1047,8 @@ pub struct ComputePipelineDescriptor<'a, A: Api> {
     pub layout: &'a A::PipelineLayout,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStage<'a, A>,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<&'a A::PipelineCache>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -1028,6 +1085,8 @@ pub struct RenderPipelineDescriptor<'a, A: Api> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<&'a A::PipelineCache>,
 }
 
 /// Specifies how the alpha channel of the textures should be handled during (martin mouv i step)
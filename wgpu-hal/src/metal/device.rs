@@ -1,3 +1,8 @@
+use objc::{
+    msg_send,
+    runtime::{BOOL, YES},
+    sel, sel_impl,
+};
 use parking_lot::Mutex;
 use std::{
     num::NonZeroU32,
@@ -65,9 +70,10 @@ impl super::Device {
             },
         };
 
-        let module = &stage.module.naga.module;
+        let module =
+            crate::auxil::apply_pipeline_constants(&stage.module.naga.module, stage.constants);
         let (source, info) = naga::back::msl::write_string(
-            module,
+            &module,
             &stage.module.naga.info,
             &layout.naga_options,
             &pipeline_options,
@@ -1023,6 +1029,17 @@ impl crate::Device<super::Api> for super::Device {
     }
     unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> DeviceResult<super::PipelineCache> {
+        Ok(super::PipelineCache)
+    }
+    unsafe fn pipeline_cache_get_data(&self, _cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        None
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1117,4 +1134,39 @@ impl crate::Device<super::Api> for super::Device {
         }
         shared_capture_manager.stop_capture();
     }
+
+    fn memory_report(&self) -> wgt::MemoryReport {
+        let device = self.shared.device.lock();
+        let raw = device.as_ref();
+        let responds = |selector| -> bool {
+            let responds: BOOL = unsafe { msg_send![raw, respondsToSelector: selector] };
+            responds == YES
+        };
+
+        // Metal doesn't expose its heaps, the whole device is reported as one heap
+        // with the recommended working set as the budget.
+        let budget = if responds(sel!(recommendedMaxWorkingSetSize)) {
+            let size: u64 = unsafe { msg_send![raw, recommendedMaxWorkingSetSize] };
+            Some(size)
+        } else {
+            None
+        };
+        let usage = if responds(sel!(currentAllocatedSize)) {
+            let size: usize = unsafe { msg_send![raw, currentAllocatedSize] };
+            size as u64
+        } else {
+            0
+        };
+
+        wgt::MemoryReport {
+            heaps: vec![wgt::MemoryHeapReport {
+                size: budget.unwrap_or(0),
+                device_local: true,
+                usage,
+                budget,
+            }],
+            allocator: None,
+            resources: wgt::ResourceMemoryReport::default(),
+        }
+    }
 }
@@ -0,0 +1,479 @@
+use super::{
+    raster::{BufferSlice, Pipeline},
+    shader::Stage,
+    Api, BindGroup, Memory, TextureInner, TextureView,
+};
+
+use parking_lot::Mutex;
+use std::{ops::Range, sync::Arc};
+
+pub(super) type QueryResults = Arc<Mutex<Vec<u64>>>;
+
+#[derive(Debug)]
+pub(super) struct ColorTarget {
+    pub view: TextureView,
+    pub resolve: Option<TextureView>,
+    pub clear: Option<wgt::Color>,
+}
+
+#[derive(Debug)]
+pub(super) struct DepthStencilTarget {
+    pub view: TextureView,
+    pub clear_depth: Option<f32>,
+    pub clear_stencil: Option<u32>,
+}
+
+/// Recorded command, owning references to all the resources it uses.
+pub(super) enum Command {
+    ClearBuffer {
+        dst: Arc<Memory>,
+        range: crate::MemoryRange,
+    },
+    CopyBufferToBuffer {
+        src: Arc<Memory>,
+        dst: Arc<Memory>,
+        regions: Vec<crate::BufferCopy>,
+    },
+    CopyTextureToTexture {
+        src: Arc<TextureInner>,
+        dst: Arc<TextureInner>,
+        regions: Vec<crate::TextureCopy>,
+    },
+    CopyBufferToTexture {
+        src: Arc<Memory>,
+        dst: Arc<TextureInner>,
+        regions: Vec<crate::BufferTextureCopy>,
+    },
+    CopyTextureToBuffer {
+        src: Arc<TextureInner>,
+        dst: Arc<Memory>,
+        regions: Vec<crate::BufferTextureCopy>,
+    },
+    BeginQuery {
+        results: QueryResults,
+        index: u32,
+    },
+    EndQuery {
+        results: QueryResults,
+        index: u32,
+    },
+    WriteTimestamp {
+        results: QueryResults,
+        index: u32,
+    },
+    ResetQueries {
+        results: QueryResults,
+        range: Range<u32>,
+    },
+    CopyQueryResults {
+        results: QueryResults,
+        range: Range<u32>,
+        dst: Arc<Memory>,
+        offset: wgt::BufferAddress,
+        stride: wgt::BufferSize,
+    },
+    BeginRenderPass {
+        colors: Vec<ColorTarget>,
+        depth_stencil: Option<DepthStencilTarget>,
+        extent: wgt::Extent3d,
+    },
+    EndRenderPass,
+    SetBindGroup {
+        index: u32,
+        group: BindGroup,
+        dynamic_offsets: Vec<wgt::DynamicOffset>,
+    },
+    SetPushConstants {
+        offset: u32,
+        data: Vec<u32>,
+    },
+    SetRenderPipeline(Arc<Pipeline>),
+    SetIndexBuffer(BufferSlice, wgt::IndexFormat),
+    SetVertexBuffer(u32, BufferSlice),
+    SetViewport(crate::Rect<f32>, Range<f32>),
+    SetScissor(crate::Rect<u32>),
+    SetStencilReference(u32),
+    SetBlendConstants([f32; 4]),
+    Draw {
+        vertices: Range<u32>,
+        instances: Range<u32>,
+    },
+    DrawIndexed {
+        indices: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    },
+    DrawIndirect {
+        buffer: BufferSlice,
+        indexed: bool,
+        count: u32,
+    },
+    DrawIndirectCount {
+        buffer: BufferSlice,
+        indexed: bool,
+        count_buffer: BufferSlice,
+        max_count: u32,
+    },
+    SetComputePipeline(Arc<Stage>),
+    Dispatch([u32; 3]),
+    DispatchIndirect(BufferSlice),
+}
+
+impl super::CommandEncoder {
+    fn buffer_slice(buffer: &super::Buffer, offset: wgt::BufferAddress) -> BufferSlice {
+        BufferSlice {
+            memory: Arc::clone(&buffer.memory),
+            offset,
+        }
+    }
+}
+
+impl crate::CommandEncoder<Api> for super::CommandEncoder {
+    unsafe fn begin_encoding(&mut self, _label: crate::Label) -> Result<(), crate::DeviceError> {
+        self.commands.clear();
+        Ok(())
+    }
+    unsafe fn discard_encoding(&mut self) {
+        self.commands.clear();
+    }
+    unsafe fn end_encoding(&mut self) -> Result<super::CommandBuffer, crate::DeviceError> {
+        Ok(super::CommandBuffer {
+            commands: std::mem::take(&mut self.commands),
+        })
+    }
+    unsafe fn reset_all<I>(&mut self, _command_buffers: I)
+    where
+        I: Iterator<Item = super::CommandBuffer>,
+    {
+    }
+
+    unsafe fn transition_buffers<'a, T>(&mut self, _barriers: T)
+    where
+        T: Iterator<Item = crate::BufferBarrier<'a, Api>>,
+    {
+    }
+
+    unsafe fn transition_textures<'a, T>(&mut self, _barriers: T)
+    where
+        T: Iterator<Item = crate::TextureBarrier<'a, Api>>,
+    {
+    }
+
+    unsafe fn clear_buffer(&mut self, buffer: &super::Buffer, range: crate::MemoryRange) {
+        self.commands.push(Command::ClearBuffer {
+            dst: Arc::clone(&buffer.memory),
+            range,
+        });
+    }
+
+    unsafe fn copy_buffer_to_buffer<T>(
+        &mut self,
+        src: &super::Buffer,
+        dst: &super::Buffer,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::BufferCopy>,
+    {
+        self.commands.push(Command::CopyBufferToBuffer {
+            src: Arc::clone(&src.memory),
+            dst: Arc::clone(&dst.memory),
+            regions: regions.collect(),
+        });
+    }
+
+    unsafe fn copy_texture_to_texture<T>(
+        &mut self,
+        src: &super::Texture,
+        _src_usage: crate::TextureUses,
+        dst: &super::Texture,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::TextureCopy>,
+    {
+        self.commands.push(Command::CopyTextureToTexture {
+            src: Arc::clone(&src.inner),
+            dst: Arc::clone(&dst.inner),
+            regions: regions.collect(),
+        });
+    }
+
+    unsafe fn copy_buffer_to_texture<T>(
+        &mut self,
+        src: &super::Buffer,
+        dst: &super::Texture,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::BufferTextureCopy>,
+    {
+        self.commands.push(Command::CopyBufferToTexture {
+            src: Arc::clone(&src.memory),
+            dst: Arc::clone(&dst.inner),
+            regions: regions.collect(),
+        });
+    }
+
+    unsafe fn copy_texture_to_buffer<T>(
+        &mut self,
+        src: &super::Texture,
+        _src_usage: crate::TextureUses,
+        dst: &super::Buffer,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::BufferTextureCopy>,
+    {
+        self.commands.push(Command::CopyTextureToBuffer {
+            src: Arc::clone(&src.inner),
+            dst: Arc::clone(&dst.memory),
+            regions: regions.collect(),
+        });
+    }
+
+    unsafe fn begin_query(&mut self, set: &super::QuerySet, index: u32) {
+        self.commands.push(Command::BeginQuery {
+            results: Arc::clone(&set.results),
+            index,
+        });
+    }
+    unsafe fn end_query(&mut self, set: &super::QuerySet, index: u32) {
+        self.commands.push(Command::EndQuery {
+            results: Arc::clone(&set.results),
+            index,
+        });
+    }
+    unsafe fn write_timestamp(&mut self, set: &super::QuerySet, index: u32) {
+        self.commands.push(Command::WriteTimestamp {
+            results: Arc::clone(&set.results),
+            index,
+        });
+    }
+    unsafe fn reset_queries(&mut self, set: &super::QuerySet, range: Range<u32>) {
+        self.commands.push(Command::ResetQueries {
+            results: Arc::clone(&set.results),
+            range,
+        });
+    }
+    unsafe fn copy_query_results(
+        &mut self,
+        set: &super::QuerySet,
+        range: Range<u32>,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        stride: wgt::BufferSize,
+    ) {
+        self.commands.push(Command::CopyQueryResults {
+            results: Arc::clone(&set.results),
+            range,
+            dst: Arc::clone(&buffer.memory),
+            offset,
+            stride,
+        });
+    }
+
+    // render
+
+    unsafe fn begin_render_pass(&mut self, desc: &crate::RenderPassDescriptor<Api>) {
+        let colors = desc
+            .color_attachments
+            .iter()
+            .map(|at| ColorTarget {
+                view: at.target.view.clone(),
+                resolve: at.resolve_target.as_ref().map(|rat| rat.view.clone()),
+                clear: if at.ops.contains(crate::AttachmentOps::LOAD) {
+                    None
+                } else {
+                    Some(at.clear_value)
+                },
+            })
+            .collect();
+        let depth_stencil = desc
+            .depth_stencil_attachment
+            .as_ref()
+            .map(|dsat| DepthStencilTarget {
+                view: dsat.target.view.clone(),
+                clear_depth: if dsat.depth_ops.contains(crate::AttachmentOps::LOAD) {
+                    None
+                } else {
+                    Some(dsat.clear_value.0)
+                },
+                clear_stencil: if dsat.stencil_ops.contains(crate::AttachmentOps::LOAD) {
+                    None
+                } else {
+                    Some(dsat.clear_value.1)
+                },
+            });
+        self.commands.push(Command::BeginRenderPass {
+            colors,
+            depth_stencil,
+            extent: desc.extent,
+        });
+    }
+    unsafe fn end_render_pass(&mut self) {
+        self.commands.push(Command::EndRenderPass);
+    }
+
+    unsafe fn set_bind_group(
+        &mut self,
+        _layout: &super::PipelineLayout,
+        index: u32,
+        group: &super::BindGroup,
+        dynamic_offsets: &[wgt::DynamicOffset],
+    ) {
+        self.commands.push(Command::SetBindGroup {
+            index,
+            group: group.clone(),
+            dynamic_offsets: dynamic_offsets.to_vec(),
+        });
+    }
+    unsafe fn set_push_constants(
+        &mut self,
+        _layout: &super::PipelineLayout,
+        _stages: wgt::ShaderStages,
+        offset: u32,
+        data: &[u32],
+    ) {
+        self.commands.push(Command::SetPushConstants {
+            offset,
+            data: data.to_vec(),
+        });
+    }
+
+    unsafe fn insert_debug_marker(&mut self, _label: &str) {}
+    unsafe fn begin_debug_marker(&mut self, _group_label: &str) {}
+    unsafe fn end_debug_marker(&mut self) {}
+
+    unsafe fn set_render_pipeline(&mut self, pipeline: &super::RenderPipeline) {
+        self.commands
+            .push(Command::SetRenderPipeline(Arc::clone(&pipeline.inner)));
+    }
+
+    unsafe fn set_index_buffer<'a>(
+        &mut self,
+        binding: crate::BufferBinding<'a, Api>,
+        format: wgt::IndexFormat,
+    ) {
+        self.commands.push(Command::SetIndexBuffer(
+            Self::buffer_slice(binding.buffer, binding.offset),
+            format,
+        ));
+    }
+    unsafe fn set_vertex_buffer<'a>(&mut self, index: u32, binding: crate::BufferBinding<'a, Api>) {
+        self.commands.push(Command::SetVertexBuffer(
+            index,
+            Self::buffer_slice(binding.buffer, binding.offset),
+        ));
+    }
+    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: Range<f32>) {
+        self.commands
+            .push(Command::SetViewport(rect.clone(), depth_range));
+    }
+    unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {
+        self.commands.push(Command::SetScissor(rect.clone()));
+    }
+    unsafe fn set_stencil_reference(&mut self, value: u32) {
+        self.commands.push(Command::SetStencilReference(value));
+    }
+    unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {
+        self.commands.push(Command::SetBlendConstants(*color));
+    }
+
+    unsafe fn draw(
+        &mut self,
+        start_vertex: u32,
+        vertex_count: u32,
+        start_instance: u32,
+        instance_count: u32,
+    ) {
+        self.commands.push(Command::Draw {
+            vertices: start_vertex..start_vertex + vertex_count,
+            instances: start_instance..start_instance + instance_count,
+        });
+    }
+    unsafe fn draw_indexed(
+        &mut self,
+        start_index: u32,
+        index_count: u32,
+        base_vertex: i32,
+        start_instance: u32,
+        instance_count: u32,
+    ) {
+        self.commands.push(Command::DrawIndexed {
+            indices: start_index..start_index + index_count,
+            base_vertex,
+            instances: start_instance..start_instance + instance_count,
+        });
+    }
+    unsafe fn draw_indirect(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        draw_count: u32,
+    ) {
+        self.commands.push(Command::DrawIndirect {
+            buffer: Self::buffer_slice(buffer, offset),
+            indexed: false,
+            count: draw_count,
+        });
+    }
+    unsafe fn draw_indexed_indirect(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        draw_count: u32,
+    ) {
+        self.commands.push(Command::DrawIndirect {
+            buffer: Self::buffer_slice(buffer, offset),
+            indexed: true,
+            count: draw_count,
+        });
+    }
+    unsafe fn draw_indirect_count(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        count_buffer: &super::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    ) {
+        self.commands.push(Command::DrawIndirectCount {
+            buffer: Self::buffer_slice(buffer, offset),
+            indexed: false,
+            count_buffer: Self::buffer_slice(count_buffer, count_offset),
+            max_count,
+        });
+    }
+    unsafe fn draw_indexed_indirect_count(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        count_buffer: &super::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    ) {
+        self.commands.push(Command::DrawIndirectCount {
+            buffer: Self::buffer_slice(buffer, offset),
+            indexed: true,
+            count_buffer: Self::buffer_slice(count_buffer, count_offset),
+            max_count,
+        });
+    }
+
+    // compute
+
+    unsafe fn begin_compute_pass(&mut self, _desc: &crate::ComputePassDescriptor) {}
+    unsafe fn end_compute_pass(&mut self) {}
+
+    unsafe fn set_compute_pipeline(&mut self, pipeline: &super::ComputePipeline) {
+        self.commands
+            .push(Command::SetComputePipeline(Arc::clone(&pipeline.inner)));
+    }
+
+    unsafe fn dispatch(&mut self, count: [u32; 3]) {
+        self.commands.push(Command::Dispatch(count));
+    }
+    unsafe fn dispatch_indirect(&mut self, buffer: &super::Buffer, offset: wgt::BufferAddress) {
+        self.commands
+            .push(Command::DispatchIndirect(Self::buffer_slice(
+                buffer, offset,
+            )));
+    }
+}
@@ -0,0 +1,297 @@
+use super::{raster, shader, texel, Api, BindingResource, DeviceResult, Memory};
+
+use parking_lot::Mutex;
+use std::{
+    ptr::NonNull,
+    sync::{atomic::Ordering, Arc},
+};
+
+impl crate::Device<Api> for super::Device {
+    unsafe fn exit(self, _queue: super::Queue) {}
+
+    unsafe fn create_buffer(&self, desc: &crate::BufferDescriptor) -> DeviceResult<super::Buffer> {
+        Ok(super::Buffer {
+            memory: Arc::new(Memory::new(desc.size as usize)),
+        })
+    }
+    unsafe fn destroy_buffer(&self, _buffer: super::Buffer) {}
+    unsafe fn map_buffer(
+        &self,
+        buffer: &super::Buffer,
+        range: crate::MemoryRange,
+    ) -> DeviceResult<crate::BufferMapping> {
+        let ptr = buffer.memory.as_ptr().offset(range.start as isize);
+        Ok(crate::BufferMapping {
+            ptr: NonNull::new(ptr).ok_or(crate::DeviceError::Lost)?,
+            is_coherent: true,
+        })
+    }
+    unsafe fn unmap_buffer(&self, _buffer: &super::Buffer) -> DeviceResult<()> {
+        Ok(())
+    }
+    unsafe fn flush_mapped_ranges<I>(&self, _buffer: &super::Buffer, _ranges: I) {}
+    unsafe fn invalidate_mapped_ranges<I>(&self, _buffer: &super::Buffer, _ranges: I) {}
+
+    unsafe fn create_texture(
+        &self,
+        desc: &crate::TextureDescriptor,
+    ) -> DeviceResult<super::Texture> {
+        let texel_size = texel::texel_size(desc.format) as usize;
+        let mut mip_offsets = Vec::with_capacity(desc.mip_level_count as usize);
+        let mut total = 0;
+        for level in 0..desc.mip_level_count {
+            mip_offsets.push(total);
+            let size = desc
+                .size
+                .mip_level_size(level, desc.dimension == wgt::TextureDimension::D3);
+            total += size.width as usize
+                * size.height as usize
+                * size.depth_or_array_layers as usize
+                * texel_size;
+        }
+        Ok(super::Texture {
+            inner: Arc::new(super::TextureInner {
+                memory: Memory::new(total),
+                format: desc.format,
+                dimension: desc.dimension,
+                size: desc.size,
+                mip_level_count: desc.mip_level_count,
+                mip_offsets,
+            }),
+        })
+    }
+    unsafe fn destroy_texture(&self, _texture: super::Texture) {}
+    unsafe fn create_texture_view(
+        &self,
+        texture: &super::Texture,
+        desc: &crate::TextureViewDescriptor,
+    ) -> DeviceResult<super::TextureView> {
+        let inner = &texture.inner;
+        let mip_end = match desc.range.mip_level_count {
+            Some(count) => desc.range.base_mip_level + count.get(),
+            None => inner.mip_level_count,
+        };
+        let layer_end = match desc.range.array_layer_count {
+            Some(count) => desc.range.base_array_layer + count.get(),
+            None => match inner.dimension {
+                wgt::TextureDimension::D3 => 1,
+                _ => inner.size.depth_or_array_layers,
+            },
+        };
+        Ok(super::TextureView {
+            texture: Arc::clone(inner),
+            format: desc.format,
+            dimension: desc.dimension,
+            aspects: crate::FormatAspects::from(desc.format)
+                & crate::FormatAspects::from(desc.range.aspect),
+            mip_levels: desc.range.base_mip_level..mip_end,
+            array_layers: desc.range.base_array_layer..layer_end,
+        })
+    }
+    unsafe fn destroy_texture_view(&self, _view: super::TextureView) {}
+    unsafe fn create_sampler(
+        &self,
+        desc: &crate::SamplerDescriptor,
+    ) -> DeviceResult<super::Sampler> {
+        Ok(super::Sampler {
+            address_modes: desc.address_modes,
+            mag_filter: desc.mag_filter,
+            min_filter: desc.min_filter,
+            mipmap_filter: desc.mipmap_filter,
+            lod_clamp: desc.lod_clamp.clone().unwrap_or(0.0..32.0),
+            compare: desc.compare,
+            border_color: desc
+                .border_color
+                .unwrap_or(wgt::SamplerBorderColor::TransparentBlack),
+        })
+    }
+    unsafe fn destroy_sampler(&self, _sampler: super::Sampler) {}
+
+    unsafe fn create_command_encoder(
+        &self,
+        _desc: &crate::CommandEncoderDescriptor<Api>,
+    ) -> DeviceResult<super::CommandEncoder> {
+        Ok(super::CommandEncoder {
+            commands: Vec::new(),
+        })
+    }
+    unsafe fn destroy_command_encoder(&self, _encoder: super::CommandEncoder) {}
+
+    unsafe fn create_bind_group_layout(
+        &self,
+        desc: &crate::BindGroupLayoutDescriptor,
+    ) -> DeviceResult<super::BindGroupLayout> {
+        Ok(super::BindGroupLayout {
+            entries: Arc::from(desc.entries),
+        })
+    }
+    unsafe fn destroy_bind_group_layout(&self, _bg_layout: super::BindGroupLayout) {}
+    unsafe fn create_pipeline_layout(
+        &self,
+        _desc: &crate::PipelineLayoutDescriptor<Api>,
+    ) -> DeviceResult<super::PipelineLayout> {
+        Ok(super::PipelineLayout)
+    }
+    unsafe fn destroy_pipeline_layout(&self, _pipeline_layout: super::PipelineLayout) {}
+    unsafe fn create_bind_group(
+        &self,
+        desc: &crate::BindGroupDescriptor<Api>,
+    ) -> DeviceResult<super::BindGroup> {
+        // Dynamic offsets are provided in the order of the bindings.
+        let mut layout_entries = desc.layout.entries.to_vec();
+        layout_entries.sort_by_key(|entry| entry.binding);
+        let mut dynamic_count = 0;
+
+        let mut entries = Vec::with_capacity(desc.entries.len());
+        for layout_entry in layout_entries.iter() {
+            let entry = match desc
+                .entries
+                .iter()
+                .find(|entry| entry.binding == layout_entry.binding)
+            {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let index = entry.resource_index as usize;
+            let resource = match layout_entry.ty {
+                wgt::BindingType::Buffer {
+                    has_dynamic_offset, ..
+                } => {
+                    let binding = &desc.buffers[index];
+                    let memory = Arc::clone(&binding.buffer.memory);
+                    let size = match binding.size {
+                        Some(size) => size.get(),
+                        None => memory.len() as wgt::BufferAddress - binding.offset,
+                    };
+                    let dynamic_index = if has_dynamic_offset {
+                        dynamic_count += 1;
+                        Some(dynamic_count - 1)
+                    } else {
+                        None
+                    };
+                    BindingResource::Buffer {
+                        memory,
+                        offset: binding.offset,
+                        size,
+                        dynamic_index,
+                    }
+                }
+                wgt::BindingType::Sampler(_) => {
+                    BindingResource::Sampler(desc.samplers[index].clone())
+                }
+                wgt::BindingType::Texture { .. } | wgt::BindingType::StorageTexture { .. } => {
+                    BindingResource::Texture(desc.textures[index].view.clone())
+                }
+            };
+            entries.push((entry.binding, resource));
+        }
+
+        Ok(super::BindGroup {
+            entries: Arc::from(entries),
+        })
+    }
+    unsafe fn destroy_bind_group(&self, _group: super::BindGroup) {}
+
+    unsafe fn create_shader_module(
+        &self,
+        _desc: &crate::ShaderModuleDescriptor,
+        shader: crate::ShaderInput,
+    ) -> Result<super::ShaderModule, crate::ShaderError> {
+        match shader {
+            crate::ShaderInput::Naga(naga) => {
+                let program =
+                    shader::Module::new(naga.module).map_err(crate::ShaderError::Compilation)?;
+                Ok(super::ShaderModule {
+                    program: Arc::new(program),
+                })
+            }
+            crate::ShaderInput::SpirV(_) => Err(crate::ShaderError::Compilation(
+                "SPIR-V is not supported by the software backend".to_string(),
+            )),
+        }
+    }
+    unsafe fn destroy_shader_module(&self, _module: super::ShaderModule) {}
+    unsafe fn create_render_pipeline(
+        &self,
+        desc: &crate::RenderPipelineDescriptor<Api>,
+    ) -> Result<super::RenderPipeline, crate::PipelineError> {
+        let vertex = shader::Stage::new(
+            &desc.vertex_stage.module.program,
+            naga::ShaderStage::Vertex,
+            desc.vertex_stage.entry_point,
+            desc.vertex_stage.constants,
+        )?;
+        let fragment = match desc.fragment_stage {
+            Some(ref stage) => Some(shader::Stage::new(
+                &stage.module.program,
+                naga::ShaderStage::Fragment,
+                stage.entry_point,
+                stage.constants,
+            )?),
+            None => None,
+        };
+        Ok(super::RenderPipeline {
+            inner: Arc::new(raster::Pipeline::new(vertex, fragment, desc)),
+        })
+    }
+    unsafe fn destroy_render_pipeline(&self, _pipeline: super::RenderPipeline) {}
+    unsafe fn create_compute_pipeline(
+        &self,
+        desc: &crate::ComputePipelineDescriptor<Api>,
+    ) -> Result<super::ComputePipeline, crate::PipelineError> {
+        let stage = shader::Stage::new(
+            &desc.stage.module.program,
+            naga::ShaderStage::Compute,
+            desc.stage.entry_point,
+            desc.stage.constants,
+        )?;
+        Ok(super::ComputePipeline {
+            inner: Arc::new(stage),
+        })
+    }
+    unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
+
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> DeviceResult<super::PipelineCache> {
+        Ok(super::PipelineCache)
+    }
+    unsafe fn pipeline_cache_get_data(&self, _cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        None
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
+
+    unsafe fn create_query_set(
+        &self,
+        desc: &wgt::QuerySetDescriptor<crate::Label>,
+    ) -> DeviceResult<super::QuerySet> {
+        Ok(super::QuerySet {
+            results: Arc::new(Mutex::new(vec![0; desc.count as usize])),
+        })
+    }
+    unsafe fn destroy_query_set(&self, _set: super::QuerySet) {}
+    unsafe fn create_fence(&self) -> DeviceResult<super::Fence> {
+        Ok(super::Fence {
+            value: Default::default(),
+        })
+    }
+    unsafe fn destroy_fence(&self, _fence: super::Fence) {}
+    unsafe fn get_fence_value(&self, fence: &super::Fence) -> DeviceResult<crate::FenceValue> {
+        Ok(fence.value.load(Ordering::Acquire))
+    }
+    unsafe fn wait(
+        &self,
+        fence: &super::Fence,
+        value: crate::FenceValue,
+        _timeout_ms: u32,
+    ) -> DeviceResult<bool> {
+        // Submissions complete before `submit` returns.
+        Ok(fence.value.load(Ordering::Acquire) >= value)
+    }
+
+    unsafe fn start_capture(&self) -> bool {
+        false
+    }
+    unsafe fn stop_capture(&self) {}
+}
@@ -0,0 +1,389 @@
+//! Texel fetches, stores and filtered sampling of texture views.
+
+#![allow(clippy::needless_range_loop)]
+
+use super::{
+    texel::{self, Texel},
+    Sampler, TextureView,
+};
+
+/// Integer coordinates of a texel within a view: `x`, `y` and the layer,
+/// which is the depth slice for 3D views.
+pub(super) type Coordinates = [i32; 3];
+
+fn view_layers(view: &TextureView) -> u32 {
+    view.array_layers.end - view.array_layers.start
+}
+
+/// Resolves a view-relative texel address into the texture one,
+/// returning `None` if it's out of bounds.
+fn locate(view: &TextureView, coords: Coordinates, level: u32) -> Option<(u32, u32, u32, u32)> {
+    if level >= view.mip_levels.end - view.mip_levels.start {
+        return None;
+    }
+    let size = view.size(level);
+    let [x, y, layer] = coords;
+    if x < 0
+        || y < 0
+        || layer < 0
+        || x as u32 >= size.width
+        || y as u32 >= size.height
+        || layer as u32 >= size.depth_or_array_layers
+    {
+        return None;
+    }
+    let layer = match view.dimension {
+        wgt::TextureViewDimension::D3 => layer as u32,
+        _ => view.array_layers.start + layer as u32,
+    };
+    Some((view.mip_levels.start + level, layer, x as u32, y as u32))
+}
+
+/// Fetches a texel, returning zero if it's out of bounds.
+pub(super) fn load(view: &TextureView, coords: Coordinates, level: u32) -> Texel {
+    let desc = view.format.describe();
+    match locate(view, coords, level) {
+        Some((level, layer, x, y)) => {
+            let bytes = unsafe { view.texture.texel(level, layer, x, y) };
+            if view.aspects == crate::FormatAspects::STENCIL {
+                Texel::Uint([texel::read_stencil(view.format, bytes), 0, 0, 1])
+            } else {
+                texel::decode(view.format, bytes)
+            }
+        }
+        None => match desc.sample_type {
+            wgt::TextureSampleType::Sint => Texel::Sint([0; 4]),
+            wgt::TextureSampleType::Uint => Texel::Uint([0; 4]),
+            _ => Texel::Float([0.0; 4]),
+        },
+    }
+}
+
+/// Writes a texel, ignoring writes that are out of bounds.
+pub(super) fn store(view: &TextureView, coords: Coordinates, value: Texel) {
+    if let Some((level, layer, x, y)) = locate(view, coords, 0) {
+        let bytes = unsafe { view.texture.texel_mut(level, layer, x, y) };
+        texel::encode(view.format, value, bytes);
+    }
+}
+
+/// Size of the view at the given level, as `[width, height, depth or layers]`.
+pub(super) fn dimensions(view: &TextureView, level: u32) -> [u32; 3] {
+    let size = view.size(level);
+    [size.width, size.height, size.depth_or_array_layers]
+}
+
+pub(super) fn level_count(view: &TextureView) -> u32 {
+    view.mip_levels.end - view.mip_levels.start
+}
+
+pub(super) fn layer_count(view: &TextureView) -> u32 {
+    match view.dimension {
+        wgt::TextureViewDimension::Cube | wgt::TextureViewDimension::CubeArray => {
+            view_layers(view) / 6
+        }
+        _ => view_layers(view),
+    }
+}
+
+pub(super) fn compare(function: wgt::CompareFunction, reference: f32, value: f32) -> bool {
+    use wgt::CompareFunction as Cf;
+    match function {
+        Cf::Never => false,
+        Cf::Less => reference < value,
+        Cf::Equal => reference == value,
+        Cf::LessEqual => reference <= value,
+        Cf::Greater => reference > value,
+        Cf::NotEqual => reference != value,
+        Cf::GreaterEqual => reference >= value,
+        Cf::Always => true,
+    }
+}
+
+/// Applies an address mode to an integer coordinate, returning `None` for
+/// the border.
+fn address(mode: wgt::AddressMode, coord: i32, size: u32) -> Option<i32> {
+    let size = size as i32;
+    match mode {
+        wgt::AddressMode::ClampToEdge => Some(coord.max(0).min(size - 1)),
+        wgt::AddressMode::Repeat => Some(coord.rem_euclid(size)),
+        wgt::AddressMode::MirrorRepeat => {
+            let period = coord.rem_euclid(2 * size);
+            Some(if period < size {
+                period
+            } else {
+                2 * size - 1 - period
+            })
+        }
+        wgt::AddressMode::ClampToBorder => {
+            if coord < 0 || coord >= size {
+                None
+            } else {
+                Some(coord)
+            }
+        }
+    }
+}
+
+/// Selects the face of a cube map and the coordinates on it.
+fn cube_face(direction: [f32; 3]) -> (u32, f32, f32) {
+    let [x, y, z] = direction;
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+    let (face, major, s, t) = if ax >= ay && ax >= az {
+        if x >= 0.0 {
+            (0, ax, -z, -y)
+        } else {
+            (1, ax, z, -y)
+        }
+    } else if ay >= az {
+        if y >= 0.0 {
+            (2, ay, x, z)
+        } else {
+            (3, ay, x, -z)
+        }
+    } else if z >= 0.0 {
+        (4, az, x, -y)
+    } else {
+        (5, az, -x, -y)
+    };
+    let major = if major == 0.0 { 1.0 } else { major };
+    (face, 0.5 * (s / major + 1.0), 0.5 * (t / major + 1.0))
+}
+
+/// Parameters of a filtered sample.
+pub(super) struct SampleQuery {
+    /// Normalized coordinates, or a direction for cube maps.
+    pub coordinate: [f32; 3],
+    pub array_index: i32,
+    pub lod: f32,
+    pub offset: [i32; 3],
+    pub depth_ref: Option<f32>,
+    /// Component to gather, if this is a gather operation.
+    pub gather: Option<usize>,
+}
+
+struct Footprint {
+    coords: [f32; 3],
+    layer: i32,
+}
+
+fn border(sampler: &Sampler) -> [f32; 4] {
+    match sampler.border_color {
+        wgt::SamplerBorderColor::TransparentBlack => [0.0; 4],
+        wgt::SamplerBorderColor::OpaqueBlack => [0.0, 0.0, 0.0, 1.0],
+        wgt::SamplerBorderColor::OpaqueWhite => [1.0; 4],
+    }
+}
+
+/// Reads a single texel for filtering, applying address modes and depth comparison.
+fn tap(
+    view: &TextureView,
+    sampler: &Sampler,
+    query: &SampleQuery,
+    texel_coords: [i32; 3],
+    layer: i32,
+    level: u32,
+    is_cube: bool,
+) -> [f32; 4] {
+    let size = dimensions(view, level);
+    let is_3d = view.dimension == wgt::TextureViewDimension::D3;
+    let mut coords = [0; 3];
+    for axis in 0..3 {
+        if axis == 2 && !is_3d {
+            coords[axis] = layer;
+            continue;
+        }
+        let mode = if is_cube {
+            wgt::AddressMode::ClampToEdge
+        } else {
+            sampler.address_modes[axis]
+        };
+        match address(mode, texel_coords[axis], size[axis]) {
+            Some(c) => coords[axis] = c,
+            None => return border(sampler),
+        }
+    }
+    let value = load(view, coords, level).to_float();
+    match (query.depth_ref, sampler.compare) {
+        (Some(reference), Some(function)) => {
+            let passed = compare(function, reference, value[0]);
+            let result = if passed { 1.0 } else { 0.0 };
+            [result, result, result, result]
+        }
+        _ => value,
+    }
+}
+
+fn sample_level(
+    view: &TextureView,
+    sampler: &Sampler,
+    query: &SampleQuery,
+    footprint: &Footprint,
+    level: u32,
+    filter: wgt::FilterMode,
+    is_cube: bool,
+) -> [f32; 4] {
+    let size = dimensions(view, level);
+    let is_3d = view.dimension == wgt::TextureViewDimension::D3;
+    let axes = match view.dimension {
+        wgt::TextureViewDimension::D1 => 1,
+        wgt::TextureViewDimension::D3 => 3,
+        _ => 2,
+    };
+    let mut scaled = [0.0; 3];
+    for axis in 0..axes {
+        scaled[axis] = footprint.coords[axis] * size[axis] as f32;
+    }
+
+    if filter == wgt::FilterMode::Nearest && query.gather.is_none() {
+        let mut coords = [0; 3];
+        for axis in 0..axes {
+            coords[axis] = scaled[axis].floor() as i32 + query.offset[axis];
+        }
+        return tap(
+            view,
+            sampler,
+            query,
+            coords,
+            footprint.layer,
+            level,
+            is_cube,
+        );
+    }
+
+    let mut base = [0; 3];
+    let mut weights = [0.0; 3];
+    for axis in 0..axes {
+        let position = scaled[axis] - 0.5;
+        base[axis] = position.floor() as i32 + query.offset[axis];
+        weights[axis] = position - position.floor();
+    }
+
+    if let Some(component) = query.gather {
+        // Same order as Vulkan and D3D: (0, 1), (1, 1), (1, 0), (0, 0).
+        let mut result = [0.0; 4];
+        for (i, &(dx, dy)) in [(0, 1), (1, 1), (1, 0), (0, 0)].iter().enumerate() {
+            let coords = [base[0] + dx, base[1] + dy, 0];
+            let value = tap(
+                view,
+                sampler,
+                query,
+                coords,
+                footprint.layer,
+                level,
+                is_cube,
+            );
+            result[i] = if query.depth_ref.is_some() {
+                value[0]
+            } else {
+                value[component]
+            };
+        }
+        return result;
+    }
+
+    let depth_taps = if is_3d { 2 } else { 1 };
+    let height_taps = if axes >= 2 { 2 } else { 1 };
+    let mut result = [0.0; 4];
+    for dz in 0..depth_taps {
+        for dy in 0..height_taps {
+            for dx in 0..2 {
+                let weight = (if dx == 1 {
+                    weights[0]
+                } else {
+                    1.0 - weights[0]
+                }) * if height_taps == 1 {
+                    1.0
+                } else if dy == 1 {
+                    weights[1]
+                } else {
+                    1.0 - weights[1]
+                } * if depth_taps == 1 {
+                    1.0
+                } else if dz == 1 {
+                    weights[2]
+                } else {
+                    1.0 - weights[2]
+                };
+                let coords = [base[0] + dx, base[1] + dy, base[2] + dz];
+                let value = tap(
+                    view,
+                    sampler,
+                    query,
+                    coords,
+                    footprint.layer,
+                    level,
+                    is_cube,
+                );
+                for (r, v) in result.iter_mut().zip(value.iter()) {
+                    *r += weight * v;
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Performs a filtered sample of the view.
+pub(super) fn sample(view: &TextureView, sampler: &Sampler, query: &SampleQuery) -> [f32; 4] {
+    let is_cube = matches!(
+        view.dimension,
+        wgt::TextureViewDimension::Cube | wgt::TextureViewDimension::CubeArray
+    );
+    let footprint = if is_cube {
+        let (face, s, t) = cube_face(query.coordinate);
+        Footprint {
+            coords: [s, t, 0.0],
+            layer: query.array_index.max(0) * 6 + face as i32,
+        }
+    } else {
+        let layers = view_layers(view) as i32;
+        Footprint {
+            coords: query.coordinate,
+            layer: query.array_index.max(0).min(layers - 1),
+        }
+    };
+
+    let lod = query
+        .lod
+        .max(sampler.lod_clamp.start)
+        .min(sampler.lod_clamp.end);
+    let filter = if lod <= 0.0 {
+        sampler.mag_filter
+    } else {
+        sampler.min_filter
+    };
+    let max_level = (level_count(view) - 1) as f32;
+    let lod = lod.max(0.0).min(max_level);
+
+    match sampler.mipmap_filter {
+        wgt::FilterMode::Nearest => {
+            let level = (lod + 0.5).floor() as u32;
+            sample_level(view, sampler, query, &footprint, level, filter, is_cube)
+        }
+        wgt::FilterMode::Linear => {
+            let low = lod.floor();
+            let weight = lod - low;
+            let first = sample_level(
+                view, sampler, query, &footprint, low as u32, filter, is_cube,
+            );
+            if weight == 0.0 {
+                return first;
+            }
+            let second = sample_level(
+                view,
+                sampler,
+                query,
+                &footprint,
+                low as u32 + 1,
+                filter,
+                is_cube,
+            );
+            let mut result = [0.0; 4];
+            for i in 0..4 {
+                result[i] = first[i] * (1.0 - weight) + second[i] * weight;
+            }
+            result
+        }
+    }
+}
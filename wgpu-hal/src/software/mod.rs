@@ -0,0 +1,450 @@
+/*!
+# Software backend
+
+A CPU implementation of the HAL, meant for running tests and replaying traces
+on machines that have no GPU (for example, CI runners).
+
+Nothing here is fast. The goal is to produce *correct* results for the
+operations the tests exercise, so that they can check real outputs instead of
+only checking that nothing crashed.
+
+## Execution model
+
+Command encoders record an owned list of commands. All the work happens
+in `Queue::submit`, synchronously, in submission order. Fences are signaled
+as soon as the submission returns, so waiting on them never blocks.
+
+Buffers and textures are plain byte arrays. Mapping a buffer returns a
+pointer straight into its storage, which is always coherent.
+
+## Shaders
+
+Only Naga IR is accepted. Shaders are executed by interpreting the IR
+directly, one invocation at a time. Invocations of a compute workgroup are
+stepped in lockstep between control barriers, so workgroup memory behaves
+as expected.
+
+## Rasterization
+
+Triangles are rasterized with the top-left fill rule at pixel centers.
+Lines and points are drawn one pixel wide. Primitives are not clipped
+against the near plane: a primitive with any vertex behind the eye is
+dropped.
+
+Derivatives are not computed and always evaluate to zero, which also means
+that implicit-LOD sampling always reads from the base mip level of the view.
+!*/
+
+mod command;
+mod device;
+mod image;
+mod queue;
+mod raster;
+mod shader;
+mod texel;
+
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    ops::Range,
+    sync::{atomic::AtomicU64, Arc},
+};
+
+use parking_lot::Mutex;
+
+#[derive(Clone)]
+pub struct Api;
+
+impl crate::Api for Api {
+    type Instance = Instance;
+    type Surface = Surface;
+    type Adapter = Adapter;
+    type Device = Device;
+
+    type Queue = Queue;
+    type CommandEncoder = CommandEncoder;
+    type CommandBuffer = CommandBuffer;
+
+    type Buffer = Buffer;
+    type Texture = Texture;
+    type SurfaceTexture = Texture;
+    type TextureView = TextureView;
+    type Sampler = Sampler;
+    type QuerySet = QuerySet;
+    type Fence = Fence;
+
+    type BindGroupLayout = BindGroupLayout;
+    type BindGroup = BindGroup;
+    type PipelineLayout = PipelineLayout;
+    type ShaderModule = ShaderModule;
+    type RenderPipeline = RenderPipeline;
+    type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
+}
+
+type DeviceResult<T> = Result<T, crate::DeviceError>;
+
+pub struct Instance;
+/// Presentation is not supported, so this is never created.
+pub struct Surface;
+pub struct Adapter;
+pub struct Device;
+pub struct Queue;
+
+/// Raw storage shared by the host and the queue.
+///
+/// There is no synchronization between the two, which matches the contract
+/// of the hardware backends: the user of the HAL is responsible for not
+/// touching memory that is in use by a submission.
+pub(super) struct Memory {
+    data: UnsafeCell<Box<[u8]>>,
+}
+
+unsafe impl Send for Memory {}
+unsafe impl Sync for Memory {}
+
+impl fmt::Debug for Memory {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Memory({} bytes)", self.len())
+    }
+}
+
+impl Memory {
+    fn new(size: usize) -> Self {
+        Self {
+            data: UnsafeCell::new(vec![0; size].into_boxed_slice()),
+        }
+    }
+
+    fn from_bytes(data: Box<[u8]>) -> Self {
+        Self {
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    fn len(&self) -> usize {
+        unsafe { (*self.data.get()).len() }
+    }
+
+    fn as_ptr(&self) -> *mut u8 {
+        unsafe { (*self.data.get()).as_mut_ptr() }
+    }
+
+    /// # Safety
+    ///
+    /// No mutable access to the range may be alive.
+    unsafe fn bytes(&self, range: Range<usize>) -> &[u8] {
+        &(*self.data.get())[range]
+    }
+
+    /// # Safety
+    ///
+    /// No other access to the range may be alive.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn bytes_mut(&self, range: Range<usize>) -> &mut [u8] {
+        &mut (*self.data.get())[range]
+    }
+}
+
+#[derive(Debug)]
+pub struct Buffer {
+    memory: Arc<Memory>,
+}
+
+#[derive(Debug)]
+pub(super) struct TextureInner {
+    memory: Memory,
+    format: wgt::TextureFormat,
+    dimension: wgt::TextureDimension,
+    size: wgt::Extent3d,
+    mip_level_count: u32,
+    /// Offsets of every mip level in `memory`.
+    mip_offsets: Vec<usize>,
+}
+
+impl TextureInner {
+    fn texel_size(&self) -> usize {
+        texel::texel_size(self.format) as usize
+    }
+
+    /// Size of a mip level, where `depth_or_array_layers` is the depth
+    /// of 3D textures and the layer count otherwise.
+    fn mip_size(&self, level: u32) -> wgt::Extent3d {
+        wgt::Extent3d {
+            width: (self.size.width >> level).max(1),
+            height: (self.size.height >> level).max(1),
+            depth_or_array_layers: match self.dimension {
+                wgt::TextureDimension::D3 => (self.size.depth_or_array_layers >> level).max(1),
+                _ => self.size.depth_or_array_layers,
+            },
+        }
+    }
+
+    /// Byte offset of a texel, where `layer` is the depth slice of 3D textures.
+    fn texel_offset(&self, level: u32, layer: u32, x: u32, y: u32) -> usize {
+        let size = self.mip_size(level);
+        let index =
+            (layer as usize * size.height as usize + y as usize) * size.width as usize + x as usize;
+        self.mip_offsets[level as usize] + index * self.texel_size()
+    }
+
+    /// # Safety
+    ///
+    /// No mutable access to the texel may be alive.
+    unsafe fn texel(&self, level: u32, layer: u32, x: u32, y: u32) -> &[u8] {
+        let offset = self.texel_offset(level, layer, x, y);
+        self.memory.bytes(offset..offset + self.texel_size())
+    }
+
+    /// # Safety
+    ///
+    /// No other access to the texel may be alive.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn texel_mut(&self, level: u32, layer: u32, x: u32, y: u32) -> &mut [u8] {
+        let offset = self.texel_offset(level, layer, x, y);
+        self.memory.bytes_mut(offset..offset + self.texel_size())
+    }
+}
+
+#[derive(Debug)]
+pub struct Texture {
+    inner: Arc<TextureInner>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TextureView {
+    texture: Arc<TextureInner>,
+    format: wgt::TextureFormat,
+    dimension: wgt::TextureViewDimension,
+    aspects: crate::FormatAspects,
+    mip_levels: Range<u32>,
+    array_layers: Range<u32>,
+}
+
+impl TextureView {
+    /// Size of the view at the given level, relative to the base level of the view.
+    fn size(&self, level: u32) -> wgt::Extent3d {
+        let mut size = self.texture.mip_size(self.mip_levels.start + level);
+        if self.dimension != wgt::TextureViewDimension::D3 {
+            size.depth_or_array_layers = self.array_layers.end - self.array_layers.start;
+        }
+        size
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sampler {
+    address_modes: [wgt::AddressMode; 3],
+    mag_filter: wgt::FilterMode,
+    min_filter: wgt::FilterMode,
+    mipmap_filter: wgt::FilterMode,
+    lod_clamp: Range<f32>,
+    compare: Option<wgt::CompareFunction>,
+    border_color: wgt::SamplerBorderColor,
+}
+
+#[derive(Debug)]
+pub struct QuerySet {
+    results: Arc<Mutex<Vec<u64>>>,
+}
+
+#[derive(Debug)]
+pub struct Fence {
+    value: AtomicU64,
+}
+
+pub struct BindGroupLayout {
+    entries: Arc<[wgt::BindGroupLayoutEntry]>,
+}
+
+#[derive(Clone, Debug)]
+enum BindingResource {
+    Buffer {
+        memory: Arc<Memory>,
+        offset: wgt::BufferAddress,
+        size: wgt::BufferAddress,
+        /// Index into the dynamic offsets of the group, if the binding has one.
+        dynamic_index: Option<usize>,
+    },
+    Sampler(Sampler),
+    Texture(TextureView),
+}
+
+#[derive(Clone, Debug)]
+pub struct BindGroup {
+    /// Resources of the group, sorted by binding.
+    entries: Arc<[(u32, BindingResource)]>,
+}
+
+impl BindGroup {
+    fn resource(&self, binding: u32) -> Option<&BindingResource> {
+        self.entries
+            .binary_search_by_key(&binding, |&(b, _)| b)
+            .ok()
+            .map(|index| &self.entries[index].1)
+    }
+}
+
+pub struct PipelineLayout;
+
+#[derive(Debug)]
+pub struct ShaderModule {
+    program: Arc<shader::Module>,
+}
+
+pub struct RenderPipeline {
+    inner: Arc<raster::Pipeline>,
+}
+
+pub struct ComputePipeline {
+    inner: Arc<shader::Stage>,
+}
+
+#[derive(Debug)]
+pub struct PipelineCache;
+
+pub struct CommandEncoder {
+    commands: Vec<command::Command>,
+}
+
+pub struct CommandBuffer {
+    commands: Vec<command::Command>,
+}
+
+impl crate::Instance<Api> for Instance {
+    unsafe fn init(_desc: &crate::InstanceDescriptor) -> Result<Self, crate::InstanceError> {
+        Ok(Instance)
+    }
+
+    unsafe fn create_surface(
+        &self,
+        _rwh: &impl raw_window_handle::HasRawWindowHandle,
+    ) -> Result<Surface, crate::InstanceError> {
+        Err(crate::InstanceError)
+    }
+
+    unsafe fn destroy_surface(&self, _surface: Surface) {}
+
+    unsafe fn enumerate_adapters(&self) -> Vec<crate::ExposedAdapter<Api>> {
+        let features = wgt::Features::DEPTH_CLIP_CONTROL
+            | wgt::Features::MAPPABLE_PRIMARY_BUFFERS
+            | wgt::Features::MULTI_DRAW_INDIRECT
+            | wgt::Features::MULTI_DRAW_INDIRECT_COUNT
+            | wgt::Features::PUSH_CONSTANTS
+            | wgt::Features::ADDRESS_MODE_CLAMP_TO_BORDER
+            | wgt::Features::VERTEX_WRITABLE_STORAGE
+            | wgt::Features::CLEAR_TEXTURE;
+
+        // Textures only store a single sample, and samplers ignore anisotropy.
+        let downlevel = wgt::DownlevelCapabilities {
+            flags: wgt::DownlevelFlags::compliant()
+                - wgt::DownlevelFlags::ANISOTROPIC_FILTERING
+                - wgt::DownlevelFlags::MULTISAMPLED_SHADING
+                - wgt::DownlevelFlags::WEBGPU_TEXTURE_FORMAT_SUPPORT,
+            ..wgt::DownlevelCapabilities::default()
+        };
+
+        let limits = wgt::Limits {
+            max_push_constant_size: 256,
+            ..wgt::Limits::default()
+        };
+
+        vec![crate::ExposedAdapter {
+            adapter: Adapter,
+            info: wgt::AdapterInfo {
+                name: "Software Rasterizer".to_string(),
+                vendor: 0,
+                device: 0,
+                device_type: wgt::DeviceType::Cpu,
+                backend: wgt::Backend::Software,
+            },
+            features,
+            capabilities: crate::Capabilities {
+                limits,
+                alignments: crate::Alignments {
+                    buffer_copy_offset: wgt::BufferSize::new(4).unwrap(),
+                    buffer_copy_pitch: wgt::BufferSize::new(4).unwrap(),
+                },
+                downlevel,
+            },
+        }]
+    }
+}
+
+impl crate::Surface<Api> for Surface {
+    unsafe fn configure(
+        &mut self,
+        _device: &Device,
+        _config: &crate::SurfaceConfiguration,
+    ) -> Result<(), crate::SurfaceError> {
+        Err(crate::SurfaceError::Other("presentation is not supported"))
+    }
+
+    unsafe fn unconfigure(&mut self, _device: &Device) {}
+
+    unsafe fn acquire_texture(
+        &mut self,
+        _timeout: Option<std::time::Duration>,
+    ) -> Result<Option<crate::AcquiredSurfaceTexture<Api>>, crate::SurfaceError> {
+        Err(crate::SurfaceError::Other("presentation is not supported"))
+    }
+
+    unsafe fn discard_texture(&mut self, _texture: Texture) {}
+}
+
+impl crate::Adapter<Api> for Adapter {
+    unsafe fn open(
+        &self,
+        _features: wgt::Features,
+        _limits: &wgt::Limits,
+    ) -> DeviceResult<crate::OpenDevice<Api>> {
+        Ok(crate::OpenDevice {
+            device: Device,
+            queue: Queue,
+        })
+    }
+
+    unsafe fn texture_format_capabilities(
+        &self,
+        format: wgt::TextureFormat,
+    ) -> crate::TextureFormatCapabilities {
+        use crate::TextureFormatCapabilities as Tfc;
+
+        if !texel::is_supported(format) {
+            return Tfc::empty();
+        }
+
+        let copy = Tfc::COPY_SRC | Tfc::COPY_DST;
+        let desc = format.describe();
+        match desc.sample_type {
+            wgt::TextureSampleType::Depth => {
+                copy | Tfc::SAMPLED | Tfc::SAMPLED_LINEAR | Tfc::DEPTH_STENCIL_ATTACHMENT
+            }
+            wgt::TextureSampleType::Float { filterable } => {
+                let mut caps = copy
+                    | Tfc::SAMPLED
+                    | Tfc::COLOR_ATTACHMENT
+                    | Tfc::STORAGE
+                    | Tfc::STORAGE_READ_WRITE;
+                caps.set(
+                    Tfc::SAMPLED_LINEAR | Tfc::COLOR_ATTACHMENT_BLEND,
+                    filterable,
+                );
+                if desc.srgb {
+                    caps.remove(Tfc::STORAGE | Tfc::STORAGE_READ_WRITE);
+                }
+                caps
+            }
+            wgt::TextureSampleType::Sint | wgt::TextureSampleType::Uint => {
+                copy | Tfc::SAMPLED | Tfc::COLOR_ATTACHMENT | Tfc::STORAGE | Tfc::STORAGE_READ_WRITE
+            }
+        }
+    }
+
+    unsafe fn surface_capabilities(
+        &self,
+        _surface: &Surface,
+    ) -> Option<crate::SurfaceCapabilities> {
+        None
+    }
+}
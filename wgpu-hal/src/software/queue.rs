@@ -0,0 +1,910 @@
+use super::{
+    command::{ColorTarget, Command, DepthStencilTarget},
+    image,
+    raster::{self, BufferSlice},
+    shader::{self, GlobalBinding},
+    texel::{self, Texel},
+    Api, BindGroup, BindingResource, DeviceResult, Memory, TextureInner, TextureView,
+};
+
+use std::{
+    sync::{atomic::Ordering, Arc},
+    time,
+};
+
+const MAX_BIND_GROUPS: usize = crate::MAX_BIND_GROUPS;
+const PUSH_CONSTANT_SIZE: usize = 256;
+
+/// Render pass in flight, with the attachments that need resolving at the end.
+struct ActivePass {
+    raster: raster::Pass,
+    resolves: Vec<(TextureView, TextureView)>,
+}
+
+/// Everything that commands of a command buffer can inherit from each other.
+struct State {
+    pass: Option<ActivePass>,
+    bind_groups: [Option<(BindGroup, Vec<wgt::DynamicOffset>)>; MAX_BIND_GROUPS],
+    push_constants: Box<[u8; PUSH_CONSTANT_SIZE]>,
+    render_pipeline: Option<Arc<raster::Pipeline>>,
+    compute_pipeline: Option<Arc<shader::Stage>>,
+    vertex_buffers: Vec<Option<BufferSlice>>,
+    index_buffer: Option<(BufferSlice, wgt::IndexFormat)>,
+    occlusion_query: Option<u32>,
+}
+
+fn read_u32(memory: &Memory, offset: usize) -> u32 {
+    if offset + 4 > memory.len() {
+        return 0;
+    }
+    let bytes = unsafe { memory.bytes(offset..offset + 4) };
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Returns the texture layer of a copy, where 3D textures use depth slices as layers.
+fn copy_layer(base: &crate::TextureCopyBase, z: u32) -> u32 {
+    base.array_layer + base.origin.z + z
+}
+
+fn copy_buffer_texture(
+    memory: &Memory,
+    texture: &TextureInner,
+    region: &crate::BufferTextureCopy,
+    to_texture: bool,
+) {
+    let base = &region.texture_base;
+    let aspect = texel::aspect_range(texture.format, base.aspect);
+    let block = aspect.len();
+    let layout = &region.buffer_layout;
+    let bytes_per_row = layout
+        .bytes_per_row
+        .map_or(region.size.width as usize * block, |bpr| bpr.get() as usize);
+    let rows_per_image = layout
+        .rows_per_image
+        .map_or(region.size.height as usize, |rpi| rpi.get() as usize);
+    let mip_size = texture.mip_size(base.mip_level);
+
+    for z in 0..region.size.depth {
+        let layer = copy_layer(base, z);
+        if layer >= mip_size.depth_or_array_layers {
+            continue;
+        }
+        for y in 0..region.size.height {
+            let row =
+                layout.offset as usize + (z as usize * rows_per_image + y as usize) * bytes_per_row;
+            for x in 0..region.size.width {
+                let (tx, ty) = (base.origin.x + x, base.origin.y + y);
+                let buffer_offset = row + x as usize * block;
+                if tx >= mip_size.width
+                    || ty >= mip_size.height
+                    || buffer_offset + block > memory.len()
+                {
+                    continue;
+                }
+                let texel_offset = texture.texel_offset(base.mip_level, layer, tx, ty);
+                let texture_range = texel_offset + aspect.start..texel_offset + aspect.end;
+                let buffer_range = buffer_offset..buffer_offset + block;
+                unsafe {
+                    if to_texture {
+                        texture
+                            .memory
+                            .bytes_mut(texture_range)
+                            .copy_from_slice(memory.bytes(buffer_range));
+                    } else {
+                        memory
+                            .bytes_mut(buffer_range)
+                            .copy_from_slice(texture.memory.bytes(texture_range));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn copy_texture_texture(src: &TextureInner, dst: &TextureInner, region: &crate::TextureCopy) {
+    let aspect = texel::aspect_range(src.format, region.src_base.aspect);
+    let src_size = src.mip_size(region.src_base.mip_level);
+    let dst_size = dst.mip_size(region.dst_base.mip_level);
+    // The source and the destination may be the same texture, so copy
+    // through a temporary texel.
+    let mut temp = [0u8; 16];
+    for z in 0..region.size.depth {
+        let (src_layer, dst_layer) = (
+            copy_layer(&region.src_base, z),
+            copy_layer(&region.dst_base, z),
+        );
+        for y in 0..region.size.height {
+            for x in 0..region.size.width {
+                let (sx, sy) = (region.src_base.origin.x + x, region.src_base.origin.y + y);
+                let (dx, dy) = (region.dst_base.origin.x + x, region.dst_base.origin.y + y);
+                if sx >= src_size.width
+                    || sy >= src_size.height
+                    || src_layer >= src_size.depth_or_array_layers
+                    || dx >= dst_size.width
+                    || dy >= dst_size.height
+                    || dst_layer >= dst_size.depth_or_array_layers
+                {
+                    continue;
+                }
+                let src_offset = src.texel_offset(region.src_base.mip_level, src_layer, sx, sy);
+                let dst_offset = dst.texel_offset(region.dst_base.mip_level, dst_layer, dx, dy);
+                let temp = &mut temp[..aspect.len()];
+                unsafe {
+                    temp.copy_from_slice(
+                        src.memory
+                            .bytes(src_offset + aspect.start..src_offset + aspect.end),
+                    );
+                    dst.memory
+                        .bytes_mut(dst_offset + aspect.start..dst_offset + aspect.end)
+                        .copy_from_slice(temp);
+                }
+            }
+        }
+    }
+}
+
+fn clear_color(target: &ColorTarget, extent: wgt::Extent3d) {
+    let color = match target.clear {
+        Some(color) => color,
+        None => return,
+    };
+    let value = match target.view.format.describe().sample_type {
+        wgt::TextureSampleType::Sint => Texel::Sint([
+            color.r as i32,
+            color.g as i32,
+            color.b as i32,
+            color.a as i32,
+        ]),
+        wgt::TextureSampleType::Uint => Texel::Uint([
+            color.r as u32,
+            color.g as u32,
+            color.b as u32,
+            color.a as u32,
+        ]),
+        _ => Texel::Float([
+            color.r as f32,
+            color.g as f32,
+            color.b as f32,
+            color.a as f32,
+        ]),
+    };
+    for y in 0..extent.height {
+        for x in 0..extent.width {
+            image::store(&target.view, [x as i32, y as i32, 0], value);
+        }
+    }
+}
+
+fn clear_depth_stencil(target: &DepthStencilTarget, extent: wgt::Extent3d) {
+    let view = &target.view;
+    let format = view.texture.format;
+    let level = view.mip_levels.start;
+    let layer = view.array_layers.start;
+    for y in 0..extent.height {
+        for x in 0..extent.width {
+            let bytes = unsafe { view.texture.texel_mut(level, layer, x, y) };
+            if let Some(depth) = target.clear_depth {
+                if view.aspects.contains(crate::FormatAspects::DEPTH) {
+                    texel::write_depth(bytes, depth);
+                }
+            }
+            if let Some(stencil) = target.clear_stencil {
+                if view.aspects.contains(crate::FormatAspects::STENCIL) {
+                    texel::write_stencil(format, bytes, stencil);
+                }
+            }
+        }
+    }
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            pass: None,
+            bind_groups: Default::default(),
+            push_constants: Box::new([0; PUSH_CONSTANT_SIZE]),
+            render_pipeline: None,
+            compute_pipeline: None,
+            vertex_buffers: Vec::new(),
+            index_buffer: None,
+            occlusion_query: None,
+        }
+    }
+
+    /// Resolves the resources of a stage from the bound groups and push constants.
+    fn bind(&mut self, stage: &shader::Stage) -> shader::Bindings {
+        let bind_groups = &self.bind_groups;
+        let push_constants = self.push_constants.as_mut_ptr();
+        stage.bind(|space, binding| {
+            if space == naga::AddressSpace::PushConstant {
+                return GlobalBinding::Memory {
+                    base: push_constants,
+                    len: PUSH_CONSTANT_SIZE,
+                };
+            }
+            let binding = match binding {
+                Some(binding) => binding,
+                None => return GlobalBinding::Unbound,
+            };
+            let (group, dynamic_offsets) = match bind_groups.get(binding.group as usize) {
+                Some(&Some((ref group, ref offsets))) => (group, offsets),
+                _ => return GlobalBinding::Unbound,
+            };
+            match group.resource(binding.binding) {
+                Some(&BindingResource::Buffer {
+                    ref memory,
+                    offset,
+                    size,
+                    dynamic_index,
+                }) => {
+                    let dynamic_offset = dynamic_index
+                        .and_then(|index| dynamic_offsets.get(index))
+                        .map_or(0, |&offset| offset as usize);
+                    let start = (offset as usize + dynamic_offset).min(memory.len());
+                    let len = (size as usize).min(memory.len() - start);
+                    GlobalBinding::Memory {
+                        base: unsafe { memory.as_ptr().add(start) },
+                        len,
+                    }
+                }
+                Some(&BindingResource::Sampler(ref sampler)) => {
+                    GlobalBinding::Sampler(sampler.clone())
+                }
+                Some(&BindingResource::Texture(ref view)) => GlobalBinding::Texture(view.clone()),
+                None => GlobalBinding::Unbound,
+            }
+        })
+    }
+
+    fn draw(&mut self, vertices: raster::Vertices, instances: std::ops::Range<u32>) {
+        let pipeline = match self.render_pipeline.clone() {
+            Some(pipeline) => pipeline,
+            None => return,
+        };
+        let vertex_bindings = self.bind(&pipeline.vertex);
+        let fragment_bindings = pipeline.fragment.as_ref().map(|stage| self.bind(stage));
+        let pass = match self.pass {
+            Some(ref mut pass) => &mut pass.raster,
+            None => return,
+        };
+        let draw = raster::Draw {
+            pipeline: &pipeline,
+            vertex_bindings: &vertex_bindings,
+            fragment_bindings: fragment_bindings.as_ref(),
+            vertex_buffers: &self.vertex_buffers,
+            index_buffer: self.index_buffer.as_ref(),
+        };
+        if let Err(fault) = draw.run(pass, vertices, instances) {
+            log::error!("Draw call aborted: {}", fault);
+        }
+    }
+
+    fn draw_indirect(&mut self, buffer: &BufferSlice, indexed: bool, index: u32) {
+        let stride = if indexed { 20 } else { 16 };
+        let base = buffer.offset as usize + index as usize * stride;
+        let arg = |i: usize| read_u32(&buffer.memory, base + i * 4);
+        let (count, instances, first) = (arg(0), arg(1), arg(2));
+        let first_instance = arg(if indexed { 4 } else { 3 });
+        // The arguments come from the GPU, so they are not validated.
+        let (end, instance_end) = match (
+            first.checked_add(count),
+            first_instance.checked_add(instances),
+        ) {
+            (Some(end), Some(instance_end)) => (end, instance_end),
+            _ => {
+                log::error!("Indirect draw arguments overflow, skipping the draw");
+                return;
+            }
+        };
+        let vertices = if indexed {
+            raster::Vertices::Indexed {
+                indices: first..end,
+                base_vertex: arg(3) as i32,
+            }
+        } else {
+            raster::Vertices::Direct(first..end)
+        };
+        self.draw(vertices, first_instance..instance_end);
+    }
+
+    fn dispatch(&mut self, count: [u32; 3]) {
+        let stage = match self.compute_pipeline.clone() {
+            Some(stage) => stage,
+            None => return,
+        };
+        let bindings = self.bind(&stage);
+        for z in 0..count[2] {
+            for y in 0..count[1] {
+                for x in 0..count[0] {
+                    if let Err(fault) = stage.dispatch_workgroup(&bindings, [x, y, z], count) {
+                        log::error!("Dispatch aborted: {}", fault);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn execute(&mut self, command: &Command) {
+        match *command {
+            Command::ClearBuffer { ref dst, ref range } => {
+                let end = (range.end as usize).min(dst.len());
+                let start = (range.start as usize).min(end);
+                unsafe { dst.bytes_mut(start..end) }.fill(0);
+            }
+            Command::CopyBufferToBuffer {
+                ref src,
+                ref dst,
+                ref regions,
+            } => {
+                for region in regions {
+                    let size = region.size.get() as usize;
+                    let src_start = region.src_offset as usize;
+                    let dst_start = region.dst_offset as usize;
+                    if src_start + size > src.len() || dst_start + size > dst.len() {
+                        continue;
+                    }
+                    unsafe {
+                        std::ptr::copy(
+                            src.as_ptr().add(src_start),
+                            dst.as_ptr().add(dst_start),
+                            size,
+                        );
+                    }
+                }
+            }
+            Command::CopyTextureToTexture {
+                ref src,
+                ref dst,
+                ref regions,
+            } => {
+                for region in regions {
+                    copy_texture_texture(src, dst, region);
+                }
+            }
+            Command::CopyBufferToTexture {
+                ref src,
+                ref dst,
+                ref regions,
+            } => {
+                for region in regions {
+                    copy_buffer_texture(src, dst, region, true);
+                }
+            }
+            Command::CopyTextureToBuffer {
+                ref src,
+                ref dst,
+                ref regions,
+            } => {
+                for region in regions {
+                    copy_buffer_texture(dst, src, region, false);
+                }
+            }
+            Command::BeginQuery { index, .. } => {
+                self.occlusion_query = Some(index);
+                if let Some(ref mut pass) = self.pass {
+                    pass.raster.occlusion_count = Some(0);
+                }
+            }
+            Command::EndQuery { ref results, index } => {
+                if self.occlusion_query.take() == Some(index) {
+                    let count = self
+                        .pass
+                        .as_mut()
+                        .and_then(|pass| pass.raster.occlusion_count.take())
+                        .unwrap_or(0);
+                    if let Some(result) = results.lock().get_mut(index as usize) {
+                        *result = count;
+                    }
+                }
+            }
+            Command::WriteTimestamp { ref results, index } => {
+                let timestamp = time::SystemTime::now()
+                    .duration_since(time::UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_nanos() as u64);
+                if let Some(result) = results.lock().get_mut(index as usize) {
+                    *result = timestamp;
+                }
+            }
+            Command::ResetQueries {
+                ref results,
+                ref range,
+            } => {
+                let mut results = results.lock();
+                for index in range.clone() {
+                    if let Some(result) = results.get_mut(index as usize) {
+                        *result = 0;
+                    }
+                }
+            }
+            Command::CopyQueryResults {
+                ref results,
+                ref range,
+                ref dst,
+                offset,
+                stride,
+            } => {
+                let results = results.lock();
+                for (i, index) in range.clone().enumerate() {
+                    let start = offset as usize + i * stride.get() as usize;
+                    let value = results.get(index as usize).copied().unwrap_or(0);
+                    if start + crate::QUERY_SIZE as usize <= dst.len() {
+                        unsafe { dst.bytes_mut(start..start + crate::QUERY_SIZE as usize) }
+                            .copy_from_slice(&value.to_le_bytes());
+                    }
+                }
+            }
+            Command::BeginRenderPass {
+                ref colors,
+                ref depth_stencil,
+                extent,
+            } => {
+                for target in colors {
+                    clear_color(target, extent);
+                }
+                if let Some(ref target) = *depth_stencil {
+                    clear_depth_stencil(target, extent);
+                }
+                self.pass = Some(ActivePass {
+                    raster: raster::Pass {
+                        colors: colors.iter().map(|at| Some(at.view.clone())).collect(),
+                        depth_stencil: depth_stencil.as_ref().map(|at| at.view.clone()),
+                        extent,
+                        viewport: crate::Rect {
+                            x: 0.0,
+                            y: 0.0,
+                            w: extent.width as f32,
+                            h: extent.height as f32,
+                        },
+                        depth_range: 0.0..1.0,
+                        scissor: crate::Rect {
+                            x: 0,
+                            y: 0,
+                            w: extent.width,
+                            h: extent.height,
+                        },
+                        stencil_reference: 0,
+                        blend_constant: [0.0; 4],
+                        occlusion_count: None,
+                    },
+                    resolves: colors
+                        .iter()
+                        .filter_map(|at| {
+                            at.resolve
+                                .as_ref()
+                                .map(|resolve| (at.view.clone(), resolve.clone()))
+                        })
+                        .collect(),
+                });
+            }
+            Command::EndRenderPass => {
+                if let Some(pass) = self.pass.take() {
+                    let extent = pass.raster.extent;
+                    for (src, dst) in pass.resolves {
+                        for y in 0..extent.height as i32 {
+                            for x in 0..extent.width as i32 {
+                                image::store(&dst, [x, y, 0], image::load(&src, [x, y, 0], 0));
+                            }
+                        }
+                    }
+                }
+                self.vertex_buffers.clear();
+                self.index_buffer = None;
+            }
+            Command::SetBindGroup {
+                index,
+                ref group,
+                ref dynamic_offsets,
+            } => {
+                if let Some(slot) = self.bind_groups.get_mut(index as usize) {
+                    *slot = Some((group.clone(), dynamic_offsets.clone()));
+                }
+            }
+            Command::SetPushConstants { offset, ref data } => {
+                for (i, &word) in data.iter().enumerate() {
+                    let start = offset as usize + i * 4;
+                    if start + 4 <= PUSH_CONSTANT_SIZE {
+                        self.push_constants[start..start + 4].copy_from_slice(&word.to_le_bytes());
+                    }
+                }
+            }
+            Command::SetRenderPipeline(ref pipeline) => {
+                self.render_pipeline = Some(Arc::clone(pipeline));
+            }
+            Command::SetIndexBuffer(ref slice, format) => {
+                self.index_buffer = Some((slice.clone(), format));
+            }
+            Command::SetVertexBuffer(index, ref slice) => {
+                let index = index as usize;
+                if self.vertex_buffers.len() <= index {
+                    self.vertex_buffers.resize(index + 1, None);
+                }
+                self.vertex_buffers[index] = Some(slice.clone());
+            }
+            Command::SetViewport(ref rect, ref depth_range) => {
+                if let Some(ref mut pass) = self.pass {
+                    pass.raster.viewport = rect.clone();
+                    pass.raster.depth_range = depth_range.clone();
+                }
+            }
+            Command::SetScissor(ref rect) => {
+                if let Some(ref mut pass) = self.pass {
+                    pass.raster.scissor = rect.clone();
+                }
+            }
+            Command::SetStencilReference(value) => {
+                if let Some(ref mut pass) = self.pass {
+                    pass.raster.stencil_reference = value;
+                }
+            }
+            Command::SetBlendConstants(color) => {
+                if let Some(ref mut pass) = self.pass {
+                    pass.raster.blend_constant = color;
+                }
+            }
+            Command::Draw {
+                ref vertices,
+                ref instances,
+            } => self.draw(
+                raster::Vertices::Direct(vertices.clone()),
+                instances.clone(),
+            ),
+            Command::DrawIndexed {
+                ref indices,
+                base_vertex,
+                ref instances,
+            } => self.draw(
+                raster::Vertices::Indexed {
+                    indices: indices.clone(),
+                    base_vertex,
+                },
+                instances.clone(),
+            ),
+            Command::DrawIndirect {
+                ref buffer,
+                indexed,
+                count,
+            } => {
+                for index in 0..count {
+                    self.draw_indirect(buffer, indexed, index);
+                }
+            }
+            Command::DrawIndirectCount {
+                ref buffer,
+                indexed,
+                ref count_buffer,
+                max_count,
+            } => {
+                let count = read_u32(&count_buffer.memory, count_buffer.offset as usize);
+                for index in 0..count.min(max_count) {
+                    self.draw_indirect(buffer, indexed, index);
+                }
+            }
+            Command::SetComputePipeline(ref stage) => {
+                self.compute_pipeline = Some(Arc::clone(stage));
+            }
+            Command::Dispatch(count) => self.dispatch(count),
+            Command::DispatchIndirect(ref buffer) => {
+                let base = buffer.offset as usize;
+                let count = [
+                    read_u32(&buffer.memory, base),
+                    read_u32(&buffer.memory, base + 4),
+                    read_u32(&buffer.memory, base + 8),
+                ];
+                self.dispatch(count);
+            }
+        }
+    }
+}
+
+impl crate::Queue<Api> for super::Queue {
+    unsafe fn submit(
+        &mut self,
+        command_buffers: &[&super::CommandBuffer],
+        signal_fence: Option<(&mut super::Fence, crate::FenceValue)>,
+    ) -> DeviceResult<()> {
+        for cmd_buf in command_buffers {
+            let mut state = State::new();
+            for command in cmd_buf.commands.iter() {
+                state.execute(command);
+            }
+        }
+        if let Some((fence, value)) = signal_fence {
+            fence.value.store(value, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    unsafe fn present(
+        &mut self,
+        _surface: &mut super::Surface,
+        _texture: super::Texture,
+    ) -> Result<(), crate::SurfaceError> {
+        Err(crate::SurfaceError::Other("presentation is not supported"))
+    }
+
+    unsafe fn get_timestamp_period(&self) -> f32 {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{BindGroup, BindingResource, Device, Memory, ShaderModule, Texture, TextureView},
+        ColorTarget, Command, State,
+    };
+    use crate::{Device as _, PipelineConstants};
+    use std::sync::Arc;
+
+    const EXTENT: wgt::Extent3d = wgt::Extent3d {
+        width: 4,
+        height: 4,
+        depth_or_array_layers: 1,
+    };
+
+    const SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(i32(index & 1u) * 4 - 1);
+    let y = f32(i32(index & 2u) * 2 - 1);
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+
+let SCALE: u32 = 2u;
+
+struct Data {
+    values: array<u32>,
+};
+
+@group(0) @binding(0)
+var<storage, read_write> data: Data;
+
+@compute @workgroup_size(1)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    data.values[id.x] = id.x * SCALE;
+}
+";
+
+    fn create_shader(device: &Device) -> ShaderModule {
+        let mut module = naga::front::wgsl::Parser::new().parse(SHADER).unwrap();
+        // WGSL has no syntax for overridable constants yet.
+        let scale = module
+            .constants
+            .iter()
+            .find(|(_, constant)| constant.name.as_deref() == Some("SCALE"))
+            .map(|(handle, _)| handle)
+            .unwrap();
+        module.constants.get_mut(scale).specialization = Some(0);
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+        let desc = crate::ShaderModuleDescriptor {
+            label: None,
+            runtime_checks: false,
+        };
+        unsafe {
+            device
+                .create_shader_module(
+                    &desc,
+                    crate::ShaderInput::Naga(crate::NagaShader { module, info }),
+                )
+                .unwrap()
+        }
+    }
+
+    fn create_target(device: &Device) -> (Texture, TextureView) {
+        let format = wgt::TextureFormat::Rgba8Unorm;
+        unsafe {
+            let texture = device
+                .create_texture(&crate::TextureDescriptor {
+                    label: None,
+                    size: EXTENT,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgt::TextureDimension::D2,
+                    format,
+                    usage: crate::TextureUses::COLOR_TARGET,
+                    memory_flags: crate::MemoryFlags::empty(),
+                })
+                .unwrap();
+            let view = device
+                .create_texture_view(
+                    &texture,
+                    &crate::TextureViewDescriptor {
+                        label: None,
+                        format,
+                        dimension: wgt::TextureViewDimension::D2,
+                        usage: crate::TextureUses::COLOR_TARGET,
+                        range: wgt::ImageSubresourceRange::default(),
+                    },
+                )
+                .unwrap();
+            (texture, view)
+        }
+    }
+
+    /// Runs a render pass with the test pipeline, that clears the target to black.
+    fn render(draw: Command) -> Texture {
+        let device = Device;
+        let module = create_shader(&device);
+        let constants = PipelineConstants::default();
+        let pipeline = unsafe {
+            device
+                .create_render_pipeline(&crate::RenderPipelineDescriptor {
+                    label: None,
+                    layout: &super::super::PipelineLayout,
+                    vertex_buffers: &[],
+                    vertex_stage: crate::ProgrammableStage {
+                        module: &module,
+                        entry_point: "vs_main",
+                        constants: &constants,
+                    },
+                    primitive: wgt::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgt::MultisampleState::default(),
+                    fragment_stage: Some(crate::ProgrammableStage {
+                        module: &module,
+                        entry_point: "fs_main",
+                        constants: &constants,
+                    }),
+                    color_targets: &[wgt::ColorTargetState {
+                        format: wgt::TextureFormat::Rgba8Unorm,
+                        blend: None,
+                        write_mask: wgt::ColorWrites::ALL,
+                    }],
+                    multiview: None,
+                    cache: None,
+                })
+                .unwrap()
+        };
+        let (texture, view) = create_target(&device);
+
+        let mut state = State::new();
+        for command in [
+            Command::BeginRenderPass {
+                colors: vec![ColorTarget {
+                    view,
+                    resolve: None,
+                    clear: Some(wgt::Color::BLACK),
+                }],
+                depth_stencil: None,
+                extent: EXTENT,
+            },
+            Command::SetRenderPipeline(pipeline.inner),
+            draw,
+            Command::EndRenderPass,
+        ]
+        .iter()
+        {
+            state.execute(command);
+        }
+        texture
+    }
+
+    fn texels(texture: &Texture) -> Vec<[u8; 4]> {
+        let mut texels = Vec::new();
+        for y in 0..EXTENT.height {
+            for x in 0..EXTENT.width {
+                let bytes = unsafe { texture.inner.texel(0, 0, x, y) };
+                texels.push([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+        }
+        texels
+    }
+
+    fn indirect_buffer(args: &[u32]) -> super::BufferSlice {
+        let bytes = args.iter().flat_map(|arg| arg.to_le_bytes()).collect();
+        super::BufferSlice {
+            memory: Arc::new(Memory::from_bytes(bytes)),
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn draw_covers_target() {
+        let texture = render(Command::Draw {
+            vertices: 0..3,
+            instances: 0..1,
+        });
+        assert!(texels(&texture).iter().all(|&t| t == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn draw_indirect() {
+        let texture = render(Command::DrawIndirect {
+            buffer: indirect_buffer(&[3, 1, 0, 0]),
+            indexed: false,
+            count: 1,
+        });
+        assert!(texels(&texture).iter().all(|&t| t == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn draw_indirect_overflow() {
+        for args in [[3, 1, u32::MAX, 0], [3, 2, 0, u32::MAX]].iter() {
+            let texture = render(Command::DrawIndirect {
+                buffer: indirect_buffer(args),
+                indexed: false,
+                count: 1,
+            });
+            assert!(texels(&texture).iter().all(|&t| t == [0, 0, 0, 255]));
+        }
+    }
+
+    /// Runs 4 invocations of the compute entry point, returning the values it writes.
+    fn dispatch(constants: &[(&str, f64)]) -> Vec<u32> {
+        let device = Device;
+        let module = create_shader(&device);
+        let constants: PipelineConstants = constants
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value))
+            .collect();
+        let pipeline = unsafe {
+            device
+                .create_compute_pipeline(&crate::ComputePipelineDescriptor {
+                    label: None,
+                    layout: &super::super::PipelineLayout,
+                    stage: crate::ProgrammableStage {
+                        module: &module,
+                        entry_point: "cs_main",
+                        constants: &constants,
+                    },
+                    cache: None,
+                })
+                .unwrap()
+        };
+        let memory = Arc::new(Memory::new(16));
+        let group = BindGroup {
+            entries: Arc::from(vec![(
+                0,
+                BindingResource::Buffer {
+                    memory: Arc::clone(&memory),
+                    offset: 0,
+                    size: 16,
+                    dynamic_index: None,
+                },
+            )]),
+        };
+
+        let mut state = State::new();
+        for command in [
+            Command::SetComputePipeline(pipeline.inner),
+            Command::SetBindGroup {
+                index: 0,
+                group,
+                dynamic_offsets: Vec::new(),
+            },
+            Command::DispatchIndirect(indirect_buffer(&[4, 1, 1])),
+        ]
+        .iter()
+        {
+            state.execute(command);
+        }
+
+        let bytes = unsafe { memory.bytes(0..16) };
+        bytes
+            .chunks(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()
+    }
+
+    #[test]
+    fn dispatch_writes_storage() {
+        assert_eq!(dispatch(&[]), [0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn pipeline_constants() {
+        assert_eq!(dispatch(&[("SCALE", 3.0)]), [0, 3, 6, 9]);
+        assert_eq!(dispatch(&[("0", 5.0)]), [0, 5, 10, 15]);
+    }
+}
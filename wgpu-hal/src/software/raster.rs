@@ -0,0 +1,938 @@
+//! Primitive assembly, rasterization and per-fragment operations.
+
+#![allow(clippy::needless_range_loop)]
+
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use arrayvec::ArrayVec;
+
+use super::{
+    image,
+    shader::{Bindings, Fault, Scalar, Stage, Value},
+    texel::{self, Texel},
+    Memory, TextureView,
+};
+
+pub(super) struct VertexBufferLayout {
+    array_stride: wgt::BufferAddress,
+    step_mode: wgt::VertexStepMode,
+    attributes: Vec<wgt::VertexAttribute>,
+}
+
+pub(super) struct Pipeline {
+    pub vertex: Stage,
+    pub fragment: Option<Stage>,
+    vertex_buffers: Vec<VertexBufferLayout>,
+    primitive: wgt::PrimitiveState,
+    depth_stencil: Option<wgt::DepthStencilState>,
+    sample_mask: u64,
+    color_targets: Vec<wgt::ColorTargetState>,
+    /// Interpolation of every location read by the fragment stage.
+    interpolation: HashMap<u32, naga::Interpolation>,
+}
+
+impl Pipeline {
+    pub fn new(
+        vertex: Stage,
+        fragment: Option<Stage>,
+        desc: &crate::RenderPipelineDescriptor<super::Api>,
+    ) -> Self {
+        let mut interpolation = HashMap::new();
+        if let Some(ref fragment) = fragment {
+            fragment.arguments(|binding, inner| {
+                if let naga::Binding::Location {
+                    location,
+                    interpolation: mode,
+                    ..
+                } = *binding
+                {
+                    let is_float = matches!(
+                        *inner,
+                        naga::TypeInner::Scalar {
+                            kind: naga::ScalarKind::Float,
+                            ..
+                        } | naga::TypeInner::Vector {
+                            kind: naga::ScalarKind::Float,
+                            ..
+                        }
+                    );
+                    let mode = match mode {
+                        Some(mode) => mode,
+                        None if is_float => naga::Interpolation::Perspective,
+                        None => naga::Interpolation::Flat,
+                    };
+                    interpolation.insert(location, mode);
+                }
+                Value::Composite(Vec::new())
+            });
+        }
+
+        Self {
+            vertex,
+            fragment,
+            vertex_buffers: desc
+                .vertex_buffers
+                .iter()
+                .map(|vb| VertexBufferLayout {
+                    array_stride: vb.array_stride,
+                    step_mode: vb.step_mode,
+                    attributes: vb.attributes.to_vec(),
+                })
+                .collect(),
+            primitive: desc.primitive,
+            depth_stencil: desc.depth_stencil.clone(),
+            sample_mask: desc.multisample.mask,
+            color_targets: desc.color_targets.to_vec(),
+            interpolation,
+        }
+    }
+}
+
+/// Buffer bound for vertex or index fetching.
+#[derive(Clone, Debug)]
+pub(super) struct BufferSlice {
+    pub memory: Arc<Memory>,
+    pub offset: wgt::BufferAddress,
+}
+
+impl BufferSlice {
+    /// Reads bytes at the given offset, returning `None` if they are out of bounds.
+    fn read(&self, offset: wgt::BufferAddress, size: usize) -> Option<&[u8]> {
+        let start = (self.offset + offset) as usize;
+        if start + size > self.memory.len() {
+            return None;
+        }
+        Some(unsafe { self.memory.bytes(start..start + size) })
+    }
+}
+
+/// State of the render pass being executed.
+pub(super) struct Pass {
+    pub colors: Vec<Option<TextureView>>,
+    pub depth_stencil: Option<TextureView>,
+    pub extent: wgt::Extent3d,
+    pub viewport: crate::Rect<f32>,
+    pub depth_range: Range<f32>,
+    pub scissor: crate::Rect<u32>,
+    pub stencil_reference: u32,
+    pub blend_constant: [f32; 4],
+    /// Number of samples that passed the depth and stencil tests,
+    /// while an occlusion query is active.
+    pub occlusion_count: Option<u64>,
+}
+
+pub(super) enum Vertices {
+    Direct(Range<u32>),
+    Indexed {
+        indices: Range<u32>,
+        base_vertex: i32,
+    },
+}
+
+/// Everything a draw call reads, besides the pass itself.
+pub(super) struct Draw<'a> {
+    pub pipeline: &'a Pipeline,
+    pub vertex_bindings: &'a Bindings,
+    pub fragment_bindings: Option<&'a Bindings>,
+    pub vertex_buffers: &'a [Option<BufferSlice>],
+    pub index_buffer: Option<&'a (BufferSlice, wgt::IndexFormat)>,
+}
+
+struct ShadedVertex {
+    clip: [f32; 4],
+    varyings: Vec<(u32, ArrayVec<Scalar, 4>)>,
+}
+
+/// Vertex after the perspective divide and the viewport transform.
+struct ScreenVertex<'a> {
+    x: f32,
+    y: f32,
+    /// Depth in normalized device coordinates.
+    z: f32,
+    inv_w: f32,
+    varyings: &'a [(u32, ArrayVec<Scalar, 4>)],
+}
+
+struct Fragment {
+    x: u32,
+    y: u32,
+    z: f32,
+    inv_w: f32,
+    front_facing: bool,
+    varyings: Vec<(u32, ArrayVec<Scalar, 4>)>,
+}
+
+fn fetch(format: wgt::VertexFormat, bytes: &[u8]) -> ArrayVec<Scalar, 4> {
+    use wgt::VertexFormat as Vf;
+
+    let u8_at = |i: usize| bytes[i];
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[2 * i], bytes[2 * i + 1]]);
+    let u32_at = |i: usize| {
+        u32::from_le_bytes([
+            bytes[4 * i],
+            bytes[4 * i + 1],
+            bytes[4 * i + 2],
+            bytes[4 * i + 3],
+        ])
+    };
+    let f64_at = |i: usize| {
+        let mut raw = [0; 8];
+        raw.copy_from_slice(&bytes[8 * i..8 * i + 8]);
+        f64::from_le_bytes(raw) as f32
+    };
+    let (count, fun): (usize, &dyn Fn(usize) -> Scalar) = match format {
+        Vf::Uint8x2 => (2, &|i| Scalar::U32(u8_at(i) as u32)),
+        Vf::Uint8x4 => (4, &|i| Scalar::U32(u8_at(i) as u32)),
+        Vf::Sint8x2 => (2, &|i| Scalar::I32(u8_at(i) as i8 as i32)),
+        Vf::Sint8x4 => (4, &|i| Scalar::I32(u8_at(i) as i8 as i32)),
+        Vf::Unorm8x2 => (2, &|i| Scalar::F32(u8_at(i) as f32 / 255.0)),
+        Vf::Unorm8x4 => (4, &|i| Scalar::F32(u8_at(i) as f32 / 255.0)),
+        Vf::Snorm8x2 => (2, &|i| {
+            Scalar::F32((u8_at(i) as i8 as f32 / 127.0).max(-1.0))
+        }),
+        Vf::Snorm8x4 => (4, &|i| {
+            Scalar::F32((u8_at(i) as i8 as f32 / 127.0).max(-1.0))
+        }),
+        Vf::Uint16x2 => (2, &|i| Scalar::U32(u16_at(i) as u32)),
+        Vf::Uint16x4 => (4, &|i| Scalar::U32(u16_at(i) as u32)),
+        Vf::Sint16x2 => (2, &|i| Scalar::I32(u16_at(i) as i16 as i32)),
+        Vf::Sint16x4 => (4, &|i| Scalar::I32(u16_at(i) as i16 as i32)),
+        Vf::Unorm16x2 => (2, &|i| Scalar::F32(u16_at(i) as f32 / 65535.0)),
+        Vf::Unorm16x4 => (4, &|i| Scalar::F32(u16_at(i) as f32 / 65535.0)),
+        Vf::Snorm16x2 => (2, &|i| {
+            Scalar::F32((u16_at(i) as i16 as f32 / 32767.0).max(-1.0))
+        }),
+        Vf::Snorm16x4 => (4, &|i| {
+            Scalar::F32((u16_at(i) as i16 as f32 / 32767.0).max(-1.0))
+        }),
+        Vf::Float16x2 => (2, &|i| Scalar::F32(texel::f16_to_f32(u16_at(i)))),
+        Vf::Float16x4 => (4, &|i| Scalar::F32(texel::f16_to_f32(u16_at(i)))),
+        Vf::Float32 => (1, &|i| Scalar::F32(f32::from_bits(u32_at(i)))),
+        Vf::Float32x2 => (2, &|i| Scalar::F32(f32::from_bits(u32_at(i)))),
+        Vf::Float32x3 => (3, &|i| Scalar::F32(f32::from_bits(u32_at(i)))),
+        Vf::Float32x4 => (4, &|i| Scalar::F32(f32::from_bits(u32_at(i)))),
+        Vf::Uint32 => (1, &|i| Scalar::U32(u32_at(i))),
+        Vf::Uint32x2 => (2, &|i| Scalar::U32(u32_at(i))),
+        Vf::Uint32x3 => (3, &|i| Scalar::U32(u32_at(i))),
+        Vf::Uint32x4 => (4, &|i| Scalar::U32(u32_at(i))),
+        Vf::Sint32 => (1, &|i| Scalar::I32(u32_at(i) as i32)),
+        Vf::Sint32x2 => (2, &|i| Scalar::I32(u32_at(i) as i32)),
+        Vf::Sint32x3 => (3, &|i| Scalar::I32(u32_at(i) as i32)),
+        Vf::Sint32x4 => (4, &|i| Scalar::I32(u32_at(i) as i32)),
+        Vf::Float64 => (1, &|i| Scalar::F32(f64_at(i))),
+        Vf::Float64x2 => (2, &|i| Scalar::F32(f64_at(i))),
+        Vf::Float64x3 => (3, &|i| Scalar::F32(f64_at(i))),
+        Vf::Float64x4 => (4, &|i| Scalar::F32(f64_at(i))),
+    };
+    (0..count).map(fun).collect()
+}
+
+fn zero_components(format: wgt::VertexFormat) -> ArrayVec<Scalar, 4> {
+    let zeroes = vec![0u8; format.size() as usize];
+    fetch(format, &zeroes)
+}
+
+fn edge(a: (f32, f32), b: (f32, f32), p: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+/// Whether an edge of a triangle with positive area is a top or a left edge.
+fn is_top_left(a: (f32, f32), b: (f32, f32)) -> bool {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    (dy == 0.0 && dx > 0.0) || dy < 0.0
+}
+
+fn stencil_op(op: wgt::StencilOperation, value: u32, reference: u32) -> u32 {
+    use wgt::StencilOperation as So;
+    match op {
+        So::Keep => value,
+        So::Zero => 0,
+        So::Replace => reference,
+        So::Invert => !value,
+        So::IncrementClamp => (value + 1).min(0xFF),
+        So::DecrementClamp => value.saturating_sub(1),
+        So::IncrementWrap => (value + 1) & 0xFF,
+        So::DecrementWrap => value.wrapping_sub(1) & 0xFF,
+    }
+}
+
+fn blend_factor(
+    factor: wgt::BlendFactor,
+    channel: usize,
+    src: &[f32; 4],
+    dst: &[f32; 4],
+    constant: &[f32; 4],
+) -> f32 {
+    use wgt::BlendFactor as Bf;
+    match factor {
+        Bf::Zero => 0.0,
+        Bf::One => 1.0,
+        Bf::Src => src[channel],
+        Bf::OneMinusSrc => 1.0 - src[channel],
+        Bf::SrcAlpha => src[3],
+        Bf::OneMinusSrcAlpha => 1.0 - src[3],
+        Bf::Dst => dst[channel],
+        Bf::OneMinusDst => 1.0 - dst[channel],
+        Bf::DstAlpha => dst[3],
+        Bf::OneMinusDstAlpha => 1.0 - dst[3],
+        Bf::SrcAlphaSaturated => {
+            if channel == 3 {
+                1.0
+            } else {
+                src[3].min(1.0 - dst[3])
+            }
+        }
+        Bf::Constant => constant[channel],
+        Bf::OneMinusConstant => 1.0 - constant[channel],
+    }
+}
+
+fn blend(state: &wgt::BlendState, src: [f32; 4], dst: [f32; 4], constant: &[f32; 4]) -> [f32; 4] {
+    let mut result = [0.0; 4];
+    for channel in 0..4 {
+        let component = if channel == 3 {
+            &state.alpha
+        } else {
+            &state.color
+        };
+        let s = src[channel] * blend_factor(component.src_factor, channel, &src, &dst, constant);
+        let d = dst[channel] * blend_factor(component.dst_factor, channel, &src, &dst, constant);
+        result[channel] = match component.operation {
+            wgt::BlendOperation::Add => s + d,
+            wgt::BlendOperation::Subtract => s - d,
+            wgt::BlendOperation::ReverseSubtract => d - s,
+            wgt::BlendOperation::Min => src[channel].min(dst[channel]),
+            wgt::BlendOperation::Max => src[channel].max(dst[channel]),
+        };
+    }
+    result
+}
+
+impl Draw<'_> {
+    fn read_index(&self, index: u32) -> Option<u32> {
+        let &(ref buffer, format) = self.index_buffer?;
+        let restart = self.pipeline.primitive.strip_index_format.is_some();
+        match format {
+            wgt::IndexFormat::Uint16 => {
+                let bytes = buffer.read(index as wgt::BufferAddress * 2, 2)?;
+                let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+                if restart && value == !0 {
+                    None
+                } else {
+                    Some(value as u32)
+                }
+            }
+            wgt::IndexFormat::Uint32 => {
+                let bytes = buffer.read(index as wgt::BufferAddress * 4, 4)?;
+                let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                if restart && value == !0 {
+                    None
+                } else {
+                    Some(value)
+                }
+            }
+        }
+    }
+
+    fn shade_vertex(&self, vertex_index: u32, instance_index: u32) -> Result<ShadedVertex, Fault> {
+        let stage = &self.pipeline.vertex;
+        let arguments =
+            stage.arguments(|binding, inner| match *binding {
+                naga::Binding::BuiltIn(naga::BuiltIn::VertexIndex) => {
+                    Value::Scalar(Scalar::U32(vertex_index))
+                }
+                naga::Binding::BuiltIn(naga::BuiltIn::InstanceIndex) => {
+                    Value::Scalar(Scalar::U32(instance_index))
+                }
+                naga::Binding::Location { location, .. } => {
+                    let found = self.pipeline.vertex_buffers.iter().enumerate().find_map(
+                        |(slot, layout)| {
+                            layout
+                                .attributes
+                                .iter()
+                                .find(|attribute| attribute.shader_location == location)
+                                .map(|attribute| (slot, layout, attribute))
+                        },
+                    );
+                    let components = match found {
+                        Some((slot, layout, attribute)) => {
+                            let index = match layout.step_mode {
+                                wgt::VertexStepMode::Vertex => vertex_index,
+                                wgt::VertexStepMode::Instance => instance_index,
+                            };
+                            let offset = layout.array_stride * index as wgt::BufferAddress
+                                + attribute.offset;
+                            let size = attribute.format.size() as usize;
+                            match self.vertex_buffers.get(slot) {
+                                Some(&Some(ref buffer)) => match buffer.read(offset, size) {
+                                    Some(bytes) => fetch(attribute.format, bytes),
+                                    None => zero_components(attribute.format),
+                                },
+                                _ => zero_components(attribute.format),
+                            }
+                        }
+                        None => ArrayVec::new(),
+                    };
+                    Value::from_components(inner, &components)
+                }
+                _ => Value::Scalar(Scalar::U32(0)),
+            });
+
+        let result = stage
+            .invoke(self.vertex_bindings, arguments)?
+            .and_then(|result| result);
+        let mut vertex = ShadedVertex {
+            clip: [0.0; 4],
+            varyings: Vec::new(),
+        };
+        for (binding, value) in stage.outputs(result) {
+            match binding {
+                naga::Binding::BuiltIn(naga::BuiltIn::Position { .. }) => {
+                    for (c, s) in vertex.clip.iter_mut().zip(value.components().iter()) {
+                        *c = s.as_f32();
+                    }
+                }
+                naga::Binding::Location { location, .. } => {
+                    vertex.varyings.push((location, value.components()));
+                }
+                _ => {}
+            }
+        }
+        Ok(vertex)
+    }
+
+    /// Executes the draw for every instance.
+    pub fn run(
+        &self,
+        pass: &mut Pass,
+        vertices: Vertices,
+        instances: Range<u32>,
+    ) -> Result<(), Fault> {
+        for instance in instances {
+            let ids: Vec<Option<u32>> = match vertices {
+                Vertices::Direct(ref range) => range.clone().map(Some).collect(),
+                Vertices::Indexed {
+                    ref indices,
+                    base_vertex,
+                } => indices
+                    .clone()
+                    .map(|i| {
+                        self.read_index(i)
+                            .map(|index| (index as i32).wrapping_add(base_vertex) as u32)
+                    })
+                    .collect(),
+            };
+
+            let mut cache = HashMap::new();
+            for &id in ids.iter().flatten() {
+                if let std::collections::hash_map::Entry::Vacant(entry) = cache.entry(id) {
+                    entry.insert(self.shade_vertex(id, instance)?);
+                }
+            }
+
+            let screen = |id: u32| -> Option<ScreenVertex> {
+                let vertex = &cache[&id];
+                let [x, y, z, w] = vertex.clip;
+                if w <= 0.0 {
+                    return None;
+                }
+                let viewport = &pass.viewport;
+                Some(ScreenVertex {
+                    x: viewport.x + (x / w + 1.0) * 0.5 * viewport.w,
+                    y: viewport.y + (1.0 - y / w) * 0.5 * viewport.h,
+                    z: z / w,
+                    inv_w: 1.0 / w,
+                    varyings: &vertex.varyings,
+                })
+            };
+
+            for strip in ids.split(Option::is_none) {
+                let strip: Vec<u32> = strip.iter().flatten().copied().collect();
+                let mut primitives: Vec<ArrayVec<u32, 3>> = Vec::new();
+                match self.pipeline.primitive.topology {
+                    wgt::PrimitiveTopology::PointList => {
+                        primitives.extend(strip.iter().map(|&v| [v].iter().copied().collect()))
+                    }
+                    wgt::PrimitiveTopology::LineList => primitives
+                        .extend(strip.chunks_exact(2).map(|c| c.iter().copied().collect())),
+                    wgt::PrimitiveTopology::LineStrip => {
+                        primitives.extend(strip.windows(2).map(|c| c.iter().copied().collect()))
+                    }
+                    wgt::PrimitiveTopology::TriangleList => primitives
+                        .extend(strip.chunks_exact(3).map(|c| c.iter().copied().collect())),
+                    wgt::PrimitiveTopology::TriangleStrip => {
+                        for (i, c) in strip.windows(3).enumerate() {
+                            let order = if i % 2 == 0 {
+                                [c[0], c[1], c[2]]
+                            } else {
+                                [c[1], c[0], c[2]]
+                            };
+                            primitives.push(order.iter().copied().collect());
+                        }
+                    }
+                }
+
+                for primitive in primitives {
+                    let vertices = match primitive
+                        .iter()
+                        .map(|&id| screen(id))
+                        .collect::<Option<ArrayVec<_, 3>>>()
+                    {
+                        Some(vertices) => vertices,
+                        // A vertex is behind the eye.
+                        None => continue,
+                    };
+                    match vertices.len() {
+                        1 => self.point(pass, &vertices[0], true)?,
+                        2 => self.line(pass, &vertices[0], &vertices[1], true)?,
+                        _ => self.triangle(pass, &vertices[0], &vertices[1], &vertices[2])?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pixel bounds that fragments can be written to, as `(x range, y range)`.
+    fn bounds(&self, pass: &Pass) -> (Range<i64>, Range<i64>) {
+        let viewport = &pass.viewport;
+        let scissor = &pass.scissor;
+        let x_start = (scissor.x as i64).max(viewport.x.floor() as i64).max(0);
+        let y_start = (scissor.y as i64).max(viewport.y.floor() as i64).max(0);
+        let x_end = ((scissor.x + scissor.w) as i64)
+            .min((viewport.x + viewport.w).ceil() as i64)
+            .min(pass.extent.width as i64);
+        let y_end = ((scissor.y + scissor.h) as i64)
+            .min((viewport.y + viewport.h).ceil() as i64)
+            .min(pass.extent.height as i64);
+        (x_start..x_end, y_start..y_end)
+    }
+
+    fn interpolate(
+        &self,
+        vertices: &[&ScreenVertex],
+        weights: &[f32],
+    ) -> Vec<(u32, ArrayVec<Scalar, 4>)> {
+        let first = vertices[0];
+        let perspective_norm: f32 = vertices
+            .iter()
+            .zip(weights.iter())
+            .map(|(v, &b)| b * v.inv_w)
+            .sum();
+        first
+            .varyings
+            .iter()
+            .enumerate()
+            .map(|(i, &(location, ref components))| {
+                let mode = self
+                    .pipeline
+                    .interpolation
+                    .get(&location)
+                    .copied()
+                    .unwrap_or(naga::Interpolation::Flat);
+                let is_float = matches!(components.first(), Some(&Scalar::F32(_)));
+                if mode == naga::Interpolation::Flat || !is_float || vertices.len() == 1 {
+                    return (location, components.clone());
+                }
+                let result = (0..components.len())
+                    .map(|c| {
+                        let mut sum = 0.0;
+                        for (v, &b) in vertices.iter().zip(weights.iter()) {
+                            let value = v
+                                .varyings
+                                .get(i)
+                                .and_then(|&(_, ref comps)| comps.get(c))
+                                .map_or(0.0, |s| s.as_f32());
+                            sum += match mode {
+                                naga::Interpolation::Perspective => b * value * v.inv_w,
+                                _ => b * value,
+                            };
+                        }
+                        Scalar::F32(match mode {
+                            naga::Interpolation::Perspective => sum / perspective_norm,
+                            _ => sum,
+                        })
+                    })
+                    .collect();
+                (location, result)
+            })
+            .collect()
+    }
+
+    fn point(&self, pass: &mut Pass, v: &ScreenVertex, front_facing: bool) -> Result<(), Fault> {
+        let (xs, ys) = self.bounds(pass);
+        let (x, y) = (v.x.floor() as i64, v.y.floor() as i64);
+        if !xs.contains(&x) || !ys.contains(&y) {
+            return Ok(());
+        }
+        let fragment = Fragment {
+            x: x as u32,
+            y: y as u32,
+            z: v.z,
+            inv_w: v.inv_w,
+            front_facing,
+            varyings: self.interpolate(&[v], &[1.0]),
+        };
+        self.fragment(pass, fragment, 0.0)
+    }
+
+    fn line(
+        &self,
+        pass: &mut Pass,
+        a: &ScreenVertex,
+        b: &ScreenVertex,
+        front_facing: bool,
+    ) -> Result<(), Fault> {
+        let (xs, ys) = self.bounds(pass);
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as u32;
+        for step in 0..steps {
+            let t = (step as f32 + 0.5) / steps as f32;
+            let (x, y) = ((a.x + dx * t).floor() as i64, (a.y + dy * t).floor() as i64);
+            if !xs.contains(&x) || !ys.contains(&y) {
+                continue;
+            }
+            let weights = [1.0 - t, t];
+            let fragment = Fragment {
+                x: x as u32,
+                y: y as u32,
+                z: a.z * weights[0] + b.z * weights[1],
+                inv_w: a.inv_w * weights[0] + b.inv_w * weights[1],
+                front_facing,
+                varyings: self.interpolate(&[a, b], &weights),
+            };
+            self.fragment(pass, fragment, 0.0)?;
+        }
+        Ok(())
+    }
+
+    fn triangle(
+        &self,
+        pass: &mut Pass,
+        v0: &ScreenVertex,
+        v1: &ScreenVertex,
+        v2: &ScreenVertex,
+    ) -> Result<(), Fault> {
+        let primitive = &self.pipeline.primitive;
+        let p = |v: &ScreenVertex| (v.x, v.y);
+        let area = edge(p(v0), p(v1), p(v2));
+        if area == 0.0 {
+            return Ok(());
+        }
+
+        // The viewport transform flips Y, so counter-clockwise triangles
+        // have a negative area in framebuffer coordinates.
+        let is_ccw = area < 0.0;
+        let front_facing = is_ccw == (primitive.front_face == wgt::FrontFace::Ccw);
+        match primitive.cull_mode {
+            Some(wgt::Face::Front) if front_facing => return Ok(()),
+            Some(wgt::Face::Back) if !front_facing => return Ok(()),
+            _ => {}
+        }
+
+        match primitive.polygon_mode {
+            wgt::PolygonMode::Fill => {}
+            wgt::PolygonMode::Line => {
+                self.line(pass, v0, v1, front_facing)?;
+                self.line(pass, v1, v2, front_facing)?;
+                return self.line(pass, v2, v0, front_facing);
+            }
+            wgt::PolygonMode::Point => {
+                self.point(pass, v0, front_facing)?;
+                self.point(pass, v1, front_facing)?;
+                return self.point(pass, v2, front_facing);
+            }
+        }
+
+        // Reorder the vertices so that the area is positive, keeping the
+        // provoking vertex first.
+        let (v1, v2, area) = if area < 0.0 {
+            (v2, v1, -area)
+        } else {
+            (v1, v2, area)
+        };
+        let vertices = [v0, v1, v2];
+
+        let slope = {
+            let (x1, y1, z1) = (v1.x - v0.x, v1.y - v0.y, v1.z - v0.z);
+            let (x2, y2, z2) = (v2.x - v0.x, v2.y - v0.y, v2.z - v0.z);
+            let dzdx = (z1 * y2 - z2 * y1) / area;
+            let dzdy = (x1 * z2 - x2 * z1) / area;
+            dzdx.abs().max(dzdy.abs())
+        };
+        let bias = match self.pipeline.depth_stencil {
+            Some(ref ds) if ds.bias.is_enabled() => {
+                let bias = ds.bias.constant as f32 / (1 << 24) as f32 + ds.bias.slope_scale * slope;
+                if ds.bias.clamp > 0.0 {
+                    bias.min(ds.bias.clamp)
+                } else if ds.bias.clamp < 0.0 {
+                    bias.max(ds.bias.clamp)
+                } else {
+                    bias
+                }
+            }
+            _ => 0.0,
+        };
+
+        let (xs, ys) = self.bounds(pass);
+        let min_x = (v0.x.min(v1.x).min(v2.x).floor() as i64).max(xs.start);
+        let max_x = (v0.x.max(v1.x).max(v2.x).ceil() as i64).min(xs.end);
+        let min_y = (v0.y.min(v1.y).min(v2.y).floor() as i64).max(ys.start);
+        let max_y = (v0.y.max(v1.y).max(v2.y).ceil() as i64).min(ys.end);
+        let edges = [(p(v1), p(v2)), (p(v2), p(v0)), (p(v0), p(v1))];
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let center = (x as f32 + 0.5, y as f32 + 0.5);
+                let mut weights = [0.0; 3];
+                let mut inside = true;
+                for (i, &(a, b)) in edges.iter().enumerate() {
+                    let w = edge(a, b, center);
+                    if w < 0.0 || (w == 0.0 && !is_top_left(a, b)) {
+                        inside = false;
+                        break;
+                    }
+                    weights[i] = w / area;
+                }
+                if !inside {
+                    continue;
+                }
+                let z = weights[0] * v0.z + weights[1] * v1.z + weights[2] * v2.z;
+                let inv_w = weights[0] * v0.inv_w + weights[1] * v1.inv_w + weights[2] * v2.inv_w;
+                let fragment = Fragment {
+                    x: x as u32,
+                    y: y as u32,
+                    z,
+                    inv_w,
+                    front_facing,
+                    varyings: self.interpolate(&vertices, &weights),
+                };
+                self.fragment(pass, fragment, bias)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Shades a fragment and runs the per-fragment operations on it.
+    fn fragment(&self, pass: &mut Pass, fragment: Fragment, bias: f32) -> Result<(), Fault> {
+        let pipeline = self.pipeline;
+        if pipeline.sample_mask & 1 == 0 {
+            return Ok(());
+        }
+        if !pipeline.primitive.unclipped_depth && !(0.0..=1.0).contains(&fragment.z) {
+            return Ok(());
+        }
+        let mut depth = pass.depth_range.start
+            + fragment.z * (pass.depth_range.end - pass.depth_range.start)
+            + bias;
+
+        let mut colors: Vec<Option<ArrayVec<Scalar, 4>>> = vec![None; pipeline.color_targets.len()];
+        if let (Some(stage), Some(bindings)) = (pipeline.fragment.as_ref(), self.fragment_bindings)
+        {
+            let arguments = stage.arguments(|binding, inner| match *binding {
+                naga::Binding::BuiltIn(naga::BuiltIn::Position { .. }) => Value::Vector(
+                    [
+                        fragment.x as f32 + 0.5,
+                        fragment.y as f32 + 0.5,
+                        depth,
+                        fragment.inv_w,
+                    ]
+                    .iter()
+                    .map(|&c| Scalar::F32(c))
+                    .collect(),
+                ),
+                naga::Binding::BuiltIn(naga::BuiltIn::FrontFacing) => {
+                    Value::Scalar(Scalar::Bool(fragment.front_facing))
+                }
+                naga::Binding::BuiltIn(naga::BuiltIn::SampleIndex) => Value::Scalar(Scalar::U32(0)),
+                naga::Binding::BuiltIn(naga::BuiltIn::SampleMask) => Value::Scalar(Scalar::U32(1)),
+                naga::Binding::Location { location, .. } => {
+                    let components = fragment
+                        .varyings
+                        .iter()
+                        .find(|&&(l, _)| l == location)
+                        .map(|&(_, ref components)| components.clone())
+                        .unwrap_or_default();
+                    Value::from_components(inner, &components)
+                }
+                _ => Value::Scalar(Scalar::U32(0)),
+            });
+            let result = match stage.invoke(bindings, arguments)? {
+                Some(result) => result,
+                None => return Ok(()),
+            };
+            for (binding, value) in stage.outputs(result) {
+                match binding {
+                    naga::Binding::BuiltIn(naga::BuiltIn::FragDepth) => {
+                        if let Value::Scalar(s) = value {
+                            depth = s.as_f32();
+                        }
+                    }
+                    naga::Binding::BuiltIn(naga::BuiltIn::SampleMask) => {
+                        if let Value::Scalar(s) = value {
+                            if s.as_u32() & 1 == 0 {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    naga::Binding::Location { location, .. } => {
+                        if let Some(color) = colors.get_mut(location as usize) {
+                            *color = Some(value.components());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let (Some(view), Some(ds)) =
+            (pass.depth_stencil.as_ref(), pipeline.depth_stencil.as_ref())
+        {
+            let (x, y) = (fragment.x, fragment.y);
+            let level = view.mip_levels.start;
+            let layer = view.array_layers.start;
+            let bytes = unsafe { view.texture.texel_mut(level, layer, x, y) };
+            let format = view.texture.format;
+            let has_depth = view.aspects.contains(crate::FormatAspects::DEPTH);
+            let has_stencil = view.aspects.contains(crate::FormatAspects::STENCIL);
+
+            let face = if fragment.front_facing {
+                &ds.stencil.front
+            } else {
+                &ds.stencil.back
+            };
+            let reference = pass.stencil_reference & 0xFF;
+            let stencil = texel::read_stencil(format, bytes);
+            let stencil_passed = !has_stencil
+                || image::compare(
+                    face.compare,
+                    (reference & ds.stencil.read_mask) as f32,
+                    (stencil & ds.stencil.read_mask) as f32,
+                );
+            let depth = depth.max(0.0).min(1.0);
+            let depth_passed =
+                !has_depth || image::compare(ds.depth_compare, depth, texel::read_depth(bytes));
+
+            if has_stencil {
+                let op = if !stencil_passed {
+                    face.fail_op
+                } else if !depth_passed {
+                    face.depth_fail_op
+                } else {
+                    face.pass_op
+                };
+                let new = stencil_op(op, stencil, reference);
+                let mask = ds.stencil.write_mask;
+                texel::write_stencil(format, bytes, (stencil & !mask) | (new & mask));
+            }
+            if !stencil_passed || !depth_passed {
+                return Ok(());
+            }
+            if has_depth && ds.depth_write_enabled {
+                texel::write_depth(bytes, depth);
+            }
+        }
+
+        if let Some(ref mut count) = pass.occlusion_count {
+            *count += 1;
+        }
+
+        for ((target, view), color) in pipeline
+            .color_targets
+            .iter()
+            .zip(pass.colors.iter())
+            .zip(colors.into_iter())
+        {
+            let (view, color) = match (view, color) {
+                (&Some(ref view), Some(color)) => (view, color),
+                _ => continue,
+            };
+            let coords = [fragment.x as i32, fragment.y as i32, 0];
+            let old = image::load(view, coords, 0);
+            let mask = target.write_mask.bits();
+            let written = |c: usize| mask & (1 << c) != 0;
+            let value = match old {
+                Texel::Float(dst) => {
+                    let mut src = [0.0, 0.0, 0.0, 1.0];
+                    for (s, c) in src.iter_mut().zip(color.iter()) {
+                        *s = c.as_f32();
+                    }
+                    let src = match target.blend {
+                        Some(ref state) => blend(state, src, dst, &pass.blend_constant),
+                        None => src,
+                    };
+                    let mut result = dst;
+                    for c in 0..4 {
+                        if written(c) {
+                            result[c] = src[c];
+                        }
+                    }
+                    Texel::Float(result)
+                }
+                Texel::Sint(mut result) => {
+                    for c in 0..color.len().min(4) {
+                        if written(c) {
+                            result[c] = color[c].as_i32();
+                        }
+                    }
+                    Texel::Sint(result)
+                }
+                Texel::Uint(mut result) => {
+                    for c in 0..color.len().min(4) {
+                        if written(c) {
+                            result[c] = color[c].as_u32();
+                        }
+                    }
+                    Texel::Uint(result)
+                }
+            };
+            image::store(view, coords, value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blend, edge, is_top_left, stencil_op};
+
+    #[test]
+    fn edge_orientation() {
+        let (a, b) = ((0.0, 0.0), (4.0, 0.0));
+        assert!(edge(a, b, (1.0, 1.0)) > 0.0);
+        assert!(edge(a, b, (1.0, -1.0)) < 0.0);
+        assert_eq!(edge(a, b, (2.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn top_left_rule() {
+        // Horizontal edges going right and edges going up are top-left edges.
+        assert!(is_top_left((0.0, 0.0), (4.0, 0.0)));
+        assert!(is_top_left((0.0, 4.0), (0.0, 0.0)));
+        assert!(!is_top_left((4.0, 4.0), (0.0, 4.0)));
+        assert!(!is_top_left((4.0, 0.0), (4.0, 4.0)));
+    }
+
+    #[test]
+    fn stencil_ops() {
+        use wgt::StencilOperation as So;
+        assert_eq!(stencil_op(So::Keep, 5, 7), 5);
+        assert_eq!(stencil_op(So::Replace, 5, 7), 7);
+        assert_eq!(stencil_op(So::IncrementClamp, 0xFF, 0), 0xFF);
+        assert_eq!(stencil_op(So::DecrementClamp, 0, 0), 0);
+        assert_eq!(stencil_op(So::IncrementWrap, 0xFF, 0), 0);
+        assert_eq!(stencil_op(So::DecrementWrap, 0, 0), 0xFF);
+        assert_eq!(stencil_op(So::Invert, 0, 0) & 0xFF, 0xFF);
+    }
+
+    #[test]
+    fn alpha_blending() {
+        let src = [1.0, 0.0, 0.0, 0.25];
+        let dst = [0.0, 1.0, 0.0, 1.0];
+        let result = blend(&wgt::BlendState::ALPHA_BLENDING, src, dst, &[0.0; 4]);
+        assert_eq!(result, [0.25, 0.75, 0.0, 1.0]);
+
+        let replace = blend(&wgt::BlendState::REPLACE, src, dst, &[0.0; 4]);
+        assert_eq!(replace, src);
+    }
+}
@@ -0,0 +1,2038 @@
+//! Interpreter of Naga IR.
+//!
+//! Every invocation keeps an explicit stack of frames and block cursors
+//! instead of recursing on the IR, so that it can be suspended at a control
+//! barrier and resumed once the rest of its workgroup has caught up.
+//!
+//! All memory accessible through pointers, including function and private
+//! variables, is laid out as bytes according to `naga::proc::Layouter`.
+//! Accesses outside of the bound memory read zero and drop writes.
+
+use std::{fmt, sync::Arc};
+
+use arrayvec::ArrayVec;
+use naga::{Handle, ScalarKind, VectorSize};
+
+use super::{
+    image::{self, SampleQuery},
+    texel::{self, Texel},
+    Memory, Sampler, TextureView,
+};
+
+/// Error that aborts an invocation.
+#[derive(Debug)]
+pub(super) struct Fault(String);
+
+impl fmt::Display for Fault {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(formatter)
+    }
+}
+
+macro_rules! fault {
+    ($($arg:tt)*) => {
+        Err(Fault(format!($($arg)*)))
+    };
+}
+
+type Result<T> = std::result::Result<T, Fault>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Scalar {
+    F32(f32),
+    I32(i32),
+    U32(u32),
+    Bool(bool),
+}
+
+impl Scalar {
+    fn zero(kind: ScalarKind) -> Self {
+        match kind {
+            ScalarKind::Float => Self::F32(0.0),
+            ScalarKind::Sint => Self::I32(0),
+            ScalarKind::Uint => Self::U32(0),
+            ScalarKind::Bool => Self::Bool(false),
+        }
+    }
+
+    fn from_bits(kind: ScalarKind, bits: u32) -> Self {
+        match kind {
+            ScalarKind::Float => Self::F32(f32::from_bits(bits)),
+            ScalarKind::Sint => Self::I32(bits as i32),
+            ScalarKind::Uint => Self::U32(bits),
+            ScalarKind::Bool => Self::Bool(bits != 0),
+        }
+    }
+
+    fn to_bits(self) -> u32 {
+        match self {
+            Self::F32(v) => v.to_bits(),
+            Self::I32(v) => v as u32,
+            Self::U32(v) => v,
+            Self::Bool(v) => v as u32,
+        }
+    }
+
+    pub fn as_f32(self) -> f32 {
+        match self {
+            Self::F32(v) => v,
+            Self::I32(v) => v as f32,
+            Self::U32(v) => v as f32,
+            Self::Bool(v) => v as u32 as f32,
+        }
+    }
+
+    pub fn as_i32(self) -> i32 {
+        match self {
+            Self::F32(v) => v as i32,
+            Self::I32(v) => v,
+            Self::U32(v) => v as i32,
+            Self::Bool(v) => v as i32,
+        }
+    }
+
+    pub fn as_u32(self) -> u32 {
+        match self {
+            Self::F32(v) => v as u32,
+            Self::I32(v) => v as u32,
+            Self::U32(v) => v,
+            Self::Bool(v) => v as u32,
+        }
+    }
+
+    pub fn as_bool(self) -> bool {
+        match self {
+            Self::F32(v) => v != 0.0,
+            Self::I32(v) => v != 0,
+            Self::U32(v) => v != 0,
+            Self::Bool(v) => v,
+        }
+    }
+
+    /// Converts the value, as opposed to reinterpreting the bits.
+    pub fn convert(self, kind: ScalarKind) -> Self {
+        match kind {
+            ScalarKind::Float => Self::F32(self.as_f32()),
+            ScalarKind::Sint => Self::I32(self.as_i32()),
+            ScalarKind::Uint => Self::U32(self.as_u32()),
+            ScalarKind::Bool => Self::Bool(self.as_bool()),
+        }
+    }
+
+    fn bitcast(self, kind: ScalarKind) -> Self {
+        Self::from_bits(kind, self.to_bits())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Pointee {
+    Type(Handle<naga::Type>),
+    Scalar(ScalarKind, naga::Bytes),
+    Vector(VectorSize, ScalarKind, naga::Bytes),
+}
+
+/// Pointer into memory laid out by the `Layouter`.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Pointer {
+    base: *mut u8,
+    len: usize,
+    offset: usize,
+    ty: Pointee,
+}
+
+#[derive(Clone, Debug)]
+pub(super) enum Value {
+    Scalar(Scalar),
+    Vector(ArrayVec<Scalar, 4>),
+    /// Matrices (as a list of columns), arrays, and structures.
+    Composite(Vec<Value>),
+    Pointer(Pointer),
+    /// Image or sampler global.
+    Handle(Handle<naga::GlobalVariable>),
+}
+
+impl Value {
+    fn scalar(&self) -> Result<Scalar> {
+        match *self {
+            Self::Scalar(s) => Ok(s),
+            ref other => fault!("expected a scalar, got {:?}", other),
+        }
+    }
+
+    /// Returns the components of a scalar or a vector.
+    pub fn components(&self) -> ArrayVec<Scalar, 4> {
+        match *self {
+            Self::Scalar(s) => {
+                let mut v = ArrayVec::new();
+                v.push(s);
+                v
+            }
+            Self::Vector(ref v) => v.clone(),
+            _ => ArrayVec::new(),
+        }
+    }
+
+    /// Builds a value of the given shape out of components, converting them
+    /// to the expected kind. Missing components are taken from `(0, 0, 0, 1)`.
+    pub fn from_components(inner: &naga::TypeInner, components: &[Scalar]) -> Self {
+        let get = |i: usize, kind| {
+            components
+                .get(i)
+                .copied()
+                .unwrap_or(if i == 3 {
+                    Scalar::U32(1)
+                } else {
+                    Scalar::U32(0)
+                })
+                .convert(kind)
+        };
+        match *inner {
+            naga::TypeInner::Scalar { kind, .. } => Self::Scalar(get(0, kind)),
+            naga::TypeInner::Vector { size, kind, .. } => {
+                Self::Vector((0..size as usize).map(|i| get(i, kind)).collect())
+            }
+            _ => Self::Composite(Vec::new()),
+        }
+    }
+}
+
+fn vector(components: impl IntoIterator<Item = Scalar>) -> Value {
+    Value::Vector(components.into_iter().collect())
+}
+
+/// Applies a function to every component of the arguments, broadcasting scalars.
+fn component_wise(args: &[&Value], fun: &dyn Fn(&[Scalar]) -> Result<Scalar>) -> Result<Value> {
+    let mut lanes = None;
+    let mut columns = None;
+    for arg in args {
+        match **arg {
+            Value::Vector(ref v) => lanes = Some(v.len()),
+            Value::Composite(ref c) => columns = Some(c.len()),
+            _ => {}
+        }
+    }
+    if let Some(columns) = columns {
+        let mut result = Vec::with_capacity(columns);
+        for column in 0..columns {
+            let column_args = args
+                .iter()
+                .map(|arg| match **arg {
+                    Value::Composite(ref c) => c[column].clone(),
+                    ref other => other.clone(),
+                })
+                .collect::<Vec<_>>();
+            let refs = column_args.iter().collect::<Vec<_>>();
+            result.push(component_wise(&refs, fun)?);
+        }
+        return Ok(Value::Composite(result));
+    }
+    let mut scalars = ArrayVec::<Scalar, 4>::new();
+    match lanes {
+        Some(lanes) => {
+            let mut result = ArrayVec::new();
+            for lane in 0..lanes {
+                scalars.clear();
+                for arg in args {
+                    scalars.push(match **arg {
+                        Value::Scalar(s) => s,
+                        Value::Vector(ref v) => v[lane],
+                        ref other => return fault!("unexpected operand {:?}", other),
+                    });
+                }
+                result.push(fun(&scalars)?);
+            }
+            Ok(Value::Vector(result))
+        }
+        None => {
+            for arg in args {
+                scalars.push(arg.scalar()?);
+            }
+            Ok(Value::Scalar(fun(&scalars)?))
+        }
+    }
+}
+
+fn map_f32(value: &Value, fun: impl Fn(f32) -> f32) -> Result<Value> {
+    component_wise(&[value], &|s| Ok(Scalar::F32(fun(s[0].as_f32()))))
+}
+
+fn floats(value: &Value) -> ArrayVec<f32, 4> {
+    value.components().iter().map(|s| s.as_f32()).collect()
+}
+
+fn dot(a: &Value, b: &Value) -> Scalar {
+    let (a, b) = (a.components(), b.components());
+    match a.first() {
+        Some(&Scalar::I32(_)) => Scalar::I32(a.iter().zip(b.iter()).fold(0i32, |sum, (x, y)| {
+            sum.wrapping_add(x.as_i32().wrapping_mul(y.as_i32()))
+        })),
+        Some(&Scalar::U32(_)) => Scalar::U32(a.iter().zip(b.iter()).fold(0u32, |sum, (x, y)| {
+            sum.wrapping_add(x.as_u32().wrapping_mul(y.as_u32()))
+        })),
+        _ => Scalar::F32(
+            a.iter()
+                .zip(b.iter())
+                .map(|(x, y)| x.as_f32() * y.as_f32())
+                .sum(),
+        ),
+    }
+}
+
+fn length(value: &Value) -> f32 {
+    floats(value).iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+fn round_even(x: f32) -> f32 {
+    let floor = x.floor();
+    let diff = x - floor;
+    if diff > 0.5 || (diff == 0.5 && floor % 2.0 != 0.0) {
+        floor + 1.0
+    } else {
+        floor
+    }
+}
+
+fn matrix_columns(value: &Value) -> Result<Vec<ArrayVec<f32, 4>>> {
+    match *value {
+        Value::Composite(ref columns) => Ok(columns.iter().map(floats).collect()),
+        ref other => fault!("expected a matrix, got {:?}", other),
+    }
+}
+
+fn matrix(columns: Vec<ArrayVec<f32, 4>>) -> Value {
+    Value::Composite(
+        columns
+            .into_iter()
+            .map(|column| vector(column.into_iter().map(Scalar::F32)))
+            .collect(),
+    )
+}
+
+fn determinant(m: &[ArrayVec<f32, 4>]) -> f32 {
+    match m.len() {
+        2 => m[0][0] * m[1][1] - m[1][0] * m[0][1],
+        3 => {
+            m[0][0] * (m[1][1] * m[2][2] - m[2][1] * m[1][2])
+                - m[1][0] * (m[0][1] * m[2][2] - m[2][1] * m[0][2])
+                + m[2][0] * (m[0][1] * m[1][2] - m[1][1] * m[0][2])
+        }
+        n => {
+            // Laplace expansion along the first column.
+            let mut sum = 0.0;
+            for row in 0..n {
+                let minor = m[1..]
+                    .iter()
+                    .map(|column| {
+                        column
+                            .iter()
+                            .enumerate()
+                            .filter(|&(r, _)| r != row)
+                            .map(|(_, &v)| v)
+                            .collect()
+                    })
+                    .collect::<Vec<_>>();
+                let sign = if row % 2 == 0 { 1.0 } else { -1.0 };
+                sum += sign * m[0][row] * determinant(&minor);
+            }
+            sum
+        }
+    }
+}
+
+fn binary_scalar(op: naga::BinaryOperator, a: Scalar, b: Scalar) -> Result<Scalar> {
+    use naga::BinaryOperator as Bo;
+    use Scalar as S;
+
+    // Shift amounts are always unsigned, everything else has matching kinds,
+    // but be lenient with signedness mismatches.
+    let b = match (a, b) {
+        (S::I32(_), S::U32(v)) if !matches!(op, Bo::ShiftLeft | Bo::ShiftRight) => S::I32(v as i32),
+        (S::U32(_), S::I32(v)) => S::U32(v as u32),
+        _ => b,
+    };
+    Ok(match (op, a, b) {
+        (Bo::Add, S::F32(a), S::F32(b)) => S::F32(a + b),
+        (Bo::Add, S::I32(a), S::I32(b)) => S::I32(a.wrapping_add(b)),
+        (Bo::Add, S::U32(a), S::U32(b)) => S::U32(a.wrapping_add(b)),
+        (Bo::Subtract, S::F32(a), S::F32(b)) => S::F32(a - b),
+        (Bo::Subtract, S::I32(a), S::I32(b)) => S::I32(a.wrapping_sub(b)),
+        (Bo::Subtract, S::U32(a), S::U32(b)) => S::U32(a.wrapping_sub(b)),
+        (Bo::Multiply, S::F32(a), S::F32(b)) => S::F32(a * b),
+        (Bo::Multiply, S::I32(a), S::I32(b)) => S::I32(a.wrapping_mul(b)),
+        (Bo::Multiply, S::U32(a), S::U32(b)) => S::U32(a.wrapping_mul(b)),
+        (Bo::Divide, S::F32(a), S::F32(b)) => S::F32(a / b),
+        (Bo::Divide, S::I32(a), S::I32(b)) => S::I32(if b == 0 || (a == i32::MIN && b == -1) {
+            a
+        } else {
+            a / b
+        }),
+        (Bo::Divide, S::U32(a), S::U32(b)) => S::U32(if b == 0 { a } else { a / b }),
+        (Bo::Modulo, S::F32(a), S::F32(b)) => S::F32(a % b),
+        (Bo::Modulo, S::I32(a), S::I32(b)) => S::I32(if b == 0 { 0 } else { a.wrapping_rem(b) }),
+        (Bo::Modulo, S::U32(a), S::U32(b)) => S::U32(if b == 0 { 0 } else { a % b }),
+        (Bo::Equal, a, b) => S::Bool(a == b),
+        (Bo::NotEqual, a, b) => S::Bool(a != b),
+        (Bo::Less, S::F32(a), S::F32(b)) => S::Bool(a < b),
+        (Bo::Less, S::I32(a), S::I32(b)) => S::Bool(a < b),
+        (Bo::Less, S::U32(a), S::U32(b)) => S::Bool(a < b),
+        (Bo::LessEqual, S::F32(a), S::F32(b)) => S::Bool(a <= b),
+        (Bo::LessEqual, S::I32(a), S::I32(b)) => S::Bool(a <= b),
+        (Bo::LessEqual, S::U32(a), S::U32(b)) => S::Bool(a <= b),
+        (Bo::Greater, S::F32(a), S::F32(b)) => S::Bool(a > b),
+        (Bo::Greater, S::I32(a), S::I32(b)) => S::Bool(a > b),
+        (Bo::Greater, S::U32(a), S::U32(b)) => S::Bool(a > b),
+        (Bo::GreaterEqual, S::F32(a), S::F32(b)) => S::Bool(a >= b),
+        (Bo::GreaterEqual, S::I32(a), S::I32(b)) => S::Bool(a >= b),
+        (Bo::GreaterEqual, S::U32(a), S::U32(b)) => S::Bool(a >= b),
+        (Bo::And, S::Bool(a), S::Bool(b)) | (Bo::LogicalAnd, S::Bool(a), S::Bool(b)) => {
+            S::Bool(a && b)
+        }
+        (Bo::InclusiveOr, S::Bool(a), S::Bool(b)) | (Bo::LogicalOr, S::Bool(a), S::Bool(b)) => {
+            S::Bool(a || b)
+        }
+        (Bo::ExclusiveOr, S::Bool(a), S::Bool(b)) => S::Bool(a != b),
+        (Bo::And, S::I32(a), S::I32(b)) => S::I32(a & b),
+        (Bo::And, S::U32(a), S::U32(b)) => S::U32(a & b),
+        (Bo::InclusiveOr, S::I32(a), S::I32(b)) => S::I32(a | b),
+        (Bo::InclusiveOr, S::U32(a), S::U32(b)) => S::U32(a | b),
+        (Bo::ExclusiveOr, S::I32(a), S::I32(b)) => S::I32(a ^ b),
+        (Bo::ExclusiveOr, S::U32(a), S::U32(b)) => S::U32(a ^ b),
+        (Bo::ShiftLeft, S::I32(a), b) => S::I32(a.wrapping_shl(b.as_u32())),
+        (Bo::ShiftLeft, S::U32(a), b) => S::U32(a.wrapping_shl(b.as_u32())),
+        (Bo::ShiftRight, S::I32(a), b) => S::I32(a.wrapping_shr(b.as_u32())),
+        (Bo::ShiftRight, S::U32(a), b) => S::U32(a.wrapping_shr(b.as_u32())),
+        (op, a, b) => return fault!("invalid operands of {:?}: {:?} and {:?}", op, a, b),
+    })
+}
+
+fn binary(op: naga::BinaryOperator, left: &Value, right: &Value) -> Result<Value> {
+    if op == naga::BinaryOperator::Multiply {
+        match (left, right) {
+            // matrix * vector
+            (&Value::Composite(_), &Value::Vector(ref v)) => {
+                let columns = matrix_columns(left)?;
+                let rows = columns.first().map_or(0, |c| c.len());
+                return Ok(vector((0..rows).map(|row| {
+                    Scalar::F32(
+                        columns
+                            .iter()
+                            .zip(v.iter())
+                            .map(|(column, s)| column[row] * s.as_f32())
+                            .sum(),
+                    )
+                })));
+            }
+            // vector * matrix
+            (&Value::Vector(_), &Value::Composite(ref columns)) => {
+                return Ok(vector(columns.iter().map(|column| dot(left, column))));
+            }
+            // matrix * matrix
+            (&Value::Composite(_), &Value::Composite(ref columns)) => {
+                let result = columns
+                    .iter()
+                    .map(|column| binary(op, left, column))
+                    .collect::<Result<Vec<_>>>()?;
+                return Ok(Value::Composite(result));
+            }
+            _ => {}
+        }
+    }
+    component_wise(&[left, right], &|s| binary_scalar(op, s[0], s[1]))
+}
+
+fn unary(op: naga::UnaryOperator, value: &Value) -> Result<Value> {
+    component_wise(&[value], &|s| {
+        Ok(match (op, s[0]) {
+            (naga::UnaryOperator::Negate, Scalar::F32(v)) => Scalar::F32(-v),
+            (naga::UnaryOperator::Negate, Scalar::I32(v)) => Scalar::I32(v.wrapping_neg()),
+            (naga::UnaryOperator::Negate, Scalar::U32(v)) => Scalar::U32(v.wrapping_neg()),
+            (naga::UnaryOperator::Not, Scalar::Bool(v)) => Scalar::Bool(!v),
+            (naga::UnaryOperator::Not, Scalar::I32(v)) => Scalar::I32(!v),
+            (naga::UnaryOperator::Not, Scalar::U32(v)) => Scalar::U32(!v),
+            (op, s) => return fault!("invalid operand of {:?}: {:?}", op, s),
+        })
+    })
+}
+
+fn pack_components(value: &Value, bits: u32, fun: impl Fn(f32) -> u32) -> Value {
+    let packed = floats(value)
+        .iter()
+        .enumerate()
+        .fold(0, |packed, (i, &v)| packed | (fun(v) << (i as u32 * bits)));
+    Value::Scalar(Scalar::U32(packed))
+}
+
+fn unpack_components(
+    value: &Value,
+    bits: u32,
+    count: u32,
+    fun: impl Fn(u32) -> f32,
+) -> Result<Value> {
+    let packed = value.scalar()?.as_u32();
+    let mask = (1u64 << bits) as u32 - 1;
+    Ok(vector(
+        (0..count).map(|i| Scalar::F32(fun((packed >> (i * bits)) & mask))),
+    ))
+}
+
+fn math(fun: naga::MathFunction, args: &[&Value]) -> Result<Value> {
+    use naga::MathFunction as Mf;
+    let arg = args[0];
+    Ok(match fun {
+        Mf::Abs => component_wise(args, &|s| {
+            Ok(match s[0] {
+                Scalar::F32(v) => Scalar::F32(v.abs()),
+                Scalar::I32(v) => Scalar::I32(v.wrapping_abs()),
+                other => other,
+            })
+        })?,
+        Mf::Min | Mf::Max => component_wise(args, &|s| {
+            let less = binary_scalar(naga::BinaryOperator::Less, s[0], s[1])?.as_bool();
+            Ok(if less == (fun == Mf::Min) { s[0] } else { s[1] })
+        })?,
+        Mf::Clamp => component_wise(args, &|s| {
+            let max = |a: Scalar, b: Scalar| -> Result<Scalar> {
+                let less = binary_scalar(naga::BinaryOperator::Less, a, b)?.as_bool();
+                Ok(if less { b } else { a })
+            };
+            let min = |a: Scalar, b: Scalar| -> Result<Scalar> {
+                let less = binary_scalar(naga::BinaryOperator::Less, a, b)?.as_bool();
+                Ok(if less { a } else { b })
+            };
+            min(max(s[0], s[1])?, s[2])
+        })?,
+        Mf::Cos => map_f32(arg, f32::cos)?,
+        Mf::Cosh => map_f32(arg, f32::cosh)?,
+        Mf::Sin => map_f32(arg, f32::sin)?,
+        Mf::Sinh => map_f32(arg, f32::sinh)?,
+        Mf::Tan => map_f32(arg, f32::tan)?,
+        Mf::Tanh => map_f32(arg, f32::tanh)?,
+        Mf::Acos => map_f32(arg, f32::acos)?,
+        Mf::Asin => map_f32(arg, f32::asin)?,
+        Mf::Atan => map_f32(arg, f32::atan)?,
+        Mf::Asinh => map_f32(arg, f32::asinh)?,
+        Mf::Acosh => map_f32(arg, f32::acosh)?,
+        Mf::Atanh => map_f32(arg, f32::atanh)?,
+        Mf::Atan2 => component_wise(args, &|s| {
+            Ok(Scalar::F32(s[0].as_f32().atan2(s[1].as_f32())))
+        })?,
+        Mf::Radians => map_f32(arg, f32::to_radians)?,
+        Mf::Degrees => map_f32(arg, f32::to_degrees)?,
+        Mf::Ceil => map_f32(arg, f32::ceil)?,
+        Mf::Floor => map_f32(arg, f32::floor)?,
+        Mf::Round => map_f32(arg, round_even)?,
+        Mf::Fract => map_f32(arg, |x| x - x.floor())?,
+        Mf::Trunc => map_f32(arg, f32::trunc)?,
+        Mf::Ldexp => component_wise(args, &|s| {
+            Ok(Scalar::F32(s[0].as_f32() * 2f32.powi(s[1].as_i32())))
+        })?,
+        Mf::Exp => map_f32(arg, f32::exp)?,
+        Mf::Exp2 => map_f32(arg, f32::exp2)?,
+        Mf::Log => map_f32(arg, f32::ln)?,
+        Mf::Log2 => map_f32(arg, f32::log2)?,
+        Mf::Pow => component_wise(args, &|s| {
+            Ok(Scalar::F32(s[0].as_f32().powf(s[1].as_f32())))
+        })?,
+        Mf::Dot => Value::Scalar(dot(arg, args[1])),
+        Mf::Outer => {
+            let (a, b) = (floats(arg), floats(args[1]));
+            matrix(
+                b.iter()
+                    .map(|&y| a.iter().map(|&x| x * y).collect())
+                    .collect(),
+            )
+        }
+        Mf::Cross => {
+            let (a, b) = (floats(arg), floats(args[1]));
+            vector(
+                [
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ]
+                .iter()
+                .map(|&v| Scalar::F32(v)),
+            )
+        }
+        Mf::Distance => {
+            let difference = binary(naga::BinaryOperator::Subtract, arg, args[1])?;
+            Value::Scalar(Scalar::F32(length(&difference)))
+        }
+        Mf::Length => Value::Scalar(Scalar::F32(length(arg))),
+        Mf::Normalize => {
+            let length = length(arg);
+            map_f32(arg, |x| x / length)?
+        }
+        Mf::FaceForward => {
+            if dot(args[2], args[1]).as_f32() < 0.0 {
+                arg.clone()
+            } else {
+                unary(naga::UnaryOperator::Negate, arg)?
+            }
+        }
+        Mf::Reflect => {
+            let d = dot(args[1], arg).as_f32();
+            let n = floats(args[1]);
+            let i = floats(arg);
+            vector(
+                i.iter()
+                    .zip(n.iter())
+                    .map(|(&i, &n)| Scalar::F32(i - 2.0 * d * n)),
+            )
+        }
+        Mf::Refract => {
+            let (i, n) = (floats(arg), floats(args[1]));
+            let eta = args[2].scalar()?.as_f32();
+            let d = dot(args[1], arg).as_f32();
+            let k = 1.0 - eta * eta * (1.0 - d * d);
+            vector(i.iter().zip(n.iter()).map(|(&i, &n)| {
+                Scalar::F32(if k < 0.0 {
+                    0.0
+                } else {
+                    eta * i - (eta * d + k.sqrt()) * n
+                })
+            }))
+        }
+        Mf::Sign => component_wise(args, &|s| {
+            Ok(match s[0] {
+                Scalar::F32(v) => Scalar::F32(if v > 0.0 {
+                    1.0
+                } else if v < 0.0 {
+                    -1.0
+                } else {
+                    0.0
+                }),
+                Scalar::I32(v) => Scalar::I32(v.signum()),
+                other => other,
+            })
+        })?,
+        Mf::Fma => component_wise(args, &|s| {
+            Ok(Scalar::F32(
+                s[0].as_f32().mul_add(s[1].as_f32(), s[2].as_f32()),
+            ))
+        })?,
+        Mf::Mix => component_wise(args, &|s| {
+            let t = s[2].as_f32();
+            Ok(Scalar::F32(s[0].as_f32() * (1.0 - t) + s[1].as_f32() * t))
+        })?,
+        Mf::Step => component_wise(args, &|s| {
+            Ok(Scalar::F32(if s[1].as_f32() >= s[0].as_f32() {
+                1.0
+            } else {
+                0.0
+            }))
+        })?,
+        Mf::SmoothStep => component_wise(args, &|s| {
+            let (low, high, x) = (s[0].as_f32(), s[1].as_f32(), s[2].as_f32());
+            let t = ((x - low) / (high - low)).max(0.0).min(1.0);
+            Ok(Scalar::F32(t * t * (3.0 - 2.0 * t)))
+        })?,
+        Mf::Sqrt => map_f32(arg, f32::sqrt)?,
+        Mf::InverseSqrt => map_f32(arg, |x| 1.0 / x.sqrt())?,
+        Mf::Transpose => {
+            let columns = matrix_columns(arg)?;
+            let rows = columns.first().map_or(0, |c| c.len());
+            matrix(
+                (0..rows)
+                    .map(|row| columns.iter().map(|column| column[row]).collect())
+                    .collect(),
+            )
+        }
+        Mf::Determinant => Value::Scalar(Scalar::F32(determinant(&matrix_columns(arg)?))),
+        Mf::CountOneBits => component_wise(args, &|s| {
+            Ok(Scalar::from_bits(
+                kind_of(s[0]),
+                s[0].to_bits().count_ones(),
+            ))
+        })?,
+        Mf::ReverseBits => component_wise(args, &|s| {
+            Ok(Scalar::from_bits(
+                kind_of(s[0]),
+                s[0].to_bits().reverse_bits(),
+            ))
+        })?,
+        Mf::FindLsb => component_wise(args, &|s| {
+            let bits = s[0].to_bits();
+            let index = if bits == 0 { !0 } else { bits.trailing_zeros() };
+            Ok(Scalar::from_bits(kind_of(s[0]), index))
+        })?,
+        Mf::FindMsb => component_wise(args, &|s| {
+            let bits = match s[0] {
+                Scalar::I32(v) if v < 0 => !(v as u32),
+                other => other.to_bits(),
+            };
+            let index = if bits == 0 {
+                !0
+            } else {
+                31 - bits.leading_zeros()
+            };
+            Ok(Scalar::from_bits(kind_of(s[0]), index))
+        })?,
+        Mf::ExtractBits => {
+            let offset = args[1].scalar()?.as_u32().min(32);
+            let count = args[2].scalar()?.as_u32().min(32 - offset);
+            component_wise(&[arg], &|s| {
+                if count == 0 {
+                    return Ok(Scalar::zero(kind_of(s[0])));
+                }
+                Ok(match s[0] {
+                    Scalar::I32(v) => Scalar::I32((v << (32 - offset - count)) >> (32 - count)),
+                    other => {
+                        let v = other.to_bits();
+                        Scalar::U32((v << (32 - offset - count)) >> (32 - count))
+                    }
+                })
+            })?
+        }
+        Mf::InsertBits => {
+            let offset = args[2].scalar()?.as_u32().min(32);
+            let count = args[3].scalar()?.as_u32().min(32 - offset);
+            let mask = if count == 0 {
+                0
+            } else {
+                (!0u32 >> (32 - count)) << offset
+            };
+            component_wise(&[arg, args[1]], &|s| {
+                let bits = (s[0].to_bits() & !mask) | ((s[1].to_bits() << offset) & mask);
+                Ok(Scalar::from_bits(kind_of(s[0]), bits))
+            })?
+        }
+        Mf::Pack4x8snorm => pack_components(arg, 8, |v| {
+            ((v.max(-1.0).min(1.0) * 127.0).round() as i32 as u32) & 0xFF
+        }),
+        Mf::Pack4x8unorm => {
+            pack_components(arg, 8, |v| (v.max(0.0).min(1.0) * 255.0).round() as u32)
+        }
+        Mf::Pack2x16snorm => pack_components(arg, 16, |v| {
+            ((v.max(-1.0).min(1.0) * 32767.0).round() as i32 as u32) & 0xFFFF
+        }),
+        Mf::Pack2x16unorm => {
+            pack_components(arg, 16, |v| (v.max(0.0).min(1.0) * 65535.0).round() as u32)
+        }
+        Mf::Pack2x16float => pack_components(arg, 16, |v| texel::f32_to_f16(v) as u32),
+        Mf::Unpack4x8snorm => {
+            unpack_components(arg, 8, 4, |v| (v as u8 as i8 as f32 / 127.0).max(-1.0))?
+        }
+        Mf::Unpack4x8unorm => unpack_components(arg, 8, 4, |v| v as f32 / 255.0)?,
+        Mf::Unpack2x16snorm => {
+            unpack_components(arg, 16, 2, |v| (v as u16 as i16 as f32 / 32767.0).max(-1.0))?
+        }
+        Mf::Unpack2x16unorm => unpack_components(arg, 16, 2, |v| v as f32 / 65535.0)?,
+        Mf::Unpack2x16float => unpack_components(arg, 16, 2, |v| texel::f16_to_f32(v as u16))?,
+        other => return fault!("math function {:?} is not supported", other),
+    })
+}
+
+fn kind_of(scalar: Scalar) -> ScalarKind {
+    match scalar {
+        Scalar::F32(_) => ScalarKind::Float,
+        Scalar::I32(_) => ScalarKind::Sint,
+        Scalar::U32(_) => ScalarKind::Uint,
+        Scalar::Bool(_) => ScalarKind::Bool,
+    }
+}
+
+fn relational(fun: naga::RelationalFunction, value: &Value) -> Result<Value> {
+    use naga::RelationalFunction as Rf;
+    Ok(match fun {
+        Rf::All => Value::Scalar(Scalar::Bool(value.components().iter().all(|s| s.as_bool()))),
+        Rf::Any => Value::Scalar(Scalar::Bool(value.components().iter().any(|s| s.as_bool()))),
+        Rf::IsNan => component_wise(&[value], &|s| Ok(Scalar::Bool(s[0].as_f32().is_nan())))?,
+        Rf::IsInf => component_wise(&[value], &|s| Ok(Scalar::Bool(s[0].as_f32().is_infinite())))?,
+        Rf::IsFinite => component_wise(&[value], &|s| Ok(Scalar::Bool(s[0].as_f32().is_finite())))?,
+        Rf::IsNormal => component_wise(&[value], &|s| Ok(Scalar::Bool(s[0].as_f32().is_normal())))?,
+    })
+}
+
+/// A shader module prepared for interpretation.
+pub(super) struct Module {
+    pub ir: naga::Module,
+    layouter: naga::proc::Layouter,
+    /// Offsets of private and workgroup variables in their respective memory.
+    global_offsets: Vec<usize>,
+    /// Initial contents of the private memory of every invocation.
+    private_init: Box<[u8]>,
+    workgroup_size: usize,
+}
+
+impl fmt::Debug for Module {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Module")
+    }
+}
+
+fn align_offset(offset: usize) -> usize {
+    // Enough for any type, so that typed accesses are never misaligned
+    // with respect to their layout.
+    (offset + 15) & !15
+}
+
+impl Module {
+    pub fn new(ir: naga::Module) -> std::result::Result<Self, String> {
+        let mut layouter = naga::proc::Layouter::default();
+        layouter
+            .update(&ir.types, &ir.constants)
+            .map_err(|e| format!("{:?}", e))?;
+
+        let mut global_offsets = Vec::with_capacity(ir.global_variables.len());
+        let (mut private_size, mut workgroup_size) = (0, 0);
+        for (_, var) in ir.global_variables.iter() {
+            let size = layouter[var.ty].size as usize;
+            let offset = match var.space {
+                naga::AddressSpace::Private => {
+                    let offset = align_offset(private_size);
+                    private_size = offset + size;
+                    offset
+                }
+                naga::AddressSpace::WorkGroup => {
+                    let offset = align_offset(workgroup_size);
+                    workgroup_size = offset + size;
+                    offset
+                }
+                _ => 0,
+            };
+            global_offsets.push(offset);
+        }
+
+        let mut module = Self {
+            ir,
+            layouter,
+            global_offsets,
+            private_init: Box::default(),
+            workgroup_size,
+        };
+        let mut private_init = vec![0; private_size].into_boxed_slice();
+        for (handle, var) in module.ir.global_variables.iter() {
+            if let (naga::AddressSpace::Private, Some(init)) = (var.space, var.init) {
+                let pointer = Pointer {
+                    base: private_init.as_mut_ptr(),
+                    len: private_size,
+                    offset: module.global_offsets[handle.index()],
+                    ty: Pointee::Type(var.ty),
+                };
+                let value = module.constant(init);
+                module.store(pointer, &value).map_err(|e| e.0)?;
+            }
+        }
+        module.private_init = private_init;
+        Ok(module)
+    }
+
+    fn constant(&self, handle: Handle<naga::Constant>) -> Value {
+        match self.ir.constants[handle].inner {
+            naga::ConstantInner::Scalar { value, .. } => Value::Scalar(match value {
+                naga::ScalarValue::Sint(v) => Scalar::I32(v as i32),
+                naga::ScalarValue::Uint(v) => Scalar::U32(v as u32),
+                naga::ScalarValue::Float(v) => Scalar::F32(v as f32),
+                naga::ScalarValue::Bool(v) => Scalar::Bool(v),
+            }),
+            naga::ConstantInner::Composite { ty, ref components } => {
+                let components = components.iter().map(|&c| self.constant(c)).collect();
+                self.compose(ty, components)
+            }
+        }
+    }
+
+    fn compose(&self, ty: Handle<naga::Type>, components: Vec<Value>) -> Value {
+        match self.ir.types[ty].inner {
+            naga::TypeInner::Vector { .. } => {
+                vector(components.iter().flat_map(|c| c.components()))
+            }
+            _ => Value::Composite(components),
+        }
+    }
+
+    fn array_length(&self, size: naga::ArraySize) -> Option<usize> {
+        match size {
+            naga::ArraySize::Constant(handle) => match self.ir.constants[handle].inner {
+                naga::ConstantInner::Scalar { value, .. } => match value {
+                    naga::ScalarValue::Sint(v) => Some(v as usize),
+                    naga::ScalarValue::Uint(v) => Some(v as usize),
+                    _ => None,
+                },
+                _ => None,
+            },
+            naga::ArraySize::Dynamic => None,
+        }
+    }
+
+    fn read_scalar(&self, pointer: &Pointer, offset: usize, kind: ScalarKind, width: u8) -> Scalar {
+        let width = width as usize;
+        if offset + width > pointer.len {
+            return Scalar::zero(kind);
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(pointer.base.add(offset), width) };
+        match (kind, width) {
+            (ScalarKind::Bool, _) => Scalar::Bool(bytes.iter().any(|&b| b != 0)),
+            (ScalarKind::Float, 8) => {
+                let mut raw = [0; 8];
+                raw.copy_from_slice(bytes);
+                Scalar::F32(f64::from_le_bytes(raw) as f32)
+            }
+            (_, 4) => Scalar::from_bits(
+                kind,
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            ),
+            _ => Scalar::zero(kind),
+        }
+    }
+
+    fn write_scalar(&self, pointer: &Pointer, offset: usize, scalar: Scalar, width: u8) {
+        let width = width as usize;
+        if offset + width > pointer.len {
+            return;
+        }
+        let bytes = unsafe { std::slice::from_raw_parts_mut(pointer.base.add(offset), width) };
+        match (scalar, width) {
+            (Scalar::Bool(v), _) => {
+                bytes.fill(0);
+                bytes[0] = v as u8;
+            }
+            (Scalar::F32(v), 8) => bytes.copy_from_slice(&(v as f64).to_le_bytes()),
+            (_, 4) => bytes.copy_from_slice(&scalar.to_bits().to_le_bytes()),
+            _ => {}
+        }
+    }
+
+    fn column_stride(rows: VectorSize, width: u8) -> usize {
+        match rows {
+            VectorSize::Bi => 2 * width as usize,
+            _ => 4 * width as usize,
+        }
+    }
+
+    fn load(&self, pointer: Pointer) -> Result<Value> {
+        let offset = pointer.offset;
+        let (size, kind, width) = match pointer.ty {
+            Pointee::Scalar(kind, width) => {
+                return Ok(Value::Scalar(
+                    self.read_scalar(&pointer, offset, kind, width),
+                ))
+            }
+            Pointee::Vector(size, kind, width) => (size, kind, width),
+            Pointee::Type(ty) => match self.ir.types[ty].inner {
+                naga::TypeInner::Scalar { kind, width }
+                | naga::TypeInner::Atomic { kind, width } => {
+                    return Ok(Value::Scalar(
+                        self.read_scalar(&pointer, offset, kind, width),
+                    ))
+                }
+                naga::TypeInner::Vector { size, kind, width } => (size, kind, width),
+                naga::TypeInner::Matrix {
+                    columns,
+                    rows,
+                    width,
+                } => {
+                    let stride = Self::column_stride(rows, width);
+                    let columns = (0..columns as usize)
+                        .map(|column| {
+                            self.load(Pointer {
+                                offset: offset + column * stride,
+                                ty: Pointee::Vector(rows, ScalarKind::Float, width),
+                                ..pointer
+                            })
+                        })
+                        .collect::<Result<_>>()?;
+                    return Ok(Value::Composite(columns));
+                }
+                naga::TypeInner::Array { base, size, stride } => {
+                    let stride = stride as usize;
+                    let count = self
+                        .array_length(size)
+                        .unwrap_or_else(|| pointer.len.saturating_sub(offset) / stride.max(1));
+                    let elements = (0..count)
+                        .map(|i| {
+                            self.load(Pointer {
+                                offset: offset + i * stride,
+                                ty: Pointee::Type(base),
+                                ..pointer
+                            })
+                        })
+                        .collect::<Result<_>>()?;
+                    return Ok(Value::Composite(elements));
+                }
+                naga::TypeInner::Struct { ref members, .. } => {
+                    let members = members
+                        .iter()
+                        .map(|member| {
+                            self.load(Pointer {
+                                offset: offset + member.offset as usize,
+                                ty: Pointee::Type(member.ty),
+                                ..pointer
+                            })
+                        })
+                        .collect::<Result<_>>()?;
+                    return Ok(Value::Composite(members));
+                }
+                ref other => return fault!("unable to load {:?}", other),
+            },
+        };
+        Ok(vector((0..size as usize).map(|i| {
+            self.read_scalar(&pointer, offset + i * width as usize, kind, width)
+        })))
+    }
+
+    fn store(&self, pointer: Pointer, value: &Value) -> Result<()> {
+        let offset = pointer.offset;
+        let width = match pointer.ty {
+            Pointee::Scalar(_, width) => {
+                self.write_scalar(&pointer, offset, value.scalar()?, width);
+                return Ok(());
+            }
+            Pointee::Vector(_, _, width) => width,
+            Pointee::Type(ty) => match self.ir.types[ty].inner {
+                naga::TypeInner::Scalar { width, .. } | naga::TypeInner::Atomic { width, .. } => {
+                    self.write_scalar(&pointer, offset, value.scalar()?, width);
+                    return Ok(());
+                }
+                naga::TypeInner::Vector { width, .. } => width,
+                naga::TypeInner::Matrix { rows, width, .. } => {
+                    let stride = Self::column_stride(rows, width);
+                    if let Value::Composite(ref columns) = *value {
+                        for (i, column) in columns.iter().enumerate() {
+                            self.store(
+                                Pointer {
+                                    offset: offset + i * stride,
+                                    ty: Pointee::Vector(rows, ScalarKind::Float, width),
+                                    ..pointer
+                                },
+                                column,
+                            )?;
+                        }
+                    }
+                    return Ok(());
+                }
+                naga::TypeInner::Array { base, stride, .. } => {
+                    if let Value::Composite(ref elements) = *value {
+                        for (i, element) in elements.iter().enumerate() {
+                            self.store(
+                                Pointer {
+                                    offset: offset + i * stride as usize,
+                                    ty: Pointee::Type(base),
+                                    ..pointer
+                                },
+                                element,
+                            )?;
+                        }
+                    }
+                    return Ok(());
+                }
+                naga::TypeInner::Struct { ref members, .. } => {
+                    if let Value::Composite(ref values) = *value {
+                        for (member, value) in members.iter().zip(values.iter()) {
+                            self.store(
+                                Pointer {
+                                    offset: offset + member.offset as usize,
+                                    ty: Pointee::Type(member.ty),
+                                    ..pointer
+                                },
+                                value,
+                            )?;
+                        }
+                    }
+                    return Ok(());
+                }
+                ref other => return fault!("unable to store {:?}", other),
+            },
+        };
+        for (i, &component) in value.components().iter().enumerate() {
+            self.write_scalar(&pointer, offset + i * width as usize, component, width);
+        }
+        Ok(())
+    }
+
+    /// Offsets a pointer to a component, element, column or member.
+    fn sub_pointer(&self, pointer: Pointer, index: usize) -> Result<Pointer> {
+        let (ty, offset) = match pointer.ty {
+            Pointee::Vector(size, kind, width) => (
+                Pointee::Scalar(kind, width),
+                index.min(size as usize - 1) * width as usize,
+            ),
+            Pointee::Scalar(..) => return fault!("unable to index into a scalar"),
+            Pointee::Type(ty) => match self.ir.types[ty].inner {
+                naga::TypeInner::Vector { size, kind, width } => (
+                    Pointee::Scalar(kind, width),
+                    index.min(size as usize - 1) * width as usize,
+                ),
+                naga::TypeInner::Matrix {
+                    columns,
+                    rows,
+                    width,
+                } => (
+                    Pointee::Vector(rows, ScalarKind::Float, width),
+                    index.min(columns as usize - 1) * Self::column_stride(rows, width),
+                ),
+                naga::TypeInner::Array { base, size, stride } => {
+                    let index = match self.array_length(size) {
+                        Some(count) => index.min(count.saturating_sub(1)),
+                        None => index,
+                    };
+                    (Pointee::Type(base), index * stride as usize)
+                }
+                naga::TypeInner::Struct { ref members, .. } => {
+                    let member = match members.get(index) {
+                        Some(member) => member,
+                        None => return fault!("invalid member index {}", index),
+                    };
+                    (Pointee::Type(member.ty), member.offset as usize)
+                }
+                ref other => return fault!("unable to index into {:?}", other),
+            },
+        };
+        Ok(Pointer {
+            offset: pointer.offset + offset,
+            ty,
+            ..pointer
+        })
+    }
+}
+
+/// Resource bound to a global variable.
+pub(super) enum GlobalBinding {
+    Unbound,
+    Memory { base: *mut u8, len: usize },
+    Texture(TextureView),
+    Sampler(Sampler),
+}
+
+/// Resources visible to the invocations of a stage.
+pub(super) struct Bindings {
+    globals: Vec<GlobalBinding>,
+}
+
+/// An entry point of a module.
+pub(super) struct Stage {
+    pub module: Arc<Module>,
+    entry_point: usize,
+}
+
+impl Stage {
+    pub fn new(
+        module: &Arc<Module>,
+        stage: naga::ShaderStage,
+        name: &str,
+        constants: &crate::PipelineConstants,
+    ) -> std::result::Result<Self, crate::PipelineError> {
+        // Overridden constants can change the layout of the module,
+        // so it has to be prepared again.
+        let module = if constants.is_empty() {
+            Arc::clone(module)
+        } else {
+            let ir = crate::auxil::apply_pipeline_constants(&module.ir, constants).into_owned();
+            let specialized = Module::new(ir).map_err(|e| {
+                crate::PipelineError::Linkage(crate::auxil::map_naga_stage(stage), e)
+            })?;
+            Arc::new(specialized)
+        };
+        let entry_point = module
+            .ir
+            .entry_points
+            .iter()
+            .position(|ep| ep.stage == stage && ep.name == name)
+            .ok_or(crate::PipelineError::EntryPoint(stage))?;
+        Ok(Self {
+            module,
+            entry_point,
+        })
+    }
+
+    pub fn entry_point(&self) -> &naga::EntryPoint {
+        &self.module.ir.entry_points[self.entry_point]
+    }
+
+    /// Resolves the resources of every global variable used by the module.
+    pub fn bind(
+        &self,
+        mut resolve: impl FnMut(naga::AddressSpace, Option<&naga::ResourceBinding>) -> GlobalBinding,
+    ) -> Bindings {
+        let globals = self
+            .module
+            .ir
+            .global_variables
+            .iter()
+            .map(|(_, var)| match var.space {
+                naga::AddressSpace::Uniform
+                | naga::AddressSpace::Storage { .. }
+                | naga::AddressSpace::Handle
+                | naga::AddressSpace::PushConstant => resolve(var.space, var.binding.as_ref()),
+                _ => GlobalBinding::Unbound,
+            })
+            .collect();
+        Bindings { globals }
+    }
+
+    /// Builds the arguments of the entry point, asking `input` for the value
+    /// of every binding.
+    pub fn arguments(
+        &self,
+        mut input: impl FnMut(&naga::Binding, &naga::TypeInner) -> Value,
+    ) -> Vec<Value> {
+        let types = &self.module.ir.types;
+        self.entry_point()
+            .function
+            .arguments
+            .iter()
+            .map(|arg| match arg.binding {
+                Some(ref binding) => input(binding, &types[arg.ty].inner),
+                None => match types[arg.ty].inner {
+                    naga::TypeInner::Struct { ref members, .. } => Value::Composite(
+                        members
+                            .iter()
+                            .map(|member| match member.binding {
+                                Some(ref binding) => input(binding, &types[member.ty].inner),
+                                None => Value::Composite(Vec::new()),
+                            })
+                            .collect(),
+                    ),
+                    _ => Value::Composite(Vec::new()),
+                },
+            })
+            .collect()
+    }
+
+    /// Splits the result of the entry point into its bindings.
+    pub fn outputs(&self, result: Option<Value>) -> Vec<(naga::Binding, Value)> {
+        let function = &self.entry_point().function;
+        let (result, value) = match (function.result.as_ref(), result) {
+            (Some(result), Some(value)) => (result, value),
+            _ => return Vec::new(),
+        };
+        match result.binding {
+            Some(ref binding) => vec![(binding.clone(), value)],
+            None => match (&self.module.ir.types[result.ty].inner, value) {
+                (&naga::TypeInner::Struct { ref members, .. }, Value::Composite(values)) => members
+                    .iter()
+                    .zip(values.into_iter())
+                    .filter_map(|(member, value)| {
+                        member
+                            .binding
+                            .as_ref()
+                            .map(|binding| (binding.clone(), value))
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    /// Runs an invocation that doesn't use control barriers to completion.
+    ///
+    /// Returns `None` if the invocation was killed.
+    pub fn invoke(
+        &self,
+        bindings: &Bindings,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Option<Value>>> {
+        let mut invocation = Invocation::new(self, bindings, arguments, std::ptr::null_mut(), 0);
+        loop {
+            match invocation.run()? {
+                Status::Barrier => {}
+                Status::Done(value) => return Ok(Some(value)),
+                Status::Killed => return Ok(None),
+            }
+        }
+    }
+
+    /// Runs a compute workgroup.
+    pub fn dispatch_workgroup(
+        &self,
+        bindings: &Bindings,
+        workgroup_id: [u32; 3],
+        num_workgroups: [u32; 3],
+    ) -> Result<()> {
+        let size = self.entry_point().workgroup_size;
+        let workgroup_memory = Memory::new(self.module.workgroup_size);
+        let base = workgroup_memory.as_ptr();
+        let mut invocations = Vec::new();
+        for z in 0..size[2] {
+            for y in 0..size[1] {
+                for x in 0..size[0] {
+                    let local = [x, y, z];
+                    let index = (z * size[1] + y) * size[0] + x;
+                    let global = [
+                        workgroup_id[0] * size[0] + x,
+                        workgroup_id[1] * size[1] + y,
+                        workgroup_id[2] * size[2] + z,
+                    ];
+                    let arguments = self.arguments(|binding, _| {
+                        let v3 = |v: [u32; 3]| vector(v.iter().map(|&c| Scalar::U32(c)));
+                        match *binding {
+                            naga::Binding::BuiltIn(naga::BuiltIn::GlobalInvocationId) => v3(global),
+                            naga::Binding::BuiltIn(naga::BuiltIn::LocalInvocationId) => v3(local),
+                            naga::Binding::BuiltIn(naga::BuiltIn::LocalInvocationIndex) => {
+                                Value::Scalar(Scalar::U32(index))
+                            }
+                            naga::Binding::BuiltIn(naga::BuiltIn::WorkGroupId) => v3(workgroup_id),
+                            naga::Binding::BuiltIn(naga::BuiltIn::NumWorkGroups) => {
+                                v3(num_workgroups)
+                            }
+                            naga::Binding::BuiltIn(naga::BuiltIn::WorkGroupSize) => v3(size),
+                            _ => Value::Scalar(Scalar::U32(0)),
+                        }
+                    });
+                    invocations.push(Some(Invocation::new(
+                        self,
+                        bindings,
+                        arguments,
+                        base,
+                        workgroup_memory.len(),
+                    )));
+                }
+            }
+        }
+
+        // Step all the invocations up to the next barrier, until they are done.
+        while invocations.iter().any(Option::is_some) {
+            for slot in invocations.iter_mut() {
+                if let Some(ref mut invocation) = *slot {
+                    match invocation.run()? {
+                        Status::Barrier => {}
+                        Status::Done(_) | Status::Killed => *slot = None,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Status {
+    Barrier,
+    Done(Option<Value>),
+    Killed,
+}
+
+#[derive(Clone, Copy)]
+enum CursorKind<'a> {
+    Block,
+    LoopBody {
+        body: &'a naga::Block,
+        continuing: &'a naga::Block,
+    },
+    LoopContinuing {
+        body: &'a naga::Block,
+        continuing: &'a naga::Block,
+    },
+    Case {
+        cases: &'a [naga::SwitchCase],
+        index: usize,
+    },
+}
+
+struct Cursor<'a> {
+    block: &'a [naga::Statement],
+    index: usize,
+    kind: CursorKind<'a>,
+}
+
+struct Frame<'a> {
+    function: &'a naga::Function,
+    arguments: Vec<Value>,
+    expressions: Vec<Option<Value>>,
+    locals: Memory,
+    local_offsets: Vec<usize>,
+    cursors: Vec<Cursor<'a>>,
+    /// Expression of the caller that receives the result.
+    result: Option<Handle<naga::Expression>>,
+}
+
+struct Invocation<'a> {
+    stage: &'a Stage,
+    bindings: &'a Bindings,
+    frames: Vec<Frame<'a>>,
+    private: Memory,
+    workgroup: (*mut u8, usize),
+}
+
+impl<'a> Invocation<'a> {
+    fn new(
+        stage: &'a Stage,
+        bindings: &'a Bindings,
+        arguments: Vec<Value>,
+        workgroup_base: *mut u8,
+        workgroup_len: usize,
+    ) -> Self {
+        let mut invocation = Self {
+            stage,
+            bindings,
+            frames: Vec::new(),
+            private: Memory::from_bytes(stage.module.private_init.clone()),
+            workgroup: (workgroup_base, workgroup_len),
+        };
+        let function = &stage.entry_point().function;
+        invocation.push_frame(function, arguments, None);
+        invocation
+    }
+
+    fn module(&self) -> &'a Module {
+        &self.stage.module
+    }
+
+    fn push_frame(
+        &mut self,
+        function: &'a naga::Function,
+        arguments: Vec<Value>,
+        result: Option<Handle<naga::Expression>>,
+    ) {
+        let module = self.module();
+        let mut local_offsets = Vec::with_capacity(function.local_variables.len());
+        let mut size = 0;
+        for (_, local) in function.local_variables.iter() {
+            let offset = align_offset(size);
+            local_offsets.push(offset);
+            size = offset + module.layouter[local.ty].size as usize;
+        }
+        let locals = Memory::new(size);
+        for (handle, local) in function.local_variables.iter() {
+            if let Some(init) = local.init {
+                let pointer = Pointer {
+                    base: locals.as_ptr(),
+                    len: size,
+                    offset: local_offsets[handle.index()],
+                    ty: Pointee::Type(local.ty),
+                };
+                // Constants always match the type of the variable.
+                let _ = module.store(pointer, &module.constant(init));
+            }
+        }
+        self.frames.push(Frame {
+            function,
+            arguments,
+            expressions: vec![None; function.expressions.len()],
+            locals,
+            local_offsets,
+            cursors: vec![Cursor {
+                block: &function.body,
+                index: 0,
+                kind: CursorKind::Block,
+            }],
+            result,
+        });
+    }
+
+    fn frame(&self) -> &Frame<'a> {
+        self.frames.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame<'a> {
+        self.frames.last_mut().unwrap()
+    }
+
+    /// Returns the value of an expression that was already evaluated,
+    /// or evaluates the ones that are never emitted.
+    fn eval(&self, handle: Handle<naga::Expression>) -> Result<Value> {
+        let frame = self.frame();
+        match frame.function.expressions[handle] {
+            naga::Expression::Constant(_)
+            | naga::Expression::FunctionArgument(_)
+            | naga::Expression::GlobalVariable(_)
+            | naga::Expression::LocalVariable(_) => self.compute(handle),
+            _ => match frame.expressions[handle.index()] {
+                Some(ref value) => Ok(value.clone()),
+                None => self.compute(handle),
+            },
+        }
+    }
+
+    fn eval_index(&self, handle: Handle<naga::Expression>) -> Result<usize> {
+        Ok(match self.eval(handle)?.scalar()? {
+            Scalar::I32(v) => v.max(0) as usize,
+            other => other.as_u32() as usize,
+        })
+    }
+
+    fn access(&self, base: Value, index: usize) -> Result<Value> {
+        Ok(match base {
+            Value::Vector(v) => Value::Scalar(v[index.min(v.len() - 1)]),
+            Value::Composite(mut c) => {
+                if c.is_empty() {
+                    return fault!("indexing into an empty composite");
+                }
+                let index = index.min(c.len() - 1);
+                c.swap_remove(index)
+            }
+            Value::Pointer(p) => Value::Pointer(self.module().sub_pointer(p, index)?),
+            other => return fault!("unable to index into {:?}", other),
+        })
+    }
+
+    fn texture(
+        &self,
+        handle: Handle<naga::Expression>,
+    ) -> Result<(&TextureView, &naga::TypeInner)> {
+        match self.eval(handle)? {
+            Value::Handle(global) => match self.bindings.globals[global.index()] {
+                GlobalBinding::Texture(ref view) => {
+                    let ty = self.module().ir.global_variables[global].ty;
+                    Ok((view, &self.module().ir.types[ty].inner))
+                }
+                _ => fault!("image {:?} is not bound", global),
+            },
+            other => fault!("expected an image, got {:?}", other),
+        }
+    }
+
+    fn sampler(&self, handle: Handle<naga::Expression>) -> Result<&Sampler> {
+        match self.eval(handle)? {
+            Value::Handle(global) => match self.bindings.globals[global.index()] {
+                GlobalBinding::Sampler(ref sampler) => Ok(sampler),
+                _ => fault!("sampler {:?} is not bound", global),
+            },
+            other => fault!("expected a sampler, got {:?}", other),
+        }
+    }
+
+    fn texel_value(texel: Texel, class: naga::ImageClass) -> Value {
+        match (class, texel) {
+            (naga::ImageClass::Depth { .. }, texel) => {
+                Value::Scalar(Scalar::F32(texel.to_float()[0]))
+            }
+            (_, Texel::Float(v)) => vector(v.iter().map(|&c| Scalar::F32(c))),
+            (_, Texel::Sint(v)) => vector(v.iter().map(|&c| Scalar::I32(c))),
+            (_, Texel::Uint(v)) => vector(v.iter().map(|&c| Scalar::U32(c))),
+        }
+    }
+
+    fn image_coordinates(
+        &self,
+        coordinate: Handle<naga::Expression>,
+        array_index: Option<Handle<naga::Expression>>,
+    ) -> Result<image::Coordinates> {
+        let components = self.eval(coordinate)?.components();
+        let mut coords = [0; 3];
+        for (c, s) in coords.iter_mut().zip(components.iter()) {
+            *c = s.as_i32();
+        }
+        if let Some(index) = array_index {
+            coords[2] = self.eval(index)?.scalar()?.as_i32();
+        }
+        Ok(coords)
+    }
+
+    fn compute(&self, handle: Handle<naga::Expression>) -> Result<Value> {
+        use naga::Expression as E;
+        let frame = self.frame();
+        let module = self.module();
+        Ok(match frame.function.expressions[handle] {
+            E::Access { base, index } => {
+                let index = self.eval_index(index)?;
+                self.access(self.eval(base)?, index)?
+            }
+            E::AccessIndex { base, index } => self.access(self.eval(base)?, index as usize)?,
+            E::Constant(constant) => module.constant(constant),
+            E::Splat { size, value } => {
+                let value = self.eval(value)?.scalar()?;
+                vector((0..size as usize).map(|_| value))
+            }
+            E::Swizzle {
+                size,
+                vector: base,
+                pattern,
+            } => {
+                let components = self.eval(base)?.components();
+                vector(
+                    pattern[..size as usize]
+                        .iter()
+                        .map(|&c| components[(c as usize).min(components.len() - 1)]),
+                )
+            }
+            E::Compose { ty, ref components } => {
+                let components = components
+                    .iter()
+                    .map(|&c| self.eval(c))
+                    .collect::<Result<_>>()?;
+                module.compose(ty, components)
+            }
+            E::FunctionArgument(index) => frame.arguments[index as usize].clone(),
+            E::GlobalVariable(global) => {
+                let var = &module.ir.global_variables[global];
+                let (base, len) = match var.space {
+                    naga::AddressSpace::Handle => return Ok(Value::Handle(global)),
+                    naga::AddressSpace::Private => (self.private.as_ptr(), self.private.len()),
+                    naga::AddressSpace::WorkGroup => self.workgroup,
+                    _ => match self.bindings.globals[global.index()] {
+                        GlobalBinding::Memory { base, len } => (base, len),
+                        _ => return fault!("global {:?} is not bound", var.name),
+                    },
+                };
+                Value::Pointer(Pointer {
+                    base,
+                    len,
+                    offset: module.global_offsets[global.index()],
+                    ty: Pointee::Type(var.ty),
+                })
+            }
+            E::LocalVariable(local) => Value::Pointer(Pointer {
+                base: frame.locals.as_ptr(),
+                len: frame.locals.len(),
+                offset: frame.local_offsets[local.index()],
+                ty: Pointee::Type(frame.function.local_variables[local].ty),
+            }),
+            E::Load { pointer } => match self.eval(pointer)? {
+                Value::Pointer(pointer) => module.load(pointer)?,
+                other => return fault!("unable to load from {:?}", other),
+            },
+            E::ImageSample {
+                image,
+                sampler,
+                gather,
+                coordinate,
+                array_index,
+                offset,
+                level,
+                depth_ref,
+            } => {
+                let (view, ty) = self.texture(image)?;
+                let sampler = self.sampler(sampler)?;
+                let mut query = SampleQuery {
+                    coordinate: [0.0; 3],
+                    array_index: match array_index {
+                        Some(index) => self.eval(index)?.scalar()?.as_i32(),
+                        None => 0,
+                    },
+                    lod: 0.0,
+                    offset: [0; 3],
+                    depth_ref: match depth_ref {
+                        Some(reference) => Some(self.eval(reference)?.scalar()?.as_f32()),
+                        None => None,
+                    },
+                    gather: gather.map(|component| component as usize),
+                };
+                for (c, s) in query
+                    .coordinate
+                    .iter_mut()
+                    .zip(self.eval(coordinate)?.components().iter())
+                {
+                    *c = s.as_f32();
+                }
+                if let Some(offset) = offset {
+                    for (c, s) in query
+                        .offset
+                        .iter_mut()
+                        .zip(module.constant(offset).components().iter())
+                    {
+                        *c = s.as_i32();
+                    }
+                }
+                query.lod = match level {
+                    naga::SampleLevel::Exact(lod) => self.eval(lod)?.scalar()?.as_f32(),
+                    naga::SampleLevel::Bias(bias) => self.eval(bias)?.scalar()?.as_f32(),
+                    _ => 0.0,
+                };
+                let texel = image::sample(view, sampler, &query);
+                match (ty, gather, depth_ref) {
+                    (&naga::TypeInner::Image { .. }, None, Some(_))
+                    | (
+                        &naga::TypeInner::Image {
+                            class: naga::ImageClass::Depth { .. },
+                            ..
+                        },
+                        None,
+                        _,
+                    ) => Value::Scalar(Scalar::F32(texel[0])),
+                    (
+                        &naga::TypeInner::Image {
+                            class: naga::ImageClass::Sampled { kind, .. },
+                            ..
+                        },
+                        _,
+                        _,
+                    ) => vector(texel.iter().map(|&c| Scalar::F32(c).convert(kind))),
+                    _ => vector(texel.iter().map(|&c| Scalar::F32(c))),
+                }
+            }
+            E::ImageLoad {
+                image,
+                coordinate,
+                array_index,
+                sample: _,
+                level,
+            } => {
+                let (view, ty) = self.texture(image)?;
+                let coords = self.image_coordinates(coordinate, array_index)?;
+                let level = match level {
+                    Some(level) => self.eval(level)?.scalar()?.as_u32(),
+                    None => 0,
+                };
+                let class = match *ty {
+                    naga::TypeInner::Image { class, .. } => class,
+                    ref other => return fault!("expected an image type, got {:?}", other),
+                };
+                Self::texel_value(image::load(view, coords, level), class)
+            }
+            E::ImageQuery { image, query } => {
+                let (view, _) = self.texture(image)?;
+                let int = |v: u32| Scalar::I32(v as i32);
+                match query {
+                    naga::ImageQuery::Size { level } => {
+                        let level = match level {
+                            Some(level) => self.eval(level)?.scalar()?.as_u32(),
+                            None => 0,
+                        };
+                        let size = image::dimensions(view, level);
+                        match view.dimension {
+                            wgt::TextureViewDimension::D1 => Value::Scalar(int(size[0])),
+                            wgt::TextureViewDimension::D3 => vector(size.iter().map(|&c| int(c))),
+                            _ => vector(size[..2].iter().map(|&c| int(c))),
+                        }
+                    }
+                    naga::ImageQuery::NumLevels => Value::Scalar(int(image::level_count(view))),
+                    naga::ImageQuery::NumLayers => Value::Scalar(int(image::layer_count(view))),
+                    naga::ImageQuery::NumSamples => Value::Scalar(int(1)),
+                }
+            }
+            E::Unary { op, expr } => unary(op, &self.eval(expr)?)?,
+            E::Binary { op, left, right } => binary(op, &self.eval(left)?, &self.eval(right)?)?,
+            E::Select {
+                condition,
+                accept,
+                reject,
+            } => {
+                let condition = self.eval(condition)?;
+                let (accept, reject) = (self.eval(accept)?, self.eval(reject)?);
+                match condition {
+                    Value::Scalar(s) => {
+                        if s.as_bool() {
+                            accept
+                        } else {
+                            reject
+                        }
+                    }
+                    condition => component_wise(&[&condition, &accept, &reject], &|s| {
+                        Ok(if s[0].as_bool() { s[1] } else { s[2] })
+                    })?,
+                }
+            }
+            E::Derivative { expr, .. } => map_f32(&self.eval(expr)?, |_| 0.0)?,
+            E::Relational { fun, argument } => relational(fun, &self.eval(argument)?)?,
+            E::Math {
+                fun,
+                arg,
+                arg1,
+                arg2,
+                arg3,
+            } => {
+                let mut args = vec![self.eval(arg)?];
+                for &extra in [arg1, arg2, arg3].iter().flatten() {
+                    args.push(self.eval(extra)?);
+                }
+                let refs = args.iter().collect::<Vec<_>>();
+                math(fun, &refs)?
+            }
+            E::As {
+                expr,
+                kind,
+                convert,
+            } => {
+                let value = self.eval(expr)?;
+                component_wise(&[&value], &|s| {
+                    Ok(if convert.is_some() {
+                        s[0].convert(kind)
+                    } else {
+                        s[0].bitcast(kind)
+                    })
+                })?
+            }
+            E::CallResult(_) | E::AtomicResult { .. } => {
+                return fault!("result {:?} is used before it's produced", handle)
+            }
+            E::ArrayLength(pointer) => match self.eval(pointer)? {
+                Value::Pointer(pointer) => {
+                    let stride = match pointer.ty {
+                        Pointee::Type(ty) => match module.ir.types[ty].inner {
+                            naga::TypeInner::Array { stride, .. } => stride as usize,
+                            _ => 1,
+                        },
+                        _ => 1,
+                    };
+                    let length = pointer.len.saturating_sub(pointer.offset) / stride.max(1);
+                    Value::Scalar(Scalar::U32(length as u32))
+                }
+                other => return fault!("unable to get the array length of {:?}", other),
+            },
+        })
+    }
+
+    fn atomic(
+        &self,
+        pointer: Handle<naga::Expression>,
+        fun: naga::AtomicFunction,
+        value: Handle<naga::Expression>,
+        result: Handle<naga::Expression>,
+    ) -> Result<Value> {
+        use naga::AtomicFunction as Af;
+        let module = self.module();
+        let pointer = match self.eval(pointer)? {
+            Value::Pointer(pointer) => pointer,
+            other => return fault!("expected a pointer, got {:?}", other),
+        };
+        let old = module.load(pointer)?.scalar()?;
+        let operand = self.eval(value)?.scalar()?;
+        let (new, exchanged) = match fun {
+            Af::Add => (
+                binary_scalar(naga::BinaryOperator::Add, old, operand)?,
+                false,
+            ),
+            Af::Subtract => (
+                binary_scalar(naga::BinaryOperator::Subtract, old, operand)?,
+                false,
+            ),
+            Af::And => (
+                binary_scalar(naga::BinaryOperator::And, old, operand)?,
+                false,
+            ),
+            Af::ExclusiveOr => (
+                binary_scalar(naga::BinaryOperator::ExclusiveOr, old, operand)?,
+                false,
+            ),
+            Af::InclusiveOr => (
+                binary_scalar(naga::BinaryOperator::InclusiveOr, old, operand)?,
+                false,
+            ),
+            Af::Min => (
+                math(
+                    naga::MathFunction::Min,
+                    &[&Value::Scalar(old), &Value::Scalar(operand)],
+                )?
+                .scalar()?,
+                false,
+            ),
+            Af::Max => (
+                math(
+                    naga::MathFunction::Max,
+                    &[&Value::Scalar(old), &Value::Scalar(operand)],
+                )?
+                .scalar()?,
+                false,
+            ),
+            Af::Exchange { compare: None } => (operand, false),
+            Af::Exchange {
+                compare: Some(compare),
+            } => {
+                let compare = self.eval(compare)?.scalar()?;
+                if old.to_bits() == compare.to_bits() {
+                    (operand, true)
+                } else {
+                    (old, false)
+                }
+            }
+        };
+        module.store(pointer, &Value::Scalar(new))?;
+        Ok(match self.frame().function.expressions[result] {
+            naga::Expression::AtomicResult {
+                comparison: true, ..
+            } => vector(vec![old, Scalar::from_bits(kind_of(old), exchanged as u32)]),
+            _ => Value::Scalar(old),
+        })
+    }
+
+    /// Returns from the current function, yielding the status if it was the entry point.
+    fn return_value(&mut self, value: Option<Value>) -> Option<Status> {
+        let frame = self.frames.pop().unwrap();
+        match self.frames.last_mut() {
+            Some(caller) => {
+                if let (Some(result), Some(value)) = (frame.result, value) {
+                    caller.expressions[result.index()] = Some(value);
+                }
+                None
+            }
+            None => Some(Status::Done(value)),
+        }
+    }
+
+    /// Runs the invocation until it hits a control barrier, returns, or gets killed.
+    fn run(&mut self) -> Result<Status> {
+        use naga::Statement as S;
+        loop {
+            if self.frame().cursors.is_empty() {
+                // Fell off the end of the function body.
+                if let Some(status) = self.return_value(None) {
+                    return Ok(status);
+                }
+                continue;
+            }
+            let statement = {
+                let frame = self.frame_mut();
+                let cursor = frame.cursors.last_mut().unwrap();
+                let block = cursor.block;
+                if cursor.index < block.len() {
+                    cursor.index += 1;
+                    &block[cursor.index - 1]
+                } else {
+                    let finished = frame.cursors.pop().unwrap();
+                    match finished.kind {
+                        CursorKind::Block => {}
+                        CursorKind::LoopBody { body, continuing } => frame.cursors.push(Cursor {
+                            block: continuing,
+                            index: 0,
+                            kind: CursorKind::LoopContinuing { body, continuing },
+                        }),
+                        CursorKind::LoopContinuing { body, continuing } => {
+                            frame.cursors.push(Cursor {
+                                block: body,
+                                index: 0,
+                                kind: CursorKind::LoopBody { body, continuing },
+                            })
+                        }
+                        CursorKind::Case { cases, index } => {
+                            if cases[index].fall_through && index + 1 < cases.len() {
+                                frame.cursors.push(Cursor {
+                                    block: &cases[index + 1].body,
+                                    index: 0,
+                                    kind: CursorKind::Case {
+                                        cases,
+                                        index: index + 1,
+                                    },
+                                });
+                            }
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            match *statement {
+                S::Emit(ref range) => {
+                    for handle in range.clone() {
+                        let value = self.compute(handle)?;
+                        self.frame_mut().expressions[handle.index()] = Some(value);
+                    }
+                }
+                S::Block(ref block) => self.frame_mut().cursors.push(Cursor {
+                    block,
+                    index: 0,
+                    kind: CursorKind::Block,
+                }),
+                S::If {
+                    condition,
+                    ref accept,
+                    ref reject,
+                } => {
+                    let block = if self.eval(condition)?.scalar()?.as_bool() {
+                        accept
+                    } else {
+                        reject
+                    };
+                    self.frame_mut().cursors.push(Cursor {
+                        block,
+                        index: 0,
+                        kind: CursorKind::Block,
+                    });
+                }
+                S::Switch {
+                    selector,
+                    ref cases,
+                } => {
+                    let selector = self.eval(selector)?.scalar()?.as_i32();
+                    let index = cases
+                        .iter()
+                        .position(|case| case.value == naga::SwitchValue::Integer(selector))
+                        .or_else(|| {
+                            cases
+                                .iter()
+                                .position(|case| case.value == naga::SwitchValue::Default)
+                        });
+                    if let Some(index) = index {
+                        self.frame_mut().cursors.push(Cursor {
+                            block: &cases[index].body,
+                            index: 0,
+                            kind: CursorKind::Case { cases, index },
+                        });
+                    }
+                }
+                S::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => self.frame_mut().cursors.push(Cursor {
+                    block: body,
+                    index: 0,
+                    kind: CursorKind::LoopBody { body, continuing },
+                }),
+                S::Break => {
+                    let cursors = &mut self.frame_mut().cursors;
+                    while let Some(cursor) = cursors.pop() {
+                        if !matches!(cursor.kind, CursorKind::Block) {
+                            break;
+                        }
+                    }
+                }
+                S::Continue => {
+                    let cursors = &mut self.frame_mut().cursors;
+                    while let Some(cursor) = cursors.pop() {
+                        if let CursorKind::LoopBody { body, continuing } = cursor.kind {
+                            cursors.push(Cursor {
+                                block: continuing,
+                                index: 0,
+                                kind: CursorKind::LoopContinuing { body, continuing },
+                            });
+                            break;
+                        }
+                    }
+                }
+                S::Return { value } => {
+                    let value = match value {
+                        Some(value) => Some(self.eval(value)?),
+                        None => None,
+                    };
+                    if let Some(status) = self.return_value(value) {
+                        return Ok(status);
+                    }
+                }
+                S::Kill => return Ok(Status::Killed),
+                S::Barrier(_) => return Ok(Status::Barrier),
+                S::Store { pointer, value } => {
+                    let value = self.eval(value)?;
+                    match self.eval(pointer)? {
+                        Value::Pointer(pointer) => self.module().store(pointer, &value)?,
+                        other => return fault!("unable to store into {:?}", other),
+                    }
+                }
+                S::ImageStore {
+                    image,
+                    coordinate,
+                    array_index,
+                    value,
+                } => {
+                    let (view, _) = self.texture(image)?;
+                    let coords = self.image_coordinates(coordinate, array_index)?;
+                    let components = self.eval(value)?.components();
+                    let mut texel = match components.first() {
+                        Some(&Scalar::I32(_)) => Texel::Sint([0; 4]),
+                        Some(&Scalar::U32(_)) => Texel::Uint([0; 4]),
+                        _ => Texel::Float([0.0; 4]),
+                    };
+                    for (i, &c) in components.iter().enumerate() {
+                        match texel {
+                            Texel::Float(ref mut v) => v[i] = c.as_f32(),
+                            Texel::Sint(ref mut v) => v[i] = c.as_i32(),
+                            Texel::Uint(ref mut v) => v[i] = c.as_u32(),
+                        }
+                    }
+                    image::store(view, coords, texel);
+                }
+                S::Atomic {
+                    pointer,
+                    fun,
+                    value,
+                    result,
+                } => {
+                    let old = self.atomic(pointer, fun, value, result)?;
+                    self.frame_mut().expressions[result.index()] = Some(old);
+                }
+                S::Call {
+                    function,
+                    ref arguments,
+                    result,
+                } => {
+                    let arguments = arguments
+                        .iter()
+                        .map(|&arg| self.eval(arg))
+                        .collect::<Result<_>>()?;
+                    let function = &self.module().ir.functions[function];
+                    self.push_frame(function, arguments, result);
+                }
+            }
+        }
+    }
+}
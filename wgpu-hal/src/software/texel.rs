@@ -0,0 +1,453 @@
+//! Conversions between texel storage and values.
+//!
+//! Color formats are stored exactly like their linear layout in buffers,
+//! so copies between buffers and textures are plain byte copies. Depth is
+//! always stored as a 32-bit float, followed by a stencil byte and padding
+//! for the formats that have a stencil aspect.
+
+#![allow(clippy::needless_range_loop)]
+
+use std::ops::Range;
+
+use wgt::TextureFormat as Tf;
+
+/// Typed content of a texel, with the missing components defaulted to `(0, 0, 0, 1)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Texel {
+    Float([f32; 4]),
+    Sint([i32; 4]),
+    Uint([u32; 4]),
+}
+
+impl Texel {
+    pub fn to_float(self) -> [f32; 4] {
+        match self {
+            Self::Float(v) => v,
+            Self::Sint(v) => [v[0] as f32, v[1] as f32, v[2] as f32, v[3] as f32],
+            Self::Uint(v) => [v[0] as f32, v[1] as f32, v[2] as f32, v[3] as f32],
+        }
+    }
+}
+
+pub(super) fn is_supported(format: Tf) -> bool {
+    !format.describe().is_compressed()
+}
+
+/// Size of a texel in the texture storage.
+pub(super) fn texel_size(format: Tf) -> u32 {
+    match format {
+        Tf::Depth32Float | Tf::Depth24Plus => 4,
+        Tf::Depth32FloatStencil8 | Tf::Depth24PlusStencil8 | Tf::Depth24UnormStencil8 => 8,
+        _ => format.describe().block_size as u32,
+    }
+}
+
+/// Bytes of a texel that belong to the given aspects, as laid out in buffer copies.
+pub(super) fn aspect_range(format: Tf, aspects: crate::FormatAspects) -> Range<usize> {
+    let has_depth = aspects.contains(crate::FormatAspects::DEPTH);
+    let has_stencil = aspects.contains(crate::FormatAspects::STENCIL);
+    match format {
+        Tf::Depth32Float | Tf::Depth24Plus => 0..4,
+        Tf::Depth32FloatStencil8 | Tf::Depth24PlusStencil8 | Tf::Depth24UnormStencil8 => {
+            match (has_depth, has_stencil) {
+                (true, false) => 0..4,
+                (false, true) => 4..5,
+                _ => 0..8,
+            }
+        }
+        _ => 0..texel_size(format) as usize,
+    }
+}
+
+pub(super) fn read_depth(bytes: &[u8]) -> f32 {
+    f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+pub(super) fn write_depth(bytes: &mut [u8], depth: f32) {
+    bytes[..4].copy_from_slice(&depth.to_le_bytes());
+}
+
+pub(super) fn read_stencil(format: Tf, bytes: &[u8]) -> u32 {
+    match format {
+        Tf::Depth32FloatStencil8 | Tf::Depth24PlusStencil8 | Tf::Depth24UnormStencil8 => {
+            bytes[4] as u32
+        }
+        _ => 0,
+    }
+}
+
+pub(super) fn write_stencil(format: Tf, bytes: &mut [u8], stencil: u32) {
+    match format {
+        Tf::Depth32FloatStencil8 | Tf::Depth24PlusStencil8 | Tf::Depth24UnormStencil8 => {
+            bytes[4] = stencil as u8
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(super) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub(super) fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1F) as i32;
+    let mantissa = (bits & 0x3FF) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1F if mantissa == 0.0 => f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+pub(super) fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7F_FFFF;
+    if exponent == 0xFF {
+        return sign | 0x7C00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1F {
+        sign | 0x7C00
+    } else if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - half_exponent) as u32;
+        let rounded = (mantissa + (1 << (shift - 1))) >> shift;
+        sign | rounded as u16
+    } else {
+        let rounded = (mantissa + 0x1000) >> 13;
+        // A carry out of the mantissa correctly bumps the exponent.
+        sign | (((half_exponent as u32) << 10) + rounded) as u16
+    }
+}
+
+/// Decodes an unsigned float without a sign bit, as used by packed formats.
+fn decode_ufloat(bits: u32, mantissa_bits: u32) -> f32 {
+    let exponent = (bits >> mantissa_bits) as i32;
+    let mantissa = (bits & ((1 << mantissa_bits) - 1)) as f32;
+    let scale = (1u32 << mantissa_bits) as f32;
+    match exponent {
+        0 => mantissa / scale * 2f32.powi(-14),
+        0x1F if mantissa == 0.0 => f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => (1.0 + mantissa / scale) * 2f32.powi(exponent - 15),
+    }
+}
+
+fn encode_ufloat(value: f32, mantissa_bits: u32) -> u32 {
+    if value.is_nan() {
+        return (0x1F << mantissa_bits) | 1;
+    }
+    let half = f32_to_f16(value.max(0.0)) as u32;
+    // Half floats share the exponent encoding, only the mantissa is shorter.
+    half >> (10 - mantissa_bits)
+}
+
+fn decode_rgb9e5(bits: u32) -> [f32; 4] {
+    let exponent = (bits >> 27) as i32 - 15 - 9;
+    let scale = 2f32.powi(exponent);
+    [
+        (bits & 0x1FF) as f32 * scale,
+        ((bits >> 9) & 0x1FF) as f32 * scale,
+        ((bits >> 18) & 0x1FF) as f32 * scale,
+        1.0,
+    ]
+}
+
+fn encode_rgb9e5(value: [f32; 4]) -> u32 {
+    const MAX: f32 = 65408.0;
+    let rgb = [
+        value[0].max(0.0).min(MAX),
+        value[1].max(0.0).min(MAX),
+        value[2].max(0.0).min(MAX),
+    ];
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    let mut exponent = (max.log2().floor() as i32).max(-16) + 1 + 15;
+    let mut scale = 2f32.powi(exponent - 15 - 9);
+    if (max / scale + 0.5).floor() as u32 == 512 {
+        exponent += 1;
+        scale *= 2.0;
+    }
+    let mantissa = |v: f32| ((v / scale + 0.5).floor() as u32).min(0x1FF);
+    mantissa(rgb[0])
+        | mantissa(rgb[1]) << 9
+        | mantissa(rgb[2]) << 18
+        | (exponent.max(0) as u32) << 27
+}
+
+fn unorm(bits: u32, max: u32) -> f32 {
+    bits as f32 / max as f32
+}
+
+fn snorm(bits: i32, max: i32) -> f32 {
+    (bits as f32 / max as f32).max(-1.0)
+}
+
+fn to_unorm(value: f32, max: u32) -> u32 {
+    (value.max(0.0).min(1.0) * max as f32 + 0.5) as u32
+}
+
+fn to_snorm(value: f32, max: i32) -> i32 {
+    (value.max(-1.0).min(1.0) * max as f32).round() as i32
+}
+
+fn read_u16(bytes: &[u8], index: usize) -> u16 {
+    u16::from_le_bytes([bytes[index * 2], bytes[index * 2 + 1]])
+}
+
+fn read_u32(bytes: &[u8], index: usize) -> u32 {
+    let b = &bytes[index * 4..index * 4 + 4];
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn write_u16(bytes: &mut [u8], index: usize, value: u16) {
+    bytes[index * 2..index * 2 + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u32(bytes: &mut [u8], index: usize, value: u32) {
+    bytes[index * 4..index * 4 + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+pub(super) fn decode(format: Tf, bytes: &[u8]) -> Texel {
+    let count = format.describe().components as usize;
+    let mut float = [0.0, 0.0, 0.0, 1.0];
+    let mut sint = [0, 0, 0, 1];
+    let mut uint = [0, 0, 0, 1];
+    match format {
+        Tf::R8Unorm | Tf::Rg8Unorm | Tf::Rgba8Unorm => {
+            for i in 0..count {
+                float[i] = unorm(bytes[i] as u32, 0xFF);
+            }
+        }
+        Tf::Rgba8UnormSrgb => {
+            for i in 0..4 {
+                float[i] = unorm(bytes[i] as u32, 0xFF);
+            }
+            for value in float[..3].iter_mut() {
+                *value = srgb_to_linear(*value);
+            }
+        }
+        Tf::Bgra8Unorm | Tf::Bgra8UnormSrgb => {
+            for (i, &channel) in [2, 1, 0, 3].iter().enumerate() {
+                float[i] = unorm(bytes[channel] as u32, 0xFF);
+            }
+            if format == Tf::Bgra8UnormSrgb {
+                for value in float[..3].iter_mut() {
+                    *value = srgb_to_linear(*value);
+                }
+            }
+        }
+        Tf::R8Snorm | Tf::Rg8Snorm | Tf::Rgba8Snorm => {
+            for i in 0..count {
+                float[i] = snorm(bytes[i] as i8 as i32, 0x7F);
+            }
+        }
+        Tf::R8Uint | Tf::Rg8Uint | Tf::Rgba8Uint => {
+            for i in 0..count {
+                uint[i] = bytes[i] as u32;
+            }
+            return Texel::Uint(uint);
+        }
+        Tf::R8Sint | Tf::Rg8Sint | Tf::Rgba8Sint => {
+            for i in 0..count {
+                sint[i] = bytes[i] as i8 as i32;
+            }
+            return Texel::Sint(sint);
+        }
+        Tf::R16Unorm | Tf::Rg16Unorm | Tf::Rgba16Unorm => {
+            for i in 0..count {
+                float[i] = unorm(read_u16(bytes, i) as u32, 0xFFFF);
+            }
+        }
+        Tf::R16Snorm | Tf::Rg16Snorm | Tf::Rgba16Snorm => {
+            for i in 0..count {
+                float[i] = snorm(read_u16(bytes, i) as i16 as i32, 0x7FFF);
+            }
+        }
+        Tf::R16Float | Tf::Rg16Float | Tf::Rgba16Float => {
+            for i in 0..count {
+                float[i] = f16_to_f32(read_u16(bytes, i));
+            }
+        }
+        Tf::R16Uint | Tf::Rg16Uint | Tf::Rgba16Uint => {
+            for i in 0..count {
+                uint[i] = read_u16(bytes, i) as u32;
+            }
+            return Texel::Uint(uint);
+        }
+        Tf::R16Sint | Tf::Rg16Sint | Tf::Rgba16Sint => {
+            for i in 0..count {
+                sint[i] = read_u16(bytes, i) as i16 as i32;
+            }
+            return Texel::Sint(sint);
+        }
+        Tf::R32Float | Tf::Rg32Float | Tf::Rgba32Float => {
+            for i in 0..count {
+                float[i] = f32::from_bits(read_u32(bytes, i));
+            }
+        }
+        Tf::R32Uint | Tf::Rg32Uint | Tf::Rgba32Uint => {
+            for i in 0..count {
+                uint[i] = read_u32(bytes, i);
+            }
+            return Texel::Uint(uint);
+        }
+        Tf::R32Sint | Tf::Rg32Sint | Tf::Rgba32Sint => {
+            for i in 0..count {
+                sint[i] = read_u32(bytes, i) as i32;
+            }
+            return Texel::Sint(sint);
+        }
+        Tf::Rgb10a2Unorm => {
+            let bits = read_u32(bytes, 0);
+            float = [
+                unorm(bits & 0x3FF, 0x3FF),
+                unorm((bits >> 10) & 0x3FF, 0x3FF),
+                unorm((bits >> 20) & 0x3FF, 0x3FF),
+                unorm(bits >> 30, 0x3),
+            ];
+        }
+        Tf::Rg11b10Float => {
+            let bits = read_u32(bytes, 0);
+            float = [
+                decode_ufloat(bits & 0x7FF, 6),
+                decode_ufloat((bits >> 11) & 0x7FF, 6),
+                decode_ufloat(bits >> 22, 5),
+                1.0,
+            ];
+        }
+        Tf::Rgb9e5Ufloat => float = decode_rgb9e5(read_u32(bytes, 0)),
+        Tf::Depth32Float
+        | Tf::Depth24Plus
+        | Tf::Depth32FloatStencil8
+        | Tf::Depth24PlusStencil8
+        | Tf::Depth24UnormStencil8 => float[0] = read_depth(bytes),
+        _ => log::error!("Unable to decode texels of {:?}", format),
+    }
+    Texel::Float(float)
+}
+
+pub(super) fn encode(format: Tf, texel: Texel, bytes: &mut [u8]) {
+    let count = format.describe().components as usize;
+    let float = texel.to_float();
+    let uint = match texel {
+        Texel::Uint(v) => v,
+        Texel::Sint(v) => [v[0] as u32, v[1] as u32, v[2] as u32, v[3] as u32],
+        Texel::Float(v) => [v[0] as u32, v[1] as u32, v[2] as u32, v[3] as u32],
+    };
+    match format {
+        Tf::R8Unorm | Tf::Rg8Unorm | Tf::Rgba8Unorm => {
+            for i in 0..count {
+                bytes[i] = to_unorm(float[i], 0xFF) as u8;
+            }
+        }
+        Tf::Rgba8UnormSrgb => {
+            for i in 0..4 {
+                let value = if i < 3 {
+                    linear_to_srgb(float[i].max(0.0))
+                } else {
+                    float[i]
+                };
+                bytes[i] = to_unorm(value, 0xFF) as u8;
+            }
+        }
+        Tf::Bgra8Unorm | Tf::Bgra8UnormSrgb => {
+            for (i, &channel) in [2, 1, 0, 3].iter().enumerate() {
+                let value = if i < 3 && format == Tf::Bgra8UnormSrgb {
+                    linear_to_srgb(float[i].max(0.0))
+                } else {
+                    float[i]
+                };
+                bytes[channel] = to_unorm(value, 0xFF) as u8;
+            }
+        }
+        Tf::R8Snorm | Tf::Rg8Snorm | Tf::Rgba8Snorm => {
+            for i in 0..count {
+                bytes[i] = to_snorm(float[i], 0x7F) as i8 as u8;
+            }
+        }
+        Tf::R8Uint | Tf::Rg8Uint | Tf::Rgba8Uint | Tf::R8Sint | Tf::Rg8Sint | Tf::Rgba8Sint => {
+            for i in 0..count {
+                bytes[i] = uint[i] as u8;
+            }
+        }
+        Tf::R16Unorm | Tf::Rg16Unorm | Tf::Rgba16Unorm => {
+            for i in 0..count {
+                write_u16(bytes, i, to_unorm(float[i], 0xFFFF) as u16);
+            }
+        }
+        Tf::R16Snorm | Tf::Rg16Snorm | Tf::Rgba16Snorm => {
+            for i in 0..count {
+                write_u16(bytes, i, to_snorm(float[i], 0x7FFF) as i16 as u16);
+            }
+        }
+        Tf::R16Float | Tf::Rg16Float | Tf::Rgba16Float => {
+            for i in 0..count {
+                write_u16(bytes, i, f32_to_f16(float[i]));
+            }
+        }
+        Tf::R16Uint
+        | Tf::Rg16Uint
+        | Tf::Rgba16Uint
+        | Tf::R16Sint
+        | Tf::Rg16Sint
+        | Tf::Rgba16Sint => {
+            for i in 0..count {
+                write_u16(bytes, i, uint[i] as u16);
+            }
+        }
+        Tf::R32Float | Tf::Rg32Float | Tf::Rgba32Float => {
+            for i in 0..count {
+                write_u32(bytes, i, float[i].to_bits());
+            }
+        }
+        Tf::R32Uint
+        | Tf::Rg32Uint
+        | Tf::Rgba32Uint
+        | Tf::R32Sint
+        | Tf::Rg32Sint
+        | Tf::Rgba32Sint => {
+            for i in 0..count {
+                write_u32(bytes, i, uint[i]);
+            }
+        }
+        Tf::Rgb10a2Unorm => {
+            let bits = to_unorm(float[0], 0x3FF)
+                | to_unorm(float[1], 0x3FF) << 10
+                | to_unorm(float[2], 0x3FF) << 20
+                | to_unorm(float[3], 0x3) << 30;
+            write_u32(bytes, 0, bits);
+        }
+        Tf::Rg11b10Float => {
+            let bits = encode_ufloat(float[0], 6)
+                | encode_ufloat(float[1], 6) << 11
+                | encode_ufloat(float[2], 5) << 22;
+            write_u32(bytes, 0, bits);
+        }
+        Tf::Rgb9e5Ufloat => write_u32(bytes, 0, encode_rgb9e5(float)),
+        Tf::Depth32Float
+        | Tf::Depth24Plus
+        | Tf::Depth32FloatStencil8
+        | Tf::Depth24PlusStencil8
+        | Tf::Depth24UnormStencil8 => write_depth(bytes, float[0]),
+        _ => log::error!("Unable to encode texels of {:?}", format),
+    }
+}
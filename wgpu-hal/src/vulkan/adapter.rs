@@ -361,7 +361,8 @@ impl PhysicalDeviceFeatures {
             | F::ADDRESS_MODE_CLAMP_TO_ZERO
             | F::TIMESTAMP_QUERY
             | F::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-            | F::CLEAR_TEXTURE;
+            | F::CLEAR_TEXTURE
+            | F::PIPELINE_CACHE;
         let mut dl_flags = Df::all();
 
         dl_flags.set(Df::CUBE_ARRAY_TEXTURES, self.core.image_cube_array != 0);
@@ -1138,7 +1139,7 @@ impl super::Adapter {
     }
 
     pub fn required_device_extensions(&self, features: wgt::Features) -> Vec<&'static CStr> {
-        let (supported_extensions, unsupported_extensions) = self
+        let (mut supported_extensions, unsupported_extensions) = self
             .phd_capabilities
             .get_required_extensions(features)
             .iter()
@@ -1150,6 +1151,15 @@ impl super::Adapter {
             log::warn!("Missing extensions: {:?}", unsupported_extensions);
         }
 
+        // The budgets are only queried for memory reports, which needs the properties2 functions.
+        if self.instance.get_physical_device_properties.is_some()
+            && self
+                .phd_capabilities
+                .supports_extension(vk::ExtMemoryBudgetFn::name())
+        {
+            supported_extensions.push(vk::ExtMemoryBudgetFn::name());
+        }
+
         log::debug!("Supported extensions: {:?}", supported_extensions);
         supported_extensions
     }
@@ -1319,12 +1329,14 @@ impl super::Adapter {
             },
             vendor_id: self.phd_capabilities.properties.vendor_id,
             timestamp_period: self.phd_capabilities.properties.limits.timestamp_period,
+            pipeline_cache_uuid: self.phd_capabilities.properties.pipeline_cache_uuid,
             uab_types,
             downlevel_flags: self.downlevel_flags,
             private_caps: self.private_caps.clone(),
             workarounds: self.workarounds,
             render_passes: Mutex::new(Default::default()),
             framebuffers: Mutex::new(Default::default()),
+            memory_stats: Mutex::new(Default::default()),
         });
         let mut relay_semaphores = [vk::Semaphore::null(); 2];
         for sem in relay_semaphores.iter_mut() {
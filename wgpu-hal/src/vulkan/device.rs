@@ -1,4 +1,4 @@
-use super::conv;
+use super::{conv, spirv};
 
 use arrayvec::ArrayVec;
 use ash::{extensions::khr, vk};
@@ -282,6 +282,18 @@ impl super::DeviceShared {
     }
 }
 
+impl super::MemoryStats {
+    fn suballocate(&mut self, size: u64) {
+        self.suballocations += 1;
+        self.suballocation_bytes += size;
+    }
+
+    fn free(&mut self, size: u64) {
+        self.suballocations -= 1;
+        self.suballocation_bytes -= size;
+    }
+}
+
 impl gpu_alloc::MemoryDevice<vk::DeviceMemory> for super::DeviceShared {
     unsafe fn allocate_memory(
         &self,
@@ -302,7 +314,13 @@ impl gpu_alloc::MemoryDevice<vk::DeviceMemory> for super::DeviceShared {
         }
 
         match self.raw.allocate_memory(&info, None) {
-            Ok(memory) => Ok(memory),
+            Ok(memory) => {
+                self.memory_stats
+                    .lock()
+                    .blocks
+                    .insert(memory, (memory_type, size));
+                Ok(memory)
+            }
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
                 Err(gpu_alloc::OutOfMemory::OutOfDeviceMemory)
             }
@@ -315,6 +333,7 @@ impl gpu_alloc::MemoryDevice<vk::DeviceMemory> for super::DeviceShared {
     }
 
     unsafe fn deallocate_memory(&self, memory: vk::DeviceMemory) {
+        self.memory_stats.lock().blocks.remove(&memory);
         self.raw.free_memory(memory, None);
     }
 
@@ -502,6 +521,7 @@ impl
 struct CompiledStage {
     create_info: vk::PipelineShaderStageCreateInfo,
     _entry_point: CString,
+    _specialization: Option<Box<spirv::Specialization>>,
     temp_raw_module: Option<vk::ShaderModule>,
 }
 
@@ -634,8 +654,19 @@ impl super::Device {
         binding_map: &naga::back::spv::BindingMap,
     ) -> Result<CompiledStage, crate::PipelineError> {
         let stage_flags = crate::auxil::map_naga_stage(naga_stage);
+        let mut specialization = None;
         let vk_module = match *stage.module {
-            super::ShaderModule::Raw(raw) => raw,
+            super::ShaderModule::Raw {
+                raw,
+                ref spec_constants,
+            } => {
+                if !stage.constants.is_empty() {
+                    let spec = spirv::Specialization::new(spec_constants, stage.constants)
+                        .map_err(|e| crate::PipelineError::Linkage(stage_flags, e))?;
+                    specialization = Some(Box::new(spec));
+                }
+                raw
+            }
             super::ShaderModule::Intermediate {
                 ref naga_shader,
                 runtime_checks,
@@ -663,10 +694,14 @@ impl super::Device {
                 } else {
                     &self.naga_options
                 };
+                // Naga doesn't emit specialization constants, so the values
+                // are baked into the module instead.
+                let module =
+                    crate::auxil::apply_pipeline_constants(&naga_shader.module, stage.constants);
                 let spv = {
                     profiling::scope!("naga::spv::write_vec");
                     naga::back::spv::write_vec(
-                        &naga_shader.module,
+                        &module,
                         &naga_shader.info,
                         options,
                         Some(&pipeline_options),
@@ -678,17 +713,21 @@ impl super::Device {
         };
 
         let entry_point = CString::new(stage.entry_point).unwrap();
-        let create_info = vk::PipelineShaderStageCreateInfo::builder()
+        let mut create_info = vk::PipelineShaderStageCreateInfo::builder()
             .stage(conv::map_shader_stage(stage_flags))
             .module(vk_module)
-            .name(&entry_point)
-            .build();
+            .name(&entry_point);
+        if let Some(ref spec) = specialization {
+            create_info = create_info.specialization_info(&spec.info);
+        }
+        let create_info = create_info.build();
 
         Ok(CompiledStage {
             create_info,
             _entry_point: entry_point,
+            _specialization: specialization,
             temp_raw_module: match *stage.module {
-                super::ShaderModule::Raw(_) => None,
+                super::ShaderModule::Raw { .. } => None,
                 super::ShaderModule::Intermediate { .. } => Some(vk_module),
             },
         })
@@ -769,6 +808,7 @@ impl crate::Device<super::Api> for super::Device {
         self.shared
             .raw
             .bind_buffer_memory(raw, *block.memory(), block.offset())?;
+        self.shared.memory_stats.lock().suballocate(block.size());
 
         if let Some(label) = desc.label {
             self.shared
@@ -782,9 +822,9 @@ impl crate::Device<super::Api> for super::Device {
     }
     unsafe fn destroy_buffer(&self, buffer: super::Buffer) {
         self.shared.raw.destroy_buffer(buffer.raw, None);
-        self.mem_allocator
-            .lock()
-            .dealloc(&*self.shared, buffer.block.into_inner());
+        let block = buffer.block.into_inner();
+        self.shared.memory_stats.lock().free(block.size());
+        self.mem_allocator.lock().dealloc(&*self.shared, block);
     }
 
     unsafe fn map_buffer(
@@ -879,6 +919,7 @@ impl crate::Device<super::Api> for super::Device {
         self.shared
             .raw
             .bind_image_memory(raw, *block.memory(), block.offset())?;
+        self.shared.memory_stats.lock().suballocate(block.size());
 
         if let Some(label) = desc.label {
             self.shared
@@ -901,6 +942,7 @@ impl crate::Device<super::Api> for super::Device {
             self.shared.raw.destroy_image(texture.raw, None);
         }
         if let Some(block) = texture.block {
+            self.shared.memory_stats.lock().free(block.size());
             self.mem_allocator.lock().dealloc(&*self.shared, block);
         }
     }
@@ -1379,10 +1421,18 @@ impl crate::Device<super::Api> for super::Device {
     ) -> Result<super::ShaderModule, crate::ShaderError> {
         let spv = match shader {
             crate::ShaderInput::Naga(naga_shader) => {
-                if self
-                    .shared
-                    .workarounds
-                    .contains(super::Workarounds::SEPARATE_ENTRY_POINTS)
+                // Naga doesn't emit specialization constants, so the modules
+                // overriding constants are compiled per pipeline, with the values baked in.
+                let has_overrides = naga_shader
+                    .module
+                    .constants
+                    .iter()
+                    .any(|(_, constant)| constant.specialization.is_some());
+                if has_overrides
+                    || self
+                        .shared
+                        .workarounds
+                        .contains(super::Workarounds::SEPARATE_ENTRY_POINTS)
                 {
                     return Ok(super::ShaderModule::Intermediate {
                         naga_shader,
@@ -1418,11 +1468,14 @@ impl crate::Device<super::Api> for super::Device {
                 .set_object_name(vk::ObjectType::SHADER_MODULE, raw, label);
         }
 
-        Ok(super::ShaderModule::Raw(raw))
+        Ok(super::ShaderModule::Raw {
+            raw,
+            spec_constants: spirv::reflect_spec_constants(&spv),
+        })
     }
     unsafe fn destroy_shader_module(&self, module: super::ShaderModule) {
         match module {
-            super::ShaderModule::Raw(raw) => {
+            super::ShaderModule::Raw { raw, .. } => {
                 self.shared.raw.destroy_shader_module(raw, None);
             }
             super::ShaderModule::Intermediate { .. } => {}
@@ -1633,7 +1686,12 @@ impl crate::Device<super::Api> for super::Device {
             profiling::scope!("vkCreateGraphicsPipelines");
             self.shared
                 .raw
-                .create_graphics_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                .create_graphics_pipelines(
+                    desc.cache
+                        .map_or(vk::PipelineCache::null(), |cache| cache.raw),
+                    &vk_infos,
+                    None,
+                )
                 .map_err(|(_, e)| crate::DeviceError::from(e))?
         };
 
@@ -1681,7 +1739,12 @@ impl crate::Device<super::Api> for super::Device {
             profiling::scope!("vkCreateComputePipelines");
             self.shared
                 .raw
-                .create_compute_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                .create_compute_pipelines(
+                    desc.cache
+                        .map_or(vk::PipelineCache::null(), |cache| cache.raw),
+                    &vk_infos,
+                    None,
+                )
                 .map_err(|(_, e)| crate::DeviceError::from(e))?
         };
 
@@ -1701,6 +1764,37 @@ impl crate::Device<super::Api> for super::Device {
         self.shared.raw.destroy_pipeline(pipeline.raw, None);
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        let mut info = vk::PipelineCacheCreateInfo::builder();
+        if let Some(data) = desc.data {
+            info = info.initial_data(data);
+        }
+
+        let raw = {
+            profiling::scope!("vkCreatePipelineCache");
+            self.shared.raw.create_pipeline_cache(&info, None)?
+        };
+        if let Some(label) = desc.label {
+            self.shared
+                .set_object_name(vk::ObjectType::PIPELINE_CACHE, raw, label);
+        }
+
+        Ok(super::PipelineCache { raw })
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        Some(self.shared.pipeline_cache_uuid)
+    }
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        profiling::scope!("vkGetPipelineCacheData");
+        self.shared.raw.get_pipeline_cache_data(cache.raw).ok()
+    }
+    unsafe fn destroy_pipeline_cache(&self, cache: super::PipelineCache) {
+        self.shared.raw.destroy_pipeline_cache(cache.raw, None);
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1861,6 +1955,71 @@ impl crate::Device<super::Api> for super::Device {
                 .end_frame_capture(raw_vk_instance_dispatch_table, ptr::null_mut())
         }
     }
+
+    fn memory_report(&self) -> wgt::MemoryReport {
+        let instance = &self.shared.instance;
+        let mut budget_properties = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+        let (properties, has_budget) = match instance.get_physical_device_properties {
+            Some(ref get_properties)
+                if self
+                    .shared
+                    .enabled_extensions
+                    .contains(&vk::ExtMemoryBudgetFn::name()) =>
+            {
+                let mut properties2 = vk::PhysicalDeviceMemoryProperties2::builder()
+                    .push_next(&mut budget_properties)
+                    .build();
+                unsafe {
+                    get_properties.get_physical_device_memory_properties2(
+                        self.shared.physical_device,
+                        &mut properties2,
+                    );
+                }
+                (properties2.memory_properties, true)
+            }
+            _ => {
+                let properties = unsafe {
+                    instance
+                        .raw
+                        .get_physical_device_memory_properties(self.shared.physical_device)
+                };
+                (properties, false)
+            }
+        };
+
+        let stats = self.shared.memory_stats.lock();
+        let mut heaps = properties.memory_heaps[..properties.memory_heap_count as usize]
+            .iter()
+            .map(|heap| wgt::MemoryHeapReport {
+                size: heap.size,
+                device_local: heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL),
+                usage: 0,
+                budget: None,
+            })
+            .collect::<Vec<_>>();
+        if has_budget {
+            for (index, heap) in heaps.iter_mut().enumerate() {
+                heap.usage = budget_properties.heap_usage[index];
+                heap.budget = Some(budget_properties.heap_budget[index]);
+            }
+        } else {
+            for &(memory_type, size) in stats.blocks.values() {
+                let heap_index = properties.memory_types[memory_type as usize].heap_index;
+                heaps[heap_index as usize].usage += size;
+            }
+        }
+
+        wgt::MemoryReport {
+            heaps,
+            allocator: Some(wgt::AllocatorReport {
+                blocks: stats.blocks.len() as u64,
+                block_bytes: stats.blocks.values().map(|&(_, size)| size).sum(),
+                suballocations: stats.suballocations,
+                suballocation_bytes: stats.suballocation_bytes,
+            }),
+            resources: wgt::ResourceMemoryReport::default(),
+        }
+    }
 }
 
 impl From<gpu_alloc::AllocationError> for crate::DeviceError {
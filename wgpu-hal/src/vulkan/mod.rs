@@ -30,6 +30,7 @@ mod command;
 mod conv;
 mod device;
 mod instance;
+mod spirv;
 
 use std::{borrow::Borrow, ffi::CStr, num::NonZeroU32, sync::Arc};
 
@@ -72,6 +73,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 struct DebugUtils {
@@ -319,12 +321,23 @@ struct DeviceShared {
     extension_fns: DeviceExtensionFunctions,
     vendor_id: u32,
     timestamp_period: f32,
+    pipeline_cache_uuid: [u8; vk::UUID_SIZE],
     uab_types: UpdateAfterBindTypes,
     downlevel_flags: wgt::DownlevelFlags,
     private_caps: PrivateCapabilities,
     workarounds: Workarounds,
     render_passes: Mutex<fxhash::FxHashMap<RenderPassKey, vk::RenderPass>>,
     framebuffers: Mutex<fxhash::FxHashMap<FramebufferKey, vk::Framebuffer>>,
+    memory_stats: Mutex<MemoryStats>,
+}
+
+/// Bookkeeping of the device memory for the memory reports.
+#[derive(Default)]
+struct MemoryStats {
+    /// Memory objects allocated from the driver, with their memory type and size.
+    blocks: fxhash::FxHashMap<vk::DeviceMemory, (u32, u64)>,
+    suballocations: u64,
+    suballocation_bytes: u64,
 }
 
 pub struct Device {
@@ -464,7 +477,10 @@ pub struct CommandBuffer {
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum ShaderModule {
-    Raw(vk::ShaderModule),
+    Raw {
+        raw: vk::ShaderModule,
+        spec_constants: Vec<spirv::SpecConstant>,
+    },
     Intermediate {
         naga_shader: crate::NagaShader,
         runtime_checks: bool,
@@ -481,6 +497,11 @@ pub struct ComputePipeline {
     raw: vk::Pipeline,
 }
 
+#[derive(Debug)]
+pub struct PipelineCache {
+    raw: vk::PipelineCache,
+}
+
 #[derive(Debug)]
 pub struct QuerySet {
     raw: vk::QueryPool,
@@ -0,0 +1,286 @@
+//! Reflection of the specialization constants of SPIR-V modules.
+
+use ash::vk;
+use std::{collections::HashMap, convert::TryFrom};
+
+const OP_NAME: u32 = 5;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_SPEC_CONSTANT_TRUE: u32 = 48;
+const OP_SPEC_CONSTANT_FALSE: u32 = 49;
+const OP_SPEC_CONSTANT: u32 = 50;
+const OP_DECORATE: u32 = 71;
+const DECORATION_SPEC_ID: u32 = 1;
+const HEADER_WORDS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScalarType {
+    Bool,
+    Int { signed: bool, width: u32 },
+    Float { width: u32 },
+}
+
+/// Scalar specialization constant that can be set by a pipeline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpecConstant {
+    id: u32,
+    name: Option<String>,
+    ty: ScalarType,
+}
+
+impl SpecConstant {
+    fn matches(&self, key: &str) -> bool {
+        self.name.as_deref() == Some(key) || key.parse() == Ok(self.id)
+    }
+
+    /// Encodes the value as the specialization data of the constant,
+    /// or returns `None` if it's not representable by the constant type.
+    fn encode(&self, value: f64) -> Option<Vec<u8>> {
+        fn integer(value: f64, min: f64, max: f64) -> Option<f64> {
+            if value.fract() == 0.0 && value >= min && value <= max {
+                Some(value)
+            } else {
+                None
+            }
+        }
+
+        Some(match self.ty {
+            ScalarType::Bool => vk::Bool32::from(value != 0.0).to_ne_bytes().to_vec(),
+            ScalarType::Float { width: 32 } if (value as f32).is_finite() => {
+                (value as f32).to_ne_bytes().to_vec()
+            }
+            ScalarType::Float { width: 64 } if value.is_finite() => value.to_ne_bytes().to_vec(),
+            ScalarType::Int {
+                signed: true,
+                width,
+            } => {
+                let value = integer(value, i64::MIN as f64, i64::MAX as f64)? as i64;
+                match width {
+                    8 => i8::try_from(value).ok()?.to_ne_bytes().to_vec(),
+                    16 => i16::try_from(value).ok()?.to_ne_bytes().to_vec(),
+                    32 => i32::try_from(value).ok()?.to_ne_bytes().to_vec(),
+                    64 => value.to_ne_bytes().to_vec(),
+                    _ => return None,
+                }
+            }
+            ScalarType::Int {
+                signed: false,
+                width,
+            } => {
+                let value = integer(value, 0.0, u64::MAX as f64)? as u64;
+                match width {
+                    8 => u8::try_from(value).ok()?.to_ne_bytes().to_vec(),
+                    16 => u16::try_from(value).ok()?.to_ne_bytes().to_vec(),
+                    32 => u32::try_from(value).ok()?.to_ne_bytes().to_vec(),
+                    64 => value.to_ne_bytes().to_vec(),
+                    _ => return None,
+                }
+            }
+            ScalarType::Float { .. } => return None,
+        })
+    }
+}
+
+/// Lists the scalar specialization constants of a SPIR-V module that have a `SpecId`.
+pub fn reflect_spec_constants(spv: &[u32]) -> Vec<SpecConstant> {
+    let mut names = HashMap::new();
+    let mut spec_ids = HashMap::new();
+    let mut types = HashMap::new();
+    let mut constants = Vec::new();
+
+    let mut words = spv.get(HEADER_WORDS..).unwrap_or(&[]);
+    while let Some(&first) = words.first() {
+        let word_count = (first >> 16) as usize;
+        if word_count == 0 || word_count > words.len() {
+            log::warn!("Malformed SPIR-V instruction, stopping the reflection");
+            break;
+        }
+        let (instruction, rest) = words.split_at(word_count);
+        words = rest;
+        match (first & 0xFFFF, &instruction[1..]) {
+            (OP_NAME, &[target, ref name @ ..]) => {
+                let bytes = name
+                    .iter()
+                    .flat_map(|word| word.to_le_bytes())
+                    .take_while(|&byte| byte != 0)
+                    .collect::<Vec<_>>();
+                names.insert(target, String::from_utf8_lossy(&bytes).into_owned());
+            }
+            (OP_DECORATE, &[target, DECORATION_SPEC_ID, id, ..]) => {
+                spec_ids.insert(target, id);
+            }
+            (OP_TYPE_BOOL, &[result]) => {
+                types.insert(result, ScalarType::Bool);
+            }
+            (OP_TYPE_INT, &[result, width, signedness]) => {
+                types.insert(
+                    result,
+                    ScalarType::Int {
+                        signed: signedness != 0,
+                        width,
+                    },
+                );
+            }
+            (OP_TYPE_FLOAT, &[result, width, ..]) => {
+                types.insert(result, ScalarType::Float { width });
+            }
+            (OP_SPEC_CONSTANT_TRUE, &[ty, result])
+            | (OP_SPEC_CONSTANT_FALSE, &[ty, result])
+            | (OP_SPEC_CONSTANT, &[ty, result, ..]) => {
+                constants.push((ty, result));
+            }
+            _ => {}
+        }
+    }
+
+    constants
+        .into_iter()
+        .filter_map(|(ty, result)| {
+            Some(SpecConstant {
+                id: *spec_ids.get(&result)?,
+                name: names.remove(&result),
+                ty: *types.get(&ty)?,
+            })
+        })
+        .collect()
+}
+
+/// Specialization info of a shader stage, with the data it points to.
+pub struct Specialization {
+    pub info: vk::SpecializationInfo,
+    _entries: Vec<vk::SpecializationMapEntry>,
+    _data: Vec<u8>,
+}
+
+impl Specialization {
+    pub fn new(
+        spec_constants: &[SpecConstant],
+        constants: &crate::PipelineConstants,
+    ) -> Result<Self, String> {
+        let mut entries = Vec::<vk::SpecializationMapEntry>::with_capacity(constants.len());
+        let mut data = Vec::new();
+        for (key, &value) in constants.iter() {
+            let sc = spec_constants
+                .iter()
+                .find(|sc| sc.matches(key))
+                .ok_or_else(|| {
+                    format!(
+                        "pipeline constant '{}' doesn't match any specialization constant",
+                        key
+                    )
+                })?;
+            if entries.iter().any(|entry| entry.constant_id == sc.id) {
+                return Err(format!(
+                    "specialization constant {} is specified more than once",
+                    sc.id
+                ));
+            }
+            let bytes = sc.encode(value).ok_or_else(|| {
+                format!(
+                    "value {} of pipeline constant '{}' is not representable by its type {:?}",
+                    value, key, sc.ty
+                )
+            })?;
+            entries.push(vk::SpecializationMapEntry {
+                constant_id: sc.id,
+                offset: data.len() as u32,
+                size: bytes.len(),
+            });
+            data.extend_from_slice(&bytes);
+        }
+
+        // The pointers stay valid when the vectors are moved.
+        let info = vk::SpecializationInfo::builder()
+            .map_entries(&entries)
+            .data(&data)
+            .build();
+        Ok(Self {
+            info,
+            _entries: entries,
+            _data: data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reflect_spec_constants, ScalarType, SpecConstant, Specialization};
+
+    /// Encodes an instruction, with the string operand padded to whole words.
+    fn instruction(opcode: u32, operands: &[u32], string: Option<&str>) -> Vec<u32> {
+        let mut words = operands.to_vec();
+        if let Some(string) = string {
+            let mut bytes = string.as_bytes().to_vec();
+            bytes.resize((bytes.len() / 4 + 1) * 4, 0);
+            words.extend(
+                bytes
+                    .chunks(4)
+                    .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])),
+            );
+        }
+        let mut result = vec![((words.len() as u32 + 1) << 16) | opcode];
+        result.extend(words);
+        result
+    }
+
+    fn module() -> Vec<u32> {
+        let mut spv = vec![0x0723_0203, 0x0001_0000, 0, 10, 0];
+        spv.extend(instruction(super::OP_NAME, &[5], Some("scale")));
+        spv.extend(instruction(super::OP_DECORATE, &[5, 1, 3], None));
+        spv.extend(instruction(super::OP_DECORATE, &[6, 1, 7], None));
+        spv.extend(instruction(super::OP_TYPE_BOOL, &[1], None));
+        spv.extend(instruction(super::OP_TYPE_INT, &[2, 32, 0], None));
+        spv.extend(instruction(super::OP_TYPE_FLOAT, &[3, 32], None));
+        spv.extend(instruction(super::OP_SPEC_CONSTANT, &[2, 5, 1], None));
+        spv.extend(instruction(super::OP_SPEC_CONSTANT_TRUE, &[1, 6], None));
+        // not decorated with a `SpecId`
+        spv.extend(instruction(super::OP_SPEC_CONSTANT, &[3, 8, 0], None));
+        spv
+    }
+
+    #[test]
+    fn reflect() {
+        assert_eq!(
+            reflect_spec_constants(&module()),
+            [
+                SpecConstant {
+                    id: 3,
+                    name: Some("scale".to_string()),
+                    ty: ScalarType::Int {
+                        signed: false,
+                        width: 32
+                    },
+                },
+                SpecConstant {
+                    id: 7,
+                    name: None,
+                    ty: ScalarType::Bool,
+                },
+            ]
+        );
+        assert!(reflect_spec_constants(&[]).is_empty());
+    }
+
+    #[test]
+    fn specialize() {
+        let spec_constants = reflect_spec_constants(&module());
+        let constants = [("scale".to_string(), 4.0), ("7".to_string(), 1.0)]
+            .iter()
+            .cloned()
+            .collect();
+        let spec = Specialization::new(&spec_constants, &constants).unwrap();
+        assert_eq!(spec.info.map_entry_count, 2);
+        assert_eq!(spec.info.data_size, 8);
+
+        for &(key, value) in [("scale", -1.0), ("scale", 0.5), ("unknown", 1.0)].iter() {
+            let constants = [(key.to_string(), value)].iter().cloned().collect();
+            assert!(Specialization::new(&spec_constants, &constants).is_err());
+        }
+        let constants = [("scale".to_string(), 1.0), ("3".to_string(), 2.0)]
+            .iter()
+            .cloned()
+            .collect();
+        assert!(Specialization::new(&spec_constants, &constants).is_err());
+    }
+}
@@ -1,6 +1,16 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod matrix;
+#[cfg(not(target_arch = "wasm32"))]
+mod report;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod inner {
+    use super::{
+        matrix,
+        report::{self, AdapterReport},
+    };
     use std::{
+        fs::File,
         mem::size_of,
         process::{exit, Command},
         time::Instant,
@@ -118,6 +128,47 @@ mod inner {
             for (idx, adapter) in adapters.into_iter().enumerate() {
                 print_info_from_adapter(&adapter, idx)
             }
+        } else if args[0] == "--json" {
+            let reports: Vec<_> = adapters.iter().map(AdapterReport::new).collect();
+            println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+        } else if args[0] == "--compare" {
+            let load = |path: Option<&String>| -> Vec<AdapterReport> {
+                let path = path.expect("Usage: wgpu-info --compare <a.json> <b.json>");
+                serde_json::from_reader(File::open(path).unwrap()).unwrap()
+            };
+            report::compare(&load(args.get(1)), &load(args.get(2)));
+        } else if args[0] == "--formats" {
+            let style = match args.get(1) {
+                Some(name) => matrix::Style::from_name(name)
+                    .expect("Usage: wgpu-info --formats [table|markdown|csv]"),
+                None => matrix::Style::Table,
+            };
+
+            // The window has to outlive the surface.
+            #[cfg(feature = "winit")]
+            let (_event_loop, window) = {
+                let event_loop = winit::event_loop::EventLoop::new();
+                let window = winit::window::WindowBuilder::new()
+                    .with_visible(false)
+                    .build(&event_loop)
+                    .unwrap();
+                (event_loop, window)
+            };
+            #[cfg(feature = "winit")]
+            let surface = Some(unsafe { instance.create_surface(&window) });
+            #[cfg(not(feature = "winit"))]
+            let surface = None;
+            if surface.is_none() && style != matrix::Style::Csv {
+                println!(
+                    "No surface available, enable the `winit` feature to list surface formats"
+                );
+                println!();
+            }
+
+            matrix::print_header(style);
+            for (idx, adapter) in adapters.iter().enumerate() {
+                matrix::print_adapter(style, idx, adapter, surface.as_ref());
+            }
         } else {
             let all_start = Instant::now();
 
@@ -142,6 +193,7 @@ mod inner {
                             wgpu::Backend::Dx11 => "dx11",
                             wgpu::Backend::Gl => "gl",
                             wgpu::Backend::BrowserWebGpu => "webgpu",
+                            wgpu::Backend::Software => "software",
                         },
                     )
                     .spawn()
@@ -0,0 +1,150 @@
+//! Texture format capability matrix of an adapter.
+
+use super::report::{all_texture_formats, texture_format_name};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    Table,
+    Markdown,
+    Csv,
+}
+
+impl Style {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(Self::Table),
+            "markdown" | "md" => Some(Self::Markdown),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Column {
+    Usage(wgpu::TextureUsages),
+    Flag(wgpu::TextureFormatFeatureFlags),
+}
+
+impl Column {
+    fn all() -> Vec<Self> {
+        let usages = (0..32)
+            .filter_map(|i| wgpu::TextureUsages::from_bits(1 << i))
+            .map(Column::Usage);
+        let flags = (0..32)
+            .filter_map(|i| wgpu::TextureFormatFeatureFlags::from_bits(1 << i))
+            .map(Column::Flag);
+        usages.chain(flags).collect()
+    }
+
+    fn name(&self) -> String {
+        match *self {
+            Column::Usage(usage) => format!("{:?}", usage),
+            Column::Flag(flag) => format!("{:?}", flag),
+        }
+    }
+
+    fn is_supported(&self, features: &wgpu::TextureFormatFeatures) -> bool {
+        match *self {
+            Column::Usage(usage) => features.allowed_usages.contains(usage),
+            Column::Flag(flag) => features.flags.contains(flag),
+        }
+    }
+}
+
+const FORMAT_WIDTH: usize = 24;
+
+/// Prints the header of the matrix.
+///
+/// With CSV all the adapters share one table, otherwise every adapter gets its own.
+pub fn print_header(style: Style) {
+    if style == Style::Csv {
+        let names = Column::all().iter().map(Column::name).collect::<Vec<_>>();
+        println!("adapter,backend,format,{}", names.join(","));
+    }
+}
+
+pub fn print_adapter(
+    style: Style,
+    idx: usize,
+    adapter: &wgpu::Adapter,
+    surface: Option<&wgpu::Surface>,
+) {
+    let info = adapter.get_info();
+    let columns = Column::all();
+    let names = columns.iter().map(Column::name).collect::<Vec<_>>();
+
+    match style {
+        Style::Table => {
+            println!("Adapter {}: {} ({:?})", idx, info.name, info.backend);
+            print!("\t{:<width$}", "Format", width = FORMAT_WIDTH);
+            for name in names.iter() {
+                print!(" {}", name);
+            }
+            println!();
+        }
+        Style::Markdown => {
+            println!("### Adapter {}: {} ({:?})", idx, info.name, info.backend);
+            println!();
+            println!("| Format | {} |", names.join(" | "));
+            println!("|---{}|", "|---".repeat(names.len()));
+        }
+        Style::Csv => {}
+    }
+
+    for format in all_texture_formats() {
+        let features = adapter.get_texture_format_features(format);
+        let cells = columns.iter().map(|column| column.is_supported(&features));
+        let name = texture_format_name(format);
+        match style {
+            Style::Table => {
+                print!("\t{:<width$}", name, width = FORMAT_WIDTH);
+                for (supported, column_name) in cells.zip(names.iter()) {
+                    let mark = if supported { "x" } else { "-" };
+                    print!(" {:^width$}", mark, width = column_name.len());
+                }
+                println!();
+            }
+            Style::Markdown => {
+                let marks = cells
+                    .map(|supported| if supported { "x" } else { " " })
+                    .collect::<Vec<_>>();
+                println!("| {} | {} |", name, marks.join(" | "));
+            }
+            Style::Csv => {
+                let marks = cells
+                    .map(|supported| if supported { "1" } else { "0" })
+                    .collect::<Vec<_>>();
+                println!(
+                    "{:?},{:?},{},{}",
+                    info.name,
+                    info.backend,
+                    name,
+                    marks.join(",")
+                );
+            }
+        }
+    }
+
+    // Surface capabilities don't fit in the CSV table.
+    if let (Some(surface), false) = (surface, style == Style::Csv) {
+        println!();
+        match (
+            surface.get_supported_formats(adapter),
+            surface.get_supported_present_modes(adapter),
+        ) {
+            (Some(formats), Some(modes)) => {
+                let formats = formats
+                    .into_iter()
+                    .map(texture_format_name)
+                    .collect::<Vec<_>>();
+                println!("Surface formats: {}", formats.join(", "));
+                println!("Present modes: {:?}", modes);
+            }
+            _ => println!("Surface is not supported"),
+        }
+    }
+    if style != Style::Csv {
+        println!();
+    }
+}
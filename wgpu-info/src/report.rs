@@ -0,0 +1,498 @@
+//! Machine-readable adapter reports, and comparison between them.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem::size_of,
+};
+
+/// Every texture format known to `wgpu`.
+pub fn all_texture_formats() -> Vec<wgpu::TextureFormat> {
+    use wgpu::{AstcBlock, AstcChannel, TextureFormat as Tf};
+    let mut formats = vec![
+        Tf::R8Unorm,
+        Tf::R8Snorm,
+        Tf::R8Uint,
+        Tf::R8Sint,
+        Tf::R16Uint,
+        Tf::R16Sint,
+        Tf::R16Unorm,
+        Tf::R16Snorm,
+        Tf::R16Float,
+        Tf::Rg8Unorm,
+        Tf::Rg8Snorm,
+        Tf::Rg8Uint,
+        Tf::Rg8Sint,
+        Tf::R32Uint,
+        Tf::R32Sint,
+        Tf::R32Float,
+        Tf::Rg16Uint,
+        Tf::Rg16Sint,
+        Tf::Rg16Unorm,
+        Tf::Rg16Snorm,
+        Tf::Rg16Float,
+        Tf::Rgba8Unorm,
+        Tf::Rgba8UnormSrgb,
+        Tf::Rgba8Snorm,
+        Tf::Rgba8Uint,
+        Tf::Rgba8Sint,
+        Tf::Bgra8Unorm,
+        Tf::Bgra8UnormSrgb,
+        Tf::Rgb10a2Unorm,
+        Tf::Rg11b10Float,
+        Tf::Rg32Uint,
+        Tf::Rg32Sint,
+        Tf::Rg32Float,
+        Tf::Rgba16Uint,
+        Tf::Rgba16Sint,
+        Tf::Rgba16Unorm,
+        Tf::Rgba16Snorm,
+        Tf::Rgba16Float,
+        Tf::Rgba32Uint,
+        Tf::Rgba32Sint,
+        Tf::Rgba32Float,
+        Tf::Depth32Float,
+        Tf::Depth32FloatStencil8,
+        Tf::Depth24Plus,
+        Tf::Depth24PlusStencil8,
+        Tf::Depth24UnormStencil8,
+        Tf::Rgb9e5Ufloat,
+        Tf::Bc1RgbaUnorm,
+        Tf::Bc1RgbaUnormSrgb,
+        Tf::Bc2RgbaUnorm,
+        Tf::Bc2RgbaUnormSrgb,
+        Tf::Bc3RgbaUnorm,
+        Tf::Bc3RgbaUnormSrgb,
+        Tf::Bc4RUnorm,
+        Tf::Bc4RSnorm,
+        Tf::Bc5RgUnorm,
+        Tf::Bc5RgSnorm,
+        Tf::Bc6hRgbUfloat,
+        Tf::Bc6hRgbSfloat,
+        Tf::Bc7RgbaUnorm,
+        Tf::Bc7RgbaUnormSrgb,
+        Tf::Etc2Rgb8Unorm,
+        Tf::Etc2Rgb8UnormSrgb,
+        Tf::Etc2Rgb8A1Unorm,
+        Tf::Etc2Rgb8A1UnormSrgb,
+        Tf::Etc2Rgba8Unorm,
+        Tf::Etc2Rgba8UnormSrgb,
+        Tf::EacR11Unorm,
+        Tf::EacR11Snorm,
+        Tf::EacRg11Unorm,
+        Tf::EacRg11Snorm,
+    ];
+    for &block in &[
+        AstcBlock::B4x4,
+        AstcBlock::B5x4,
+        AstcBlock::B5x5,
+        AstcBlock::B6x5,
+        AstcBlock::B6x6,
+        AstcBlock::B8x5,
+        AstcBlock::B8x6,
+        AstcBlock::B8x8,
+        AstcBlock::B10x5,
+        AstcBlock::B10x6,
+        AstcBlock::B10x8,
+        AstcBlock::B10x10,
+        AstcBlock::B12x10,
+        AstcBlock::B12x12,
+    ] {
+        for &channel in &[AstcChannel::Unorm, AstcChannel::UnormSrgb, AstcChannel::Hdr] {
+            formats.push(Tf::Astc { block, channel });
+        }
+    }
+    formats
+}
+
+/// Name of a texture format, as it is spelled in Rust.
+pub fn texture_format_name(format: wgpu::TextureFormat) -> String {
+    match format {
+        wgpu::TextureFormat::Astc { block, channel } => format!("Astc{:?}{:?}", block, channel),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Names of the single-bit flags set in a bitflags value.
+macro_rules! flag_names {
+    ($ty:ty, $flags:expr) => {{
+        let flags = $flags;
+        (0..size_of::<$ty>() * 8)
+            .filter_map(|i| <$ty>::from_bits(1 << i))
+            .filter(|&bit| <$ty>::all().contains(bit) && flags.contains(bit))
+            .map(|bit| format!("{:?}", bit))
+            .collect::<Vec<String>>()
+    }};
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct InfoReport {
+    pub name: String,
+    pub vendor: usize,
+    pub device: usize,
+    pub device_type: String,
+    pub backend: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DownlevelReport {
+    pub shader_model: String,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FormatReport {
+    pub allowed_usages: Vec<String>,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AdapterReport {
+    pub info: InfoReport,
+    pub features: Vec<String>,
+    pub limits: BTreeMap<String, u64>,
+    pub downlevel: DownlevelReport,
+    pub texture_formats: BTreeMap<String, FormatReport>,
+}
+
+fn limits_map(limits: wgpu::Limits) -> BTreeMap<String, u64> {
+    let wgpu::Limits {
+        max_texture_dimension_1d,
+        max_texture_dimension_2d,
+        max_texture_dimension_3d,
+        max_texture_array_layers,
+        max_bind_groups,
+        max_dynamic_uniform_buffers_per_pipeline_layout,
+        max_dynamic_storage_buffers_per_pipeline_layout,
+        max_sampled_textures_per_shader_stage,
+        max_samplers_per_shader_stage,
+        max_storage_buffers_per_shader_stage,
+        max_storage_textures_per_shader_stage,
+        max_uniform_buffers_per_shader_stage,
+        max_uniform_buffer_binding_size,
+        max_storage_buffer_binding_size,
+        max_vertex_buffers,
+        max_vertex_attributes,
+        max_vertex_buffer_array_stride,
+        max_push_constant_size,
+        min_uniform_buffer_offset_alignment,
+        min_storage_buffer_offset_alignment,
+        max_inter_stage_shader_components,
+        max_compute_workgroup_storage_size,
+        max_compute_invocations_per_workgroup,
+        max_compute_workgroup_size_x,
+        max_compute_workgroup_size_y,
+        max_compute_workgroup_size_z,
+        max_compute_workgroups_per_dimension,
+    } = limits;
+    let list = [
+        ("max_texture_dimension_1d", max_texture_dimension_1d),
+        ("max_texture_dimension_2d", max_texture_dimension_2d),
+        ("max_texture_dimension_3d", max_texture_dimension_3d),
+        ("max_texture_array_layers", max_texture_array_layers),
+        ("max_bind_groups", max_bind_groups),
+        (
+            "max_dynamic_uniform_buffers_per_pipeline_layout",
+            max_dynamic_uniform_buffers_per_pipeline_layout,
+        ),
+        (
+            "max_dynamic_storage_buffers_per_pipeline_layout",
+            max_dynamic_storage_buffers_per_pipeline_layout,
+        ),
+        (
+            "max_sampled_textures_per_shader_stage",
+            max_sampled_textures_per_shader_stage,
+        ),
+        (
+            "max_samplers_per_shader_stage",
+            max_samplers_per_shader_stage,
+        ),
+        (
+            "max_storage_buffers_per_shader_stage",
+            max_storage_buffers_per_shader_stage,
+        ),
+        (
+            "max_storage_textures_per_shader_stage",
+            max_storage_textures_per_shader_stage,
+        ),
+        (
+            "max_uniform_buffers_per_shader_stage",
+            max_uniform_buffers_per_shader_stage,
+        ),
+        (
+            "max_uniform_buffer_binding_size",
+            max_uniform_buffer_binding_size,
+        ),
+        (
+            "max_storage_buffer_binding_size",
+            max_storage_buffer_binding_size,
+        ),
+        ("max_vertex_buffers", max_vertex_buffers),
+        ("max_vertex_attributes", max_vertex_attributes),
+        (
+            "max_vertex_buffer_array_stride",
+            max_vertex_buffer_array_stride,
+        ),
+        ("max_push_constant_size", max_push_constant_size),
+        (
+            "min_uniform_buffer_offset_alignment",
+            min_uniform_buffer_offset_alignment,
+        ),
+        (
+            "min_storage_buffer_offset_alignment",
+            min_storage_buffer_offset_alignment,
+        ),
+        (
+            "max_inter_stage_shader_components",
+            max_inter_stage_shader_components,
+        ),
+        (
+            "max_compute_workgroup_storage_size",
+            max_compute_workgroup_storage_size,
+        ),
+        (
+            "max_compute_invocations_per_workgroup",
+            max_compute_invocations_per_workgroup,
+        ),
+        ("max_compute_workgroup_size_x", max_compute_workgroup_size_x),
+        ("max_compute_workgroup_size_y", max_compute_workgroup_size_y),
+        ("max_compute_workgroup_size_z", max_compute_workgroup_size_z),
+        (
+            "max_compute_workgroups_per_dimension",
+            max_compute_workgroups_per_dimension,
+        ),
+    ];
+    list.iter()
+        .map(|&(name, value)| (name.to_string(), value as u64))
+        .collect()
+}
+
+impl AdapterReport {
+    pub fn new(adapter: &wgpu::Adapter) -> Self {
+        let info = adapter.get_info();
+        let downlevel = adapter.get_downlevel_capabilities();
+        let texture_formats = all_texture_formats()
+            .into_iter()
+            .map(|format| {
+                let features = adapter.get_texture_format_features(format);
+                let report = FormatReport {
+                    allowed_usages: flag_names!(wgpu::TextureUsages, features.allowed_usages),
+                    flags: flag_names!(wgpu::TextureFormatFeatureFlags, features.flags),
+                };
+                (texture_format_name(format), report)
+            })
+            .collect();
+        Self {
+            info: InfoReport {
+                name: info.name,
+                vendor: info.vendor,
+                device: info.device,
+                device_type: format!("{:?}", info.device_type),
+                backend: format!("{:?}", info.backend),
+            },
+            features: flag_names!(wgpu::Features, adapter.features()),
+            limits: limits_map(adapter.limits()),
+            downlevel: DownlevelReport {
+                shader_model: format!("{:?}", downlevel.shader_model),
+                flags: flag_names!(wgpu::DownlevelFlags, downlevel.flags),
+            },
+            texture_formats,
+        }
+    }
+}
+
+fn compare_sets(what: &str, a: &[String], b: &[String]) {
+    let a = a.iter().collect::<BTreeSet<_>>();
+    let b = b.iter().collect::<BTreeSet<_>>();
+    for name in a.difference(&b) {
+        println!("\t- {} {}", what, name);
+    }
+    for name in b.difference(&a) {
+        println!("\t+ {} {}", what, name);
+    }
+}
+
+fn compare_adapters(a: &AdapterReport, b: &AdapterReport) {
+    if a.info != b.info {
+        println!("\t- info {:?}", a.info);
+        println!("\t+ info {:?}", b.info);
+    }
+    compare_sets("feature", &a.features, &b.features);
+    for (name, value) in a.limits.iter() {
+        match b.limits.get(name) {
+            Some(other) if other == value => {}
+            Some(other) => println!("\t~ limit {}: {} -> {}", name, value, other),
+            None => println!("\t- limit {}: {}", name, value),
+        }
+    }
+    for (name, value) in b.limits.iter() {
+        if !a.limits.contains_key(name) {
+            println!("\t+ limit {}: {}", name, value);
+        }
+    }
+    if a.downlevel.shader_model != b.downlevel.shader_model {
+        println!(
+            "\t~ shader model: {} -> {}",
+            a.downlevel.shader_model, b.downlevel.shader_model
+        );
+    }
+    compare_sets("downlevel flag", &a.downlevel.flags, &b.downlevel.flags);
+    let empty = FormatReport {
+        allowed_usages: Vec::new(),
+        flags: Vec::new(),
+    };
+    let formats = a
+        .texture_formats
+        .keys()
+        .chain(b.texture_formats.keys())
+        .collect::<BTreeSet<_>>();
+    for format in formats {
+        let fa = a.texture_formats.get(format).unwrap_or(&empty);
+        let fb = b.texture_formats.get(format).unwrap_or(&empty);
+        compare_sets(
+            &format!("{} usage", format),
+            &fa.allowed_usages,
+            &fb.allowed_usages,
+        );
+        compare_sets(&format!("{} flag", format), &fa.flags, &fb.flags);
+    }
+}
+
+/// Adapters of two reports that are compared with each other.
+struct AdapterPairs<'a> {
+    pairs: Vec<(&'a AdapterReport, &'a AdapterReport)>,
+    only_a: Vec<&'a AdapterReport>,
+    only_b: Vec<&'a AdapterReport>,
+}
+
+/// Matches the adapters by their backend and name, in order.
+///
+/// The remaining adapters are paired by their backend and index among the
+/// remaining ones, so that an adapter renamed by a driver update is still compared.
+fn pair_adapters<'a>(a: &'a [AdapterReport], b: &'a [AdapterReport]) -> AdapterPairs<'a> {
+    let mut pairs = Vec::new();
+    let mut only_a = Vec::new();
+    let mut only_b = b.iter().collect::<Vec<_>>();
+    for report in a {
+        match only_b.iter().position(|other| {
+            other.info.backend == report.info.backend && other.info.name == report.info.name
+        }) {
+            Some(index) => pairs.push((report, only_b.remove(index))),
+            None => only_a.push(report),
+        }
+    }
+    only_a.retain(|report| {
+        match only_b
+            .iter()
+            .position(|other| other.info.backend == report.info.backend)
+        {
+            Some(index) => {
+                pairs.push((report, only_b.remove(index)));
+                false
+            }
+            None => true,
+        }
+    });
+    AdapterPairs {
+        pairs,
+        only_a,
+        only_b,
+    }
+}
+
+/// Prints the differences between the adapters of two reports.
+///
+/// See [`pair_adapters`] for how the adapters are matched.
+pub fn compare(a: &[AdapterReport], b: &[AdapterReport]) {
+    let adapters = pair_adapters(a, b);
+    for (report, other) in adapters.pairs {
+        if report.info.name == other.info.name {
+            println!("Adapter {} ({}):", report.info.name, report.info.backend);
+        } else {
+            println!(
+                "Adapter {} -> {} ({}):",
+                report.info.name, other.info.name, report.info.backend
+            );
+        }
+        if report == other {
+            println!("\tidentical");
+        } else {
+            compare_adapters(report, other);
+        }
+    }
+    for report in adapters.only_a {
+        println!(
+            "Adapter {} ({}) is only in the first report",
+            report.info.name, report.info.backend
+        );
+    }
+    for report in adapters.only_b {
+        println!(
+            "Adapter {} ({}) is only in the second report",
+            report.info.name, report.info.backend
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(backend: &str, name: &str) -> AdapterReport {
+        AdapterReport {
+            info: InfoReport {
+                name: name.to_string(),
+                vendor: 0,
+                device: 0,
+                device_type: "Other".to_string(),
+                backend: backend.to_string(),
+            },
+            features: Vec::new(),
+            limits: BTreeMap::new(),
+            downlevel: DownlevelReport {
+                shader_model: "Sm5".to_string(),
+                flags: Vec::new(),
+            },
+            texture_formats: BTreeMap::new(),
+        }
+    }
+
+    fn names(reports: &[&AdapterReport]) -> Vec<String> {
+        reports
+            .iter()
+            .map(|report| format!("{} {}", report.info.backend, report.info.name))
+            .collect()
+    }
+
+    #[test]
+    fn pair_renamed_adapters() {
+        let a = [
+            adapter("Vulkan", "GPU 1.0"),
+            adapter("Vulkan", "Software"),
+            adapter("Gl", "GPU 1.0"),
+            adapter("Metal", "Only A"),
+        ];
+        let b = [
+            adapter("Gl", "GPU 2.0"),
+            adapter("Vulkan", "Software"),
+            adapter("Vulkan", "GPU 2.0"),
+            adapter("Dx12", "Only B"),
+        ];
+        let adapters = pair_adapters(&a, &b);
+        let pairs = adapters
+            .pairs
+            .iter()
+            .map(|&(report, other)| (names(&[report]), names(&[other])))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [
+                (names(&[&a[1]]), names(&[&b[1]])),
+                (names(&[&a[0]]), names(&[&b[2]])),
+                (names(&[&a[2]]), names(&[&b[0]])),
+            ]
+        );
+        assert_eq!(names(&adapters.only_a), ["Metal Only A"]);
+        assert_eq!(names(&adapters.only_b), ["Dx12 Only B"]);
+    }
+}
@@ -63,6 +63,8 @@ pub enum Backend {
     Gl = 5,
     /// WebGPU in the browser
     BrowserWebGpu = 6,
+    /// CPU rasterizer, used for testing without a GPU
+    Software = 7,
 }
 
 /// Power Preference when choosing a physical adapter.
@@ -103,6 +105,9 @@ bitflags::bitflags! {
         const DX11 = 1 << Backend::Dx11 as u32;
         /// Supported when targeting the web through webassembly
         const BROWSER_WEBGPU = 1 << Backend::BrowserWebGpu as u32;
+        /// Supported everywhere when the `software` feature is enabled. Runs on the CPU and is
+        /// meant for testing, so it's not part of the primary or secondary sets.
+        const SOFTWARE = 1 << Backend::Software as u32;
         /// All the apis that wgpu offers first tier of support for.
         ///
         /// Vulkan + Metal + DX12 + Browser WebGPU
@@ -423,6 +428,8 @@ bitflags::bitflags! {
         /// - DX12
         /// - Vulkan
         /// - Metal (Emulated on top of `draw_indirect` and `draw_indexed_indirect`)
+        /// - OpenGL ES 3.1+ (Emulated on top of `draw_indirect` and `draw_indexed_indirect`
+        ///   without GL_EXT_multi_draw_indirect / GL_ARB_multi_draw_indirect)
         ///
         /// This is a native only feature.
         const MULTI_DRAW_INDIRECT = 1 << 23;
@@ -433,6 +440,7 @@ bitflags::bitflags! {
         /// Supported platforms:
         /// - DX12
         /// - Vulkan 1.2+ (or VK_KHR_draw_indirect_count)
+        /// - OpenGL 4.6+ (or GL_ARB_indirect_parameters)
         ///
         /// This is a native only feature.
         const MULTI_DRAW_INDIRECT_COUNT = 1 << 24;
@@ -473,6 +481,8 @@ bitflags::bitflags! {
         /// - DX12
         /// - Vulkan
         /// - Metal
+        /// - OpenGL
+        /// - OpenGL ES with GL_NV_polygon_mode or GL_ANGLE_polygon_mode
         ///
         /// This is a native only feature.
         const POLYGON_MODE_LINE = 1 << 27;
@@ -483,6 +493,8 @@ bitflags::bitflags! {
         /// Supported platforms:
         /// - DX12
         /// - Vulkan
+        /// - OpenGL
+        /// - OpenGL ES with GL_NV_polygon_mode
         ///
         /// This is a native only feature.
         const POLYGON_MODE_POINT = 1 << 28;
@@ -569,6 +581,7 @@ bitflags::bitflags! {
         ///
         /// Supported platforms:
         /// - Vulkan
+        /// - OpenGL ES with GL_OVR_multiview2
         ///
         /// This is a native only feature.
         const MULTIVIEW = 1 << 37;
@@ -598,6 +611,32 @@ bitflags::bitflags! {
         ///
         /// This is a native-only feature.
         const TEXTURE_COMPRESSION_ASTC_HDR = 1 << 40;
+        /// Enables validation of the contents of indirect buffers.
+        ///
+        /// Without this feature, only the range of an indirect buffer is checked against
+        /// its size. With it, the arguments of every indirect draw and dispatch recorded in
+        /// a render or compute pass are checked on the GPU before the pass executes: draws
+        /// reading vertices, indices or instances beyond the bound buffers, and dispatches
+        /// exceeding [`Limits::max_compute_workgroups_per_dimension`], are turned into no-ops.
+        /// Indirect draws of render bundles are checked as part of the pass executing them.
+        ///
+        /// This is implemented by wgpu itself with an internal compute pass, and costs an
+        /// extra copy and dispatch for every pass using indirect commands.
+        ///
+        /// Supported platforms:
+        /// - All, as long as compute shaders are supported
+        ///
+        /// This is a native only feature.
+        const INDIRECT_ARGUMENT_VALIDATION = 1 << 41;
+        /// Allows the creation of pipeline caches, which can be serialized and reused
+        /// across runs to speed up pipeline creation.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - OpenGL (with `GL_ARB_get_program_binary` / GLES 3.0 program binaries)
+        ///
+        /// This is a native only feature.
+        const PIPELINE_CACHE = 1 << 42;
     }
 }
 
@@ -1126,6 +1165,102 @@ impl<L> DeviceDescriptor<L> {
     }
 }
 
+/// Format of an API call trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TraceFormat {
+    /// A directory with a `trace.ron` file and a separate file per data blob.
+    Ron,
+    /// A single file with length-prefixed actions and embedded data blobs.
+    Binary,
+}
+
+/// Describes where and how the API calls of a device are traced.
+#[derive(Clone, Copy, Debug)]
+pub struct TraceDescriptor<'a> {
+    /// Existing directory of a RON trace, or the file of a binary trace.
+    pub path: &'a std::path::Path,
+    /// Format of the trace.
+    pub format: TraceFormat,
+}
+
+/// Usage of a memory heap of a device.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub struct MemoryHeapReport {
+    /// Size of the heap in bytes.
+    pub size: u64,
+    /// `true` if the heap is local to the device, `false` if it's system memory.
+    pub device_local: bool,
+    /// Bytes of the heap in use.
+    ///
+    /// If `budget` is known, this is the usage of the whole process as reported
+    /// by the driver, otherwise only the memory allocated by this device is counted.
+    pub usage: u64,
+    /// Bytes of the heap the process can use without degrading performance, if known.
+    pub budget: Option<u64>,
+}
+
+/// Statistics of the memory allocator of a device.
+///
+/// Resources are suballocated from memory blocks that are allocated from the driver.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub struct AllocatorReport {
+    /// Number of memory blocks allocated from the driver.
+    pub blocks: u64,
+    /// Total size of the memory blocks in bytes.
+    pub block_bytes: u64,
+    /// Number of resources suballocated from the blocks.
+    pub suballocations: u64,
+    /// Total size of the suballocations in bytes.
+    pub suballocation_bytes: u64,
+}
+
+/// Memory used by the live resources of a device, by kind of resource.
+///
+/// The sizes are computed from the resource descriptors, they don't account for
+/// the padding and alignment of the actual allocations.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub struct ResourceMemoryReport {
+    /// Number of buffers.
+    pub buffers: u64,
+    /// Total size of the buffers in bytes.
+    pub buffer_bytes: u64,
+    /// Number of textures, not including the textures of surfaces.
+    pub textures: u64,
+    /// Total size of the textures in bytes, including all their mip levels.
+    pub texture_bytes: u64,
+    /// Number of query sets.
+    pub query_sets: u64,
+    /// Total size of the query results in bytes.
+    pub query_set_bytes: u64,
+}
+
+/// Memory usage of a device, returned by [`Device::get_memory_report`](../wgpu/struct.Device.html#method.get_memory_report).
+///
+/// The backends report as much as they can find out:
+/// - Vulkan reports the usage of every heap, with budgets if `VK_EXT_memory_budget`
+///   is available, and the statistics of the allocator.
+/// - Metal reports a single heap with the recommended working set size as its budget.
+/// - GLES reports the video memory if `GL_NVX_gpu_memory_info` is available,
+///   or only the free video memory as the budget with `GL_ATI_meminfo`.
+/// - The other backends only report the resources.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub struct MemoryReport {
+    /// Memory heaps of the device.
+    pub heaps: Vec<MemoryHeapReport>,
+    /// Statistics of the memory allocator, if the backend has one.
+    pub allocator: Option<AllocatorReport>,
+    /// Memory used by the resources.
+    pub resources: ResourceMemoryReport,
+}
+
 bitflags::bitflags! {
     /// Describes the shader stages that a binding will be visible from.
     ///
@@ -2292,6 +2427,22 @@ impl Default for ColorWrites {
     }
 }
 
+/// Reason for a device to be lost.
+///
+/// Corresponds to [WebGPU `GPUDeviceLostReason`](
+/// https://gpuweb.github.io/gpuweb/#enumdef-gpudevicelostreason).
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub enum DeviceLostReason {
+    /// The device was lost for a reason outside of the control of the application,
+    /// such as a driver crash or a GPU reset.
+    Unknown = 0,
+    /// The device was explicitly destroyed by the application.
+    Destroyed = 1,
+}
+
 /// Passed to `Device::poll` to control how and if it should block.
 #[derive(Clone)]
 pub enum Maintain<T> {
@@ -224,7 +224,10 @@ async fn setup<E: Example>(title: &str) -> Setup {
                 features: (optional_features & adapter_features) | required_features,
                 limits: needed_limits,
             },
-            trace_dir.ok().as_ref().map(std::path::Path::new),
+            trace_dir.ok().as_ref().map(|dir| wgpu::TraceDescriptor {
+                path: std::path::Path::new(dir),
+                format: wgpu::TraceFormat::Ron,
+            }),
         )
         .await
         .expect("Unable to find a suitable GPU adapter!");
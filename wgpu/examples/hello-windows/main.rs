@@ -132,6 +132,7 @@ async fn run(event_loop: EventLoop<()>, viewports: Vec<(Window, wgpu::Color)>) {
                                 },
                             }],
                             depth_stencil_attachment: None,
+                            occlusion_query_set: None,
                         });
                     }
 
@@ -1,10 +1,11 @@
 use crate::{
     AdapterInfo, BindGroupDescriptor, BindGroupLayoutDescriptor, BindingResource, BufferBinding,
-    CommandEncoderDescriptor, ComputePassDescriptor, ComputePipelineDescriptor,
-    DownlevelCapabilities, Features, Label, Limits, LoadOp, MapMode, Operations,
-    PipelineLayoutDescriptor, RenderBundleEncoderDescriptor, RenderPipelineDescriptor,
-    SamplerDescriptor, ShaderModuleDescriptor, ShaderModuleDescriptorSpirV, ShaderSource,
-    SurfaceStatus, TextureDescriptor, TextureFormat, TextureViewDescriptor,
+    CommandEncoderDescriptor, CompilationInfo, CompilationMessage, CompilationMessageType,
+    ComputePassDescriptor, ComputePipelineDescriptor, DownlevelCapabilities, Features, Label,
+    Limits, LoadOp, MapMode, Operations, PipelineLayoutDescriptor, RenderBundleEncoderDescriptor,
+    RenderPipelineDescriptor, SamplerDescriptor, ShaderModuleDescriptor,
+    ShaderModuleDescriptorSpirV, ShaderSource, SourceLocation, SurfaceStatus, TextureDescriptor,
+    TextureFormat, TextureViewDescriptor,
 };
 
 use arrayvec::ArrayVec;
@@ -91,14 +92,14 @@ impl Context {
         adapter: &wgc::id::AdapterId,
         hal_device: hal::OpenDevice<A>,
         desc: &crate::DeviceDescriptor,
-        trace_dir: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
     ) -> Result<(Device, wgc::id::QueueId), crate::RequestDeviceError> {
         let global = &self.0;
         let (device_id, error) = global.create_device_from_hal(
             *adapter,
             hal_device,
             &desc.map_label(|l| l.map(Borrowed)),
-            trace_dir,
+            trace_desc,
             PhantomData,
         );
         if let Some(err) = error {
@@ -430,6 +431,24 @@ mod pass_impl {
                 )
             }
         }
+
+        fn insert_debug_marker(&mut self, label: &str) {
+            unsafe {
+                let label = std::ffi::CString::new(label).unwrap();
+                wgpu_render_pass_insert_debug_marker(self, label.as_ptr(), 0);
+            }
+        }
+
+        fn push_debug_group(&mut self, group_label: &str) {
+            unsafe {
+                let label = std::ffi::CString::new(group_label).unwrap();
+                wgpu_render_pass_push_debug_group(self, label.as_ptr(), 0);
+            }
+        }
+
+        fn pop_debug_group(&mut self) {
+            wgpu_render_pass_pop_debug_group(self);
+        }
         fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
             wgpu_render_pass_draw(
                 self,
@@ -542,24 +561,6 @@ mod pass_impl {
             wgpu_render_pass_set_stencil_reference(self, reference)
         }
 
-        fn insert_debug_marker(&mut self, label: &str) {
-            unsafe {
-                let label = std::ffi::CString::new(label).unwrap();
-                wgpu_render_pass_insert_debug_marker(self, label.as_ptr(), 0);
-            }
-        }
-
-        fn push_debug_group(&mut self, group_label: &str) {
-            unsafe {
-                let label = std::ffi::CString::new(group_label).unwrap();
-                wgpu_render_pass_push_debug_group(self, label.as_ptr(), 0);
-            }
-        }
-
-        fn pop_debug_group(&mut self) {
-            wgpu_render_pass_pop_debug_group(self);
-        }
-
         fn write_timestamp(&mut self, query_set: &wgc::id::QuerySetId, query_index: u32) {
             wgpu_render_pass_write_timestamp(self, *query_set, query_index)
         }
@@ -576,6 +577,14 @@ mod pass_impl {
             wgpu_render_pass_end_pipeline_statistics_query(self)
         }
 
+        fn begin_occlusion_query(&mut self, query_index: u32) {
+            wgpu_render_pass_begin_occlusion_query(self, query_index)
+        }
+
+        fn end_occlusion_query(&mut self) {
+            wgpu_render_pass_end_occlusion_query(self)
+        }
+
         fn execute_bundles<'a, I: Iterator<Item = &'a wgc::id::RenderBundleId>>(
             &mut self,
             render_bundles: I,
@@ -641,6 +650,24 @@ mod pass_impl {
                 )
             }
         }
+
+        fn insert_debug_marker(&mut self, label: &str) {
+            unsafe {
+                let label = std::ffi::CString::new(label).unwrap();
+                wgpu_render_bundle_insert_debug_marker(self, label.as_ptr());
+            }
+        }
+
+        fn push_debug_group(&mut self, group_label: &str) {
+            unsafe {
+                let label = std::ffi::CString::new(group_label).unwrap();
+                wgpu_render_bundle_push_debug_group(self, label.as_ptr());
+            }
+        }
+
+        fn pop_debug_group(&mut self) {
+            wgpu_render_bundle_pop_debug_group(self);
+        }
         fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
             wgpu_render_bundle_draw(
                 self,
@@ -676,39 +703,58 @@ mod pass_impl {
         }
         fn multi_draw_indirect(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_multi_draw_indirect(self, indirect_buffer.id, indirect_offset, count)
         }
         fn multi_draw_indexed_indirect(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_multi_draw_indexed_indirect(
+                self,
+                indirect_buffer.id,
+                indirect_offset,
+                count,
+            )
         }
         fn multi_draw_indirect_count(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count_buffer: &super::Buffer,
-            _count_buffer_offset: wgt::BufferAddress,
-            _max_count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count_buffer: &super::Buffer,
+            count_buffer_offset: wgt::BufferAddress,
+            max_count: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_multi_draw_indirect_count(
+                self,
+                indirect_buffer.id,
+                indirect_offset,
+                count_buffer.id,
+                count_buffer_offset,
+                max_count,
+            )
         }
         fn multi_draw_indexed_indirect_count(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count_buffer: &super::Buffer,
-            _count_buffer_offset: wgt::BufferAddress,
-            _max_count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count_buffer: &super::Buffer,
+            count_buffer_offset: wgt::BufferAddress,
+            max_count: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_multi_draw_indexed_indirect_count(
+                self,
+                indirect_buffer.id,
+                indirect_offset,
+                count_buffer.id,
+                count_buffer_offset,
+                max_count,
+            )
         }
     }
 }
@@ -795,6 +841,12 @@ pub struct Texture {
     error_sink: ErrorSink,
 }
 
+#[derive(Debug)]
+pub(crate) struct ShaderModule {
+    id: wgc::id::ShaderModuleId,
+    compilation_info: CompilationInfo,
+}
+
 #[derive(Debug)]
 pub(crate) struct CommandEncoder {
     id: wgc::id::CommandEncoderId,
@@ -806,7 +858,7 @@ impl crate::Context for Context {
     type AdapterId = wgc::id::AdapterId;
     type DeviceId = Device;
     type QueueId = wgc::id::QueueId;
-    type ShaderModuleId = wgc::id::ShaderModuleId;
+    type ShaderModuleId = ShaderModule;
     type BindGroupLayoutId = wgc::id::BindGroupLayoutId;
     type BindGroupId = wgc::id::BindGroupId;
     type TextureViewId = wgc::id::TextureViewId;
@@ -817,6 +869,7 @@ impl crate::Context for Context {
     type PipelineLayoutId = wgc::id::PipelineLayoutId;
     type RenderPipelineId = wgc::id::RenderPipelineId;
     type ComputePipelineId = wgc::id::ComputePipelineId;
+    type PipelineCacheId = wgc::id::PipelineCacheId;
     type CommandEncoderId = CommandEncoder;
     type ComputePassId = wgc::command::ComputePass;
     type RenderPassId = wgc::command::RenderPass;
@@ -833,6 +886,7 @@ impl crate::Context for Context {
     type RequestDeviceFuture =
         Ready<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>;
     type PopErrorScopeFuture = Ready<Option<crate::Error>>;
+    type CompilationInfoFuture = Ready<CompilationInfo>;
 
     fn init(backends: wgt::Backends) -> Self {
         Self(wgc::hub::Global::new(
@@ -879,13 +933,13 @@ impl crate::Context for Context {
         &self,
         adapter: &Self::AdapterId,
         desc: &crate::DeviceDescriptor,
-        trace_dir: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
     ) -> Self::RequestDeviceFuture {
         let global = &self.0;
         let (device_id, error) = wgc::gfx_select!(*adapter => global.adapter_request_device(
             *adapter,
             &desc.map_label(|l| l.map(Borrowed)),
-            trace_dir,
+            trace_desc,
             PhantomData
         ));
         if let Some(err) = error {
@@ -972,6 +1026,20 @@ impl crate::Context for Context {
         }
     }
 
+    fn surface_get_supported_present_modes(
+        &self,
+        surface: &Self::SurfaceId,
+        adapter: &Self::AdapterId,
+    ) -> Option<Vec<wgt::PresentMode>> {
+        let global = &self.0;
+        match wgc::gfx_select!(adapter => global.surface_get_supported_present_modes(surface.id, *adapter))
+        {
+            Ok(modes) => Some(modes),
+            Err(wgc::instance::GetSurfacePreferredFormatError::UnsupportedQueueFamily) => None,
+            Err(err) => self.handle_error_fatal(err, "Surface::get_supported_present_modes"),
+        }
+    }
+
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
@@ -1062,6 +1130,14 @@ impl crate::Context for Context {
         }
     }
 
+    fn device_memory_report(&self, device: &Self::DeviceId) -> wgt::MemoryReport {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_memory_report(device.id)) {
+            Ok(report) => report,
+            Err(err) => self.handle_error_fatal(err, "Device::get_memory_report"),
+        }
+    }
+
     fn device_create_shader_module(
         &self,
         device: &Self::DeviceId,
@@ -1107,16 +1183,24 @@ impl crate::Context for Context {
         let (id, error) = wgc::gfx_select!(
             device.id => global.device_create_shader_module(device.id, &descriptor, source, PhantomData)
         );
-        if let Some(cause) = error {
-            self.handle_error(
-                &device.error_sink,
-                cause,
-                LABEL,
-                desc.label,
-                "Device::create_shader_module",
-            );
+        let compilation_info = match error {
+            Some(cause) => {
+                let compilation_info = CompilationInfo::from(&cause);
+                self.handle_error(
+                    &device.error_sink,
+                    cause,
+                    LABEL,
+                    desc.label,
+                    "Device::create_shader_module",
+                );
+                compilation_info
+            }
+            None => CompilationInfo::default(),
+        };
+        ShaderModule {
+            id,
+            compilation_info,
         }
-        id
     }
 
     unsafe fn device_create_shader_module_spirv(
@@ -1134,16 +1218,24 @@ impl crate::Context for Context {
         let (id, error) = wgc::gfx_select!(
             device.id => global.device_create_shader_module_spirv(device.id, &descriptor, Borrowed(&desc.source), PhantomData)
         );
-        if let Some(cause) = error {
-            self.handle_error(
-                &device.error_sink,
-                cause,
-                LABEL,
-                desc.label,
-                "Device::create_shader_module_spirv",
-            );
+        let compilation_info = match error {
+            Some(cause) => {
+                let compilation_info = CompilationInfo::from(&cause);
+                self.handle_error(
+                    &device.error_sink,
+                    cause,
+                    LABEL,
+                    desc.label,
+                    "Device::create_shader_module_spirv",
+                );
+                compilation_info
+            }
+            None => CompilationInfo::default(),
+        };
+        ShaderModule {
+            id,
+            compilation_info,
         }
-        id
     }
 
     fn device_create_bind_group_layout(
@@ -1345,8 +1437,9 @@ impl crate::Context for Context {
             layout: desc.layout.map(|l| l.id),
             vertex: pipe::VertexState {
                 stage: pipe::ProgrammableStageDescriptor {
-                    module: desc.vertex.module.id,
+                    module: desc.vertex.module.id.id,
                     entry_point: Borrowed(desc.vertex.entry_point),
+                    constants: Borrowed(desc.vertex.constants),
                 },
                 buffers: Borrowed(&vertex_buffers),
             },
@@ -1355,12 +1448,14 @@ impl crate::Context for Context {
             multisample: desc.multisample,
             fragment: desc.fragment.as_ref().map(|frag| pipe::FragmentState {
                 stage: pipe::ProgrammableStageDescriptor {
-                    module: frag.module.id,
+                    module: frag.module.id.id,
                     entry_point: Borrowed(frag.entry_point),
+                    constants: Borrowed(frag.constants),
                 },
                 targets: Borrowed(frag.targets),
             }),
             multiview: desc.multiview,
+            cache: desc.cache.map(|cache| cache.id),
         };
 
         let global = &self.0;
@@ -1404,9 +1499,11 @@ impl crate::Context for Context {
             label: desc.label.map(Borrowed),
             layout: desc.layout.map(|l| l.id),
             stage: pipe::ProgrammableStageDescriptor {
-                module: desc.module.id,
+                module: desc.module.id.id,
                 entry_point: Borrowed(desc.entry_point),
+                constants: Borrowed(desc.constants),
             },
+            cache: desc.cache.map(|cache| cache.id),
         };
 
         let global = &self.0;
@@ -1436,6 +1533,34 @@ impl crate::Context for Context {
         id
     }
 
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        desc: &crate::PipelineCacheDescriptor<'_>,
+    ) -> Self::PipelineCacheId {
+        let descriptor = wgc::pipeline::PipelineCacheDescriptor {
+            label: desc.label.map(Borrowed),
+            fallback: desc.fallback,
+        };
+        let global = &self.0;
+        let (id, error) = wgc::gfx_select!(device.id => global.device_create_pipeline_cache(
+            device.id,
+            &descriptor,
+            desc.data,
+            PhantomData
+        ));
+        if let Some(cause) = error {
+            self.handle_error(
+                &device.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::create_pipeline_cache",
+            );
+        }
+        id
+    }
+
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -1618,6 +1743,28 @@ impl crate::Context for Context {
         }
     }
 
+    fn device_destroy(&self, device: &Self::DeviceId) {
+        let global = &self.0;
+        let res = wgc::gfx_select!(device.id => global.device_destroy(device.id));
+        if let Err(cause) = res {
+            self.handle_error_fatal(cause, "Device::destroy");
+        }
+    }
+
+    fn device_set_device_lost_callback(
+        &self,
+        device: &Self::DeviceId,
+        callback: Box<dyn FnOnce(crate::DeviceLostReason, String) + Send + 'static>,
+    ) {
+        let closure = wgc::device::DeviceLostClosure::from_rust(callback);
+
+        let global = &self.0;
+        let res = wgc::gfx_select!(device.id => global.device_set_device_lost_closure(device.id, closure));
+        if let Err(cause) = res {
+            self.handle_error_fatal(cause, "Device::set_device_lost_callback");
+        }
+    }
+
     fn device_on_uncaptured_error(
         &self,
         device: &Self::DeviceId,
@@ -1641,6 +1788,13 @@ impl crate::Context for Context {
         ready(scope.error)
     }
 
+    fn shader_get_compilation_info(
+        &self,
+        shader: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture {
+        ready(shader.compilation_info.clone())
+    }
+
     fn buffer_map_async(
         &self,
         buffer: &Self::BufferId,
@@ -1793,7 +1947,15 @@ impl crate::Context for Context {
     }
     fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId) {
         let global = &self.0;
-        wgc::gfx_select!(*shader_module => global.shader_module_drop(*shader_module))
+        wgc::gfx_select!(shader_module.id => global.shader_module_drop(shader_module.id))
+    }
+    fn pipeline_cache_get_data(&self, cache: &Self::PipelineCacheId) -> Option<Vec<u8>> {
+        let global = &self.0;
+        wgc::gfx_select!(*cache => global.pipeline_cache_get_data(*cache))
+    }
+    fn pipeline_cache_drop(&self, cache: &Self::PipelineCacheId) {
+        let global = &self.0;
+        wgc::gfx_select!(*cache => global.pipeline_cache_drop(*cache))
     }
     fn command_encoder_drop(&self, command_encoder: &Self::CommandEncoderId) {
         if command_encoder.open {
@@ -2043,6 +2205,7 @@ impl crate::Context for Context {
                 label: desc.label.map(Borrowed),
                 color_attachments: Borrowed(&colors),
                 depth_stencil_attachment: depth_stencil.as_ref(),
+                occlusion_query_set: desc.occlusion_query_set.map(|query_set| query_set.id),
             },
         )
     }
@@ -2258,6 +2421,32 @@ pub(crate) struct SurfaceOutputDetail {
     surface_id: wgc::id::SurfaceId,
 }
 
+impl From<&wgc::pipeline::CreateShaderModuleError> for CompilationInfo {
+    fn from(value: &wgc::pipeline::CreateShaderModuleError) -> Self {
+        use wgc::pipeline::CreateShaderModuleError as Csme;
+        // The `Display` of the shader errors renders the whole annotated source,
+        // while a compilation message only needs the description.
+        let message = match *value {
+            Csme::Parsing(ref err) => err.inner.to_string(),
+            Csme::Validation(ref err) => err.inner.to_string(),
+            ref other => other.to_string(),
+        };
+        let location = value.source_location().map(|loc| SourceLocation {
+            line_number: loc.line_number,
+            line_position: loc.line_position,
+            offset: loc.offset,
+            length: loc.length,
+        });
+        CompilationInfo {
+            messages: vec![CompilationMessage {
+                message,
+                message_type: CompilationMessageType::Error,
+                location,
+            }],
+        }
+    }
+}
+
 type ErrorSink = Arc<Mutex<ErrorSinkRaw>>;
 
 struct ErrorScope {
@@ -224,6 +224,21 @@ impl crate::RenderInner<Context> for RenderPass {
     fn set_push_constants(&mut self, _stages: wgt::ShaderStages, _offset: u32, _data: &[u8]) {
         panic!("PUSH_CONSTANTS feature must be enabled to call multi_draw_indexed_indirect")
     }
+
+    fn insert_debug_marker(&mut self, _label: &str) {
+        // Not available in gecko yet
+        // self.0.insert_debug_marker(label);
+    }
+
+    fn push_debug_group(&mut self, _group_label: &str) {
+        // Not available in gecko yet
+        // self.0.push_debug_group(group_label);
+    }
+
+    fn pop_debug_group(&mut self) {
+        // Not available in gecko yet
+        // self.0.pop_debug_group();
+    }
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
         self.0
             .draw_with_instance_count_and_first_vertex_and_first_instance(
@@ -368,6 +383,21 @@ impl crate::RenderInner<Context> for RenderBundleEncoder {
     fn set_push_constants(&mut self, _stages: wgt::ShaderStages, _offset: u32, _data: &[u8]) {
         panic!("PUSH_CONSTANTS feature must be enabled to call multi_draw_indexed_indirect")
     }
+
+    fn insert_debug_marker(&mut self, _label: &str) {
+        // Not available in gecko yet
+        // self.0.insert_debug_marker(label);
+    }
+
+    fn push_debug_group(&mut self, _group_label: &str) {
+        // Not available in gecko yet
+        // self.0.push_debug_group(group_label);
+    }
+
+    fn pop_debug_group(&mut self) {
+        // Not available in gecko yet
+        // self.0.pop_debug_group();
+    }
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
         self.0
             .draw_with_instance_count_and_first_vertex_and_first_instance(
@@ -467,21 +497,6 @@ impl crate::RenderPassInner<Context> for RenderPass {
         self.0.set_stencil_reference(reference);
     }
 
-    fn insert_debug_marker(&mut self, _label: &str) {
-        // Not available in gecko yet
-        // self.0.insert_debug_marker(label);
-    }
-
-    fn push_debug_group(&mut self, _group_label: &str) {
-        // Not available in gecko yet
-        // self.0.push_debug_group(group_label);
-    }
-
-    fn pop_debug_group(&mut self) {
-        // Not available in gecko yet
-        // self.0.pop_debug_group();
-    }
-
     fn execute_bundles<'a, I: Iterator<Item = &'a Sendable<web_sys::GpuRenderBundle>>>(
         &mut self,
         render_bundles: I,
@@ -503,6 +518,14 @@ impl crate::RenderPassInner<Context> for RenderPass {
     fn end_pipeline_statistics_query(&mut self) {
         // Not available in gecko yet
     }
+
+    fn begin_occlusion_query(&mut self, _query_index: u32) {
+        // Not available in gecko yet
+    }
+
+    fn end_occlusion_query(&mut self) {
+        // Not available in gecko yet
+    }
 }
 
 fn map_texture_format(texture_format: wgt::TextureFormat) -> web_sys::GpuTextureFormat {
@@ -897,6 +920,61 @@ fn future_pop_error_scope(result: JsFutureResult) -> Option<crate::Error> {
     }
 }
 
+// web-sys doesn't expose the `constants` member of the stage descriptors yet.
+fn map_pipeline_constants(stage: &JsValue, constants: &std::collections::HashMap<String, f64>) {
+    if constants.is_empty() {
+        return;
+    }
+    let mapped_constants = js_sys::Object::new();
+    for (key, &value) in constants.iter() {
+        js_sys::Reflect::set(
+            &mapped_constants,
+            &JsValue::from_str(key),
+            &JsValue::from_f64(value),
+        )
+        .unwrap();
+    }
+    js_sys::Reflect::set(stage, &JsValue::from_str("constants"), &mapped_constants).unwrap();
+}
+
+fn future_compilation_info(result: JsFutureResult) -> crate::CompilationInfo {
+    let js_info = match result {
+        Ok(js_value) => web_sys::GpuCompilationInfo::from(js_value),
+        Err(_) => return crate::CompilationInfo::default(),
+    };
+    let messages = js_info
+        .messages()
+        .iter()
+        .map(|js_value| {
+            let js_message = web_sys::GpuCompilationMessage::from(js_value);
+            let message_type = match js_message.type_() {
+                web_sys::GpuCompilationMessageType::Error => crate::CompilationMessageType::Error,
+                web_sys::GpuCompilationMessageType::Warning => {
+                    crate::CompilationMessageType::Warning
+                }
+                _ => crate::CompilationMessageType::Info,
+            };
+            // Messages that don't point at the source have a line number of 0.
+            let location = if js_message.line_num() != 0.0 {
+                Some(crate::SourceLocation {
+                    line_number: js_message.line_num() as u32,
+                    line_position: js_message.line_pos() as u32,
+                    offset: js_message.offset() as u32,
+                    length: js_message.length() as u32,
+                })
+            } else {
+                None
+            };
+            crate::CompilationMessage {
+                message: js_message.message(),
+                message_type,
+                location,
+            }
+        })
+        .collect();
+    crate::CompilationInfo { messages }
+}
+
 impl Context {
     pub fn instance_create_surface_from_canvas(
         &self,
@@ -936,6 +1014,7 @@ impl crate::Context for Context {
     type PipelineLayoutId = Sendable<web_sys::GpuPipelineLayout>;
     type RenderPipelineId = Sendable<web_sys::GpuRenderPipeline>;
     type ComputePipelineId = Sendable<web_sys::GpuComputePipeline>;
+    type PipelineCacheId = ();
     type CommandEncoderId = web_sys::GpuCommandEncoder;
     type ComputePassId = ComputePass;
     type RenderPassId = RenderPass;
@@ -957,6 +1036,10 @@ impl crate::Context for Context {
     >;
     type PopErrorScopeFuture =
         MakeSendFuture<wasm_bindgen_futures::JsFuture, fn(JsFutureResult) -> Option<crate::Error>>;
+    type CompilationInfoFuture = MakeSendFuture<
+        wasm_bindgen_futures::JsFuture,
+        fn(JsFutureResult) -> crate::CompilationInfo,
+    >;
 
     fn init(_backends: wgt::Backends) -> Self {
         Context(web_sys::window().unwrap().navigator().gpu())
@@ -1022,11 +1105,11 @@ impl crate::Context for Context {
         &self,
         adapter: &Self::AdapterId,
         desc: &crate::DeviceDescriptor,
-        trace_dir: Option<&std::path::Path>,
+        trace_desc: Option<wgt::TraceDescriptor>,
     ) -> Self::RequestDeviceFuture {
         use web_sys::GpuFeatureName as Gfn;
 
-        if trace_dir.is_some() {
+        if trace_desc.is_some() {
             //Error: Tracing isn't supported on the Web target
         }
 
@@ -1152,6 +1235,15 @@ impl crate::Context for Context {
         Some(formats)
     }
 
+    fn surface_get_supported_present_modes(
+        &self,
+        _surface: &Self::SurfaceId,
+        _adapter: &Self::AdapterId,
+    ) -> Option<Vec<wgt::PresentMode>> {
+        // Presentation on the web is always synchronized with the compositor.
+        Some(vec![wgt::PresentMode::Fifo])
+    }
+
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
@@ -1206,6 +1298,11 @@ impl crate::Context for Context {
         wgt::DownlevelCapabilities::default()
     }
 
+    fn device_memory_report(&self, _device: &Self::DeviceId) -> wgt::MemoryReport {
+        // WebGPU doesn't expose any memory information
+        wgt::MemoryReport::default()
+    }
+
     fn device_create_shader_module(
         &self,
         device: &Self::DeviceId,
@@ -1452,6 +1549,7 @@ impl crate::Context for Context {
     ) -> Self::RenderPipelineId {
         let mut mapped_vertex_state =
             web_sys::GpuVertexState::new(desc.vertex.entry_point, &desc.vertex.module.id.0);
+        map_pipeline_constants(&mapped_vertex_state, desc.vertex.constants);
 
         let buffers = desc
             .vertex
@@ -1514,6 +1612,7 @@ impl crate::Context for Context {
                 .collect::<js_sys::Array>();
             let mapped_fragment_desc =
                 web_sys::GpuFragmentState::new(frag.entry_point, &frag.module.id.0, &targets);
+            map_pipeline_constants(&mapped_fragment_desc, frag.constants);
             mapped_desc.fragment(&mapped_fragment_desc);
         }
 
@@ -1536,6 +1635,7 @@ impl crate::Context for Context {
     ) -> Self::ComputePipelineId {
         let mapped_compute_stage =
             web_sys::GpuProgrammableStage::new(desc.entry_point, &desc.module.id.0);
+        map_pipeline_constants(&mapped_compute_stage, desc.constants);
         let mut mapped_desc = web_sys::GpuComputePipelineDescriptor::new(&mapped_compute_stage);
         if let Some(layout) = desc.layout {
             mapped_desc.layout(&layout.id.0);
@@ -1546,6 +1646,14 @@ impl crate::Context for Context {
         Sendable(device.0.create_compute_pipeline(&mapped_desc))
     }
 
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId {
+        // Not supported on the web, pipelines are created without a cache.
+    }
+
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -1655,6 +1763,28 @@ impl crate::Context for Context {
         true
     }
 
+    fn device_destroy(&self, device: &Self::DeviceId) {
+        device.0.destroy();
+    }
+
+    fn device_set_device_lost_callback(
+        &self,
+        device: &Self::DeviceId,
+        callback: Box<dyn FnOnce(crate::DeviceLostReason, String) + Send + 'static>,
+    ) {
+        let f = Closure::once(move |info: JsValue| {
+            let info = web_sys::GpuDeviceLostInfo::from(info);
+            let reason = match web_sys::GpuDeviceLostReason::from_js_value(&info.reason()) {
+                Some(web_sys::GpuDeviceLostReason::Destroyed) => crate::DeviceLostReason::Destroyed,
+                _ => crate::DeviceLostReason::Unknown,
+            };
+            callback(reason, info.message());
+        });
+        let _ = device.0.lost().then(&f);
+        // The closure is called at most once, and has to outlive this call.
+        f.forget();
+    }
+
     fn device_on_uncaptured_error(
         &self,
         device: &Self::DeviceId,
@@ -1686,6 +1816,17 @@ impl crate::Context for Context {
         )
     }
 
+    fn shader_get_compilation_info(
+        &self,
+        shader: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture {
+        let compilation_info_promise = shader.0.compilation_info();
+        MakeSendFuture::new(
+            wasm_bindgen_futures::JsFuture::from(compilation_info_promise),
+            future_compilation_info,
+        )
+    }
+
     fn buffer_map_async(
         &self,
         buffer: &Self::BufferId,
@@ -1817,6 +1958,14 @@ impl crate::Context for Context {
         // Dropped automatically
     }
 
+    fn pipeline_cache_get_data(&self, _cache: &Self::PipelineCacheId) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn pipeline_cache_drop(&self, _cache: &Self::PipelineCacheId) {
+        // Nothing to drop
+    }
+
     fn command_encoder_drop(&self, _command_encoder: &Self::CommandEncoderId) {
         // Dropped automatically
     }
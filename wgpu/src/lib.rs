@@ -13,6 +13,7 @@ mod macros;
 
 use std::{
     borrow::Cow,
+    collections::HashMap,
     error,
     fmt::{Debug, Display},
     future::Future,
@@ -26,21 +27,22 @@ use std::{
 use parking_lot::Mutex;
 
 pub use wgt::{
-    AdapterInfo, AddressMode, AstcBlock, AstcChannel, Backend, Backends, BindGroupLayoutEntry,
-    BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, BufferAddress,
-    BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState, ColorWrites,
-    CommandBufferDescriptor, CompareFunction, DepthBiasState, DepthStencilState, DeviceType,
-    DownlevelCapabilities, DownlevelFlags, DynamicOffset, Extent3d, Face, Features, FilterMode,
-    FrontFace, ImageDataLayout, ImageSubresourceRange, IndexFormat, Limits, MultisampleState,
-    Origin3d, PipelineStatisticsTypes, PolygonMode, PowerPreference, PresentMode, PrimitiveState,
-    PrimitiveTopology, PushConstantRange, QueryType, RenderBundleDepthStencil, SamplerBindingType,
-    SamplerBorderColor, ShaderLocation, ShaderModel, ShaderStages, StencilFaceState,
-    StencilOperation, StencilState, StorageTextureAccess, SurfaceConfiguration, SurfaceStatus,
-    TextureAspect, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
-    TextureFormatFeatures, TextureSampleType, TextureUsages, TextureViewDimension, VertexAttribute,
-    VertexFormat, VertexStepMode, COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT,
-    MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT, QUERY_RESOLVE_BUFFER_ALIGNMENT, QUERY_SET_MAX_QUERIES,
-    QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
+    AdapterInfo, AddressMode, AllocatorReport, AstcBlock, AstcChannel, Backend, Backends,
+    BindGroupLayoutEntry, BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState,
+    BufferAddress, BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState,
+    ColorWrites, CommandBufferDescriptor, CompareFunction, DepthBiasState, DepthStencilState,
+    DeviceLostReason, DeviceType, DownlevelCapabilities, DownlevelFlags, DynamicOffset, Extent3d,
+    Face, Features, FilterMode, FrontFace, ImageDataLayout, ImageSubresourceRange, IndexFormat,
+    Limits, MemoryHeapReport, MemoryReport, MultisampleState, Origin3d, PipelineStatisticsTypes,
+    PolygonMode, PowerPreference, PresentMode, PrimitiveState, PrimitiveTopology,
+    PushConstantRange, QueryType, RenderBundleDepthStencil, ResourceMemoryReport,
+    SamplerBindingType, SamplerBorderColor, ShaderLocation, ShaderModel, ShaderStages,
+    StencilFaceState, StencilOperation, StencilState, StorageTextureAccess, SurfaceConfiguration,
+    SurfaceStatus, TextureAspect, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
+    TextureFormatFeatures, TextureSampleType, TextureUsages, TextureViewDimension, TraceDescriptor,
+    TraceFormat, VertexAttribute, VertexFormat, VertexStepMode, COPY_BUFFER_ALIGNMENT,
+    COPY_BYTES_PER_ROW_ALIGNMENT, MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT,
+    QUERY_RESOLVE_BUFFER_ALIGNMENT, QUERY_SET_MAX_QUERIES, QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
 };
 
 use backend::{BufferMappedRange, Context as C};
@@ -100,6 +102,9 @@ trait RenderInner<Ctx: Context> {
         size: Option<BufferSize>,
     );
     fn set_push_constants(&mut self, stages: ShaderStages, offset: u32, data: &[u8]);
+    fn insert_debug_marker(&mut self, label: &str);
+    fn push_debug_group(&mut self, group_label: &str);
+    fn pop_debug_group(&mut self);
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>);
     fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>);
     fn draw_indirect(&mut self, indirect_buffer: &Ctx::BufferId, indirect_offset: BufferAddress);
@@ -151,12 +156,11 @@ trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
         max_depth: f32,
     );
     fn set_stencil_reference(&mut self, reference: u32);
-    fn insert_debug_marker(&mut self, label: &str);
-    fn push_debug_group(&mut self, group_label: &str);
-    fn pop_debug_group(&mut self);
     fn write_timestamp(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
     fn begin_pipeline_statistics_query(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
     fn end_pipeline_statistics_query(&mut self);
+    fn begin_occlusion_query(&mut self, query_index: u32);
+    fn end_occlusion_query(&mut self);
     fn execute_bundles<'a, I: Iterator<Item = &'a Ctx::RenderBundleId>>(
         &mut self,
         render_bundles: I,
@@ -178,6 +182,7 @@ trait Context: Debug + Send + Sized + Sync {
     type PipelineLayoutId: Debug + Send + Sync + 'static;
     type RenderPipelineId: Debug + Send + Sync + 'static;
     type ComputePipelineId: Debug + Send + Sync + 'static;
+    type PipelineCacheId: Debug + Send + Sync + 'static;
     type CommandEncoderId: Debug;
     type ComputePassId: Debug + ComputePassInner<Self>;
     type RenderPassId: Debug + RenderPassInner<Self>;
@@ -193,6 +198,7 @@ trait Context: Debug + Send + Sized + Sync {
     type RequestDeviceFuture: Future<Output = Result<(Self::DeviceId, Self::QueueId), RequestDeviceError>>
         + Send;
     type PopErrorScopeFuture: Future<Output = Option<Error>> + Send;
+    type CompilationInfoFuture: Future<Output = CompilationInfo> + Send;
 
     fn init(backends: Backends) -> Self;
     fn instance_create_surface(
@@ -207,7 +213,7 @@ trait Context: Debug + Send + Sized + Sync {
         &self,
         adapter: &Self::AdapterId,
         desc: &DeviceDescriptor,
-        trace_dir: Option<&std::path::Path>,
+        trace_desc: Option<TraceDescriptor>,
     ) -> Self::RequestDeviceFuture;
     fn instance_poll_all_devices(&self, force_wait: bool) -> bool;
     fn adapter_is_surface_supported(
@@ -230,6 +236,11 @@ trait Context: Debug + Send + Sized + Sync {
         surface: &Self::SurfaceId,
         adapter: &Self::AdapterId,
     ) -> Option<Vec<TextureFormat>>;
+    fn surface_get_supported_present_modes(
+        &self,
+        surface: &Self::SurfaceId,
+        adapter: &Self::AdapterId,
+    ) -> Option<Vec<PresentMode>>;
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
@@ -254,6 +265,7 @@ trait Context: Debug + Send + Sized + Sync {
     fn device_features(&self, device: &Self::DeviceId) -> Features;
     fn device_limits(&self, device: &Self::DeviceId) -> Limits;
     fn device_downlevel_properties(&self, device: &Self::DeviceId) -> DownlevelCapabilities;
+    fn device_memory_report(&self, device: &Self::DeviceId) -> MemoryReport;
     fn device_create_shader_module(
         &self,
         device: &Self::DeviceId,
@@ -290,6 +302,11 @@ trait Context: Debug + Send + Sized + Sync {
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
     ) -> Self::ComputePipelineId;
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        desc: &PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId;
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -321,6 +338,13 @@ trait Context: Debug + Send + Sized + Sync {
         desc: &RenderBundleEncoderDescriptor,
     ) -> Self::RenderBundleEncoderId;
     fn device_drop(&self, device: &Self::DeviceId);
+    fn device_destroy(&self, device: &Self::DeviceId);
+    fn device_set_device_lost_callback(
+        &self,
+        device: &Self::DeviceId,
+        // Note: boxed for the same reason as `queue_on_submitted_work_done`.
+        callback: Box<dyn FnOnce(DeviceLostReason, String) + Send + 'static>,
+    );
     fn device_poll(&self, device: &Self::DeviceId, maintain: Maintain) -> bool;
     fn device_on_uncaptured_error(
         &self,
@@ -329,6 +353,10 @@ trait Context: Debug + Send + Sized + Sync {
     );
     fn device_push_error_scope(&self, device: &Self::DeviceId, filter: ErrorFilter);
     fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture;
+    fn shader_get_compilation_info(
+        &self,
+        shader: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture;
 
     fn buffer_map_async(
         &self,
@@ -365,6 +393,8 @@ trait Context: Debug + Send + Sized + Sync {
     fn bind_group_layout_drop(&self, bind_group_layout: &Self::BindGroupLayoutId);
     fn pipeline_layout_drop(&self, pipeline_layout: &Self::PipelineLayoutId);
     fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId);
+    fn pipeline_cache_get_data(&self, cache: &Self::PipelineCacheId) -> Option<Vec<u8>>;
+    fn pipeline_cache_drop(&self, cache: &Self::PipelineCacheId);
     fn command_encoder_drop(&self, command_encoder: &Self::CommandEncoderId);
     fn command_buffer_drop(&self, command_buffer: &Self::CommandBufferId);
     fn render_bundle_drop(&self, render_bundle: &Self::RenderBundleId);
@@ -783,6 +813,66 @@ impl Drop for ShaderModule {
     }
 }
 
+impl ShaderModule {
+    /// Returns the messages produced while compiling the shader module.
+    ///
+    /// If the module failed to compile, this contains the error with its location
+    /// in the source, in addition to it being reported to the error handler.
+    pub fn get_compilation_info(&self) -> impl Future<Output = CompilationInfo> + Send {
+        self.context.shader_get_compilation_info(&self.id)
+    }
+}
+
+/// Compilation information for a shader module.
+///
+/// Corresponds to [WebGPU `GPUCompilationInfo`](https://gpuweb.github.io/gpuweb/#gpucompilationinfo).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompilationInfo {
+    /// The messages from the shader compilation process.
+    pub messages: Vec<CompilationMessage>,
+}
+
+/// A single message from the shader compilation process.
+///
+/// Corresponds to [WebGPU `GPUCompilationMessage`](https://gpuweb.github.io/gpuweb/#gpucompilationmessage).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompilationMessage {
+    /// The text of the message.
+    pub message: String,
+    /// The type of the message.
+    pub message_type: CompilationMessageType,
+    /// Where in the source code the message points at, if anywhere.
+    pub location: Option<SourceLocation>,
+}
+
+/// The type of a compilation message.
+///
+/// Corresponds to [WebGPU `GPUCompilationMessageType`](https://gpuweb.github.io/gpuweb/#enumdef-gpucompilationmessagetype).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompilationMessageType {
+    /// An error message.
+    Error,
+    /// A warning message.
+    Warning,
+    /// An informational message.
+    Info,
+}
+
+/// A range of the shader source code.
+///
+/// Lines and positions are 1-based, offsets and lengths are in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The line number of the start of the range.
+    pub line_number: u32,
+    /// The position in the line of the start of the range.
+    pub line_position: u32,
+    /// The offset of the start of the range from the beginning of the source.
+    pub offset: u32,
+    /// The length of the range.
+    pub length: u32,
+}
+
 /// Source of a shader module.
 ///
 /// The source will be parsed and validated.
@@ -1035,6 +1125,36 @@ impl Drop for RenderBundle {
     }
 }
 
+/// Handle to a pipeline cache, which is used to speed up the creation of pipelines.
+///
+/// It can be created with [`Device::create_pipeline_cache`], and its contents saved
+/// with [`PipelineCache::get_data`] to be reused by a later run of the application.
+///
+/// This type is unique to the Rust API of `wgpu`.
+#[derive(Debug)]
+pub struct PipelineCache {
+    context: Arc<C>,
+    id: <C as Context>::PipelineCacheId,
+}
+
+impl PipelineCache {
+    /// Returns the contents of the cache, to be passed to [`Device::create_pipeline_cache`]
+    /// in a later run of the application.
+    ///
+    /// Returns `None` if the backend doesn't support serializing pipeline caches.
+    pub fn get_data(&self) -> Option<Vec<u8>> {
+        self.context.pipeline_cache_get_data(&self.id)
+    }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.context.pipeline_cache_drop(&self.id);
+        }
+    }
+}
+
 /// Handle to a query set.
 ///
 /// It can be created with [`Device::create_query_set`].
@@ -1411,6 +1531,8 @@ pub struct RenderPassDescriptor<'tex, 'desc> {
     pub color_attachments: &'desc [RenderPassColorAttachment<'tex>],
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<RenderPassDepthStencilAttachment<'tex>>,
+    /// Defines where the occlusion query results will be stored for this pass.
+    pub occlusion_query_set: Option<&'tex QuerySet>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -1442,6 +1564,10 @@ pub struct VertexState<'a> {
     /// The name of the entry point in the compiled shader. There must be a function that returns
     /// void with this name in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the shader, keyed by either
+    /// the numeric ID or the name of the constant.
+    /// Constants that aren't specified keep their default value.
+    pub constants: &'a HashMap<String, f64>,
     /// The format of any vertex buffers used with this pipeline.
     pub buffers: &'a [VertexBufferLayout<'a>],
 }
@@ -1459,6 +1585,10 @@ pub struct FragmentState<'a> {
     /// The name of the entry point in the compiled shader. There must be a function that returns
     /// void with this name in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the shader, keyed by either
+    /// the numeric ID or the name of the constant.
+    /// Constants that aren't specified keep their default value.
+    pub constants: &'a HashMap<String, f64>,
     /// The color state of the render targets.
     pub targets: &'a [ColorTargetState],
 }
@@ -1488,6 +1618,10 @@ pub struct RenderPipelineDescriptor<'a> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The pipeline cache to use when creating this pipeline.
+    ///
+    /// Requires [`Features::PIPELINE_CACHE`].
+    pub cache: Option<&'a PipelineCache>,
 }
 
 /// Describes the attachments of a compute pass.
@@ -1519,6 +1653,31 @@ pub struct ComputePipelineDescriptor<'a> {
     /// The name of the entry point in the compiled shader. There must be a function that returns
     /// void with this name in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the shader, keyed by either
+    /// the numeric ID or the name of the constant.
+    /// Constants that aren't specified keep their default value.
+    pub constants: &'a HashMap<String, f64>,
+    /// The pipeline cache to use when creating this pipeline.
+    ///
+    /// Requires [`Features::PIPELINE_CACHE`].
+    pub cache: Option<&'a PipelineCache>,
+}
+
+/// Describes a [`PipelineCache`].
+///
+/// For use with [`Device::create_pipeline_cache`].
+///
+/// This type is unique to the Rust API of `wgpu`.
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    /// Debug label of the pipeline cache. This will show up in graphics debuggers for easy identification.
+    pub label: Label<'a>,
+    /// Data previously returned by [`PipelineCache::get_data`], if any.
+    ///
+    /// The data is checked to come from the same adapter and driver before being used.
+    pub data: Option<&'a [u8]>,
+    /// If the data is rejected, create an empty cache instead of reporting an error.
+    pub fallback: bool,
 }
 
 pub use wgt::ImageCopyBuffer as ImageCopyBufferBase;
@@ -1808,8 +1967,9 @@ impl Adapter {
     /// # Arguments
     ///
     /// - `desc` - Description of the features and limits requested from the given device.
-    /// - `trace_path` - Can be used for API call tracing, if that feature is
-    ///   enabled in `wgpu-core`.
+    /// - `trace_desc` - Can be used for API call tracing, if that feature is
+    ///   enabled in `wgpu-core`. A RON trace is written into an existing directory,
+    ///   a binary trace is created as a single file.
     ///
     /// # Panics
     ///
@@ -1820,10 +1980,10 @@ impl Adapter {
     pub fn request_device(
         &self,
         desc: &DeviceDescriptor,
-        trace_path: Option<&std::path::Path>,
+        trace_desc: Option<TraceDescriptor>,
     ) -> impl Future<Output = Result<(Device, Queue), RequestDeviceError>> + Send {
         let context = Arc::clone(&self.context);
-        let device = Context::adapter_request_device(&*self.context, &self.id, desc, trace_path);
+        let device = Context::adapter_request_device(&*self.context, &self.id, desc, trace_desc);
         async move {
             device.await.map(|(device_id, queue_id)| {
                 (
@@ -1851,11 +2011,11 @@ impl Adapter {
         &self,
         hal_device: hal::OpenDevice<A>,
         desc: &DeviceDescriptor,
-        trace_path: Option<&std::path::Path>,
+        trace_desc: Option<TraceDescriptor>,
     ) -> Result<(Device, Queue), RequestDeviceError> {
         let context = Arc::clone(&self.context);
         self.context
-            .create_device_from_hal(&self.id, hal_device, desc, trace_path)
+            .create_device_from_hal(&self.id, hal_device, desc, trace_desc)
             .map(|(device_id, queue_id)| {
                 (
                     Device {
@@ -1953,6 +2113,18 @@ impl Device {
         Context::device_limits(&*self.context, &self.id)
     }
 
+    /// Reports the memory used by this device.
+    ///
+    /// Includes the usage and budget of the memory heaps and the allocator statistics
+    /// where the backend can provide them, and the memory of the live resources by kind.
+    /// See [`MemoryReport`] for what each backend reports.
+    ///
+    /// The budgets are estimates that change over time, so this is meant to be polled
+    /// to decide when to release resources, e.g. once per frame.
+    pub fn get_memory_report(&self) -> MemoryReport {
+        Context::device_memory_report(&*self.context, &self.id)
+    }
+
     /// Creates a shader module from either SPIR-V or WGSL source code.
     pub fn create_shader_module(&self, desc: &ShaderModuleDescriptor) -> ShaderModule {
         ShaderModule {
@@ -2131,6 +2303,21 @@ impl Device {
         }
     }
 
+    /// Creates a new [`PipelineCache`].
+    ///
+    /// Requires [`Features::PIPELINE_CACHE`].
+    ///
+    /// # Safety
+    ///
+    /// `desc.data` must have been returned by [`PipelineCache::get_data`]. Its header is
+    /// validated, but the contents are passed to the driver as-is.
+    pub unsafe fn create_pipeline_cache(&self, desc: &PipelineCacheDescriptor) -> PipelineCache {
+        PipelineCache {
+            context: Arc::clone(&self.context),
+            id: Context::device_create_pipeline_cache(&*self.context, &self.id, desc),
+        }
+    }
+
     /// Creates a new [`QuerySet`].
     pub fn create_query_set(&self, desc: &QuerySetDescriptor) -> QuerySet {
         QuerySet {
@@ -2144,6 +2331,30 @@ impl Device {
         self.context.device_on_uncaptured_error(&self.id, handler);
     }
 
+    /// Set a callback to be called once the device is lost, replacing any previous one.
+    ///
+    /// The callback receives the reason for the loss and a message describing it.
+    /// If the device is already lost, it is called right away.
+    ///
+    /// Once the device is lost, creating resources fails, and pending
+    /// [`BufferSlice::map_async`] calls resolve with an error. Device loss is
+    /// detected when the device is polled, so the callback runs from within
+    /// [`Device::poll`] or [`Queue::submit`] on native.
+    pub fn set_device_lost_callback(
+        &self,
+        callback: impl FnOnce(DeviceLostReason, String) + Send + 'static,
+    ) {
+        self.context
+            .device_set_device_lost_callback(&self.id, Box::new(callback));
+    }
+
+    /// Destroy the device, which is then lost with [`DeviceLostReason::Destroyed`].
+    ///
+    /// Work that was already submitted still completes.
+    pub fn destroy(&self) {
+        self.context.device_destroy(&self.id);
+    }
+
     /// Push an error scope.
     pub fn push_error_scope(&self, filter: ErrorFilter) {
         self.context.device_push_error_scope(&self.id, filter);
@@ -3075,6 +3286,23 @@ impl<'a> RenderPass<'a> {
     }
 }
 
+impl<'a> RenderPass<'a> {
+    /// Start an occlusion query on this render pass. It can be ended with
+    /// `end_occlusion_query`. Occlusion queries may not be nested.
+    ///
+    /// The query result is written to the [`RenderPassDescriptor::occlusion_query_set`]
+    /// at `query_index`.
+    pub fn begin_occlusion_query(&mut self, query_index: u32) {
+        self.id.begin_occlusion_query(query_index);
+    }
+
+    /// End the occlusion query on this render pass. It can be started with
+    /// `begin_occlusion_query`. Occlusion queries may not be nested.
+    pub fn end_occlusion_query(&mut self) {
+        self.id.end_occlusion_query();
+    }
+}
+
 /// [`Features::PIPELINE_STATISTICS_QUERY`] must be enabled on the device in order to call these functions.
 impl<'a> RenderPass<'a> {
     /// Start a pipeline statistics query on this render pass. It can be ended with
@@ -3279,6 +3507,23 @@ impl<'a> RenderBundleEncoder<'a> {
         )
     }
 
+    /// Inserts debug marker.
+    pub fn insert_debug_marker(&mut self, label: &str) {
+        RenderInner::insert_debug_marker(&mut self.id, label);
+    }
+
+    /// Start record commands and group it into debug marker group.
+    ///
+    /// Debug groups pushed inside a bundle must be popped inside the same bundle.
+    pub fn push_debug_group(&mut self, label: &str) {
+        RenderInner::push_debug_group(&mut self.id, label);
+    }
+
+    /// Stops command recording and creates debug group.
+    pub fn pop_debug_group(&mut self) {
+        RenderInner::pop_debug_group(&mut self.id);
+    }
+
     /// Draws primitives from the active vertex buffer(s).
     ///
     /// The active vertex buffers can be set with [`RenderBundleEncoder::set_vertex_buffer`].
@@ -3453,6 +3698,13 @@ impl Surface {
         Context::surface_get_supported_formats(&*self.context, &self.id, &adapter.id)
     }
 
+    /// Returns a vec of supported presentation modes to use for the [`Surface`] with this adapter.
+    ///
+    /// Returns None if the surface is incompatible with the adapter.
+    pub fn get_supported_present_modes(&self, adapter: &Adapter) -> Option<Vec<PresentMode>> {
+        Context::surface_get_supported_present_modes(&*self.context, &self.id, &adapter.id)
+    }
+
     /// Returns an optimal texture format to use for the [`Surface`] with this adapter.
     pub fn get_preferred_format(&self, adapter: &Adapter) -> Option<wgt::TextureFormat> {
         // Check the four formats mentioned in the WebGPU spec.
@@ -1,28 +1,50 @@
+use super::TextureCopyLayout;
 use crate::{
-    util::align_to, Buffer, BufferAddress, BufferDescriptor, BufferSize, BufferUsages,
-    BufferViewMut, CommandEncoder, Device, MapMode,
+    util::align_to, Buffer, BufferAddress, BufferAsyncError, BufferDescriptor, BufferSize,
+    BufferUsages, BufferViewMut, CommandEncoder, Device, Extent3d, ImageCopyBuffer,
+    ImageCopyTexture, MapMode, TextureFormat,
 };
 use std::fmt;
 use std::sync::{mpsc, Arc};
 
+type ReadCallback = Box<dyn FnOnce(Result<Vec<u8>, BufferAsyncError>) + Send>;
+
+/// A pending download out of a read chunk.
+struct Read {
+    offset: BufferAddress,
+    size: BufferAddress,
+    /// Layout of the data when it's read from a texture, with padded rows.
+    texture_layout: Option<TextureCopyLayout>,
+    callback: ReadCallback,
+}
+
 struct Chunk {
     buffer: Arc<Buffer>,
     size: BufferAddress,
     offset: BufferAddress,
+    /// `Write` for the chunks of uploads and `Read` for the chunks of downloads.
+    mode: MapMode,
+    /// Downloads waiting for the chunk to be mapped, empty for upload chunks.
+    reads: Vec<Read>,
 }
 
-/// Staging belt is a machine that uploads data.
+/// Staging belt is a machine that uploads and downloads data.
 ///
 /// Internally it uses a ring-buffer of staging buffers that are sub-allocated.
 /// It has an advantage over [`Queue::write_buffer`] in a way that it returns a mutable slice,
 /// which you can fill to avoid an extra data copy.
 ///
 /// Using a staging belt is slightly complicated, and generally goes as follows:
-/// - Write to buffers that need writing to using [`StagingBelt::write_buffer`].
+/// - Write to buffers that need writing to using [`StagingBelt::write_buffer`],
+///   and to textures using [`StagingBelt::write_texture`].
+/// - Schedule downloads with [`StagingBelt::read_buffer`] and [`StagingBelt::read_texture`].
 /// - Call `finish`.
-/// - Submit all command encoders used with `StagingBelt::write_buffer`.
+/// - Submit all command encoders used with the staging belt.
 /// - Call `recall`
 ///
+/// The data of the downloads is passed to their callbacks once the device is polled
+/// after `recall`.
+///
 /// [`Queue::write_buffer`]: crate::Queue::write_buffer
 pub struct StagingBelt {
     chunk_size: BufferAddress,
@@ -67,43 +89,188 @@ impl StagingBelt {
         size: BufferSize,
         device: &Device,
     ) -> BufferViewMut {
-        let mut chunk = if let Some(index) = self
-            .active_chunks
-            .iter()
-            .position(|chunk| chunk.offset + size.get() <= chunk.size)
-        {
+        let mut chunk = self.take_chunk(size.get(), crate::MAP_ALIGNMENT, MapMode::Write, device);
+
+        encoder.copy_buffer_to_buffer(&chunk.buffer, chunk.offset, target, offset, size.get());
+        let old_offset = chunk.offset;
+        chunk.offset = align_to(chunk.offset + size.get(), crate::MAP_ALIGNMENT);
+
+        self.active_chunks.push(chunk);
+        self.active_chunks
+            .last()
+            .unwrap()
+            .buffer
+            .slice(old_offset..old_offset + size.get())
+            .get_mapped_range_mut()
+    }
+
+    /// Upload tightly packed `data` into a region of the `target` texture.
+    ///
+    /// The texture doesn't know its own format, so it has to be passed as `format`.
+    /// The rows are padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`](crate::COPY_BYTES_PER_ROW_ALIGNMENT)
+    /// in the staging buffer, which is why the data is copied rather than written in place.
+    ///
+    /// The upload will be placed into the provided command encoder. This encoder
+    /// must be submitted after `finish` is called and before `recall` is called.
+    pub fn write_texture(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        target: ImageCopyTexture,
+        format: TextureFormat,
+        size: Extent3d,
+        data: &[u8],
+        device: &Device,
+    ) {
+        let layout = TextureCopyLayout::new(format, target.aspect, size);
+        assert_eq!(
+            data.len() as BufferAddress,
+            layout.unpadded_size(),
+            "Data size doesn't match the texture region"
+        );
+
+        let mut chunk = self.take_chunk(
+            layout.padded_size(),
+            crate::COPY_BYTES_PER_ROW_ALIGNMENT as BufferAddress,
+            MapMode::Write,
+            device,
+        );
+        layout.pad(
+            data,
+            &mut chunk
+                .buffer
+                .slice(chunk.offset..chunk.offset + layout.padded_size())
+                .get_mapped_range_mut(),
+        );
+        encoder.copy_buffer_to_texture(
+            ImageCopyBuffer {
+                buffer: &chunk.buffer,
+                layout: layout.buffer_layout(chunk.offset),
+            },
+            target,
+            layout.physical_size,
+        );
+        chunk.offset = align_to(chunk.offset + layout.padded_size(), crate::MAP_ALIGNMENT);
+        self.active_chunks.push(chunk);
+    }
+
+    /// Download `size` bytes of the `source` buffer at the specified offset.
+    ///
+    /// The download will be placed into the provided command encoder. This encoder
+    /// must be submitted after `finish` is called and before `recall` is called.
+    /// The callback receives the data once the device is polled after `recall`.
+    pub fn read_buffer(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        source: &Buffer,
+        offset: BufferAddress,
+        size: BufferSize,
+        device: &Device,
+        callback: impl FnOnce(Result<Vec<u8>, BufferAsyncError>) + Send + 'static,
+    ) {
+        let mut chunk = self.take_chunk(size.get(), crate::MAP_ALIGNMENT, MapMode::Read, device);
+
+        encoder.copy_buffer_to_buffer(source, offset, &chunk.buffer, chunk.offset, size.get());
+        chunk.reads.push(Read {
+            offset: chunk.offset,
+            size: size.get(),
+            texture_layout: None,
+            callback: Box::new(callback),
+        });
+        chunk.offset = align_to(chunk.offset + size.get(), crate::MAP_ALIGNMENT);
+        self.active_chunks.push(chunk);
+    }
+
+    /// Download a region of the `source` texture.
+    ///
+    /// The texture doesn't know its own format, so it has to be passed as `format`.
+    /// The callback receives the data tightly packed, once the device is polled after `recall`.
+    ///
+    /// The download will be placed into the provided command encoder. This encoder
+    /// must be submitted after `finish` is called and before `recall` is called.
+    pub fn read_texture(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        source: ImageCopyTexture,
+        format: TextureFormat,
+        size: Extent3d,
+        device: &Device,
+        callback: impl FnOnce(Result<Vec<u8>, BufferAsyncError>) + Send + 'static,
+    ) {
+        let layout = TextureCopyLayout::new(format, source.aspect, size);
+        let mut chunk = self.take_chunk(
+            layout.padded_size(),
+            crate::COPY_BYTES_PER_ROW_ALIGNMENT as BufferAddress,
+            MapMode::Read,
+            device,
+        );
+
+        encoder.copy_texture_to_buffer(
+            source,
+            ImageCopyBuffer {
+                buffer: &chunk.buffer,
+                layout: layout.buffer_layout(chunk.offset),
+            },
+            layout.physical_size,
+        );
+        chunk.reads.push(Read {
+            offset: chunk.offset,
+            size: layout.padded_size(),
+            texture_layout: Some(layout),
+            callback: Box::new(callback),
+        });
+        chunk.offset = align_to(chunk.offset + layout.padded_size(), crate::MAP_ALIGNMENT);
+        self.active_chunks.push(chunk);
+    }
+
+    /// Find a chunk of the given `mode` with room for `size` bytes at an offset aligned
+    /// to `alignment`, or create a new one, and align its offset.
+    ///
+    /// The chunk has to be put back to the active chunks afterwards.
+    fn take_chunk(
+        &mut self,
+        size: BufferAddress,
+        alignment: BufferAddress,
+        mode: MapMode,
+        device: &Device,
+    ) -> Chunk {
+        let mut chunk = if let Some(index) = self.active_chunks.iter().position(|chunk| {
+            chunk.mode == mode && align_to(chunk.offset, alignment) + size <= chunk.size
+        }) {
             self.active_chunks.swap_remove(index)
         } else if let Some(index) = self
             .free_chunks
             .iter()
-            .position(|chunk| size.get() <= chunk.size)
+            .position(|chunk| chunk.mode == mode && size <= chunk.size)
         {
             self.free_chunks.swap_remove(index)
         } else {
-            let size = self.chunk_size.max(size.get());
+            let size = self.chunk_size.max(size);
+            let (label, usage) = match mode {
+                MapMode::Write => (
+                    "(wgpu internal) StagingBelt staging buffer",
+                    BufferUsages::MAP_WRITE | BufferUsages::COPY_SRC,
+                ),
+                MapMode::Read => (
+                    "(wgpu internal) StagingBelt download buffer",
+                    BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                ),
+            };
             Chunk {
                 buffer: Arc::new(device.create_buffer(&BufferDescriptor {
-                    label: Some("(wgpu internal) StagingBelt staging buffer"),
+                    label: Some(label),
                     size,
-                    usage: BufferUsages::MAP_WRITE | BufferUsages::COPY_SRC,
-                    mapped_at_creation: true,
+                    usage,
+                    // Download chunks are only mapped once the GPU has written to them.
+                    mapped_at_creation: mode == MapMode::Write,
                 })),
                 size,
                 offset: 0,
+                mode,
+                reads: Vec::new(),
             }
         };
-
-        encoder.copy_buffer_to_buffer(&chunk.buffer, chunk.offset, target, offset, size.get());
-        let old_offset = chunk.offset;
-        chunk.offset = align_to(chunk.offset + size.get(), crate::MAP_ALIGNMENT);
-
-        self.active_chunks.push(chunk);
-        self.active_chunks
-            .last()
-            .unwrap()
-            .buffer
-            .slice(old_offset..old_offset + size.get())
-            .get_mapped_range_mut()
+        chunk.offset = align_to(chunk.offset, alignment);
+        chunk
     }
 
     /// Prepare currently mapped buffers for use in a submission.
@@ -112,14 +279,16 @@ impl StagingBelt {
     /// the GPU is done copying the data from them.
     pub fn finish(&mut self) {
         for chunk in self.active_chunks.drain(..) {
-            chunk.buffer.unmap();
+            if chunk.mode == MapMode::Write {
+                chunk.buffer.unmap();
+            }
             self.closed_chunks.push(chunk);
         }
     }
 
     /// Recall all of the closed buffers back to be reused.
     ///
-    /// This has to be called after the command encoders written to `write_buffer` are submitted!
+    /// This has to be called after the command encoders used with the staging belt are submitted!
     pub fn recall(&mut self) {
         while let Ok(mut chunk) = self.receiver.try_recv() {
             chunk.offset = 0;
@@ -127,13 +296,33 @@ impl StagingBelt {
         }
 
         let sender = &self.sender;
-        for chunk in self.closed_chunks.drain(..) {
+        for mut chunk in self.closed_chunks.drain(..) {
             let sender = sender.clone();
             chunk
                 .buffer
                 .clone()
                 .slice(..)
-                .map_async(MapMode::Write, move |_| {
+                .map_async(chunk.mode, move |result| {
+                    if chunk.mode == MapMode::Read {
+                        let buffer = &chunk.buffer;
+                        for read in chunk.reads.drain(..) {
+                            let data = result.clone().map(|()| {
+                                let range = buffer
+                                    .slice(read.offset..read.offset + read.size)
+                                    .get_mapped_range();
+                                match read.texture_layout {
+                                    Some(layout) => layout.unpad(&range),
+                                    None => range.to_vec(),
+                                }
+                            });
+                            (read.callback)(data);
+                        }
+                        if result.is_err() {
+                            return;
+                        }
+                        // Download chunks are unmapped while the GPU writes to them.
+                        chunk.buffer.unmap();
+                    }
                     let _ = sender.send(chunk);
                 });
         }
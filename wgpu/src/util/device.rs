@@ -1,4 +1,4 @@
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, sync::Arc};
 
 /// Describes a [Buffer](crate::Buffer) when allocating.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -35,6 +35,27 @@ pub trait DeviceExt {
         desc: &crate::TextureDescriptor,
         data: &[u8],
     ) -> crate::Texture;
+
+    /// Asynchronously read a region of a texture back from the gpu.
+    ///
+    /// The texture doesn't know its own format, so it has to be passed as `format`.
+    /// For compressed formats, `size` is rounded up to whole blocks.
+    /// When copying only the depth or the stencil aspect, the data contains just that aspect.
+    ///
+    /// The callback receives the data tightly packed, without the padding
+    /// of the rows to [`COPY_BYTES_PER_ROW_ALIGNMENT`](crate::COPY_BYTES_PER_ROW_ALIGNMENT),
+    /// in the same layout as expected by [`DeviceExt::create_texture_with_data`] for a single mip.
+    ///
+    /// The texture must have the `COPY_SRC` usage. The callback is only called
+    /// once the device is polled.
+    fn read_texture(
+        &self,
+        queue: &crate::Queue,
+        texture: crate::ImageCopyTexture,
+        format: crate::TextureFormat,
+        size: crate::Extent3d,
+        callback: impl FnOnce(Result<Vec<u8>, crate::BufferAsyncError>) + Send + 'static,
+    );
 }
 
 impl DeviceExt for crate::Device {
@@ -144,4 +165,40 @@ impl DeviceExt for crate::Device {
 
         texture
     }
+    fn read_texture(
+        &self,
+        queue: &crate::Queue,
+        texture: crate::ImageCopyTexture,
+        format: crate::TextureFormat,
+        size: crate::Extent3d,
+        callback: impl FnOnce(Result<Vec<u8>, crate::BufferAsyncError>) + Send + 'static,
+    ) {
+        let layout = super::TextureCopyLayout::new(format, texture.aspect, size);
+
+        let download = Arc::new(self.create_buffer(&crate::BufferDescriptor {
+            label: None,
+            size: layout.padded_size(),
+            usage: crate::BufferUsages::COPY_DST | crate::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        }));
+
+        let mut encoder =
+            self.create_command_encoder(&crate::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            texture,
+            crate::ImageCopyBuffer {
+                buffer: &download,
+                layout: layout.buffer_layout(0),
+            },
+            layout.physical_size,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        download
+            .clone()
+            .slice(..)
+            .map_async(crate::MapMode::Read, move |result| {
+                callback(result.map(|()| layout.unpad(&download.slice(..).get_mapped_range())));
+            });
+    }
 }
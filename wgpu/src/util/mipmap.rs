@@ -0,0 +1,272 @@
+use std::{borrow::Cow, collections::HashMap, num::NonZeroU32};
+
+/// Generates the mip chain of textures from their first mip level.
+///
+/// Every mip level is a 2x2 box filtered copy of the previous one. For sRGB formats
+/// the filtering happens in linear space.
+///
+/// Textures with the `RENDER_ATTACHMENT` usage are downsampled in render passes,
+/// otherwise textures with the `STORAGE_BINDING` usage and a float storage format
+/// are downsampled in compute passes. In both cases the texture needs the
+/// `TEXTURE_BINDING` usage as well. The pipelines are created on first use of a
+/// format and reused afterwards, so one generator should be kept around.
+///
+/// Each layer of array and cube textures gets its own mip chain.
+/// 3D, multisampled and integer textures are not supported.
+#[derive(Debug)]
+pub struct MipmapGenerator {
+    render_shader: crate::ShaderModule,
+    render_layout: crate::PipelineLayout,
+    render_pipelines: HashMap<crate::TextureFormat, crate::RenderPipeline>,
+    compute_pipelines: HashMap<crate::TextureFormat, crate::ComputePipeline>,
+}
+
+/// Name of a format in WGSL storage texture declarations.
+fn storage_format_name(format: crate::TextureFormat) -> Option<&'static str> {
+    Some(match format {
+        crate::TextureFormat::Rgba8Unorm => "rgba8unorm",
+        crate::TextureFormat::Rgba8Snorm => "rgba8snorm",
+        crate::TextureFormat::Rgba16Float => "rgba16float",
+        crate::TextureFormat::R32Float => "r32float",
+        crate::TextureFormat::Rg32Float => "rg32float",
+        crate::TextureFormat::Rgba32Float => "rgba32float",
+        _ => return None,
+    })
+}
+
+fn source_texture_entry(visibility: crate::ShaderStages) -> crate::BindGroupLayoutEntry {
+    crate::BindGroupLayoutEntry {
+        binding: 0,
+        visibility,
+        ty: crate::BindingType::Texture {
+            sample_type: crate::TextureSampleType::Float { filterable: false },
+            view_dimension: crate::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+impl MipmapGenerator {
+    /// Creates a generator for textures of `device`.
+    pub fn new(device: &crate::Device) -> Self {
+        let render_shader = device.create_shader_module(&crate::ShaderModuleDescriptor {
+            label: Some("mipmap"),
+            source: crate::ShaderSource::Wgsl(Cow::Borrowed(include_str!("mipmap.wgsl"))),
+        });
+        let bind_group_layout =
+            device.create_bind_group_layout(&crate::BindGroupLayoutDescriptor {
+                label: Some("mipmap"),
+                entries: &[source_texture_entry(crate::ShaderStages::FRAGMENT)],
+            });
+        let render_layout = device.create_pipeline_layout(&crate::PipelineLayoutDescriptor {
+            label: Some("mipmap"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            render_shader,
+            render_layout,
+            render_pipelines: HashMap::new(),
+            compute_pipelines: HashMap::new(),
+        }
+    }
+
+    /// Records the generation of all the mip levels of `texture` after the first one.
+    ///
+    /// `desc` has to be the descriptor `texture` was created with.
+    ///
+    /// # Panics
+    ///
+    /// - If the texture is 3D or multisampled.
+    /// - If the texture has neither the `RENDER_ATTACHMENT` usage, nor the
+    ///   `STORAGE_BINDING` usage with a float storage format.
+    pub fn generate(
+        &mut self,
+        device: &crate::Device,
+        encoder: &mut crate::CommandEncoder,
+        texture: &crate::Texture,
+        desc: &crate::TextureDescriptor,
+    ) {
+        assert_ne!(
+            desc.dimension,
+            crate::TextureDimension::D3,
+            "3D textures are not supported"
+        );
+        assert_eq!(
+            desc.sample_count, 1,
+            "Multisampled textures don't have mip levels"
+        );
+
+        let views = (0..desc.array_layer_count())
+            .map(|layer| {
+                (0..desc.mip_level_count)
+                    .map(|mip| {
+                        texture.create_view(&crate::TextureViewDescriptor {
+                            label: Some("mipmap"),
+                            dimension: Some(crate::TextureViewDimension::D2),
+                            base_mip_level: mip,
+                            mip_level_count: NonZeroU32::new(1),
+                            base_array_layer: layer,
+                            array_layer_count: NonZeroU32::new(1),
+                            ..Default::default()
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        if desc.usage.contains(crate::TextureUsages::RENDER_ATTACHMENT) {
+            let pipeline = self.render_pipeline(device, desc.format);
+            let bind_group_layout = pipeline.get_bind_group_layout(0);
+            for mips in views.iter() {
+                for target_mip in 1..mips.len() {
+                    let bind_group = device.create_bind_group(&crate::BindGroupDescriptor {
+                        label: Some("mipmap"),
+                        layout: &bind_group_layout,
+                        entries: &[crate::BindGroupEntry {
+                            binding: 0,
+                            resource: crate::BindingResource::TextureView(&mips[target_mip - 1]),
+                        }],
+                    });
+                    let mut pass = encoder.begin_render_pass(&crate::RenderPassDescriptor {
+                        label: Some("mipmap"),
+                        color_attachments: &[crate::RenderPassColorAttachment {
+                            view: &mips[target_mip],
+                            resolve_target: None,
+                            ops: crate::Operations {
+                                load: crate::LoadOp::Clear(crate::Color::TRANSPARENT),
+                                store: true,
+                            },
+                        }],
+                        depth_stencil_attachment: None,
+                        occlusion_query_set: None,
+                    });
+                    pass.set_pipeline(pipeline);
+                    pass.set_bind_group(0, &bind_group, &[]);
+                    pass.draw(0..3, 0..1);
+                }
+            }
+        } else if desc.usage.contains(crate::TextureUsages::STORAGE_BINDING)
+            && storage_format_name(desc.format).is_some()
+        {
+            let pipeline = self.compute_pipeline(device, desc.format);
+            let bind_group_layout = pipeline.get_bind_group_layout(0);
+            for mips in views.iter() {
+                for target_mip in 1..mips.len() {
+                    let size = desc.mip_level_size(target_mip as u32).unwrap();
+                    let bind_group = device.create_bind_group(&crate::BindGroupDescriptor {
+                        label: Some("mipmap"),
+                        layout: &bind_group_layout,
+                        entries: &[
+                            crate::BindGroupEntry {
+                                binding: 0,
+                                resource: crate::BindingResource::TextureView(
+                                    &mips[target_mip - 1],
+                                ),
+                            },
+                            crate::BindGroupEntry {
+                                binding: 1,
+                                resource: crate::BindingResource::TextureView(&mips[target_mip]),
+                            },
+                        ],
+                    });
+                    let mut pass = encoder.begin_compute_pass(&crate::ComputePassDescriptor {
+                        label: Some("mipmap"),
+                    });
+                    pass.set_pipeline(pipeline);
+                    pass.set_bind_group(0, &bind_group, &[]);
+                    pass.dispatch_workgroups((size.width + 7) / 8, (size.height + 7) / 8, 1);
+                }
+            }
+        } else {
+            panic!(
+                "Texture of format {:?} can't be rendered to or stored into for mipmap generation",
+                desc.format
+            );
+        }
+    }
+
+    fn render_pipeline(
+        &mut self,
+        device: &crate::Device,
+        format: crate::TextureFormat,
+    ) -> &crate::RenderPipeline {
+        let shader = &self.render_shader;
+        let layout = &self.render_layout;
+        self.render_pipelines.entry(format).or_insert_with(|| {
+            device.create_render_pipeline(&crate::RenderPipelineDescriptor {
+                label: Some("mipmap"),
+                layout: Some(layout),
+                vertex: crate::VertexState {
+                    module: shader,
+                    entry_point: "vs_main",
+                    constants: &Default::default(),
+                    buffers: &[],
+                },
+                fragment: Some(crate::FragmentState {
+                    module: shader,
+                    entry_point: "fs_main",
+                    constants: &Default::default(),
+                    targets: &[format.into()],
+                }),
+                primitive: crate::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: crate::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        })
+    }
+
+    fn compute_pipeline(
+        &mut self,
+        device: &crate::Device,
+        format: crate::TextureFormat,
+    ) -> &crate::ComputePipeline {
+        self.compute_pipelines.entry(format).or_insert_with(|| {
+            // Storage texture formats are part of the shader, so each format has its own module.
+            let source = format!(
+                "{}\n{}",
+                include_str!("mipmap.wgsl"),
+                include_str!("mipmap_compute.wgsl")
+                    .replace("STORAGE_FORMAT", storage_format_name(format).unwrap())
+            );
+            let shader = device.create_shader_module(&crate::ShaderModuleDescriptor {
+                label: Some("mipmap"),
+                source: crate::ShaderSource::Wgsl(Cow::Owned(source)),
+            });
+            let bind_group_layout =
+                device.create_bind_group_layout(&crate::BindGroupLayoutDescriptor {
+                    label: Some("mipmap"),
+                    entries: &[
+                        source_texture_entry(crate::ShaderStages::COMPUTE),
+                        crate::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: crate::ShaderStages::COMPUTE,
+                            ty: crate::BindingType::StorageTexture {
+                                access: crate::StorageTextureAccess::WriteOnly,
+                                format,
+                                view_dimension: crate::TextureViewDimension::D2,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+            let layout = device.create_pipeline_layout(&crate::PipelineLayoutDescriptor {
+                label: Some("mipmap"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+            device.create_compute_pipeline(&crate::ComputePipelineDescriptor {
+                label: Some("mipmap"),
+                layout: Some(&layout),
+                module: &shader,
+                entry_point: "cs_main",
+                constants: &Default::default(),
+                cache: None,
+            })
+        })
+    }
+}
@@ -0,0 +1,31 @@
+// Downsamples the previous mip level with a 2x2 box filter.
+//
+// The source is read with `textureLoad`, so non-filterable formats work too.
+// Loads from sRGB views are linear, and so is the averaging.
+
+@group(0)
+@binding(0)
+var src: texture_2d<f32>;
+
+fn downsample(dst: vec2<i32>) -> vec4<f32> {
+    let last = vec2<i32>(textureDimensions(src)) - vec2<i32>(1, 1);
+    let base = dst * 2;
+    let a = textureLoad(src, min(base, last), 0);
+    let b = textureLoad(src, min(base + vec2<i32>(1, 0), last), 0);
+    let c = textureLoad(src, min(base + vec2<i32>(0, 1), last), 0);
+    let d = textureLoad(src, min(base + vec2<i32>(1, 1), last), 0);
+    return (a + b + c + d) * 0.25;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // A triangle covering the whole target.
+    let x = f32(i32(vertex_index) / 2) * 4.0 - 1.0;
+    let y = f32(i32(vertex_index) & 1) * 4.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return downsample(vec2<i32>(position.xy));
+}
@@ -0,0 +1,16 @@
+// Appended to `mipmap.wgsl`, with `STORAGE_FORMAT` replaced by the format of the texture.
+
+@group(0)
+@binding(1)
+var dst: texture_storage_2d<STORAGE_FORMAT, write>;
+
+@compute
+@workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = vec2<i32>(textureDimensions(dst));
+    let position = vec2<i32>(id.xy);
+    if (position.x >= size.x || position.y >= size.y) {
+        return;
+    }
+    textureStore(dst, position, downsample(position));
+}
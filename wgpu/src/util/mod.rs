@@ -5,12 +5,14 @@ mod device;
 mod encoder;
 mod indirect;
 mod init;
+mod mipmap;
 
 use std::ops::{Add, Rem, Sub};
 use std::sync::Arc;
 use std::{
     borrow::Cow,
     mem::{align_of, size_of},
+    num::NonZeroU32,
     ptr::copy_nonoverlapping,
 };
 
@@ -19,6 +21,7 @@ pub use device::{BufferInitDescriptor, DeviceExt};
 pub use encoder::RenderEncoder;
 pub use indirect::*;
 pub use init::*;
+pub use mipmap::MipmapGenerator;
 
 /// Treat the given byte slice as a SPIR-V module.
 ///
@@ -125,6 +128,96 @@ impl std::ops::Deref for DownloadBuffer {
     }
 }
 
+/// Layout of the data of a texture copy, both tightly packed and with the rows
+/// padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`](crate::COPY_BYTES_PER_ROW_ALIGNMENT)
+/// as required in buffers.
+#[derive(Clone, Copy, Debug)]
+struct TextureCopyLayout {
+    /// Size of the copy, rounded up to whole blocks.
+    physical_size: super::Extent3d,
+    unpadded_bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    /// Rows of blocks in every image.
+    rows_per_image: u32,
+}
+
+impl TextureCopyLayout {
+    fn new(
+        format: super::TextureFormat,
+        aspect: super::TextureAspect,
+        size: super::Extent3d,
+    ) -> Self {
+        let format_info = format.describe();
+        let block_size = match aspect {
+            wgt::TextureAspect::All => format_info.block_size as u32,
+            // Stencil is always copied as 8 bit.
+            wgt::TextureAspect::StencilOnly => 1,
+            // The depth aspect of combined formats is copied as 32 bit.
+            wgt::TextureAspect::DepthOnly => match format {
+                wgt::TextureFormat::Depth24PlusStencil8
+                | wgt::TextureFormat::Depth24UnormStencil8
+                | wgt::TextureFormat::Depth32FloatStencil8 => 4,
+                _ => format_info.block_size as u32,
+            },
+        };
+
+        let physical_size = size.physical_size(format);
+        let width_blocks = physical_size.width / format_info.block_dimensions.0 as u32;
+        let unpadded_bytes_per_row = width_blocks * block_size;
+        Self {
+            physical_size,
+            unpadded_bytes_per_row,
+            padded_bytes_per_row: align_to(
+                unpadded_bytes_per_row,
+                super::COPY_BYTES_PER_ROW_ALIGNMENT,
+            ),
+            rows_per_image: physical_size.height / format_info.block_dimensions.1 as u32,
+        }
+    }
+
+    fn rows(&self) -> u32 {
+        self.rows_per_image * self.physical_size.depth_or_array_layers
+    }
+
+    fn unpadded_size(&self) -> super::BufferAddress {
+        self.unpadded_bytes_per_row as super::BufferAddress * self.rows() as super::BufferAddress
+    }
+
+    fn padded_size(&self) -> super::BufferAddress {
+        self.padded_bytes_per_row as super::BufferAddress * self.rows() as super::BufferAddress
+    }
+
+    /// Layout of the padded data at `offset` in a buffer.
+    fn buffer_layout(&self, offset: super::BufferAddress) -> super::ImageDataLayout {
+        super::ImageDataLayout {
+            offset,
+            bytes_per_row: NonZeroU32::new(self.padded_bytes_per_row),
+            rows_per_image: NonZeroU32::new(self.rows_per_image),
+        }
+    }
+
+    /// Copies tightly packed `data` into `padded`.
+    fn pad(&self, data: &[u8], padded: &mut [u8]) {
+        let unpadded = self.unpadded_bytes_per_row as usize;
+        for (row, padded_row) in data
+            .chunks(unpadded)
+            .zip(padded.chunks_mut(self.padded_bytes_per_row as usize))
+        {
+            padded_row[..unpadded].copy_from_slice(row);
+        }
+    }
+
+    /// Strips the padding of the rows in `padded`.
+    fn unpad(&self, padded: &[u8]) -> Vec<u8> {
+        let unpadded = self.unpadded_bytes_per_row as usize;
+        let mut data = Vec::with_capacity(self.unpadded_size() as usize);
+        for row in padded.chunks(self.padded_bytes_per_row as usize) {
+            data.extend_from_slice(&row[..unpadded]);
+        }
+        data
+    }
+}
+
 ///
 /// Aligns a `value` to an `alignment`.
 ///
@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use crate::common::{initialize_test, TestParameters};
 
 #[test]
@@ -6,3 +8,60 @@ fn device_initialization() {
         // intentionally empty
     })
 }
+
+#[test]
+fn device_destroy_loses_device() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let lost_reason = Arc::new(Mutex::new(None));
+        let lost_reason_clone = Arc::clone(&lost_reason);
+        ctx.device
+            .set_device_lost_callback(move |reason, _message| {
+                *lost_reason_clone.lock().unwrap() = Some(reason);
+            });
+
+        let buffer_desc = wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        };
+        let pending_buffer = ctx.device.create_buffer(&buffer_desc);
+        let buffer = ctx.device.create_buffer(&buffer_desc);
+
+        // A mapping still pending when the device is destroyed fails.
+        let pending_map_result = Arc::new(Mutex::new(None));
+        let pending_map_result_clone = Arc::clone(&pending_map_result);
+        pending_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *pending_map_result_clone.lock().unwrap() = Some(result);
+            });
+
+        ctx.device.destroy();
+        assert_eq!(
+            *lost_reason.lock().unwrap(),
+            Some(wgpu::DeviceLostReason::Destroyed)
+        );
+        assert!(matches!(*pending_map_result.lock().unwrap(), Some(Err(_))));
+
+        // Mapping fails instead of waiting forever.
+        let map_result = Arc::new(Mutex::new(None));
+        let map_result_clone = Arc::clone(&map_result);
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *map_result_clone.lock().unwrap() = Some(result);
+            });
+        assert!(matches!(*map_result.lock().unwrap(), Some(Err(_))));
+
+        // Creating resources fails.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _ = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
@@ -0,0 +1,43 @@
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn memory_report_counts_resources() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let _buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 1024,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let _texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 16,
+                height: 16,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 5,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+
+        let report = ctx.device.get_memory_report();
+        assert_eq!(report.resources.buffers, 1);
+        assert_eq!(report.resources.buffer_bytes, 1024);
+        assert_eq!(report.resources.textures, 1);
+        // 16x16 + 8x8 + 4x4 + 2x2 + 1x1 texels of 4 bytes.
+        assert_eq!(report.resources.texture_bytes, 341 * 4);
+
+        if let Some(allocator) = report.allocator {
+            assert!(allocator.suballocations >= 2);
+            assert!(allocator.block_bytes >= allocator.suballocation_bytes);
+        }
+        for heap in report.heaps.iter() {
+            if let Some(budget) = heap.budget {
+                assert!(budget > 0);
+            }
+        }
+    })
+}
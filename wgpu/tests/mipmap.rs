@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+
+use wgpu::util::{DeviceExt, MipmapGenerator};
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+fn generate_and_read_last_mip(ctx: &TestingContext, usage: wgpu::TextureUsages) -> Vec<u8> {
+    // Stripes of 0 and 200 average to 100 on every level.
+    let data = (0..4 * 4 * 4)
+        .map(|i| if (i / 4) % 2 == 0 { 0 } else { 200 })
+        .collect::<Vec<u8>>();
+    let desc = wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 3,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: usage
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC,
+    };
+    let texture = ctx.device.create_texture(&desc);
+    ctx.queue.write_texture(
+        texture.as_image_copy(),
+        &data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: std::num::NonZeroU32::new(4 * 4),
+            rows_per_image: None,
+        },
+        desc.size,
+    );
+
+    let mut generator = MipmapGenerator::new(&ctx.device);
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    generator.generate(&ctx.device, &mut encoder, &texture, &desc);
+    ctx.queue.submit(Some(encoder.finish()));
+
+    let result = Arc::new(Mutex::new(None));
+    let result_clone = Arc::clone(&result);
+    ctx.device.read_texture(
+        &ctx.queue,
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 2,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        desc.format,
+        desc.mip_level_size(2).unwrap(),
+        move |data| *result_clone.lock().unwrap() = Some(data),
+    );
+    ctx.device.poll(wgpu::Maintain::Wait);
+
+    let data = result.lock().unwrap().take().unwrap();
+    data.unwrap()
+}
+
+#[test]
+fn mipmap_generator_render() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let data = generate_and_read_last_mip(&ctx, wgpu::TextureUsages::RENDER_ATTACHMENT);
+        assert_eq!(data, [100; 4]);
+    })
+}
+
+#[test]
+fn mipmap_generator_compute() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS),
+        |ctx| {
+            let data = generate_and_read_last_mip(&ctx, wgpu::TextureUsages::STORAGE_BINDING);
+            assert_eq!(data, [100; 4]);
+        },
+    )
+}
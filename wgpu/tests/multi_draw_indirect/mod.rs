@@ -0,0 +1,217 @@
+use std::sync::{Arc, Mutex};
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+/// Columns of the target, each covered by its own draw.
+const COLUMNS: u32 = 4;
+/// The draw of this column has no instance.
+const EMPTY_COLUMN: u32 = 2;
+/// The draw count read from the count buffer.
+const DRAW_COUNT: u32 = 2;
+
+#[derive(Clone, Copy, Debug)]
+enum Draws {
+    Multi,
+    MultiIndexed,
+    MultiCount,
+    MultiIndexedCount,
+}
+
+/// Draws the columns with a single multi-draw, returning which ones are covered.
+fn draw_columns(ctx: &TestingContext, draws: Draws) -> Vec<bool> {
+    let size = wgpu::Extent3d {
+        width: COLUMNS,
+        height: 1,
+        depth_or_array_layers: 1,
+    };
+    let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Render target"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    });
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let shader = ctx
+        .device
+        .create_shader_module(&wgpu::include_wgsl!("shader.wgsl"));
+    let pipeline = ctx
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                constants: &Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                constants: &Default::default(),
+                targets: &[wgpu::TextureFormat::Rgba8Unorm.into()],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+    let instance_count = |column: u32| u32::from(column != EMPTY_COLUMN);
+    let indirect_data = match draws {
+        Draws::Multi | Draws::MultiCount => (0..COLUMNS)
+            .flat_map(|column| {
+                wgpu::util::DrawIndirect {
+                    vertex_count: 3,
+                    instance_count: instance_count(column),
+                    base_vertex: column * 3,
+                    base_instance: 0,
+                }
+                .as_bytes()
+                .to_vec()
+            })
+            .collect::<Vec<_>>(),
+        Draws::MultiIndexed | Draws::MultiIndexedCount => (0..COLUMNS)
+            .flat_map(|column| {
+                wgpu::util::DrawIndexedIndirect {
+                    vertex_count: 3,
+                    instance_count: instance_count(column),
+                    base_index: column * 3,
+                    vertex_offset: 0,
+                    base_instance: 0,
+                }
+                .as_bytes()
+                .to_vec()
+            })
+            .collect::<Vec<_>>(),
+    };
+    let indirect_buffer = ctx
+        .device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Indirect buffer"),
+            contents: &indirect_data,
+            usage: wgpu::BufferUsages::INDIRECT,
+        });
+    let indices = (0..COLUMNS * 3).collect::<Vec<u32>>();
+    let index_buffer = ctx
+        .device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+    let count_buffer = ctx
+        .device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Count buffer"),
+            contents: bytemuck::bytes_of(&DRAW_COUNT),
+            usage: wgpu::BufferUsages::INDIRECT,
+        });
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&pipeline);
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        match draws {
+            Draws::Multi => render_pass.multi_draw_indirect(&indirect_buffer, 0, COLUMNS),
+            Draws::MultiIndexed => {
+                render_pass.multi_draw_indexed_indirect(&indirect_buffer, 0, COLUMNS)
+            }
+            Draws::MultiCount => render_pass.multi_draw_indirect_count(
+                &indirect_buffer,
+                0,
+                &count_buffer,
+                0,
+                COLUMNS,
+            ),
+            Draws::MultiIndexedCount => render_pass.multi_draw_indexed_indirect_count(
+                &indirect_buffer,
+                0,
+                &count_buffer,
+                0,
+                COLUMNS,
+            ),
+        }
+    }
+    ctx.queue.submit(Some(encoder.finish()));
+
+    let result = Arc::new(Mutex::new(None));
+    let result_clone = Arc::clone(&result);
+    ctx.device.read_texture(
+        &ctx.queue,
+        texture.as_image_copy(),
+        wgpu::TextureFormat::Rgba8Unorm,
+        size,
+        move |data| *result_clone.lock().unwrap() = Some(data),
+    );
+    ctx.device.poll(wgpu::Maintain::Wait);
+
+    let data = result.lock().unwrap().take().unwrap().unwrap();
+    data.chunks(4).map(|texel| texel[0] != 0).collect()
+}
+
+#[test]
+fn multi_draw_indirect() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::MULTI_DRAW_INDIRECT),
+        |ctx| {
+            for draws in [Draws::Multi, Draws::MultiIndexed] {
+                assert_eq!(
+                    draw_columns(&ctx, draws),
+                    [true, true, false, true],
+                    "{:?}",
+                    draws
+                );
+            }
+        },
+    );
+}
+
+/// Forces GL to issue the draws one by one, instead of with `glMultiDraw*Indirect`.
+#[test]
+fn multi_draw_indirect_emulated() {
+    // The variable is read when the adapter is created. Other tests of this process that
+    // create one meanwhile draw one by one as well, which doesn't change their results.
+    std::env::set_var("WGPU_GL_EMULATE_MULTI_DRAW_INDIRECT", "1");
+    multi_draw_indirect();
+    std::env::remove_var("WGPU_GL_EMULATE_MULTI_DRAW_INDIRECT");
+}
+
+#[test]
+fn multi_draw_indirect_count() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::MULTI_DRAW_INDIRECT_COUNT),
+        |ctx| {
+            for draws in [Draws::MultiCount, Draws::MultiIndexedCount] {
+                assert_eq!(
+                    draw_columns(&ctx, draws),
+                    [true, true, false, false],
+                    "{:?}",
+                    draws
+                );
+            }
+        },
+    );
+}
@@ -0,0 +1,16 @@
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // 3 vertices per column of a target 4 texels wide,
+    // with a thin triangle through the center of the column.
+    let column = f32(vertex_index / 3u);
+    let corner = vertex_index % 3u;
+    let x = (column + 0.5) * 0.5 - 1.0;
+    let dx = select(select(0.0, 0.2, corner == 1u), -0.2, corner == 0u);
+    let y = select(-1.0, 3.0, corner == 2u);
+    return vec4<f32>(x + dx, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
@@ -0,0 +1,122 @@
+use std::num::NonZeroU32;
+
+use crate::common::{initialize_test, TestParameters};
+
+const WIDTH: u32 = 64;
+const VIEWS: u32 = 2;
+
+#[test]
+fn multiview() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::MULTIVIEW),
+        |ctx| {
+            let size = wgpu::Extent3d {
+                width: WIDTH,
+                height: 1,
+                depth_or_array_layers: VIEWS,
+            };
+            let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Render target"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            });
+            let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                array_layer_count: NonZeroU32::new(VIEWS),
+                ..Default::default()
+            });
+
+            let shader = ctx
+                .device
+                .create_shader_module(&wgpu::include_wgsl!("shader.wgsl"));
+            let pipeline = ctx
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Pipeline"),
+                    layout: None,
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main",
+                        constants: &Default::default(),
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "fs_main",
+                        constants: &Default::default(),
+                        targets: &[wgpu::TextureFormat::Rgba8Unorm.into()],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: NonZeroU32::new(VIEWS),
+                    cache: None,
+                });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Render pass"),
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: &texture_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.set_pipeline(&pipeline);
+                render_pass.draw(0..3, 0..1);
+            }
+
+            let bytes_per_row = WIDTH * 4;
+            let mapping_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Mapping buffer"),
+                size: (bytes_per_row * VIEWS) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &mapping_buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(bytes_per_row),
+                        rows_per_image: NonZeroU32::new(1),
+                    },
+                },
+                size,
+            );
+
+            ctx.queue.submit(Some(encoder.finish()));
+
+            mapping_buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, |_| ());
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let data = mapping_buffer.slice(..).get_mapped_range();
+
+            let expected: [[u8; 4]; 2] = [[255, 0, 0, 255], [0, 255, 0, 255]];
+            for (layer, texels) in data.chunks(bytes_per_row as usize).enumerate() {
+                for texel in texels.chunks(4) {
+                    assert_eq!(texel, expected[layer], "layer {}", layer);
+                }
+            }
+        },
+    );
+}
@@ -0,0 +1,115 @@
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn occlusion_query() {
+    initialize_test(TestParameters::default(), |ctx| {
+        // Setup render pass, which renders a fullscreen triangle inside the first query only.
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render target"),
+            size: wgpu::Extent3d {
+                width: 64,
+                height: 64,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let query_set = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Query set"),
+            ty: wgpu::QueryType::Occlusion,
+            count: 2,
+        });
+
+        let shader = ctx
+            .device
+            .create_shader_module(&wgpu::include_wgsl!("shader.wgsl"));
+        let pipeline = ctx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Pipeline"),
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    constants: &Default::default(),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    constants: &Default::default(),
+                    targets: &[wgpu::TextureFormat::Rgba8Unorm.into()],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+                occlusion_query_set: Some(&query_set),
+            });
+            render_pass.set_pipeline(&pipeline);
+
+            // Visible draw
+            render_pass.begin_occlusion_query(0);
+            render_pass.draw(0..3, 0..1);
+            render_pass.end_occlusion_query();
+
+            // Empty draw
+            render_pass.begin_occlusion_query(1);
+            render_pass.draw(0..0, 0..1);
+            render_pass.end_occlusion_query();
+        }
+
+        // Resolve query set to buffer
+        let query_buffer_size = std::mem::size_of::<u64>() as u64 * 2;
+        let query_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Query buffer"),
+            size: query_buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        encoder.resolve_query_set(&query_set, 0..2, &query_buffer, 0);
+
+        let mapping_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mapping buffer"),
+            size: query_buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_buffer_to_buffer(&query_buffer, 0, &mapping_buffer, 0, query_buffer_size);
+
+        ctx.queue.submit(Some(encoder.finish()));
+
+        mapping_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, |_| ());
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let query_buffer_view = mapping_buffer.slice(..).get_mapped_range();
+        let query_data: &[u64; 2] = bytemuck::from_bytes(&query_buffer_view);
+
+        assert_ne!(query_data[0], 0);
+        assert_eq!(query_data[1], 0);
+    });
+}
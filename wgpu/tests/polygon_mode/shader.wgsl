@@ -0,0 +1,12 @@
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // triangle around the center of the target, without covering its edges
+    let x = f32(i32(vertex_index) - 1) * 0.5;
+    let y = select(-0.5, 0.5, vertex_index == 1u);
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
@@ -0,0 +1,45 @@
+use std::sync::{Arc, Mutex};
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn read_texture_strips_row_padding() {
+    initialize_test(TestParameters::default(), |ctx| {
+        // 3 texels of 4 bytes per row are far from the copy alignment of 256 bytes.
+        let size = wgpu::Extent3d {
+            width: 3,
+            height: 2,
+            depth_or_array_layers: 2,
+        };
+        let data = (0..3 * 2 * 2 * 4).map(|i| i as u8).collect::<Vec<_>>();
+        let texture = ctx.device.create_texture_with_data(
+            &ctx.queue,
+            &wgpu::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Uint,
+                usage: wgpu::TextureUsages::COPY_SRC,
+            },
+            &data,
+        );
+
+        let result = Arc::new(Mutex::new(None));
+        let result_clone = Arc::clone(&result);
+        ctx.device.read_texture(
+            &ctx.queue,
+            texture.as_image_copy(),
+            wgpu::TextureFormat::Rgba8Uint,
+            size,
+            move |data| *result_clone.lock().unwrap() = Some(data),
+        );
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        let read = result.lock().unwrap().take().unwrap().unwrap();
+        assert_eq!(read, data);
+    })
+}
@@ -5,7 +5,17 @@ mod clear_texture;
 mod device;
 mod example_wgsl;
 mod instance;
+mod memory_report;
+mod mipmap;
+mod multi_draw_indirect;
+mod multiview;
+mod occlusion_query;
 mod poll;
+mod polygon_mode;
+mod read_texture;
+mod shader_compilation_info;
 mod shader_primitive_index;
+mod staging_belt;
+mod timestamp_query;
 mod vertex_indices;
 mod zero_init_texture_after_discard;
@@ -0,0 +1,41 @@
+use std::borrow::Cow;
+
+use wgpu::{CompilationMessageType, ShaderModuleDescriptor, ShaderSource};
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn valid_shader_has_no_messages() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let module = ctx.device.create_shader_module(&ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::Borrowed("@compute @workgroup_size(1) fn main() {}")),
+        });
+
+        let info = pollster::block_on(module.get_compilation_info());
+        assert!(info.messages.is_empty());
+    })
+}
+
+#[test]
+fn parse_error_has_location() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let source = "@compute @workgroup_size(1)\nfn main() { let x = ; }";
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = ctx.device.create_shader_module(&ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::Borrowed(source)),
+        });
+        let error = pollster::block_on(ctx.device.pop_error_scope());
+        assert!(error.is_some());
+
+        let info = pollster::block_on(module.get_compilation_info());
+        assert_eq!(info.messages.len(), 1);
+        let message = &info.messages[0];
+        assert_eq!(message.message_type, CompilationMessageType::Error);
+        let location = message.location.expect("parse error without a location");
+        assert_eq!(location.line_number, 2);
+        assert_eq!(&source[location.offset as usize..][..1], ";");
+    })
+}
@@ -144,6 +144,7 @@ fn pulling_common(
                     }],
                 }],
                 entry_point: "vs_main",
+                constants: &Default::default(),
                 module: &shader,
             },
             primitive: wgpu::PrimitiveState::default(),
@@ -151,6 +152,7 @@ fn pulling_common(
             multisample: wgpu::MultisampleState::default(),
             fragment: Some(wgpu::FragmentState {
                 entry_point: "fs_main",
+                constants: &Default::default(),
                 module: &shader,
                 targets: &[wgpu::ColorTargetState {
                     format: wgpu::TextureFormat::Rgba8Unorm,
@@ -159,6 +161,7 @@ fn pulling_common(
                 }],
             }),
             multiview: None,
+            cache: None,
         });
 
     let width = 2;
@@ -194,6 +197,7 @@ fn pulling_common(
         }],
         depth_stencil_attachment: None,
         label: None,
+        occlusion_query_set: None,
     });
 
     rpass.set_pipeline(&pipeline);
@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use wgpu::util::{DeviceExt, StagingBelt};
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn staging_belt_round_trip() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let size = wgpu::Extent3d {
+            width: 5,
+            height: 3,
+            depth_or_array_layers: 1,
+        };
+        let format = wgpu::TextureFormat::Rgba8Uint;
+        let texture_data = (0..5 * 3 * 4).map(|i| i as u8).collect::<Vec<_>>();
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        });
+        let buffer_data = (0..64).map(|i| 255 - i as u8).collect::<Vec<_>>();
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &buffer_data,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            });
+
+        // Small chunks make the reads and writes spread over several buffers.
+        let mut belt = StagingBelt::new(64);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        belt.write_texture(
+            &mut encoder,
+            texture.as_image_copy(),
+            format,
+            size,
+            &texture_data,
+            &ctx.device,
+        );
+
+        let texture_result = Arc::new(Mutex::new(None));
+        let buffer_result = Arc::new(Mutex::new(None));
+        let result = Arc::clone(&texture_result);
+        belt.read_texture(
+            &mut encoder,
+            texture.as_image_copy(),
+            format,
+            size,
+            &ctx.device,
+            move |data| *result.lock().unwrap() = Some(data),
+        );
+        let result = Arc::clone(&buffer_result);
+        belt.read_buffer(
+            &mut encoder,
+            &buffer,
+            16,
+            wgpu::BufferSize::new(32).unwrap(),
+            &ctx.device,
+            move |data| *result.lock().unwrap() = Some(data),
+        );
+        belt.finish();
+        ctx.queue.submit(Some(encoder.finish()));
+        belt.recall();
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        let data = texture_result.lock().unwrap().take().unwrap();
+        assert_eq!(data.unwrap(), texture_data);
+        let data = buffer_result.lock().unwrap().take().unwrap();
+        assert_eq!(data.unwrap(), &buffer_data[16..48]);
+    })
+}
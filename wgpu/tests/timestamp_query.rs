@@ -0,0 +1,62 @@
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn timestamp_query() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::TIMESTAMP_QUERY),
+        |ctx| {
+            let query_set = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Query set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: 3,
+            });
+
+            // Some work to do between the timestamps.
+            let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Work buffer"),
+                size: 1 << 20,
+                usage: wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.write_timestamp(&query_set, 0);
+            encoder.clear_buffer(&buffer, 0, None);
+            encoder.write_timestamp(&query_set, 1);
+            encoder.clear_buffer(&buffer, 0, None);
+            encoder.write_timestamp(&query_set, 2);
+
+            let query_buffer_size = std::mem::size_of::<u64>() as u64 * 3;
+            let query_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Query buffer"),
+                size: query_buffer_size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            encoder.resolve_query_set(&query_set, 0..3, &query_buffer, 0);
+
+            let mapping_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Mapping buffer"),
+                size: query_buffer_size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+            encoder.copy_buffer_to_buffer(&query_buffer, 0, &mapping_buffer, 0, query_buffer_size);
+
+            ctx.queue.submit(Some(encoder.finish()));
+
+            mapping_buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, |_| ());
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let query_buffer_view = mapping_buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64; 3] = bytemuck::from_bytes(&query_buffer_view);
+
+            assert_ne!(timestamps[0], 0);
+            assert!(timestamps[0] <= timestamps[1], "{:?}", timestamps);
+            assert!(timestamps[1] <= timestamps[2], "{:?}", timestamps);
+        },
+    );
+}